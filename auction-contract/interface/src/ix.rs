@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::InstructionData;
+use anchor_spl::token;
+use auction_contract::state::BidOrder;
+use auction_contract::{ accounts, instruction, ID };
+use crate::pda;
+
+// Optional accounts a bid may need, depending on the listing
+#[derive(Clone, Default)]
+pub struct BidOptions {
+    pub bid_page: Option<Pubkey>, // Once the inline bidder list is full
+    pub previous_bidder: Option<Pubkey>, // To refund the outbid leader in the same transaction
+    pub gate_token_account: Option<Pubkey>, // On token-gated listings
+    pub collection_fee_config: Option<Pubkey>, // When the listing's collection has discounted fees
+    pub proof: Vec<[u8; 32]>, // On private listings
+    pub referrer: Option<Pubkey>,
+    pub token_payment: Option<TokenPayment>, // On listings paid in an SPL token
+    pub price_feed: Option<Pubkey>, // On listings priced in USD
+    pub loyalty: bool, // Earn loyalty points; the bidder's loyalty account must be open
+    pub membership: Option<Membership>, // For a membership fee discount
+}
+
+// A membership NFT the bidder holds, presented for its collection's fee discount
+#[derive(Clone)]
+pub struct Membership {
+    pub collection: Pubkey,
+    pub token_account: Pubkey,
+    pub metadata: Pubkey, // The NFT's Token Metadata account
+}
+
+// How a listing paid in an SPL token is paid: its mint, the token program that
+// owns the mint, and the token account on the caller's side, which a wrapped
+// SOL listing can leave out to pay or be paid in plain SOL
+#[derive(Clone)]
+pub struct TokenPayment {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub token_account: Option<Pubkey>,
+}
+
+// `payer` deposits `amount` toward `bidder`'s position on `listing_id`
+pub fn place_bid(
+    payer: Pubkey,
+    listing_id: &[u8; 32],
+    bidder: Pubkey,
+    amount: u64,
+    options: BidOptions
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: bid_accounts(payer, listing_id, bidder, &options),
+        data: (instruction::PlaceBid {
+            listing_id: *listing_id,
+            bidder,
+            amount,
+            proof: options.proof,
+            referrer: options.referrer,
+        }).data(),
+    }
+}
+
+// Same as `place_bid`, with the bidder's running total as their proxy ceiling
+pub fn place_proxy_bid(
+    payer: Pubkey,
+    listing_id: &[u8; 32],
+    bidder: Pubkey,
+    amount: u64,
+    options: BidOptions
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: bid_accounts(payer, listing_id, bidder, &options),
+        data: (instruction::PlaceProxyBid {
+            listing_id: *listing_id,
+            bidder,
+            amount,
+            proof: options.proof,
+            referrer: options.referrer,
+        }).data(),
+    }
+}
+
+// `matcher` settles `order` on the bidder's behalf. It must directly follow an
+// Ed25519 program instruction verifying the bidder's signature over
+// `order.message()`.
+pub fn place_signed_bid(
+    matcher: Pubkey,
+    order: BidOrder,
+    proof: Vec<[u8; 32]>,
+    collection_fee_config: Option<Pubkey>
+) -> Instruction {
+    let listing_id = order.listing_id;
+    let auction = pda::auction(&listing_id);
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::PlaceSignedBid {
+            auction_state: pda::auction_state(),
+            auction,
+            vault: pda::vault(&listing_id),
+            bidder_profile: pda::bidder_profile(&order.bidder),
+            blacklist_entry: pda::blacklist_entry(&order.bidder),
+            bid_receipt: pda::bid_receipt(&auction, &order.bidder),
+            collection_fee_config,
+            matcher,
+            system_program: system_program::ID,
+            instructions: sysvar::instructions::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(),
+            #[cfg(feature = "event-cpi")]
+            program: ID,
+        }).to_account_metas(None),
+        data: (instruction::PlaceSignedBid { listing_id, order, proof }).data(),
+    }
+}
+
+// `payer` relays `posted_vaa`, a bid on `listing_id` from another chain.
+// `bidder` is the one named in the VAA's payload, whose blacklist flag the
+// program checks.
+pub fn bridge_bid(
+    payer: Pubkey,
+    listing_id: &[u8; 32],
+    posted_vaa: Pubkey,
+    bidder: Pubkey,
+    bid_page: Option<Pubkey>
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::BridgeBid {
+            auction_state: pda::auction_state(),
+            auction: pda::auction(listing_id),
+            vault: pda::vault(listing_id),
+            posted_vaa,
+            consumed_vaa: pda::consumed_vaa(&posted_vaa),
+            bridge_vault: pda::bridge_vault(),
+            blacklist_entry: pda::blacklist_entry(&bidder),
+            bid_page,
+            payer,
+            system_program: system_program::ID,
+        }).to_account_metas(None),
+        data: (instruction::BridgeBid { listing_id: *listing_id }).data(),
+    }
+}
+
+// `bidder` takes back what they may withdraw from `listing_id`, paid to
+// `recipient` or else to themselves; into the payment's token account on a
+// listing paid in an SPL token. A wrapped SOL listing named without one pays a
+// wallet recipient in SOL instead. `amount` takes only part of it, leaving the
+// rest on the bid.
+pub fn withdraw(
+    bidder: Pubkey,
+    listing_id: &[u8; 32],
+    recipient: Option<Pubkey>,
+    bid_page: Option<Pubkey>,
+    payment: Option<&TokenPayment>,
+    amount: Option<u64>
+) -> Instruction {
+    let unwrap = payment.is_some_and(|payment| {
+        payment.mint == token::spl_token::native_mint::ID && payment.token_account.is_none()
+    });
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::Withdraw {
+            auction_state: pda::auction_state(),
+            auction: pda::auction(listing_id),
+            vault: pda::vault(listing_id),
+            bidder,
+            to: recipient.unwrap_or(bidder),
+            bid_page,
+            bid_receipt: pda::bid_receipt(&pda::auction(listing_id), &bidder),
+            token_vault: payment.map(|_| pda::token_vault(listing_id)),
+            to_token_account: payment.and_then(|payment| payment.token_account),
+            token_program: payment.map(|payment| payment.token_program),
+            payment_mint: payment.map(|payment| payment.mint),
+            unwrap_account: unwrap.then(|| pda::wsol_unwrap(listing_id)),
+            native_mint: unwrap.then_some(token::spl_token::native_mint::ID),
+            system_program: system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(),
+            #[cfg(feature = "event-cpi")]
+            program: ID,
+        }).to_account_metas(None),
+        data: (instruction::Withdraw {
+            listing_id: *listing_id,
+            recipient,
+            amount,
+        }).data(),
+    }
+}
+
+fn bid_accounts(
+    payer: Pubkey,
+    listing_id: &[u8; 32],
+    bidder: Pubkey,
+    options: &BidOptions
+) -> Vec<AccountMeta> {
+    let auction = pda::auction(listing_id);
+    let membership = options.membership.as_ref();
+    let payment = options.token_payment.as_ref();
+    (accounts::PlaceBid {
+        auction_state: pda::auction_state(),
+        auction,
+        vault: pda::vault(listing_id),
+        payer,
+        blacklist_entry: pda::blacklist_entry(&bidder),
+        bid_page: options.bid_page,
+        previous_bidder: options.previous_bidder,
+        previous_bid_receipt: options.previous_bidder.map(|previous| {
+            pda::bid_receipt(&auction, &previous)
+        }),
+        bid_receipt: pda::bid_receipt(&auction, &bidder),
+        loyalty_account: options.loyalty.then(|| pda::loyalty_account(&bidder)),
+        gate_token_account: options.gate_token_account,
+        collection_fee_config: options.collection_fee_config,
+        membership_discount: membership.map(|membership| {
+            pda::membership_discount(&membership.collection)
+        }),
+        membership_token_account: membership.map(|membership| membership.token_account),
+        membership_metadata: membership.map(|membership| membership.metadata),
+        price_feed: options.price_feed,
+        token_vault: payment.map(|_| pda::token_vault(listing_id)),
+        payer_token_account: payment.and_then(|payment| payment.token_account),
+        token_program: payment.map(|payment| payment.token_program),
+        payment_mint: payment.map(|payment| payment.mint),
+        feature_flags: payment.map(|_| pda::feature_flags()),
+        system_program: system_program::ID,
+        instructions: sysvar::instructions::ID,
+        #[cfg(feature = "event-cpi")]
+        event_authority: pda::event_authority(),
+        #[cfg(feature = "event-cpi")]
+        program: ID,
+    }).to_account_metas(None)
+}
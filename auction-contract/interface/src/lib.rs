@@ -0,0 +1,71 @@
+// Interface to the auction program for other on-chain programs and for
+// off-chain clients, without pulling in its entrypoint.
+//
+// Anchor programs compose through the generated CPI helpers:
+//
+//     nft_com_auction::cpi::place_bid(
+//         CpiContext::new_with_signer(auction_program, accounts, signer_seeds),
+//         listing_id, bidder, amount, vec![], None,
+//     )?;
+//
+// Anything that builds raw instructions instead uses the builders in `ix`,
+// which fill in the PDAs from `pda`. Events decode with the types in `events`,
+// and `errors`, `state` and `constants` carry the rest of the program's types,
+// so clients share the program's definitions rather than keeping copies.
+pub mod ix;
+pub mod pda;
+
+pub use auction_contract::{ accounts, cpi, id, instruction, program, ID };
+
+pub mod events {
+    pub use auction_contract::events::*;
+}
+
+pub mod state {
+    pub use auction_contract::state::*;
+}
+
+pub mod errors {
+    pub use auction_contract::errors::ErrorCode;
+}
+
+// PDA seed prefixes, fee bounds and query limits
+pub mod constants {
+    pub use auction_contract::math::BPS_DENOMINATOR;
+    pub use auction_contract::{
+        AUCTION_SEED,
+        AUCTION_STATE_SEED,
+        AUCTION_TEMPLATE_SEED,
+        BIDDER_PROFILE_SEED,
+        BID_PAGE_SEED,
+        BID_RECEIPT_SEED,
+        BLACKLIST_SEED,
+        BRIDGE_VAULT_SEED,
+        COLLECTION_FEE_SEED,
+        COLLECTION_SEED,
+        CONSUMED_VAA_SEED,
+        CROWD_SHARE_SEED,
+        FEATURE_FLAGS_SEED,
+        FEE_TREASURY_SEED,
+        FIXED_PRICE_SEED,
+        LOYALTY_SEED,
+        MAX_QUERY_RESULTS,
+        NFT_ESCROW_SEED,
+        NFT_MINT_SEED,
+        OFFER_SEED,
+        PARAM_CHANGE_SEED,
+        PROVENANCE_SEED,
+        ROLES_SEED,
+        SELLER_PROFILE_SEED,
+        TOKEN_VAULT_SEED,
+        VAULT_SEED,
+        WATCHLIST_SEED,
+        WSOL_UNWRAP_SEED,
+    };
+
+    // Cap on buyer and seller fees each, in basis points
+    pub const MAX_FEE_BPS: u64 = auction_contract::state::NftComAuction::MAX_FEE_BPS;
+
+    // Owner of the posted VAAs bridge_bid reads
+    pub use auction_contract::wormhole::WORMHOLE_CORE_BRIDGE;
+}
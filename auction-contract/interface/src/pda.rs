@@ -0,0 +1,174 @@
+use anchor_lang::prelude::Pubkey;
+use auction_contract::realms::SPL_GOVERNANCE_PROGRAM_ID;
+use auction_contract::state::{ AuctionDetails, SettlementStep };
+use auction_contract::{
+    ARCHIVE_SEED,
+    AUCTION_REGISTRY_SEED,
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    AUCTION_TEMPLATE_SEED,
+    AUDIT_LOG_SEED,
+    BID_PAGE_SEED,
+    BID_RECEIPT_SEED,
+    BIDDER_PROFILE_SEED,
+    BLACKLIST_SEED,
+    BRIDGE_VAULT_SEED,
+    COLLECTION_FEE_SEED,
+    COLLECTION_STATS_SEED,
+    CONSUMED_VAA_SEED,
+    FEATURE_FLAGS_SEED,
+    FEE_TREASURY_SEED,
+    FLOOR_ORACLE_SEED,
+    ID,
+    LOYALTY_SEED,
+    MEMBERSHIP_DISCOUNT_SEED,
+    NFT_ESCROW_SEED,
+    PROVENANCE_SEED,
+    REGISTRY_PAGE_SEED,
+    SELLER_PROFILE_SEED,
+    SETTLEMENT_RECEIPT_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+    WSOL_UNWRAP_SEED,
+};
+
+// Addresses of the program's accounts, derived the same way its constraints do
+
+pub fn auction_state() -> Pubkey {
+    Pubkey::find_program_address(&[AUCTION_STATE_SEED], &ID).0
+}
+
+// A seller's listing named `name` lives under this id
+pub fn listing_id(seller: &Pubkey, name: &str) -> [u8; 32] {
+    AuctionDetails::listing_id_for(seller, name)
+}
+
+pub fn auction(listing_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[AUCTION_SEED, listing_id.as_ref()], &ID).0
+}
+
+pub fn vault(listing_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_SEED, listing_id.as_ref()], &ID).0
+}
+
+pub fn token_vault(listing_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[TOKEN_VAULT_SEED, listing_id.as_ref()], &ID).0
+}
+
+pub fn wsol_unwrap(listing_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[WSOL_UNWRAP_SEED, listing_id.as_ref()], &ID).0
+}
+
+pub fn bid_page(listing_id: &[u8; 32], index: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[BID_PAGE_SEED, listing_id.as_ref(), &index.to_le_bytes()],
+        &ID
+    ).0
+}
+
+pub fn bid_receipt(auction: &Pubkey, bidder: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[BID_RECEIPT_SEED, auction.as_ref(), bidder.as_ref()], &ID).0
+}
+
+pub fn bidder_profile(bidder: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[BIDDER_PROFILE_SEED, bidder.as_ref()], &ID).0
+}
+
+pub fn seller_profile(seller: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[SELLER_PROFILE_SEED, seller.as_ref()], &ID).0
+}
+
+pub fn auction_template(owner: &Pubkey, template_id: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AUCTION_TEMPLATE_SEED, owner.as_ref(), &template_id.to_le_bytes()],
+        &ID
+    ).0
+}
+
+pub fn blacklist_entry(address: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[BLACKLIST_SEED, address.as_ref()], &ID).0
+}
+
+pub fn collection_fee_config(collection: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[COLLECTION_FEE_SEED, collection.as_ref()], &ID).0
+}
+
+pub fn membership_discount(collection: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[MEMBERSHIP_DISCOUNT_SEED, collection.as_ref()], &ID).0
+}
+
+pub fn collection_stats(collection: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[COLLECTION_STATS_SEED, collection.as_ref()], &ID).0
+}
+
+pub fn floor_oracle(collection: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[FLOOR_ORACLE_SEED, collection.as_ref()], &ID).0
+}
+
+pub fn archive() -> Pubkey {
+    Pubkey::find_program_address(&[ARCHIVE_SEED], &ID).0
+}
+
+pub fn auction_registry() -> Pubkey {
+    Pubkey::find_program_address(&[AUCTION_REGISTRY_SEED], &ID).0
+}
+
+pub fn audit_log() -> Pubkey {
+    Pubkey::find_program_address(&[AUDIT_LOG_SEED], &ID).0
+}
+
+pub fn registry_page(index: u32) -> Pubkey {
+    Pubkey::find_program_address(&[REGISTRY_PAGE_SEED, &index.to_le_bytes()], &ID).0
+}
+
+pub fn fee_treasury(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[FEE_TREASURY_SEED, mint.as_ref()], &ID).0
+}
+
+pub fn loyalty_account(owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LOYALTY_SEED, owner.as_ref()], &ID).0
+}
+
+// Holds a listed NFT until settlement
+pub fn nft_escrow(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[NFT_ESCROW_SEED, mint.as_ref()], &ID).0
+}
+
+pub fn provenance(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[PROVENANCE_SEED, mint.as_ref()], &ID).0
+}
+
+pub fn settlement_receipt(auction: &Pubkey, step: SettlementStep, party: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SETTLEMENT_RECEIPT_SEED, auction.as_ref(), &step.seed(), party.as_ref()],
+        &ID
+    ).0
+}
+
+// Liquidity bridged bids are paid out of
+pub fn bridge_vault() -> Pubkey {
+    Pubkey::find_program_address(&[BRIDGE_VAULT_SEED], &ID).0
+}
+
+// Marks a posted VAA as consumed, so it can't be replayed
+pub fn consumed_vaa(posted_vaa: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CONSUMED_VAA_SEED, posted_vaa.as_ref()], &ID).0
+}
+
+pub fn feature_flags() -> Pubkey {
+    Pubkey::find_program_address(&[FEATURE_FLAGS_SEED], &ID).0
+}
+
+// Signs the program's self-CPI events when built with `event-cpi`
+pub fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &ID).0
+}
+
+// Governance spl-governance creates in `realm` over `governed_account`; the
+// address to hand to `set_governance`, whose proposals then sign for it
+pub fn realm_governance(realm: &Pubkey, governed_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"account-governance", realm.as_ref(), governed_account.as_ref()],
+        &SPL_GOVERNANCE_PROGRAM_ID
+    ).0
+}
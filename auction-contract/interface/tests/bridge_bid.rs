@@ -0,0 +1,92 @@
+// A bid bridged in over Wormhole is held to the blacklist like any other,
+// against the bidder its payload names. The posted VAA is planted in the bank
+// as the core bridge would leave it, so no guardian set is needed; the program
+// is loaded from its compiled binary as for the lifecycle test:
+//
+//     anchor build
+//     SBF_OUT_DIR=$PWD/target/deploy cargo test -p nft-com-auction --test bridge_bid
+mod common;
+
+use anchor_lang::prelude::*;
+use common::*;
+use nft_com_auction::constants::WORMHOLE_CORE_BRIDGE;
+use nft_com_auction::errors::ErrorCode;
+use nft_com_auction::{ ix, pda, ID };
+use solana_program_test::ProgramTest;
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::signature::Signer;
+
+const EMITTER_CHAIN: u16 = 2;
+const EMITTER_ADDRESS: [u8; 32] = [7; 32];
+
+// A PostedVAA account carrying a bid of `amount` by `bidder` on `listing_id`
+fn posted_vaa(listing_id: &[u8; 32], bidder: &Pubkey, amount: u64) -> SolanaAccount {
+    let mut payload = vec![1];
+    payload.extend_from_slice(bidder.as_ref());
+    payload.extend_from_slice(&amount.to_be_bytes());
+    payload.extend_from_slice(listing_id);
+
+    // Magic and header, of which only the length matters here
+    let mut data = b"vaa".to_vec();
+    data.extend_from_slice(&[0; 46]);
+    data.extend_from_slice(&1u64.to_le_bytes());
+    data.extend_from_slice(&EMITTER_CHAIN.to_le_bytes());
+    data.extend_from_slice(&EMITTER_ADDRESS);
+    payload.serialize(&mut data).unwrap();
+    SolanaAccount { lamports: SOL, data, owner: WORMHOLE_CORE_BRIDGE, executable: false, rent_epoch: 0 }
+}
+
+#[tokio::test]
+async fn bridged_bid_from_blacklisted_bidder_is_rejected() {
+    let mut program_test = ProgramTest::new("auction_contract", ID, None);
+    let seller = funded(&mut program_test);
+    let bidder = Pubkey::new_unique();
+    let listing_id = pda::listing_id(&seller.pubkey(), "bridged");
+    let vaa = Pubkey::new_unique();
+    program_test.add_account(vaa, posted_vaa(&listing_id, &bidder, SOL));
+    program_test.add_account(
+        pda::bridge_vault(),
+        SolanaAccount::new(10 * SOL, 0, &anchor_lang::system_program::ID)
+    );
+    let mut context = program_test.start_with_context().await;
+    let admin = context.payer.pubkey();
+
+    send(
+        &mut context,
+        &[
+            initialize(admin),
+            init_fee_treasury(admin),
+            init_auction_archive(admin),
+            add_bridge_emitter(admin, EMITTER_CHAIN, EMITTER_ADDRESS),
+        ],
+        &[]
+    ).await;
+    let (nft_mint, seller_nft_account) = mint_nft(&mut context, &seller).await;
+    send(
+        &mut context,
+        &[initialize_auction(&listing_id, seller.pubkey(), nft_mint, seller_nft_account, SOL / 2)],
+        &[&seller]
+    ).await;
+    send(&mut context, &[add_to_blacklist(admin, bidder)], &[]).await;
+
+    // The payload's bidder is checked, not whoever's flag the relayer passes
+    let relayer = context.payer.pubkey();
+    let mut dodge = ix::bridge_bid(relayer, &listing_id, vaa, bidder, None);
+    for meta in dodge.accounts.iter_mut().filter(|meta| meta.pubkey == pda::blacklist_entry(&bidder)) {
+        meta.pubkey = pda::blacklist_entry(&relayer);
+    }
+    assert_eq!(
+        error_code(try_send(&mut context, &[dodge], &[]).await),
+        Some(u32::from(ErrorCode::BlacklistEntryMismatch))
+    );
+    let bid = ix::bridge_bid(relayer, &listing_id, vaa, bidder, None);
+    assert_eq!(
+        error_code(try_send(&mut context, &[bid.clone()], &[]).await),
+        Some(u32::from(ErrorCode::Blacklisted))
+    );
+
+    // Once the bidder is cleared, the same VAA goes through
+    send(&mut context, &[remove_from_blacklist(admin, bidder)], &[]).await;
+    send(&mut context, &[bid], &[]).await;
+    assert_eq!(auction(&mut context, &listing_id).await.highest_bidder, bidder);
+}
@@ -0,0 +1,47 @@
+// The collection TWAP over windows the observation ring reaches back past,
+// ends inside and doesn't reach. Pure state math, so unlike the other tests
+// this needs no built program:
+//
+//     cargo test -p nft-com-auction --test collection_twap
+
+use anchor_lang::prelude::Pubkey;
+use nft_com_auction::state::{ CollectionStats, PriceObservation };
+
+// A collection that sold at 100 lamports at t = 0 and at 300 at t = 1_000
+fn two_sales() -> CollectionStats {
+    let mut stats = CollectionStats {
+        version: CollectionStats::VERSION,
+        collection: Pubkey::new_unique(),
+        sales: 0,
+        last_price: 0,
+        last_sale_time: 0,
+        cumulative: 0,
+        observations: [PriceObservation::default(); CollectionStats::MAX_OBSERVATIONS],
+        next_observation: 0,
+        bump: 0,
+    };
+    stats.record_sale(100, 0);
+    stats.record_sale(300, 1_000);
+    stats
+}
+
+#[test]
+fn window_starting_between_observations_is_interpolated() {
+    // 100 over [500, 1_000) and 300 over [1_000, 2_000), across exactly the window
+    let (twap, span) = two_sales().twap(1_500, 2_000).unwrap();
+    assert_eq!(span, 1_500);
+    assert_eq!(twap, (100 * 500 + 300 * 1_000) / 1_500);
+}
+
+#[test]
+fn window_starting_after_the_last_sale_is_its_price() {
+    let (twap, span) = two_sales().twap(500, 2_000).unwrap();
+    assert_eq!((twap, span), (300, 500));
+}
+
+#[test]
+fn window_older_than_every_observation_covers_what_they_do() {
+    let (twap, span) = two_sales().twap(5_000, 2_000).unwrap();
+    assert_eq!(span, 2_000);
+    assert_eq!(twap, (100 * 1_000 + 300 * 1_000) / 2_000);
+}
@@ -0,0 +1,510 @@
+// Bank setup, clock warping and instruction builders shared by the integration
+// tests; each test binary uses only some of them
+#![allow(dead_code)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::{ program_pack::Pack, system_instruction };
+use anchor_lang::{ AccountDeserialize, InstructionData };
+use anchor_spl::token::spl_token;
+use nft_com_auction::state::{
+    AuctionDefaults,
+    AuctionDetails,
+    ListingOverrides,
+    ReservePolicy,
+    SettlementStep,
+};
+use nft_com_auction::{ accounts, instruction, pda, ID };
+use solana_program_test::{ BanksClientError, ProgramTest, ProgramTestContext };
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::signature::{ Keypair, Signer };
+use solana_sdk::transaction::{ Transaction, TransactionError };
+
+pub const SOL: u64 = 1_000_000_000;
+pub const DURATION: i64 = 24 * 60 * 60;
+pub const SNIPING_WINDOW: i64 = 10 * 60;
+pub const EXTENSION: i64 = 5 * 60;
+pub const BUYER_FEE_BPS: u64 = 250;
+
+// What a deposit is credited as once the buyer fee is taken out of it
+pub fn net_of_fee(deposit: u64) -> u64 {
+    deposit - (deposit * BUYER_FEE_BPS) / 10_000
+}
+
+// Move the bank's clock to `unix_timestamp`, keeping its slot
+pub async fn warp_to(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+pub async fn now(context: &mut ProgramTestContext) -> i64 {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp
+}
+
+pub async fn send(context: &mut ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) {
+    try_send(context, instructions, signers).await.unwrap();
+}
+
+// Sign with the payer and `signers` under a fresh blockhash, so repeating an
+// instruction doesn't collide with an earlier transaction
+pub async fn try_send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair]
+) -> std::result::Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers: Vec<&Keypair> = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+// The program error a transaction failed with, if it failed with one
+pub fn error_code(result: std::result::Result<(), BanksClientError>) -> Option<u32> {
+    match result {
+        Err(
+            BanksClientError::TransactionError(
+                TransactionError::InstructionError(_, InstructionError::Custom(code)),
+            ),
+        ) => Some(code),
+        _ => None,
+    }
+}
+
+pub async fn auction(context: &mut ProgramTestContext, listing_id: &[u8; 32]) -> AuctionDetails {
+    let account = context.banks_client.get_account(pda::auction(listing_id)).await.unwrap().unwrap();
+    AuctionDetails::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn lamports(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    context.banks_client.get_balance(address).await.unwrap()
+}
+
+pub fn funded(program_test: &mut ProgramTest) -> Keypair {
+    let wallet = Keypair::new();
+    program_test.add_account(
+        wallet.pubkey(),
+        SolanaAccount::new(100 * SOL, 0, &anchor_lang::system_program::ID)
+    );
+    wallet
+}
+
+// An SPL token account for `mint` owned by `owner`, at a fresh address
+pub async fn token_account(context: &mut ProgramTestContext, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    let account = Keypair::new();
+    let payer = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    send(
+        context,
+        &[
+            system_instruction::create_account(
+                &payer,
+                &account.pubkey(),
+                rent.minimum_balance(spl_token::state::Account::LEN),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID
+            ),
+            spl_token::instruction::initialize_account3(&spl_token::ID, &account.pubkey(), mint, owner).unwrap(),
+        ],
+        &[&account]
+    ).await;
+    account.pubkey()
+}
+
+// A one-of-one mint held by `owner`, returned with the owner's token account
+pub async fn mint_nft(context: &mut ProgramTestContext, owner: &Keypair) -> (Pubkey, Pubkey) {
+    let mint = Keypair::new();
+    let payer = context.payer.pubkey();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    send(
+        context,
+        &[
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID
+            ),
+            spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer, None, 0).unwrap(),
+        ],
+        &[&mint]
+    ).await;
+    let holder = token_account(context, &mint.pubkey(), &owner.pubkey()).await;
+    send(
+        context,
+        &[spl_token::instruction::mint_to(&spl_token::ID, &mint.pubkey(), &holder, &payer, &[], 1).unwrap()],
+        &[]
+    ).await;
+    (mint.pubkey(), holder)
+}
+
+pub fn initialize(authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::Initialize {
+            auction_state: pda::auction_state(),
+            authority,
+            system_program: anchor_lang::system_program::ID,
+        }).to_account_metas(None),
+        data: (instruction::Initialize {
+            fee_recipient: authority,
+            nft_contract: Pubkey::default(),
+            buyer_fee_bps: BUYER_FEE_BPS,
+            seller_fee_bps: 250,
+            defaults: AuctionDefaults {
+                default_duration: DURATION,
+                min_increment_bps: 500,
+                sniping_time_window: SNIPING_WINDOW,
+                time_extension: EXTENSION,
+                reserve_policy: ReservePolicy::NoReserve,
+            },
+        }).data(),
+    }
+}
+
+pub fn init_fee_treasury(authority: Pubkey) -> Instruction {
+    let mint = spl_token::native_mint::ID;
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::InitFeeTreasury {
+            auction_state: pda::auction_state(),
+            fee_treasury: pda::fee_treasury(&mint),
+            authority,
+            payer: authority,
+            system_program: anchor_lang::system_program::ID,
+        }).to_account_metas(None),
+        data: (instruction::InitFeeTreasury { mint }).data(),
+    }
+}
+
+pub fn init_auction_archive(payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::InitAuctionArchive {
+            archive: pda::archive(),
+            payer,
+            system_program: anchor_lang::system_program::ID,
+        }).to_account_metas(None),
+        data: (instruction::InitAuctionArchive {}).data(),
+    }
+}
+
+pub fn initialize_auction(
+    listing_id: &[u8; 32],
+    owner: Pubkey,
+    nft_mint: Pubkey,
+    seller_nft_account: Pubkey,
+    minimum: u64
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::InitializeAuction {
+            auction_state: pda::auction_state(),
+            auction: pda::auction(listing_id),
+            vault: pda::vault(listing_id),
+            owner,
+            payer: owner,
+            seller_profile: None,
+            blacklist_entry: pda::blacklist_entry(&owner),
+            fee_approver: None,
+            roles: None,
+            collection_config: None,
+            nft_metadata: None,
+            membership_discount: None,
+            membership_token_account: None,
+            membership_metadata: None,
+            registry_page: None,
+            nft_mint,
+            seller_nft_account,
+            nft_escrow: pda::nft_escrow(&nft_mint),
+            token_program: spl_token::ID,
+            system_program: anchor_lang::system_program::ID,
+        }).to_account_metas(None),
+        data: (instruction::InitializeAuction {
+            listing_id: *listing_id,
+            minimum,
+            owner,
+            overrides: ListingOverrides::default(),
+            fee_override_bps: None,
+        }).data(),
+    }
+}
+
+// The nested NFT return accounts have no client type outside the program, so
+// the metas are laid out by hand, in the order `CancelAuction` declares them
+pub fn cancel_auction(
+    listing_id: &[u8; 32],
+    owner: Pubkey,
+    nft_mint: Pubkey,
+    owner_nft_account: Pubkey
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new_readonly(pda::auction_state(), false),
+        AccountMeta::new(pda::auction(listing_id), false),
+        AccountMeta::new(owner, true),
+        AccountMeta::new(pda::nft_escrow(&nft_mint), false),
+        AccountMeta::new(owner_nft_account, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+    ];
+    // The Core, programmable NFT and Bubblegum accounts are all left out
+    metas.extend((0..18).map(|_| AccountMeta::new_readonly(ID, false)));
+    metas.push(AccountMeta::new_readonly(anchor_lang::system_program::ID, false));
+    if cfg!(feature = "event-cpi") {
+        metas.push(AccountMeta::new_readonly(pda::event_authority(), false));
+        metas.push(AccountMeta::new_readonly(ID, false));
+    }
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: (instruction::CancelAuction { listing_id: *listing_id }).data(),
+    }
+}
+
+pub fn end_auction(listing_id: &[u8; 32], caller: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::EndAuction {
+            auction_state: pda::auction_state(),
+            auction: pda::auction(listing_id),
+            vault: pda::vault(listing_id),
+            caller,
+            token_vault: None,
+            caller_token_account: None,
+            token_program: None,
+            payment_mint: None,
+            unwrap_account: None,
+            native_mint: None,
+            collection_stats: None,
+            floor_oracle: None,
+            registry_page: None,
+            collection_fee_config: None,
+            metadata: None,
+            core_asset: None,
+            core_collection: None,
+            system_program: anchor_lang::system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(),
+            #[cfg(feature = "event-cpi")]
+            program: ID,
+        }).to_account_metas(None),
+        data: (instruction::EndAuction { listing_id: *listing_id }).data(),
+    }
+}
+
+pub fn claim_nft(
+    listing_id: &[u8; 32],
+    winner: Pubkey,
+    seller: Pubkey,
+    nft_mint: Pubkey,
+    recipient_nft_account: Pubkey
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::ClaimNft {
+            auction_state: pda::auction_state(),
+            auction: pda::auction(listing_id),
+            winner,
+            seller,
+            token_account: None,
+            adapter_program: None,
+            nft_escrow: Some(pda::nft_escrow(&nft_mint)),
+            recipient: None,
+            recipient_nft_account: Some(recipient_nft_account),
+            nft_mint: None,
+            metadata: None,
+            master_edition: None,
+            token_program: Some(spl_token::ID),
+            associated_token_program: None,
+            token_metadata_program: None,
+            rent: None,
+            core_asset: None,
+            core_collection: None,
+            mpl_core_program: None,
+            escrow_token_record: None,
+            recipient_token_record: None,
+            authorization_rules: None,
+            authorization_rules_program: None,
+            sysvar_instructions: None,
+            merkle_tree: None,
+            tree_config: None,
+            log_wrapper: None,
+            compression_program: None,
+            bubblegum_program: None,
+            loyalty_account: None,
+            delivery_receipt: pda::settlement_receipt(
+                &pda::auction(listing_id),
+                SettlementStep::NftDelivered,
+                &winner
+            ),
+            system_program: anchor_lang::system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(),
+            #[cfg(feature = "event-cpi")]
+            program: ID,
+        }).to_account_metas(None),
+        data: (instruction::ClaimNft { listing_id: *listing_id, deliver_to: None }).data(),
+    }
+}
+
+pub fn claim_proceeds(listing_id: &[u8; 32], owner: Pubkey, nft_mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::ClaimProceeds {
+            auction_state: pda::auction_state(),
+            auction: pda::auction(listing_id),
+            vault: pda::vault(listing_id),
+            owner,
+            fee_treasury: pda::fee_treasury(&spl_token::native_mint::ID),
+            collection_fee_config: None,
+            referrer: None,
+            beneficiary: None,
+            winner: None,
+            metadata: None,
+            core_asset: None,
+            core_collection: None,
+            provenance: Some(pda::provenance(&nft_mint)),
+            token_vault: None,
+            owner_token_account: None,
+            treasury_token_account: None,
+            token_program: None,
+            payment_mint: None,
+            unwrap_account: None,
+            native_mint: None,
+            funds_receipt: pda::settlement_receipt(
+                &pda::auction(listing_id),
+                SettlementStep::FundsPaid,
+                &owner
+            ),
+            fees_receipt: pda::settlement_receipt(
+                &pda::auction(listing_id),
+                SettlementStep::FeesCollected,
+                &pda::fee_treasury(&spl_token::native_mint::ID)
+            ),
+            system_program: anchor_lang::system_program::ID,
+            #[cfg(feature = "event-cpi")]
+            event_authority: pda::event_authority(),
+            #[cfg(feature = "event-cpi")]
+            program: ID,
+        }).to_account_metas(None),
+        data: (instruction::ClaimProceeds { listing_id: *listing_id, hook: Pubkey::default() }).data(),
+    }
+}
+
+pub fn default_winner(
+    listing_id: &[u8; 32],
+    owner: Pubkey,
+    winner_bid_receipt: Option<Pubkey>
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::DefaultWinner {
+            auction: pda::auction(listing_id),
+            vault: pda::vault(listing_id),
+            winner_bid_receipt,
+            owner,
+            system_program: anchor_lang::system_program::ID,
+        }).to_account_metas(None),
+        data: (instruction::DefaultWinner { listing_id: *listing_id }).data(),
+    }
+}
+
+pub fn set_installment_plan(
+    listing_id: &[u8; 32],
+    owner: Pubkey,
+    count: u8,
+    interval: i64,
+    penalty_bps: u16
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::SetInstallmentPlan {
+            auction: pda::auction(listing_id),
+            owner,
+        }).to_account_metas(None),
+        data: (instruction::SetInstallmentPlan {
+            listing_id: *listing_id,
+            count,
+            interval,
+            penalty_bps,
+        }).data(),
+    }
+}
+
+pub fn pay_installment(listing_id: &[u8; 32], winner: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::PayInstallment {
+            auction: pda::auction(listing_id),
+            vault: pda::vault(listing_id),
+            winner,
+            system_program: anchor_lang::system_program::ID,
+        }).to_account_metas(None),
+        data: (instruction::PayInstallment { listing_id: *listing_id }).data(),
+    }
+}
+
+pub fn prune_archives(listing_id: &[u8; 32], rent_destination: Pubkey) -> Instruction {
+    let mut metas = (accounts::PruneArchives {
+        auction_state: pda::auction_state(),
+        archive: pda::archive(),
+        rent_destination,
+        system_program: anchor_lang::system_program::ID,
+    }).to_account_metas(None);
+    metas.push(AccountMeta::new(pda::auction(listing_id), false));
+    metas.push(AccountMeta::new(pda::vault(listing_id), false));
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: (instruction::PruneArchives { before_ts: i64::MAX, batch: 1 }).data(),
+    }
+}
+
+pub fn add_to_blacklist(authority: Pubkey, address: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::AddToBlacklist {
+            auction_state: pda::auction_state(),
+            roles: None,
+            blacklist_entry: pda::blacklist_entry(&address),
+            authority,
+            payer: authority,
+            system_program: anchor_lang::system_program::ID,
+            audit_log: pda::audit_log(),
+        }).to_account_metas(None),
+        data: (instruction::AddToBlacklist { address }).data(),
+    }
+}
+
+pub fn remove_from_blacklist(authority: Pubkey, address: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::RemoveFromBlacklist {
+            auction_state: pda::auction_state(),
+            roles: None,
+            blacklist_entry: pda::blacklist_entry(&address),
+            authority,
+            audit_log: pda::audit_log(),
+        }).to_account_metas(None),
+        data: (instruction::RemoveFromBlacklist {}).data(),
+    }
+}
+
+pub fn add_bridge_emitter(authority: Pubkey, chain: u16, address: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::ManageBridgeEmitters {
+            auction_state: pda::auction_state(),
+            authority,
+        }).to_account_metas(None),
+        data: (instruction::AddBridgeEmitter { chain, address }).data(),
+    }
+}
@@ -0,0 +1,60 @@
+// Listing a Core asset is held to the seller blacklist like every other
+// listing path. The asset only has to be owned by the Core program for the
+// accounts to pass, since the flag is checked before it's read, so no Core
+// program is loaded:
+//
+//     anchor build
+//     SBF_OUT_DIR=$PWD/target/deploy cargo test -p nft-com-auction --test core_listing
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use common::*;
+use nft_com_auction::errors::ErrorCode;
+use nft_com_auction::state::ListingOverrides;
+use nft_com_auction::{ accounts, instruction, pda, ID };
+use solana_program_test::ProgramTest;
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::signature::Signer;
+
+fn list_core_asset(listing_id: &[u8; 32], owner: Pubkey, asset: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: (accounts::ListCoreAsset {
+            auction_state: pda::auction_state(),
+            auction: pda::auction(listing_id),
+            vault: pda::vault(listing_id),
+            owner,
+            seller_profile: None,
+            blacklist_entry: pda::blacklist_entry(&owner),
+            asset,
+            core_collection: None,
+            mpl_core_program: mpl_core::ID,
+            system_program: anchor_lang::system_program::ID,
+        }).to_account_metas(None),
+        data: (instruction::ListCoreAsset {
+            listing_id: *listing_id,
+            minimum: SOL,
+            overrides: ListingOverrides::default(),
+        }).data(),
+    }
+}
+
+#[tokio::test]
+async fn blacklisted_seller_cannot_list_a_core_asset() {
+    let mut program_test = ProgramTest::new("auction_contract", ID, None);
+    let seller = funded(&mut program_test);
+    let asset = Pubkey::new_unique();
+    program_test.add_account(asset, SolanaAccount::new(SOL, 1, &mpl_core::ID));
+    let mut context = program_test.start_with_context().await;
+    let admin = context.payer.pubkey();
+
+    send(&mut context, &[initialize(admin), add_to_blacklist(admin, seller.pubkey())], &[]).await;
+    let listing_id = pda::listing_id(&seller.pubkey(), "core");
+    let list = list_core_asset(&listing_id, seller.pubkey(), asset);
+    assert_eq!(
+        error_code(try_send(&mut context, &[list], &[&seller]).await),
+        Some(u32::from(ErrorCode::Blacklisted))
+    );
+}
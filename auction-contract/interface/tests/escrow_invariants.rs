@@ -0,0 +1,198 @@
+// Property test of the escrow's books: random sequences of bids, withdrawals,
+// cancellations, clock jumps and settlements are run against the built program,
+// and after every step, whether the program accepted it or not:
+//
+// - the vault holds exactly its rent floor plus what the auction still owes
+// - every bidder's recorded position matches what they put in, net of the
+//   buyer fee, less what they took out, so a refund can't exceed the position
+//   or wrap it around
+// - buyer fees never exceed the marketplace cap on what was bid
+// - the highest bid never goes down
+//
+// Built and run as the lifecycle test is:
+//
+//     anchor build
+//     SBF_OUT_DIR=$PWD/target/deploy cargo test -p nft-com-auction --test escrow_invariants
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use auction_contract::math;
+use common::*;
+use nft_com_auction::ix::{ self, BidOptions };
+use nft_com_auction::state::{ AuctionStatus, NftComAuction };
+use nft_com_auction::{ pda, ID };
+use proptest::collection::vec;
+use proptest::prelude::*;
+use solana_program_test::{ ProgramTest, ProgramTestContext };
+use solana_sdk::signature::{ Keypair, Signer };
+
+const BIDDERS: usize = 3;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Bid {
+        bidder: usize,
+        amount: u64,
+    },
+    // Part of the position in basis points, or all of it
+    Withdraw {
+        bidder: usize,
+        part_bps: Option<u64>,
+    },
+    Cancel,
+    Warp {
+        seconds: i64,
+    },
+    // Run out the clock, end the auction and pay the seller
+    Settle,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        4 => (0..BIDDERS, 1..=40u64).prop_map(|(bidder, tenths)| Op::Bid {
+            bidder,
+            amount: (tenths * SOL) / 10,
+        }),
+        3 => (0..BIDDERS, proptest::option::of(1..=10_000u64)).prop_map(|(bidder, part_bps)| {
+            Op::Withdraw { bidder, part_bps }
+        }),
+        1 => Just(Op::Cancel),
+        2 => (0..DURATION / 2).prop_map(|seconds| Op::Warp { seconds }),
+        1 => Just(Op::Settle)
+    ]
+}
+
+struct Run {
+    context: ProgramTestContext,
+    seller: Keypair,
+    bidders: Vec<Keypair>,
+    listing_id: [u8; 32],
+    nft_mint: Pubkey,
+    seller_nft_account: Pubkey,
+    // What each bidder should have in escrow, kept alongside the program's books
+    escrowed: Vec<u64>,
+    highest_bid: u64,
+}
+
+impl Run {
+    async fn start() -> Run {
+        let mut program_test = ProgramTest::new("auction_contract", ID, None);
+        let seller = funded(&mut program_test);
+        let bidders: Vec<Keypair> = (0..BIDDERS).map(|_| funded(&mut program_test)).collect();
+        let mut context = program_test.start_with_context().await;
+        let admin = context.payer.pubkey();
+        send(&mut context, &[initialize(admin), init_fee_treasury(admin)], &[]).await;
+
+        let listing_id = pda::listing_id(&seller.pubkey(), "invariants");
+        let (nft_mint, seller_nft_account) = mint_nft(&mut context, &seller).await;
+        send(
+            &mut context,
+            &[initialize_auction(&listing_id, seller.pubkey(), nft_mint, seller_nft_account, SOL)],
+            &[&seller]
+        ).await;
+        Run {
+            context,
+            seller,
+            bidders,
+            listing_id,
+            nft_mint,
+            seller_nft_account,
+            escrowed: vec![0; BIDDERS],
+            highest_bid: 0,
+        }
+    }
+
+    async fn apply(&mut self, op: &Op) {
+        match *op {
+            Op::Bid { bidder, amount } => {
+                let wallet = self.bidders[bidder].pubkey();
+                let bid = ix::place_bid(wallet, &self.listing_id, wallet, amount, BidOptions::default());
+                if try_send(&mut self.context, &[bid], &[&self.bidders[bidder]]).await.is_ok() {
+                    self.escrowed[bidder] += net_of_fee(amount);
+                }
+            }
+            Op::Withdraw { bidder, part_bps } => {
+                let wallet = self.bidders[bidder].pubkey();
+                let requested = part_bps.map(|bps| (self.escrowed[bidder] * bps) / 10_000);
+                let withdrawal = ix::withdraw(wallet, &self.listing_id, None, None, None, requested);
+                let before = lamports(&mut self.context, wallet).await;
+                if try_send(&mut self.context, &[withdrawal], &[&self.bidders[bidder]]).await.is_ok() {
+                    let refunded = lamports(&mut self.context, wallet).await - before;
+                    if let Some(requested) = requested {
+                        assert_eq!(refunded, requested, "partial withdrawal paid the wrong amount");
+                    }
+                    self.escrowed[bidder] = self.escrowed[bidder]
+                        .checked_sub(refunded)
+                        .expect("withdrawal refunded more than the position held");
+                }
+            }
+            Op::Cancel => {
+                let cancel = cancel_auction(
+                    &self.listing_id,
+                    self.seller.pubkey(),
+                    self.nft_mint,
+                    self.seller_nft_account
+                );
+                let _ = try_send(&mut self.context, &[cancel], &[&self.seller]).await;
+            }
+            Op::Warp { seconds } => {
+                let later = now(&mut self.context).await + seconds;
+                warp_to(&mut self.context, later).await;
+            }
+            Op::Settle => {
+                let end_time = auction(&mut self.context, &self.listing_id).await.end_time;
+                if now(&mut self.context).await < end_time {
+                    warp_to(&mut self.context, end_time).await;
+                }
+                let admin = self.context.payer.pubkey();
+                let _ = try_send(&mut self.context, &[end_auction(&self.listing_id, admin)], &[]).await;
+                let claim = claim_proceeds(&self.listing_id, self.seller.pubkey(), self.nft_mint);
+                let _ = try_send(&mut self.context, &[claim], &[&self.seller]).await;
+            }
+        }
+    }
+
+    async fn check_invariants(&mut self) {
+        let auction = auction(&mut self.context, &self.listing_id).await;
+        let rent_floor = self.context.banks_client.get_rent().await.unwrap().minimum_balance(0);
+        let vault = lamports(&mut self.context, pda::vault(&self.listing_id)).await;
+        assert_eq!(vault, rent_floor + auction.outstanding().unwrap(), "vault out of step with the books");
+
+        for (bidder, escrowed) in self.bidders.iter().zip(&self.escrowed) {
+            let recorded = auction.bids
+                .iter()
+                .find(|bid| bid.bidder == bidder.pubkey())
+                .map_or(0, |bid| bid.amount);
+            assert_eq!(recorded, *escrowed, "recorded position differs from deposits less refunds");
+        }
+        if auction.status != AuctionStatus::Settled {
+            let positions: u64 = self.escrowed.iter().sum();
+            assert_eq!(auction.outstanding().unwrap(), positions + auction.fees);
+        }
+
+        let fee_cap = math::compute_fee(auction.total_amount, NftComAuction::MAX_FEE_BPS).unwrap();
+        assert!(auction.fees <= fee_cap, "buyer fees above the cap");
+
+        assert!(auction.highest_bid >= self.highest_bid, "highest bid went down");
+        self.highest_bid = auction.highest_bid;
+    }
+}
+
+async fn run(ops: Vec<Op>) {
+    let mut run = Run::start().await;
+    run.check_invariants().await;
+    for op in &ops {
+        run.apply(op).await;
+        run.check_invariants().await;
+    }
+}
+
+proptest! {
+    // Every case boots its own bank, so keep the count modest
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn escrow_stays_conserved(ops in vec(op(), 1..24)) {
+        tokio::runtime::Runtime::new().unwrap().block_on(run(ops));
+    }
+}
@@ -0,0 +1,260 @@
+// End-to-end run of a listing against the built program, in a bank rather than
+// a validator: initialize the marketplace, list an NFT, bid, outbid, withdraw,
+// extend on a late bid, end, claim and finally prune the settled accounts.
+// Further runs have the winner default and the sale go to the runner-up, and
+// the winner pay in installments.
+//
+// The program is loaded from its compiled binary, so build it first and point
+// the test at it, from the workspace root:
+//
+//     anchor build
+//     SBF_OUT_DIR=$PWD/target/deploy cargo test -p nft-com-auction --test lifecycle
+mod common;
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::spl_token;
+use common::*;
+use nft_com_auction::ix::{ self, BidOptions };
+use nft_com_auction::{ pda, ID };
+use nft_com_auction::state::{
+    AuctionArchive,
+    AuctionDetails,
+    AuctionStatus,
+    SettlementReceipt,
+    SettlementStep,
+};
+use solana_program_test::ProgramTest;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn auction_lifecycle() {
+    let mut program_test = ProgramTest::new("auction_contract", ID, None);
+    let seller = funded(&mut program_test);
+    let alice = funded(&mut program_test);
+    let bob = funded(&mut program_test);
+    let carol = funded(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+    let admin = context.payer.pubkey();
+
+    send(
+        &mut context,
+        &[initialize(admin), init_fee_treasury(admin), init_auction_archive(admin)],
+        &[]
+    ).await;
+
+    // List
+    let listing_id = pda::listing_id(&seller.pubkey(), "lifecycle");
+    let (nft_mint, seller_nft_account) = mint_nft(&mut context, &seller).await;
+    send(
+        &mut context,
+        &[initialize_auction(&listing_id, seller.pubkey(), nft_mint, seller_nft_account, SOL / 2)],
+        &[&seller]
+    ).await;
+    let listed = auction(&mut context, &listing_id).await;
+    assert_eq!(listed.status, AuctionStatus::Live);
+
+    // Bid, then outbid
+    send(
+        &mut context,
+        &[ix::place_bid(alice.pubkey(), &listing_id, alice.pubkey(), SOL, BidOptions::default())],
+        &[&alice]
+    ).await;
+    send(
+        &mut context,
+        &[ix::place_bid(bob.pubkey(), &listing_id, bob.pubkey(), 2 * SOL, BidOptions::default())],
+        &[&bob]
+    ).await;
+    let outbid = auction(&mut context, &listing_id).await;
+    assert_eq!(outbid.highest_bidder, bob.pubkey());
+    assert_eq!(outbid.highest_bid, net_of_fee(2 * SOL));
+
+    // The outbid bidder takes their bid back
+    let before = lamports(&mut context, alice.pubkey()).await;
+    send(
+        &mut context,
+        &[ix::withdraw(alice.pubkey(), &listing_id, None, None, None, None)],
+        &[&alice]
+    ).await;
+    assert_eq!(lamports(&mut context, alice.pubkey()).await, before + net_of_fee(SOL));
+
+    // A bid inside the sniping window pushes the end back
+    warp_to(&mut context, listed.end_time - SNIPING_WINDOW / 2).await;
+    send(
+        &mut context,
+        &[ix::place_bid(carol.pubkey(), &listing_id, carol.pubkey(), 3 * SOL, BidOptions::default())],
+        &[&carol]
+    ).await;
+    let extended = auction(&mut context, &listing_id).await;
+    assert_eq!(extended.end_time, listed.end_time + EXTENSION);
+    assert_eq!(extended.highest_bidder, carol.pubkey());
+
+    // End once the extended time is up; bob is refunded afterwards
+    warp_to(&mut context, extended.end_time).await;
+    send(&mut context, &[end_auction(&listing_id, admin)], &[]).await;
+    let ended = auction(&mut context, &listing_id).await;
+    assert_eq!(ended.status, AuctionStatus::Ended);
+    assert_eq!(ended.bid_history.records.len(), 3);
+    assert_eq!(ended.bid_snapshot, Some(ended.bid_history.snapshot_root()));
+    send(
+        &mut context,
+        &[ix::withdraw(bob.pubkey(), &listing_id, None, None, None, None)],
+        &[&bob]
+    ).await;
+
+    // The winner takes the NFT and the seller the proceeds
+    let carol_nft_account = token_account(&mut context, &nft_mint, &carol.pubkey()).await;
+    send(
+        &mut context,
+        &[claim_nft(&listing_id, carol.pubkey(), seller.pubkey(), nft_mint, carol_nft_account)],
+        &[&carol]
+    ).await;
+    let delivered = context.banks_client.get_account(carol_nft_account).await.unwrap().unwrap();
+    assert_eq!(spl_token::state::Account::unpack(&delivered.data).unwrap().amount, 1);
+
+    let before = lamports(&mut context, seller.pubkey()).await;
+    send(&mut context, &[claim_proceeds(&listing_id, seller.pubkey(), nft_mint)], &[&seller]).await;
+    assert!(lamports(&mut context, seller.pubkey()).await > before);
+    let settled = auction(&mut context, &listing_id).await;
+    assert_eq!(settled.status, AuctionStatus::Settled);
+    assert_eq!(settled.outstanding().unwrap(), 0);
+
+    // The payout left a receipt, so a retried claim pays the seller nothing more
+    let receipt = pda::settlement_receipt(
+        &pda::auction(&listing_id),
+        SettlementStep::FundsPaid,
+        &seller.pubkey()
+    );
+    let account = context.banks_client.get_account(receipt).await.unwrap().unwrap();
+    let receipt = SettlementReceipt::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert!(receipt.step == SettlementStep::FundsPaid);
+    assert_eq!(receipt.party, seller.pubkey());
+    let paid = lamports(&mut context, seller.pubkey()).await;
+    let retry = claim_proceeds(&listing_id, seller.pubkey(), nft_mint);
+    assert!(try_send(&mut context, &[retry], &[&seller]).await.is_err());
+    assert_eq!(lamports(&mut context, seller.pubkey()).await, paid);
+
+    // Close the settled accounts once they're past the archive retention,
+    // keeping the outcome in the archive
+    let retention = 31 * 24 * 60 * 60;
+    let later = now(&mut context).await + retention;
+    warp_to(&mut context, later).await;
+    send(&mut context, &[prune_archives(&listing_id, admin)], &[]).await;
+    assert!(context.banks_client.get_account(pda::auction(&listing_id)).await.unwrap().is_none());
+    assert!(context.banks_client.get_account(pda::vault(&listing_id)).await.unwrap().is_none());
+    let account = context.banks_client.get_account(pda::archive()).await.unwrap().unwrap();
+    let archive = AuctionArchive::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(archive.leaves, 1);
+    assert_eq!(archive.branch[0], settled.archive_leaf());
+}
+
+#[tokio::test]
+async fn winner_default_re_awards_to_runner_up() {
+    let mut program_test = ProgramTest::new("auction_contract", ID, None);
+    let seller = funded(&mut program_test);
+    let alice = funded(&mut program_test);
+    let bob = funded(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+    let admin = context.payer.pubkey();
+    send(&mut context, &[initialize(admin), init_fee_treasury(admin)], &[]).await;
+
+    let listing_id = pda::listing_id(&seller.pubkey(), "default");
+    let (nft_mint, seller_nft_account) = mint_nft(&mut context, &seller).await;
+    send(
+        &mut context,
+        &[initialize_auction(&listing_id, seller.pubkey(), nft_mint, seller_nft_account, SOL / 2)],
+        &[&seller]
+    ).await;
+    send(
+        &mut context,
+        &[ix::place_bid(alice.pubkey(), &listing_id, alice.pubkey(), SOL, BidOptions::default())],
+        &[&alice]
+    ).await;
+    send(
+        &mut context,
+        &[ix::place_bid(bob.pubkey(), &listing_id, bob.pubkey(), 2 * SOL, BidOptions::default())],
+        &[&bob]
+    ).await;
+    let listed = auction(&mut context, &listing_id).await;
+    assert_eq!(listed.runners_up.len(), 1);
+    assert_eq!(listed.runners_up[0].bidder, alice.pubkey());
+
+    warp_to(&mut context, listed.end_time).await;
+    send(&mut context, &[end_auction(&listing_id, admin)], &[]).await;
+
+    // Bob has a week to claim before the seller may declare him in default
+    let default = default_winner(&listing_id, seller.pubkey(), None);
+    assert!(try_send(&mut context, &[default.clone()], &[&seller]).await.is_err());
+    let ended = auction(&mut context, &listing_id).await;
+    warp_to(&mut context, ended.closed_at + AuctionDetails::BOND_CLAIM_WINDOW).await;
+    send(&mut context, &[default], &[&seller]).await;
+
+    let re_awarded = auction(&mut context, &listing_id).await;
+    assert_eq!(re_awarded.status, AuctionStatus::Ended);
+    assert_eq!(re_awarded.highest_bidder, alice.pubkey());
+    assert_eq!(re_awarded.highest_bid, net_of_fee(SOL));
+    assert!(re_awarded.runners_up.is_empty());
+
+    // The defaulter takes their bid back like any outbid bidder
+    let before = lamports(&mut context, bob.pubkey()).await;
+    send(
+        &mut context,
+        &[ix::withdraw(bob.pubkey(), &listing_id, None, None, None, None)],
+        &[&bob]
+    ).await;
+    assert_eq!(lamports(&mut context, bob.pubkey()).await, before + net_of_fee(2 * SOL));
+}
+
+#[tokio::test]
+async fn installment_sale() {
+    let mut program_test = ProgramTest::new("auction_contract", ID, None);
+    let seller = funded(&mut program_test);
+    let alice = funded(&mut program_test);
+    let mut context = program_test.start_with_context().await;
+    let admin = context.payer.pubkey();
+    send(&mut context, &[initialize(admin), init_fee_treasury(admin)], &[]).await;
+
+    let listing_id = pda::listing_id(&seller.pubkey(), "installments");
+    let (nft_mint, seller_nft_account) = mint_nft(&mut context, &seller).await;
+    let week = 7 * 24 * 60 * 60;
+    send(
+        &mut context,
+        &[
+            initialize_auction(&listing_id, seller.pubkey(), nft_mint, seller_nft_account, SOL / 2),
+            set_installment_plan(&listing_id, seller.pubkey(), 3, week, 1_000),
+        ],
+        &[&seller]
+    ).await;
+    send(
+        &mut context,
+        &[ix::place_bid(alice.pubkey(), &listing_id, alice.pubkey(), SOL, BidOptions::default())],
+        &[&alice]
+    ).await;
+    let listed = auction(&mut context, &listing_id).await;
+    warp_to(&mut context, listed.end_time).await;
+    send(&mut context, &[end_auction(&listing_id, admin)], &[]).await;
+
+    // The NFT stays in escrow until the last installment is in
+    let alice_nft_account = token_account(&mut context, &nft_mint, &alice.pubkey()).await;
+    let claim = claim_nft(&listing_id, alice.pubkey(), seller.pubkey(), nft_mint, alice_nft_account);
+    assert!(try_send(&mut context, &[claim.clone()], &[&alice]).await.is_err());
+
+    let installment = net_of_fee(SOL);
+    for paid in 2..=3 {
+        let before = lamports(&mut context, alice.pubkey()).await;
+        send(&mut context, &[pay_installment(&listing_id, alice.pubkey())], &[&alice]).await;
+        assert_eq!(lamports(&mut context, alice.pubkey()).await, before - installment);
+        let plan = auction(&mut context, &listing_id).await.installment_plan.unwrap();
+        assert_eq!(plan.paid, paid);
+    }
+    let paid_up = auction(&mut context, &listing_id).await;
+    assert_eq!(paid_up.winning_price(), 3 * installment);
+    let overpayment = pay_installment(&listing_id, alice.pubkey());
+    assert!(try_send(&mut context, &[overpayment], &[&alice]).await.is_err());
+
+    send(&mut context, &[claim], &[&alice]).await;
+    send(&mut context, &[claim_proceeds(&listing_id, seller.pubkey(), nft_mint)], &[&seller]).await;
+    let settled = auction(&mut context, &listing_id).await;
+    assert_eq!(settled.status, AuctionStatus::Settled);
+    assert_eq!(settled.outstanding().unwrap(), 0);
+}
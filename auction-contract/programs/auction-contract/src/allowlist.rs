@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak::hashv;
+use crate::errors::ErrorCode;
+use crate::state::AuctionDetails;
+
+// Private listings keep the Merkle root of their allowlist. Leaves are the
+// keccak hash of a bidder's key; each level hashes the sorted pair of nodes, so
+// proofs carry no path bits and the usual off-chain Merkle tools can build them.
+
+pub fn leaf(bidder: &Pubkey) -> [u8; 32] {
+    hashv(&[bidder.as_ref()]).to_bytes()
+}
+
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        hashv(&[a, b]).to_bytes()
+    } else {
+        hashv(&[b, a]).to_bytes()
+    }
+}
+
+// Fold `proof` (leaf to root) over `bidder`'s leaf
+pub fn compute_root(bidder: &Pubkey, proof: &[[u8; 32]]) -> [u8; 32] {
+    proof.iter().fold(leaf(bidder), |node, sibling| hash_pair(&node, sibling))
+}
+
+// Root of a tree over `leaves` built the same way; a node without a sibling
+// moves up a level as it is. All zero for no leaves.
+pub fn root_of(mut leaves: Vec<[u8; 32]>) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+    while leaves.len() > 1 {
+        leaves = leaves
+            .chunks(2)
+            .map(|pair| {
+                match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                }
+            })
+            .collect();
+    }
+    leaves[0]
+}
+
+// Let `bidder` through unless the listing has an allowlist that `proof` doesn't place them on
+pub fn check(auction: &AuctionDetails, bidder: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+    if let Some(root) = auction.allowlist_root {
+        require!(compute_root(bidder, proof) == root, ErrorCode::NotAllowlisted);
+    }
+    Ok(())
+}
@@ -0,0 +1,556 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{ AccountMeta, Instruction };
+use anchor_lang::solana_program::keccak::hashv;
+use anchor_lang::solana_program::program::{ invoke, invoke_signed };
+use crate::errors::ErrorCode;
+use crate::state::{ CompressedCreator, CompressedLeaf };
+
+// Compressed NFT (Bubblegum) proof handling shared by every instruction that
+// touches a cNFT: listing, settlement and reclaim, plus the mint used for
+// participation receipts.
+//
+// Proof nodes travel as remaining accounts, leaf first order, truncated by the
+// tree's canopy (the top `canopy_depth` levels are cached on-chain in the tree
+// account). `proof_accounts` sizes that slice from the tree account itself,
+// `leaf_hash`/`compute_root` rebuild a root locally, and `verify_leaf` checks
+// the leaf against the live tree before any transfer is attempted.
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = pubkey!(
+    "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+);
+
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// account type (1) + header version (1) + V1 header (54)
+const TREE_HEADER_LEN: usize = 56;
+const LEAF_SCHEMA_V1: u8 = 1;
+
+pub struct TreeShape {
+    pub max_buffer_size: u32,
+    pub max_depth: u32,
+    pub canopy_depth: u32,
+}
+
+// Read max depth and canopy depth from a concurrent merkle tree account
+pub fn tree_shape(merkle_tree: &AccountInfo) -> Result<TreeShape> {
+    require_keys_eq!(
+        *merkle_tree.owner,
+        ACCOUNT_COMPRESSION_PROGRAM_ID,
+        ErrorCode::InvalidMerkleTree
+    );
+    let data = merkle_tree.try_borrow_data()?;
+    require!(data.len() > TREE_HEADER_LEN && data[0] == 1, ErrorCode::InvalidMerkleTree);
+
+    let max_buffer_size = u32::from_le_bytes(data[2..6].try_into().unwrap());
+    let max_depth = u32::from_le_bytes(data[6..10].try_into().unwrap());
+
+    // sequence number, active index, buffer size, then the change log buffer
+    // and rightmost proof, each path node 32 bytes plus root/leaf and index
+    let depth = max_depth as usize;
+    let tree_len = 24 + (max_buffer_size as usize) * (32 * depth + 40) + (32 * depth + 40);
+    let canopy_len = data
+        .len()
+        .checked_sub(TREE_HEADER_LEN + tree_len)
+        .ok_or(ErrorCode::InvalidMerkleTree)?;
+
+    // A canopy of depth c caches 2^(c+1) - 2 nodes
+    let canopy_nodes = canopy_len / 32;
+    let canopy_depth = (canopy_nodes + 2).ilog2().saturating_sub(1);
+    require!(canopy_depth <= max_depth, ErrorCode::InvalidMerkleTree);
+
+    Ok(TreeShape { max_buffer_size, max_depth, canopy_depth })
+}
+
+// The tree's current root, that of the change log entry at the active index
+pub fn current_root(merkle_tree: &AccountInfo) -> Result<[u8; 32]> {
+    let shape = tree_shape(merkle_tree)?;
+    let data = merkle_tree.try_borrow_data()?;
+    let active_index = u64::from_le_bytes(
+        data[TREE_HEADER_LEN + 8..TREE_HEADER_LEN + 16].try_into().unwrap()
+    );
+    require!(active_index < (shape.max_buffer_size as u64), ErrorCode::InvalidMerkleTree);
+    let change_log_len = 32 * (shape.max_depth as usize) + 40;
+    let start = TREE_HEADER_LEN + 24 + (active_index as usize) * change_log_len;
+    Ok(data[start..start + 32].try_into().unwrap())
+}
+
+// Split the proof nodes off the front of `remaining_accounts`, returning
+// them and whatever follows
+pub fn proof_accounts<'a, 'info>(
+    merkle_tree: &AccountInfo,
+    remaining_accounts: &'a [AccountInfo<'info>]
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let shape = tree_shape(merkle_tree)?;
+    let proof_len = (shape.max_depth - shape.canopy_depth) as usize;
+    require!(remaining_accounts.len() >= proof_len, ErrorCode::InvalidProofLength);
+    Ok(remaining_accounts.split_at(proof_len))
+}
+
+// Bubblegum's asset id for the leaf minted at `nonce` in `merkle_tree`
+pub fn asset_id(merkle_tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"asset", merkle_tree.as_ref(), &nonce.to_le_bytes()],
+        &BUBBLEGUM_PROGRAM_ID
+    ).0
+}
+
+// Bubblegum's config account of `merkle_tree`
+pub fn tree_config(merkle_tree: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+// Leaves minted so far into the tree behind `tree_config`, i.e. the nonce of
+// the next one: discriminator, creator, delegate, capacity, then the count
+fn num_minted(tree_config: &AccountInfo) -> Result<u64> {
+    require_keys_eq!(*tree_config.owner, BUBBLEGUM_PROGRAM_ID, ErrorCode::InvalidMerkleTree);
+    let data = tree_config.try_borrow_data()?;
+    let count = data.get(80..88).ok_or(ErrorCode::InvalidMerkleTree)?;
+    Ok(u64::from_le_bytes(count.try_into().unwrap()))
+}
+
+// Bubblegum's MetadataArgs, with the enums as their variant index
+#[derive(AnchorSerialize)]
+struct MetadataArgs {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+    token_standard: Option<u8>,
+    collection: Option<(bool, Pubkey)>,
+    uses: Option<(u8, u64, u64)>,
+    token_program_version: u8,
+    creators: Vec<(Pubkey, bool, u8)>,
+}
+
+pub struct MintAccounts<'a, 'info> {
+    pub tree_config: &'a AccountInfo<'info>,
+    pub leaf_owner: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    // Delegate of the tree, signing with `signer_seeds`
+    pub tree_delegate: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub bubblegum_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+// Mint an immutable, royalty-free cNFT named `name` to the leaf owner, who is
+// also its delegate, returning its asset id
+pub fn mint_v1(
+    accounts: MintAccounts,
+    name: String,
+    symbol: String,
+    uri: String,
+    signer_seeds: &[&[&[u8]]]
+) -> Result<Pubkey> {
+    require_keys_eq!(
+        accounts.bubblegum_program.key(),
+        BUBBLEGUM_PROGRAM_ID,
+        ErrorCode::InvalidMerkleTree
+    );
+    require_keys_eq!(
+        accounts.compression_program.key(),
+        ACCOUNT_COMPRESSION_PROGRAM_ID,
+        ErrorCode::InvalidMerkleTree
+    );
+    require_keys_eq!(accounts.log_wrapper.key(), NOOP_PROGRAM_ID, ErrorCode::InvalidMerkleTree);
+    require_keys_eq!(
+        accounts.tree_config.key(),
+        tree_config(&accounts.merkle_tree.key()),
+        ErrorCode::InvalidMerkleTree
+    );
+    let asset = asset_id(&accounts.merkle_tree.key(), num_minted(accounts.tree_config)?);
+
+    let metadata = MetadataArgs {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0,
+        primary_sale_happened: false,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(0), // NonFungible
+        collection: None,
+        uses: None,
+        token_program_version: 0, // Original
+        creators: Vec::new(),
+    };
+    let mut data = hash(b"global:mint_v1").to_bytes()[..8].to_vec();
+    metadata.serialize(&mut data)?;
+
+    let accounts_meta = vec![
+        AccountMeta::new(accounts.tree_config.key(), false),
+        AccountMeta::new_readonly(accounts.leaf_owner.key(), false),
+        AccountMeta::new_readonly(accounts.leaf_owner.key(), false),
+        AccountMeta::new(accounts.merkle_tree.key(), false),
+        AccountMeta::new(accounts.payer.key(), true),
+        AccountMeta::new_readonly(accounts.tree_delegate.key(), true),
+        AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+        AccountMeta::new_readonly(accounts.system_program.key(), false)
+    ];
+    invoke_signed(
+        &(Instruction { program_id: BUBBLEGUM_PROGRAM_ID, accounts: accounts_meta, data }),
+        &[
+            accounts.tree_config.clone(),
+            accounts.leaf_owner.clone(),
+            accounts.merkle_tree.clone(),
+            accounts.payer.clone(),
+            accounts.tree_delegate.clone(),
+            accounts.log_wrapper.clone(),
+            accounts.compression_program.clone(),
+            accounts.system_program.clone(),
+            accounts.bubblegum_program.clone(),
+        ],
+        signer_seeds
+    )?;
+    Ok(asset)
+}
+
+// Bubblegum's data hash: the keccak hash of the metadata args, hashed again
+// with the royalty rate so the rate can be proven without the whole metadata
+pub fn data_hash(metadata_hash: &[u8; 32], seller_fee_basis_points: u16) -> [u8; 32] {
+    hashv(&[metadata_hash, &seller_fee_basis_points.to_le_bytes()]).to_bytes()
+}
+
+// Bubblegum's creator hash over (address, verified, share) of each creator
+pub fn creator_hash(creators: &[CompressedCreator]) -> [u8; 32] {
+    let creators: Vec<[u8; 34]> = creators
+        .iter()
+        .map(|creator| {
+            let mut entry = [0u8; 34];
+            entry[..32].copy_from_slice(creator.address.as_ref());
+            entry[32] = creator.verified as u8;
+            entry[33] = creator.share;
+            entry
+        })
+        .collect();
+    hashv(&creators.iter().map(|entry| entry.as_ref()).collect::<Vec<&[u8]>>()).to_bytes()
+}
+
+pub struct TransferAccounts<'a, 'info> {
+    pub tree_config: &'a AccountInfo<'info>,
+    // Signs, with `signer_seeds` when it's the state PDA
+    pub leaf_owner: &'a AccountInfo<'info>,
+    pub leaf_delegate: &'a AccountInfo<'info>,
+    pub new_leaf_owner: &'a AccountInfo<'info>,
+    pub merkle_tree: &'a AccountInfo<'info>,
+    pub log_wrapper: &'a AccountInfo<'info>,
+    pub compression_program: &'a AccountInfo<'info>,
+    pub bubblegum_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+// Hand the cNFT at `leaf` to a new owner, proving it against the tree's
+// current root with the proof nodes at the front of `remaining_accounts`
+pub fn transfer<'info>(
+    accounts: TransferAccounts<'_, 'info>,
+    leaf: &CompressedLeaf,
+    remaining_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]]
+) -> Result<()> {
+    require_keys_eq!(
+        accounts.bubblegum_program.key(),
+        BUBBLEGUM_PROGRAM_ID,
+        ErrorCode::InvalidMerkleTree
+    );
+    require_keys_eq!(
+        accounts.compression_program.key(),
+        ACCOUNT_COMPRESSION_PROGRAM_ID,
+        ErrorCode::InvalidMerkleTree
+    );
+    require_keys_eq!(accounts.log_wrapper.key(), NOOP_PROGRAM_ID, ErrorCode::InvalidMerkleTree);
+    require_keys_eq!(accounts.merkle_tree.key(), leaf.merkle_tree, ErrorCode::InvalidMerkleTree);
+    require_keys_eq!(
+        accounts.tree_config.key(),
+        tree_config(&leaf.merkle_tree),
+        ErrorCode::InvalidMerkleTree
+    );
+    let (proof, _) = proof_accounts(accounts.merkle_tree, remaining_accounts)?;
+    let index = u32::try_from(leaf.nonce).map_err(|_| ErrorCode::InvalidMerkleTree)?;
+    let root = current_root(accounts.merkle_tree)?;
+
+    // Fail on a stale proof or the wrong owner here rather than deep in Bubblegum
+    let hashed = leaf_hash(
+        &asset_id(&leaf.merkle_tree, leaf.nonce),
+        accounts.leaf_owner.key,
+        accounts.leaf_delegate.key,
+        leaf.nonce,
+        &leaf.data_hash,
+        &leaf.creator_hash
+    );
+    verify_leaf(accounts.compression_program, accounts.merkle_tree, proof, root, hashed, index)?;
+
+    let mut data = hash(b"global:transfer").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&root);
+    data.extend_from_slice(&leaf.data_hash);
+    data.extend_from_slice(&leaf.creator_hash);
+    data.extend_from_slice(&leaf.nonce.to_le_bytes());
+    data.extend_from_slice(&index.to_le_bytes());
+
+    let mut accounts_meta = vec![
+        AccountMeta::new_readonly(accounts.tree_config.key(), false),
+        AccountMeta::new_readonly(accounts.leaf_owner.key(), true),
+        AccountMeta::new_readonly(accounts.leaf_delegate.key(), false),
+        AccountMeta::new_readonly(accounts.new_leaf_owner.key(), false),
+        AccountMeta::new(accounts.merkle_tree.key(), false),
+        AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+        AccountMeta::new_readonly(accounts.system_program.key(), false)
+    ];
+    accounts_meta.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+
+    let mut account_infos = vec![
+        accounts.tree_config.clone(),
+        accounts.leaf_owner.clone(),
+        accounts.leaf_delegate.clone(),
+        accounts.new_leaf_owner.clone(),
+        accounts.merkle_tree.clone(),
+        accounts.log_wrapper.clone(),
+        accounts.compression_program.clone(),
+        accounts.system_program.clone(),
+        accounts.bubblegum_program.clone()
+    ];
+    account_infos.extend(proof.iter().cloned());
+
+    invoke_signed(
+        &(Instruction { program_id: BUBBLEGUM_PROGRAM_ID, accounts: accounts_meta, data }),
+        &account_infos,
+        signer_seeds
+    )?;
+    Ok(())
+}
+
+// Hash of a Bubblegum V1 leaf
+pub fn leaf_hash(
+    asset_id: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    nonce: u64,
+    data_hash: &[u8; 32],
+    creator_hash: &[u8; 32]
+) -> [u8; 32] {
+    hashv(
+        &[
+            &[LEAF_SCHEMA_V1],
+            asset_id.as_ref(),
+            owner.as_ref(),
+            delegate.as_ref(),
+            &nonce.to_le_bytes(),
+            data_hash,
+            creator_hash,
+        ]
+    ).to_bytes()
+}
+
+// Fold `proof` (leaf to root) over `leaf` at position `index`
+pub fn compute_root(leaf: [u8; 32], proof: &[[u8; 32]], index: u32) -> [u8; 32] {
+    proof
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (level, sibling)| {
+            if (index >> level) & 1 == 0 {
+                hashv(&[&node, sibling]).to_bytes()
+            } else {
+                hashv(&[sibling, &node]).to_bytes()
+            }
+        })
+}
+
+// Check `leaf` against `root`. A full proof that folds to it needs nothing
+// more; otherwise account compression checks the leaf against the root or one
+// still in its change log, completing the proof from the canopy.
+pub fn verify_leaf<'info>(
+    compression_program: &AccountInfo<'info>,
+    merkle_tree: &AccountInfo<'info>,
+    proof: &[AccountInfo<'info>],
+    root: [u8; 32],
+    leaf: [u8; 32],
+    index: u32
+) -> Result<()> {
+    require_keys_eq!(
+        compression_program.key(),
+        ACCOUNT_COMPRESSION_PROGRAM_ID,
+        ErrorCode::InvalidMerkleTree
+    );
+    let shape = tree_shape(merkle_tree)?;
+    if shape.canopy_depth == 0 {
+        let nodes: Vec<[u8; 32]> = proof
+            .iter()
+            .map(|node| node.key.to_bytes())
+            .collect();
+        if compute_root(leaf, &nodes, index) == root {
+            return Ok(());
+        }
+    }
+
+    let mut data = hash(b"global:verify_leaf").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&root);
+    data.extend_from_slice(&leaf);
+    data.extend_from_slice(&index.to_le_bytes());
+
+    let mut accounts = vec![AccountMeta::new_readonly(merkle_tree.key(), false)];
+    accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+
+    let mut account_infos = vec![merkle_tree.clone()];
+    account_infos.extend(proof.iter().cloned());
+    account_infos.push(compression_program.clone());
+
+    invoke(
+        &(Instruction { program_id: ACCOUNT_COMPRESSION_PROGRAM_ID, accounts, data }),
+        &account_infos
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A concurrent merkle tree account of the given shape, with `root` at the
+    // active change log entry
+    fn tree_data(
+        max_depth: u32,
+        max_buffer_size: u32,
+        canopy_depth: u32,
+        root: [u8; 32]
+    ) -> Vec<u8> {
+        let change_log_len = 32 * (max_depth as usize) + 40;
+        let tree_len = 24 + (max_buffer_size as usize) * change_log_len + change_log_len;
+        let canopy_len = 32 * ((1usize << (canopy_depth + 1)) - 2);
+        let mut data = vec![0u8; TREE_HEADER_LEN + tree_len + canopy_len];
+        data[0] = 1;
+        data[2..6].copy_from_slice(&max_buffer_size.to_le_bytes());
+        data[6..10].copy_from_slice(&max_depth.to_le_bytes());
+
+        let active_index = 1u64;
+        data[TREE_HEADER_LEN + 8..TREE_HEADER_LEN + 16].copy_from_slice(
+            &active_index.to_le_bytes()
+        );
+        let start = TREE_HEADER_LEN + 24 + (active_index as usize) * change_log_len;
+        data[start..start + 32].copy_from_slice(&root);
+        data
+    }
+
+    fn account<'a>(
+        key: &'a Pubkey,
+        lamports: &'a mut u64,
+        data: &'a mut Vec<u8>,
+        owner: &'a Pubkey
+    ) -> AccountInfo<'a> {
+        AccountInfo::new(key, false, false, lamports, data, owner, false, 0)
+    }
+
+    fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        hashv(&[a, b]).to_bytes()
+    }
+
+    #[test]
+    fn tree_shape_reads_depth_and_canopy() {
+        for canopy in [0, 1, 3] {
+            let key = Pubkey::new_unique();
+            let mut lamports = 0;
+            let mut data = tree_data(5, 8, canopy, [0u8; 32]);
+            let owner = ACCOUNT_COMPRESSION_PROGRAM_ID;
+            let tree = account(&key, &mut lamports, &mut data, &owner);
+
+            let shape = tree_shape(&tree).unwrap();
+            assert_eq!(shape.max_depth, 5);
+            assert_eq!(shape.max_buffer_size, 8);
+            assert_eq!(shape.canopy_depth, canopy);
+        }
+    }
+
+    #[test]
+    fn tree_shape_rejects_foreign_and_short_accounts() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = tree_data(5, 8, 0, [0u8; 32]);
+        let owner = Pubkey::new_unique();
+        let tree = account(&key, &mut lamports, &mut data, &owner);
+        assert!(tree_shape(&tree).is_err());
+
+        let mut lamports = 0;
+        let mut data = tree_data(5, 8, 0, [0u8; 32]);
+        data.truncate(TREE_HEADER_LEN + 100);
+        let owner = ACCOUNT_COMPRESSION_PROGRAM_ID;
+        let tree = account(&key, &mut lamports, &mut data, &owner);
+        assert!(tree_shape(&tree).is_err());
+    }
+
+    #[test]
+    fn compute_root_matches_a_hand_built_tree() {
+        let leaves: Vec<[u8; 32]> = (0u8..4).map(|i| hash(&[i]).to_bytes()).collect();
+        let left = node(&leaves[0], &leaves[1]);
+        let right = node(&leaves[2], &leaves[3]);
+        let root = node(&left, &right);
+
+        assert_eq!(compute_root(leaves[0], &[leaves[1], right], 0), root);
+        assert_eq!(compute_root(leaves[1], &[leaves[0], right], 1), root);
+        assert_eq!(compute_root(leaves[2], &[leaves[3], left], 2), root);
+        assert_eq!(compute_root(leaves[3], &[leaves[2], left], 3), root);
+        // The right proof at the wrong position lands elsewhere
+        assert_ne!(compute_root(leaves[2], &[leaves[3], left], 0), root);
+    }
+
+    #[test]
+    fn verify_leaf_accepts_a_full_proof_without_a_cpi() {
+        let leaves: Vec<[u8; 32]> = (0u8..4).map(|i| hash(&[i]).to_bytes()).collect();
+        let left = node(&leaves[0], &leaves[1]);
+        let root = node(&left, &node(&leaves[2], &leaves[3]));
+
+        let tree_key = Pubkey::new_unique();
+        let mut tree_lamports = 0;
+        let mut tree_data = tree_data(2, 4, 0, root);
+        let compression = ACCOUNT_COMPRESSION_PROGRAM_ID;
+        let tree = account(&tree_key, &mut tree_lamports, &mut tree_data, &compression);
+
+        let loader = Pubkey::default();
+        let mut program_lamports = 0;
+        let mut program_data = vec![];
+        let program = account(&compression, &mut program_lamports, &mut program_data, &loader);
+
+        let sibling = Pubkey::new_from_array(leaves[3]);
+        let uncle = Pubkey::new_from_array(left);
+        let (mut sibling_lamports, mut uncle_lamports) = (0, 0);
+        let (mut sibling_data, mut uncle_data) = (vec![], vec![]);
+        let proof = [
+            account(&sibling, &mut sibling_lamports, &mut sibling_data, &loader),
+            account(&uncle, &mut uncle_lamports, &mut uncle_data, &loader),
+        ];
+        assert!(verify_leaf(&program, &tree, &proof, root, leaves[2], 2).is_ok());
+    }
+
+    #[test]
+    fn current_root_reads_the_active_change_log() {
+        let root = hash(b"root").to_bytes();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = tree_data(3, 4, 0, root);
+        let owner = ACCOUNT_COMPRESSION_PROGRAM_ID;
+        let tree = account(&key, &mut lamports, &mut data, &owner);
+        assert_eq!(current_root(&tree).unwrap(), root);
+    }
+
+    #[test]
+    fn leaf_hash_binds_owner_and_creators() {
+        let asset = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let data_hash = data_hash(&[7u8; 32], 500);
+        let creators = [
+            CompressedCreator { address: Pubkey::new_unique(), verified: true, share: 100 },
+        ];
+        let creators_hash = creator_hash(&creators);
+        let leaf = leaf_hash(&asset, &owner, &owner, 3, &data_hash, &creators_hash);
+
+        let stranger = Pubkey::new_unique();
+        assert_ne!(leaf, leaf_hash(&asset, &stranger, &owner, 3, &data_hash, &creators_hash));
+        assert_ne!(leaf, leaf_hash(&asset, &owner, &owner, 3, &data_hash, &creator_hash(&[])));
+        assert_ne!(data_hash, super::data_hash(&[7u8; 32], 0));
+    }
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{ AccountMeta, Instruction };
+use anchor_lang::solana_program::keccak::hashv;
+use anchor_lang::solana_program::program::invoke_signed;
+use crate::cnft::ACCOUNT_COMPRESSION_PROGRAM_ID;
+use crate::AUCTION_STATE_SEED;
+
+// Bidder positions kept as leaves of an spl-account-compression tree instead
+// of inline or on bid pages, so auctions with tens of thousands of bidders
+// don't pay rent per bidder.
+//
+// The auction state PDA is the tree authority. Each leaf commits to
+// (listing id, bidder, running total, last bid time); the full values are
+// logged through the noop program and the `CompressedPositionUpdated` event so
+// indexers can serve leaves and proofs back to bidders.
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// A bidder's existing leaf, as supplied by the client from the indexer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CompressedPosition {
+    pub amount: u64,
+    pub time: i64,
+    pub index: u32,
+}
+
+pub fn position_leaf(listing_id: &[u8; 32], bidder: &Pubkey, amount: u64, time: i64) -> [u8; 32] {
+    hashv(
+        &[listing_id.as_ref(), bidder.as_ref(), &amount.to_le_bytes(), &time.to_le_bytes()]
+    ).to_bytes()
+}
+
+// The accounts every tree instruction needs
+pub struct PositionTree<'info> {
+    pub compression_program: AccountInfo<'info>,
+    pub merkle_tree: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub noop_program: AccountInfo<'info>,
+    pub authority_bump: u8,
+}
+
+impl<'info> PositionTree<'info> {
+    pub fn init(&self, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+        let mut data = max_depth.to_le_bytes().to_vec();
+        data.extend_from_slice(&max_buffer_size.to_le_bytes());
+        self.invoke("global:init_empty_merkle_tree", data, &[])
+    }
+
+    pub fn append(&self, leaf: [u8; 32]) -> Result<()> {
+        self.invoke("global:append", leaf.to_vec(), &[])
+    }
+
+    // Swap `previous` for `leaf` at `index`; the compression program rejects the
+    // call unless `previous` is really there, `proof` being its path
+    pub fn replace(
+        &self,
+        root: [u8; 32],
+        previous: [u8; 32],
+        leaf: [u8; 32],
+        index: u32,
+        proof: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        let mut data = root.to_vec();
+        data.extend_from_slice(&previous);
+        data.extend_from_slice(&leaf);
+        data.extend_from_slice(&index.to_le_bytes());
+        self.invoke("global:replace_leaf", data, proof)
+    }
+
+    fn invoke(&self, name: &str, args: Vec<u8>, proof: &[AccountInfo<'info>]) -> Result<()> {
+        let mut data = hash(name.as_bytes()).to_bytes()[..8].to_vec();
+        data.extend(args);
+
+        let mut accounts = vec![
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new_readonly(self.authority.key(), true),
+            AccountMeta::new_readonly(self.noop_program.key(), false)
+        ];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+
+        let mut account_infos = vec![
+            self.merkle_tree.clone(),
+            self.authority.clone(),
+            self.noop_program.clone()
+        ];
+        account_infos.extend(proof.iter().cloned());
+        account_infos.push(self.compression_program.clone());
+
+        invoke_signed(
+            &(Instruction { program_id: ACCOUNT_COMPRESSION_PROGRAM_ID, accounts, data }),
+            &account_infos,
+            &[&[AUCTION_STATE_SEED, &[self.authority_bump]]]
+        )?;
+        Ok(())
+    }
+}
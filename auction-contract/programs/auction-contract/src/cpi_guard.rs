@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{ get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT };
+use anchor_lang::solana_program::sysvar::instructions::get_instruction_relative;
+use crate::errors::ErrorCode;
+
+// Instructions that marketplaces may reach through CPI (place_bid today) call
+// this first. Top-level calls are always allowed; when invoked from another
+// program, the outermost program of the transaction must be on the admin's
+// aggregator whitelist.
+//
+// Callers fund bids from whatever `payer` they pass in (a wallet or a PDA
+// signed with their own seeds), so nothing here assumes the transaction fee
+// payer is involved.
+pub fn assert_cpi_caller_allowed(instructions: &AccountInfo, whitelist: &[Pubkey]) -> Result<()> {
+    if get_stack_height() <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(());
+    }
+
+    let current = get_instruction_relative(0, instructions)?;
+    require!(whitelist.contains(&current.program_id), ErrorCode::CpiCallerNotAllowed);
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+// Events go to the program log with `emit!` by default, where they're lost once a
+// transaction runs past the log limit. Built with the `event-cpi` feature, the
+// contexts that move funds or settle a listing also take Anchor's `#[event_cpi]`
+// accounts, and their events are sent as a self-CPI signed by the event authority
+// instead, so indexers can read them back from the inner instructions.
+
+// The event authority and its bump when the context carries one
+pub type EventAuthority<'info> = Option<(AccountInfo<'info>, u8)>;
+
+#[cfg(feature = "event-cpi")]
+macro_rules! event_authority {
+    ($ctx:expr) => {
+        Some(($ctx.accounts.event_authority.to_account_info(), $ctx.bumps.event_authority))
+    };
+}
+
+#[cfg(not(feature = "event-cpi"))]
+macro_rules! event_authority {
+    ($ctx:expr) => {
+        None
+    };
+}
+
+pub(crate) use event_authority;
+
+pub fn emit<E: Event>(authority: &EventAuthority, event: E) -> Result<()> {
+    match authority {
+        #[cfg(feature = "event-cpi")]
+        Some((authority, bump)) => {
+            use anchor_lang::event::EVENT_IX_TAG_LE;
+            use anchor_lang::solana_program::instruction::Instruction;
+            use anchor_lang::solana_program::program::invoke_signed;
+
+            let data: Vec<u8> = EVENT_IX_TAG_LE.iter().copied().chain(event.data()).collect();
+            let ix = Instruction::new_with_bytes(
+                crate::ID,
+                &data,
+                vec![AccountMeta::new_readonly(authority.key(), true)]
+            );
+            invoke_signed(&ix, &[authority.clone()], &[&[b"__event_authority", &[*bump]]])?;
+        }
+        _ => emit!(event),
+    }
+    Ok(())
+}
@@ -0,0 +1,1737 @@
+use anchor_lang::error::ERROR_CODE_OFFSET;
+use anchor_lang::prelude::*;
+
+// Single error enum for the whole program; instructions and helpers both return these
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Invalid listing ID.")]
+    InvalidListingId,
+    #[msg("Auction has not ended yet.")]
+    AuctionNotEnded,
+    #[msg("The bid must be greater than zero.")]
+    MinimumBidError,
+    #[msg("End time must be in the future.")]
+    EndTimeError,
+    #[msg("The auction cannot move to the requested status.")]
+    InvalidStatusTransition,
+    #[msg("The auction owner cannot bid on their own listing.")]
+    BidderIsOwner,
+    #[msg("The auction is no longer accepting bids.")]
+    AuctionEnded,
+    #[msg("The auction is paused.")]
+    AuctionPaused,
+    #[msg("Withdrawals are not available for alien auctions.")]
+    AlienAuctionError,
+    #[msg("The highest bidder cannot withdraw their bid.")]
+    HighestBidderCannotWithdraw,
+    #[msg("No funds available to withdraw.")]
+    NoFundsToWithdraw,
+    #[msg("The auction has already been ended.")]
+    AuctionAlreadyEnded,
+    #[msg("The auction has no winning bid to settle.")]
+    NothingToWithdraw,
+    #[msg("Minting the NFT for the winner failed.")]
+    MintingFailed,
+    #[msg("Invalid seller address.")]
+    InvalidSellerAddress,
+    #[msg("Invalid payment contract address.")]
+    InvalidPaymentContractAddress,
+    #[msg("The signer is not allowed to perform this action.")]
+    Unauthorized,
+    #[msg("The refund recipient does not match the requested address.")]
+    InvalidRecipient,
+    #[msg("The fee recipient does not match the configured fee recipient.")]
+    InvalidFeeRecipient,
+    #[msg("The inline bidder list is full; pass the auction's newest bid page.")]
+    BidPageRequired,
+    #[msg("The bid page is full; open a new bid page first.")]
+    BidPageFull,
+    #[msg("A new bid page can only be opened once the current one is full.")]
+    BidPageNotNeeded,
+    #[msg("Escrowed funds no longer cover the recorded bid positions.")]
+    FundsNotConserved,
+    #[msg("This bid nonce has already been used.")]
+    NonceAlreadyUsed,
+    #[msg("This bid nonce is older than the replay window.")]
+    NonceTooOld,
+    #[msg("The settlement hook program is not whitelisted.")]
+    HookNotWhitelisted,
+    #[msg("The settlement hook whitelist is full.")]
+    HookWhitelistFull,
+    #[msg("The auction has no pending settlement hook.")]
+    NoPendingHook,
+    #[msg("The calling program is not an approved aggregator.")]
+    CpiCallerNotAllowed,
+    #[msg("The aggregator whitelist is full.")]
+    AggregatorWhitelistFull,
+    #[msg("The VAA account is not a verified Wormhole message.")]
+    InvalidVaa,
+    #[msg("The VAA emitter is not on the bridge whitelist.")]
+    BridgeEmitterNotAllowed,
+    #[msg("The bridge emitter whitelist is full.")]
+    BridgeEmitterWhitelistFull,
+    #[msg("The bridged bid payload is malformed.")]
+    InvalidBridgePayload,
+    #[msg("The external escrow program is not an approved adapter.")]
+    EscrowAdapterNotAllowed,
+    #[msg("The escrow adapter whitelist is full.")]
+    EscrowAdapterWhitelistFull,
+    #[msg("The token account is not held for this seller by the external escrow.")]
+    InvalidExternalEscrow,
+    #[msg("The auction is not listed from an external escrow.")]
+    NotExternalListing,
+    #[msg("The external escrow has already released this NFT.")]
+    ExternalEscrowReleased,
+    #[msg("Protocol parameters are controlled by governance.")]
+    GovernanceControlled,
+    #[msg("The parameter change is still in its waiting period.")]
+    ParamChangeNotReady,
+    #[msg("The parameter change has already been executed.")]
+    ParamChangeExecuted,
+    #[msg("The randomness account is not a valid Switchboard randomness account.")]
+    InvalidRandomnessAccount,
+    #[msg("The randomness request is not from the previous slot.")]
+    RandomnessExpired,
+    #[msg("The randomness value was revealed before it was committed.")]
+    RandomnessAlreadyRevealed,
+    #[msg("The randomness value has not been revealed yet.")]
+    RandomnessNotRevealed,
+    #[msg("Randomness has already been committed for this auction.")]
+    RandomnessAlreadyCommitted,
+    #[msg("No randomness has been committed for this auction.")]
+    RandomnessNotCommitted,
+    #[msg("The randomness request has not timed out yet.")]
+    RandomnessNotStale,
+    #[msg("The price feed account is not owned by a supported oracle.")]
+    UnsupportedOracle,
+    #[msg("The price feed does not match the expected feed.")]
+    OracleFeedMismatch,
+    #[msg("The oracle price is too old.")]
+    OraclePriceStale,
+    #[msg("The oracle price confidence interval is too wide.")]
+    OracleConfidenceTooWide,
+    #[msg("The oracle price is not positive.")]
+    OraclePriceInvalid,
+    #[msg("The payment mint is not a valid token mint.")]
+    InvalidPaymentMint,
+    #[msg("The payment mint has a transfer hook; pass its extra account metas and hook program.")]
+    TransferHookAccountsMissing,
+    #[msg("The merkle tree account is not a valid concurrent merkle tree.")]
+    InvalidMerkleTree,
+    #[msg("Not enough proof accounts for the merkle tree's depth and canopy.")]
+    InvalidProofLength,
+    #[msg("The swap program is not whitelisted.")]
+    SwapProgramNotAllowed,
+    #[msg("The swap program whitelist is full.")]
+    SwapProgramWhitelistFull,
+    #[msg("The swap returned less than the minimum bid amount.")]
+    SwapOutputTooLow,
+    #[msg("This auction keeps bidder positions compressed; use the compressed instructions.")]
+    CompressedPositionsRequired,
+    #[msg("This auction does not keep compressed bidder positions.")]
+    CompressedPositionsDisabled,
+    #[msg("Compressed positions can only be enabled before the first bid.")]
+    CompressedPositionsUnavailable,
+    #[msg("The collection is not enabled in the registry.")]
+    CollectionNotEnabled,
+    #[msg("Fees are expressed in basis points and cannot exceed 10000.")]
+    InvalidFee,
+    #[msg("The payment processor is not whitelisted for this listing.")]
+    PaymentProcessorNotAllowed,
+    #[msg("The payment processor whitelist is full.")]
+    PaymentProcessorWhitelistFull,
+    #[msg("The payment attestation is malformed or does not match the listing.")]
+    InvalidAttestation,
+    #[msg("Bidding has already started on this auction.")]
+    BiddingStarted,
+    #[msg("The metadata URI is too long.")]
+    MetadataUriTooLong,
+    #[msg("The highest bid is below the reserve price.")]
+    ReserveNotMet,
+    #[msg("The batch is empty or larger than the allowed maximum.")]
+    InvalidBatchSize,
+    #[msg("This marketplace requires every listing to set a reserve price.")]
+    ReserveRequired,
+    #[msg("The auction defaults are out of range.")]
+    InvalidAuctionDefaults,
+    #[msg("The extension must be positive and within the allowed maximum.")]
+    InvalidExtension,
+    #[msg("The archive retention period cannot be negative.")]
+    InvalidArchivePolicy,
+    #[msg("Only unsold listings that have ended or been cancelled can be relisted.")]
+    ListingNotRelistable,
+    #[msg("The watchlist is full; remove a listing first.")]
+    WatchlistFull,
+    #[msg("The payment mint has an extension the marketplace does not support.")]
+    UnsupportedMintExtension,
+    #[msg("The mint is not an NFT.")]
+    InvalidNftMint,
+    #[msg("The token account does not hold the NFT.")]
+    NftNotHeld,
+    #[msg("The NFT escrow accounts are missing or do not match the listing.")]
+    NftEscrowMismatch,
+    #[msg("Accounts required to mint the NFT were not provided.")]
+    MissingMintAccounts,
+    #[msg("Metadata account does not belong to the NFT.")]
+    InvalidRoyaltyMetadata,
+    #[msg("Royalty metadata or creator accounts are missing.")]
+    MissingRoyaltyAccounts,
+    #[msg("Royalties and fees exceed the sale price.")]
+    RoyaltiesExceedProceeds,
+    #[msg("Core asset is frozen or has a permanent delegate.")]
+    UnsupportedCoreAsset,
+    #[msg("This listing has no buy-now price.")]
+    BuyNowUnavailable,
+    #[msg("Buy-now price must be at least the minimum bid and reserve.")]
+    InvalidBuyNowPrice,
+    #[msg("Refund accounts do not match the auction's bidders.")]
+    RefundAccountsMismatch,
+    #[msg("Bid does not beat the highest bid by the minimum increment.")]
+    BidTooLow,
+    #[msg("Proxy bids are not supported on compressed, edition or candle auctions.")]
+    ProxyBidUnavailable,
+    #[msg("Auction already has bids.")]
+    AuctionHasBids,
+    #[msg("The NFT of this auction has already been claimed.")]
+    NftAlreadyClaimed,
+    #[msg("The anti-sniping window and extension must not be negative.")]
+    InvalidAntiSnipingConfig,
+    #[msg("Only listings with an NFT in the program's escrow can be bundled.")]
+    BundleUnavailable,
+    #[msg("The listing already holds the most NFTs a bundle can.")]
+    BundleFull,
+    #[msg("An edition auction needs between 2 and 16 winners.")]
+    InvalidEditionCount,
+    #[msg("The bidder is not on this listing's allowlist.")]
+    NotAllowlisted,
+    #[msg("The bidder does not hold this listing's gate token.")]
+    NotTokenHolder,
+    #[msg("The address is blacklisted.")]
+    Blacklisted,
+    #[msg("The seller can only pause a listing before its first bid.")]
+    SellerPauseAfterBids,
+    #[msg("The fee treasury doesn't hold that much.")]
+    InsufficientTreasuryFunds,
+    #[msg("The buyer fee exceeds the maximum of 1000 basis points.")]
+    BuyerFeeTooHigh,
+    #[msg("The seller fee exceeds the maximum of 1000 basis points.")]
+    SellerFeeTooHigh,
+    #[msg("A listing's fee override must be co-signed by the admin or fee manager.")]
+    FeeOverrideNotApproved,
+    #[msg("The referrer account doesn't match the winning bid's referrer.")]
+    InvalidReferrer,
+    #[msg("The referral share can't exceed 10000 basis points.")]
+    InvalidReferralShare,
+    #[msg("Payout splits need at most MAX_PAYOUT_SPLITS distinct recipients whose shares sum to 10000 basis points.")]
+    InvalidPayoutSplits,
+    #[msg("A payout split recipient's account is missing or out of order.")]
+    MissingPayoutAccounts,
+    #[msg("Arithmetic overflow.")]
+    ArithmeticOverflow,
+    #[msg("Bid history capacity must be between 1 and the maximum.")]
+    InvalidBidHistoryCapacity,
+    #[msg("Account layout version is not supported.")]
+    UnsupportedAccountVersion,
+    #[msg("The query window must not be negative.")]
+    InvalidQueryWindow,
+    #[msg("The sold NFT's provenance account is missing.")]
+    MissingProvenanceAccount,
+    #[msg("An offer must expire in the future.")]
+    InvalidOfferExpiry,
+    #[msg("The offer has expired.")]
+    OfferExpired,
+    #[msg("Only the buyer may cancel an offer before it expires.")]
+    OfferNotExpired,
+    #[msg("Crowd auctions need a target of at least the minimum contribution, and no buy-now price, allowlist or token gate.")]
+    InvalidCrowdAuction,
+    #[msg("This auction is not a crowd auction.")]
+    NotCrowdAuction,
+    #[msg("Crowd auctions take contributions, not bids.")]
+    ContributionsOnly,
+    #[msg("The crowd auction met its target, so contributions are not refunded.")]
+    CrowdTargetMet,
+    #[msg("The NFT of a crowd auction stays in escrow for its share holders.")]
+    CrowdNftLocked,
+    #[msg("Raffles need a ticket price, and no reserve or buy-now price.")]
+    InvalidRaffle,
+    #[msg("This listing is not a raffle.")]
+    NotRaffle,
+    #[msg("Raffles sell tickets, not bids.")]
+    TicketsOnly,
+    #[msg("Raffles end when their winner is drawn.")]
+    RaffleNotDrawn,
+    #[msg("Raffle tickets are only refunded when the draw is cancelled.")]
+    RaffleTicketsLocked,
+    #[msg("No tickets were sold for this raffle.")]
+    NoTicketsSold,
+    #[msg("The candle window must fit within the auction, and a candle auction has no buy-now price.")]
+    InvalidCandleWindow,
+    #[msg("The candle window has recorded as many changes of lead as it can.")]
+    CandleMarksFull,
+    #[msg("Candle auctions close with their drawn cutoff.")]
+    CandleNotClosed,
+    #[msg("This auction is not a candle auction.")]
+    NotCandleAuction,
+    #[msg("Rental term must be between 1 and 365 days.")]
+    InvalidRentalTerm,
+    #[msg("This auction doesn't rent out its NFT.")]
+    NotRental,
+    #[msg("A rented NFT must be claimed with claim_rental.")]
+    RentalClaimRequired,
+    #[msg("The rental term hasn't run out yet.")]
+    RentalNotExpired,
+    #[msg("No rental of this NFT is running.")]
+    RentalNotActive,
+    #[msg("Listings can only be paid in SOL or an SPL token the marketplace collects fees in.")]
+    InvalidPaymentMint,
+    #[msg("The accounts given don't match the listing's payment currency.")]
+    WrongPaymentMint,
+    #[msg("USD pricing needs a minimum bid and a SOL-denominated listing.")]
+    InvalidUsdPricing,
+    #[msg("Listing's minimum bid and reserve are set in USD.")]
+    UsdPricedListing,
+    #[msg("Listing name exceeds the maximum length.")]
+    ListingNameTooLong,
+    #[msg("Listing names may only use printable ASCII characters.")]
+    InvalidListingName,
+    #[msg("The listing ID is already in use.")]
+    ListingIdTaken,
+    #[msg("The seller already has the most open listings allowed.")]
+    TooManyActiveAuctions,
+    #[msg("Listing requires a seller profile while a listing cap is set.")]
+    SellerProfileRequired,
+    #[msg("The amount exceeds what can be withdrawn from the bid.")]
+    WithdrawalTooLarge,
+    #[msg("Dispute window must not be negative.")]
+    InvalidDisputeWindow,
+    #[msg("Sale is held in escrow for its dispute window.")]
+    DisputeWindowOpen,
+    #[msg("Sale cannot be disputed.")]
+    DisputeNotAllowed,
+    #[msg("Highest bid cannot be accepted early on this listing.")]
+    EarlyAcceptanceUnavailable,
+    #[msg("New end time exceeds the maximum auction duration.")]
+    AuctionTooLong,
+    #[msg("Config change delay is negative or shorter than the current one.")]
+    InvalidConfigChangeDelay,
+    #[msg("Config changes must be queued while a delay is set.")]
+    ConfigChangeTimelocked,
+    #[msg("Loyalty schedule ends before it starts.")]
+    InvalidLoyaltySchedule,
+    #[msg("Listing requires a bid bond.")]
+    BidBondRequired,
+    #[msg("Bid bond can't be released yet.")]
+    BidBondLocked,
+    #[msg("Seller collateral needs an amount and a delivery window.")]
+    InvalidSellerCollateral,
+    #[msg("This listing holds no seller collateral.")]
+    NoSellerCollateral,
+    #[msg("The seller has not confirmed delivery yet.")]
+    DeliveryNotConfirmed,
+    #[msg("The delivery deadline has passed.")]
+    DeliveryOverdue,
+    #[msg("This feature is not enabled on this deployment.")]
+    FeatureDisabled,
+    #[msg("Unknown feature flag bits.")]
+    InvalidFeatureFlags,
+    #[msg("The signed order must follow an Ed25519 verify instruction.")]
+    OrderSignatureMissing,
+    #[msg("The Ed25519 instruction doesn't verify this order for its bidder.")]
+    InvalidOrderSignature,
+    #[msg("The signed order has expired.")]
+    OrderExpired,
+    #[msg("The bidder profile doesn't hold enough lamports.")]
+    InsufficientProfileBalance,
+    #[msg("This cross-chain sale hasn't been attested over Wormhole.")]
+    AttestationRequired,
+    #[msg("This sale has already been attested.")]
+    AlreadyAttested,
+    #[msg("The VAA payload isn't a settlement attestation for this listing.")]
+    InvalidAttestationPayload,
+    #[msg("The NFT is not in a verified, approved collection.")]
+    CollectionNotApproved,
+    #[msg("The NFT metadata account is not the Token Metadata account of the NFT.")]
+    InvalidNftMetadata,
+    #[msg("The NFT is not a verified member of the collection.")]
+    CollectionNotVerified,
+    #[msg("Participation receipts are not enabled.")]
+    ReceiptTreeNotSet,
+    #[msg("A participation receipt was already minted for this bidder.")]
+    ReceiptAlreadyMinted,
+    #[msg("Bidder never placed a bid on this auction.")]
+    NoBidToReceipt,
+    #[msg("Winner cannot be declared in default.")]
+    DefaultNotAllowed,
+    #[msg("Winner's claim window is still open.")]
+    ClaimWindowOpen,
+    #[msg("Invalid installment plan.")]
+    InvalidInstallmentPlan,
+    #[msg("No installment is due.")]
+    NoInstallmentDue,
+    #[msg("Installment is not overdue.")]
+    InstallmentNotOverdue,
+    #[msg("Installments are still outstanding.")]
+    InstallmentsOutstanding,
+    #[msg("Listing takes delegated bids only.")]
+    DelegatedBidsOnly,
+    #[msg("Delegated bidding is not available on this listing.")]
+    DelegatedBiddingUnavailable,
+    #[msg("Token account does not approve the vault for the bid.")]
+    DelegateApprovalMissing,
+    #[msg("Winning delegated bid is not yet collected.")]
+    DelegatedBidUncollected,
+    #[msg("Bidder has placed the most bids this listing allows.")]
+    BidLimitReached,
+    #[msg("Bidder bid too recently.")]
+    BidCooldownActive,
+    #[msg("Listing limits bids per bidder, which needs a bid receipt.")]
+    BidLimitsApply,
+    #[msg("Bid cooldown cannot be negative.")]
+    InvalidBidLimits,
+    #[msg("No bid found for this bidder.")]
+    BidNotFound,
+    #[msg("Collection stats account missing or mismatched.")]
+    CollectionStatsMissing,
+    #[msg("Collection has no recorded sales.")]
+    NoCollectionSales,
+    #[msg("A co-seller has not approved the listing.")]
+    CoSellerNotApproved,
+    #[msg("Invalid proceeds vesting.")]
+    InvalidVesting,
+    #[msg("Invalid buyer fee tiers.")]
+    InvalidFeeTiers,
+    #[msg("Invalid membership discount.")]
+    InvalidMembershipDiscount,
+    #[msg("The membership NFT is not verified in the discount's collection.")]
+    NotAMember,
+    #[msg("Invalid beneficiary.")]
+    InvalidBeneficiary,
+    #[msg("The config has been frozen.")]
+    ConfigFrozen,
+    #[msg("Auction registry page is full.")]
+    RegistryPageFull,
+    #[msg("Registry page is out of order or doesn't list the auction.")]
+    InvalidRegistryPage,
+    #[msg("This settlement step has already run.")]
+    SettlementStepDone,
+    #[msg("Retraction penalty exceeds the fee cap.")]
+    InvalidRetractionPenalty,
+    #[msg("Bids on this listing can't be retracted.")]
+    RetractionNotAllowed,
+    #[msg("Leaving a live auction takes retract_bid, which charges the retraction penalty.")]
+    RetractionRequired,
+    #[msg("The bid receipt isn't the bidder's for this listing.")]
+    BidReceiptMismatch,
+    #[msg("The blacklist entry isn't the bidder's.")]
+    BlacklistEntryMismatch,
+    #[msg("The seller is on vacation.")]
+    SellerOnVacation,
+    #[msg("Every open listing of the seller must be passed.")]
+    IncompleteVacationSet,
+    #[msg("The sale has more creators than a settlement quote holds.")]
+    TooManyCreators,
+}
+
+impl ErrorCode {
+    // Every variant in declaration order, so an on-chain code maps back to its variant
+    pub const ALL: [ErrorCode; 213] = [
+        ErrorCode::InvalidListingId,
+        ErrorCode::AuctionNotEnded,
+        ErrorCode::MinimumBidError,
+        ErrorCode::EndTimeError,
+        ErrorCode::InvalidStatusTransition,
+        ErrorCode::BidderIsOwner,
+        ErrorCode::AuctionEnded,
+        ErrorCode::AuctionPaused,
+        ErrorCode::AlienAuctionError,
+        ErrorCode::HighestBidderCannotWithdraw,
+        ErrorCode::NoFundsToWithdraw,
+        ErrorCode::AuctionAlreadyEnded,
+        ErrorCode::NothingToWithdraw,
+        ErrorCode::MintingFailed,
+        ErrorCode::InvalidSellerAddress,
+        ErrorCode::InvalidPaymentContractAddress,
+        ErrorCode::Unauthorized,
+        ErrorCode::InvalidRecipient,
+        ErrorCode::InvalidFeeRecipient,
+        ErrorCode::BidPageRequired,
+        ErrorCode::BidPageFull,
+        ErrorCode::BidPageNotNeeded,
+        ErrorCode::FundsNotConserved,
+        ErrorCode::NonceAlreadyUsed,
+        ErrorCode::NonceTooOld,
+        ErrorCode::HookNotWhitelisted,
+        ErrorCode::HookWhitelistFull,
+        ErrorCode::NoPendingHook,
+        ErrorCode::CpiCallerNotAllowed,
+        ErrorCode::AggregatorWhitelistFull,
+        ErrorCode::InvalidVaa,
+        ErrorCode::BridgeEmitterNotAllowed,
+        ErrorCode::BridgeEmitterWhitelistFull,
+        ErrorCode::InvalidBridgePayload,
+        ErrorCode::EscrowAdapterNotAllowed,
+        ErrorCode::EscrowAdapterWhitelistFull,
+        ErrorCode::InvalidExternalEscrow,
+        ErrorCode::NotExternalListing,
+        ErrorCode::ExternalEscrowReleased,
+        ErrorCode::GovernanceControlled,
+        ErrorCode::ParamChangeNotReady,
+        ErrorCode::ParamChangeExecuted,
+        ErrorCode::InvalidRandomnessAccount,
+        ErrorCode::RandomnessExpired,
+        ErrorCode::RandomnessAlreadyRevealed,
+        ErrorCode::RandomnessNotRevealed,
+        ErrorCode::RandomnessAlreadyCommitted,
+        ErrorCode::RandomnessNotCommitted,
+        ErrorCode::RandomnessNotStale,
+        ErrorCode::UnsupportedOracle,
+        ErrorCode::OracleFeedMismatch,
+        ErrorCode::OraclePriceStale,
+        ErrorCode::OracleConfidenceTooWide,
+        ErrorCode::OraclePriceInvalid,
+        ErrorCode::InvalidPaymentMint,
+        ErrorCode::TransferHookAccountsMissing,
+        ErrorCode::InvalidMerkleTree,
+        ErrorCode::InvalidProofLength,
+        ErrorCode::SwapProgramNotAllowed,
+        ErrorCode::SwapProgramWhitelistFull,
+        ErrorCode::SwapOutputTooLow,
+        ErrorCode::CompressedPositionsRequired,
+        ErrorCode::CompressedPositionsDisabled,
+        ErrorCode::CompressedPositionsUnavailable,
+        ErrorCode::CollectionNotEnabled,
+        ErrorCode::InvalidFee,
+        ErrorCode::PaymentProcessorNotAllowed,
+        ErrorCode::PaymentProcessorWhitelistFull,
+        ErrorCode::InvalidAttestation,
+        ErrorCode::BiddingStarted,
+        ErrorCode::MetadataUriTooLong,
+        ErrorCode::ReserveNotMet,
+        ErrorCode::InvalidBatchSize,
+        ErrorCode::ReserveRequired,
+        ErrorCode::InvalidAuctionDefaults,
+        ErrorCode::InvalidExtension,
+        ErrorCode::InvalidArchivePolicy,
+        ErrorCode::ListingNotRelistable,
+        ErrorCode::WatchlistFull,
+        ErrorCode::UnsupportedMintExtension,
+        ErrorCode::InvalidNftMint,
+        ErrorCode::NftNotHeld,
+        ErrorCode::NftEscrowMismatch,
+        ErrorCode::MissingMintAccounts,
+        ErrorCode::InvalidRoyaltyMetadata,
+        ErrorCode::MissingRoyaltyAccounts,
+        ErrorCode::RoyaltiesExceedProceeds,
+        ErrorCode::UnsupportedCoreAsset,
+        ErrorCode::BuyNowUnavailable,
+        ErrorCode::InvalidBuyNowPrice,
+        ErrorCode::RefundAccountsMismatch,
+        ErrorCode::BidTooLow,
+        ErrorCode::ProxyBidUnavailable,
+        ErrorCode::AuctionHasBids,
+        ErrorCode::NftAlreadyClaimed,
+        ErrorCode::InvalidAntiSnipingConfig,
+        ErrorCode::BundleUnavailable,
+        ErrorCode::BundleFull,
+        ErrorCode::InvalidEditionCount,
+        ErrorCode::NotAllowlisted,
+        ErrorCode::NotTokenHolder,
+        ErrorCode::Blacklisted,
+        ErrorCode::SellerPauseAfterBids,
+        ErrorCode::InsufficientTreasuryFunds,
+        ErrorCode::BuyerFeeTooHigh,
+        ErrorCode::SellerFeeTooHigh,
+        ErrorCode::FeeOverrideNotApproved,
+        ErrorCode::InvalidReferrer,
+        ErrorCode::InvalidReferralShare,
+        ErrorCode::InvalidPayoutSplits,
+        ErrorCode::MissingPayoutAccounts,
+        ErrorCode::ArithmeticOverflow,
+        ErrorCode::InvalidBidHistoryCapacity,
+        ErrorCode::UnsupportedAccountVersion,
+        ErrorCode::InvalidQueryWindow,
+        ErrorCode::MissingProvenanceAccount,
+        ErrorCode::InvalidOfferExpiry,
+        ErrorCode::OfferExpired,
+        ErrorCode::OfferNotExpired,
+        ErrorCode::InvalidCrowdAuction,
+        ErrorCode::NotCrowdAuction,
+        ErrorCode::ContributionsOnly,
+        ErrorCode::CrowdTargetMet,
+        ErrorCode::CrowdNftLocked,
+        ErrorCode::InvalidRaffle,
+        ErrorCode::NotRaffle,
+        ErrorCode::TicketsOnly,
+        ErrorCode::RaffleNotDrawn,
+        ErrorCode::RaffleTicketsLocked,
+        ErrorCode::NoTicketsSold,
+        ErrorCode::InvalidCandleWindow,
+        ErrorCode::CandleMarksFull,
+        ErrorCode::CandleNotClosed,
+        ErrorCode::NotCandleAuction,
+        ErrorCode::InvalidRentalTerm,
+        ErrorCode::NotRental,
+        ErrorCode::RentalClaimRequired,
+        ErrorCode::RentalNotExpired,
+        ErrorCode::RentalNotActive,
+        ErrorCode::InvalidPaymentMint,
+        ErrorCode::WrongPaymentMint,
+        ErrorCode::InvalidUsdPricing,
+        ErrorCode::UsdPricedListing,
+        ErrorCode::ListingNameTooLong,
+        ErrorCode::InvalidListingName,
+        ErrorCode::ListingIdTaken,
+        ErrorCode::TooManyActiveAuctions,
+        ErrorCode::SellerProfileRequired,
+        ErrorCode::WithdrawalTooLarge,
+        ErrorCode::InvalidDisputeWindow,
+        ErrorCode::DisputeWindowOpen,
+        ErrorCode::DisputeNotAllowed,
+        ErrorCode::EarlyAcceptanceUnavailable,
+        ErrorCode::AuctionTooLong,
+        ErrorCode::InvalidConfigChangeDelay,
+        ErrorCode::ConfigChangeTimelocked,
+        ErrorCode::InvalidLoyaltySchedule,
+        ErrorCode::BidBondRequired,
+        ErrorCode::BidBondLocked,
+        ErrorCode::InvalidSellerCollateral,
+        ErrorCode::NoSellerCollateral,
+        ErrorCode::DeliveryNotConfirmed,
+        ErrorCode::DeliveryOverdue,
+        ErrorCode::FeatureDisabled,
+        ErrorCode::InvalidFeatureFlags,
+        ErrorCode::OrderSignatureMissing,
+        ErrorCode::InvalidOrderSignature,
+        ErrorCode::OrderExpired,
+        ErrorCode::InsufficientProfileBalance,
+        ErrorCode::AttestationRequired,
+        ErrorCode::AlreadyAttested,
+        ErrorCode::InvalidAttestationPayload,
+        ErrorCode::CollectionNotApproved,
+        ErrorCode::InvalidNftMetadata,
+        ErrorCode::CollectionNotVerified,
+        ErrorCode::ReceiptTreeNotSet,
+        ErrorCode::ReceiptAlreadyMinted,
+        ErrorCode::NoBidToReceipt,
+        ErrorCode::DefaultNotAllowed,
+        ErrorCode::ClaimWindowOpen,
+        ErrorCode::InvalidInstallmentPlan,
+        ErrorCode::NoInstallmentDue,
+        ErrorCode::InstallmentNotOverdue,
+        ErrorCode::InstallmentsOutstanding,
+        ErrorCode::DelegatedBidsOnly,
+        ErrorCode::DelegatedBiddingUnavailable,
+        ErrorCode::DelegateApprovalMissing,
+        ErrorCode::DelegatedBidUncollected,
+        ErrorCode::BidLimitReached,
+        ErrorCode::BidCooldownActive,
+        ErrorCode::BidLimitsApply,
+        ErrorCode::InvalidBidLimits,
+        ErrorCode::BidNotFound,
+        ErrorCode::CollectionStatsMissing,
+        ErrorCode::NoCollectionSales,
+        ErrorCode::CoSellerNotApproved,
+        ErrorCode::InvalidVesting,
+        ErrorCode::InvalidFeeTiers,
+        ErrorCode::InvalidMembershipDiscount,
+        ErrorCode::NotAMember,
+        ErrorCode::InvalidBeneficiary,
+        ErrorCode::ConfigFrozen,
+        ErrorCode::RegistryPageFull,
+        ErrorCode::InvalidRegistryPage,
+        ErrorCode::SettlementStepDone,
+        ErrorCode::InvalidRetractionPenalty,
+        ErrorCode::RetractionNotAllowed,
+        ErrorCode::RetractionRequired,
+        ErrorCode::BidReceiptMismatch,
+        ErrorCode::BlacklistEntryMismatch,
+        ErrorCode::SellerOnVacation,
+        ErrorCode::IncompleteVacationSet,
+        ErrorCode::TooManyCreators,
+    ];
+
+    pub fn from_code(code: u32) -> Option<ErrorCode> {
+        code.checked_sub(ERROR_CODE_OFFSET).and_then(|index| Self::ALL.get(index as usize).copied())
+    }
+
+    // User-facing message and suggested next step
+    pub fn explanation(self) -> (&'static str, &'static str) {
+        match self {
+            ErrorCode::InvalidListingId =>
+                (
+                    "This listing doesn't exist.",
+                    "Check the listing link or ID and try again.",
+                ),
+            ErrorCode::AuctionNotEnded =>
+                (
+                    "This auction is still running.",
+                    "Wait until the end time has passed, then try again.",
+                ),
+            ErrorCode::MinimumBidError =>
+                (
+                    "Your bid must be more than zero.",
+                    "Enter a bid amount above zero.",
+                ),
+            ErrorCode::EndTimeError =>
+                (
+                    "The end time has to be in the future.",
+                    "Pick an end time later than now.",
+                ),
+            ErrorCode::InvalidStatusTransition =>
+                (
+                    "The auction can't do that in its current state.",
+                    "Refresh the listing to see its latest status.",
+                ),
+            ErrorCode::BidderIsOwner =>
+                (
+                    "You can't bid on your own listing.",
+                    "Bid from a different wallet than the one that listed the item.",
+                ),
+            ErrorCode::AuctionEnded =>
+                (
+                    "This auction is no longer taking bids.",
+                    "Look for a relisting or browse other auctions.",
+                ),
+            ErrorCode::AuctionPaused =>
+                (
+                    "Bidding on this auction is paused.",
+                    "Try again once the auction is resumed.",
+                ),
+            ErrorCode::AlienAuctionError =>
+                (
+                    "Bids on this auction can't be withdrawn here.",
+                    "Contact the seller or marketplace for a refund.",
+                ),
+            ErrorCode::HighestBidderCannotWithdraw =>
+                (
+                    "You're the highest bidder, so your bid stays locked.",
+                    "Wait until you're outbid or the auction settles.",
+                ),
+            ErrorCode::NoFundsToWithdraw =>
+                (
+                    "You have nothing to withdraw from this auction.",
+                    "Check your other auctions for refundable bids.",
+                ),
+            ErrorCode::AuctionAlreadyEnded =>
+                (
+                    "This auction has already been closed.",
+                    "Refresh the listing to see the final result.",
+                ),
+            ErrorCode::NothingToWithdraw =>
+                (
+                    "This auction has no winning bid to settle.",
+                    "Cancel or relist the item instead.",
+                ),
+            ErrorCode::MintingFailed =>
+                (
+                    "The NFT couldn't be minted for the winner.",
+                    "Try settling again; contact support if it keeps failing.",
+                ),
+            ErrorCode::InvalidSellerAddress =>
+                (
+                    "The seller address is invalid.",
+                    "Double-check the seller wallet and try again.",
+                ),
+            ErrorCode::InvalidPaymentContractAddress =>
+                (
+                    "The payment contract address is invalid.",
+                    "Double-check the payment address and try again.",
+                ),
+            ErrorCode::Unauthorized =>
+                (
+                    "Your wallet isn't allowed to do this.",
+                    "Connect the wallet that owns the listing or holds the required role.",
+                ),
+            ErrorCode::InvalidRecipient =>
+                (
+                    "The refund recipient doesn't match your request.",
+                    "Send the refund to the wallet that placed the bid.",
+                ),
+            ErrorCode::InvalidFeeRecipient =>
+                (
+                    "The fee recipient is not the marketplace's fee account.",
+                    "Refresh your app; the marketplace fee account may have changed.",
+                ),
+            ErrorCode::BidPageRequired =>
+                (
+                    "This auction needs an extra bid page for new bidders.",
+                    "Retry; your app should include the newest bid page.",
+                ),
+            ErrorCode::BidPageFull =>
+                (
+                    "The current bid page is full.",
+                    "Retry after a new bid page has been opened.",
+                ),
+            ErrorCode::BidPageNotNeeded =>
+                (
+                    "A new bid page isn't needed yet.",
+                    "Bid using the auction's current bid page.",
+                ),
+            ErrorCode::FundsNotConserved =>
+                (
+                    "The escrow balance doesn't cover recorded bids.",
+                    "Stop and contact support; no funds were moved.",
+                ),
+            ErrorCode::NonceAlreadyUsed =>
+                (
+                    "This bid was already submitted.",
+                    "Sign a fresh bid instead of resending the old one.",
+                ),
+            ErrorCode::NonceTooOld =>
+                (
+                    "This signed bid has expired.",
+                    "Sign a fresh bid and submit it again.",
+                ),
+            ErrorCode::HookNotWhitelisted =>
+                (
+                    "The settlement hook program isn't approved.",
+                    "Settle without a hook or pick an approved one.",
+                ),
+            ErrorCode::HookWhitelistFull =>
+                (
+                    "No more settlement hooks can be approved.",
+                    "Remove an unused hook program first.",
+                ),
+            ErrorCode::NoPendingHook =>
+                (
+                    "There's no settlement hook waiting to run.",
+                    "Nothing to do; the auction is fully settled.",
+                ),
+            ErrorCode::CpiCallerNotAllowed =>
+                (
+                    "This app isn't approved to place bids for you.",
+                    "Bid directly on the marketplace or through an approved aggregator.",
+                ),
+            ErrorCode::AggregatorWhitelistFull =>
+                (
+                    "No more aggregators can be approved.",
+                    "Remove an unused aggregator first.",
+                ),
+            ErrorCode::InvalidVaa =>
+                (
+                    "The cross-chain message couldn't be verified.",
+                    "Wait for the message to be posted by the guardians, then retry.",
+                ),
+            ErrorCode::BridgeEmitterNotAllowed =>
+                (
+                    "Bids from this chain or contract aren't accepted.",
+                    "Bid from a supported chain.",
+                ),
+            ErrorCode::BridgeEmitterWhitelistFull =>
+                (
+                    "No more bridge emitters can be approved.",
+                    "Remove an unused bridge emitter first.",
+                ),
+            ErrorCode::InvalidBridgePayload =>
+                (
+                    "The cross-chain bid couldn't be read.",
+                    "Resubmit the bid from the source chain.",
+                ),
+            ErrorCode::EscrowAdapterNotAllowed =>
+                (
+                    "This escrow program isn't supported.",
+                    "List from a supported escrow or hold the NFT in your wallet.",
+                ),
+            ErrorCode::EscrowAdapterWhitelistFull =>
+                (
+                    "No more escrow adapters can be approved.",
+                    "Remove an unused escrow adapter first.",
+                ),
+            ErrorCode::InvalidExternalEscrow =>
+                (
+                    "The escrowed NFT isn't held for your wallet.",
+                    "Check that the NFT is escrowed under the listing wallet.",
+                ),
+            ErrorCode::NotExternalListing =>
+                (
+                    "This listing isn't held in an external escrow.",
+                    "Use the regular settlement flow.",
+                ),
+            ErrorCode::ExternalEscrowReleased =>
+                (
+                    "The NFT has already been released from escrow.",
+                    "Nothing to do; check the winner's wallet.",
+                ),
+            ErrorCode::GovernanceControlled =>
+                (
+                    "This setting is managed by governance.",
+                    "Submit a governance proposal instead.",
+                ),
+            ErrorCode::ParamChangeNotReady =>
+                (
+                    "This change is still in its waiting period.",
+                    "Execute it once the waiting period has passed.",
+                ),
+            ErrorCode::ParamChangeExecuted =>
+                (
+                    "This change has already been applied.",
+                    "Nothing to do.",
+                ),
+            ErrorCode::InvalidRandomnessAccount =>
+                (
+                    "The randomness account isn't valid.",
+                    "Create a new Switchboard randomness account and retry.",
+                ),
+            ErrorCode::RandomnessExpired =>
+                (
+                    "The randomness request is out of date.",
+                    "Request fresh randomness and retry.",
+                ),
+            ErrorCode::RandomnessAlreadyRevealed =>
+                (
+                    "The randomness was revealed too early.",
+                    "Request fresh randomness and commit before revealing.",
+                ),
+            ErrorCode::RandomnessNotRevealed =>
+                (
+                    "The random value isn't available yet.",
+                    "Wait a few seconds for the reveal, then retry.",
+                ),
+            ErrorCode::RandomnessAlreadyCommitted =>
+                (
+                    "Randomness is already requested for this auction.",
+                    "Wait for it to be revealed.",
+                ),
+            ErrorCode::RandomnessNotCommitted =>
+                (
+                    "No randomness has been requested for this auction.",
+                    "Commit randomness first.",
+                ),
+            ErrorCode::RandomnessNotStale =>
+                (
+                    "The randomness request is still pending.",
+                    "Wait for the reveal or until the request times out.",
+                ),
+            ErrorCode::UnsupportedOracle =>
+                (
+                    "The price feed isn't from a supported oracle.",
+                    "Use a Pyth or Switchboard price feed.",
+                ),
+            ErrorCode::OracleFeedMismatch =>
+                (
+                    "The price feed doesn't match this listing.",
+                    "Refresh your app and retry with the listing's price feed.",
+                ),
+            ErrorCode::OraclePriceStale =>
+                (
+                    "The price quote is out of date.",
+                    "Wait for a fresh price update, then retry.",
+                ),
+            ErrorCode::OracleConfidenceTooWide =>
+                (
+                    "The market price is too uncertain right now.",
+                    "Try again when the market is calmer.",
+                ),
+            ErrorCode::OraclePriceInvalid =>
+                (
+                    "The oracle reported an invalid price.",
+                    "Try again later.",
+                ),
+            ErrorCode::InvalidPaymentMint =>
+                (
+                    "This token can't be used for payment.",
+                    "Pay with the token the listing accepts.",
+                ),
+            ErrorCode::TransferHookAccountsMissing =>
+                (
+                    "This token needs extra accounts to transfer.",
+                    "Update your app so it includes the token's transfer hook accounts.",
+                ),
+            ErrorCode::InvalidMerkleTree =>
+                (
+                    "The compressed NFT tree is invalid.",
+                    "Refresh the asset data and retry.",
+                ),
+            ErrorCode::InvalidProofLength =>
+                (
+                    "The compressed NFT proof is incomplete.",
+                    "Fetch a fresh proof and retry.",
+                ),
+            ErrorCode::SwapProgramNotAllowed =>
+                (
+                    "This swap route isn't supported.",
+                    "Pick a different route or bid in SOL.",
+                ),
+            ErrorCode::SwapProgramWhitelistFull =>
+                (
+                    "No more swap programs can be approved.",
+                    "Remove an unused swap program first.",
+                ),
+            ErrorCode::SwapOutputTooLow =>
+                (
+                    "The swap returned less than your minimum bid.",
+                    "Raise your slippage tolerance or bid in SOL.",
+                ),
+            ErrorCode::CompressedPositionsRequired =>
+                (
+                    "This auction uses compressed bids.",
+                    "Update your app to the latest version and retry.",
+                ),
+            ErrorCode::CompressedPositionsDisabled =>
+                (
+                    "This auction doesn't use compressed bids.",
+                    "Bid with the regular bid flow.",
+                ),
+            ErrorCode::CompressedPositionsUnavailable =>
+                (
+                    "Compressed bids can only be turned on before the first bid.",
+                    "Relist the item to use compressed bids.",
+                ),
+            ErrorCode::CollectionNotEnabled =>
+                (
+                    "This collection isn't enabled on the marketplace.",
+                    "Ask the marketplace to enable the collection.",
+                ),
+            ErrorCode::InvalidFee =>
+                (
+                    "Fees can't be more than 100%.",
+                    "Enter a fee of at most 10000 basis points.",
+                ),
+            ErrorCode::PaymentProcessorNotAllowed =>
+                (
+                    "This payment provider isn't accepted for this listing.",
+                    "Pay with a provider the listing accepts.",
+                ),
+            ErrorCode::PaymentProcessorWhitelistFull =>
+                (
+                    "No more payment providers can be approved.",
+                    "Remove an unused payment provider first.",
+                ),
+            ErrorCode::InvalidAttestation =>
+                (
+                    "The payment confirmation doesn't match this listing.",
+                    "Contact the payment provider to resend the confirmation.",
+                ),
+            ErrorCode::BiddingStarted =>
+                (
+                    "Bidding has already started, so this can't be changed.",
+                    "Only make changes before the first bid.",
+                ),
+            ErrorCode::MetadataUriTooLong =>
+                (
+                    "The metadata link is too long.",
+                    "Use a shorter URI.",
+                ),
+            ErrorCode::ReserveNotMet =>
+                (
+                    "The highest bid didn't reach the seller's reserve price.",
+                    "Relist the item or lower the reserve price.",
+                ),
+            ErrorCode::InvalidBatchSize =>
+                (
+                    "Too many or too few items in this batch.",
+                    "Split the batch into smaller groups.",
+                ),
+            ErrorCode::ReserveRequired =>
+                (
+                    "This marketplace requires a reserve price.",
+                    "Set a reserve price on the listing.",
+                ),
+            ErrorCode::InvalidAuctionDefaults =>
+                (
+                    "Those auction defaults are out of range.",
+                    "Pick values within the allowed limits.",
+                ),
+            ErrorCode::InvalidExtension =>
+                (
+                    "The extension is too long or not positive.",
+                    "Pick a shorter extension.",
+                ),
+            ErrorCode::InvalidArchivePolicy =>
+                (
+                    "The archive retention period can't be negative.",
+                    "Enter a retention period of zero or more.",
+                ),
+            ErrorCode::ListingNotRelistable =>
+                (
+                    "This listing can't be relisted.",
+                    "Relist only items that ended unsold or were cancelled.",
+                ),
+            ErrorCode::WatchlistFull =>
+                (
+                    "Your watchlist is full.",
+                    "Remove a listing you no longer follow, then try again.",
+                ),
+            ErrorCode::UnsupportedMintExtension =>
+                (
+                    "This token can't be used on the marketplace.",
+                    "Pay with a different token.",
+                ),
+            ErrorCode::InvalidNftMint =>
+                (
+                    "This token isn't an NFT.",
+                    "List an NFT with a supply of one and no decimals.",
+                ),
+            ErrorCode::NftNotHeld =>
+                (
+                    "Your wallet doesn't hold this NFT.",
+                    "Check that the NFT is still in your wallet.",
+                ),
+            ErrorCode::NftEscrowMismatch =>
+                (
+                    "The listing's NFT accounts are missing or wrong.",
+                    "Update your app so it passes the listing's escrowed NFT accounts.",
+                ),
+            ErrorCode::MissingMintAccounts =>
+                (
+                    "The accounts needed to mint this NFT are missing.",
+                    "Update your app so it passes the accounts for minting the winner's NFT.",
+                ),
+            ErrorCode::InvalidRoyaltyMetadata =>
+                (
+                    "The NFT metadata passed for royalties doesn't match this listing's NFT.",
+                    "Update your app so it passes the metadata account of the listing's NFT.",
+                ),
+            ErrorCode::MissingRoyaltyAccounts =>
+                (
+                    "The accounts needed to pay the NFT's creators are missing.",
+                    "Update your app so it passes the NFT metadata and every creator's account.",
+                ),
+            ErrorCode::RoyaltiesExceedProceeds =>
+                (
+                    "The NFT's royalties plus marketplace fees add up to more than the winning bid.",
+                    "Contact support; this listing can't settle with its current royalties.",
+                ),
+            ErrorCode::UnsupportedCoreAsset =>
+                (
+                    "This Core asset is frozen or can be moved by someone other than its owner, so it can't be listed.",
+                    "Thaw the asset or list an NFT without permanent freeze, transfer or burn delegates.",
+                ),
+            ErrorCode::BuyNowUnavailable =>
+                (
+                    "This listing can't be bought outright.",
+                    "Place a bid instead.",
+                ),
+            ErrorCode::InvalidBuyNowPrice =>
+                (
+                    "The buy-now price is below the listing's minimum bid or reserve.",
+                    "Choose a buy-now price at or above the minimum bid and reserve.",
+                ),
+            ErrorCode::RefundAccountsMismatch =>
+                (
+                    "The accounts for refunding the other bidders are missing or out of order.",
+                    "Update your app so it passes every other bidder's wallet, in the auction's bid order.",
+                ),
+            ErrorCode::BidTooLow =>
+                (
+                    "Your bid is too low to beat the current highest bid.",
+                    "Raise your bid to at least the highest bid plus the listing's minimum increment.",
+                ),
+            ErrorCode::ProxyBidUnavailable =>
+                (
+                    "Proxy bidding isn't available on this listing.",
+                    "Place a regular bid instead.",
+                ),
+            ErrorCode::AuctionHasBids =>
+                (
+                    "This listing already has bids, so it can't be cancelled.",
+                    "Wait for the auction to end and settle it instead.",
+                ),
+            ErrorCode::NftAlreadyClaimed =>
+                (
+                    "This NFT has already been delivered.",
+                    "Check the recipient's wallet for the NFT.",
+                ),
+            ErrorCode::InvalidAntiSnipingConfig =>
+                (
+                    "This auction's anti-sniping settings are invalid.",
+                    "Update your app so it passes a non-negative sniping window and time extension.",
+                ),
+            ErrorCode::BundleUnavailable =>
+                (
+                    "This listing can't hold a bundle.",
+                    "Update your app so it only bundles NFTs with listings escrowed by the marketplace.",
+                ),
+            ErrorCode::BundleFull =>
+                (
+                    "This bundle is full.",
+                    "List the remaining NFTs separately.",
+                ),
+            ErrorCode::InvalidEditionCount =>
+                (
+                    "This edition auction has an invalid number of editions.",
+                    "Update your app so it passes an edition count between 2 and 16.",
+                ),
+            ErrorCode::NotAllowlisted =>
+                (
+                    "This auction is private and your wallet isn't on its allowlist.",
+                    "Ask the seller to add your wallet, or update your app so it passes your allowlist proof.",
+                ),
+            ErrorCode::NotTokenHolder =>
+                (
+                    "This auction is only open to holders of a specific token, and your wallet doesn't hold one.",
+                    "Get the token into your wallet, or update your app so it passes your token account for it.",
+                ),
+            ErrorCode::Blacklisted =>
+                (
+                    "This wallet has been barred from listing and bidding on the marketplace.",
+                    "Contact the marketplace if you believe this is a mistake.",
+                ),
+            ErrorCode::SellerPauseAfterBids =>
+                (
+                    "Sellers can only pause their own listing before anyone has bid on it.",
+                    "Contact the marketplace if the listing needs to be paused.",
+                ),
+            ErrorCode::InsufficientTreasuryFunds =>
+                (
+                    "The fee treasury doesn't hold enough to withdraw that amount.",
+                    "Withdraw at most the treasury's balance above its rent-exempt minimum.",
+                ),
+            ErrorCode::BuyerFeeTooHigh =>
+                (
+                    "The buyer fee is above the marketplace's 10% cap.",
+                    "Enter a buyer fee of at most 1000 basis points.",
+                ),
+            ErrorCode::SellerFeeTooHigh =>
+                (
+                    "The seller fee is above the marketplace's 10% cap.",
+                    "Enter a seller fee of at most 1000 basis points.",
+                ),
+            ErrorCode::FeeOverrideNotApproved =>
+                (
+                    "This listing asks for special fee terms the marketplace hasn't signed off on.",
+                    "List without the fee override, or have the marketplace co-sign the listing.",
+                ),
+            ErrorCode::InvalidReferrer =>
+                (
+                    "The referrer passed for this sale doesn't match the one on the winning bid.",
+                    "Update your app so it passes the winning bid's referrer.",
+                ),
+            ErrorCode::InvalidReferralShare =>
+                (
+                    "The referral share must be at most 100% of the buyer fee.",
+                    "Enter a referral share of at most 10000 basis points.",
+                ),
+            ErrorCode::InvalidPayoutSplits =>
+                (
+                    "The payout split for this listing is invalid: the shares must add up to exactly 100%.",
+                    "Give each recipient a nonzero share, list each once, and make the shares total 10000 basis points.",
+                ),
+            ErrorCode::MissingPayoutAccounts =>
+                (
+                    "One of this listing's payout recipients wasn't included in the transaction.",
+                    "Update your app so it passes each payout recipient, in the listing's order, after the creators.",
+                ),
+            ErrorCode::ArithmeticOverflow =>
+                (
+                    "A calculation on this auction's amounts went out of range.",
+                    "Check the amounts involved and try again with smaller values.",
+                ),
+            ErrorCode::InvalidBidHistoryCapacity =>
+                (
+                    "The requested bid history size is out of range.",
+                    "Choose a bid history capacity between 1 and 64 bids.",
+                ),
+            ErrorCode::UnsupportedAccountVersion =>
+                (
+                    "This account was written by a program version this one can't read.",
+                    "Upgrade through each intermediate program release, or contact support.",
+                ),
+            ErrorCode::InvalidQueryWindow =>
+                (
+                    "That time window isn't valid.",
+                    "Ask for a window of zero seconds or more.",
+                ),
+            ErrorCode::MissingProvenanceAccount =>
+                (
+                    "The NFT's sale history account wasn't included.",
+                    "Include the provenance account for the listing's NFT mint.",
+                ),
+            ErrorCode::InvalidOfferExpiry =>
+                (
+                    "That offer expiry has already passed.",
+                    "Choose an expiry time in the future.",
+                ),
+            ErrorCode::OfferExpired =>
+                (
+                    "This offer has expired.",
+                    "Ask the buyer for a new offer.",
+                ),
+            ErrorCode::OfferNotExpired =>
+                (
+                    "This offer is still active.",
+                    "Wait until the offer expires, or have the buyer cancel it.",
+                ),
+            ErrorCode::InvalidCrowdAuction =>
+                (
+                    "This crowd auction's settings aren't allowed.",
+                    "Set a target of at least the minimum contribution and drop the buy-now price, allowlist and token gate.",
+                ),
+            ErrorCode::NotCrowdAuction =>
+                (
+                    "This listing doesn't take contributions.",
+                    "Place a bid instead.",
+                ),
+            ErrorCode::ContributionsOnly =>
+                (
+                    "This listing is crowdfunded.",
+                    "Contribute to the pool instead of bidding.",
+                ),
+            ErrorCode::CrowdTargetMet =>
+                (
+                    "This crowd auction reached its target.",
+                    "Claim your shares once the auction has ended.",
+                ),
+            ErrorCode::CrowdNftLocked =>
+                (
+                    "This NFT belongs to the crowd that funded it.",
+                    "Claim your shares instead.",
+                ),
+            ErrorCode::InvalidRaffle =>
+                (
+                    "This raffle's settings aren't allowed.",
+                    "Set a ticket price above zero and drop the reserve and buy-now price.",
+                ),
+            ErrorCode::NotRaffle =>
+                (
+                    "This listing doesn't sell tickets.",
+                    "Place a bid instead.",
+                ),
+            ErrorCode::TicketsOnly =>
+                (
+                    "This listing is a raffle.",
+                    "Buy tickets instead of bidding.",
+                ),
+            ErrorCode::RaffleNotDrawn =>
+                (
+                    "This raffle closes with its draw.",
+                    "Commit randomness, then draw the raffle once it's revealed.",
+                ),
+            ErrorCode::RaffleTicketsLocked =>
+                (
+                    "Raffle tickets can't be withdrawn.",
+                    "Wait for the draw; tickets are refunded only if it's cancelled.",
+                ),
+            ErrorCode::NoTicketsSold =>
+                (
+                    "Nobody entered this raffle.",
+                    "Sweep the listing to return the NFT to the seller.",
+                ),
+            ErrorCode::InvalidCandleWindow =>
+                (
+                    "This candle window isn't allowed.",
+                    "Pick a window no longer than the auction and drop the buy-now price.",
+                ),
+            ErrorCode::CandleMarksFull =>
+                (
+                    "This auction can't take another lead change.",
+                    "Wait for the auction to close.",
+                ),
+            ErrorCode::CandleNotClosed =>
+                (
+                    "This candle auction closes with its draw.",
+                    "Commit randomness, then close the auction once it's revealed.",
+                ),
+            ErrorCode::NotCandleAuction =>
+                (
+                    "This auction has a fixed end.",
+                    "End it with end_auction instead.",
+                ),
+            ErrorCode::InvalidRentalTerm =>
+                (
+                    "This rental term isn't allowed.",
+                    "Pick a term of at most a year.",
+                ),
+            ErrorCode::NotRental =>
+                (
+                    "This listing sells the NFT outright.",
+                    "Claim it with claim_nft instead.",
+                ),
+            ErrorCode::RentalClaimRequired =>
+                (
+                    "This listing rents out its NFT.",
+                    "Claim it with claim_rental instead.",
+                ),
+            ErrorCode::RentalNotExpired =>
+                (
+                    "The renter still has this NFT.",
+                    "Try again once the rental term is over.",
+                ),
+            ErrorCode::RentalNotActive =>
+                (
+                    "There's no rental to end.",
+                    "Check that the winner has claimed the rental and it hasn't been returned.",
+                ),
+            ErrorCode::InvalidPaymentMint =>
+                (
+                    "This listing can't be paid in that currency.",
+                    "Pick SOL or a token the marketplace accepts.",
+                ),
+            ErrorCode::WrongPaymentMint =>
+                (
+                    "This listing is paid in a different currency.",
+                    "Pay with the listing's currency and pass its token accounts.",
+                ),
+            ErrorCode::InvalidUsdPricing =>
+                (
+                    "This listing's USD pricing isn't valid.",
+                    "Set a USD minimum bid, and only price SOL listings in USD.",
+                ),
+            ErrorCode::UsdPricedListing =>
+                (
+                    "This listing is priced in USD.",
+                    "Its minimum bid and reserve follow the SOL/USD price and can't be set in SOL.",
+                ),
+            ErrorCode::ListingNameTooLong =>
+                (
+                    "This listing's name is too long.",
+                    "Use a name of at most 32 bytes.",
+                ),
+            ErrorCode::InvalidListingName =>
+                (
+                    "This listing's name has characters that aren't allowed.",
+                    "Use letters, digits, spaces and punctuation only.",
+                ),
+            ErrorCode::ListingIdTaken =>
+                (
+                    "There's already a listing with this ID.",
+                    "Pick another name for the listing.",
+                ),
+            ErrorCode::TooManyActiveAuctions =>
+                (
+                    "You already have as many open listings as the marketplace allows.",
+                    "Wait for one of your listings to close, release its slot, then try again.",
+                ),
+            ErrorCode::SellerProfileRequired =>
+                (
+                    "You need a seller profile to list on this marketplace.",
+                    "Create your seller profile, then list again.",
+                ),
+            ErrorCode::WithdrawalTooLarge =>
+                (
+                    "You can't withdraw more than your bid has escrowed.",
+                    "Withdraw a smaller amount, or leave the amount out to withdraw everything.",
+                ),
+            ErrorCode::InvalidDisputeWindow =>
+                (
+                    "That dispute window isn't valid.",
+                    "Set a window of zero or more seconds.",
+                ),
+            ErrorCode::DisputeWindowOpen =>
+                (
+                    "This sale is still within its dispute window.",
+                    "Wait for the window to pass, or for the open dispute to be resolved.",
+                ),
+            ErrorCode::DisputeNotAllowed =>
+                (
+                    "This sale can't be disputed.",
+                    "Disputes are only open on a single sale while it's held in escrow.",
+                ),
+            ErrorCode::EarlyAcceptanceUnavailable =>
+                (
+                    "This listing can't be closed early by accepting its highest bid.",
+                    "Wait for the listing to end on its own.",
+                ),
+            ErrorCode::AuctionTooLong =>
+                (
+                    "The auction can't run that long.",
+                    "Pick an earlier end time.",
+                ),
+            ErrorCode::InvalidConfigChangeDelay =>
+                (
+                    "That config change delay isn't valid.",
+                    "Use a longer delay, or queue a shorter one as a config change.",
+                ),
+            ErrorCode::ConfigChangeTimelocked =>
+                (
+                    "This change has to go through the config change queue.",
+                    "Queue the change, then execute it once its delay has passed.",
+                ),
+            ErrorCode::InvalidLoyaltySchedule =>
+                (
+                    "The loyalty schedule's end time must come after its start.",
+                    "Set an end time after the start time, or 0 for no end.",
+                ),
+            ErrorCode::BidBondRequired =>
+                (
+                    "This listing takes a refundable bid bond, which only a direct bid can post.",
+                    "Bid through place_bid or place_proxy_bid.",
+                ),
+            ErrorCode::BidBondLocked =>
+                (
+                    "This bid bond is still held for the auction.",
+                    "Wait until the auction closes, or until its winner has claimed or let the claim window pass.",
+                ),
+            ErrorCode::InvalidSellerCollateral =>
+                (
+                    "The collateral settings are invalid.",
+                    "Stake a non-zero amount and give yourself time to deliver.",
+                ),
+            ErrorCode::NoSellerCollateral =>
+                (
+                    "There is no collateral on this listing.",
+                    "Check the listing.",
+                ),
+            ErrorCode::DeliveryNotConfirmed =>
+                (
+                    "Delivery hasn't been confirmed yet.",
+                    "Confirm delivery first, or wait for the deadline.",
+                ),
+            ErrorCode::DeliveryOverdue =>
+                (
+                    "It's too late to confirm delivery.",
+                    "Contributors can now take back their contributions and the collateral.",
+                ),
+            ErrorCode::FeatureDisabled =>
+                (
+                    "This feature isn't available yet.",
+                    "Try again once the marketplace enables it.",
+                ),
+            ErrorCode::InvalidFeatureFlags =>
+                (
+                    "The feature flags are invalid.",
+                    "Only set the bits of known features.",
+                ),
+            ErrorCode::OrderSignatureMissing =>
+                (
+                    "The order's signature wasn't checked.",
+                    "Submit the order right after an Ed25519 program instruction verifying it.",
+                ),
+            ErrorCode::InvalidOrderSignature =>
+                (
+                    "The order's signature doesn't match.",
+                    "Have the bidder sign the order exactly as submitted.",
+                ),
+            ErrorCode::OrderExpired =>
+                (
+                    "This bid order has expired.",
+                    "Ask the bidder to sign a new order.",
+                ),
+            ErrorCode::InsufficientProfileBalance =>
+                (
+                    "Your bidding balance is too low.",
+                    "Top up your bidder profile or lower the bid.",
+                ),
+            ErrorCode::AttestationRequired =>
+                (
+                    "The other chain's side of this sale isn't confirmed yet.",
+                    "Wait for the attestation to be relayed.",
+                ),
+            ErrorCode::AlreadyAttested =>
+                (
+                    "This sale is already attested.",
+                    "No action needed.",
+                ),
+            ErrorCode::InvalidAttestationPayload =>
+                (
+                    "The attestation is invalid.",
+                    "Relay the attestation VAA emitted for this listing.",
+                ),
+            ErrorCode::CollectionNotApproved =>
+                (
+                    "Only NFTs from approved collections can be listed here.",
+                    "List an NFT from a collection the marketplace has approved.",
+                ),
+            ErrorCode::InvalidNftMetadata =>
+                (
+                    "The NFT's metadata couldn't be read.",
+                    "Pass the Token Metadata account of the NFT being listed.",
+                ),
+            ErrorCode::CollectionNotVerified =>
+                (
+                    "This NFT isn't verified as part of that collection.",
+                    "List it without the collection, or verify it in the collection first.",
+                ),
+            ErrorCode::ReceiptTreeNotSet =>
+                (
+                    "This marketplace doesn't mint participation receipts.",
+                    "Ask the marketplace to set a receipt tree.",
+                ),
+            ErrorCode::ReceiptAlreadyMinted =>
+                (
+                    "You already have a participation receipt for this auction.",
+                    "Check your wallet for the receipt.",
+                ),
+            ErrorCode::NoBidToReceipt =>
+                (
+                    "There's no bid to issue a receipt for.",
+                    "Receipts only go to bidders of the auction.",
+                ),
+            ErrorCode::DefaultNotAllowed =>
+                (
+                    "This sale's winner can't be declared in default.",
+                    "Only an ended single sale whose winner hasn't claimed can be defaulted.",
+                ),
+            ErrorCode::ClaimWindowOpen =>
+                (
+                    "The winner still has time to claim.",
+                    "Wait until the claim window has passed.",
+                ),
+            ErrorCode::InvalidInstallmentPlan =>
+                (
+                    "This listing can't be paid in installments on those terms.",
+                    "Use 2 to 12 installments, a positive interval and a penalty of at most 100%, on a single sale in SOL.",
+                ),
+            ErrorCode::NoInstallmentDue =>
+                (
+                    "There's no installment left to pay on this sale.",
+                    "Claim the sale once every installment is in.",
+                ),
+            ErrorCode::InstallmentNotOverdue =>
+                (
+                    "The winner's next installment isn't due yet.",
+                    "Wait until the installment's due date has passed.",
+                ),
+            ErrorCode::InstallmentsOutstanding =>
+                (
+                    "This sale isn't fully paid yet.",
+                    "Pay the remaining installments before claiming.",
+                ),
+            ErrorCode::DelegatedBidsOnly =>
+                (
+                    "This listing takes bids by token approval only.",
+                    "Approve the listing's vault as delegate and use place_delegated_bid.",
+                ),
+            ErrorCode::DelegatedBiddingUnavailable =>
+                (
+                    "This listing can't take bids by token approval.",
+                    "Delegated bidding is for single-winner token listings, set before the first bid.",
+                ),
+            ErrorCode::DelegateApprovalMissing =>
+                (
+                    "Your token approval or balance doesn't cover this bid.",
+                    "Approve the listing's vault as delegate for the full bid and hold that much.",
+                ),
+            ErrorCode::DelegatedBidUncollected =>
+                (
+                    "The winning bid hasn't been collected yet.",
+                    "Collect the winning bid first.",
+                ),
+            ErrorCode::BidLimitReached =>
+                (
+                    "You've reached the number of bids allowed on this listing.",
+                    "You can't bid again on this listing.",
+                ),
+            ErrorCode::BidCooldownActive =>
+                (
+                    "You bid too recently on this listing.",
+                    "Wait for the listing's cooldown to pass before bidding again.",
+                ),
+            ErrorCode::BidLimitsApply =>
+                (
+                    "This listing limits bids per bidder.",
+                    "Bid with place_bid instead.",
+                ),
+            ErrorCode::InvalidBidLimits =>
+                (
+                    "The bid limits are invalid.",
+                    "Use a cooldown of 0 or more seconds.",
+                ),
+            ErrorCode::BidNotFound =>
+                (
+                    "This wallet has no bid on the listing.",
+                    "Check the bidder and listing, or place a bid first.",
+                ),
+            ErrorCode::CollectionStatsMissing =>
+                (
+                    "This collection's price stats weren't provided.",
+                    "Pass the collection's stats account, creating it with init_collection_stats if needed.",
+                ),
+            ErrorCode::NoCollectionSales =>
+                (
+                    "This collection has no sales to price it by yet.",
+                    "Try again once one of its listings has sold.",
+                ),
+            ErrorCode::CoSellerNotApproved =>
+                (
+                    "Not every co-owner has approved this listing.",
+                    "Have each co-owner co-sign the listing or approve it with approve_co_listing for their exact share.",
+                ),
+            ErrorCode::InvalidVesting =>
+                (
+                    "Proceeds vesting isn't available with these terms.",
+                    "Vest proceeds of SOL listings without payout splits, for at most a year.",
+                ),
+            ErrorCode::InvalidFeeTiers =>
+                (
+                    "The buyer fee tiers are invalid.",
+                    "Give at most 8 tiers in increasing order, the last one up to u64::MAX.",
+                ),
+            ErrorCode::InvalidMembershipDiscount =>
+                (
+                    "The membership discount is invalid.",
+                    "Use a discount of at most 10000 basis points.",
+                ),
+            ErrorCode::NotAMember =>
+                (
+                    "This NFT doesn't qualify for the membership discount.",
+                    "Present a membership NFT you hold, with its Token Metadata account, verified in the discounted collection.",
+                ),
+            ErrorCode::InvalidBeneficiary =>
+                (
+                    "The listing's beneficiary is missing or invalid.",
+                    "Give the beneficiary a share of 1 to 10000 basis points, and pass its wallet or token account to claim_proceeds.",
+                ),
+            ErrorCode::ConfigFrozen =>
+                (
+                    "The marketplace has permanently frozen this setting.",
+                    "Nothing; frozen fees, fee recipient and NFT contract can't be changed.",
+                ),
+            ErrorCode::RegistryPageFull =>
+                (
+                    "This page of the auction registry has no room left.",
+                    "Open the next registry page, or pass one with room.",
+                ),
+            ErrorCode::InvalidRegistryPage =>
+                (
+                    "That auction registry page can't be used here.",
+                    "Pass the registry page recorded on the auction, or open pages in order.",
+                ),
+            ErrorCode::SettlementStepDone =>
+                (
+                    "This part of the sale has already been settled.",
+                    "Nothing to do; check the settlement receipt for what was paid or delivered.",
+                ),
+            ErrorCode::InvalidRetractionPenalty =>
+                (
+                    "That retraction penalty is higher than allowed.",
+                    "Choose a penalty of at most the maximum fee.",
+                ),
+            ErrorCode::RetractionNotAllowed =>
+                (
+                    "This kind of listing doesn't allow retracting a bid.",
+                    "Withdraw any outbid funds instead.",
+                ),
+            ErrorCode::RetractionRequired =>
+                (
+                    "Bids are binding while the auction is live, so pulling out early costs the retraction penalty.",
+                    "Retract the bid to pull out now, or withdraw in full once the auction has ended.",
+                ),
+            ErrorCode::BidReceiptMismatch =>
+                (
+                    "The bid receipt given doesn't belong to this bidder and listing.",
+                    "Pass the receipt address derived from the listing and bidder.",
+                ),
+            ErrorCode::BlacklistEntryMismatch =>
+                (
+                    "The blacklist account given doesn't match the bidder.",
+                    "Pass the blacklist address derived from the bidder's wallet.",
+                ),
+            ErrorCode::SellerOnVacation =>
+                (
+                    "This seller is away and isn't taking new listings right now.",
+                    "List again once vacation mode is off.",
+                ),
+            ErrorCode::IncompleteVacationSet =>
+                (
+                    "Vacation mode has to cover all of your open listings.",
+                    "Include every open listing tracked by your seller profile and try again.",
+                ),
+            ErrorCode::TooManyCreators =>
+                (
+                    "This NFT has too many creators to settle in steps.",
+                    "Settle it with claim_proceeds instead.",
+                ),
+        }
+    }
+}
+
+// Shared by the CLI, client crate and frontends so every surface explains a
+// failure the same way; `code` is the number returned in the transaction error
+pub fn explain(code: u32) -> &'static str {
+    ErrorCode::from_code(code).map_or("Something went wrong.", |error| error.explanation().0)
+}
+
+pub fn suggested_action(code: u32) -> &'static str {
+    ErrorCode::from_code(code).map_or("Try again later.", |error| error.explanation().1)
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{ AccountMeta, Instruction };
+
+// Adapter interface for NFTs that stay in an external, delegate-based escrow.
+//
+// An approved escrow program takes part in a two-step handshake, each step a
+// single instruction of the 8-byte Anchor discriminator followed by the
+// borsh-encoded payload:
+//
+// - `confirm_external_listing` when the seller lists: accounts are the auction
+//   state PDA (signer), the seller (signer) and the escrowed token account. The
+//   adapter must fail unless it holds the NFT for that seller and agrees to
+//   release it only on this program's request.
+// - `release_to_winner` after settlement: accounts are the auction state PDA
+//   (signer) and the escrowed token account, followed by whatever the adapter
+//   needs to deliver the NFT (recipient's token account, its own PDAs, ...).
+//   The recipient is the winner unless the winner named someone else.
+pub const CONFIRM_INSTRUCTION_NAME: &str = "global:confirm_external_listing";
+pub const RELEASE_INSTRUCTION_NAME: &str = "global:release_to_winner";
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfirmListingPayload {
+    pub listing_id: [u8; 32],
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReleasePayload {
+    pub listing_id: [u8; 32],
+    pub winner: Pubkey,
+    pub price: u64,
+    pub recipient: Pubkey,
+}
+
+pub fn adapter_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(name.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+pub fn build_adapter_instruction<T: AnchorSerialize>(
+    adapter_program: Pubkey,
+    name: &str,
+    payload: &T,
+    accounts: Vec<AccountMeta>,
+    extra_accounts: &[AccountInfo]
+) -> Result<Instruction> {
+    let mut data = adapter_discriminator(name).to_vec();
+    payload.serialize(&mut data)?;
+
+    let mut accounts = accounts;
+    accounts.extend(
+        extra_accounts.iter().map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+    );
+
+    Ok(Instruction { program_id: adapter_program, accounts, data })
+}
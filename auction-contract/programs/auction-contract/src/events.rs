@@ -0,0 +1,565 @@
+use anchor_lang::prelude::*;
+use crate::state::{ AttestationKind, BuyerFeeTier, ParamChange, Role };
+
+#[event]
+pub struct AuctionEnded {
+    pub listing_id: [u8; 32],
+    pub winner: Pubkey,
+    pub amount: u64,
+    pub buy_now: bool,
+}
+
+#[event]
+pub struct AuctionInitialized {
+    pub listing_id: [u8; 32],
+    pub minimum: u64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub listing_id: [u8; 32],
+    pub sender: Pubkey,
+    pub value: u64,
+    pub referrer: Option<Pubkey>,
+    pub relayer: Option<Pubkey>, // Who submitted and paid for the bid, when not the bidder
+}
+
+#[event]
+pub struct CompressedPositionUpdated {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub time: i64,
+    pub index: u32,
+}
+
+#[event]
+pub struct MinimumBidUpdated {
+    pub listing_id: [u8; 32],
+    pub old_minimum: u64,
+    pub new_minimum: u64,
+}
+
+#[event]
+pub struct ListingUpdated {
+    pub listing_id: [u8; 32],
+    pub metadata_uri: String,
+    pub reserve_price: u64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct AuctionExtended {
+    pub listing_id: [u8; 32],
+    pub end_time: i64,
+}
+
+#[event]
+pub struct NftDelivered {
+    pub listing_id: [u8; 32],
+    pub winner: Pubkey,
+    pub recipient: Pubkey,
+    pub price: u64,
+}
+
+#[event]
+pub struct WatchReminder {
+    pub owner: Pubkey,
+    pub listing_id: [u8; 32],
+    pub end_time: i64,
+}
+
+#[event]
+pub struct DeprecatedInstructionUsed {
+    pub instruction: String,
+    pub replacement: String,
+}
+
+#[event]
+pub struct RoyaltiesPaid {
+    pub listing_id: [u8; 32],
+    pub creator: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AuctionCancelled {
+    pub listing_id: [u8; 32],
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct AuctionSwept {
+    pub listing_id: [u8; 32],
+    pub caller: Pubkey,
+    pub bounty: u64,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeesUpdated {
+    pub old_buyer_fee_bps: u64,
+    pub old_seller_fee_bps: u64,
+    pub buyer_fee_bps: u64,
+    pub seller_fee_bps: u64,
+}
+
+#[event]
+pub struct ReferralPaid {
+    pub listing_id: [u8; 32],
+    pub referrer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct BidRefunded {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FundsWithdrawn {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub remaining: u64, // Still escrowed on the bid after a partial withdrawal
+}
+
+#[event]
+pub struct AuctionPaused {
+    pub listing_id: [u8; 32],
+    pub paused: bool, // False when the auction resumes
+    pub by: Pubkey,
+}
+
+#[event]
+pub struct FeeRecipientChanged {
+    pub old_fee_recipient: Pubkey,
+    pub fee_recipient: Pubkey,
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub id: u64,
+    pub change: ParamChange,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct ConfigChangeExecuted {
+    pub id: u64,
+    pub change: ParamChange,
+}
+
+#[event]
+pub struct AuthorityChanged {
+    pub old_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct OfferMade {
+    pub mint: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct OfferAccepted {
+    pub mint: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64, // After the buyer fee
+    pub royalties: u64,
+}
+
+#[event]
+pub struct OfferCancelled {
+    pub mint: Pubkey,
+    pub buyer: Pubkey,
+}
+
+#[event]
+pub struct FixedPriceListed {
+    pub listing_id: [u8; 32],
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64,
+}
+
+#[event]
+pub struct FixedPriceSold {
+    pub listing_id: [u8; 32],
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+}
+
+#[event]
+pub struct FixedPriceCancelled {
+    pub listing_id: [u8; 32],
+}
+
+#[event]
+pub struct NftClaimed {
+    pub listing_id: [u8; 32],
+    pub winner: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>, // None when the NFT was minted on claim
+    pub edition: Option<u16>, // Index into the edition book for an edition auction
+    pub price: u64,
+}
+
+#[event]
+pub struct CrowdAuctionCreated {
+    pub listing_id: [u8; 32],
+    pub owner: Pubkey,
+    pub target: u64,
+    pub share_mint: Pubkey,
+    pub collateral: u64, // Staked by the seller on delivering off-chain; 0 for none
+}
+
+#[event]
+pub struct ContributionMade {
+    pub listing_id: [u8; 32],
+    pub contributor: Pubkey,
+    pub amount: u64, // After the buyer fee
+    pub total: u64, // The pool so far
+}
+
+#[event]
+pub struct ContributionRefunded {
+    pub listing_id: [u8; 32],
+    pub contributor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CrowdSharesClaimed {
+    pub listing_id: [u8; 32],
+    pub contributor: Pubkey,
+    pub shares: u64,
+}
+
+#[event]
+pub struct TicketsBought {
+    pub listing_id: [u8; 32],
+    pub buyer: Pubkey,
+    pub count: u64,
+    pub cost: u64, // Before the buyer fee
+}
+
+#[event]
+pub struct RaffleDrawn {
+    pub listing_id: [u8; 32],
+    pub winner: Pubkey,
+    pub tickets: u64,
+    pub pot: u64,
+}
+
+#[event]
+pub struct CandleAuctionClosed {
+    pub listing_id: [u8; 32],
+    pub cutoff: i64,
+    pub winner: Option<Pubkey>, // None when nobody had met the reserve by the cutoff
+    pub amount: u64,
+}
+
+#[event]
+pub struct RentalStarted {
+    pub listing_id: [u8; 32],
+    pub renter: Pubkey,
+    pub mint: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct RentalExpired {
+    pub listing_id: [u8; 32],
+    pub renter: Pubkey,
+    pub seller: Pubkey, // Holds the NFT again
+}
+
+#[event]
+pub struct TokenVaultOpened {
+    pub listing_id: [u8; 32],
+    pub mint: Pubkey,
+    pub token_vault: Pubkey,
+}
+
+#[event]
+pub struct DisputeOpened {
+    pub listing_id: [u8; 32],
+    pub opened_by: Pubkey,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub listing_id: [u8; 32],
+    pub refunded: bool, // The sale was voided and the winner refunded, rather than released to the seller
+}
+
+#[event]
+pub struct LoyaltyPointsEarned {
+    pub owner: Pubkey,
+    pub points: u64,
+    pub total: u64,
+}
+
+#[event]
+pub struct BidBondSet {
+    pub listing_id: [u8; 32],
+    pub bond: u64,
+}
+
+#[event]
+pub struct BidBondPosted {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BidBondReleased {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub to: Pubkey, // The bidder, or the seller when the bond was forfeited
+    pub amount: u64,
+    pub forfeited: bool,
+}
+
+#[event]
+pub struct DeliveryConfirmed {
+    pub listing_id: [u8; 32],
+}
+
+#[event]
+pub struct SellerCollateralSlashed {
+    pub listing_id: [u8; 32],
+    pub amount: u64, // Split between the contributors as they refund
+}
+
+#[event]
+pub struct SellerCollateralReturned {
+    pub listing_id: [u8; 32],
+    pub amount: u64,
+}
+
+#[event]
+pub struct RoleChanged {
+    pub role: Role,
+    pub holder: Option<Pubkey>, // None once revoked
+}
+
+#[event]
+pub struct FeatureFlagsChanged {
+    pub flags: u64,
+}
+
+#[event]
+pub struct BidderBalanceChanged {
+    pub bidder: Pubkey,
+    pub balance: u64,
+}
+
+#[event]
+pub struct SignedBidPlaced {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub matcher: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AuctionArchived {
+    pub listing_id: [u8; 32],
+    pub index: u64, // Leaf index in the archive
+    pub leaf: [u8; 32],
+}
+
+#[event]
+pub struct ParticipationReceiptMinted {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub best_bid: u64,
+    pub asset_id: Pubkey, // The receipt cNFT
+}
+
+#[event]
+pub struct WinnerDefaulted {
+    pub listing_id: [u8; 32],
+    pub defaulter: Pubkey,
+    pub new_winner: Option<Pubkey>, // None when the auction reopened for bids
+    pub price: u64, // What the new winner won at; 0 when reopened
+    pub bond_forfeited: u64,
+}
+
+#[event]
+pub struct InstallmentPlanSet {
+    pub listing_id: [u8; 32],
+    pub count: u8, // 0 when the plan was dropped
+    pub interval: i64,
+    pub penalty_bps: u16,
+}
+
+#[event]
+pub struct InstallmentPaid {
+    pub listing_id: [u8; 32],
+    pub winner: Pubkey,
+    pub amount: u64,
+    pub paid: u8,
+    pub remaining: u8,
+}
+
+#[event]
+pub struct InstallmentsDefaulted {
+    pub listing_id: [u8; 32],
+    pub winner: Pubkey,
+    pub paid: u8, // Installments paid before the default, the winning bid included
+    pub penalty: u64, // Kept by the seller
+}
+
+#[event]
+pub struct DelegatedBiddingSet {
+    pub listing_id: [u8; 32],
+    pub enabled: bool,
+}
+
+#[event]
+pub struct DelegatedBidCollected {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub amount: u64, // Pulled into the token vault, buyer fee included
+}
+
+#[event]
+pub struct DelegatedBidDropped {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub next: Option<Pubkey>, // Who leads now; None when no bid is left
+}
+
+#[event]
+pub struct BidLimitsSet {
+    pub listing_id: [u8; 32],
+    pub max_bids_per_bidder: u16,
+    pub bid_cooldown: i64,
+}
+
+#[event]
+pub struct CoSellerApproved {
+    pub listing_id: [u8; 32],
+    pub co_seller: Pubkey,
+    pub share_bps: u16,
+}
+
+#[event]
+pub struct CoSellerApprovalRevoked {
+    pub listing_id: [u8; 32],
+    pub co_seller: Pubkey,
+}
+
+#[event]
+pub struct ProceedsVestingSet {
+    pub listing_id: [u8; 32],
+    pub duration: i64, // 0 when the proceeds are paid at once again
+}
+
+#[event]
+pub struct VestedProceedsClaimed {
+    pub listing_id: [u8; 32],
+    pub amount: u64,
+    pub remaining: u64, // Still to vest or to claim
+}
+
+#[event]
+pub struct BuyerFeeTiersSet {
+    pub tiers: Vec<BuyerFeeTier>, // Empty when the flat buyer fee applies again
+}
+
+#[event]
+pub struct MembershipDiscountSet {
+    pub collection: Pubkey,
+    pub discount_bps: u64,
+}
+
+#[event]
+pub struct CharityContribution {
+    pub listing_id: [u8; 32],
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ConfigFrozen {
+    pub authority: Pubkey,
+    // The parameters the marketplace is now committed to
+    pub buyer_fee_bps: u64,
+    pub seller_fee_bps: u64,
+    pub fee_recipient: Pubkey,
+}
+
+#[event]
+pub struct VaultReconciled {
+    pub listing_id: [u8; 32],
+    pub held: u64, // In escrow before the sweep
+    pub owed: u64,
+    pub swept: u64, // Surplus moved to the fee treasury
+    pub shortfall: u64, // Owed beyond what's held; 0 when the books balance
+}
+
+#[event]
+pub struct AuctionRegistered {
+    pub listing_id: [u8; 32],
+    pub auction: Pubkey,
+    pub page: u32,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct AuctionDeregistered {
+    pub listing_id: [u8; 32],
+    pub auction: Pubkey,
+    pub page: u32,
+}
+
+#[event]
+pub struct RetractionPenaltySet {
+    pub penalty_bps: u64,
+    pub to_seller: bool,
+}
+
+#[event]
+pub struct BidRetracted {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub refunded: u64,
+    pub penalty: u64,
+    pub to_seller: bool, // The penalty went to the seller rather than the fee treasury
+}
+
+#[event]
+pub struct AlienSettlementAttested {
+    pub listing_id: [u8; 32],
+    pub kind: AttestationKind,
+    pub emitter_chain: u16,
+    pub sequence: u64,
+}
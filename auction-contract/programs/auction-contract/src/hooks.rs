@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{ AccountMeta, Instruction };
+
+// Settlement hook interface.
+//
+// A whitelisted hook program is called once per settled auction with a single
+// instruction: the 8-byte Anchor discriminator of `on_auction_settled`
+// followed by the borsh-encoded `SettlementHookPayload`. The first account is
+// always the auction state PDA as a signer, so the hook can verify the call
+// came from this program; any remaining accounts are forwarded as passed.
+pub const HOOK_INSTRUCTION_NAME: &str = "global:on_auction_settled";
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SettlementHookPayload {
+    pub listing_id: [u8; 32],
+    pub winner: Pubkey,
+    pub price: u64,
+}
+
+pub fn hook_discriminator() -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(HOOK_INSTRUCTION_NAME.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+pub fn build_hook_instruction(
+    hook_program: Pubkey,
+    auction_state: Pubkey,
+    payload: &SettlementHookPayload,
+    extra_accounts: &[AccountInfo]
+) -> Result<Instruction> {
+    let mut data = hook_discriminator().to_vec();
+    payload.serialize(&mut data)?;
+
+    let mut accounts = vec![AccountMeta::new_readonly(auction_state, true)];
+    accounts.extend(
+        extra_accounts.iter().map(|account| AccountMeta {
+            pubkey: account.key(),
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+    );
+
+    Ok(Instruction { program_id: hook_program, accounts, data })
+}
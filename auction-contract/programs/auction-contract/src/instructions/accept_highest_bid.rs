@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::emitter::{ self, event_authority, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::{ AuctionEnded, BidRefunded };
+use crate::invariants::assert_funds_conserved;
+use crate::state::*;
+use crate::vault::Escrow;
+use crate::{ AUCTION_SEED, TOKEN_VAULT_SEED, VAULT_SEED, WSOL_UNWRAP_SEED };
+
+// Every other inline bidder's wallet (their token account, on a listing paid in
+// a token) follows as a remaining account, in the auction's bid order, to be
+// refunded on the spot, as for a buy-now sale. Positions on overflow pages or
+// in a compressed tree stay claimable through the usual withdrawals.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct AcceptHighestBid<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    // Listings paid in a token only
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    // Wrapped SOL listings only: the account payouts to wallets are unwrapped
+    // through, and the native mint it's opened for
+    /// CHECK: created and closed again within the instruction by the escrow
+    #[account(mut, seeds = [WSOL_UNWRAP_SEED, listing_id.as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
+
+// The seller takes the current highest bid before the auction runs out: it ends
+// now, the other bidders are refunded and the sale is ready for `claim_nft`
+// and `claim_proceeds`. Accepting is the seller's call, so a bid under their
+// own reserve can be taken too.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptHighestBid<'info>>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let events: EventAuthority = event_authority!(ctx);
+    let auction = &mut ctx.accounts.auction;
+
+    // Only a plain single-winner auction has one highest bid to take
+    require!(
+        auction.editions == 0 &&
+            auction.ticket_price == 0 &&
+            auction.candle_window == 0 &&
+            !auction.is_alien,
+        ErrorCode::EarlyAcceptanceUnavailable
+    );
+    require!(auction.status == AuctionStatus::Live, ErrorCode::AuctionEnded);
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < auction.end_time, ErrorCode::AuctionEnded);
+    require!(auction.highest_bid > 0, ErrorCode::NothingToWithdraw);
+
+    let winner = auction.highest_bidder;
+    auction.end_time = now;
+    auction.transition_to(AuctionStatus::Ended)?;
+
+    // Refund every other inline position
+    let escrow = Escrow::new(
+        auction,
+        &listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?.unwrapping(
+        ctx.accounts.unwrap_account.as_ref(),
+        ctx.accounts.native_mint.as_ref(),
+        ctx.accounts.owner.to_account_info()
+    );
+    let mut refund_accounts = ctx.remaining_accounts.iter();
+    for bid in auction.bids.iter_mut().filter(|bid| bid.bidder != winner && bid.amount > 0) {
+        let wallet = refund_accounts.next().ok_or(ErrorCode::RefundAccountsMismatch)?;
+        require!(escrow.pays(wallet, &bid.bidder), ErrorCode::RefundAccountsMismatch);
+        let amount = std::mem::take(&mut bid.amount);
+        escrow.pay(wallet, amount)?;
+        emitter::emit(&events, BidRefunded {
+            listing_id,
+            bidder: bid.bidder,
+            amount,
+        })?;
+    }
+    require!(refund_accounts.next().is_none(), ErrorCode::RefundAccountsMismatch);
+    escrow.settle()?;
+
+    assert_funds_conserved(escrow.holding(), auction)?;
+
+    emitter::emit(&events, AuctionEnded {
+        listing_id,
+        winner,
+        amount: auction.highest_bid,
+        buy_now: false,
+    })?;
+    Ok(())
+}
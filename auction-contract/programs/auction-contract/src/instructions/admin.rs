@@ -0,0 +1,816 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::{ AuctionPaused, AuthorityChanged, BuyerFeeTiersSet, ConfigFrozen };
+use crate::instructions::audit_log::record_fee_change;
+use crate::instructions::seller_pause::set_seller_paused;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, AUDIT_LOG_SEED, ROLES_SEED };
+
+#[derive(Accounts)]
+pub struct ChangeFeeRecipient<'info> {
+    // Only direct while no config change delay is set; otherwise queued
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled,
+        constraint = auction_state.config_change_delay == 0 @ ErrorCode::ConfigChangeTimelocked
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeConfig<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeNFTContract<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageAggregatorPrograms<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageSwapPrograms<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFees<'info> {
+    // Only direct while no config change delay is set; otherwise queued
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled,
+        constraint = auction_state.config_change_delay == 0 @ ErrorCode::ConfigChangeTimelocked
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetBuyerFeeTiers<'info> {
+    // Only direct while no config change delay is set, as for set_fees
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled,
+        constraint = auction_state.config_change_delay == 0 @ ErrorCode::ConfigChangeTimelocked
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoyaltyEnforcement<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCuratedListings<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetReceiptTree<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferralShare<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetKeeperTip<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxActiveAuctions<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+// The proposed authority signs to take over, which a multisig vault does
+// through its program's CPI like any other admin instruction
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.pending_authority == Some(new_authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub new_authority: Signer<'info>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetConfigChangeDelay<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxAuctionDuration<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLoyaltySchedule<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDisputeConfig<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct EmergencyPauseAuction<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Only the program admin, the pauser or the listing's own seller may pause it
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        constraint = auction_state.may_pause(roles.as_deref(), &owner.key()) ||
+        auction.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+// The listings to pause are passed as writable remaining accounts
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct PauseAllOf<'info> {
+    // The admin or pauser, or the seller for their own listings
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.may_pause(roles.as_deref(), &signer.key()) ||
+        owner == signer.key() @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub signer: Signer<'info>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+// The listings to pause are passed as writable remaining accounts
+#[derive(Accounts)]
+pub struct PauseCollection<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.may_pause(roles.as_deref(), &authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    // The admin or the pauser
+    pub authority: Signer<'info>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuctionDefaults<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAntisnipingConfig<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutomationAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeManager<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SetAuctionFeeOverride<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.manages_fees(roles.as_deref(), &signer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetArchivePolicy<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+// Listings looked at per batch pause call, keeps each call within compute limits
+pub const MAX_PAUSE_BATCH: usize = 25;
+
+pub fn change_fee_recipient(
+    ctx: Context<ChangeFeeRecipient>,
+    new_fee_recipient: Pubkey
+) -> Result<()> {
+    ctx.accounts.auction_state.set_fee_recipient(new_fee_recipient)
+}
+
+// Hand the admin authority to `new_authority`, e.g. a Squads vault, once it
+// accepts; proposing None withdraws a pending proposal. Two steps, so the
+// authority can't be handed to an address that can't sign for it.
+pub fn propose_authority(
+    ctx: Context<ProposeAuthority>,
+    new_authority: Option<Pubkey>
+) -> Result<()> {
+    ctx.accounts.auction_state.pending_authority = new_authority;
+    AuditLog::record(
+        &ctx.accounts.audit_log,
+        AuditAction::AuthorityProposed,
+        ctx.accounts.authority.key(),
+        new_authority.unwrap_or_default(),
+        0,
+        0
+    )
+}
+
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    let authority = ctx.accounts.new_authority.key();
+    let old_authority = auction_state.authority;
+    emit!(AuthorityChanged { old_authority, authority });
+    auction_state.authority = authority;
+    auction_state.pending_authority = None;
+    AuditLog::record(
+        &ctx.accounts.audit_log,
+        AuditAction::AuthorityTransferred,
+        authority,
+        old_authority,
+        0,
+        0
+    )
+}
+
+// Approve an external marketplace/aggregator program to bid via CPI
+pub fn add_aggregator_program(ctx: Context<ManageAggregatorPrograms>, program: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    if !auction_state.aggregator_programs.contains(&program) {
+        require!(
+            auction_state.aggregator_programs.len() < NftComAuction::MAX_AGGREGATOR_PROGRAMS,
+            ErrorCode::AggregatorWhitelistFull
+        );
+        auction_state.aggregator_programs.push(program);
+    }
+    Ok(())
+}
+
+pub fn remove_aggregator_program(
+    ctx: Context<ManageAggregatorPrograms>,
+    program: Pubkey
+) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.aggregator_programs.retain(|key| *key != program);
+    Ok(())
+}
+
+// Approve a swap program for bids paid in other tokens
+pub fn add_swap_program(ctx: Context<ManageSwapPrograms>, program: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    if !auction_state.swap_programs.contains(&program) {
+        require!(
+            auction_state.swap_programs.len() < NftComAuction::MAX_SWAP_PROGRAMS,
+            ErrorCode::SwapProgramWhitelistFull
+        );
+        auction_state.swap_programs.push(program);
+    }
+    Ok(())
+}
+
+pub fn remove_swap_program(ctx: Context<ManageSwapPrograms>, program: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.swap_programs.retain(|key| *key != program);
+    Ok(())
+}
+
+// Commit the marketplace to its current fees, buyer fee tiers, fee recipient
+// and NFT contract for good, directly or through queued or governance changes.
+// There is no way back. Emergency pauses, blacklisting and the rest of the
+// admin's controls stay.
+pub fn freeze_config(ctx: Context<FreezeConfig>) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.ensure_unfrozen()?;
+    auction_state.config_frozen = true;
+
+    emit!(ConfigFrozen {
+        authority: auction_state.authority,
+        buyer_fee_bps: auction_state.buyer_fee_bps,
+        seller_fee_bps: auction_state.seller_fee_bps,
+        fee_recipient: auction_state.fee_recipient,
+    });
+    Ok(())
+}
+
+// Change the NFT contract address
+pub fn change_nft_contract(ctx: Context<ChangeNFTContract>, new_nft_contract: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.ensure_unfrozen()?;
+    auction_state.nft_contract = new_nft_contract;
+    Ok(())
+}
+
+// Only affects listings created afterwards
+pub fn set_auction_defaults(ctx: Context<SetAuctionDefaults>, defaults: AuctionDefaults) -> Result<()> {
+    require!(defaults.is_valid(), ErrorCode::InvalidAuctionDefaults);
+
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.default_duration = defaults.default_duration;
+    auction_state.min_increment_bps = defaults.min_increment_bps;
+    auction_state.sniping_time_window = defaults.sniping_time_window;
+    auction_state.time_extension = defaults.time_extension;
+    auction_state.reserve_policy = defaults.reserve_policy;
+    Ok(())
+}
+
+// Applies to listings made from now on; existing ones keep the window and
+// extension they were opened with, but are held to the new cap
+pub fn set_antisniping_config(
+    ctx: Context<SetAntisnipingConfig>,
+    sniping_time_window: i64,
+    time_extension: i64,
+    max_extensions: u32
+) -> Result<()> {
+    require!(
+        sniping_time_window >= 0 && time_extension >= 0,
+        ErrorCode::InvalidAntiSnipingConfig
+    );
+
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.sniping_time_window = sniping_time_window;
+    auction_state.time_extension = time_extension;
+    auction_state.max_extensions = max_extensions;
+    Ok(())
+}
+
+pub fn set_automation_authority(
+    ctx: Context<SetAutomationAuthority>,
+    automation_authority: Option<Pubkey>
+) -> Result<()> {
+    ctx.accounts.auction_state.automation_authority = automation_authority;
+    Ok(())
+}
+
+pub fn set_fee_manager(ctx: Context<SetFeeManager>, fee_manager: Option<Pubkey>) -> Result<()> {
+    ctx.accounts.auction_state.fee_manager = fee_manager;
+    Ok(())
+}
+
+// Share of an auction's buyer fees, in basis points, paid at settlement to
+// whoever referred the winning bidder
+pub fn set_referral_share(ctx: Context<SetReferralShare>, referral_share_bps: u64) -> Result<()> {
+    require!(referral_share_bps <= 10_000, ErrorCode::InvalidReferralShare);
+    ctx.accounts.auction_state.referral_share_bps = referral_share_bps;
+    Ok(())
+}
+
+pub fn set_keeper_tip(ctx: Context<SetKeeperTip>, keeper_tip: u64) -> Result<()> {
+    ctx.accounts.auction_state.keeper_tip = keeper_tip;
+    Ok(())
+}
+
+// Cap the listings a seller may have open at once; 0 lifts the cap. Sellers
+// need a profile to list while a cap is set, since it keeps their count.
+pub fn set_max_active_auctions_per_seller(
+    ctx: Context<SetMaxActiveAuctions>,
+    max_active_auctions: u32
+) -> Result<()> {
+    ctx.accounts.auction_state.max_active_auctions_per_seller = max_active_auctions;
+    Ok(())
+}
+
+// Make config changes wait `config_change_delay` seconds in the queue, after
+// which fees and the fee recipient can't be changed directly. Only a longer
+// wait applies at once; a shorter one has to go through the current wait.
+pub fn set_config_change_delay(
+    ctx: Context<SetConfigChangeDelay>,
+    config_change_delay: i64
+) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    require!(
+        config_change_delay >= auction_state.config_change_delay,
+        ErrorCode::InvalidConfigChangeDelay
+    );
+    auction_state.config_change_delay = config_change_delay;
+    Ok(())
+}
+
+// Bound how far past its listing time `extend_auction` may push an auction's
+// end; 0 lifts the bound
+pub fn set_max_auction_duration(
+    ctx: Context<SetMaxAuctionDuration>,
+    max_auction_duration: i64
+) -> Result<()> {
+    require!(max_auction_duration >= 0, ErrorCode::InvalidExtension);
+    ctx.accounts.auction_state.max_auction_duration = max_auction_duration;
+    Ok(())
+}
+
+// Points already earned stay; the new rates apply from the next bid or purchase
+pub fn set_loyalty_schedule(
+    ctx: Context<SetLoyaltySchedule>,
+    schedule: LoyaltySchedule
+) -> Result<()> {
+    require!(
+        schedule.ends_at == 0 || schedule.ends_at > schedule.starts_at,
+        ErrorCode::InvalidLoyaltySchedule
+    );
+    ctx.accounts.auction_state.loyalty_schedule = schedule;
+    Ok(())
+}
+
+// Hold every sale's proceeds and NFT in escrow for `dispute_window` seconds
+// after it closes, so a disputed sale can still be unwound; 0 pays out at once.
+// The arbiter, if set, resolves disputes alongside the authority.
+pub fn set_dispute_config(
+    ctx: Context<SetDisputeConfig>,
+    dispute_window: i64,
+    arbiter: Option<Pubkey>
+) -> Result<()> {
+    require!(dispute_window >= 0, ErrorCode::InvalidDisputeWindow);
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.dispute_window = dispute_window;
+    auction_state.arbiter = arbiter;
+    Ok(())
+}
+
+// Apply negotiated fees (basis points) to one listing; only before the first bid so
+// nobody's bid is charged differently from what they saw when placing it
+pub fn set_auction_fee_override(
+    ctx: Context<SetAuctionFeeOverride>,
+    _listing_id: [u8; 32],
+    buyer_fee_bps: u64,
+    seller_fee_bps: u64
+) -> Result<()> {
+    NftComAuction::validate_fees(buyer_fee_bps, seller_fee_bps)?;
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+    require!(!auction.status.is_closed(), ErrorCode::AuctionEnded);
+
+    auction.fee_override = Some(FeeOverride { buyer_fee_bps, seller_fee_bps });
+    Ok(())
+}
+
+pub fn set_archive_policy(
+    ctx: Context<SetArchivePolicy>,
+    retention: i64,
+    rent_destination: Pubkey
+) -> Result<()> {
+    require!(retention >= 0, ErrorCode::InvalidArchivePolicy);
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.archive_retention = retention;
+    auction_state.rent_destination = rent_destination;
+    Ok(())
+}
+
+// Set buyer and seller fees, in basis points up to MAX_FEE_BPS each
+pub fn set_fees(ctx: Context<SetFees>, buyer_fee_bps: u64, seller_fee_bps: u64) -> Result<()> {
+    NftComAuction::validate_fees(buyer_fee_bps, seller_fee_bps)?;
+    let auction_state = &mut ctx.accounts.auction_state;
+    let old = (auction_state.buyer_fee_bps, auction_state.seller_fee_bps);
+    auction_state.update_fees(buyer_fee_bps, seller_fee_bps)?;
+    record_fee_change(
+        &ctx.accounts.audit_log,
+        ctx.accounts.authority.key(),
+        old,
+        (buyer_fee_bps, seller_fee_bps)
+    )
+}
+
+// Charge buyers by the size of their bid or purchase, e.g. 5% up to 1 SOL,
+// 2.5% up to 100 SOL and 1% above, in place of the flat buyer fee; no tiers go
+// back to it. Listings with their own fees keep them, and a collection's lower
+// buyer fee still caps every tier. Bids already placed keep what they paid.
+pub fn set_buyer_fee_tiers(ctx: Context<SetBuyerFeeTiers>, tiers: Vec<BuyerFeeTier>) -> Result<()> {
+    ctx.accounts.auction_state.ensure_unfrozen()?;
+    NftComAuction::validate_buyer_fee_tiers(&tiers)?;
+    emit!(BuyerFeeTiersSet { tiers: tiers.clone() });
+    ctx.accounts.auction_state.buyer_fee_tiers = tiers;
+    Ok(())
+}
+
+// Choose between enforced and optional creator royalties; under governance this
+// is the FLAG_ENFORCE_ROYALTIES bit of a FeatureFlags change
+pub fn set_royalty_enforcement(ctx: Context<SetRoyaltyEnforcement>, enforced: bool) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    if enforced {
+        auction_state.feature_flags |= NftComAuction::FLAG_ENFORCE_ROYALTIES;
+    } else {
+        auction_state.feature_flags &= !NftComAuction::FLAG_ENFORCE_ROYALTIES;
+    }
+    Ok(())
+}
+
+// Choose between listing only NFTs of approved collections and open listing;
+// under governance this is the FLAG_CURATED_LISTINGS bit of a FeatureFlags change
+pub fn set_curated_listings(ctx: Context<SetCuratedListings>, curated: bool) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    if curated {
+        auction_state.feature_flags |= NftComAuction::FLAG_CURATED_LISTINGS;
+    } else {
+        auction_state.feature_flags &= !NftComAuction::FLAG_CURATED_LISTINGS;
+    }
+    Ok(())
+}
+
+// Point participation receipts at a Bubblegum tree whose delegate is the
+// auction state PDA, or turn them off with None
+pub fn set_receipt_tree(ctx: Context<SetReceiptTree>, merkle_tree: Option<Pubkey>) -> Result<()> {
+    ctx.accounts.auction_state.receipt_tree = merkle_tree;
+    Ok(())
+}
+
+// Emergency pause auction
+// The seller signing instead of the admin or pauser gets the seller's rules
+pub fn emergency_pause_auction(
+    ctx: Context<EmergencyPauseAuction>,
+    _listing_id: [u8; 32],
+    status: bool
+) -> Result<()> {
+    let signer = ctx.accounts.owner.key();
+    let by_admin = ctx.accounts.auction_state.may_pause(ctx.accounts.roles.as_deref(), &signer);
+    let key = ctx.accounts.auction.key();
+    let auction = &mut ctx.accounts.auction;
+    if !by_admin {
+        return set_seller_paused(auction, status);
+    }
+    let was_paused = auction.status == AuctionStatus::Paused;
+    let next = if status { AuctionStatus::Paused } else { AuctionStatus::Live };
+    auction.transition_to(next)?;
+    auction.seller_paused = false;
+    emit!(AuctionPaused {
+        listing_id: auction.listing_id,
+        paused: status,
+        by: signer,
+    });
+    AuditLog::record(
+        &ctx.accounts.audit_log,
+        AuditAction::AuctionPaused,
+        signer,
+        key,
+        was_paused as u64,
+        status as u64
+    )
+}
+
+// Pause up to MAX_PAUSE_BATCH of `owner`'s listings, passed as remaining
+// accounts. Listings that aren't live or belong to someone else are skipped,
+// as are listings with bids when the seller rather than the admin signs.
+pub fn pause_all_of<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PauseAllOf<'info>>,
+    owner: Pubkey
+) -> Result<()> {
+    let signer = ctx.accounts.signer.key();
+    let by_admin = ctx.accounts.auction_state.may_pause(ctx.accounts.roles.as_deref(), &signer);
+    // A seller pausing their own listings isn't a privileged action
+    let audit_log = by_admin.then_some(ctx.accounts.audit_log.as_ref());
+    pause_listings(ctx.remaining_accounts, signer, audit_log, |auction| {
+        auction.owner == owner && (by_admin || !auction.has_bids())
+    })
+}
+
+// Pause up to MAX_PAUSE_BATCH listings of `collection`, passed as remaining
+// accounts. Listings that aren't live or aren't in the collection are skipped.
+pub fn pause_collection<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PauseCollection<'info>>,
+    collection: Pubkey
+) -> Result<()> {
+    let audit_log = Some(ctx.accounts.audit_log.as_ref());
+    pause_listings(ctx.remaining_accounts, ctx.accounts.authority.key(), audit_log, |auction| {
+        auction.collection == Some(collection)
+    })
+}
+
+// Pause the selected listings among `accounts`. Pauses by the admin or pauser
+// are recorded in `audit_log`; the seller's own, passed without one, aren't.
+fn pause_listings<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    by: Pubkey,
+    audit_log: Option<&AccountInfo<'info>>,
+    selected: impl Fn(&AuctionDetails) -> bool
+) -> Result<()> {
+    let by_seller = audit_log.is_none();
+    require!(accounts.len() <= MAX_PAUSE_BATCH, ErrorCode::InvalidBatchSize);
+    for account in accounts {
+        let mut auction = Account::<AuctionDetails>::try_from(account)?;
+        if selected(&auction) && auction.status == AuctionStatus::Live {
+            auction.transition_to(AuctionStatus::Paused)?;
+            auction.seller_paused = by_seller;
+            auction.exit(&crate::ID)?;
+            emit!(AuctionPaused { listing_id: auction.listing_id, paused: true, by });
+            if let Some(audit_log) = audit_log {
+                AuditLog::record(audit_log, AuditAction::AuctionPaused, by, account.key(), 0, 1)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::AlienSettlementAttested;
+use crate::state::*;
+use crate::wormhole::{ AlienAttestation, PostedVaa };
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, CONSUMED_VAA_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct AttestAlienSettlement<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    /// CHECK: owner and layout are verified by `PostedVaa::load`
+    pub posted_vaa: UncheckedAccount<'info>,
+    // `init` fails if this VAA was consumed before, which is the replay protection
+    #[account(
+        init,
+        payer = payer,
+        space = ConsumedVaa::SPACE,
+        seeds = [CONSUMED_VAA_SEED, posted_vaa.key().as_ref()],
+        bump
+    )]
+    pub consumed_vaa: Account<'info, ConsumedVaa>,
+    // Relayer submitting the VAA; pays only for the replay marker
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Record a whitelisted emitter's attestation that the other chain's side of a
+// closed crowd sale is done. An NFT lock also stands for the seller's delivery,
+// so it releases any collateral as `confirm_delivery` would; it can't rescue a
+// sale whose collateral was already slashed.
+pub fn attest_alien_settlement(ctx: Context<AttestAlienSettlement>, listing_id: [u8; 32]) -> Result<()> {
+    let vaa = PostedVaa::load(&ctx.accounts.posted_vaa)?;
+    let emitter = BridgeEmitter { chain: vaa.emitter_chain, address: vaa.emitter_address };
+    require!(
+        ctx.accounts.auction_state.bridge_emitters.contains(&emitter),
+        ErrorCode::BridgeEmitterNotAllowed
+    );
+    let attestation = AlienAttestation::parse(&vaa.payload)?;
+    require!(attestation.listing_id == listing_id, ErrorCode::InvalidAttestationPayload);
+
+    ctx.accounts.consumed_vaa.version = ConsumedVaa::VERSION;
+    ctx.accounts.consumed_vaa.bump = ctx.bumps.consumed_vaa;
+
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_alien, ErrorCode::NotCrowdAuction);
+    require!(auction.is_sold(), ErrorCode::AuctionNotEnded);
+    require!(auction.alien_attestation.is_none(), ErrorCode::AlreadyAttested);
+    if attestation.kind == AttestationKind::NftLock {
+        require!(!auction.collateral_slashed, ErrorCode::DeliveryOverdue);
+        auction.delivery_confirmed = true;
+    }
+    auction.alien_attestation = Some(attestation.kind);
+
+    emit!(AlienSettlementAttested {
+        listing_id,
+        kind: attestation.kind,
+        emitter_chain: vaa.emitter_chain,
+        sequence: vaa.sequence,
+    });
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::{ AuctionDeregistered, AuctionRegistered };
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::{ AUCTION_REGISTRY_SEED, AUCTION_SEED, REGISTRY_PAGE_SEED };
+
+// Auction registry. A chain of pages listing every live auction PDA with its
+// end time, so a client pages through `get_auctions` rather than scanning the
+// program's accounts. A listing initialized with a registry page is added to
+// it; one listed without, or before the registry existed, is added with
+// `register_auction`. Ending an auction with its page passed takes it out
+// again, and `deregister_auction` does so for listings closed any other way.
+// Pages are opened by whoever needs room, in order. The auction records its
+// page, and prune_archives leaves it alone while it's still listed.
+
+#[derive(Accounts)]
+pub struct InitAuctionRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = AuctionRegistry::SPACE,
+        seeds = [AUCTION_REGISTRY_SEED],
+        bump
+    )]
+    pub registry: Account<'info, AuctionRegistry>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u32)]
+pub struct OpenRegistryPage<'info> {
+    #[account(mut, seeds = [AUCTION_REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Account<'info, AuctionRegistry>,
+    #[account(
+        init,
+        payer = payer,
+        space = AuctionRegistryPage::SPACE,
+        seeds = [REGISTRY_PAGE_SEED, &index.to_le_bytes()],
+        bump
+    )]
+    pub registry_page: AccountLoader<'info, AuctionRegistryPage>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct RegisterAuction<'info> {
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(
+        mut,
+        seeds = [REGISTRY_PAGE_SEED, &registry_page.load()?.index.to_le_bytes()],
+        bump = registry_page.load()?.bump
+    )]
+    pub registry_page: AccountLoader<'info, AuctionRegistryPage>,
+}
+
+pub fn init_auction_registry(ctx: Context<InitAuctionRegistry>) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    registry.version = AuctionRegistry::VERSION;
+    registry.pages = 0;
+    registry.bump = ctx.bumps.registry;
+    Ok(())
+}
+
+pub fn open_registry_page(ctx: Context<OpenRegistryPage>, index: u32) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    require!(index == registry.pages, ErrorCode::InvalidRegistryPage);
+
+    let mut page = ctx.accounts.registry_page.load_init()?;
+    page.index = index;
+    page.bump = ctx.bumps.registry_page;
+    page.version = AuctionRegistryPage::VERSION;
+
+    registry.pages = registry.pages.try_add(1)?;
+    Ok(())
+}
+
+// List an open auction on the page, or bring its end time there up to date if
+// it's already listed on it; anyone may crank this
+pub fn register_auction(ctx: Context<RegisterAuction>, listing_id: [u8; 32]) -> Result<()> {
+    let key = ctx.accounts.auction.key();
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    let mut page = ctx.accounts.registry_page.load_mut()?;
+    match auction.registry_page {
+        Some(index) => {
+            require!(index == page.index, ErrorCode::InvalidRegistryPage);
+            let position = page.position(&key).ok_or(ErrorCode::InvalidRegistryPage)?;
+            page.entries[position].end_time = auction.end_time;
+        }
+        None => {
+            register(&mut page, key, auction)?;
+        }
+    }
+
+    emit!(AuctionRegistered {
+        listing_id,
+        auction: key,
+        page: page.index,
+        end_time: auction.end_time,
+    });
+    Ok(())
+}
+
+// Take a closed auction off its registry page; anyone may crank this
+pub fn deregister_auction(ctx: Context<RegisterAuction>, listing_id: [u8; 32]) -> Result<()> {
+    let key = ctx.accounts.auction.key();
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.status.is_closed(), ErrorCode::AuctionNotEnded);
+    let mut page = ctx.accounts.registry_page.load_mut()?;
+    deregister(&mut page, key, auction)?;
+
+    emit!(AuctionDeregistered { listing_id, auction: key, page: page.index });
+    Ok(())
+}
+
+// Add the auction at `key` to the page and record the page on it
+pub(crate) fn register(
+    page: &mut AuctionRegistryPage,
+    key: Pubkey,
+    auction: &mut AuctionDetails
+) -> Result<()> {
+    require!(!page.is_full(), ErrorCode::RegistryPageFull);
+    page.push(RegistryEntry { auction: key, end_time: auction.end_time })?;
+    auction.registry_page = Some(page.index);
+    Ok(())
+}
+
+// Remove the auction at `key` from the page it's listed on
+pub(crate) fn deregister(
+    page: &mut AuctionRegistryPage,
+    key: Pubkey,
+    auction: &mut AuctionDetails
+) -> Result<()> {
+    require!(auction.registry_page == Some(page.index), ErrorCode::InvalidRegistryPage);
+    let position = page.position(&key).ok_or(ErrorCode::InvalidRegistryPage)?;
+    page.swap_remove(position)?;
+    auction.registry_page = None;
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{ Mint, Token, TokenAccount };
+use crate::errors::ErrorCode;
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::initialize_auction::{ list_nft, ListedNft };
+use crate::state::*;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    AUCTION_TEMPLATE_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_SEED,
+    NFT_ESCROW_SEED,
+    SELLER_PROFILE_SEED,
+    VAULT_SEED,
+};
+
+// Listing templates. A seller who lists often stores the terms they list on
+// once, per template id, and then lists each NFT with only its name; the rest
+// of the listing takes the marketplace defaults, as with unset overrides.
+
+#[derive(Accounts)]
+#[instruction(template_id: u16)]
+pub struct CreateAuctionTemplate<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = AuctionTemplate::SPACE,
+        seeds = [AUCTION_TEMPLATE_SEED, owner.key().as_ref(), &template_id.to_le_bytes()],
+        bump
+    )]
+    pub template: Account<'info, AuctionTemplate>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAuctionTemplate<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_TEMPLATE_SEED, owner.key().as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub template: Account<'info, AuctionTemplate>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAuctionTemplate<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [AUCTION_TEMPLATE_SEED, owner.key().as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub template: Account<'info, AuctionTemplate>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+// The accounts of `initialize_auction`, less the fee approval, plus the template
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct InitializeFromTemplate<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        seeds = [AUCTION_TEMPLATE_SEED, owner.key().as_ref(), &template.template_id.to_le_bytes()],
+        bump = template.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub template: Account<'info, AuctionTemplate>,
+    #[account(
+        init,
+        payer = payer,
+        space = AuctionDetails::space(BidHistory::DEFAULT_CAPACITY),
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    pub owner: Signer<'info>,
+    // Pays the rent of the listing's accounts: the owner, or a marketplace sponsoring it
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, owner.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Registry entry of the collection being listed, if any
+    #[account(
+        seeds = [COLLECTION_SEED, collection_config.collection.as_ref()],
+        bump = collection_config.bump
+    )]
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+    // Required with a collection config, and so while the marketplace curates listings
+    /// CHECK: the NFT's Token Metadata account, checked against the mint when read
+    pub nft_metadata: Option<UncheckedAccount<'info>>,
+    #[account(
+        constraint = nft_mint.decimals == 0 && nft_mint.supply == 1 @ ErrorCode::InvalidNftMint
+    )]
+    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = owner,
+        constraint = seller_nft_account.amount == 1 @ ErrorCode::NftNotHeld
+    )]
+    pub seller_nft_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        token::mint = nft_mint,
+        token::authority = auction_state,
+        seeds = [NFT_ESCROW_SEED, nft_mint.key().as_ref()],
+        bump
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_auction_template(
+    ctx: Context<CreateAuctionTemplate>,
+    template_id: u16,
+    terms: TemplateTerms
+) -> Result<()> {
+    terms.validate()?;
+    let template = &mut ctx.accounts.template;
+    template.version = AuctionTemplate::VERSION;
+    template.owner = ctx.accounts.owner.key();
+    template.template_id = template_id;
+    template.terms = terms;
+    template.bump = ctx.bumps.template;
+    Ok(())
+}
+
+// Listings already created from the template keep the terms they were created with
+pub fn update_auction_template(
+    ctx: Context<UpdateAuctionTemplate>,
+    terms: TemplateTerms
+) -> Result<()> {
+    terms.validate()?;
+    ctx.accounts.template.terms = terms;
+    Ok(())
+}
+
+pub fn close_auction_template(_ctx: Context<CloseAuctionTemplate>) -> Result<()> {
+    Ok(())
+}
+
+// List the NFT named `name` on the template's terms. `listing_id` is derived
+// from the owner and name as for any listing.
+pub fn initialize_from_template(
+    ctx: Context<InitializeFromTemplate>,
+    listing_id: [u8; 32],
+    name: String
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let terms = ctx.accounts.template.terms;
+    let overrides = terms.overrides(name, Clock::get()?.unix_timestamp)?;
+
+    let accounts = &mut ctx.accounts;
+    let auction = list_nft(
+        ListedNft {
+            auction_state: &accounts.auction_state,
+            owner: &accounts.owner,
+            payer: &accounts.payer,
+            vault: &accounts.vault,
+            seller_profile: accounts.seller_profile.as_mut(),
+            collection_config: accounts.collection_config.as_deref(),
+            nft_metadata: accounts.nft_metadata.as_ref().map(|metadata| metadata.as_ref()),
+            nft_mint: &accounts.nft_mint,
+            seller_nft_account: &accounts.seller_nft_account,
+            nft_escrow: &accounts.nft_escrow,
+            token_program: &accounts.token_program,
+            system_program: &accounts.system_program,
+        },
+        listing_id,
+        terms.minimum,
+        &overrides,
+        ctx.bumps.auction,
+        ctx.bumps.vault
+    )?;
+    ctx.accounts.auction.set_inner(auction);
+    Ok(())
+}
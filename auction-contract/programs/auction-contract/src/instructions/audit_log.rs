@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::*;
+use crate::{ AUCTION_STATE_SEED, AUDIT_LOG_SEED };
+
+// Privileged action log. Once the admin initializes the AuditLog, every
+// instruction that changes fees, pauses a listing on the admin's authority,
+// updates the blacklist, hands over the authority or withdraws from a fee
+// treasury takes it at its fixed address and appends an entry, so it can't
+// be left out. Until it exists the address is empty and nothing is recorded.
+
+#[derive(Accounts)]
+pub struct InitAuditLog<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(init, payer = payer, space = AuditLog::SPACE, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: Account<'info, AuditLog>,
+    pub authority: Signer<'info>,
+    // Rent payer; needn't be the authority
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_audit_log(ctx: Context<InitAuditLog>) -> Result<()> {
+    let audit_log = &mut ctx.accounts.audit_log;
+    audit_log.version = AuditLog::VERSION;
+    audit_log.recorded = 0;
+    audit_log.digest = [0; 32];
+    audit_log.entries = Vec::new();
+    audit_log.bump = ctx.bumps.audit_log;
+    Ok(())
+}
+
+// Record a change of the marketplace fees from `old` to `new`, each a
+// (buyer fee, seller fee) pair in basis points
+pub(crate) fn record_fee_change(
+    audit_log: &AccountInfo,
+    actor: Pubkey,
+    old: (u64, u64),
+    new: (u64, u64)
+) -> Result<()> {
+    let target = Pubkey::default();
+    AuditLog::record(audit_log, AuditAction::BuyerFeeChanged, actor, target, old.0, new.0)?;
+    AuditLog::record(audit_log, AuditAction::SellerFeeChanged, actor, target, old.1, new.1)
+}
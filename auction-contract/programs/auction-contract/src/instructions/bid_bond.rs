@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use crate::emitter::{ self, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::{ BidBondPosted, BidBondReleased, BidBondSet };
+use crate::invariants::assert_funds_conserved;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, BID_RECEIPT_SEED, VAULT_SEED };
+
+// Bid bonds. A seller can have each bidder lock a refundable deposit, on top of
+// and apart from their bids, to keep throwaway wallets off a listing. The bond
+// is taken in the auction's vault with the bidder's first direct bid, recorded
+// on their bid receipt, and held until the auction closes. Bidders who lost,
+// and everyone on a cancelled or voided listing, then take theirs back with
+// `refund_bid_bond`. The winner's stays until they complete the sale by
+// claiming the NFT; one who hasn't within AuctionDetails::BOND_CLAIM_WINDOW of
+// the close forfeits it to the seller through `forfeit_bid_bond`. Bonds are in
+// lamports, so only SOL listings take them.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SetBidBond<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct RefundBidBond<'info> {
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid_receipt.bump
+    )]
+    pub bid_receipt: Account<'info, BidReceipt>,
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ForfeitBidBond<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    // The winner's receipt
+    #[account(
+        mut,
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), bid_receipt.bidder.as_ref()],
+        bump = bid_receipt.bump
+    )]
+    pub bid_receipt: Account<'info, BidReceipt>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Require a bond of `bond` lamports from every bidder; 0 drops the requirement.
+// Only before the first bid, so everyone bids under the same terms.
+pub fn set_bid_bond(ctx: Context<SetBidBond>, listing_id: [u8; 32], bond: u64) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+    require!(bond == 0 || auction.payment_mint.is_none(), ErrorCode::WrongPaymentMint);
+    auction.bid_bond = bond;
+
+    emit!(BidBondSet { listing_id, bond });
+    Ok(())
+}
+
+// Take the listing's bond from `payer` into the vault for the bidder on
+// `receipt`, unless they already have one locked
+pub(crate) fn lock<'info>(
+    auction: &mut AuctionDetails,
+    receipt: &mut BidReceipt,
+    payer: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    events: &EventAuthority
+) -> Result<()> {
+    let bond = auction.bid_bond;
+    if bond == 0 || receipt.bond > 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(system_program.clone(), system_program::Transfer {
+            from: payer.clone(),
+            to: vault.clone(),
+        }),
+        bond
+    )?;
+    receipt.bond = bond;
+    auction.bonds_held = auction.bonds_held.try_add(bond)?;
+
+    emitter::emit(events, BidBondPosted {
+        listing_id: auction.listing_id,
+        bidder: receipt.bidder,
+        amount: bond,
+    })
+}
+
+pub fn refund_bid_bond(ctx: Context<RefundBidBond>, listing_id: [u8; 32]) -> Result<()> {
+    let bidder = ctx.accounts.bidder.key();
+    let auction = &mut ctx.accounts.auction;
+    let refundable = match auction.status {
+        AuctionStatus::Cancelled | AuctionStatus::Voided => true,
+        _ if auction.is_winner(&bidder) => auction.has_taken_delivery(&bidder),
+        status => status.is_closed(),
+    };
+    require!(refundable, ErrorCode::BidBondLocked);
+
+    release(
+        auction,
+        &mut ctx.accounts.bid_receipt,
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.bidder.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &listing_id,
+        false
+    )
+}
+
+pub fn forfeit_bid_bond(ctx: Context<ForfeitBidBond>, listing_id: [u8; 32]) -> Result<()> {
+    let winner = ctx.accounts.bid_receipt.bidder;
+    let auction = &mut ctx.accounts.auction;
+    require!(
+        auction.is_sold() && auction.is_winner(&winner) && !auction.has_taken_delivery(&winner),
+        ErrorCode::BidBondLocked
+    );
+    let deadline = auction.closed_at.saturating_add(AuctionDetails::BOND_CLAIM_WINDOW);
+    require!(Clock::get()?.unix_timestamp >= deadline, ErrorCode::BidBondLocked);
+
+    release(
+        auction,
+        &mut ctx.accounts.bid_receipt,
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &listing_id,
+        true
+    )
+}
+
+// Pay the bond on `receipt` out of the vault to `to`
+pub(crate) fn release<'info>(
+    auction: &mut AuctionDetails,
+    receipt: &mut BidReceipt,
+    vault_info: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    listing_id: &[u8; 32],
+    forfeited: bool
+) -> Result<()> {
+    let amount = std::mem::take(&mut receipt.bond);
+    require!(amount > 0, ErrorCode::NothingToWithdraw);
+    auction.bonds_held = auction.bonds_held.try_sub(amount)?;
+    vault::pay_out(vault_info, listing_id, auction.vault_bump, to, system_program, amount)?;
+    assert_funds_conserved(vault_info, auction)?;
+
+    emit!(BidBondReleased {
+        listing_id: *listing_id,
+        bidder: receipt.bidder,
+        to: to.key(),
+        amount,
+        forfeited,
+    });
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::BidderBalanceChanged;
+use crate::state::*;
+use crate::BIDDER_PROFILE_SEED;
+
+#[derive(Accounts)]
+pub struct InitBidderProfile<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = BidderProfile::SPACE,
+        seeds = [BIDDER_PROFILE_SEED, bidder.key().as_ref()],
+        bump
+    )]
+    pub bidder_profile: Account<'info, BidderProfile>,
+    /// CHECK: only used as a seed; the profile belongs to this key
+    pub bidder: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Moves the bidder's own balance in or out
+#[derive(Accounts)]
+pub struct ManageBidderBalance<'info> {
+    #[account(
+        mut,
+        seeds = [BIDDER_PROFILE_SEED, bidder.key().as_ref()],
+        bump = bidder_profile.bump,
+        has_one = bidder
+    )]
+    pub bidder_profile: Account<'info, BidderProfile>,
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_bidder_profile(ctx: Context<InitBidderProfile>) -> Result<()> {
+    let profile = &mut ctx.accounts.bidder_profile;
+    profile.bidder = ctx.accounts.bidder.key();
+    profile.nonce_floor = 0;
+    profile.used_nonces = 0;
+    profile.version = BidderProfile::VERSION;
+    profile.bump = ctx.bumps.bidder_profile;
+    Ok(())
+}
+
+// Lamports on the profile above its rent-exempt minimum
+pub fn balance(profile: &AccountInfo) -> Result<u64> {
+    let rent_exempt = Rent::get()?.minimum_balance(profile.data_len());
+    Ok(profile.lamports().saturating_sub(rent_exempt))
+}
+
+// Add `amount` to the balance signed bid orders draw on
+pub fn fund_bidder_profile(ctx: Context<ManageBidderBalance>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InsufficientProfileBalance);
+    let profile = ctx.accounts.bidder_profile.to_account_info();
+    system_program::transfer(
+        CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.bidder.to_account_info(),
+            to: profile.clone(),
+        }),
+        amount
+    )?;
+
+    emit!(BidderBalanceChanged { bidder: ctx.accounts.bidder.key(), balance: balance(&profile)? });
+    Ok(())
+}
+
+// Take `amount` of the balance back; orders the bidder has signed but which
+// haven't settled simply fail once it no longer covers them
+pub fn withdraw_bidder_balance(ctx: Context<ManageBidderBalance>, amount: u64) -> Result<()> {
+    let profile = ctx.accounts.bidder_profile.to_account_info();
+    require!(amount > 0 && amount <= balance(&profile)?, ErrorCode::InsufficientProfileBalance);
+    profile.sub_lamports(amount)?;
+    ctx.accounts.bidder.add_lamports(amount)?;
+
+    emit!(BidderBalanceChanged { bidder: ctx.accounts.bidder.key(), balance: balance(&profile)? });
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::*;
+use crate::{ AUCTION_STATE_SEED, AUDIT_LOG_SEED, BLACKLIST_SEED, ROLES_SEED };
+
+#[derive(Accounts)]
+#[instruction(address: Pubkey)]
+pub struct AddToBlacklist<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.authority == authority.key() ||
+        Roles::grants(roles.as_deref(), Role::BlacklistManager, &authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(
+        init,
+        payer = payer,
+        space = BlacklistEntry::SPACE,
+        seeds = [BLACKLIST_SEED, address.as_ref()],
+        bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    // The admin or the blacklist manager
+    pub authority: Signer<'info>,
+    // Pays the rent, so a multisig vault holding the authority needn't hold SOL
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromBlacklist<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.authority == authority.key() ||
+        Roles::grants(roles.as_deref(), Role::BlacklistManager, &authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [BLACKLIST_SEED, blacklist_entry.address.as_ref()],
+        bump = blacklist_entry.bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+// Bar `address` from new listings and bids. Its existing listings and bids
+// stay as they are; admins can pause or cancel those separately.
+pub fn add_to_blacklist(ctx: Context<AddToBlacklist>, address: Pubkey) -> Result<()> {
+    let entry = &mut ctx.accounts.blacklist_entry;
+    entry.address = address;
+    entry.version = BlacklistEntry::VERSION;
+    entry.bump = ctx.bumps.blacklist_entry;
+    AuditLog::record(
+        &ctx.accounts.audit_log,
+        AuditAction::BlacklistUpdated,
+        ctx.accounts.authority.key(),
+        address,
+        0,
+        1
+    )
+}
+
+pub fn remove_from_blacklist(ctx: Context<RemoveFromBlacklist>) -> Result<()> {
+    AuditLog::record(
+        &ctx.accounts.audit_log,
+        AuditAction::BlacklistUpdated,
+        ctx.accounts.authority.key(),
+        ctx.accounts.blacklist_entry.address,
+        1,
+        0
+    )
+}
+
+// `entry` is the [BLACKLIST_SEED, address] PDA, checked by the caller's seeds
+// constraint. Only add_to_blacklist can put data there, so an empty account
+// means the address is in good standing.
+pub(crate) fn ensure_not_blacklisted(entry: &AccountInfo) -> Result<()> {
+    require!(entry.data_is_empty(), ErrorCode::Blacklisted);
+    Ok(())
+}
+
+// As ensure_not_blacklisted, for an `entry` no seeds constraint has checked,
+// such as that of a bridged bid's bidder, who is only known from the payload
+pub(crate) fn ensure_address_not_blacklisted(entry: &AccountInfo, address: &Pubkey) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[BLACKLIST_SEED, address.as_ref()],
+        &crate::ID
+    );
+    require_keys_eq!(entry.key(), expected, ErrorCode::BlacklistEntryMismatch);
+    ensure_not_blacklisted(entry)
+}
@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::instructions::place_bid::apply_bid;
+use crate::state::*;
+use crate::wormhole::{ BridgedBid, PostedVaa };
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BRIDGE_VAULT_SEED,
+    CONSUMED_VAA_SEED,
+    VAULT_SEED,
+};
+
+#[derive(Accounts)]
+pub struct ManageBridgeEmitters<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct BridgeBid<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: owner and layout are verified by `PostedVaa::load`
+    pub posted_vaa: UncheckedAccount<'info>,
+    // `init` fails if this VAA was consumed before, which is the replay protection
+    #[account(
+        init,
+        payer = payer,
+        space = ConsumedVaa::SPACE,
+        seeds = [CONSUMED_VAA_SEED, posted_vaa.key().as_ref()],
+        bump
+    )]
+    pub consumed_vaa: Account<'info, ConsumedVaa>,
+    // Liquidity for bridged bids, topped up by the bridge operator as it
+    // redeems the matching transfers on the token bridge
+    #[account(mut, seeds = [BRIDGE_VAULT_SEED], bump)]
+    pub bridge_vault: SystemAccount<'info>,
+    /// CHECK: the blacklist flag of the payload's bidder; must not exist, checked
+    /// against the bidder when the bid is applied
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Only needed once the auction's inline bidder list is full
+    #[account(mut, has_one = auction)]
+    pub bid_page: Option<AccountLoader<'info, BidPage>>,
+    // Relayer submitting the VAA; pays only for the replay marker
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<BridgeBid>, listing_id: [u8; 32]) -> Result<()> {
+    let vaa = PostedVaa::load(&ctx.accounts.posted_vaa)?;
+    let emitter = BridgeEmitter { chain: vaa.emitter_chain, address: vaa.emitter_address };
+    require!(
+        ctx.accounts.auction_state.bridge_emitters.contains(&emitter),
+        ErrorCode::BridgeEmitterNotAllowed
+    );
+
+    let bid = BridgedBid::parse(&vaa.payload)?;
+    require!(bid.listing_id == listing_id, ErrorCode::InvalidBridgePayload);
+    require!(bid.amount > 0, ErrorCode::MinimumBidError);
+
+    ctx.accounts.consumed_vaa.version = ConsumedVaa::VERSION;
+    ctx.accounts.consumed_vaa.bump = ctx.bumps.consumed_vaa;
+
+    // Bid bonds are only posted, and bid limits only tracked, through place_bid
+    require!(ctx.accounts.auction.bid_bond == 0, ErrorCode::BidBondRequired);
+    require!(!ctx.accounts.auction.has_bid_limits(), ErrorCode::BidLimitsApply);
+
+    // Credit the bridged funds to escrow, then bid exactly what arrived
+    let escrow = ctx.accounts.vault.to_account_info();
+    let balance_before = escrow.lamports();
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.bridge_vault.to_account_info(),
+                to: escrow.clone(),
+            },
+            &[&[BRIDGE_VAULT_SEED, &[ctx.bumps.bridge_vault]]]
+        ),
+        bid.amount
+    )?;
+    let deposited = escrow.lamports() - balance_before;
+
+    let mut bid_page = ctx.accounts.bid_page.as_ref().map(|page| page.load_mut()).transpose()?;
+    apply_bid(
+        &ctx.accounts.auction_state,
+        &mut ctx.accounts.auction,
+        &escrow,
+        bid.bidder,
+        ctx.accounts.bridge_vault.key(),
+        &ctx.accounts.blacklist_entry,
+        deposited,
+        PositionStore::Accounts(bid_page.as_deref_mut()),
+        false,
+        &[],
+        None,
+        None,
+        0,
+        None,
+        &None
+    )?;
+    Ok(())
+}
+
+pub fn add_bridge_emitter(
+    ctx: Context<ManageBridgeEmitters>,
+    chain: u16,
+    address: [u8; 32]
+) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    let emitter = BridgeEmitter { chain, address };
+    if !auction_state.bridge_emitters.contains(&emitter) {
+        require!(
+            auction_state.bridge_emitters.len() < NftComAuction::MAX_BRIDGE_EMITTERS,
+            ErrorCode::BridgeEmitterWhitelistFull
+        );
+        auction_state.bridge_emitters.push(emitter);
+    }
+    Ok(())
+}
+
+pub fn remove_bridge_emitter(
+    ctx: Context<ManageBridgeEmitters>,
+    chain: u16,
+    address: [u8; 32]
+) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.bridge_emitters.retain(|emitter| *emitter != BridgeEmitter { chain, address });
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{ self, Mint, Token, TokenAccount, Transfer };
+use crate::errors::ErrorCode;
+use crate::instructions::nft_escrow::release_escrowed_nft;
+use crate::settlement;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, NFT_ESCROW_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct AddToBundle<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        constraint = nft_mint.decimals == 0 && nft_mint.supply == 1 @ ErrorCode::InvalidNftMint
+    )]
+    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = owner,
+        constraint = seller_nft_account.amount == 1 @ ErrorCode::NftNotHeld
+    )]
+    pub seller_nft_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        token::mint = nft_mint,
+        token::authority = auction_state,
+        seeds = [NFT_ESCROW_SEED, nft_mint.key().as_ref()],
+        bump
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Escrow another NFT with a listing, turning it into a bundle the winner gets
+// in full. Only Legacy listings escrowed by the program can be bundled, and
+// only before the first bid so nobody bids on a different lot than they see.
+pub fn add_to_bundle(ctx: Context<AddToBundle>, _listing_id: [u8; 32]) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.status == AuctionStatus::Live, ErrorCode::AuctionEnded);
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+    require!(
+        settlement::adapter(auction.asset_standard).bundles() && auction.nft_mint.is_some(),
+        ErrorCode::BundleUnavailable
+    );
+    require!(auction.bundle_mints.len() < AuctionDetails::MAX_BUNDLE_NFTS, ErrorCode::BundleFull);
+
+    token::transfer(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.seller_nft_account.to_account_info(),
+            to: ctx.accounts.nft_escrow.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        }),
+        1
+    )?;
+    auction.bundle_mints.push(ctx.accounts.nft_mint.key());
+    Ok(())
+}
+
+// Move every bundled NFT out of escrow to `owner`. `accounts` holds an
+// (escrow, destination token account) pair per bundled mint, in bundle order.
+pub(crate) fn release_bundle<'info>(
+    auction_state: &Account<'info, NftComAuction>,
+    token_program: &Program<'info, Token>,
+    mints: &[Pubkey],
+    accounts: &'info [AccountInfo<'info>],
+    owner: Pubkey,
+    rent_to: &AccountInfo<'info>
+) -> Result<()> {
+    require!(accounts.len() >= 2 * mints.len(), ErrorCode::NftEscrowMismatch);
+
+    for (mint, pair) in mints.iter().zip(accounts.chunks(2)) {
+        let nft_escrow = Account::<TokenAccount>::try_from(&pair[0])?;
+        let (address, _) = Pubkey::find_program_address(&[NFT_ESCROW_SEED, mint.as_ref()], &crate::ID);
+        require_keys_eq!(nft_escrow.key(), address, ErrorCode::NftEscrowMismatch);
+        let destination = Account::<TokenAccount>::try_from(&pair[1])?;
+        require_keys_eq!(destination.mint, *mint, ErrorCode::NftEscrowMismatch);
+        require_keys_eq!(destination.owner, owner, ErrorCode::NftEscrowMismatch);
+
+        release_escrowed_nft(auction_state, token_program, &nft_escrow, &pair[1], rent_to)?;
+    }
+    Ok(())
+}
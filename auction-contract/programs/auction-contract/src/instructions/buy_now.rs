@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::TokenAccount;
+use anchor_spl::token_interface;
+use crate::allowlist;
+use crate::cpi_guard::assert_cpi_caller_allowed;
+use crate::emitter::{ self, event_authority, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::{ AuctionEnded, BidRefunded };
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::invariants::assert_funds_conserved;
+use crate::math::{ self, CheckedMath };
+use crate::state::*;
+use crate::token_gate;
+use crate::vault::Escrow;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_FEE_SEED,
+    FEATURE_FLAGS_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+    WSOL_UNWRAP_SEED,
+};
+
+// Every other inline bidder's wallet (their token account, on a listing paid in
+// a token) follows as a remaining account, in the auction's bid order, to be
+// refunded on the spot. Positions on overflow pages
+// or in a compressed tree stay claimable through the usual withdrawals.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct BuyNow<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: the buyer's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, buyer.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Only needed when the buyer's position is on an overflow page, or they have
+    // none and the inline list is full
+    #[account(mut, has_one = auction)]
+    pub bid_page: Option<AccountLoader<'info, BidPage>>,
+    // The buyer's holding of the gate mint; only needed on token-gated listings
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    // The listing's collection fee config, if it has one
+    #[account(
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Option<Account<'info, CollectionFeeConfig>>,
+    // Listings paid in a token only: the listing's token vault, and the buyer's
+    // token account the price is paid from
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(mut)]
+    pub buyer_token_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_program: Option<Interface<'info, token_interface::TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+    // Wrapped SOL listings only: the account payouts to wallets are unwrapped
+    // through, and the native mint it's opened for
+    /// CHECK: created and closed again within the instruction by the escrow
+    #[account(mut, seeds = [WSOL_UNWRAP_SEED, listing_id.as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+    pub feature_flags: Account<'info, FeatureFlags>,
+    /// CHECK: the instructions sysvar, used to identify CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// End the auction at its buy-now price, refund the other bidders and leave the
+// sale ready for `claim_nft` and `claim_proceeds`. Whatever the buyer already
+// bid counts toward the price; the buyer fee applies to the rest. A private
+// listing's buyer needs an allowlist `proof`, and a token-gated listing's buyer
+// its gate token account, same as its bidders. Installment listings have no
+// buy-now: their sale price is the winning bid times the installment count.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyNow<'info>>,
+    listing_id: [u8; 32],
+    proof: Vec<[u8; 32]>
+) -> Result<()> {
+    let events: EventAuthority = event_authority!(ctx);
+    ctx.accounts.feature_flags.ensure_enabled(FeatureFlags::BUY_NOW)?;
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    assert_cpi_caller_allowed(
+        &ctx.accounts.instructions,
+        &ctx.accounts.auction_state.aggregator_programs
+    )?;
+    let buyer = ctx.accounts.buyer.key();
+    let auction = &mut ctx.accounts.auction;
+
+    require!(!auction.is_alien, ErrorCode::ContributionsOnly);
+    require!(auction.ticket_price == 0, ErrorCode::TicketsOnly);
+    require!(!auction.delegated_bidding, ErrorCode::DelegatedBidsOnly);
+    let price = auction.buy_now_price.ok_or(ErrorCode::BuyNowUnavailable)?;
+    require!(auction.installment_plan.is_none(), ErrorCode::BuyNowUnavailable);
+    require!(buyer != auction.owner, ErrorCode::BidderIsOwner);
+    allowlist::check(auction, &buyer, &proof)?;
+    token_gate::check(auction, &buyer, ctx.accounts.gate_token_account.as_deref())?;
+    match auction.status {
+        AuctionStatus::Live => {}
+        AuctionStatus::Paused => {
+            return Err(ErrorCode::AuctionPaused.into());
+        }
+        _ => {
+            return Err(ErrorCode::AuctionEnded.into());
+        }
+    }
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= auction.end_time, ErrorCode::AuctionEnded);
+
+    let mut bid_page = ctx.accounts.bid_page.as_ref().map(|page| page.load_mut()).transpose()?;
+    let already_bid = auction.position_of(&buyer, bid_page.as_deref());
+    let remainder = price.saturating_sub(already_bid);
+    // The tier is that of the whole price, however much of it was already bid
+    let buyer_fee_bps = ctx.accounts.auction_state.buyer_fee_bps_for(
+        auction,
+        ctx.accounts.collection_fee_config.as_deref(),
+        price
+    );
+    let fee = math::compute_fee(remainder, buyer_fee_bps)?;
+
+    let escrow = Escrow::new(
+        auction,
+        &listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?.unwrapping(
+        ctx.accounts.unwrap_account.as_ref(),
+        ctx.accounts.native_mint.as_ref(),
+        ctx.accounts.buyer.to_account_info()
+    );
+    let buyer_info = ctx.accounts.buyer.to_account_info();
+    let buyer_token_account = ctx.accounts.buyer_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let from = escrow.account_of(&buyer_info, buyer_token_account.as_ref())?;
+    // A mint's transfer fee comes out of the buyer fee first; the remainder
+    // itself has to arrive in full
+    let deposited = escrow.deposit(&from, &buyer_info, remainder.try_add(fee)?)?;
+    require!(deposited >= remainder, ErrorCode::BidTooLow);
+    auction.fees = auction.fees.try_add(deposited.try_sub(remainder)?)?;
+    auction.total_amount = auction.total_amount.try_add(remainder)?;
+    if remainder > 0 {
+        auction.record_bid(
+            buyer,
+            remainder,
+            now,
+            PositionStore::Accounts(bid_page.as_deref_mut()),
+            false,
+            None
+        )?;
+        auction.bid_history.push(BidRecord { bidder: buyer, amount: remainder, time: now })?;
+    }
+
+    auction.highest_bid = price;
+    auction.highest_bidder = buyer;
+    auction.highest_max_bid = price;
+    // A buy-now sale is at the buy-now price, second-price listing or not
+    auction.second_bid = price;
+    auction.end_time = now;
+    auction.transition_to(AuctionStatus::Ended)?;
+
+    // Refund every other inline position
+    let mut refund_accounts = ctx.remaining_accounts.iter();
+    for bid in auction.bids.iter_mut().filter(|bid| bid.bidder != buyer && bid.amount > 0) {
+        let wallet = refund_accounts.next().ok_or(ErrorCode::RefundAccountsMismatch)?;
+        require!(escrow.pays(wallet, &bid.bidder), ErrorCode::RefundAccountsMismatch);
+        let amount = std::mem::take(&mut bid.amount);
+        escrow.pay(wallet, amount)?;
+        emitter::emit(&events, BidRefunded {
+            listing_id,
+            bidder: bid.bidder,
+            amount,
+        })?;
+    }
+    require!(refund_accounts.next().is_none(), ErrorCode::RefundAccountsMismatch);
+    escrow.settle()?;
+
+    assert_funds_conserved(escrow.holding(), auction)?;
+
+    emitter::emit(&events, AuctionEnded {
+        listing_id,
+        winner: buyer,
+        amount: price,
+        buy_now: true,
+    })?;
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::emitter::{ self, event_authority, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::AuctionCancelled;
+use crate::instructions::nft_escrow::SellerNftReturn;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED };
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct CancelAuction<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub nft: SellerNftReturn<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Delist an auction nobody has bid on yet, handing the NFT back to the seller
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelAuction<'info>>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let events: EventAuthority = event_authority!(ctx);
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.has_bids(), ErrorCode::AuctionHasBids);
+    auction.transition_to(AuctionStatus::Cancelled)?;
+
+    let owner = ctx.accounts.owner.to_account_info();
+    ctx.accounts.nft.release_to_seller(
+        &ctx.accounts.auction_state,
+        auction,
+        &owner,
+        &owner,
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.remaining_accounts
+    )?;
+
+    emitter::emit(&events, AuctionCancelled { listing_id, owner: owner.key() })?;
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::errors::ErrorCode;
+use crate::events::CandleAuctionClosed;
+use crate::invariants::assert_funds_conserved;
+use crate::math::CheckedMath;
+use crate::randomness;
+use crate::state::*;
+use crate::vault::Escrow;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+    WSOL_UNWRAP_SEED,
+};
+
+// Candle auctions take bids until `end_time` like any other, but the auction
+// is decided at a cutoff drawn afterwards from somewhere in its final
+// `candle_window`, so a last-second bid may well come too late to count. The
+// close is two-phase: the seller commits Switchboard randomness with
+// `commit_randomness` once the auction has run out, and anyone closes it with
+// the revealed value. Whoever led at the cutoff wins at the bid they led with.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct CloseCandleAuction<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: checked against the auction's commitment by `randomness::consume`
+    pub randomness_account: UncheckedAccount<'info>,
+    // Anyone may close a candle auction once its randomness is revealed, and earns the keeper tip
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    // Listings paid in a token only, as for `end_auction`
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: must be the caller's token account for the payment mint
+    #[account(mut)]
+    pub caller_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    // Wrapped SOL listings only: the account payouts to wallets are unwrapped
+    // through, and the native mint it's opened for
+    /// CHECK: created and closed again within the instruction by the escrow
+    #[account(mut, seeds = [WSOL_UNWRAP_SEED, listing_id.as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Draw the cutoff and settle the lead as it stood then. A lead below the
+// reserve, or no lead at all, cancels the auction so every bidder can withdraw.
+pub fn handler(ctx: Context<CloseCandleAuction>, listing_id: [u8; 32]) -> Result<()> {
+    let clock = Clock::get()?;
+    let keeper_tip = ctx.accounts.auction_state.keeper_tip;
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.candle_window > 0, ErrorCode::NotCandleAuction);
+    let commitment = auction.randomness.take().ok_or(ErrorCode::RandomnessNotCommitted)?;
+    let value = randomness::consume(&commitment, &ctx.accounts.randomness_account, &clock)?;
+
+    // The cutoff falls after the window opens and no later than the end; only
+    // bids placed before it count
+    let window_start = auction.end_time.try_sub(auction.candle_window)?;
+    let roll = u64::from_le_bytes(value[..8].try_into().unwrap()) % (auction.candle_window as u64);
+    let cutoff = window_start.try_add(1 + (roll as i64))?;
+    auction.candle_cutoff = cutoff;
+
+    // Without marks the lead never changed inside the window
+    let lead = if auction.candle_marks.is_empty() {
+        Some(CandleMark {
+            bidder: auction.highest_bidder,
+            bid: auction.highest_bid,
+            time: window_start,
+        }).filter(|mark| mark.bid > 0)
+    } else {
+        auction.candle_marks
+            .iter()
+            .rev()
+            .find(|mark| mark.time < cutoff)
+            .copied()
+    };
+    let winner = lead.filter(|mark| mark.bid >= auction.reserve_price);
+    match winner {
+        Some(mark) => {
+            auction.highest_bidder = mark.bidder;
+            auction.highest_bid = mark.bid;
+            auction.highest_max_bid = mark.bid;
+            auction.transition_to(AuctionStatus::Ended)?;
+        }
+        None => {
+            // Nobody is left holding the lead, so every position can be withdrawn
+            auction.highest_bid = 0;
+            auction.highest_max_bid = 0;
+            auction.transition_to(AuctionStatus::Cancelled)?;
+        }
+    }
+
+    // The tip comes out of the buyer fees, as when ending an auction
+    let tip = keeper_tip.min(auction.fees);
+    auction.fees = auction.fees.try_sub(tip)?;
+    let escrow = Escrow::new(
+        auction,
+        &listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?.unwrapping(
+        ctx.accounts.unwrap_account.as_ref(),
+        ctx.accounts.native_mint.as_ref(),
+        ctx.accounts.caller.to_account_info()
+    );
+    let caller_token_account = ctx.accounts.caller_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let caller = escrow.account_of(
+        &ctx.accounts.caller.to_account_info(),
+        caller_token_account.as_ref()
+    )?;
+    escrow.pay(&caller, tip)?;
+    escrow.settle()?;
+    assert_funds_conserved(escrow.holding(), auction)?;
+
+    emit!(CandleAuctionClosed {
+        listing_id,
+        cutoff,
+        winner: winner.map(|mark| mark.bidder),
+        amount: winner.map_or(0, |mark| mark.bid),
+    });
+    Ok(())
+}
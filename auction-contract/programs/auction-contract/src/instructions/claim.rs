@@ -0,0 +1,748 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::{ Token, TokenAccount };
+use anchor_spl::token_interface;
+use crate::cnft;
+use crate::emitter::{ self, event_authority, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::{
+    BidRefunded,
+    CharityContribution,
+    NftClaimed,
+    NftDelivered,
+    ReferralPaid,
+    RoyaltiesPaid,
+    SellerCollateralReturned,
+};
+use crate::instructions::external_listing::release;
+use crate::instructions::loyalty::{ self, LoyaltyActivity };
+use crate::invariants::assert_funds_conserved;
+use crate::math::{ self, CheckedMath };
+use crate::minting::{ self, MintNftAccounts };
+use crate::settlement::{ self, Custody };
+use crate::state::*;
+use crate::vault::Escrow;
+use crate::wormhole;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    COLLECTION_FEE_SEED,
+    FEE_TREASURY_SEED,
+    LOYALTY_SEED,
+    NFT_ESCROW_SEED,
+    PROVENANCE_SEED,
+    SETTLEMENT_RECEIPT_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+    WSOL_UNWRAP_SEED,
+};
+use crate::utils::generate_metadata;
+
+// Which accounts are needed depends on where the NFT is: an external escrow
+// (token_account, adapter_program and the adapter's accounts as remaining
+// accounts), the program's escrow (plus an (escrow, recipient token account)
+// pair per bundled NFT as remaining accounts), a Core asset, a programmable
+// NFT, a cNFT (with its proof nodes as remaining accounts), or minted fresh
+// for the winner.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ClaimNft<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        constraint = auction.is_winner(&winner.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    // Pays for anything created on delivery
+    #[account(mut)]
+    pub winner: Signer<'info>,
+    // Gets back the rent of the emptied NFT escrow
+    /// CHECK: only receives lamports; pinned to the auction's seller
+    #[account(mut, address = auction.owner @ ErrorCode::Unauthorized)]
+    pub seller: UncheckedAccount<'info>,
+    // External escrow only: must match the token account and adapter recorded at listing
+    /// CHECK: checked against the auction's external escrow
+    #[account(mut)]
+    pub token_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the auction's external escrow
+    #[account(executable)]
+    pub adapter_program: Option<UncheckedAccount<'info>>,
+    // Required when the NFT sits in the program's escrow
+    #[account(mut, seeds = [NFT_ESCROW_SEED, nft_escrow.mint.as_ref()], bump)]
+    pub nft_escrow: Option<Account<'info, TokenAccount>>,
+    // The winner, or the gift recipient when `deliver_to` is set
+    /// CHECK: compared against the auction's recipient in the handler
+    pub recipient: Option<UncheckedAccount<'info>>,
+    // The recipient's token account for the NFT; for a minted NFT, its
+    // associated token account, created here
+    /// CHECK: checked in the handler against the escrowed or minted NFT
+    #[account(mut)]
+    pub recipient_nft_account: Option<UncheckedAccount<'info>>,
+    // Mint path: the new mint, at [NFT_MINT_SEED, listing_id] or, for an
+    // edition, [NFT_MINT_SEED, listing_id, edition]. Programmable NFTs: the
+    // escrowed mint, with its metadata and master edition below
+    /// CHECK: created by the handler at its PDA address
+    #[account(mut)]
+    pub nft_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: derived and checked by the Token Metadata program
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: derived and checked by the Token Metadata program
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub rent: Option<Sysvar<'info, Rent>>,
+    // Core listings only: the escrowed asset, its collection if any, and the Core program
+    /// CHECK: must be the asset recorded at listing
+    #[account(mut)]
+    pub core_asset: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by the Core program during the transfer
+    #[account(mut)]
+    pub core_collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: the Core program
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: Option<UncheckedAccount<'info>>,
+    // Programmable NFTs only: the token records of the escrow and of the
+    // recipient's token account, and the mint's rule set if it has one
+    /// CHECK: checked by the Token Metadata program
+    #[account(mut)]
+    pub escrow_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: created or checked by the Token Metadata program
+    #[account(mut)]
+    pub recipient_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by the Token Metadata program against the mint's rule set
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by the Token Metadata program
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: the instructions sysvar, which Token Metadata's transfer reads
+    #[account(address = sysvar_instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    // Bubblegum listings only: the cNFT's tree, its config and the programs a
+    // transfer goes through
+    /// CHECK: must be the tree recorded at listing
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the tree's config address
+    pub tree_config: Option<UncheckedAccount<'info>>,
+    /// CHECK: address checked
+    #[account(address = cnft::NOOP_PROGRAM_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+    /// CHECK: address checked
+    #[account(address = cnft::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: address checked
+    #[account(address = cnft::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+    // The winner's loyalty account, if they have one, to earn points for the purchase
+    #[account(mut, seeds = [LOYALTY_SEED, winner.key().as_ref()], bump = loyalty_account.bump)]
+    pub loyalty_account: Option<Account<'info, LoyaltyAccount>>,
+    // Marks this winner's delivery done, so a retried claim can't deliver twice
+    #[account(
+        init_if_needed,
+        payer = winner,
+        space = SettlementReceipt::SPACE,
+        seeds = [
+            SETTLEMENT_RECEIPT_SEED,
+            auction.key().as_ref(),
+            &SettlementStep::NftDelivered.seed(),
+            winner.key().as_ref(),
+        ],
+        bump
+    )]
+    pub delivery_receipt: Account<'info, SettlementReceipt>,
+    pub system_program: Program<'info, System>,
+}
+
+// Each creator named by the NFT's royalty data follows as a remaining account, then each
+// of the auction's payout split recipients in order. On a listing paid in a token these,
+// like the referrer and winner, are their token accounts for the payment mint.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ClaimProceeds<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    // The seller, who receives the proceeds
+    #[account(mut, address = auction.owner @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+    // Protocol fees accumulate here, in the treasury for the listing's currency,
+    // until they're withdrawn
+    #[account(
+        mut,
+        seeds = [FEE_TREASURY_SEED, auction.currency().as_ref()],
+        bump = fee_treasury.bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    // The listing's collection fee config, if it has one
+    #[account(
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Option<Account<'info, CollectionFeeConfig>>,
+    /// CHECK: only receives the payout; must be the referrer on the winning bid
+    #[account(mut)]
+    pub referrer: Option<UncheckedAccount<'info>>,
+    /// CHECK: only receives the payout; must be the listing's beneficiary, or their
+    /// token account for the payment mint
+    #[account(mut)]
+    pub beneficiary: Option<UncheckedAccount<'info>>,
+    /// CHECK: only receives the payout; must be the winner of a second-price listing, who
+    /// is refunded what they escrowed above the price
+    #[account(mut)]
+    pub winner: Option<UncheckedAccount<'info>>,
+    // Token Metadata of a Legacy or programmable NFT, read for creator royalties
+    /// CHECK: checked against the NFT's mint by `royalties`
+    pub metadata: Option<UncheckedAccount<'info>>,
+    // Core listings only: the sold asset and its collection, read for royalties
+    /// CHECK: must be the asset recorded at listing
+    pub core_asset: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the asset by `royalties`
+    pub core_collection: Option<UncheckedAccount<'info>>,
+    // The sold NFT's sale history, opened by its first sale; required whenever the
+    // listing sold a single NFT outright and its mint is already known
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Provenance::SPACE,
+        seeds = [PROVENANCE_SEED, auction.nft_mint.as_ref().map_or(&[][..], |mint| mint.as_ref())],
+        bump
+    )]
+    pub provenance: Option<Account<'info, Provenance>>,
+    // Listings paid in a token only: the listing's token vault, and the seller's
+    // and the fee treasury's token accounts for the payment mint
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// CHECK: must be the seller's token account for the payment mint
+    #[account(mut)]
+    pub owner_token_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: must be the fee treasury's token account for the payment mint
+    #[account(mut)]
+    pub treasury_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Interface<'info, token_interface::TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+    // Wrapped SOL listings only: the account payouts to wallets are unwrapped
+    // through, and the native mint it's opened for
+    /// CHECK: created and closed again within the instruction by the escrow
+    #[account(mut, seeds = [WSOL_UNWRAP_SEED, listing_id.as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+    // Mark the seller's payout and the fee collection done, so a retried claim
+    // can't pay either twice
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = SettlementReceipt::SPACE,
+        seeds = [
+            SETTLEMENT_RECEIPT_SEED,
+            auction.key().as_ref(),
+            &SettlementStep::FundsPaid.seed(),
+            owner.key().as_ref(),
+        ],
+        bump
+    )]
+    pub funds_receipt: Account<'info, SettlementReceipt>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = SettlementReceipt::SPACE,
+        seeds = [
+            SETTLEMENT_RECEIPT_SEED,
+            auction.key().as_ref(),
+            &SettlementStep::FeesCollected.seed(),
+            fee_treasury.key().as_ref(),
+        ],
+        bump
+    )]
+    pub fees_receipt: Account<'info, SettlementReceipt>,
+    pub system_program: Program<'info, System>,
+}
+
+// Winner pulls the NFT of a sold auction, optionally delivering it to someone
+// else, e.g. as a gift. The winner stays on record as the buyer.
+pub fn claim_nft<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimNft<'info>>,
+    listing_id: [u8; 32],
+    deliver_to: Option<Pubkey>
+) -> Result<()> {
+    let events: EventAuthority = event_authority!(ctx);
+    let auction_state = &ctx.accounts.auction_state;
+    let winner = ctx.accounts.winner.key();
+    let auction = &mut ctx.accounts.auction;
+    // A crowd auction's NFT backs its contributors' shares
+    require!(!auction.is_alien, ErrorCode::CrowdNftLocked);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !auction.in_dispute_hold(auction_state.dispute_window, now),
+        ErrorCode::DisputeWindowOpen
+    );
+    require!(auction.rental_days == 0, ErrorCode::RentalClaimRequired);
+    ctx.accounts.delivery_receipt.ensure_pending()?;
+    // Edition winners each name their own recipient; `deliver_to` is the single winner's
+    let edition = auction.edition_of(&winner);
+    if edition.is_none() {
+        auction.deliver_to = deliver_to;
+    }
+
+    if auction.external_escrow.is_some() {
+        let token_account = ctx.accounts.token_account
+            .as_ref()
+            .ok_or(ErrorCode::InvalidExternalEscrow)?;
+        let adapter_program = ctx.accounts.adapter_program
+            .as_ref()
+            .ok_or(ErrorCode::EscrowAdapterNotAllowed)?;
+        release(
+            auction_state,
+            auction,
+            token_account,
+            adapter_program,
+            ctx.remaining_accounts,
+            listing_id
+        )?;
+        let price = auction.winning_price();
+        return ctx.accounts.delivery_receipt.record(
+            auction.key(),
+            SettlementStep::NftDelivered,
+            winner,
+            price,
+            ctx.bumps.delivery_receipt
+        );
+    }
+
+    require!(auction.is_sold(), ErrorCode::AuctionNotEnded);
+    require!(auction.installments_complete(), ErrorCode::InstallmentsOutstanding);
+    require!(auction.delegated_bids.is_empty(), ErrorCode::DelegatedBidUncollected);
+    match edition {
+        Some(index) => {
+            require!(auction.editions_claimed & (1 << index) == 0, ErrorCode::NftAlreadyClaimed);
+            auction.editions_claimed |= 1 << index;
+        }
+        None => {
+            require!(!auction.nft_claimed, ErrorCode::NftAlreadyClaimed);
+            auction.nft_claimed = true;
+        }
+    }
+    if auction.payment_mint.is_none() {
+        loyalty::accrue(
+            auction_state,
+            ctx.accounts.loyalty_account.as_mut(),
+            LoyaltyActivity::Purchase,
+            auction.winning_price()
+        )?;
+    }
+
+    let recipient = match edition {
+        Some(_) => deliver_to.unwrap_or(winner),
+        None => auction.deliver_to.unwrap_or(auction.highest_bidder),
+    };
+    if auction.nft_mint.is_some() {
+        // The NFT was escrowed at listing, hand it to the winner through the
+        // adapter for its asset standard
+        let winner_info = ctx.accounts.winner.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let recipient_info = ctx.accounts.recipient
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let recipient_nft_account = ctx.accounts.recipient_nft_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let asset = ctx.accounts.core_asset.as_ref().map(|account| account.to_account_info());
+        let collection = ctx.accounts.core_collection
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let mpl_core_program = ctx.accounts.mpl_core_program
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let mint = optional(&ctx.accounts.nft_mint);
+        let metadata = optional(&ctx.accounts.metadata);
+        let edition = optional(&ctx.accounts.master_edition);
+        let escrow_token_record = optional(&ctx.accounts.escrow_token_record);
+        let recipient_token_record = optional(&ctx.accounts.recipient_token_record);
+        let authorization_rules = optional(&ctx.accounts.authorization_rules);
+        let authorization_rules_program = optional(&ctx.accounts.authorization_rules_program);
+        let token_metadata_program = optional(&ctx.accounts.token_metadata_program);
+        let associated_token_program = optional(&ctx.accounts.associated_token_program);
+        let sysvar_instructions = optional(&ctx.accounts.sysvar_instructions);
+        let merkle_tree = optional(&ctx.accounts.merkle_tree);
+        let tree_config = optional(&ctx.accounts.tree_config);
+        let log_wrapper = optional(&ctx.accounts.log_wrapper);
+        let compression_program = optional(&ctx.accounts.compression_program);
+        let bubblegum_program = optional(&ctx.accounts.bubblegum_program);
+        let custody = Custody {
+            nft_escrow: ctx.accounts.nft_escrow.as_ref(),
+            token_account: recipient_nft_account.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            remaining: ctx.remaining_accounts,
+            metadata: metadata.as_ref(),
+            mint: mint.as_ref(),
+            edition: edition.as_ref(),
+            escrow_token_record: escrow_token_record.as_ref(),
+            token_record: recipient_token_record.as_ref(),
+            authorization_rules: authorization_rules.as_ref(),
+            authorization_rules_program: authorization_rules_program.as_ref(),
+            token_metadata_program: token_metadata_program.as_ref(),
+            associated_token_program: associated_token_program.as_ref(),
+            sysvar_instructions: sysvar_instructions.as_ref(),
+            asset: asset.as_ref(),
+            collection: collection.as_ref(),
+            mpl_core_program: mpl_core_program.as_ref(),
+            recipient: recipient_info.as_ref(),
+            merkle_tree: merkle_tree.as_ref(),
+            tree_config: tree_config.as_ref(),
+            log_wrapper: log_wrapper.as_ref(),
+            compression_program: compression_program.as_ref(),
+            bubblegum_program: bubblegum_program.as_ref(),
+            ..Custody::new(auction_state, &winner_info, &system_program)
+        };
+        let adapter = settlement::adapter(auction.asset_standard);
+        adapter.release(&custody, auction, recipient, &ctx.accounts.seller.to_account_info())?;
+    } else {
+        // Nothing was escrowed, so mint the NFT for the winner
+        let metadata = generate_metadata(
+            &auction.name,
+            auction.winning_price(),
+            auction.bid_of(&winner).map_or(0, |bid| bid.time),
+            auction.owner,
+            crate::ID
+        )?;
+        // A URI the seller set wins over the generated description
+        let uri = if auction.metadata_uri.is_empty() {
+            metadata
+        } else {
+            auction.metadata_uri.clone()
+        };
+
+        let recipient_info = ctx.accounts.recipient.as_ref().ok_or(ErrorCode::MissingMintAccounts)?;
+        require_keys_eq!(recipient_info.key(), recipient, ErrorCode::InvalidRecipient);
+        let mint_accounts = MintNftAccounts {
+            auction_state,
+            payer: ctx.accounts.winner.to_account_info(),
+            mint: required(&ctx.accounts.nft_mint)?,
+            recipient: recipient_info.to_account_info(),
+            recipient_token_account: required(&ctx.accounts.recipient_nft_account)?,
+            metadata: required(&ctx.accounts.metadata)?,
+            master_edition: required(&ctx.accounts.master_edition)?,
+            token_program: required(&ctx.accounts.token_program)?,
+            associated_token_program: required(&ctx.accounts.associated_token_program)?,
+            token_metadata_program: required(&ctx.accounts.token_metadata_program)?,
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: required(&ctx.accounts.rent)?,
+        };
+        minting::mint_nft(
+            mint_accounts,
+            &listing_id,
+            &auction.name,
+            edition.map(|index| index as u8),
+            uri
+        )?;
+    }
+
+    emitter::emit(&events, NftClaimed {
+        listing_id,
+        winner,
+        recipient,
+        mint: auction.nft_mint,
+        edition: edition.map(|index| index as u16),
+        price: auction.winning_price(),
+    })?;
+    emitter::emit(&events, NftDelivered {
+        listing_id,
+        winner,
+        recipient,
+        price: auction.winning_price(),
+    })?;
+    ctx.accounts.delivery_receipt.record(
+        auction.key(),
+        SettlementStep::NftDelivered,
+        winner,
+        auction.winning_price(),
+        ctx.bumps.delivery_receipt
+    )
+}
+
+// Seller pulls the proceeds of an ended auction out of its vault, paying the
+// fee treasury, the winner's referrer and any creator royalties on the way.
+// This settles the auction.
+pub fn claim_proceeds<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimProceeds<'info>>,
+    listing_id: [u8; 32],
+    hook: Pubkey
+) -> Result<()> {
+    let events: EventAuthority = event_authority!(ctx);
+    let auction_state = &ctx.accounts.auction_state;
+
+    // A zero key means no settlement hook; anything else must be whitelisted
+    let settlement_hook = if hook == Pubkey::default() {
+        None
+    } else {
+        require!(auction_state.hook_programs.contains(&hook), ErrorCode::HookNotWhitelisted);
+        Some(hook)
+    };
+
+    let auction = &mut ctx.accounts.auction;
+
+    // Settled auctions have already paid out
+    require!(auction.status == AuctionStatus::Ended, ErrorCode::AuctionNotEnded);
+    ctx.accounts.funds_receipt.ensure_pending()?;
+    ctx.accounts.fees_receipt.ensure_pending()?;
+    require!(auction.highest_bid > 0, ErrorCode::NothingToWithdraw);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !auction.in_dispute_hold(auction_state.dispute_window, now),
+        ErrorCode::DisputeWindowOpen
+    );
+    // A crowd sale delivered off-chain pays out once the seller has confirmed delivery
+    require!(
+        auction.seller_collateral == 0 || auction.delivery_confirmed,
+        ErrorCode::DeliveryNotConfirmed
+    );
+    wormhole::ensure_attested(auction)?;
+    require!(auction.installments_complete(), ErrorCode::InstallmentsOutstanding);
+    require!(auction.delegated_bids.is_empty(), ErrorCode::DelegatedBidUncollected);
+
+    // A sale finalized in steps had its payouts worked out then
+    let quote = match auction.settlement_quote.take() {
+        Some(quote) => quote,
+        None => {
+            let owner_info = ctx.accounts.owner.to_account_info();
+            let system_program = ctx.accounts.system_program.to_account_info();
+            let metadata = optional(&ctx.accounts.metadata);
+            let asset = optional(&ctx.accounts.core_asset);
+            let collection = optional(&ctx.accounts.core_collection);
+            let royalty_accounts = Custody {
+                metadata: metadata.as_ref(),
+                asset: asset.as_ref(),
+                collection: collection.as_ref(),
+                ..Custody::new(auction_state, &owner_info, &system_program)
+            };
+            quote_settlement(
+                auction_state,
+                auction,
+                ctx.accounts.collection_fee_config.as_deref(),
+                &royalty_accounts
+            )?
+        }
+    };
+    let SettlementQuote { fee, mut owner_earnings, referral, royalties } = quote;
+    require!(ctx.remaining_accounts.len() >= royalties.len(), ErrorCode::MissingRoyaltyAccounts);
+
+    // Past the creators, the remaining accounts are the payout split recipients
+    let (creator_accounts, split_accounts) = ctx.remaining_accounts.split_at(royalties.len());
+
+    // Pay the owner (or their payout splits) and the fee treasury out of the auction's escrow
+    let escrow = Escrow::new(
+        auction,
+        &listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?.unwrapping(
+        ctx.accounts.unwrap_account.as_ref(),
+        ctx.accounts.native_mint.as_ref(),
+        ctx.accounts.owner.to_account_info()
+    );
+    // The seller's chosen cause gets its share before anyone else on the seller's side
+    if let Some(beneficiary) = auction.beneficiary {
+        let donation = math::compute_fee(owner_earnings, beneficiary.share_bps.into())?;
+        owner_earnings = owner_earnings.try_sub(donation)?;
+        let beneficiary_info = ctx.accounts.beneficiary
+            .as_ref()
+            .ok_or(ErrorCode::InvalidBeneficiary)?;
+        require!(
+            escrow.pays(beneficiary_info, &beneficiary.wallet),
+            ErrorCode::InvalidBeneficiary
+        );
+        escrow.pay(&beneficiary_info.to_account_info(), donation)?;
+        emitter::emit(&events, CharityContribution {
+            listing_id,
+            beneficiary: beneficiary.wallet,
+            amount: donation,
+        })?;
+    }
+    if let Some(vesting) = auction.proceeds_vesting.as_mut() {
+        // Stays in escrow, for claim_vested to stream out
+        vesting.total = owner_earnings;
+        vesting.start = now;
+    } else if auction.payout_splits.is_empty() {
+        let owner_token_account = ctx.accounts.owner_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let owner = escrow.account_of(
+            &ctx.accounts.owner.to_account_info(),
+            owner_token_account.as_ref()
+        )?;
+        escrow.pay(&owner, owner_earnings)?;
+    } else {
+        let payouts = PayoutSplit::amounts(&auction.payout_splits, owner_earnings)?;
+        require!(split_accounts.len() >= payouts.len(), ErrorCode::MissingPayoutAccounts);
+        for ((recipient, amount), recipient_info) in payouts.into_iter().zip(split_accounts) {
+            require!(escrow.pays(recipient_info, &recipient), ErrorCode::MissingPayoutAccounts);
+            escrow.pay(recipient_info, amount)?;
+        }
+    }
+    let treasury_token_account = ctx.accounts.treasury_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let treasury = escrow.account_of(
+        &ctx.accounts.fee_treasury.to_account_info(),
+        treasury_token_account.as_ref()
+    )?;
+    escrow.pay(&treasury, fee)?;
+    ctx.accounts.fee_treasury.collected = ctx.accounts.fee_treasury.collected.try_add(fee)?;
+    let auction_key = auction.key();
+    ctx.accounts.fees_receipt.record(
+        auction_key,
+        SettlementStep::FeesCollected,
+        ctx.accounts.fee_treasury.key(),
+        fee,
+        ctx.bumps.fees_receipt
+    )?;
+    ctx.accounts.funds_receipt.record(
+        auction_key,
+        SettlementStep::FundsPaid,
+        auction.owner,
+        owner_earnings,
+        ctx.bumps.funds_receipt
+    )?;
+    let referral = referral.filter(|payout| payout.amount > 0);
+    if let Some(Payout { recipient: referrer, amount }) = referral {
+        let referrer_info = ctx.accounts.referrer.as_ref().ok_or(ErrorCode::InvalidReferrer)?;
+        require!(escrow.pays(referrer_info, &referrer), ErrorCode::InvalidReferrer);
+        escrow.pay(&referrer_info.to_account_info(), amount)?;
+        emitter::emit(&events, ReferralPaid {
+            listing_id,
+            referrer,
+            amount,
+        })?;
+    }
+    for (royalty, creator_info) in royalties.into_iter().zip(creator_accounts) {
+        let Payout { recipient: creator, amount } = royalty;
+        require!(escrow.pays(creator_info, &creator), ErrorCode::MissingRoyaltyAccounts);
+        escrow.pay(creator_info, amount)?;
+        emitter::emit(&events, RoyaltiesPaid {
+            listing_id,
+            creator,
+            amount,
+        })?;
+    }
+
+    // A second-price winner escrowed their own bid, so the difference goes back
+    // to them. A winner whose position isn't inline withdraws it instead.
+    if auction.second_price && auction.editions == 0 {
+        let price = auction.winning_price();
+        let winner = auction.highest_bidder;
+        let surplus = match auction.bid_of_mut(&winner) {
+            Some(bid) if bid.amount > price => std::mem::replace(&mut bid.amount, price) - price,
+            _ => 0,
+        };
+        if surplus > 0 {
+            let winner_info = ctx.accounts.winner.as_ref().ok_or(ErrorCode::InvalidRecipient)?;
+            require!(escrow.pays(winner_info, &winner), ErrorCode::InvalidRecipient);
+            escrow.pay(&winner_info.to_account_info(), surplus)?;
+            emitter::emit(&events, BidRefunded {
+                listing_id,
+                bidder: winner,
+                amount: surplus,
+            })?;
+        }
+    }
+
+    // The seller's collateral comes back with the proceeds
+    let collateral = std::mem::take(&mut auction.seller_collateral);
+    if collateral > 0 {
+        escrow.pay(&ctx.accounts.owner.to_account_info(), collateral)?;
+        emitter::emit(&events, SellerCollateralReturned { listing_id, amount: collateral })?;
+    }
+
+    escrow.settle()?;
+
+    auction.transition_to(AuctionStatus::Settled)?;
+
+    // A rented NFT goes back to the seller, so only outright sales change hands
+    let outright = auction.editions == 0 && auction.rental_days == 0;
+    if let Some(mint) = auction.nft_mint.filter(|_| outright) {
+        let provenance = ctx.accounts.provenance
+            .as_mut()
+            .ok_or(ErrorCode::MissingProvenanceAccount)?;
+        provenance.record_sale(mint, ctx.bumps.provenance.unwrap_or_default(), Sale {
+            seller: auction.owner,
+            buyer: auction.highest_bidder,
+            price: auction.winning_price(),
+            time: Clock::get()?.unix_timestamp,
+        })?;
+    }
+
+    // The hook runs in its own instruction (`run_settlement_hook`) so a failing
+    // hook program can never roll back the payouts above
+    auction.settlement_hook = settlement_hook;
+
+    assert_funds_conserved(escrow.holding(), auction)?;
+
+    Ok(())
+}
+
+// Work out how a closed sale's proceeds divide. Creators are paid out of the
+// seller's share, as the NFT's asset standard records them; a minted NFT has none.
+pub(crate) fn quote_settlement(
+    auction_state: &NftComAuction,
+    auction: &AuctionDetails,
+    collection_fee_config: Option<&CollectionFeeConfig>,
+    royalty_accounts: &Custody
+) -> Result<SettlementQuote> {
+    let seller_fee_bps = auction_state.fees_for(auction, collection_fee_config).seller_fee_bps;
+    let proceeds = auction.sale_proceeds()?;
+    let seller_fee = math::compute_fee(proceeds, seller_fee_bps)?;
+    // Less the seller's membership discount, recorded when they listed
+    let mut fee = seller_fee.try_sub(
+        math::compute_fee(seller_fee, auction.seller_fee_discount_bps)?
+    )?;
+    let mut owner_earnings = proceeds.try_sub(fee)?;
+
+    // The winning bid's referrer gets their share of the buyer fees. Only inline
+    // positions of a single-winner auction carry a referrer this can see.
+    let referral = match auction.bid_of(&auction.highest_bidder) {
+        Some(BidEntry { referrer: Some(referrer), .. }) if auction.editions == 0 => {
+            let amount = math::compute_fee(auction.fees, auction_state.referral_share_bps)?;
+            Some(Payout { recipient: *referrer, amount })
+        }
+        _ => None,
+    };
+    fee = fee.try_add(auction.fees.try_sub(referral.map_or(0, |payout| payout.amount))?)?;
+
+    let royalties: Vec<Payout> = settlement::adapter(auction.asset_standard)
+        .creator_shares(royalty_accounts, auction, proceeds)?
+        .into_iter()
+        .map(|(recipient, amount)| Payout { recipient, amount })
+        .collect();
+    let royalty_total = royalties
+        .iter()
+        .try_fold(0u64, |total, payout| total.try_add(payout.amount))?;
+    owner_earnings = owner_earnings
+        .checked_sub(royalty_total)
+        .ok_or(ErrorCode::RoyaltiesExceedProceeds)?;
+
+    Ok(SettlementQuote { fee, owner_earnings, referral, royalties })
+}
+
+fn required<'info, T: ToAccountInfo<'info>>(account: &Option<T>) -> Result<AccountInfo<'info>> {
+    optional(account).ok_or(ErrorCode::MissingMintAccounts.into())
+}
+
+fn optional<'info, T: ToAccountInfo<'info>>(account: &Option<T>) -> Option<AccountInfo<'info>> {
+    account.as_ref().map(|account| account.to_account_info())
+}
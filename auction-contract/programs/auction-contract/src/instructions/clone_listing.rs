@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::initialize_auction::open_auction;
+use crate::instructions::seller_profile::take_slot;
+use crate::state::*;
+use crate::vault;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_SEED,
+    SELLER_PROFILE_SEED,
+    VAULT_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(source_listing: [u8; 32], listing_id: [u8; 32], overrides: ListingOverrides)]
+pub struct CloneListing<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, source_listing.as_ref()],
+        bump = source.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub source: Account<'info, AuctionDetails>,
+    #[account(
+        init,
+        payer = owner,
+        space = AuctionDetails::space(overrides.bid_history_capacity()),
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, owner.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    // Required when the source listing belonged to a registered collection
+    #[account(
+        seeds = [COLLECTION_SEED, collection_config.collection.as_ref()],
+        bump = collection_config.bump
+    )]
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Relist an unsold listing as `listing_id`, carrying over its minimum, reserve,
+// increment, buy-now price, anti-sniping settings, allowlist, gate mint, payout splits, metadata and
+// payment processor unless `overrides` says otherwise. An NFT still sitting in
+// an external escrow or the program's escrow moves to the new listing. The
+// relisting is named in `overrides`, and `listing_id` is the seller's id for
+// that name.
+pub fn handler(
+    ctx: Context<CloneListing>,
+    _source_listing: [u8; 32],
+    listing_id: [u8; 32],
+    overrides: ListingOverrides
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let source = &mut ctx.accounts.source;
+
+    require!(source.is_unsold(), ErrorCode::ListingNotRelistable);
+
+    let collection = ctx.accounts.collection_config
+        .as_deref()
+        .filter(|config| Some(config.collection) == source.collection);
+    require!(
+        source.collection.is_none() || collection.is_some(),
+        ErrorCode::CollectionNotEnabled
+    );
+
+    let external_escrow = if source.external_escrow.as_ref().map_or(false, |escrow| !escrow.released) {
+        source.external_escrow.take()
+    } else {
+        None
+    };
+    // The program's escrow is keyed by mint (or is the Core asset), so the NFT itself stays put
+    let nft_mint = source.nft_mint.take();
+    let bundle_mints = std::mem::take(&mut source.bundle_mints);
+    let asset_standard = source.asset_standard;
+    let compressed_leaf = source.compressed_leaf.take();
+    let editions = source.editions;
+    let minimum = source.minimum_bid;
+    let metadata_uri = source.metadata_uri.clone();
+    let payment_processor = source.payment_processor;
+    let overrides = ListingOverrides {
+        end_time: overrides.end_time,
+        reserve_price: overrides.reserve_price.or(Some(source.reserve_price)),
+        min_increment_bps: overrides.min_increment_bps.or(Some(source.min_increment_bps)),
+        min_increment: overrides.min_increment.or(Some(source.min_increment)),
+        buy_now_price: overrides.buy_now_price.or(source.buy_now_price),
+        sniping_time_window: overrides.sniping_time_window.or(Some(source.sniping_time_window)),
+        time_extension: overrides.time_extension.or(Some(source.time_extension)),
+        allowlist_root: overrides.allowlist_root.or(source.allowlist_root),
+        gate_mint: overrides.gate_mint.or(source.gate_mint),
+        payout_splits: overrides.payout_splits.or(Some(source.payout_splits.clone())),
+        bid_history_capacity: overrides.bid_history_capacity,
+        grow_bid_history: overrides.grow_bid_history.or(Some(source.bid_history.growable)),
+        second_price: overrides.second_price.or(Some(source.second_price)),
+        candle_window: overrides.candle_window.or(Some(source.candle_window)),
+        rental_days: overrides.rental_days.or(Some(source.rental_days)),
+        payment_mint: overrides.payment_mint.or(source.payment_mint),
+        usd_pricing: overrides.usd_pricing.or(source.usd_pricing),
+        // The new id is derived from the name, so the relisting needs one of its own
+        name: overrides.name,
+        // Co-sellers consented to the original listing, not to this one
+        co_owned: None,
+        beneficiary: overrides.beneficiary.or(source.beneficiary),
+    };
+
+    vault::fund(
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.system_program.to_account_info()
+    )?;
+    let mut auction = open_auction(
+        &ctx.accounts.auction_state,
+        listing_id,
+        minimum,
+        ctx.accounts.owner.key(),
+        &overrides,
+        collection,
+        external_escrow,
+        ctx.bumps.auction,
+        ctx.bumps.vault
+    )?;
+    auction.metadata_uri = metadata_uri;
+    auction.payment_processor = payment_processor;
+    auction.nft_mint = nft_mint;
+    auction.bundle_mints = bundle_mints;
+    auction.asset_standard = asset_standard;
+    auction.compressed_leaf = compressed_leaf;
+    auction.editions = editions;
+    take_slot(&ctx.accounts.auction_state, ctx.accounts.seller_profile.as_mut(), &mut auction)?;
+    ctx.accounts.auction.set_inner(auction);
+    Ok(())
+}
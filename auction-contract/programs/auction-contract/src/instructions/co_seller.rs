@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::{ CoSellerApprovalRevoked, CoSellerApproved };
+use crate::state::*;
+use crate::CO_SELLER_APPROVAL_SEED;
+
+// Co-owned listings. An NFT owned jointly, e.g. by partners or the holders of
+// a fractionalized piece, is listed by one co-owner with the others as payout
+// splits and `co_owned` set in the overrides. Every other recipient of a split
+// has to consent to the listing and to their share: either by co-signing
+// `initialize_auction`, passed as a signer among its remaining accounts, or
+// ahead of time with `approve_co_listing`, whose approval PDA is passed there
+// instead. Settlement then pays the proceeds out by the splits as for any
+// listing with payout splits.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ApproveCoListing<'info> {
+    #[account(
+        init,
+        payer = co_seller,
+        space = CoSellerApproval::SPACE,
+        seeds = [CO_SELLER_APPROVAL_SEED, listing_id.as_ref(), co_seller.key().as_ref()],
+        bump
+    )]
+    pub approval: Account<'info, CoSellerApproval>,
+    #[account(mut)]
+    pub co_seller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct RevokeCoListing<'info> {
+    #[account(
+        mut,
+        close = co_seller,
+        seeds = [CO_SELLER_APPROVAL_SEED, listing_id.as_ref(), co_seller.key().as_ref()],
+        bump = approval.bump
+    )]
+    pub approval: Account<'info, CoSellerApproval>,
+    #[account(mut)]
+    pub co_seller: Signer<'info>,
+}
+
+// Consent to being listed under `listing_id` with `share_bps` of its proceeds
+pub fn approve_co_listing(
+    ctx: Context<ApproveCoListing>,
+    listing_id: [u8; 32],
+    share_bps: u16
+) -> Result<()> {
+    let approval = &mut ctx.accounts.approval;
+    approval.version = CoSellerApproval::VERSION;
+    approval.listing_id = listing_id;
+    approval.co_seller = ctx.accounts.co_seller.key();
+    approval.share_bps = share_bps;
+    approval.bump = ctx.bumps.approval;
+
+    emit!(CoSellerApproved { listing_id, co_seller: approval.co_seller, share_bps });
+    Ok(())
+}
+
+// Withdraw an approval and take back its rent; a listing already made stands
+pub fn revoke_co_listing(ctx: Context<RevokeCoListing>, listing_id: [u8; 32]) -> Result<()> {
+    emit!(CoSellerApprovalRevoked { listing_id, co_seller: ctx.accounts.co_seller.key() });
+    Ok(())
+}
+
+// Check that every split recipient other than `owner` consented, by signing
+// or by an approval of exactly their share, among `accounts`
+pub(crate) fn verify_consent(
+    listing_id: &[u8; 32],
+    owner: &Pubkey,
+    splits: &[PayoutSplit],
+    accounts: &[AccountInfo]
+) -> Result<()> {
+    require!(!splits.is_empty(), ErrorCode::CoSellerNotApproved);
+    for split in splits.iter().filter(|split| split.recipient != *owner) {
+        let signed = accounts
+            .iter()
+            .any(|account| account.key() == split.recipient && account.is_signer);
+        require!(
+            signed || approved(listing_id, split, accounts)?,
+            ErrorCode::CoSellerNotApproved
+        );
+    }
+    Ok(())
+}
+
+fn approved(listing_id: &[u8; 32], split: &PayoutSplit, accounts: &[AccountInfo]) -> Result<bool> {
+    let (address, _) = Pubkey::find_program_address(
+        &[CO_SELLER_APPROVAL_SEED, listing_id.as_ref(), split.recipient.as_ref()],
+        &crate::ID
+    );
+    let account = match accounts.iter().find(|account| account.key() == address) {
+        Some(account) => account,
+        None => {
+            return Ok(false);
+        }
+    };
+    if account.owner != &crate::ID {
+        return Ok(false);
+    }
+    let approval = CoSellerApproval::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    Ok(approval.share_bps == split.share_bps)
+}
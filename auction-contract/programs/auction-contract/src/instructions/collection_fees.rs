@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::*;
+use crate::{ AUCTION_STATE_SEED, COLLECTION_FEE_SEED, ROLES_SEED };
+
+// Fee configs are managed by the admin or a fee manager
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey)]
+pub struct CreateCollectionFeeConfig<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.manages_fees(roles.as_deref(), &signer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(
+        init,
+        payer = signer,
+        space = CollectionFeeConfig::SPACE,
+        seeds = [COLLECTION_FEE_SEED, collection.as_ref()],
+        bump
+    )]
+    pub collection_fee_config: Account<'info, CollectionFeeConfig>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCollectionFeeConfig<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.manages_fees(roles.as_deref(), &signer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(
+        mut,
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Account<'info, CollectionFeeConfig>,
+    pub signer: Signer<'info>,
+}
+
+pub fn create_collection_fee_config(
+    ctx: Context<CreateCollectionFeeConfig>,
+    collection: Pubkey,
+    buyer_fee_bps: u64,
+    seller_fee_bps: u64
+) -> Result<()> {
+    NftComAuction::validate_fees(buyer_fee_bps, seller_fee_bps)?;
+
+    let config = &mut ctx.accounts.collection_fee_config;
+    config.collection = collection;
+    config.buyer_fee_bps = buyer_fee_bps;
+    config.seller_fee_bps = seller_fee_bps;
+    config.version = CollectionFeeConfig::VERSION;
+    config.bump = ctx.bumps.collection_fee_config;
+    Ok(())
+}
+
+// Unlike the registry's fee override, this applies to the collection's live
+// listings too, from their next bid or settlement on
+pub fn update_collection_fee_config(
+    ctx: Context<UpdateCollectionFeeConfig>,
+    buyer_fee_bps: u64,
+    seller_fee_bps: u64
+) -> Result<()> {
+    NftComAuction::validate_fees(buyer_fee_bps, seller_fee_bps)?;
+
+    let config = &mut ctx.accounts.collection_fee_config;
+    config.buyer_fee_bps = buyer_fee_bps;
+    config.seller_fee_bps = seller_fee_bps;
+    Ok(())
+}
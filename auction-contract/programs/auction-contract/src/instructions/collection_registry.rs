@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::{ self, accounts::Metadata };
+use crate::errors::ErrorCode;
+use crate::state::*;
+use crate::instructions::collection_stats;
+use crate::{ AUCTION_STATE_SEED, COLLECTION_SEED, COLLECTION_STATS_SEED, FLOOR_ORACLE_SEED };
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey)]
+pub struct RegisterCollection<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = payer,
+        space = CollectionConfig::SPACE,
+        seeds = [COLLECTION_SEED, collection.as_ref()],
+        bump
+    )]
+    pub collection_config: Account<'info, CollectionConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = CollectionStats::SPACE,
+        seeds = [COLLECTION_STATS_SEED, collection.as_ref()],
+        bump
+    )]
+    pub collection_stats: Account<'info, CollectionStats>,
+    #[account(
+        init,
+        payer = payer,
+        space = FloorOracle::SPACE,
+        seeds = [FLOOR_ORACLE_SEED, collection.as_ref()],
+        bump
+    )]
+    pub floor_oracle: AccountLoader<'info, FloorOracle>,
+    pub authority: Signer<'info>,
+    // Funds the config, stats and floor oracle accounts, so the authority can be a multisig vault
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCollection<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [COLLECTION_SEED, collection_config.collection.as_ref()],
+        bump = collection_config.bump
+    )]
+    pub collection_config: Account<'info, CollectionConfig>,
+    pub authority: Signer<'info>,
+}
+
+pub fn register_collection(
+    ctx: Context<RegisterCollection>,
+    collection: Pubkey,
+    fee_override: Option<FeeOverride>,
+    permissions: u64
+) -> Result<()> {
+    validate_fee_override(fee_override)?;
+
+    let config = &mut ctx.accounts.collection_config;
+    config.collection = collection;
+    config.enabled = true;
+    config.fee_override = fee_override;
+    config.permissions = permissions;
+    config.version = CollectionConfig::VERSION;
+    config.bump = ctx.bumps.collection_config;
+    collection_stats::init(
+        &mut ctx.accounts.collection_stats,
+        collection,
+        ctx.bumps.collection_stats
+    );
+    collection_stats::init_floor(&ctx.accounts.floor_oracle, collection, ctx.bumps.floor_oracle)
+
+}
+
+// Listings already live keep the terms they were created with
+pub fn update_collection(
+    ctx: Context<UpdateCollection>,
+    enabled: bool,
+    fee_override: Option<FeeOverride>,
+    permissions: u64
+) -> Result<()> {
+    validate_fee_override(fee_override)?;
+
+    let config = &mut ctx.accounts.collection_config;
+    config.enabled = enabled;
+    config.fee_override = fee_override;
+    config.permissions = permissions;
+    Ok(())
+}
+
+// The verified Token Metadata collection of the NFT being listed, which the
+// listing records for collection fee lookups. A registry entry only applies to
+// NFTs verified in its collection, and while the marketplace curates listings
+// every NFT has to be listed under an approved one.
+pub(crate) fn verified_collection(
+    auction_state: &NftComAuction,
+    collection: Option<&CollectionConfig>,
+    metadata: Option<&AccountInfo>,
+    mint: &Pubkey
+) -> Result<Option<Pubkey>> {
+    let verified = match metadata {
+        Some(metadata) => read_verified_collection(metadata, mint)?,
+        None => None,
+    };
+    if let Some(collection) = collection {
+        require!(verified == Some(collection.collection), ErrorCode::CollectionNotVerified);
+    }
+    if auction_state.curates_listings() {
+        require!(
+            collection.is_some_and(|collection| collection.enabled),
+            ErrorCode::CollectionNotApproved
+        );
+    }
+    Ok(verified)
+}
+
+pub(crate) fn read_verified_collection(
+    metadata: &AccountInfo,
+    mint: &Pubkey
+) -> Result<Option<Pubkey>> {
+    require_keys_eq!(*metadata.owner, mpl_token_metadata::ID, ErrorCode::InvalidNftMetadata);
+    require_keys_eq!(metadata.key(), Metadata::find_pda(mint).0, ErrorCode::InvalidNftMetadata);
+    let metadata = Metadata::safe_deserialize(&metadata.try_borrow_data()?).map_err(
+        |_| ErrorCode::InvalidNftMetadata
+    )?;
+    require_keys_eq!(metadata.mint, *mint, ErrorCode::InvalidNftMetadata);
+    Ok(
+        metadata.collection
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key)
+    )
+}
+
+fn validate_fee_override(fee_override: Option<FeeOverride>) -> Result<()> {
+    if let Some(fees) = fee_override {
+        NftComAuction::validate_fees(fees.buyer_fee_bps, fees.seller_fee_bps)?;
+    }
+    Ok(())
+}
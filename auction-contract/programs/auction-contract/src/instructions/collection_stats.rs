@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::*;
+use crate::{ COLLECTION_SEED, COLLECTION_STATS_SEED, FLOOR_ORACLE_SEED };
+
+// Per-collection sale price stats and floor oracle, kept for other programs to
+// read, e.g. lending protocols pricing collateral: the stats through
+// `get_collection_twap`, the floor through `get_floor_price` or straight from
+// the oracle account. Every registered collection gets both with
+// `register_collection`; collections registered before they were kept need
+// `init_collection_stats` and `init_floor_oracle`, which anyone may pay for,
+// before their listings can be ended.
+
+#[derive(Accounts)]
+pub struct InitCollectionStats<'info> {
+    #[account(
+        seeds = [COLLECTION_SEED, collection_config.collection.as_ref()],
+        bump = collection_config.bump
+    )]
+    pub collection_config: Account<'info, CollectionConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = CollectionStats::SPACE,
+        seeds = [COLLECTION_STATS_SEED, collection_config.collection.as_ref()],
+        bump
+    )]
+    pub collection_stats: Account<'info, CollectionStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitFloorOracle<'info> {
+    #[account(
+        seeds = [COLLECTION_SEED, collection_config.collection.as_ref()],
+        bump = collection_config.bump
+    )]
+    pub collection_config: Account<'info, CollectionConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = FloorOracle::SPACE,
+        seeds = [FLOOR_ORACLE_SEED, collection_config.collection.as_ref()],
+        bump
+    )]
+    pub floor_oracle: AccountLoader<'info, FloorOracle>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_collection_stats(ctx: Context<InitCollectionStats>) -> Result<()> {
+    init(
+        &mut ctx.accounts.collection_stats,
+        ctx.accounts.collection_config.collection,
+        ctx.bumps.collection_stats
+    );
+    Ok(())
+}
+
+pub fn init_floor_oracle(ctx: Context<InitFloorOracle>) -> Result<()> {
+    init_floor(
+        &ctx.accounts.floor_oracle,
+        ctx.accounts.collection_config.collection,
+        ctx.bumps.floor_oracle
+    )
+}
+
+pub(crate) fn init(stats: &mut CollectionStats, collection: Pubkey, bump: u8) {
+    stats.version = CollectionStats::VERSION;
+    stats.collection = collection;
+    stats.bump = bump;
+}
+
+pub(crate) fn init_floor(
+    floor_oracle: &AccountLoader<FloorOracle>,
+    collection: Pubkey,
+    bump: u8
+) -> Result<()> {
+    let mut oracle = floor_oracle.load_init()?;
+    oracle.collection = collection;
+    oracle.window = FloorOracle::WINDOW;
+    oracle.version = FloorOracle::VERSION;
+    oracle.bump = bump;
+    Ok(())
+}
+
+// Count a sold listing's price toward its collection's stats and floor. Only
+// SOL sales of a registered collection are kept, so every price is in lamports.
+pub(crate) fn record_sale(
+    auction: &AuctionDetails,
+    stats: Option<&mut Account<CollectionStats>>,
+    floor_oracle: Option<&AccountLoader<FloorOracle>>,
+    now: i64
+) -> Result<()> {
+    let collection = match auction.collection {
+        Some(collection) if auction.payment_mint.is_none() => collection,
+        _ => {
+            return Ok(());
+        }
+    };
+    let stats = stats.ok_or(ErrorCode::CollectionStatsMissing)?;
+    require_keys_eq!(stats.collection, collection, ErrorCode::CollectionStatsMissing);
+    stats.record_sale(auction.winning_price(), now);
+
+    let mut oracle = floor_oracle.ok_or(ErrorCode::CollectionStatsMissing)?.load_mut()?;
+    require_keys_eq!(oracle.collection, collection, ErrorCode::CollectionStatsMissing);
+    oracle.record_sale(auction.winning_price(), now);
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use switchboard_on_demand::ON_DEMAND_MAINNET_PID;
+use crate::errors::ErrorCode;
+use crate::events::AuctionCancelled;
+use crate::randomness;
+use crate::state::*;
+use crate::AUCTION_SEED;
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct CommitRandomness<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+    /// CHECK: parsed by `randomness::commit`; must belong to Switchboard on-demand
+    #[account(owner = ON_DEMAND_MAINNET_PID @ ErrorCode::InvalidRandomnessAccount)]
+    pub randomness_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct CancelStaleRandomness<'info> {
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+// Lock in the randomness account that will decide the auction's outcome. The
+// auction stops taking bids until the randomness is consumed.
+pub fn commit_randomness(ctx: Context<CommitRandomness>, _listing_id: [u8; 32]) -> Result<()> {
+    let clock = Clock::get()?;
+    let auction = &mut ctx.accounts.auction;
+
+    require!(clock.unix_timestamp >= auction.end_time, ErrorCode::AuctionNotEnded);
+    require!(auction.status == AuctionStatus::Live, ErrorCode::AuctionAlreadyEnded);
+    require!(auction.randomness.is_none(), ErrorCode::RandomnessAlreadyCommitted);
+    // A raffle nobody entered has no one to draw; it's swept like an auction without bids
+    require!(auction.ticket_price == 0 || auction.has_bids(), ErrorCode::NoTicketsSold);
+
+    auction.randomness = Some(randomness::commit(&ctx.accounts.randomness_account, &clock)?);
+    auction.transition_to(AuctionStatus::AwaitingRandomness)
+}
+
+// Fallback when the oracle never reveals: cancel the auction so bidders can
+// withdraw instead of waiting forever. Anyone may crank this.
+pub fn cancel_stale_randomness(
+    ctx: Context<CancelStaleRandomness>,
+    _listing_id: [u8; 32]
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &mut ctx.accounts.auction;
+
+    let commitment = auction.randomness.as_ref().ok_or(ErrorCode::RandomnessNotCommitted)?;
+    require!(commitment.is_stale(now), ErrorCode::RandomnessNotStale);
+
+    auction.randomness = None;
+    // Nobody is left holding the lead, so every position can be withdrawn
+    auction.highest_bid = 0;
+    auction.highest_max_bid = 0;
+    auction.transition_to(AuctionStatus::Cancelled)?;
+    emit!(AuctionCancelled { listing_id: auction.listing_id, owner: auction.owner });
+    Ok(())
+}
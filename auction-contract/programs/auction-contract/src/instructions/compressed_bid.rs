@@ -0,0 +1,262 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use crate::cnft::ACCOUNT_COMPRESSION_PROGRAM_ID;
+use crate::compressed_positions::*;
+use crate::cpi_guard::assert_cpi_caller_allowed;
+use crate::errors::ErrorCode;
+use crate::events::{ CompressedPositionUpdated, FundsWithdrawn };
+use crate::instructions::place_bid::apply_bid;
+use crate::invariants::assert_funds_conserved;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, BLACKLIST_SEED, VAULT_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct EnableCompressedPositions<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+    /// CHECK: allocated by the client for the compression program; initialised here
+    #[account(mut, owner = ACCOUNT_COMPRESSION_PROGRAM_ID @ ErrorCode::InvalidMerkleTree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
+
+// The state PDA is the authority of every position tree
+#[derive(Accounts)]
+pub struct CompressedPositionAccounts<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    /// CHECK: must be the auction's position tree, checked in the handler
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], bidder: Pubkey)]
+pub struct PlaceCompressedBid<'info> {
+    pub tree: CompressedPositionAccounts<'info>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: the bidder's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, bidder.as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the instructions sysvar, used to identify CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct WithdrawCompressed<'info> {
+    pub tree: CompressedPositionAccounts<'info>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CompressedPositionAccounts<'info> {
+    fn position_tree(&self) -> PositionTree<'info> {
+        PositionTree {
+            compression_program: self.compression_program.to_account_info(),
+            merkle_tree: self.merkle_tree.to_account_info(),
+            authority: self.auction_state.to_account_info(),
+            noop_program: self.noop_program.to_account_info(),
+            authority_bump: self.auction_state.bump,
+        }
+    }
+}
+
+// Switch a listing to compressed positions; only before anyone has bid
+pub fn enable_compressed_positions(
+    ctx: Context<EnableCompressedPositions>,
+    _listing_id: [u8; 32],
+    max_depth: u32,
+    max_buffer_size: u32
+) -> Result<()> {
+    let tree = PositionTree {
+        compression_program: ctx.accounts.compression_program.to_account_info(),
+        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+        authority: ctx.accounts.auction_state.to_account_info(),
+        noop_program: ctx.accounts.noop_program.to_account_info(),
+        authority_bump: ctx.accounts.auction_state.bump,
+    };
+
+    let auction = &mut ctx.accounts.auction;
+    require!(
+        auction.position_tree.is_none() &&
+            auction.bids.is_empty() &&
+            auction.status == AuctionStatus::Live,
+        ErrorCode::CompressedPositionsUnavailable
+    );
+    auction.position_tree = Some(tree.merkle_tree.key());
+
+    tree.init(max_depth, max_buffer_size)
+}
+
+// Bid on a compressed auction. New bidders append a leaf; returning bidders pass
+// their current leaf as `prior` with its proof as remaining accounts.
+pub fn place_compressed_bid<'info>(
+    ctx: Context<'_, '_, '_, 'info, PlaceCompressedBid<'info>>,
+    listing_id: [u8; 32],
+    bidder: Pubkey,
+    amount: u64,
+    prior: Option<CompressedPosition>,
+    root: [u8; 32]
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::MinimumBidError);
+    assert_cpi_caller_allowed(
+        &ctx.accounts.instructions,
+        &ctx.accounts.tree.auction_state.aggregator_programs
+    )?;
+    // Bid bonds are only posted, and bid limits only tracked, through place_bid
+    require!(ctx.accounts.auction.bid_bond == 0, ErrorCode::BidBondRequired);
+    require!(!ctx.accounts.auction.has_bid_limits(), ErrorCode::BidLimitsApply);
+
+    let escrow = ctx.accounts.vault.to_account_info();
+    let balance_before = escrow.lamports();
+    system_program::transfer(
+        CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: escrow.clone(),
+        }),
+        amount
+    )?;
+    let deposited = escrow.lamports() - balance_before;
+
+    let bid_amount = apply_bid(
+        &ctx.accounts.tree.auction_state,
+        &mut ctx.accounts.auction,
+        &escrow,
+        bidder,
+        ctx.accounts.payer.key(),
+        &ctx.accounts.blacklist_entry,
+        deposited,
+        PositionStore::Compressed,
+        false,
+        &[],
+        None,
+        None,
+        0,
+        None,
+        &None
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let tree = ctx.accounts.tree.position_tree();
+    let auction = &mut ctx.accounts.auction;
+    require!(
+        auction.position_tree == Some(ctx.accounts.tree.merkle_tree.key()),
+        ErrorCode::InvalidMerkleTree
+    );
+
+    let (total, index) = match prior {
+        Some(prior) => {
+            let total = prior.amount.try_add(bid_amount)?;
+            tree.replace(
+                root,
+                position_leaf(&listing_id, &bidder, prior.amount, prior.time),
+                position_leaf(&listing_id, &bidder, total, now),
+                prior.index,
+                ctx.remaining_accounts
+            )?;
+            (total, prior.index)
+        }
+        None => {
+            let index = auction.compressed_positions;
+            tree.append(position_leaf(&listing_id, &bidder, bid_amount, now))?;
+            auction.compressed_positions = auction.compressed_positions.try_add(1)?;
+            (bid_amount, index)
+        }
+    };
+
+    auction.resolve_bid(bidder, total, false)?;
+
+    emit!(CompressedPositionUpdated { listing_id, bidder, amount: total, time: now, index });
+    Ok(())
+}
+
+// Refund a compressed position, proving it with `position` and the remaining accounts
+pub fn withdraw_compressed<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawCompressed<'info>>,
+    listing_id: [u8; 32],
+    position: CompressedPosition,
+    root: [u8; 32]
+) -> Result<()> {
+    let bidder = ctx.accounts.bidder.key();
+    let tree = ctx.accounts.tree.position_tree();
+    let auction = &mut ctx.accounts.auction;
+
+    require!(
+        auction.position_tree == Some(ctx.accounts.tree.merkle_tree.key()),
+        ErrorCode::CompressedPositionsDisabled
+    );
+    require!(!auction.is_alien, ErrorCode::AlienAuctionError);
+    require!(!auction.is_winner(&bidder), ErrorCode::HighestBidderCannotWithdraw);
+    require!(position.amount > 0, ErrorCode::NoFundsToWithdraw);
+
+    // Fails unless the bidder's leaf holds exactly `position`
+    tree.replace(
+        root,
+        position_leaf(&listing_id, &bidder, position.amount, position.time),
+        position_leaf(&listing_id, &bidder, 0, position.time),
+        position.index,
+        ctx.remaining_accounts
+    )?;
+    auction.compressed_total = auction.compressed_total.try_sub(position.amount)?;
+
+    vault::pay_out(
+        &ctx.accounts.vault.to_account_info(),
+        &listing_id,
+        auction.vault_bump,
+        &ctx.accounts.bidder.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        position.amount
+    )?;
+    assert_funds_conserved(&ctx.accounts.vault.to_account_info(), auction)?;
+
+    emit!(FundsWithdrawn {
+        listing_id,
+        bidder,
+        recipient: bidder,
+        amount: position.amount,
+        remaining: 0,
+    });
+    emit!(CompressedPositionUpdated {
+        listing_id,
+        bidder,
+        amount: 0,
+        time: position.time,
+        index: position.index,
+    });
+    Ok(())
+}
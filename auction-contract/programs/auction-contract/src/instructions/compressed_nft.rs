@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use crate::cnft;
+use crate::errors::ErrorCode;
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::initialize_auction::open_auction;
+use crate::instructions::seller_profile::take_slot;
+use crate::settlement::{ self, Custody };
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, BLACKLIST_SEED, SELLER_PROFILE_SEED, VAULT_SEED };
+
+// A cNFT is a leaf in a Bubblegum tree, so escrow makes the state PDA its leaf
+// owner. Every transfer repeats the leaf's hashes; the listing rebuilds them
+// from the terms below, so Bubblegum only lets the cNFT into escrow if they're
+// its real ones, and settlement can pay royalties from them.
+
+// The cNFT being listed, as the seller's indexer reports it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CompressedNftArgs {
+    pub nonce: u64,
+    pub metadata_hash: [u8; 32], // keccak hash of the leaf's metadata args
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CompressedCreator>,
+}
+
+// The proof nodes of the seller's leaf follow as remaining accounts
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], minimum: u64, overrides: ListingOverrides)]
+pub struct ListCompressedNft<'info> {
+    // Leaf owner of listed cNFTs until settlement or cancellation
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = owner,
+        space = AuctionDetails::space(overrides.bid_history_capacity()),
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, owner.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // The leaf's delegate, if the seller has set one
+    /// CHECK: only named in the transfer, whose leaf hash commits to it
+    pub leaf_delegate: Option<UncheckedAccount<'info>>,
+    /// CHECK: the cNFT's tree, checked by the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: checked against the tree's config address
+    pub tree_config: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = cnft::NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = cnft::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    /// CHECK: address checked
+    #[account(address = cnft::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// List a cNFT, moving it into the program's custody
+pub fn list_compressed_nft<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ListCompressedNft<'info>>,
+    listing_id: [u8; 32],
+    minimum: u64,
+    overrides: ListingOverrides,
+    nft: CompressedNftArgs
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    require!(nft.creators.len() <= CompressedLeaf::MAX_CREATORS, ErrorCode::InvalidNftMint);
+    let merkle_tree = ctx.accounts.merkle_tree.to_account_info();
+    let leaf = CompressedLeaf {
+        merkle_tree: merkle_tree.key(),
+        nonce: nft.nonce,
+        data_hash: cnft::data_hash(&nft.metadata_hash, nft.seller_fee_basis_points),
+        creator_hash: cnft::creator_hash(&nft.creators),
+        seller_fee_basis_points: nft.seller_fee_basis_points,
+        creators: nft.creators,
+    };
+    let asset = cnft::asset_id(&leaf.merkle_tree, leaf.nonce);
+
+    let owner = ctx.accounts.owner.key();
+    let owner_info = ctx.accounts.owner.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let leaf_delegate = ctx.accounts.leaf_delegate
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let tree_config = ctx.accounts.tree_config.to_account_info();
+    let log_wrapper = ctx.accounts.log_wrapper.to_account_info();
+    let compression_program = ctx.accounts.compression_program.to_account_info();
+    let bubblegum_program = ctx.accounts.bubblegum_program.to_account_info();
+    let custody = Custody {
+        remaining: ctx.remaining_accounts,
+        merkle_tree: Some(&merkle_tree),
+        tree_config: Some(&tree_config),
+        log_wrapper: Some(&log_wrapper),
+        compression_program: Some(&compression_program),
+        bubblegum_program: Some(&bubblegum_program),
+        leaf: Some(&leaf),
+        leaf_delegate: leaf_delegate.as_ref(),
+        ..Custody::new(&ctx.accounts.auction_state, &owner_info, &system_program)
+    };
+    settlement::adapter(AssetStandard::Compressed).escrow(&custody, asset, &owner_info)?;
+
+    vault::fund(&owner_info, &ctx.accounts.vault.to_account_info(), &system_program)?;
+    let mut auction = open_auction(
+        &ctx.accounts.auction_state,
+        listing_id,
+        minimum,
+        owner,
+        &overrides,
+        None,
+        None,
+        ctx.bumps.auction,
+        ctx.bumps.vault
+    )?;
+    auction.nft_mint = Some(asset);
+    auction.asset_standard = AssetStandard::Compressed;
+    auction.compressed_leaf = Some(leaf);
+    take_slot(&ctx.accounts.auction_state, ctx.accounts.seller_profile.as_mut(), &mut auction)?;
+    ctx.accounts.auction.set_inner(auction);
+    Ok(())
+}
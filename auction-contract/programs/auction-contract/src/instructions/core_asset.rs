@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use mpl_core::accounts::BaseAssetV1;
+use mpl_core::instructions::TransferV1CpiBuilder;
+use mpl_core::types::{
+    FreezeDelegate,
+    PermanentBurnDelegate,
+    PermanentFreezeDelegate,
+    PermanentTransferDelegate,
+    PluginType,
+    UpdateAuthority,
+};
+use crate::errors::ErrorCode;
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::initialize_auction::open_auction;
+use crate::instructions::seller_profile::take_slot;
+use crate::settlement::{ self, Custody };
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, BLACKLIST_SEED, SELLER_PROFILE_SEED, VAULT_SEED };
+
+// Core assets are single accounts with an owner field, so escrow is simply a
+// transfer to the state PDA and delivery a transfer out of it.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], minimum: u64, overrides: ListingOverrides)]
+pub struct ListCoreAsset<'info> {
+    // Owns listed Core assets until settlement or cancellation
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = owner,
+        space = AuctionDetails::space(overrides.bid_history_capacity()),
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, owner.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    /// CHECK: a Core asset owned by the signer, checked in the handler
+    #[account(mut, owner = mpl_core::ID)]
+    pub asset: UncheckedAccount<'info>,
+    // The asset's collection, if it belongs to one
+    /// CHECK: checked by the Core program during the transfer
+    #[account(mut)]
+    pub core_collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: the Core program
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ReturnCoreAsset<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: must be the asset recorded at listing
+    #[account(mut)]
+    pub asset: UncheckedAccount<'info>,
+    /// CHECK: checked by the Core program during the transfer
+    #[account(mut)]
+    pub core_collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: the Core program
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// List a Core asset, moving it into the program's custody
+pub fn list_core_asset(
+    ctx: Context<ListCoreAsset>,
+    listing_id: [u8; 32],
+    minimum: u64,
+    overrides: ListingOverrides
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let asset = ctx.accounts.asset.to_account_info();
+    let owner = ctx.accounts.owner.key();
+    let owner_info = ctx.accounts.owner.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let collection = ctx.accounts.core_collection.as_ref().map(|account| account.to_account_info());
+    let mpl_core_program = ctx.accounts.mpl_core_program.to_account_info();
+    let custody = Custody {
+        asset: Some(&asset),
+        collection: collection.as_ref(),
+        mpl_core_program: Some(&mpl_core_program),
+        ..Custody::new(&ctx.accounts.auction_state, &owner_info, &system_program)
+    };
+    settlement::adapter(AssetStandard::Core).escrow(&custody, asset.key(), &owner_info)?;
+
+    vault::fund(
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.system_program.to_account_info()
+    )?;
+    let mut auction = open_auction(
+        &ctx.accounts.auction_state,
+        listing_id,
+        minimum,
+        owner,
+        &overrides,
+        None,
+        None,
+        ctx.bumps.auction,
+        ctx.bumps.vault
+    )?;
+    auction.nft_mint = Some(asset.key());
+    auction.asset_standard = AssetStandard::Core;
+    take_slot(&ctx.accounts.auction_state, ctx.accounts.seller_profile.as_mut(), &mut auction)?;
+    ctx.accounts.auction.set_inner(auction);
+    Ok(())
+}
+
+// Give the Core asset of an unsold listing back to its seller
+pub fn return_core_asset(ctx: Context<ReturnCoreAsset>, _listing_id: [u8; 32]) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_unsold(), ErrorCode::ListingNotRelistable);
+    require!(auction.nft_mint == Some(ctx.accounts.asset.key()), ErrorCode::NftEscrowMismatch);
+
+    let owner = ctx.accounts.owner.to_account_info();
+    let asset = ctx.accounts.asset.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let collection = ctx.accounts.core_collection.as_ref().map(|account| account.to_account_info());
+    let mpl_core_program = ctx.accounts.mpl_core_program.to_account_info();
+    let custody = Custody {
+        asset: Some(&asset),
+        collection: collection.as_ref(),
+        mpl_core_program: Some(&mpl_core_program),
+        recipient: Some(&owner),
+        ..Custody::new(&ctx.accounts.auction_state, &owner, &system_program)
+    };
+    let adapter = settlement::adapter(auction.asset_standard);
+    adapter.release(&custody, auction, owner.key(), &owner)?;
+    auction.nft_mint = None;
+    Ok(())
+}
+
+// Transfer a Core asset held by the state PDA to `to`
+pub(crate) fn release_core_asset<'info>(
+    auction_state: &Account<'info, NftComAuction>,
+    mpl_core_program: &AccountInfo<'info>,
+    asset: &AccountInfo<'info>,
+    collection: Option<&AccountInfo<'info>>,
+    to: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>
+) -> Result<()> {
+    TransferV1CpiBuilder::new(mpl_core_program)
+        .asset(asset)
+        .collection(collection)
+        .payer(payer)
+        .authority(Some(&auction_state.to_account_info()))
+        .new_owner(to)
+        .system_program(Some(system_program))
+        .invoke_signed(&[&[AUCTION_STATE_SEED, &[auction_state.bump]]])?;
+    Ok(())
+}
+
+// Refuse assets the program couldn't reliably hand over: frozen ones, and
+// those a permanent delegate could move, freeze or burn while in escrow
+pub(crate) fn assert_escrowable(asset: &AccountInfo) -> Result<()> {
+    if
+        let Ok((_, freeze, _)) = mpl_core::fetch_plugin::<BaseAssetV1, FreezeDelegate>(
+            asset,
+            PluginType::FreezeDelegate
+        )
+    {
+        require!(!freeze.frozen, ErrorCode::UnsupportedCoreAsset);
+    }
+    let permanent_freeze = mpl_core::fetch_plugin::<BaseAssetV1, PermanentFreezeDelegate>(
+        asset,
+        PluginType::PermanentFreezeDelegate
+    );
+    let permanent_transfer = mpl_core::fetch_plugin::<BaseAssetV1, PermanentTransferDelegate>(
+        asset,
+        PluginType::PermanentTransferDelegate
+    );
+    let permanent_burn = mpl_core::fetch_plugin::<BaseAssetV1, PermanentBurnDelegate>(
+        asset,
+        PluginType::PermanentBurnDelegate
+    );
+    require!(
+        permanent_freeze.is_err() && permanent_transfer.is_err() && permanent_burn.is_err(),
+        ErrorCode::UnsupportedCoreAsset
+    );
+    Ok(())
+}
+
+// The collection a Core asset belongs to, if any
+pub(crate) fn core_collection_of(asset: &AccountInfo) -> Result<Option<Pubkey>> {
+    let data = asset.try_borrow_data()?;
+    let base = BaseAssetV1::from_bytes(&data).map_err(|_| ErrorCode::InvalidNftMint)?;
+    Ok(match base.update_authority {
+        UpdateAuthority::Collection(collection) => Some(collection),
+        _ => None,
+    })
+}
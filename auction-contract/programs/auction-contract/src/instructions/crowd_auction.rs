@@ -0,0 +1,429 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{ self, Mint, MintTo, Token, TokenAccount };
+use crate::errors::ErrorCode;
+use crate::events::{
+    ContributionMade,
+    ContributionRefunded,
+    CrowdAuctionCreated,
+    CrowdSharesClaimed,
+    DeliveryConfirmed,
+    SellerCollateralReturned,
+    SellerCollateralSlashed,
+};
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::initialize_auction::{ self, InitializeAuction };
+use crate::invariants::assert_funds_conserved;
+use crate::math::{ self, CheckedMath };
+use crate::state::*;
+use crate::vault;
+use crate::wormhole;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_FEE_SEED,
+    CROWD_SHARE_SEED,
+    VAULT_SEED,
+};
+
+// Crowdfunded ("alien") auctions. Instead of bidding against each other,
+// contributors pool their funds in the auction's vault and the whole pool is
+// the winning amount. If the pool reaches the seller's target by the end, the
+// seller is paid as for any sale, the NFT stays in escrow and each contributor
+// claims share tokens for their part of it. Otherwise every contributor gets
+// their pro-rata part of the pool back and the NFT returns to the seller.
+//
+// Contributions are inline positions only, so a crowd auction has at most
+// AuctionDetails::MAX_INLINE_BIDDERS contributors.
+//
+// When what's sold is delivered off-chain or cross-chain, the seller stakes
+// collateral in the vault at listing and has to `confirm_delivery` within the
+// delivery window after a successful close. Until then the proceeds and the
+// shares wait. Confirming hands the collateral back along with the proceeds;
+// letting the window run out voids the sale, and each contributor refunds
+// their contribution plus their pro-rata part of the collateral. A listing
+// that goes unsold returns the collateral through `reclaim_seller_collateral`.
+
+// Share tokens use SOL's decimals: one share per lamport contributed
+pub const SHARE_DECIMALS: u8 = 9;
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct InitializeCrowdAuction<'info> {
+    // Same accounts as a regular listing
+    pub listing: InitializeAuction<'info>,
+    // Mints the contributors' shares once the target is met
+    #[account(
+        init,
+        payer = listing.owner,
+        mint::decimals = SHARE_DECIMALS,
+        mint::authority = listing.auction_state,
+        seeds = [CROWD_SHARE_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub share_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct Contribute<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+    /// CHECK: the contributor's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, contributor.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // The listing's collection fee config, if it has one
+    #[account(
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Option<Account<'info, CollectionFeeConfig>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct RefundContribution<'info> {
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ConfirmDelivery<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ReclaimSellerCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ClaimCrowdShares<'info> {
+    // Mint authority of every share mint
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [CROWD_SHARE_SEED, listing_id.as_ref()], bump)]
+    pub share_mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = contributor,
+        associated_token::mint = share_mint,
+        associated_token::authority = contributor
+    )]
+    pub contributor_shares: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// List the NFT as a crowd auction raising `target`, with `minimum` as the
+// smallest contribution. The target is the listing's reserve price, so
+// `end_auction` only closes it once the pool has reached it. A seller
+// delivering off-chain stakes `collateral` on it.
+pub fn initialize_crowd_auction<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitializeCrowdAuction<'info>>,
+    listing_id: [u8; 32],
+    minimum: u64,
+    owner: Pubkey,
+    overrides: ListingOverrides,
+    target: u64,
+    collateral: Option<SellerCollateral>
+) -> Result<()> {
+    // Nobody can buy the pool out, and anyone may join it
+    require!(
+        target > 0 &&
+            target >= minimum &&
+            overrides.buy_now_price.is_none() &&
+            overrides.allowlist_root.is_none() &&
+            overrides.gate_mint.is_none() &&
+            overrides.usd_pricing.is_none(),
+        ErrorCode::InvalidCrowdAuction
+    );
+    // The pool is raised in SOL only
+    require!(overrides.payment_mint.is_none(), ErrorCode::InvalidPaymentMint);
+    if let Some(collateral) = collateral {
+        require!(
+            collateral.amount > 0 && collateral.delivery_window > 0,
+            ErrorCode::InvalidSellerCollateral
+        );
+    }
+
+    initialize_auction::handler(
+        Context::new(
+            ctx.program_id,
+            &mut ctx.accounts.listing,
+            ctx.remaining_accounts,
+            ctx.bumps.listing
+        ),
+        listing_id,
+        minimum,
+        owner,
+        overrides,
+        None
+    )?;
+
+    // The pool itself is the highest bidder, and the buyer of record
+    let auction = &mut ctx.accounts.listing.auction;
+    let pool = auction.key();
+    auction.is_alien = true;
+    auction.reserve_price = target;
+    auction.highest_bidder = pool;
+
+    let staked = collateral.map_or(0, |collateral| collateral.amount);
+    if let Some(collateral) = collateral {
+        let vault_info = ctx.accounts.listing.vault.to_account_info();
+        system_program::transfer(
+            CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+                from: ctx.accounts.listing.owner.to_account_info(),
+                to: vault_info.clone(),
+            }),
+            collateral.amount
+        )?;
+        let auction = &mut ctx.accounts.listing.auction;
+        auction.seller_collateral = collateral.amount;
+        auction.delivery_window = collateral.delivery_window;
+        assert_funds_conserved(&vault_info, auction)?;
+    }
+
+    emit!(CrowdAuctionCreated {
+        listing_id,
+        owner,
+        target,
+        share_mint: ctx.accounts.share_mint.key(),
+        collateral: staked,
+    });
+    Ok(())
+}
+
+// Add `amount` to the pool. The buyer fee comes out of it, as for a bid; the
+// rest is the contribution.
+pub fn contribute(ctx: Context<Contribute>, listing_id: [u8; 32], amount: u64) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let contributor = ctx.accounts.contributor.key();
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_alien, ErrorCode::NotCrowdAuction);
+    require!(contributor != auction.owner, ErrorCode::BidderIsOwner);
+    match auction.status {
+        AuctionStatus::Live => {}
+        AuctionStatus::Paused => {
+            return Err(ErrorCode::AuctionPaused.into());
+        }
+        _ => {
+            return Err(ErrorCode::AuctionEnded.into());
+        }
+    }
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= auction.end_time, ErrorCode::AuctionEnded);
+    require!(amount >= auction.minimum_bid, ErrorCode::MinimumBidError);
+
+    let vault_info = ctx.accounts.vault.to_account_info();
+    system_program::transfer(
+        CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.contributor.to_account_info(),
+            to: vault_info.clone(),
+        }),
+        amount
+    )?;
+
+    let buyer_fee_bps = ctx.accounts.auction_state.buyer_fee_bps_for(
+        auction,
+        ctx.accounts.collection_fee_config.as_deref(),
+        amount
+    );
+    let fee = math::compute_fee(amount, buyer_fee_bps)?;
+    let contribution = amount.try_sub(fee)?;
+    auction.fees = auction.fees.try_add(fee)?;
+    auction.total_amount = auction.total_amount.try_add(contribution)?;
+    auction.record_bid(
+        contributor,
+        contribution,
+        now,
+        PositionStore::Accounts(None),
+        false,
+        None
+    )?;
+    auction.highest_bid = auction.total_amount;
+    auction.highest_max_bid = auction.total_amount;
+    auction.bid_history.push(BidRecord { bidder: contributor, amount: contribution, time: now })?;
+
+    assert_funds_conserved(&vault_info, auction)?;
+
+    emit!(ContributionMade {
+        listing_id,
+        contributor,
+        amount: contribution,
+        total: auction.total_amount,
+    });
+    Ok(())
+}
+
+// Once a crowd auction has run out short of its target, each contributor takes
+// back their contribution plus their pro-rata part of the buyer fees. The
+// first refund voids the auction, after which the seller can take the NFT back.
+// The same goes for a sale whose seller never confirmed delivery, with the
+// seller's collateral shared out pro rata as well.
+pub fn refund_contribution(ctx: Context<RefundContribution>, listing_id: [u8; 32]) -> Result<()> {
+    let contributor = ctx.accounts.contributor.key();
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_alien, ErrorCode::NotCrowdAuction);
+    if auction.status != AuctionStatus::Voided {
+        let now = Clock::get()?.unix_timestamp;
+        if auction.delivery_overdue(now) {
+            auction.collateral_slashed = true;
+            emit!(SellerCollateralSlashed { listing_id, amount: auction.seller_collateral });
+        } else {
+            require!(now >= auction.end_time, ErrorCode::AuctionNotEnded);
+            require!(auction.total_amount < auction.reserve_price, ErrorCode::CrowdTargetMet);
+        }
+        auction.transition_to(AuctionStatus::Voided)?;
+    }
+
+    let index = auction.find_bidder(&contributor).map_err(|_| ErrorCode::NothingToWithdraw)?;
+    let contribution = std::mem::take(&mut auction.bids[index].amount);
+    require!(contribution > 0, ErrorCode::NothingToWithdraw);
+    let fee_share = math::pro_rata(auction.fees, contribution, auction.total_amount)?;
+    auction.fees = auction.fees.try_sub(fee_share)?;
+    let collateral_share = if auction.collateral_slashed {
+        math::pro_rata(auction.seller_collateral, contribution, auction.total_amount)?
+    } else {
+        0
+    };
+    auction.seller_collateral = auction.seller_collateral.try_sub(collateral_share)?;
+    auction.total_amount = auction.total_amount.try_sub(contribution)?;
+    auction.highest_bid = auction.total_amount;
+    auction.highest_max_bid = auction.total_amount;
+
+    let amount = contribution.try_add(fee_share)?.try_add(collateral_share)?;
+    let vault_info = ctx.accounts.vault.to_account_info();
+    vault::pay_out(
+        &vault_info,
+        &listing_id,
+        auction.vault_bump,
+        &ctx.accounts.contributor.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        amount
+    )?;
+    assert_funds_conserved(&vault_info, auction)?;
+
+    emit!(ContributionRefunded { listing_id, contributor, amount });
+    Ok(())
+}
+
+// A contributor to a crowd auction that met its target mints one share per
+// lamport they contributed. The shares are their fraction of the escrowed NFT.
+pub fn claim_crowd_shares(ctx: Context<ClaimCrowdShares>, listing_id: [u8; 32]) -> Result<()> {
+    let contributor = ctx.accounts.contributor.key();
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_alien, ErrorCode::NotCrowdAuction);
+    require!(auction.is_sold(), ErrorCode::AuctionNotEnded);
+    require!(
+        auction.seller_collateral == 0 || auction.delivery_confirmed,
+        ErrorCode::DeliveryNotConfirmed
+    );
+    wormhole::ensure_attested(auction)?;
+
+    let index = auction.find_bidder(&contributor).map_err(|_| ErrorCode::NothingToWithdraw)?;
+    let shares = std::mem::take(&mut auction.bids[index].amount);
+    require!(shares > 0, ErrorCode::NothingToWithdraw);
+
+    let auction_state = &ctx.accounts.auction_state;
+    let signer_seeds: &[&[&[u8]]] = &[&[AUCTION_STATE_SEED, &[auction_state.bump]]];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.contributor_shares.to_account_info(),
+                authority: auction_state.to_account_info(),
+            },
+            signer_seeds
+        ),
+        shares
+    )?;
+
+    emit!(CrowdSharesClaimed { listing_id, contributor, shares });
+    Ok(())
+}
+
+// The seller of a crowd sale delivered off-chain confirms they've delivered,
+// releasing the proceeds and the shares. It has to come within the delivery
+// window; after that the contributors can void the sale.
+pub fn confirm_delivery(ctx: Context<ConfirmDelivery>, listing_id: [u8; 32]) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.seller_collateral > 0, ErrorCode::NoSellerCollateral);
+    require!(auction.is_sold(), ErrorCode::AuctionNotEnded);
+    require!(!auction.delivery_confirmed, ErrorCode::DeliveryNotConfirmed);
+    let now = Clock::get()?.unix_timestamp;
+    require!(!auction.delivery_overdue(now), ErrorCode::DeliveryOverdue);
+    auction.delivery_confirmed = true;
+
+    emit!(DeliveryConfirmed { listing_id });
+    Ok(())
+}
+
+// Take the collateral back off a crowd auction that went unsold; a confirmed
+// sale returns it with the proceeds instead
+pub fn reclaim_seller_collateral(
+    ctx: Context<ReclaimSellerCollateral>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_unsold(), ErrorCode::ListingNotRelistable);
+    require!(!auction.collateral_slashed, ErrorCode::DeliveryOverdue);
+    let amount = std::mem::take(&mut auction.seller_collateral);
+    require!(amount > 0, ErrorCode::NoSellerCollateral);
+
+    let vault_info = ctx.accounts.vault.to_account_info();
+    vault::pay_out(
+        &vault_info,
+        &listing_id,
+        auction.vault_bump,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        amount
+    )?;
+    assert_funds_conserved(&vault_info, auction)?;
+
+    emit!(SellerCollateralReturned { listing_id, amount });
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::allowlist;
+use crate::errors::ErrorCode;
+use crate::events::{ BidPlaced, DelegatedBidCollected, DelegatedBidDropped, DelegatedBiddingSet };
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::place_bid::extend_if_sniped;
+use crate::invariants::assert_funds_conserved;
+use crate::math::{ self, CheckedMath };
+use crate::state::*;
+use crate::token_gate;
+use crate::token_payments::deposit_payment;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    FEATURE_FLAGS_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+};
+
+// Escrowless token bids. On a token listing the seller switches to delegated
+// bidding, bidders don't move their tokens into the token vault: they approve
+// the listing's vault PDA as delegate over their token account for at least the
+// bid, buyer fee included, and `place_delegated_bid` records it after checking
+// the approval and the balance behind it. Nothing is held, so there is nothing
+// to withdraw or refund.
+//
+// Once the auction ends, `collect_delegated_bid` pulls the leader's bid into
+// the token vault, after which the sale is claimed as usual. A leader whose
+// approval or balance no longer covers the bid is dropped and the next best
+// bid still clearing the reserve leads instead, to be collected by the next
+// call; with none left the listing closes unsold. Up to
+// AuctionDetails::MAX_DELEGATED_BIDS bids are kept to fall back on.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SetDelegatedBidding<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct PlaceDelegatedBid<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Grows by a step of bid history when a growable history is full; the bidder covers the rent
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        realloc = AuctionDetails::space(auction.bid_history.capacity_for_next_bid()),
+        realloc::payer = bidder,
+        realloc::zero = false
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    // The delegate bidders approve
+    #[account(seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    #[account(token::authority = bidder)]
+    pub bidder_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: the bidder's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, bidder.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    #[account(seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+    pub feature_flags: Account<'info, FeatureFlags>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct CollectDelegatedBid<'info> {
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: the leading bid's token account; read by hand, since a closed or
+    /// emptied account must drop the bid rather than fail the call
+    #[account(mut)]
+    pub bidder_token_account: UncheckedAccount<'info>,
+    #[account(
+        constraint = auction.payment_mint == Some(payment_mint.key()) @ ErrorCode::WrongPaymentMint
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Switch a token listing to delegated bidding, or back; only before the first bid
+pub fn set_delegated_bidding(
+    ctx: Context<SetDelegatedBidding>,
+    listing_id: [u8; 32],
+    enabled: bool
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+    require!(
+        !enabled ||
+            (auction.payment_mint.is_some() &&
+                auction.editions == 0 &&
+                auction.ticket_price == 0 &&
+                auction.candle_window == 0 &&
+                auction.position_tree.is_none() &&
+                !auction.second_price &&
+                !auction.is_alien),
+        ErrorCode::DelegatedBiddingUnavailable
+    );
+    auction.delegated_bidding = enabled;
+
+    emit!(DelegatedBiddingSet { listing_id, enabled });
+    Ok(())
+}
+
+// Bid `amount`, buyer fee included, from the bidder's token account, which
+// must already have the vault approved as delegate for it. A later bid from
+// the same bidder replaces their earlier one.
+pub fn place_delegated_bid(
+    ctx: Context<PlaceDelegatedBid>,
+    listing_id: [u8; 32],
+    amount: u64
+) -> Result<()> {
+    ctx.accounts.feature_flags.ensure_enabled(FeatureFlags::TOKEN_BIDS)?;
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let bidder = ctx.accounts.bidder.key();
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.delegated_bidding, ErrorCode::DelegatedBiddingUnavailable);
+    require!(!auction.has_bid_limits(), ErrorCode::BidLimitsApply);
+    require!(bidder != auction.owner, ErrorCode::BidderIsOwner);
+    allowlist::check(auction, &bidder, &[])?;
+    token_gate::check(auction, &bidder, None)?;
+    match auction.status {
+        AuctionStatus::Live => {}
+        AuctionStatus::Paused => {
+            return Err(ErrorCode::AuctionPaused.into());
+        }
+        _ => {
+            return Err(ErrorCode::AuctionEnded.into());
+        }
+    }
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= auction.end_time, ErrorCode::AuctionEnded);
+
+    let token_account = &ctx.accounts.bidder_token_account;
+    require!(
+        covers(token_account, auction.payment_mint, &ctx.accounts.vault.key(), amount),
+        ErrorCode::DelegateApprovalMissing
+    );
+
+    let buyer_fee_bps = ctx.accounts.auction_state.buyer_fee_bps_for(auction, None, amount);
+    let credited = amount.try_sub(math::compute_fee(amount, buyer_fee_bps)?)?;
+    auction.resolve_bid(bidder, credited, false)?;
+    auction.rank_delegated_bid(DelegatedBid {
+        bidder,
+        token_account: token_account.key(),
+        amount,
+        credited,
+    });
+    extend_if_sniped(&ctx.accounts.auction_state, auction, now)?;
+    // The account was already reallocated to fit this
+    auction.bid_history.grow_for_next_bid();
+    auction.bid_history.push(BidRecord { bidder, amount: credited, time: now })?;
+
+    emit!(BidPlaced {
+        listing_id,
+        sender: bidder,
+        value: credited,
+        referrer: None,
+        relayer: None,
+    });
+    Ok(())
+}
+
+// Pull the ended auction's leading delegated bid into the token vault, or drop
+// it and promote the next bid if its account no longer covers it
+pub fn collect_delegated_bid(
+    ctx: Context<CollectDelegatedBid>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.status == AuctionStatus::Ended, ErrorCode::AuctionNotEnded);
+    let leader = match auction.delegated_bids.first() {
+        Some(leader) => *leader,
+        None => {
+            return err!(ErrorCode::NothingToWithdraw);
+        }
+    };
+    let account_info = ctx.accounts.bidder_token_account.to_account_info();
+    require_keys_eq!(account_info.key(), leader.token_account, ErrorCode::InvalidRecipient);
+
+    let vault = ctx.accounts.vault.key();
+    let payment_mint = auction.payment_mint;
+    let covered = {
+        let data = account_info.try_borrow_data()?;
+        match TokenAccount::try_deserialize(&mut &data[..]) {
+            Ok(account) => {
+                account.owner == leader.bidder &&
+                    covers(&account, payment_mint, &vault, leader.amount)
+            }
+            Err(_) => false,
+        }
+    };
+    if !covered {
+        auction.delegated_bids.remove(0);
+        let reserve_price = auction.reserve_price;
+        auction.delegated_bids.retain(|bid| bid.credited >= reserve_price);
+        let next = auction.delegated_bids.first().copied();
+        auction.highest_bidder = next.map_or(Pubkey::default(), |bid| bid.bidder);
+        auction.highest_bid = next.map_or(0, |bid| bid.credited);
+        auction.highest_max_bid = auction.highest_bid;
+
+        emit!(DelegatedBidDropped {
+            listing_id,
+            bidder: leader.bidder,
+            next: next.map(|bid| bid.bidder),
+        });
+        return Ok(());
+    }
+
+    let arrived = deposit_payment(
+        &ctx.accounts.token_program.to_account_info(),
+        &account_info,
+        &ctx.accounts.payment_mint.to_account_info(),
+        &ctx.accounts.token_vault.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        leader.amount,
+        ctx.accounts.payment_mint.decimals,
+        &[&[VAULT_SEED, listing_id.as_ref(), &[auction.vault_bump]]]
+    )?;
+    // A mint's transfer fee comes out of the buyer fee first, so the books never
+    // claim more than the vault received
+    let credited = leader.credited.min(arrived);
+    // From here the sale is an escrowed one like any other
+    let now = Clock::get()?.unix_timestamp;
+    auction.fees = auction.fees.try_add(arrived.try_sub(credited)?)?;
+    auction.total_amount = auction.total_amount.try_add(credited)?;
+    let store = PositionStore::Accounts(None);
+    auction.record_bid(leader.bidder, credited, now, store, false, None)?;
+    auction.highest_bidder = leader.bidder;
+    auction.highest_bid = credited;
+    auction.highest_max_bid = credited;
+    auction.delegated_bids.clear();
+    ctx.accounts.token_vault.reload()?;
+    assert_funds_conserved(&ctx.accounts.token_vault.to_account_info(), auction)?;
+
+    emit!(DelegatedBidCollected { listing_id, bidder: leader.bidder, amount: leader.amount });
+    Ok(())
+}
+
+// Whether `account` holds, and has delegated to `vault`, at least `amount` of `mint`
+fn covers(account: &TokenAccount, mint: Option<Pubkey>, vault: &Pubkey, amount: u64) -> bool {
+    Some(account.mint) == mint &&
+        account.delegate == COption::Some(*vault) &&
+        account.delegated_amount >= amount &&
+        account.amount >= amount
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::{ DisputeOpened, DisputeResolved };
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED };
+
+// Disputes over a sale. While the marketplace sets a dispute window, a closed
+// sale's proceeds and NFT stay in escrow for that long: `claim_proceeds` and
+// `claim_nft` wait it out. The winner or the arbiter can open a dispute during
+// the window, which keeps both in escrow until the arbiter (or the authority)
+// resolves it. Resolving for the buyer voids the sale, so the winner withdraws
+// their bid and the seller takes the NFT back with `return_nft`, as for any
+// unsold listing; e.g. a stolen NFT taken down. Resolving for the seller
+// releases the sale to be claimed as usual.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct OpenDispute<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        constraint = auction.is_winner(&opened_by.key()) ||
+        auction_state.is_arbiter(&opened_by.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub opened_by: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ResolveDispute<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.is_arbiter(&arbiter.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    pub arbiter: Signer<'info>,
+}
+
+pub fn open_dispute(ctx: Context<OpenDispute>, listing_id: [u8; 32]) -> Result<()> {
+    let window = ctx.accounts.auction_state.dispute_window;
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_disputable(), ErrorCode::DisputeNotAllowed);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        auction.status == AuctionStatus::Ended && auction.in_dispute_hold(window, now),
+        ErrorCode::DisputeNotAllowed
+    );
+    auction.transition_to(AuctionStatus::Disputed)?;
+
+    emit!(DisputeOpened { listing_id, opened_by: ctx.accounts.opened_by.key() });
+    Ok(())
+}
+
+// Settle a held sale: `refund_buyer` voids it, otherwise an open dispute is
+// closed in the seller's favour once the window has passed
+pub fn resolve_dispute(
+    ctx: Context<ResolveDispute>,
+    listing_id: [u8; 32],
+    refund_buyer: bool
+) -> Result<()> {
+    let window = ctx.accounts.auction_state.dispute_window;
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_disputable(), ErrorCode::DisputeNotAllowed);
+    let now = Clock::get()?.unix_timestamp;
+    if refund_buyer {
+        require!(auction.in_dispute_hold(window, now), ErrorCode::DisputeNotAllowed);
+        auction.transition_to(AuctionStatus::Voided)?;
+    } else {
+        require!(auction.status == AuctionStatus::Disputed, ErrorCode::DisputeNotAllowed);
+        let window_over = now >= auction.closed_at.saturating_add(window);
+        require!(window_over, ErrorCode::DisputeWindowOpen);
+        auction.transition_to(AuctionStatus::Ended)?;
+    }
+
+    emit!(DisputeResolved { listing_id, refunded: refund_buyer });
+    Ok(())
+}
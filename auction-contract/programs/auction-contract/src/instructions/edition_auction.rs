@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::instructions::initialize_auction::open_auction;
+use crate::instructions::seller_profile::take_slot;
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, COLLECTION_SEED, SELLER_PROFILE_SEED, VAULT_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], minimum: u64, editions: u8, overrides: ListingOverrides)]
+pub struct InitializeEditionAuction<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = owner,
+        space = AuctionDetails::space(overrides.bid_history_capacity()),
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, owner.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    // Registry entry of the collection being listed, if any
+    #[account(
+        seeds = [COLLECTION_SEED, collection_config.collection.as_ref()],
+        bump = collection_config.bump
+    )]
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+    pub system_program: Program<'info, System>,
+}
+
+// List a limited edition: the best `editions` positions all win and each pays
+// the lowest of them. Nothing is escrowed; every winner mints their own edition
+// through `claim_nft`, and everyone else withdraws as usual.
+pub fn initialize_edition_auction(
+    ctx: Context<InitializeEditionAuction>,
+    listing_id: [u8; 32],
+    minimum: u64,
+    editions: u8,
+    overrides: ListingOverrides
+) -> Result<()> {
+    require!(
+        (2..=AuctionDetails::MAX_EDITIONS).contains(&(editions as usize)),
+        ErrorCode::InvalidEditionCount
+    );
+    // A buy-now purchase would take every edition at once
+    require!(overrides.buy_now_price.is_none(), ErrorCode::BuyNowUnavailable);
+
+    vault::fund(
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.system_program.to_account_info()
+    )?;
+    let mut auction = open_auction(
+        &ctx.accounts.auction_state,
+        listing_id,
+        minimum,
+        ctx.accounts.owner.key(),
+        &overrides,
+        ctx.accounts.collection_config.as_deref(),
+        None,
+        ctx.bumps.auction,
+        ctx.bumps.vault
+    )?;
+    auction.editions = editions;
+    take_slot(&ctx.accounts.auction_state, ctx.accounts.seller_profile.as_mut(), &mut auction)?;
+    ctx.accounts.auction.set_inner(auction);
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::emitter::{ self, event_authority, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::{ AuctionDeregistered, AuctionEnded };
+use crate::instructions::{ auction_registry, collection_stats };
+use crate::invariants::assert_funds_conserved;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::vault::Escrow;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    COLLECTION_FEE_SEED,
+    COLLECTION_STATS_SEED,
+    FLOOR_ORACLE_SEED,
+    REGISTRY_PAGE_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+    WSOL_UNWRAP_SEED,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct EndAuction<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    // Anyone may end an auction once its time is up, and earns the keeper tip for it
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    // Listings paid in a token only: the listing's token vault, and the caller's
+    // token account the tip is paid into
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: must be the caller's token account for the payment mint
+    #[account(mut)]
+    pub caller_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    // Wrapped SOL listings only: the account payouts to wallets are unwrapped
+    // through, and the native mint it's opened for
+    /// CHECK: created and closed again within the instruction by the escrow
+    #[account(mut, seeds = [WSOL_UNWRAP_SEED, listing_id.as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<InterfaceAccount<'info, Mint>>,
+    // SOL listings of a registered collection only: its stats and floor oracle,
+    // to record the sale on
+    #[account(
+        mut,
+        seeds = [COLLECTION_STATS_SEED, collection_stats.collection.as_ref()],
+        bump = collection_stats.bump
+    )]
+    pub collection_stats: Option<Account<'info, CollectionStats>>,
+    #[account(
+        mut,
+        seeds = [FLOOR_ORACLE_SEED, floor_oracle.load()?.collection.as_ref()],
+        bump = floor_oracle.load()?.bump
+    )]
+    pub floor_oracle: Option<AccountLoader<'info, FloorOracle>>,
+    // The registry page listing the auction, to take it off
+    #[account(
+        mut,
+        seeds = [REGISTRY_PAGE_SEED, &registry_page.load()?.index.to_le_bytes()],
+        bump = registry_page.load()?.bump
+    )]
+    pub registry_page: Option<AccountLoader<'info, AuctionRegistryPage>>,
+    // Read by `finalize_auction` only, to work out the sale's payouts: the
+    // listing's collection fee config, if it has one, and what its royalties are
+    // read from, as `claim_proceeds` takes them
+    #[account(
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Option<Account<'info, CollectionFeeConfig>>,
+    /// CHECK: checked against the NFT's mint by `royalties`
+    pub metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: must be the asset recorded at listing
+    pub core_asset: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the asset by `royalties`
+    pub core_collection: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Close a sold auction. Only the keeper tip moves here: the winner then pulls
+// the NFT with `claim_nft` and the seller pulls the proceeds with `claim_proceeds`.
+pub fn handler(ctx: &mut Context<EndAuction>, listing_id: [u8; 32]) -> Result<()> {
+    let events: EventAuthority = event_authority!(ctx);
+    let keeper_tip = ctx.accounts.auction_state.keeper_tip;
+    let auction = &mut ctx.accounts.auction;
+
+    // Ensure auction has ended
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= auction.end_time, ErrorCode::AuctionNotEnded);
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(auction.ticket_price == 0, ErrorCode::RaffleNotDrawn);
+    require!(auction.candle_window == 0, ErrorCode::CandleNotClosed);
+    require!(auction.highest_bid > 0, ErrorCode::NothingToWithdraw);
+    if auction.editions > 0 {
+        auction.close_edition_book()?;
+    } else {
+        require!(auction.highest_bid >= auction.reserve_price, ErrorCode::ReserveNotMet);
+    }
+
+    auction.transition_to(AuctionStatus::Ended)?;
+    auction.bid_snapshot = Some(auction.bid_history.snapshot_root());
+    if let Some(registry_page) = ctx.accounts.registry_page.as_ref() {
+        let mut page = registry_page.load_mut()?;
+        auction_registry::deregister(&mut page, auction.key(), auction)?;
+        emitter::emit(&events, AuctionDeregistered {
+            listing_id,
+            auction: auction.key(),
+            page: page.index,
+        })?;
+    }
+    collection_stats::record_sale(
+        auction,
+        ctx.accounts.collection_stats.as_mut(),
+        ctx.accounts.floor_oracle.as_ref(),
+        now
+    )?;
+
+    // The tip comes out of the buyer fees, never out of anyone's bid
+    let tip = keeper_tip.min(auction.fees);
+    auction.fees = auction.fees.try_sub(tip)?;
+    let escrow = Escrow::new(
+        auction,
+        &listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?.unwrapping(
+        ctx.accounts.unwrap_account.as_ref(),
+        ctx.accounts.native_mint.as_ref(),
+        ctx.accounts.caller.to_account_info()
+    );
+    let caller_token_account = ctx.accounts.caller_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let caller = escrow.account_of(
+        &ctx.accounts.caller.to_account_info(),
+        caller_token_account.as_ref()
+    )?;
+    escrow.pay(&caller, tip)?;
+    escrow.settle()?;
+    assert_funds_conserved(escrow.holding(), auction)?;
+
+    emitter::emit(&events, AuctionEnded {
+        listing_id,
+        winner: auction.highest_bidder,
+        amount: auction.highest_bid,
+        buy_now: false,
+    })?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::AuctionExtended;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ExtendAuction<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+// Seller moves the end of a running listing out to `new_end_time`, bids or
+// not. It can only ever get later, and never past the marketplace's longest
+// allowed duration counted from when it was listed.
+pub fn handler(ctx: Context<ExtendAuction>, listing_id: [u8; 32], new_end_time: i64) -> Result<()> {
+    let max_duration = ctx.accounts.auction_state.max_auction_duration;
+    let auction = &mut ctx.accounts.auction;
+
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(Clock::get()?.unix_timestamp < auction.end_time, ErrorCode::AuctionEnded);
+    require!(new_end_time > auction.end_time, ErrorCode::InvalidExtension);
+    require!(
+        max_duration == 0 || new_end_time.saturating_sub(auction.created_at) <= max_duration,
+        ErrorCode::AuctionTooLong
+    );
+    auction.end_time = new_end_time;
+
+    emit!(AuctionExtended { listing_id, end_time: new_end_time });
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::AuctionExtended;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ExtendIfNoBids<'info> {
+    // The seller, or the marketplace's automation authority on their behalf
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        constraint = auction_state.automation_authority == Some(signer.key()) ||
+        auction.owner == signer.key() @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub signer: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ExtendIfNoBids>, listing_id: [u8; 32], extra_seconds: i64) -> Result<()> {
+    require!(
+        extra_seconds > 0 && extra_seconds <= NftComAuction::MAX_NO_BID_EXTENSION,
+        ErrorCode::InvalidExtension
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &mut ctx.accounts.auction;
+
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+
+    // A window that already lapsed restarts from now rather than from the past
+    auction.end_time = auction.end_time.max(now) + extra_seconds;
+
+    emit!(AuctionExtended { listing_id, end_time: auction.end_time });
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::TokenAccount;
+use crate::errors::ErrorCode;
+use crate::escrow_adapter::*;
+use crate::events::NftDelivered;
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::initialize_auction::open_auction;
+use crate::instructions::seller_profile::take_slot;
+use crate::state::*;
+use crate::vault;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    SELLER_PROFILE_SEED,
+    VAULT_SEED,
+};
+
+#[derive(Accounts)]
+pub struct ManageEscrowAdapters<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], minimum: u64, overrides: ListingOverrides)]
+pub struct ListExternal<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = seller,
+        space = AuctionDetails::space(overrides.bid_history_capacity()),
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, seller.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, seller.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    // Still owned by the seller; the external escrow holds it through a delegation
+    #[account(
+        constraint = token_account.owner == seller.key() @ ErrorCode::InvalidExternalEscrow,
+        constraint = token_account.amount == 1 @ ErrorCode::InvalidExternalEscrow,
+        constraint = token_account.delegate.is_some() @ ErrorCode::InvalidExternalEscrow,
+        constraint = token_account.delegated_amount == 1 @ ErrorCode::InvalidExternalEscrow
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+    /// CHECK: must be an approved escrow adapter, checked in the handler
+    #[account(executable)]
+    pub adapter_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ReleaseExternal<'info> {
+    // Signs the release as the marketplace
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    /// CHECK: must match the token account recorded at listing
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+    /// CHECK: must match the adapter recorded at listing
+    #[account(executable)]
+    pub adapter_program: UncheckedAccount<'info>,
+}
+
+pub fn add_escrow_adapter(ctx: Context<ManageEscrowAdapters>, program: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    if !auction_state.escrow_adapters.contains(&program) {
+        require!(
+            auction_state.escrow_adapters.len() < NftComAuction::MAX_ESCROW_ADAPTERS,
+            ErrorCode::EscrowAdapterWhitelistFull
+        );
+        auction_state.escrow_adapters.push(program);
+    }
+    Ok(())
+}
+
+pub fn remove_escrow_adapter(ctx: Context<ManageEscrowAdapters>, program: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.escrow_adapters.retain(|key| *key != program);
+    Ok(())
+}
+
+// List an NFT without moving it out of the external escrow it sits in. The
+// listing id is derived from the seller and `overrides.name`, as for
+// initialize_auction.
+pub fn list_external<'info>(
+    ctx: Context<'_, '_, '_, 'info, ListExternal<'info>>,
+    listing_id: [u8; 32],
+    minimum: u64,
+    overrides: ListingOverrides
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let adapter_program = ctx.accounts.adapter_program.key();
+    require!(
+        ctx.accounts.auction_state.escrow_adapters.contains(&adapter_program),
+        ErrorCode::EscrowAdapterNotAllowed
+    );
+
+    let state_key = ctx.accounts.auction_state.key();
+    let bump = ctx.accounts.auction_state.bump;
+    let seller = ctx.accounts.seller.key();
+    let token_account = &ctx.accounts.token_account;
+
+    // The adapter vouches for the delegation before the listing goes live
+    let instruction = build_adapter_instruction(
+        adapter_program,
+        CONFIRM_INSTRUCTION_NAME,
+        &(ConfirmListingPayload {
+            listing_id,
+            seller,
+            mint: token_account.mint,
+        }),
+        vec![
+            AccountMeta::new_readonly(state_key, true),
+            AccountMeta::new_readonly(seller, true),
+            AccountMeta::new_readonly(token_account.key(), false)
+        ],
+        ctx.remaining_accounts
+    )?;
+    let mut account_infos = vec![
+        ctx.accounts.auction_state.to_account_info(),
+        ctx.accounts.seller.to_account_info(),
+        token_account.to_account_info()
+    ];
+    account_infos.extend(ctx.remaining_accounts.iter().cloned());
+    account_infos.push(ctx.accounts.adapter_program.to_account_info());
+    invoke_signed(&instruction, &account_infos, &[&[AUCTION_STATE_SEED, &[bump]]])?;
+
+    let external_escrow = ExternalEscrow {
+        program: adapter_program,
+        mint: token_account.mint,
+        token_account: token_account.key(),
+        released: false,
+    };
+    vault::fund(
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.system_program.to_account_info()
+    )?;
+    let mut auction = open_auction(
+        &ctx.accounts.auction_state,
+        listing_id,
+        minimum,
+        seller,
+        &overrides,
+        None,
+        Some(external_escrow),
+        ctx.bumps.auction,
+        ctx.bumps.vault
+    )?;
+    take_slot(&ctx.accounts.auction_state, ctx.accounts.seller_profile.as_mut(), &mut auction)?;
+    ctx.accounts.auction.set_inner(auction);
+    Ok(())
+}
+
+// Ask the external escrow to hand a sold listing's NFT to the winner; anyone may crank this
+pub fn release_external<'info>(
+    ctx: Context<'_, '_, '_, 'info, ReleaseExternal<'info>>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    release(
+        &ctx.accounts.auction_state,
+        &mut ctx.accounts.auction,
+        &ctx.accounts.token_account,
+        &ctx.accounts.adapter_program,
+        ctx.remaining_accounts,
+        listing_id
+    )
+}
+
+// Shared by `release_external` and the winner's `claim_nft`, so every check
+// on delivery lives here rather than in either caller
+pub(crate) fn release<'info>(
+    auction_state: &Account<'info, NftComAuction>,
+    auction: &mut Account<'info, AuctionDetails>,
+    token_account: &UncheckedAccount<'info>,
+    adapter_program: &UncheckedAccount<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let state_key = auction_state.key();
+    let bump = auction_state.bump;
+
+    require!(auction.is_sold(), ErrorCode::AuctionNotEnded);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !auction.in_dispute_hold(auction_state.dispute_window, now),
+        ErrorCode::DisputeWindowOpen
+    );
+    require!(auction.installments_complete(), ErrorCode::InstallmentsOutstanding);
+    require!(auction.delegated_bids.is_empty(), ErrorCode::DelegatedBidUncollected);
+    let escrow = auction.external_escrow.as_mut().ok_or(ErrorCode::NotExternalListing)?;
+    require!(!escrow.released, ErrorCode::ExternalEscrowReleased);
+    require_keys_eq!(adapter_program.key(), escrow.program, ErrorCode::EscrowAdapterNotAllowed);
+    require_keys_eq!(token_account.key(), escrow.token_account, ErrorCode::InvalidExternalEscrow);
+    escrow.released = true;
+    let escrow = escrow.clone();
+
+    // The winner stays on record as the buyer even when someone else receives the NFT
+    let recipient = auction.deliver_to.unwrap_or(auction.highest_bidder);
+    // What the winner pays, which on a second-price listing isn't their own bid
+    let price = auction.winning_price();
+    let instruction = build_adapter_instruction(
+        escrow.program,
+        RELEASE_INSTRUCTION_NAME,
+        &(ReleasePayload {
+            listing_id,
+            winner: auction.highest_bidder,
+            price,
+            recipient,
+        }),
+        vec![
+            AccountMeta::new_readonly(state_key, true),
+            AccountMeta::new(escrow.token_account, false)
+        ],
+        remaining_accounts
+    )?;
+    emit!(NftDelivered {
+        listing_id,
+        winner: auction.highest_bidder,
+        recipient,
+        price,
+    });
+
+    let mut account_infos = vec![auction_state.to_account_info(), token_account.to_account_info()];
+    account_infos.extend(remaining_accounts.iter().cloned());
+    account_infos.push(adapter_program.to_account_info());
+
+    // Persist the release flag before handing control to foreign code
+    auction.exit(&crate::ID)?;
+    invoke_signed(&instruction, &account_infos, &[&[AUCTION_STATE_SEED, &[bump]]])?;
+    Ok(())
+}
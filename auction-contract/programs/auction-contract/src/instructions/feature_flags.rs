@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::FeatureFlagsChanged;
+use crate::state::*;
+use crate::{ AUCTION_STATE_SEED, FEATURE_FLAGS_SEED };
+
+// Staged rollouts. Each gated instruction family checks its bit in the
+// FeatureFlags account, which the admin creates once per deployment and flips
+// bits on as families go live there.
+
+#[derive(Accounts)]
+pub struct InitFeatureFlags<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = payer,
+        space = FeatureFlags::SPACE,
+        seeds = [FEATURE_FLAGS_SEED],
+        bump
+    )]
+    pub feature_flags: Account<'info, FeatureFlags>,
+    pub authority: Signer<'info>,
+    // Pays the rent, so a multisig vault holding the authority needn't hold SOL
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeatureFlags<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+    pub feature_flags: Account<'info, FeatureFlags>,
+    pub authority: Signer<'info>,
+}
+
+pub fn init_feature_flags(ctx: Context<InitFeatureFlags>, flags: u64) -> Result<()> {
+    require!(flags & !FeatureFlags::ALL == 0, ErrorCode::InvalidFeatureFlags);
+    let feature_flags = &mut ctx.accounts.feature_flags;
+    feature_flags.flags = flags;
+    feature_flags.version = FeatureFlags::VERSION;
+    feature_flags.bump = ctx.bumps.feature_flags;
+
+    emit!(FeatureFlagsChanged { flags });
+    Ok(())
+}
+
+// Replace the whole set of enabled families with `flags`
+pub fn set_feature_flags(ctx: Context<SetFeatureFlags>, flags: u64) -> Result<()> {
+    require!(flags & !FeatureFlags::ALL == 0, ErrorCode::InvalidFeatureFlags);
+    ctx.accounts.feature_flags.flags = flags;
+
+    emit!(FeatureFlagsChanged { flags });
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::errors::ErrorCode;
+use crate::events::FeesWithdrawn;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::token_payments::transfer_payment;
+use crate::{ AUCTION_STATE_SEED, AUDIT_LOG_SEED, FEE_TREASURY_SEED, ROLES_SEED };
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct InitFeeTreasury<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = payer,
+        space = FeeTreasury::SPACE,
+        seeds = [FEE_TREASURY_SEED, mint.as_ref()],
+        bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    pub authority: Signer<'info>,
+    // Rent payer; needn't be the authority
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.may_withdraw_fees(roles.as_deref(), &signer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(
+        mut,
+        seeds = [FEE_TREASURY_SEED, native_mint::ID.as_ref()],
+        bump = fee_treasury.bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    // Whoever is the fee recipient at withdrawal time, not at settlement
+    #[account(mut, address = auction_state.fee_recipient @ ErrorCode::InvalidFeeRecipient)]
+    pub fee_recipient: SystemAccount<'info>,
+    pub signer: Signer<'info>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+// Treasuries of SPL token currencies hold their fees in their associated token
+// account for the mint
+#[derive(Accounts)]
+pub struct WithdrawTokenFees<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.may_withdraw_fees(roles.as_deref(), &signer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(
+        mut,
+        seeds = [FEE_TREASURY_SEED, mint.key().as_ref()],
+        bump = fee_treasury.bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = fee_treasury
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    // The fee recipient's token account for the mint
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = recipient_token_account.owner ==
+        auction_state.fee_recipient @ ErrorCode::InvalidFeeRecipient
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub signer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+pub fn init_fee_treasury(ctx: Context<InitFeeTreasury>, mint: Pubkey) -> Result<()> {
+    let treasury = &mut ctx.accounts.fee_treasury;
+    treasury.mint = mint;
+    treasury.collected = 0;
+    treasury.withdrawn = 0;
+    treasury.version = FeeTreasury::VERSION;
+    treasury.bump = ctx.bumps.fee_treasury;
+    Ok(())
+}
+
+// Send `amount` of the accumulated lamport fees to the fee recipient. The
+// treasury keeps its rent-exempt minimum, so it can never be drained closed.
+pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+    let treasury = ctx.accounts.fee_treasury.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(treasury.data_len());
+    let available = treasury.lamports().saturating_sub(rent_exempt);
+    require!(amount > 0 && amount <= available, ErrorCode::InsufficientTreasuryFunds);
+
+    treasury.sub_lamports(amount)?;
+    ctx.accounts.fee_recipient.add_lamports(amount)?;
+    ctx.accounts.fee_treasury.withdrawn = ctx.accounts.fee_treasury.withdrawn.try_add(amount)?;
+
+    emit!(FeesWithdrawn {
+        mint: ctx.accounts.fee_treasury.mint,
+        recipient: ctx.accounts.fee_recipient.key(),
+        amount,
+    });
+    AuditLog::record(
+        &ctx.accounts.audit_log,
+        AuditAction::TreasuryWithdrawal,
+        ctx.accounts.signer.key(),
+        ctx.accounts.fee_treasury.mint,
+        available,
+        available.try_sub(amount)?
+    )
+}
+
+// Send `amount` of a token treasury's accumulated fees to the fee recipient
+pub fn withdraw_token_fees(ctx: Context<WithdrawTokenFees>, amount: u64) -> Result<()> {
+    let available = ctx.accounts.treasury_token_account.amount;
+    require!(amount > 0 && amount <= available, ErrorCode::InsufficientTreasuryFunds);
+
+    let mint = ctx.accounts.mint.key();
+    let treasury = &ctx.accounts.fee_treasury;
+    transfer_payment(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.treasury_token_account.to_account_info(),
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.recipient_token_account.to_account_info(),
+        &treasury.to_account_info(),
+        amount,
+        ctx.accounts.mint.decimals,
+        &[&[FEE_TREASURY_SEED, mint.as_ref(), &[treasury.bump]]]
+    )?;
+    ctx.accounts.fee_treasury.withdrawn = ctx.accounts.fee_treasury.withdrawn.try_add(amount)?;
+
+    emit!(FeesWithdrawn {
+        mint,
+        recipient: ctx.accounts.recipient_token_account.key(),
+        amount,
+    });
+    AuditLog::record(
+        &ctx.accounts.audit_log,
+        AuditAction::TreasuryWithdrawal,
+        ctx.accounts.signer.key(),
+        mint,
+        available,
+        available.try_sub(amount)?
+    )
+}
@@ -0,0 +1,288 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::{ self, Mint, Token, TokenAccount, Transfer };
+use crate::errors::ErrorCode;
+use crate::events::{ FixedPriceCancelled, FixedPriceListed, FixedPriceSold };
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::loyalty::{ self, LoyaltyActivity };
+use crate::instructions::nft_escrow::release_escrowed_nft;
+use crate::math::{ self, CheckedMath };
+use crate::royalties;
+use crate::state::*;
+use crate::{
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    FEE_TREASURY_SEED,
+    FIXED_PRICE_SEED,
+    LOYALTY_SEED,
+    NFT_ESCROW_SEED,
+    PROVENANCE_SEED,
+};
+
+// Instant-sale listings. The NFT sits in the same escrow as an auctioned one,
+// and a sale pays the same fees and royalties as an auction's settlement, but
+// there are no bids: the buyer pays the seller, the treasury and the creators
+// directly in the transaction that delivers the NFT.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct CreateListing<'info> {
+    // Authority of every NFT escrow account
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = seller,
+        space = FixedPriceListing::SPACE,
+        seeds = [FIXED_PRICE_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, FixedPriceListing>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, seller.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    #[account(
+        constraint = nft_mint.decimals == 0 && nft_mint.supply == 1 @ ErrorCode::InvalidNftMint
+    )]
+    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = seller,
+        constraint = seller_nft_account.amount == 1 @ ErrorCode::NftNotHeld
+    )]
+    pub seller_nft_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = seller,
+        token::mint = nft_mint,
+        token::authority = auction_state,
+        seeds = [NFT_ESCROW_SEED, nft_mint.key().as_ref()],
+        bump
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Each creator named by the NFT's royalty data follows as a remaining account
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct BuyListing<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [FIXED_PRICE_SEED, listing_id.as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+        close = seller
+    )]
+    pub listing: Account<'info, FixedPriceListing>,
+    /// CHECK: only receives lamports; must be the listing's seller
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: the buyer's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, buyer.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    #[account(address = listing.mint @ ErrorCode::NftEscrowMismatch)]
+    pub nft_mint: Account<'info, Mint>,
+    #[account(mut, seeds = [NFT_ESCROW_SEED, nft_mint.key().as_ref()], bump)]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = buyer
+    )]
+    pub buyer_nft_account: Account<'info, TokenAccount>,
+    // Protocol fees accumulate here until `withdraw_fees`
+    #[account(
+        mut,
+        seeds = [FEE_TREASURY_SEED, native_mint::ID.as_ref()],
+        bump = fee_treasury.bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    // The NFT's sale history, opened by its first sale
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = Provenance::SPACE,
+        seeds = [PROVENANCE_SEED, nft_mint.key().as_ref()],
+        bump
+    )]
+    pub provenance: Account<'info, Provenance>,
+    // The buyer's loyalty account, if they have one, to earn points for the purchase
+    #[account(mut, seeds = [LOYALTY_SEED, buyer.key().as_ref()], bump = loyalty_account.bump)]
+    pub loyalty_account: Option<Account<'info, LoyaltyAccount>>,
+    // Token Metadata of the NFT, read for creator royalties
+    /// CHECK: checked against the NFT's mint by `royalties`
+    pub metadata: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct CancelListing<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [FIXED_PRICE_SEED, listing_id.as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ErrorCode::Unauthorized,
+        close = seller
+    )]
+    pub listing: Account<'info, FixedPriceListing>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [NFT_ESCROW_SEED, listing.mint.as_ref()],
+        bump
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = listing.mint,
+        token::authority = seller
+    )]
+    pub seller_nft_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+// Escrow the seller's NFT and offer it at `price` until it's bought or cancelled
+pub fn create_listing(ctx: Context<CreateListing>, listing_id: [u8; 32], price: u64) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    require!(price > 0, ErrorCode::MinimumBidError);
+
+    token::transfer(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.seller_nft_account.to_account_info(),
+            to: ctx.accounts.nft_escrow.to_account_info(),
+            authority: ctx.accounts.seller.to_account_info(),
+        }),
+        1
+    )?;
+
+    let listing = &mut ctx.accounts.listing;
+    listing.version = FixedPriceListing::VERSION;
+    listing.listing_id = listing_id;
+    listing.seller = ctx.accounts.seller.key();
+    listing.mint = ctx.accounts.nft_mint.key();
+    listing.price = price;
+    listing.bump = ctx.bumps.listing;
+
+    emit!(FixedPriceListed { listing_id, seller: listing.seller, mint: listing.mint, price });
+    Ok(())
+}
+
+// The buyer pays the price plus the buyer fee. The seller fee and creator
+// royalties come out of the price, as at an auction's settlement.
+pub fn buy_listing<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyListing<'info>>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let auction_state = &ctx.accounts.auction_state;
+    let listing = &ctx.accounts.listing;
+    let buyer = ctx.accounts.buyer.key();
+    require_keys_neq!(buyer, listing.seller, ErrorCode::BidderIsOwner);
+
+    let price = listing.price;
+    let buyer_fee = math::compute_fee(price, auction_state.buyer_fee_bps_at(price))?;
+    let seller_fee = math::compute_fee(price, auction_state.seller_fee_bps)?;
+    let creator_shares = match ctx.accounts.metadata.as_ref() {
+        Some(metadata) => royalties::creator_shares(metadata, &listing.mint, price)?,
+        None if auction_state.enforces_royalties() => {
+            return Err(ErrorCode::MissingRoyaltyAccounts.into());
+        }
+        None => vec![],
+    };
+    let royalty_total = creator_shares
+        .iter()
+        .try_fold(0u64, |total, (_, amount)| total.try_add(*amount))?;
+    let seller_earnings = price
+        .try_sub(seller_fee)?
+        .checked_sub(royalty_total)
+        .ok_or(ErrorCode::RoyaltiesExceedProceeds)?;
+    require!(
+        ctx.remaining_accounts.len() >= creator_shares.len(),
+        ErrorCode::MissingRoyaltyAccounts
+    );
+
+    let fee = buyer_fee.try_add(seller_fee)?;
+    let payments = [
+        (ctx.accounts.seller.to_account_info(), seller_earnings),
+        (ctx.accounts.fee_treasury.to_account_info(), fee),
+    ];
+    let creator_payments = creator_shares
+        .into_iter()
+        .zip(ctx.remaining_accounts)
+        .map(|((creator, amount), creator_info)| {
+            require_keys_eq!(creator_info.key(), creator, ErrorCode::MissingRoyaltyAccounts);
+            Ok((creator_info.clone(), amount))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    for (to, amount) in payments.into_iter().chain(creator_payments) {
+        if amount == 0 {
+            continue;
+        }
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer { from: ctx.accounts.buyer.to_account_info(), to }
+            ),
+            amount
+        )?;
+    }
+    ctx.accounts.fee_treasury.collected = ctx.accounts.fee_treasury.collected.try_add(fee)?;
+
+    release_escrowed_nft(
+        &ctx.accounts.auction_state,
+        &ctx.accounts.token_program,
+        &ctx.accounts.nft_escrow,
+        &ctx.accounts.buyer_nft_account.to_account_info(),
+        &ctx.accounts.seller.to_account_info()
+    )?;
+
+    let seller = ctx.accounts.listing.seller;
+    let mint = ctx.accounts.listing.mint;
+    ctx.accounts.provenance.record_sale(mint, ctx.bumps.provenance, Sale {
+        seller,
+        buyer,
+        price,
+        time: Clock::get()?.unix_timestamp,
+    })?;
+    loyalty::accrue(
+        &ctx.accounts.auction_state,
+        ctx.accounts.loyalty_account.as_mut(),
+        LoyaltyActivity::Purchase,
+        price
+    )?;
+
+    emit!(FixedPriceSold { listing_id, buyer, seller, price });
+    Ok(())
+}
+
+// Take the NFT back out of escrow; the listing's rent goes back to the seller
+pub fn cancel_listing(ctx: Context<CancelListing>, listing_id: [u8; 32]) -> Result<()> {
+    let seller = ctx.accounts.seller.to_account_info();
+    release_escrowed_nft(
+        &ctx.accounts.auction_state,
+        &ctx.accounts.token_program,
+        &ctx.accounts.nft_escrow,
+        &ctx.accounts.seller_nft_account.to_account_info(),
+        &seller
+    )?;
+
+    emit!(FixedPriceCancelled { listing_id });
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::instructions::audit_log::record_fee_change;
+use crate::events::{ ConfigChangeExecuted, ConfigChangeQueued };
+use crate::math::CheckedMath;
+use crate::realms;
+use crate::state::*;
+use crate::{ AUCTION_STATE_SEED, AUDIT_LOG_SEED, PARAM_CHANGE_SEED };
+
+// Fees, feature flags and the fee recipient can change through a timelocked
+// queue: `queue_config_change` records the change and `execute_config_change`
+// applies it once the config change delay has passed, so users see it coming.
+// While the admin holds the config they queue changes themselves, and must
+// once they set a delay. Governance takes over from the admin once
+// `set_governance` is called: from then on these parameters only change
+// through the queue, signed by the realm's governance account (spl-governance
+// signs for it when a proposal executes), and never wait less than
+// PARAM_CHANGE_DELAY. A Realms governance can also set fees directly from its
+// proposals; see `realms`.
+
+#[derive(Accounts)]
+pub struct SetGovernance<'info> {
+    // The admin hands over once; afterwards only governance can move itself
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.governance.unwrap_or(auction_state.authority) ==
+            signer.key() @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.config_controller() == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = payer,
+        space = ParamChangeProposal::SPACE,
+        seeds = [PARAM_CHANGE_SEED, &auction_state.param_changes_queued.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, ParamChangeProposal>,
+    // The governance account once set, the admin authority before that
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Anyone may execute a change whose wait is over
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(mut, seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [PARAM_CHANGE_SEED, &proposal.id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, ParamChangeProposal>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GovernanceSetFees<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = realms::is_proposal_signer(
+            &auction_state,
+            &governance.key()
+        ) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Signed by spl-governance for the governance PDA when the proposal executes
+    pub governance: Signer<'info>,
+    // Appended to once the audit log exists
+    /// CHECK: the audit log's fixed address, which may still be empty
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump)]
+    pub audit_log: UncheckedAccount<'info>,
+}
+
+pub fn set_governance(ctx: Context<SetGovernance>, governance: Pubkey) -> Result<()> {
+    ctx.accounts.auction_state.governance = Some(governance);
+    Ok(())
+}
+
+pub fn queue_config_change(ctx: Context<QueueConfigChange>, change: ParamChange) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    let proposal = &mut ctx.accounts.proposal;
+    match change {
+        ParamChange::Fees { buyer_fee_bps, seller_fee_bps } => {
+            auction_state.ensure_unfrozen()?;
+            NftComAuction::validate_fees(buyer_fee_bps, seller_fee_bps)?;
+        }
+        ParamChange::FeeRecipient(_) => {
+            auction_state.ensure_unfrozen()?;
+        }
+        ParamChange::ConfigChangeDelay(delay) => {
+            require!(delay >= 0, ErrorCode::InvalidConfigChangeDelay);
+        }
+        _ => {}
+    }
+
+    let executable_at = Clock::get()?.unix_timestamp.try_add(auction_state.config_change_wait())?;
+    proposal.id = auction_state.param_changes_queued;
+    proposal.change = change.clone();
+    proposal.executable_at = executable_at;
+    proposal.executed = false;
+    proposal.version = ParamChangeProposal::VERSION;
+    proposal.bump = ctx.bumps.proposal;
+
+    auction_state.param_changes_queued = auction_state.param_changes_queued.try_add(1)?;
+
+    emit!(ConfigChangeQueued { id: proposal.id, change, executable_at });
+    Ok(())
+}
+
+// The realm's vote and hold-up time stand in for the queue's delay
+pub fn governance_set_fees(
+    ctx: Context<GovernanceSetFees>,
+    buyer_fee_bps: u64,
+    seller_fee_bps: u64
+) -> Result<()> {
+    NftComAuction::validate_fees(buyer_fee_bps, seller_fee_bps)?;
+    let auction_state = &mut ctx.accounts.auction_state;
+    let old = (auction_state.buyer_fee_bps, auction_state.seller_fee_bps);
+    auction_state.update_fees(buyer_fee_bps, seller_fee_bps)?;
+    record_fee_change(
+        &ctx.accounts.audit_log,
+        ctx.accounts.governance.key(),
+        old,
+        (buyer_fee_bps, seller_fee_bps)
+    )
+}
+
+pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    let proposal = &mut ctx.accounts.proposal;
+
+    require!(!proposal.executed, ErrorCode::ParamChangeExecuted);
+    require!(
+        Clock::get()?.unix_timestamp >= proposal.executable_at,
+        ErrorCode::ParamChangeNotReady
+    );
+
+    match proposal.change {
+        ParamChange::Fees { buyer_fee_bps, seller_fee_bps } => {
+            let old = (auction_state.buyer_fee_bps, auction_state.seller_fee_bps);
+            auction_state.update_fees(buyer_fee_bps, seller_fee_bps)?;
+            // Nobody signs an execution, so the queued proposal stands in as the actor
+            record_fee_change(
+                &ctx.accounts.audit_log,
+                proposal.key(),
+                old,
+                (buyer_fee_bps, seller_fee_bps)
+            )?;
+        }
+        ParamChange::FeatureFlags(flags) => {
+            auction_state.feature_flags = flags;
+        }
+        ParamChange::FeeRecipient(fee_recipient) => {
+            auction_state.set_fee_recipient(fee_recipient)?;
+        }
+        ParamChange::ConfigChangeDelay(delay) => {
+            auction_state.config_change_delay = delay;
+        }
+    }
+
+    proposal.executed = true;
+
+    emit!(ConfigChangeExecuted { id: proposal.id, change: proposal.change.clone() });
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::*;
+use crate::AUCTION_STATE_SEED;
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = NftComAuction::SPACE,
+        seeds = [AUCTION_STATE_SEED],
+        bump
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Becomes the marketplace admin
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Create the marketplace state with its initial fees and auction defaults; the
+// signer becomes the admin. Whitelists start out empty.
+pub fn handler(
+    ctx: Context<Initialize>,
+    fee_recipient: Pubkey,
+    nft_contract: Pubkey,
+    buyer_fee_bps: u64,
+    seller_fee_bps: u64,
+    defaults: AuctionDefaults
+) -> Result<()> {
+    require!(defaults.is_valid(), ErrorCode::InvalidAuctionDefaults);
+    NftComAuction::validate_fees(buyer_fee_bps, seller_fee_bps)?;
+
+    let authority = ctx.accounts.authority.key();
+    ctx.accounts.auction_state.set_inner(NftComAuction {
+        version: NftComAuction::VERSION,
+        fee_recipient,
+        buyer_fee_bps,
+        seller_fee_bps,
+        nft_contract,
+        authority,
+        bump: ctx.bumps.auction_state,
+        hook_programs: vec![],
+        aggregator_programs: vec![],
+        bridge_emitters: vec![],
+        escrow_adapters: vec![],
+        governance: None,
+        feature_flags: 0,
+        param_changes_queued: 0,
+        swap_programs: vec![],
+        payment_processors: vec![],
+        default_duration: defaults.default_duration,
+        min_increment_bps: defaults.min_increment_bps,
+        sniping_time_window: defaults.sniping_time_window,
+        time_extension: defaults.time_extension,
+        reserve_policy: defaults.reserve_policy,
+        automation_authority: None,
+        archive_retention: NftComAuction::DEFAULT_ARCHIVE_RETENTION,
+        rent_destination: authority,
+        fee_manager: None,
+        keeper_tip: 0,
+        max_extensions: NftComAuction::DEFAULT_MAX_EXTENSIONS,
+        referral_share_bps: 0,
+        max_active_auctions_per_seller: 0,
+        dispute_window: 0,
+        arbiter: None,
+        max_auction_duration: 0,
+        pending_authority: None,
+        config_change_delay: 0,
+        loyalty_schedule: LoyaltySchedule::default(),
+        receipt_tree: None,
+        buyer_fee_tiers: vec![],
+        config_frozen: false,
+        retraction_penalty_bps: 0,
+        retraction_penalty_to_seller: false,
+    });
+    Ok(())
+}
@@ -0,0 +1,647 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::{ self, InitializeAccount3, Mint, Token, TokenAccount, Transfer };
+use crate::errors::ErrorCode;
+use crate::events::*;
+use crate::instructions::auction_registry;
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::co_seller;
+use crate::instructions::collection_registry;
+use crate::instructions::membership;
+use crate::instructions::seller_profile::take_slot;
+use crate::math::CheckedMath;
+use crate::settlement::{ self, Custody };
+use crate::state::*;
+use crate::vault;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_SEED,
+    MEMBERSHIP_DISCOUNT_SEED,
+    NFT_ESCROW_SEED,
+    REGISTRY_PAGE_SEED,
+    ROLES_SEED,
+    SELLER_PROFILE_SEED,
+    VAULT_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], minimum: u64, owner: Pubkey, overrides: ListingOverrides)]
+pub struct InitializeAuction<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = payer,
+        space = AuctionDetails::space(overrides.bid_history_capacity()),
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    // Pays the rent of the listing's accounts: the owner, or a marketplace sponsoring it
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, owner.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Admin or fee manager co-signing a negotiated fee override
+    pub fee_approver: Option<Signer<'info>>,
+    // Only needed when the approver holds the fee-manager role
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    // Registry entry of the collection being listed, if any
+    #[account(
+        seeds = [COLLECTION_SEED, collection_config.collection.as_ref()],
+        bump = collection_config.bump
+    )]
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+    // Required with a collection config, and so while the marketplace curates listings
+    /// CHECK: the NFT's Token Metadata account, checked against the mint when read
+    pub nft_metadata: Option<UncheckedAccount<'info>>,
+    // A membership discount and the seller's membership NFT qualifying for it
+    #[account(
+        seeds = [MEMBERSHIP_DISCOUNT_SEED, membership_discount.collection.as_ref()],
+        bump = membership_discount.bump
+    )]
+    pub membership_discount: Option<Account<'info, MembershipDiscount>>,
+    pub membership_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: the membership NFT's Token Metadata account, checked against its mint when read
+    pub membership_metadata: Option<UncheckedAccount<'info>>,
+    // An auction registry page with room, to list the auction on
+    #[account(
+        mut,
+        seeds = [REGISTRY_PAGE_SEED, &registry_page.load()?.index.to_le_bytes()],
+        bump = registry_page.load()?.bump
+    )]
+    pub registry_page: Option<AccountLoader<'info, AuctionRegistryPage>>,
+    #[account(
+        constraint = nft_mint.decimals == 0 && nft_mint.supply == 1 @ ErrorCode::InvalidNftMint
+    )]
+    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = owner,
+        constraint = seller_nft_account.amount == 1 @ ErrorCode::NftNotHeld
+    )]
+    pub seller_nft_account: Account<'info, TokenAccount>,
+    // Holds the NFT until settlement or cancellation, under the state PDA's authority
+    #[account(
+        init,
+        payer = payer,
+        token::mint = nft_mint,
+        token::authority = auction_state,
+        seeds = [NFT_ESCROW_SEED, nft_mint.key().as_ref()],
+        bump
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Each listing's auction PDA, vault, NFT mint, the seller's token account for
+// the NFT and the NFT's escrow PDA are passed as remaining accounts, five per
+// listing and in the same order as the listings. Batches listed under a
+// collection config add the NFT's Token Metadata account as a sixth.
+#[derive(Accounts)]
+pub struct InitializeAuctionsBatch<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Every listing in the batch belongs to the signer
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, owner.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    // Registry entry shared by the whole drop, if any
+    #[account(
+        seeds = [COLLECTION_SEED, collection_config.collection.as_ref()],
+        bump = collection_config.bump
+    )]
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AuctionParams {
+    pub listing_id: [u8; 32],
+    pub minimum: u64,
+    pub overrides: ListingOverrides,
+}
+
+// Upper bound on listings per batch, keeps a batch within one transaction's compute
+pub const MAX_BATCH_AUCTIONS: usize = 10;
+// Remaining accounts each listing in a batch takes, without a collection config
+const BATCH_ACCOUNTS_PER_AUCTION: usize = 5;
+
+// `fee_override_bps` gives the listing deal-specific fees, used at bid and
+// settlement; the admin or fee manager has to co-sign it as `fee_approver`. A
+// co-owned listing takes each co-seller's signature or approval PDA as
+// remaining accounts.
+pub fn handler(
+    ctx: Context<InitializeAuction>,
+    listing_id: [u8; 32],
+    minimum: u64,
+    owner: Pubkey,
+    overrides: ListingOverrides,
+    fee_override_bps: Option<FeeOverride>
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    // The listing id is in the owner's namespace, so only they can list under it
+    require_keys_eq!(owner, ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+    if let Some(fees) = fee_override_bps {
+        let auction_state = &ctx.accounts.auction_state;
+        let roles = ctx.accounts.roles.as_deref();
+        let approved = ctx.accounts.fee_approver
+            .as_ref()
+            .is_some_and(|approver| auction_state.manages_fees(roles, &approver.key()));
+        require!(approved, ErrorCode::FeeOverrideNotApproved);
+        NftComAuction::validate_fees(fees.buyer_fee_bps, fees.seller_fee_bps)?;
+    }
+    if overrides.co_owned == Some(true) {
+        co_seller::verify_consent(
+            &listing_id,
+            &owner,
+            overrides.payout_splits.as_deref().unwrap_or_default(),
+            ctx.remaining_accounts
+        )?;
+    }
+    let accounts = &mut ctx.accounts;
+    let mut auction = list_nft(
+        ListedNft {
+            auction_state: &accounts.auction_state,
+            owner: &accounts.owner,
+            payer: &accounts.payer,
+            vault: &accounts.vault,
+            seller_profile: accounts.seller_profile.as_mut(),
+            collection_config: accounts.collection_config.as_deref(),
+            nft_metadata: accounts.nft_metadata.as_ref().map(|metadata| metadata.as_ref()),
+            nft_mint: &accounts.nft_mint,
+            seller_nft_account: &accounts.seller_nft_account,
+            nft_escrow: &accounts.nft_escrow,
+            token_program: &accounts.token_program,
+            system_program: &accounts.system_program,
+        },
+        listing_id,
+        minimum,
+        &overrides,
+        ctx.bumps.auction,
+        ctx.bumps.vault
+    )?;
+    // Deal-specific terms take precedence over the collection's
+    if fee_override_bps.is_some() {
+        auction.fee_override = fee_override_bps;
+    }
+    auction.seller_fee_discount_bps = membership::discount_bps(
+        ctx.accounts.membership_discount.as_deref(),
+        &owner,
+        ctx.accounts.membership_token_account.as_deref(),
+        ctx.accounts.membership_metadata.as_ref().map(|metadata| metadata.as_ref())
+    )?;
+    if let Some(registry_page) = ctx.accounts.registry_page.as_ref() {
+        let mut page = registry_page.load_mut()?;
+        let key = ctx.accounts.auction.key();
+        auction_registry::register(&mut page, key, &mut auction)?;
+        emit!(AuctionRegistered {
+            listing_id,
+            auction: key,
+            page: page.index,
+            end_time: auction.end_time,
+        });
+    }
+    ctx.accounts.auction.set_inner(auction);
+    Ok(())
+}
+
+// The accounts a single NFT is listed with, shared by the instructions listing one
+pub(crate) struct ListedNft<'a, 'info> {
+    pub auction_state: &'a Account<'info, NftComAuction>,
+    pub owner: &'a Signer<'info>,
+    pub payer: &'a Signer<'info>,
+    pub vault: &'a SystemAccount<'info>,
+    pub seller_profile: Option<&'a mut Account<'info, SellerProfile>>,
+    pub collection_config: Option<&'a CollectionConfig>,
+    pub nft_metadata: Option<&'a AccountInfo<'info>>,
+    pub nft_mint: &'a Account<'info, Mint>,
+    pub seller_nft_account: &'a Account<'info, TokenAccount>,
+    pub nft_escrow: &'a Account<'info, TokenAccount>,
+    pub token_program: &'a Program<'info, Token>,
+    pub system_program: &'a Program<'info, System>,
+}
+
+// Fund the vault, move the NFT into escrow and build the listing, which the
+// caller stores in the auction's freshly created PDA
+pub(crate) fn list_nft(
+    accounts: ListedNft,
+    listing_id: [u8; 32],
+    minimum: u64,
+    overrides: &ListingOverrides,
+    bump: u8,
+    vault_bump: u8
+) -> Result<AuctionDetails> {
+    let collection = collection_registry::verified_collection(
+        accounts.auction_state,
+        accounts.collection_config,
+        accounts.nft_metadata,
+        &accounts.nft_mint.key()
+    )?;
+    let payer = accounts.payer.to_account_info();
+    let system_program = accounts.system_program.to_account_info();
+    vault::fund(&payer, &accounts.vault.to_account_info(), &system_program)?;
+    let seller_nft_account = accounts.seller_nft_account.to_account_info();
+    let custody = Custody {
+        nft_escrow: Some(accounts.nft_escrow),
+        token_account: Some(&seller_nft_account),
+        token_program: Some(accounts.token_program),
+        ..Custody::new(accounts.auction_state, &payer, &system_program)
+    };
+    settlement::adapter(AssetStandard::Legacy).escrow(
+        &custody,
+        accounts.nft_mint.key(),
+        &accounts.owner.to_account_info()
+    )?;
+    let mut auction = open_auction(
+        accounts.auction_state,
+        listing_id,
+        minimum,
+        accounts.owner.key(),
+        overrides,
+        accounts.collection_config,
+        None,
+        bump,
+        vault_bump
+    )?;
+    auction.nft_mint = Some(accounts.nft_mint.key());
+    auction.collection = collection;
+    take_slot(accounts.auction_state, accounts.seller_profile, &mut auction)?;
+    Ok(auction)
+}
+
+// Create several listings for the signer at once; fails as a whole if any one is invalid
+pub fn initialize_auctions_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, InitializeAuctionsBatch<'info>>,
+    auctions: Vec<AuctionParams>
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    require!(
+        !auctions.is_empty() && auctions.len() <= MAX_BATCH_AUCTIONS,
+        ErrorCode::InvalidBatchSize
+    );
+    let collection = ctx.accounts.collection_config.as_deref();
+    let accounts_per_auction = BATCH_ACCOUNTS_PER_AUCTION + (collection.is_some() as usize);
+    require!(
+        ctx.remaining_accounts.len() == accounts_per_auction * auctions.len(),
+        ErrorCode::InvalidBatchSize
+    );
+
+    let owner = ctx.accounts.owner.key();
+    let chunks = ctx.remaining_accounts.chunks(accounts_per_auction);
+    for (params, accounts) in auctions.into_iter().zip(chunks) {
+        // Co-sellers' consent is only taken by initialize_auction
+        require!(params.overrides.co_owned != Some(true), ErrorCode::CoSellerNotApproved);
+        let (account, vault_account) = (&accounts[0], &accounts[1]);
+        let (mint_account, seller_nft_account, nft_escrow) = (&accounts[2], &accounts[3], &accounts[4]);
+        let verified = collection_registry::verified_collection(
+            &ctx.accounts.auction_state,
+            collection,
+            accounts.get(BATCH_ACCOUNTS_PER_AUCTION),
+            &mint_account.key()
+        )?;
+        let bump = create_auction_account(
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+            account,
+            &params.listing_id,
+            params.overrides.bid_history_capacity()
+        )?;
+        let (vault_address, vault_bump) = Pubkey::find_program_address(
+            &[VAULT_SEED, params.listing_id.as_ref()],
+            &crate::ID
+        );
+        require_keys_eq!(vault_account.key(), vault_address, ErrorCode::InvalidListingId);
+        vault::fund(
+            &ctx.accounts.owner.to_account_info(),
+            vault_account,
+            &ctx.accounts.system_program.to_account_info()
+        )?;
+
+        // The same checks InitializeAuction makes through its account constraints
+        let nft_mint = Account::<Mint>::try_from(mint_account)?;
+        require!(nft_mint.decimals == 0 && nft_mint.supply == 1, ErrorCode::InvalidNftMint);
+        let seller_token = Account::<TokenAccount>::try_from(seller_nft_account)?;
+        require_keys_eq!(seller_token.mint, nft_mint.key(), ErrorCode::NftNotHeld);
+        require_keys_eq!(seller_token.owner, owner, ErrorCode::NftNotHeld);
+        require!(seller_token.amount == 1, ErrorCode::NftNotHeld);
+        create_nft_escrow(
+            &ctx.accounts.owner,
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.auction_state,
+            nft_escrow,
+            mint_account
+        )?;
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: seller_nft_account.clone(),
+                to: nft_escrow.clone(),
+                authority: ctx.accounts.owner.to_account_info(),
+            }),
+            1
+        )?;
+
+        let mut auction = open_auction(
+            &ctx.accounts.auction_state,
+            params.listing_id,
+            params.minimum,
+            owner,
+            &params.overrides,
+            collection,
+            None,
+            bump,
+            vault_bump
+        )?;
+        auction.nft_mint = Some(nft_mint.key());
+        auction.collection = verified;
+        take_slot(&ctx.accounts.auction_state, ctx.accounts.seller_profile.as_mut(), &mut auction)?;
+        let mut data = account.try_borrow_mut_data()?;
+        auction.try_serialize(&mut &mut data[..])?;
+    }
+    Ok(())
+}
+
+// What `init` does for a single auction, for callers creating several at once
+fn create_auction_account<'info>(
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    account: &AccountInfo<'info>,
+    listing_id: &[u8; 32],
+    bid_history_capacity: u16
+) -> Result<u8> {
+    let (address, bump) = Pubkey::find_program_address(
+        &[AUCTION_SEED, listing_id.as_ref()],
+        &crate::ID
+    );
+    require_keys_eq!(account.key(), address, ErrorCode::InvalidListingId);
+    require!(account.data_is_empty(), ErrorCode::ListingIdTaken);
+
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::CreateAccount {
+                from: payer.to_account_info(),
+                to: account.clone(),
+            },
+            &[&[AUCTION_SEED, listing_id.as_ref(), &[bump]]]
+        ),
+        Rent::get()?.minimum_balance(AuctionDetails::space(bid_history_capacity)),
+        AuctionDetails::space(bid_history_capacity) as u64,
+        &crate::ID
+    )?;
+    Ok(bump)
+}
+
+// What `init` does for a single NFT escrow, for callers creating several at once
+fn create_nft_escrow<'info>(
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    token_program: &Program<'info, Token>,
+    auction_state: &Account<'info, NftComAuction>,
+    escrow: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>
+) -> Result<()> {
+    let mint_key = mint.key();
+    let (address, bump) = Pubkey::find_program_address(
+        &[NFT_ESCROW_SEED, mint_key.as_ref()],
+        &crate::ID
+    );
+    require_keys_eq!(escrow.key(), address, ErrorCode::NftEscrowMismatch);
+
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::CreateAccount {
+                from: payer.to_account_info(),
+                to: escrow.clone(),
+            },
+            &[&[NFT_ESCROW_SEED, mint_key.as_ref(), &[bump]]]
+        ),
+        Rent::get()?.minimum_balance(TokenAccount::LEN),
+        TokenAccount::LEN as u64,
+        &token::ID
+    )?;
+    token::initialize_account3(
+        CpiContext::new(token_program.to_account_info(), InitializeAccount3 {
+            account: escrow.clone(),
+            mint: mint.clone(),
+            authority: auction_state.to_account_info(),
+        })
+    )
+}
+
+// Build a live listing owned by `owner`, filling whatever `overrides` leaves
+// unset from the marketplace defaults. Shared by native and externally
+// escrowed listings; only the latter carry `external_escrow`. The caller
+// stores the result in the auction's freshly created PDA.
+pub(crate) fn open_auction(
+    auction_state: &NftComAuction,
+    listing_id: [u8; 32],
+    minimum: u64,
+    owner: Pubkey,
+    overrides: &ListingOverrides,
+    collection: Option<&CollectionConfig>,
+    external_escrow: Option<ExternalEscrow>,
+    bump: u8,
+    vault_bump: u8
+) -> Result<AuctionDetails> {
+    require!(minimum > 0, ErrorCode::MinimumBidError);
+    let name = overrides.name.clone().unwrap_or_default();
+    require!(name.len() <= AuctionDetails::MAX_NAME_LEN, ErrorCode::ListingNameTooLong);
+    require!(
+        name.bytes().all(|byte| byte == b' ' || byte.is_ascii_graphic()),
+        ErrorCode::InvalidListingName
+    );
+    require!(
+        listing_id == AuctionDetails::listing_id_for(&owner, &name),
+        ErrorCode::InvalidListingId
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let end_time = match overrides.end_time {
+        Some(end_time) => end_time,
+        None => now.try_add(auction_state.default_duration)?,
+    };
+    require!(end_time > now, ErrorCode::EndTimeError);
+
+    let reserve_price = match (overrides.reserve_price, auction_state.reserve_policy) {
+        (Some(reserve_price), _) => reserve_price,
+        (None, ReservePolicy::NoReserve) => 0,
+        (None, ReservePolicy::MatchMinimum) => minimum,
+        (None, ReservePolicy::Required) => {
+            return Err(ErrorCode::ReserveRequired.into());
+        }
+    };
+    let min_increment_bps = overrides.min_increment_bps.unwrap_or(auction_state.min_increment_bps);
+    let sniping_time_window = overrides.sniping_time_window.unwrap_or(
+        auction_state.sniping_time_window
+    );
+    let time_extension = overrides.time_extension.unwrap_or(auction_state.time_extension);
+    require!(
+        sniping_time_window >= 0 && time_extension >= 0,
+        ErrorCode::InvalidAntiSnipingConfig
+    );
+    let candle_window = overrides.candle_window.unwrap_or(0);
+    require!(
+        (0..=end_time - now).contains(&candle_window) &&
+            (candle_window == 0 || overrides.buy_now_price.is_none()),
+        ErrorCode::InvalidCandleWindow
+    );
+    // USD prices are converted with a SOL/USD feed, so only SOL listings can use one
+    if let Some(pricing) = overrides.usd_pricing {
+        require!(
+            pricing.minimum_bid_usd > 0 &&
+                overrides.payment_mint.unwrap_or(native_mint::ID) == native_mint::ID,
+            ErrorCode::InvalidUsdPricing
+        );
+    }
+    let rental_days = overrides.rental_days.unwrap_or(0);
+    require!(rental_days <= AuctionDetails::MAX_RENTAL_DAYS, ErrorCode::InvalidRentalTerm);
+    // A candle's drawn cutoff takes the place of anti-sniping extensions
+    let (sniping_time_window, time_extension) = if candle_window > 0 {
+        (0, 0)
+    } else {
+        (sniping_time_window, time_extension)
+    };
+    if let Some(buy_now_price) = overrides.buy_now_price {
+        require!(
+            buy_now_price >= minimum && buy_now_price >= reserve_price,
+            ErrorCode::InvalidBuyNowPrice
+        );
+    }
+    let payout_splits = overrides.payout_splits.clone().unwrap_or_default();
+    PayoutSplit::validate(&payout_splits)?;
+    if let Some(beneficiary) = overrides.beneficiary {
+        beneficiary.validate()?;
+    }
+    let bid_history = BidHistory::new(
+        overrides.bid_history_capacity(),
+        overrides.grow_bid_history.unwrap_or(false)
+    )?;
+    if let Some(collection) = collection {
+        require!(collection.enabled, ErrorCode::CollectionNotEnabled);
+    }
+
+    let auction = AuctionDetails {
+        version: AuctionDetails::VERSION,
+        listing_id,
+        name,
+        highest_bid: 0,
+        highest_bidder: Pubkey::default(),
+        highest_max_bid: 0,
+        bids: vec![],
+        minimum_bid: minimum,
+        end_time,
+        fees: 0,
+        status: AuctionStatus::Live,
+        is_alien: false,
+        total_amount: 0,
+        owner,
+        bid_pages: 0,
+        paged_total: 0,
+        settlement_hook: None,
+        external_escrow,
+        randomness: None,
+        position_tree: None,
+        compressed_positions: 0,
+        compressed_total: 0,
+        collection: collection.map(|config| config.collection),
+        fee_override: collection.and_then(|config| config.fee_override),
+        collection_permissions: collection.map_or(0, |config| config.permissions),
+        payment_processor: None,
+        off_chain_reference: None,
+        metadata_uri: String::new(),
+        reserve_price,
+        min_increment_bps,
+        min_increment: overrides.min_increment.unwrap_or(0),
+        created_at: now,
+        closed_at: 0,
+        deliver_to: None,
+        vacation_paused: false,
+        holds_seller_slot: false,
+        bump,
+        vault_bump,
+        nft_mint: None,
+        asset_standard: AssetStandard::Legacy,
+        buy_now_price: overrides.buy_now_price,
+        nft_claimed: false,
+        sniping_time_window,
+        time_extension,
+        extensions: 0,
+        bundle_mints: vec![],
+        editions: 0,
+        edition_book: vec![],
+        clearing_price: 0,
+        editions_claimed: 0,
+        allowlist_root: overrides.allowlist_root,
+        gate_mint: overrides.gate_mint,
+        seller_paused: false,
+        payout_splits,
+        ticket_price: 0,
+        second_bid: 0,
+        second_price: overrides.second_price.unwrap_or(false),
+        candle_window,
+        candle_marks: vec![],
+        candle_cutoff: 0,
+        rental_days,
+        rental_expires_at: 0,
+        payment_mint: overrides.payment_mint,
+        usd_pricing: overrides.usd_pricing,
+        usd_priced_at: 0,
+        bid_bond: 0,
+        bonds_held: 0,
+        seller_collateral: 0,
+        delivery_window: 0,
+        delivery_confirmed: false,
+        collateral_slashed: false,
+        alien_attestation: None,
+        bid_snapshot: None,
+        runners_up: vec![],
+        installment_plan: None,
+        delegated_bidding: false,
+        delegated_bids: vec![],
+        max_bids_per_bidder: 0,
+        bid_cooldown: 0,
+        co_owned: overrides.co_owned.unwrap_or(false),
+        proceeds_vesting: None,
+        seller_fee_discount_bps: 0,
+        beneficiary: overrides.beneficiary,
+        registry_page: None,
+        compressed_leaf: None,
+        settlement_quote: None,
+        bid_history,
+    };
+
+    // Listing opens with no bids; the first bid must come through `place_bid`
+    // from a wallet other than the owner.
+    emit!(AuctionInitialized { listing_id, minimum, end_time });
+    Ok(auction)
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::errors::ErrorCode;
+use crate::events::{ InstallmentPaid, InstallmentPlanSet, InstallmentsDefaulted };
+use crate::invariants::assert_funds_conserved;
+use crate::math::{ self, CheckedMath, BPS_DENOMINATOR };
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, VAULT_SEED };
+
+// Installment plans. A seller can let the winner of a high-value listing pay
+// over time: bids are made as the first installment, and the winner pays the
+// rest into the vault on schedule with `pay_installment`, each adding to their
+// position. The NFT and the proceeds stay in escrow until the last one is in,
+// then are claimed as for any sale. A winner who misses a due date can be
+// declared in default by the seller, who keeps the plan's penalty out of what
+// the winner paid; the sale is voided, so the winner withdraws the rest and the
+// seller takes the NFT back with `return_nft`.
+//
+// Single sales in SOL only, and only set before the first bid, so everyone
+// bids knowing the terms.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SetInstallmentPlan<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct PayInstallment<'info> {
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub winner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct DefaultInstallments<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Offer the winner `count` installments, `interval` seconds apart, keeping
+// `penalty_bps` of what they paid should they miss one; a count of 0 drops the plan
+pub fn set_installment_plan(
+    ctx: Context<SetInstallmentPlan>,
+    listing_id: [u8; 32],
+    count: u8,
+    interval: i64,
+    penalty_bps: u16
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+    require!(
+        auction.payment_mint.is_none() &&
+            auction.editions == 0 &&
+            auction.ticket_price == 0 &&
+            auction.rental_days == 0 &&
+            !auction.is_alien,
+        ErrorCode::InvalidInstallmentPlan
+    );
+    auction.installment_plan = if count == 0 {
+        None
+    } else {
+        require!(
+            (2..=InstallmentPlan::MAX_INSTALLMENTS).contains(&count) &&
+                interval > 0 &&
+                (penalty_bps as u64) <= BPS_DENOMINATOR,
+            ErrorCode::InvalidInstallmentPlan
+        );
+        Some(InstallmentPlan { count, interval, penalty_bps, paid: 1 })
+    };
+
+    emit!(InstallmentPlanSet { listing_id, count, interval, penalty_bps });
+    Ok(())
+}
+
+// Pay the winner's next installment into escrow. Late payments are taken for
+// as long as the seller hasn't declared the winner in default.
+pub fn pay_installment(ctx: Context<PayInstallment>, listing_id: [u8; 32]) -> Result<()> {
+    let winner = ctx.accounts.winner.key();
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.status == AuctionStatus::Ended, ErrorCode::AuctionNotEnded);
+    require!(auction.is_winner(&winner), ErrorCode::Unauthorized);
+    require!(!auction.installments_complete(), ErrorCode::NoInstallmentDue);
+
+    let amount = auction.installment_amount();
+    system_program::transfer(
+        CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.winner.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+        }),
+        amount
+    )?;
+    // Kept on the winner's inline position, so a voided sale refunds it like the bid
+    let position = auction.bid_of_mut(&winner).ok_or(ErrorCode::InvalidInstallmentPlan)?;
+    position.amount = position.amount.try_add(amount)?;
+    auction.total_amount = auction.total_amount.try_add(amount)?;
+    let mut plan = auction.installment_plan.ok_or(ErrorCode::InvalidInstallmentPlan)?;
+    plan.paid = plan.paid.try_add(1)?;
+    auction.installment_plan = Some(plan);
+    assert_funds_conserved(&ctx.accounts.vault.to_account_info(), auction)?;
+
+    emit!(InstallmentPaid {
+        listing_id,
+        winner,
+        amount,
+        paid: plan.paid,
+        remaining: plan.count - plan.paid,
+    });
+    Ok(())
+}
+
+// Declare the winner in default once an installment is overdue: the seller
+// keeps the penalty and the sale is voided
+pub fn default_installments(
+    ctx: Context<DefaultInstallments>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.status == AuctionStatus::Ended, ErrorCode::AuctionNotEnded);
+    let plan = match auction.installment_plan {
+        Some(plan) if plan.paid < plan.count => plan,
+        _ => {
+            return err!(ErrorCode::NoInstallmentDue);
+        }
+    };
+    let now = Clock::get()?.unix_timestamp;
+    require!(now > plan.next_due(auction.closed_at), ErrorCode::InstallmentNotOverdue);
+
+    let winner = auction.highest_bidder;
+    let position = auction.bid_of_mut(&winner).ok_or(ErrorCode::InvalidInstallmentPlan)?;
+    let penalty = math::compute_fee(position.amount, plan.penalty_bps as u64)?;
+    position.amount = position.amount.try_sub(penalty)?;
+    vault::pay_out(
+        &ctx.accounts.vault.to_account_info(),
+        &listing_id,
+        auction.vault_bump,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        penalty
+    )?;
+    auction.transition_to(AuctionStatus::Voided)?;
+    assert_funds_conserved(&ctx.accounts.vault.to_account_info(), auction)?;
+
+    emit!(InstallmentsDefaulted { listing_id, winner, paid: plan.paid, penalty });
+    Ok(())
+}
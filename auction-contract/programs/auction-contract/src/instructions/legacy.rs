@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::events::DeprecatedInstructionUsed;
+use crate::instructions::initialize_auction::{ self, InitializeAuction };
+use crate::state::*;
+
+// Shims keeping the previous argument layouts working for one release cycle.
+// Each one announces itself through DeprecatedInstructionUsed and hands off to
+// the current handler; drop them in the release after the replacement ships.
+//
+// `place_bid` has no shim: its old layout carried no amount, so there is
+// nothing to translate into an escrowed bid.
+
+fn deprecated(instruction: &str, replacement: &str) {
+    emit!(DeprecatedInstructionUsed {
+        instruction: instruction.to_string(),
+        replacement: replacement.to_string(),
+    });
+}
+
+// Old layout took an explicit end time and an opening bidder. Listings no
+// longer open with a bid, so `bidder` is ignored.
+pub fn initialize_auction_v1(
+    ctx: Context<InitializeAuction>,
+    listing_id: [u8; 32],
+    minimum: u64,
+    end_time: i64,
+    owner: Pubkey,
+    _bidder: Option<Pubkey>
+) -> Result<()> {
+    deprecated("initialize_auction_v1", "initialize_auction");
+    let overrides = ListingOverrides { end_time: Some(end_time), ..Default::default() };
+    initialize_auction::handler(ctx, listing_id, minimum, owner, overrides, None)
+}
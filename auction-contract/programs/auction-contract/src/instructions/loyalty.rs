@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::events::LoyaltyPointsEarned;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::LOYALTY_SEED;
+
+// Loyalty points for marketplace rewards programs. Direct and proxy bids earn
+// points on the amount bid, and purchases (claiming a won auction, buying a
+// fixed-price listing) on the price paid, at the rates of the admin's
+// `LoyaltySchedule`. Only SOL volume counts; listings paid in a token earn
+// nothing. Points only ever grow here: spending them is up to the marketplace,
+// reading `get_loyalty_balance` or the account itself.
+
+#[derive(Accounts)]
+pub struct InitLoyaltyAccount<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = LoyaltyAccount::SPACE,
+        seeds = [LOYALTY_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub loyalty_account: Account<'info, LoyaltyAccount>,
+    /// CHECK: only used as a seed; the points belong to this key
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_loyalty_account(ctx: Context<InitLoyaltyAccount>) -> Result<()> {
+    let account = &mut ctx.accounts.loyalty_account;
+    account.owner = ctx.accounts.owner.key();
+    account.points = 0;
+    account.bid_volume = 0;
+    account.purchase_volume = 0;
+    account.version = LoyaltyAccount::VERSION;
+    account.bump = ctx.bumps.loyalty_account;
+    Ok(())
+}
+
+pub(crate) enum LoyaltyActivity {
+    Bid,
+    Purchase,
+}
+
+// Credit `volume` lamports of `activity` to the user's loyalty account, when
+// they passed one. Callers pin the account to the user through its seeds.
+pub(crate) fn accrue(
+    auction_state: &NftComAuction,
+    account: Option<&mut Account<LoyaltyAccount>>,
+    activity: LoyaltyActivity,
+    volume: u64
+) -> Result<()> {
+    let account = match account {
+        Some(account) => account,
+        None => {
+            return Ok(());
+        }
+    };
+    let schedule = &auction_state.loyalty_schedule;
+    let rate = match activity {
+        LoyaltyActivity::Bid => {
+            account.bid_volume = account.bid_volume.try_add(volume)?;
+            schedule.bid_points_per_sol
+        }
+        LoyaltyActivity::Purchase => {
+            account.purchase_volume = account.purchase_volume.try_add(volume)?;
+            schedule.purchase_points_per_sol
+        }
+    };
+    let points = schedule.points_for(rate, volume, Clock::get()?.unix_timestamp)?;
+    if points > 0 {
+        account.points = account.points.try_add(points)?;
+        emit!(LoyaltyPointsEarned { owner: account.owner, points, total: account.points });
+    }
+    Ok(())
+}
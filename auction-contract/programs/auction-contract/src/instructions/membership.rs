@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::errors::ErrorCode;
+use crate::events::MembershipDiscountSet;
+use crate::instructions::collection_registry::read_verified_collection;
+use crate::math::BPS_DENOMINATOR;
+use crate::state::*;
+use crate::{ AUCTION_STATE_SEED, MEMBERSHIP_DISCOUNT_SEED, ROLES_SEED };
+
+// Membership discounts. The admin or a fee manager can set a discount for
+// holders of a membership collection, e.g. half off the fees for holders of the
+// marketplace's pass. A bidder presents an NFT verified in the collection with
+// their bid, and the discount comes off that bid's buyer fee and is recorded on
+// their bid receipt; a seller presents one when listing, and the discount is
+// recorded on the listing and comes off the seller fee at settlement.
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey)]
+pub struct CreateMembershipDiscount<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.manages_fees(roles.as_deref(), &signer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(
+        init,
+        payer = signer,
+        space = MembershipDiscount::SPACE,
+        seeds = [MEMBERSHIP_DISCOUNT_SEED, collection.as_ref()],
+        bump
+    )]
+    pub membership_discount: Account<'info, MembershipDiscount>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMembershipDiscount<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.manages_fees(roles.as_deref(), &signer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(
+        mut,
+        seeds = [MEMBERSHIP_DISCOUNT_SEED, membership_discount.collection.as_ref()],
+        bump = membership_discount.bump
+    )]
+    pub membership_discount: Account<'info, MembershipDiscount>,
+    pub signer: Signer<'info>,
+}
+
+pub fn create_membership_discount(
+    ctx: Context<CreateMembershipDiscount>,
+    collection: Pubkey,
+    discount_bps: u64
+) -> Result<()> {
+    require!(discount_bps <= BPS_DENOMINATOR, ErrorCode::InvalidMembershipDiscount);
+
+    let discount = &mut ctx.accounts.membership_discount;
+    discount.collection = collection;
+    discount.discount_bps = discount_bps;
+    discount.version = MembershipDiscount::VERSION;
+    discount.bump = ctx.bumps.membership_discount;
+
+    emit!(MembershipDiscountSet { collection, discount_bps });
+    Ok(())
+}
+
+// Bids already placed and listings already made keep the discount they got
+pub fn update_membership_discount(
+    ctx: Context<UpdateMembershipDiscount>,
+    discount_bps: u64
+) -> Result<()> {
+    require!(discount_bps <= BPS_DENOMINATOR, ErrorCode::InvalidMembershipDiscount);
+
+    let discount = &mut ctx.accounts.membership_discount;
+    discount.discount_bps = discount_bps;
+
+    emit!(MembershipDiscountSet { collection: discount.collection, discount_bps });
+    Ok(())
+}
+
+// The discount `holder` gets, in basis points of the fee: that of `discount`
+// when `holding` is their token account for an NFT whose `metadata` verifies
+// it in the discount's collection, none when nothing is presented
+pub(crate) fn discount_bps(
+    discount: Option<&MembershipDiscount>,
+    holder: &Pubkey,
+    holding: Option<&TokenAccount>,
+    metadata: Option<&AccountInfo>
+) -> Result<u64> {
+    let discount = match discount {
+        Some(discount) => discount,
+        None => {
+            return Ok(0);
+        }
+    };
+    let (holding, metadata) = match (holding, metadata) {
+        (Some(holding), Some(metadata)) => (holding, metadata),
+        _ => {
+            return err!(ErrorCode::NotAMember);
+        }
+    };
+    require!(holding.owner == *holder && holding.amount > 0, ErrorCode::NotAMember);
+    require!(
+        read_verified_collection(metadata, &holding.mint)? == Some(discount.collection),
+        ErrorCode::NotAMember
+    );
+    Ok(discount.discount_bps)
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::AccountMigrated;
+use crate::migrations;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct MigrateAuction<'info> {
+    /// CHECK: may still hold an old layout, so it's only checked to be this
+    /// program's auction PDA; the migration reads the layout from its version
+    #[account(mut, owner = crate::ID, seeds = [AUCTION_SEED, listing_id.as_ref()], bump)]
+    pub auction: UncheckedAccount<'info>,
+    // Covers any extra rent the new layout needs
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAuctionState<'info> {
+    /// CHECK: may still hold an old layout; see MigrateAuction
+    #[account(mut, owner = crate::ID, seeds = [AUCTION_STATE_SEED], bump)]
+    pub auction_state: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Bring an auction written by an earlier program version up to the current
+// layout. Anyone may crank this, since the result only depends on the account;
+// current auctions are left untouched.
+pub fn migrate_auction(ctx: Context<MigrateAuction>, _listing_id: [u8; 32]) -> Result<()> {
+    let account = ctx.accounts.auction.to_account_info();
+    let version = migrations::version_of(&account)?;
+    let upgraded = migrations::upgrade_auction(version, &account.try_borrow_data()?)?;
+    if let Some(auction) = upgraded {
+        let space = AuctionDetails::space(auction.bid_history.capacity);
+        migrations::rewrite(
+            &account,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            space,
+            &auction
+        )?;
+        emit!(AccountMigrated {
+            account: account.key(),
+            from_version: version,
+            to_version: AuctionDetails::VERSION,
+        });
+    }
+    Ok(())
+}
+
+// Same for the global state; only its admin may run it
+pub fn migrate_auction_state(ctx: Context<MigrateAuctionState>) -> Result<()> {
+    let account = ctx.accounts.auction_state.to_account_info();
+    let version = migrations::version_of(&account)?;
+    let upgraded = migrations::upgrade_auction_state(version, &account.try_borrow_data()?)?;
+    if let Some(auction_state) = upgraded {
+        require_keys_eq!(
+            auction_state.authority,
+            ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        migrations::rewrite(
+            &account,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            NftComAuction::SPACE,
+            &auction_state
+        )?;
+        emit!(AccountMigrated {
+            account: account.key(),
+            from_version: version,
+            to_version: NftComAuction::VERSION,
+        });
+    }
+    Ok(())
+}
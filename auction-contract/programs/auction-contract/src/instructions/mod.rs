@@ -0,0 +1,147 @@
+#![allow(ambiguous_glob_reexports)]
+
+pub mod accept_highest_bid;
+pub mod admin;
+pub mod auction_registry;
+pub mod auction_template;
+pub mod audit_log;
+pub mod alien_attestation;
+pub mod bid_bond;
+pub mod bidder_profile;
+pub mod blacklist;
+pub mod bridge_bid;
+pub mod bundle;
+pub mod buy_now;
+pub mod candle_auction;
+pub mod cancel_auction;
+pub mod claim;
+pub mod clone_listing;
+pub mod co_seller;
+pub mod collection_fees;
+pub mod collection_registry;
+pub mod collection_stats;
+pub mod commit_randomness;
+pub mod compressed_bid;
+pub mod compressed_nft;
+pub mod crowd_auction;
+pub mod dispute;
+pub mod edition_auction;
+pub mod core_asset;
+pub mod delegated_bid;
+pub mod end_auction;
+pub mod extend_auction;
+pub mod extend_if_no_bids;
+pub mod external_listing;
+pub mod feature_flags;
+pub mod fee_treasury;
+pub mod fixed_price;
+pub mod governance;
+pub mod initialize;
+pub mod initialize_auction;
+pub mod installments;
+pub mod legacy;
+pub mod loyalty;
+pub mod membership;
+pub mod migrate;
+pub mod nft_escrow;
+pub mod off_chain_settlement;
+pub mod offers;
+pub mod open_bid_page;
+pub mod participation_receipt;
+pub mod place_bid;
+pub mod place_bid_with_swap;
+pub mod programmable_nft;
+pub mod prune_archives;
+pub mod queries;
+pub mod raffle;
+pub mod reconcile;
+pub mod rental;
+pub mod retract_bid;
+pub mod roles;
+pub mod seller_pause;
+pub mod seller_profile;
+pub mod settlement_hook;
+pub mod settlement_steps;
+pub mod signed_bid;
+pub mod sweep_expired;
+pub mod token_vault;
+pub mod update_listing;
+pub mod update_minimum_bid;
+pub mod validate_bid;
+pub mod vesting;
+pub mod watchlist;
+pub mod winner_default;
+pub mod withdraw;
+
+pub use accept_highest_bid::*;
+pub use admin::*;
+pub use auction_registry::*;
+pub use auction_template::*;
+pub use audit_log::*;
+pub use alien_attestation::*;
+pub use bid_bond::*;
+pub use bidder_profile::*;
+pub use blacklist::*;
+pub use bridge_bid::*;
+pub use bundle::*;
+pub use buy_now::*;
+pub use candle_auction::*;
+pub use cancel_auction::*;
+pub use claim::*;
+pub use clone_listing::*;
+pub use co_seller::*;
+pub use collection_fees::*;
+pub use collection_registry::*;
+pub use collection_stats::*;
+pub use commit_randomness::*;
+pub use compressed_bid::*;
+pub use compressed_nft::*;
+pub use crowd_auction::*;
+pub use dispute::*;
+pub use edition_auction::*;
+pub use core_asset::*;
+pub use delegated_bid::*;
+pub use end_auction::*;
+pub use extend_auction::*;
+pub use extend_if_no_bids::*;
+pub use external_listing::*;
+pub use feature_flags::*;
+pub use fee_treasury::*;
+pub use fixed_price::*;
+pub use governance::*;
+pub use initialize::*;
+pub use initialize_auction::*;
+pub use installments::*;
+pub use legacy::*;
+pub use loyalty::*;
+pub use membership::*;
+pub use migrate::*;
+pub use nft_escrow::*;
+pub use off_chain_settlement::*;
+pub use offers::*;
+pub use open_bid_page::*;
+pub use participation_receipt::*;
+pub use place_bid::*;
+pub use place_bid_with_swap::*;
+pub use programmable_nft::*;
+pub use prune_archives::*;
+pub use queries::*;
+pub use raffle::*;
+pub use reconcile::*;
+pub use rental::*;
+pub use retract_bid::*;
+pub use roles::*;
+pub use seller_pause::*;
+pub use seller_profile::*;
+pub use settlement_hook::*;
+pub use settlement_steps::*;
+pub use signed_bid::*;
+pub use sweep_expired::*;
+pub use token_vault::*;
+pub use update_listing::*;
+pub use update_minimum_bid::*;
+pub use validate_bid::*;
+pub use vesting::*;
+pub use watchlist::*;
+pub use winner_default::*;
+pub use withdraw::*;
@@ -0,0 +1,295 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::{ self, CloseAccount, Token, TokenAccount, Transfer };
+use crate::cnft;
+use crate::errors::ErrorCode;
+use crate::instructions::bundle::release_bundle;
+use crate::settlement::{ self, Custody };
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, NFT_ESCROW_SEED };
+
+// Accounts for handing a listing's escrowed NFT back to its seller, whichever
+// way it's held. Legacy NFTs need the escrow, the seller's token account and the
+// token program; programmable NFTs those plus the Token Metadata accounts its
+// transfer takes; Core assets the asset, its collection if any and the Core
+// program; cNFTs the tree accounts, with the proof nodes as remaining
+// accounts. Listings with nothing in escrow need none of them.
+#[derive(Accounts)]
+pub struct SellerNftReturn<'info> {
+    #[account(mut, seeds = [NFT_ESCROW_SEED, nft_escrow.mint.as_ref()], bump)]
+    pub nft_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub owner_nft_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+    /// CHECK: must be the asset recorded at listing
+    #[account(mut)]
+    pub core_asset: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by the Core program during the transfer
+    #[account(mut)]
+    pub core_collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: the Core program
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: must be the escrowed mint
+    pub nft_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: derived and checked by the Token Metadata program
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: derived and checked by the Token Metadata program
+    pub master_edition: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by the Token Metadata program
+    #[account(mut)]
+    pub escrow_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: created or checked by the Token Metadata program
+    #[account(mut)]
+    pub owner_token_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by the Token Metadata program against the mint's rule set
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by the Token Metadata program
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    /// CHECK: the instructions sysvar, which Token Metadata's transfer reads
+    #[account(address = sysvar_instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+    /// CHECK: must be the tree recorded at listing
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the tree's config address
+    pub tree_config: Option<UncheckedAccount<'info>>,
+    /// CHECK: address checked
+    #[account(address = cnft::NOOP_PROGRAM_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+    /// CHECK: address checked
+    #[account(address = cnft::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: address checked
+    #[account(address = cnft::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> SellerNftReturn<'info> {
+    // Give whatever `auction` holds in escrow back to `seller`, through the
+    // adapter for its asset standard. `payer` covers any account the Core
+    // program has to resize on the way. A bundle's further NFTs come back
+    // through the (escrow, seller token account) pairs in `remaining`, a
+    // cNFT's proof nodes likewise.
+    pub fn release_to_seller(
+        &self,
+        auction_state: &Account<'info, NftComAuction>,
+        auction: &mut AuctionDetails,
+        seller: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        remaining: &'info [AccountInfo<'info>]
+    ) -> Result<()> {
+        if auction.nft_mint.is_none() {
+            return Ok(());
+        }
+        require_keys_eq!(seller.key(), auction.owner, ErrorCode::Unauthorized);
+
+        let owner_nft_account = self.owner_nft_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let asset = self.core_asset.as_ref().map(|account| account.to_account_info());
+        let collection = self.core_collection.as_ref().map(|account| account.to_account_info());
+        let mpl_core_program = self.mpl_core_program
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let info = |account: &Option<UncheckedAccount<'info>>| {
+            account.as_ref().map(|account| account.to_account_info())
+        };
+        let mint = info(&self.nft_mint);
+        let metadata = info(&self.metadata);
+        let edition = info(&self.master_edition);
+        let escrow_token_record = info(&self.escrow_token_record);
+        let owner_token_record = info(&self.owner_token_record);
+        let authorization_rules = info(&self.authorization_rules);
+        let authorization_rules_program = info(&self.authorization_rules_program);
+        let sysvar_instructions = info(&self.sysvar_instructions);
+        let merkle_tree = info(&self.merkle_tree);
+        let tree_config = info(&self.tree_config);
+        let log_wrapper = info(&self.log_wrapper);
+        let compression_program = info(&self.compression_program);
+        let bubblegum_program = info(&self.bubblegum_program);
+        let token_metadata_program = self.token_metadata_program
+            .as_ref()
+            .map(|program| program.to_account_info());
+        let associated_token_program = self.associated_token_program
+            .as_ref()
+            .map(|program| program.to_account_info());
+        let custody = Custody {
+            nft_escrow: self.nft_escrow.as_ref(),
+            token_account: owner_nft_account.as_ref(),
+            token_program: self.token_program.as_ref(),
+            remaining,
+            metadata: metadata.as_ref(),
+            mint: mint.as_ref(),
+            edition: edition.as_ref(),
+            escrow_token_record: escrow_token_record.as_ref(),
+            token_record: owner_token_record.as_ref(),
+            authorization_rules: authorization_rules.as_ref(),
+            authorization_rules_program: authorization_rules_program.as_ref(),
+            token_metadata_program: token_metadata_program.as_ref(),
+            associated_token_program: associated_token_program.as_ref(),
+            sysvar_instructions: sysvar_instructions.as_ref(),
+            asset: asset.as_ref(),
+            collection: collection.as_ref(),
+            mpl_core_program: mpl_core_program.as_ref(),
+            recipient: Some(seller),
+            merkle_tree: merkle_tree.as_ref(),
+            tree_config: tree_config.as_ref(),
+            log_wrapper: log_wrapper.as_ref(),
+            compression_program: compression_program.as_ref(),
+            bubblegum_program: bubblegum_program.as_ref(),
+            ..Custody::new(auction_state, payer, system_program)
+        };
+        let adapter = settlement::adapter(auction.asset_standard);
+        adapter.release(&custody, auction, seller.key(), seller)?;
+        auction.nft_mint = None;
+        auction.compressed_leaf = None;
+        auction.bundle_mints.clear();
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ReturnListedNft<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub nft: SellerNftReturn<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Give the escrowed NFT of an unsold listing back to its seller, whatever its
+// asset standard; `return_nft` and `return_core_asset` cover the two the
+// program started with
+pub fn return_listed_nft<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReturnListedNft<'info>>,
+    _listing_id: [u8; 32]
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_unsold(), ErrorCode::ListingNotRelistable);
+    require!(auction.nft_mint.is_some(), ErrorCode::NftEscrowMismatch);
+
+    let owner = ctx.accounts.owner.to_account_info();
+    ctx.accounts.nft.release_to_seller(
+        &ctx.accounts.auction_state,
+        auction,
+        &owner,
+        &owner,
+        &ctx.accounts.system_program.to_account_info(),
+        ctx.remaining_accounts
+    )
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ReturnNft<'info> {
+    // Authority of every NFT escrow account
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [NFT_ESCROW_SEED, nft_escrow.mint.as_ref()],
+        bump
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = nft_escrow.mint,
+        token::authority = owner
+    )]
+    pub owner_nft_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+// Give the escrowed NFT of an unsold listing back to its seller, along with a
+// bundle's further NFTs passed as (escrow, seller token account) pairs in
+// remaining accounts
+pub fn return_nft<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReturnNft<'info>>,
+    _listing_id: [u8; 32]
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.is_unsold(), ErrorCode::ListingNotRelistable);
+    require!(
+        auction.nft_mint == Some(ctx.accounts.nft_escrow.mint),
+        ErrorCode::NftEscrowMismatch
+    );
+    auction.nft_mint = None;
+    let bundle_mints = std::mem::take(&mut auction.bundle_mints);
+
+    let owner = ctx.accounts.owner.to_account_info();
+    release_escrowed_nft(
+        &ctx.accounts.auction_state,
+        &ctx.accounts.token_program,
+        &ctx.accounts.nft_escrow,
+        &ctx.accounts.owner_nft_account.to_account_info(),
+        &owner
+    )?;
+    release_bundle(
+        &ctx.accounts.auction_state,
+        &ctx.accounts.token_program,
+        &bundle_mints,
+        ctx.remaining_accounts,
+        owner.key(),
+        &owner
+    )
+}
+
+// Move the escrowed NFT to `to` and close the emptied escrow account, handing
+// its rent to `rent_to`
+pub(crate) fn release_escrowed_nft<'info>(
+    auction_state: &Account<'info, NftComAuction>,
+    token_program: &Program<'info, Token>,
+    nft_escrow: &Account<'info, TokenAccount>,
+    to: &AccountInfo<'info>,
+    rent_to: &AccountInfo<'info>
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[AUCTION_STATE_SEED, &[auction_state.bump]]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: nft_escrow.to_account_info(),
+                to: to.clone(),
+                authority: auction_state.to_account_info(),
+            },
+            signer_seeds
+        ),
+        nft_escrow.amount
+    )?;
+    token::close_account(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: nft_escrow.to_account_info(),
+                destination: rent_to.clone(),
+                authority: auction_state.to_account_info(),
+            },
+            signer_seeds
+        )
+    )
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use crate::errors::ErrorCode;
+use crate::events::AuctionEnded;
+use crate::invariants::assert_funds_conserved;
+use crate::math::CheckedMath;
+use crate::payment_processor::load_attestation;
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, FEE_TREASURY_SEED, VAULT_SEED };
+
+#[derive(Accounts)]
+pub struct ManagePaymentProcessors<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SetPaymentProcessor<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SettleOffChain<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: owner and layout are verified by `load_attestation`
+    pub attestation: UncheckedAccount<'info>,
+    // Protocol fees accumulate here until `withdraw_fees`
+    #[account(
+        mut,
+        seeds = [FEE_TREASURY_SEED, native_mint::ID.as_ref()],
+        bump = fee_treasury.bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_payment_processor(ctx: Context<ManagePaymentProcessors>, program: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    if !auction_state.payment_processors.contains(&program) {
+        require!(
+            auction_state.payment_processors.len() < NftComAuction::MAX_PAYMENT_PROCESSORS,
+            ErrorCode::PaymentProcessorWhitelistFull
+        );
+        auction_state.payment_processors.push(program);
+    }
+    Ok(())
+}
+
+pub fn remove_payment_processor(
+    ctx: Context<ManagePaymentProcessors>,
+    program: Pubkey
+) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.payment_processors.retain(|key| *key != program);
+    Ok(())
+}
+
+pub fn set_payment_processor(
+    ctx: Context<SetPaymentProcessor>,
+    _listing_id: [u8; 32],
+    processor: Option<Pubkey>
+) -> Result<()> {
+    if let Some(processor) = processor {
+        require!(
+            ctx.accounts.auction_state.payment_processors.contains(&processor),
+            ErrorCode::PaymentProcessorNotAllowed
+        );
+    }
+
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    auction.payment_processor = processor;
+    Ok(())
+}
+
+// The attested buyer wins outright. On-chain bidders are all outbid and can
+// withdraw in full; the buyer fees already escrowed go to the fee treasury.
+// The NFT is delivered through the usual post-settlement paths.
+pub fn settle_off_chain(ctx: Context<SettleOffChain>, listing_id: [u8; 32]) -> Result<()> {
+    let whitelist = &ctx.accounts.auction_state.payment_processors;
+    let auction = &mut ctx.accounts.auction;
+
+    let processor = auction.payment_processor.ok_or(ErrorCode::PaymentProcessorNotAllowed)?;
+    // The escrowed buyer fees are paid over in lamports
+    require!(auction.payment_mint.is_none(), ErrorCode::WrongPaymentMint);
+    require!(whitelist.contains(&processor), ErrorCode::PaymentProcessorNotAllowed);
+
+    let attestation = load_attestation(&ctx.accounts.attestation, &processor)?;
+    require!(attestation.listing_id == listing_id, ErrorCode::InvalidAttestation);
+    require!(attestation.buyer != auction.owner, ErrorCode::BidderIsOwner);
+
+    auction.transition_to(AuctionStatus::Ended)?;
+    auction.highest_bidder = attestation.buyer;
+    auction.highest_bid = 0;
+    auction.off_chain_reference = Some(attestation.reference);
+    let fees = std::mem::take(&mut auction.fees);
+    auction.transition_to(AuctionStatus::Settled)?;
+
+    vault::pay_out(
+        &ctx.accounts.vault.to_account_info(),
+        &listing_id,
+        auction.vault_bump,
+        &ctx.accounts.fee_treasury.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        fees
+    )?;
+    ctx.accounts.fee_treasury.collected = ctx.accounts.fee_treasury.collected.try_add(fees)?;
+    assert_funds_conserved(&ctx.accounts.vault.to_account_info(), auction)?;
+
+    emit!(AuctionEnded {
+        listing_id,
+        winner: attestation.buyer,
+        amount: attestation.amount,
+        buy_now: false,
+    });
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::{ self, Mint, Token, TokenAccount, Transfer };
+use crate::cpi_guard::assert_cpi_caller_allowed;
+use crate::errors::ErrorCode;
+use crate::events::{ OfferAccepted, OfferCancelled, OfferMade };
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::math::{ self, CheckedMath };
+use crate::royalties;
+use crate::state::*;
+use crate::{
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    FEATURE_FLAGS_SEED,
+    FEE_TREASURY_SEED,
+    OFFER_SEED,
+};
+
+// Standing offers on NFTs that aren't listed. The offered lamports sit on the
+// offer PDA itself until the holder accepts or the offer is cancelled.
+
+#[derive(Accounts)]
+pub struct MakeOffer<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = buyer,
+        space = Offer::SPACE,
+        seeds = [OFFER_SEED, mint.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: the buyer's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, buyer.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    #[account(constraint = mint.decimals == 0 && mint.supply == 1 @ ErrorCode::InvalidNftMint)]
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+    pub feature_flags: Account<'info, FeatureFlags>,
+    /// CHECK: the instructions sysvar, used to identify CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Each creator named by the NFT's royalty data follows as a remaining account
+#[derive(Accounts)]
+pub struct AcceptOffer<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [OFFER_SEED, mint.key().as_ref(), buyer.key().as_ref()],
+        bump = offer.bump,
+        has_one = mint,
+        has_one = buyer,
+        close = buyer
+    )]
+    pub offer: Account<'info, Offer>,
+    /// CHECK: owns the NFT's new token account and gets the offer's rent back; must be
+    /// the offer's buyer
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+    // The NFT's holder, who is paid for it
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, seller.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = seller,
+        constraint = seller_nft_account.amount == 1 @ ErrorCode::NftNotHeld
+    )]
+    pub seller_nft_account: Account<'info, TokenAccount>,
+    // The seller covers the rent if the buyer has no account for the NFT yet
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = mint,
+        associated_token::authority = buyer
+    )]
+    pub buyer_nft_account: Account<'info, TokenAccount>,
+    // Protocol fees accumulate here until `withdraw_fees`
+    #[account(
+        mut,
+        seeds = [FEE_TREASURY_SEED, native_mint::ID.as_ref()],
+        bump = fee_treasury.bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    // Token Metadata of the NFT, read for creator royalties
+    /// CHECK: checked against the NFT's mint by `royalties`
+    pub metadata: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOffer<'info> {
+    #[account(
+        mut,
+        seeds = [OFFER_SEED, offer.mint.as_ref(), buyer.key().as_ref()],
+        bump = offer.bump,
+        has_one = buyer,
+        close = buyer
+    )]
+    pub offer: Account<'info, Offer>,
+    /// CHECK: gets the escrowed amount and the rent back; must be the offer's buyer
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+    // The buyer at any time, or anyone once the offer has expired
+    pub signer: Signer<'info>,
+}
+
+// Escrow `amount` as an offer on `mint`, good until `expires_at`. A buyer has at
+// most one offer per NFT; cancel it to change the amount.
+pub fn make_offer(ctx: Context<MakeOffer>, amount: u64, expires_at: i64) -> Result<()> {
+    ctx.accounts.feature_flags.ensure_enabled(FeatureFlags::OFFERS)?;
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    assert_cpi_caller_allowed(
+        &ctx.accounts.instructions,
+        &ctx.accounts.auction_state.aggregator_programs
+    )?;
+    require!(amount > 0, ErrorCode::MinimumBidError);
+    require!(expires_at > Clock::get()?.unix_timestamp, ErrorCode::InvalidOfferExpiry);
+
+    system_program::transfer(
+        CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.buyer.to_account_info(),
+            to: ctx.accounts.offer.to_account_info(),
+        }),
+        amount
+    )?;
+
+    let offer = &mut ctx.accounts.offer;
+    offer.version = Offer::VERSION;
+    offer.buyer = ctx.accounts.buyer.key();
+    offer.mint = ctx.accounts.mint.key();
+    offer.amount = amount;
+    offer.expires_at = expires_at;
+    offer.bump = ctx.bumps.offer;
+
+    emit!(OfferMade { mint: offer.mint, buyer: offer.buyer, amount, expires_at });
+    Ok(())
+}
+
+// The NFT's holder sells it at the offer. The buyer fee comes out of the offered
+// amount, then the seller fee and creator royalties out of what's left, same as
+// at an auction's settlement.
+pub fn accept_offer<'info>(ctx: Context<'_, '_, '_, 'info, AcceptOffer<'info>>) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let auction_state = &ctx.accounts.auction_state;
+    let offer = &ctx.accounts.offer;
+    require!(Clock::get()?.unix_timestamp < offer.expires_at, ErrorCode::OfferExpired);
+    require_keys_neq!(ctx.accounts.seller.key(), offer.buyer, ErrorCode::BidderIsOwner);
+
+    let buyer_fee_bps = auction_state.buyer_fee_bps_at(offer.amount);
+    let buyer_fee = math::compute_fee(offer.amount, buyer_fee_bps)?;
+    let price = offer.amount.try_sub(buyer_fee)?;
+    let seller_fee = math::compute_fee(price, auction_state.seller_fee_bps)?;
+    let creator_shares = match ctx.accounts.metadata.as_ref() {
+        Some(metadata) => royalties::creator_shares(metadata, &offer.mint, price)?,
+        None if auction_state.enforces_royalties() => {
+            return Err(ErrorCode::MissingRoyaltyAccounts.into());
+        }
+        None => vec![],
+    };
+    let royalty_total = creator_shares
+        .iter()
+        .try_fold(0u64, |total, (_, amount)| total.try_add(*amount))?;
+    let seller_earnings = price
+        .try_sub(seller_fee)?
+        .checked_sub(royalty_total)
+        .ok_or(ErrorCode::RoyaltiesExceedProceeds)?;
+    require!(
+        ctx.remaining_accounts.len() >= creator_shares.len(),
+        ErrorCode::MissingRoyaltyAccounts
+    );
+
+    token::transfer(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.seller_nft_account.to_account_info(),
+            to: ctx.accounts.buyer_nft_account.to_account_info(),
+            authority: ctx.accounts.seller.to_account_info(),
+        }),
+        1
+    )?;
+
+    // The offer PDA is the program's own account, so its lamports move directly
+    let offer_info = ctx.accounts.offer.to_account_info();
+    let fee = buyer_fee.try_add(seller_fee)?;
+    offer_info.sub_lamports(ctx.accounts.offer.amount)?;
+    ctx.accounts.seller.to_account_info().add_lamports(seller_earnings)?;
+    ctx.accounts.fee_treasury.to_account_info().add_lamports(fee)?;
+    ctx.accounts.fee_treasury.collected = ctx.accounts.fee_treasury.collected.try_add(fee)?;
+    let creator_accounts = ctx.remaining_accounts;
+    for ((creator, amount), creator_info) in creator_shares.into_iter().zip(creator_accounts) {
+        require_keys_eq!(creator_info.key(), creator, ErrorCode::MissingRoyaltyAccounts);
+        creator_info.add_lamports(amount)?;
+    }
+
+    emit!(OfferAccepted {
+        mint: ctx.accounts.offer.mint,
+        buyer: ctx.accounts.offer.buyer,
+        seller: ctx.accounts.seller.key(),
+        price,
+        royalties: royalty_total,
+    });
+    Ok(())
+}
+
+// Closing the offer returns the escrowed amount and the rent to the buyer
+pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+    let offer = &ctx.accounts.offer;
+    require!(
+        ctx.accounts.signer.key() == offer.buyer ||
+            Clock::get()?.unix_timestamp >= offer.expires_at,
+        ErrorCode::OfferNotExpired
+    );
+
+    emit!(OfferCancelled { mint: offer.mint, buyer: offer.buyer });
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::{ AUCTION_SEED, BID_PAGE_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], index: u32)]
+pub struct OpenBidPage<'info> {
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(
+        init,
+        payer = payer,
+        space = BidPage::SPACE,
+        seeds = [BID_PAGE_SEED, listing_id.as_ref(), &index.to_le_bytes()],
+        bump
+    )]
+    pub bid_page: AccountLoader<'info, BidPage>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<OpenBidPage>, _listing_id: [u8; 32], index: u32) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+
+    // Pages are opened strictly in order and only after the inline list fills up
+    require!(index == auction.bid_pages, ErrorCode::BidPageNotNeeded);
+    require!(
+        auction.bids.len() >= AuctionDetails::MAX_INLINE_BIDDERS,
+        ErrorCode::BidPageNotNeeded
+    );
+
+    let mut bid_page = ctx.accounts.bid_page.load_init()?;
+    bid_page.auction = auction.key();
+    bid_page.index = index;
+    bid_page.bump = ctx.bumps.bid_page;
+    bid_page.version = BidPage::VERSION;
+
+    auction.bid_pages = auction.bid_pages.try_add(1)?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use crate::cnft::{ self, MintAccounts };
+use crate::errors::ErrorCode;
+use crate::events::ParticipationReceiptMinted;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, BID_RECEIPT_SEED };
+
+// Participation receipts. Once an auction settles, anyone may mint each of its
+// bidders a compressed NFT recording that they took part, into the tree the
+// marketplace set with `set_receipt_tree`. The receipt carries the listing's
+// name and metadata URI; the bidder's best bid is recorded in the event, and
+// the bid receipt remembers that it was minted, so each bidder gets one.
+//
+// Receipts go to bidders with a bid receipt, i.e. those who bid through
+// `place_bid` or a signed order.
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], bidder: Pubkey)]
+pub struct MintParticipationReceipt<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        constraint = auction.status == AuctionStatus::Settled @ ErrorCode::AuctionNotEnded
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(
+        mut,
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), bidder.as_ref()],
+        bump = bid_receipt.bump
+    )]
+    pub bid_receipt: Account<'info, BidReceipt>,
+    /// CHECK: the bidder, owner of the receipt cNFT; matched by the bid receipt seeds
+    #[account(address = bidder)]
+    pub leaf_owner: UncheckedAccount<'info>,
+    /// CHECK: Bubblegum's config of the receipt tree, checked by address in the mint
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+    /// CHECK: the marketplace's receipt tree
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: the SPL noop program, checked by address in the mint
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: the account compression program, checked by address in the mint
+    pub compression_program: UncheckedAccount<'info>,
+    /// CHECK: the Bubblegum program, checked by address in the mint
+    pub bubblegum_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn mint_participation_receipt(
+    ctx: Context<MintParticipationReceipt>,
+    listing_id: [u8; 32],
+    bidder: Pubkey
+) -> Result<()> {
+    let receipt_tree = match ctx.accounts.auction_state.receipt_tree {
+        Some(tree) => tree,
+        None => {
+            return err!(ErrorCode::ReceiptTreeNotSet);
+        }
+    };
+    require_keys_eq!(ctx.accounts.merkle_tree.key(), receipt_tree, ErrorCode::InvalidMerkleTree);
+    let best_bid = ctx.accounts.bid_receipt.best_bid;
+    require!(best_bid > 0, ErrorCode::NoBidToReceipt);
+    require!(!ctx.accounts.bid_receipt.participation_minted, ErrorCode::ReceiptAlreadyMinted);
+
+    let auction = &ctx.accounts.auction;
+    let state_seeds: &[&[&[u8]]] = &[&[AUCTION_STATE_SEED, &[ctx.accounts.auction_state.bump]]];
+    let asset_id = cnft::mint_v1(
+        MintAccounts {
+            tree_config: &ctx.accounts.tree_config,
+            leaf_owner: &ctx.accounts.leaf_owner,
+            merkle_tree: &ctx.accounts.merkle_tree,
+            payer: &ctx.accounts.payer,
+            tree_delegate: &ctx.accounts.auction_state.to_account_info(),
+            log_wrapper: &ctx.accounts.log_wrapper,
+            compression_program: &ctx.accounts.compression_program,
+            bubblegum_program: &ctx.accounts.bubblegum_program,
+            system_program: &ctx.accounts.system_program,
+        },
+        auction.name.clone(),
+        "BID".to_string(),
+        auction.metadata_uri.clone(),
+        state_seeds
+    )?;
+    ctx.accounts.bid_receipt.participation_minted = true;
+
+    emit!(ParticipationReceiptMinted { listing_id, bidder, best_bid, asset_id });
+    Ok(())
+}
@@ -0,0 +1,574 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::token::TokenAccount;
+use anchor_spl::token_interface;
+use crate::allowlist;
+use crate::cpi_guard::assert_cpi_caller_allowed;
+use crate::emitter::{ self, event_authority, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::*;
+use crate::instructions::bid_bond;
+use crate::instructions::blacklist::ensure_address_not_blacklisted;
+use crate::instructions::loyalty::{ self, LoyaltyActivity };
+use crate::instructions::membership;
+use crate::invariants::assert_funds_conserved;
+use crate::math::{ self, CheckedMath };
+use crate::oracle::{ self, OracleLimits };
+use crate::state::*;
+use crate::token_gate;
+use crate::token_payments;
+use crate::vault::{ self, Escrow };
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BID_RECEIPT_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_FEE_SEED,
+    FEATURE_FLAGS_SEED,
+    LOYALTY_SEED,
+    MEMBERSHIP_DISCOUNT_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], bidder: Pubkey)]
+pub struct PlaceBid<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Grows by a step of bid history when a growable history is full; the payer covers the rent
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        realloc = AuctionDetails::space(auction.bid_history.capacity_for_next_bid()),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    // Holds the bid in escrow
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    // Funds the bid on behalf of `bidder`; may be a program PDA signing via CPI
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: the bidder's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, bidder.as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Only needed once the auction's inline bidder list is full
+    #[account(mut, has_one = auction)]
+    pub bid_page: Option<AccountLoader<'info, BidPage>>,
+    /// CHECK: only receives lamports; the refund is skipped unless this is the
+    /// outbid highest bidder's system-owned wallet
+    #[account(mut)]
+    pub previous_bidder: Option<UncheckedAccount<'info>>,
+    /// CHECK: the outbid bidder's receipt address, required for the refund; checked in
+    /// the handler and may not exist
+    #[account(mut)]
+    pub previous_bid_receipt: Option<UncheckedAccount<'info>>,
+    // The bidder's receipt for this listing, opened by their first bid
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = BidReceipt::SPACE,
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), bidder.as_ref()],
+        bump
+    )]
+    pub bid_receipt: Account<'info, BidReceipt>,
+    // The bidder's loyalty account, if they have one, to earn points for the bid
+    #[account(mut, seeds = [LOYALTY_SEED, bidder.as_ref()], bump = loyalty_account.bump)]
+    pub loyalty_account: Option<Account<'info, LoyaltyAccount>>,
+    // The bidder's holding of the gate mint; only needed on token-gated listings
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    // The listing's collection fee config, if it has one
+    #[account(
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Option<Account<'info, CollectionFeeConfig>>,
+    // A membership discount and the bidder's membership NFT qualifying for it
+    #[account(
+        seeds = [MEMBERSHIP_DISCOUNT_SEED, membership_discount.collection.as_ref()],
+        bump = membership_discount.bump
+    )]
+    pub membership_discount: Option<Account<'info, MembershipDiscount>>,
+    pub membership_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: the membership NFT's Token Metadata account, checked against its mint when read
+    pub membership_metadata: Option<UncheckedAccount<'info>>,
+    // USD-priced listings only: the oracle account of the listing's SOL/USD feed
+    /// CHECK: checked against the listing's feed by `oracle::load_price`
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    // Listings paid in a token only: the listing's token vault, and the payer's
+    // token account the bid is paid from, which a wrapped SOL bid can leave out
+    // to pay in plain SOL
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(mut)]
+    pub payer_token_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_program: Option<Interface<'info, token_interface::TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, token_interface::Mint>>,
+    // Listings paid in a token only, which bid once token bids are switched on
+    #[account(seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+    pub feature_flags: Option<Account<'info, FeatureFlags>>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the instructions sysvar, used to identify CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+// Each bid's auction PDA, vault and the payer's bid receipt for that auction
+// follow as remaining accounts, in threes and in the order of the bids
+#[derive(Accounts)]
+pub struct PlaceBids<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Bids on its own behalf and funds every bid
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: the payer's blacklist flag, the one bidder of every bid in the batch; must not exist
+    #[account(seeds = [BLACKLIST_SEED, payer.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the instructions sysvar, used to identify CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BidRequest {
+    pub listing_id: [u8; 32],
+    pub amount: u64,
+    pub proof: Vec<[u8; 32]>, // Allowlist proof, for private listings
+}
+
+// Upper bound on bids per batch, keeps a batch within one transaction's compute
+pub const MAX_BATCH_BIDS: usize = 10;
+
+// `proof` places the bidder on a private listing's allowlist; public listings
+// ignore it. `referrer` earns a share of the buyer fees if this bidder wins.
+pub fn handler(
+    ctx: Context<PlaceBid>,
+    listing_id: [u8; 32],
+    bidder: Pubkey,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+    referrer: Option<Pubkey>
+) -> Result<()> {
+    place(ctx, &listing_id, bidder, amount, false, &proof, referrer)
+}
+
+// Deposit `amount` toward a proxy bid: the bidder's whole running total becomes
+// the most they'll pay, and the program bids on their behalf up to it
+pub fn place_proxy_bid(
+    ctx: Context<PlaceBid>,
+    listing_id: [u8; 32],
+    bidder: Pubkey,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+    referrer: Option<Pubkey>
+) -> Result<()> {
+    place(ctx, &listing_id, bidder, amount, true, &proof, referrer)
+}
+
+// Bid on several listings at once; fails as a whole if any one bid is invalid.
+// Each bid opens or adds to the payer's receipt for its listing, as place_bid
+// would. Only inline positions are supported, so a listing whose inline bidder
+// list is full (or that is token-gated) needs a separate place_bid.
+pub fn place_bids<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PlaceBids<'info>>,
+    bids: Vec<BidRequest>
+) -> Result<()> {
+    require!(!bids.is_empty() && bids.len() <= MAX_BATCH_BIDS, ErrorCode::InvalidBatchSize);
+    require!(ctx.remaining_accounts.len() == 3 * bids.len(), ErrorCode::InvalidBatchSize);
+    assert_cpi_caller_allowed(
+        &ctx.accounts.instructions,
+        &ctx.accounts.auction_state.aggregator_programs
+    )?;
+
+    let bidder = ctx.accounts.payer.key();
+    for (request, accounts) in bids.into_iter().zip(ctx.remaining_accounts.chunks(3)) {
+        require!(request.amount > 0, ErrorCode::MinimumBidError);
+        let (account, escrow, receipt) = (&accounts[0], &accounts[1], &accounts[2]);
+        let mut auction = Account::<AuctionDetails>::try_from(account)?;
+        let address = Pubkey::create_program_address(
+            &[AUCTION_SEED, request.listing_id.as_ref(), &[auction.bump]],
+            &crate::ID
+        ).map_err(|_| error!(ErrorCode::InvalidListingId))?;
+        require_keys_eq!(account.key(), address, ErrorCode::InvalidListingId);
+        require!(auction.bid_bond == 0, ErrorCode::BidBondRequired);
+        require_keys_eq!(
+            escrow.key(),
+            vault::address(&request.listing_id, auction.vault_bump)?,
+            ErrorCode::InvalidListingId
+        );
+        let mut receipt = open_bid_receipt(
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            receipt,
+            account.key(),
+            bidder
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        receipt.check_bid_limits(&auction, now)?;
+
+        let balance_before = escrow.lamports();
+        system_program::transfer(
+            CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: escrow.clone(),
+            }),
+            request.amount
+        )?;
+        let deposited = escrow.lamports().try_sub(balance_before)?;
+
+        let credited = apply_bid(
+            &ctx.accounts.auction_state,
+            &mut auction,
+            escrow,
+            bidder,
+            bidder,
+            &ctx.accounts.blacklist_entry,
+            deposited,
+            PositionStore::Accounts(None),
+            false,
+            &request.proof,
+            None,
+            None,
+            0,
+            None,
+            &None
+        )?;
+        receipt.record_bid(credited, now)?;
+        receipt.fee_discount_bps = 0;
+        receipt.exit(&crate::ID)?;
+        auction.exit(&crate::ID)?;
+    }
+    Ok(())
+}
+
+// What `init_if_needed` does for place_bid's receipt, for each listing in a batch
+fn open_bid_receipt<'info>(
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    account: &'info AccountInfo<'info>,
+    auction: Pubkey,
+    bidder: Pubkey
+) -> Result<Account<'info, BidReceipt>> {
+    let (address, bump) = Pubkey::find_program_address(
+        &[BID_RECEIPT_SEED, auction.as_ref(), bidder.as_ref()],
+        &crate::ID
+    );
+    require_keys_eq!(account.key(), address, ErrorCode::BidReceiptMismatch);
+
+    if account.data_is_empty() {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+                &[&[BID_RECEIPT_SEED, auction.as_ref(), bidder.as_ref(), &[bump]]]
+            ),
+            Rent::get()?.minimum_balance(BidReceipt::SPACE),
+            BidReceipt::SPACE as u64,
+            &crate::ID
+        )?;
+        let receipt = BidReceipt {
+            version: BidReceipt::VERSION,
+            auction,
+            bidder,
+            escrowed: 0,
+            last_bid_time: 0,
+            refunded: false,
+            bump,
+            bond: 0,
+            best_bid: 0,
+            participation_minted: false,
+            bid_count: 0,
+            fee_discount_bps: 0,
+        };
+        let mut data = account.try_borrow_mut_data()?;
+        receipt.try_serialize(&mut &mut data[..])?;
+    }
+    Account::try_from(account)
+}
+
+fn place(
+    ctx: Context<PlaceBid>,
+    listing_id: &[u8; 32],
+    bidder: Pubkey,
+    amount: u64,
+    proxy: bool,
+    proof: &[[u8; 32]],
+    referrer: Option<Pubkey>
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::MinimumBidError);
+    let events: EventAuthority = event_authority!(ctx);
+    assert_cpi_caller_allowed(
+        &ctx.accounts.instructions,
+        &ctx.accounts.auction_state.aggregator_programs
+    )?;
+
+    if ctx.accounts.auction.payment_mint.is_some() {
+        let flags = ctx.accounts.feature_flags.as_ref().ok_or(ErrorCode::FeatureDisabled)?;
+        flags.ensure_enabled(FeatureFlags::TOKEN_BIDS)?;
+    }
+
+    // A USD-priced listing is held to its minimum and reserve at the current price
+    if let Some(pricing) = ctx.accounts.auction.usd_pricing {
+        let feed = ctx.accounts.price_feed.as_ref().ok_or(ErrorCode::OracleFeedMismatch)?;
+        let clock = Clock::get()?;
+        let price = oracle::load_price(feed, &pricing.feed, OracleLimits::default(), &clock)?;
+        ctx.accounts.auction.reprice(&price, clock.unix_timestamp)?;
+    }
+
+    let fee_discount_bps = membership::discount_bps(
+        ctx.accounts.membership_discount.as_deref(),
+        &bidder,
+        ctx.accounts.membership_token_account.as_deref(),
+        ctx.accounts.membership_metadata.as_ref().map(|metadata| metadata.as_ref())
+    )?;
+
+    // The bid is whatever actually lands in escrow, never a caller-supplied number
+    let escrow = Escrow::new(
+        &ctx.accounts.auction,
+        listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?;
+    let payer = ctx.accounts.payer.to_account_info();
+    let payer_token_account = ctx.accounts.payer_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let from = escrow.account_of(&payer, payer_token_account.as_ref())?;
+    let deposited = escrow.deposit(&from, &payer, amount)?;
+    let previous = ctx.accounts.auction.highest_bidder;
+    // The account was already reallocated to fit this
+    ctx.accounts.auction.bid_history.grow_for_next_bid();
+    let mut bid_page = ctx.accounts.bid_page.as_ref().map(|page| page.load_mut()).transpose()?;
+
+    let credited = apply_bid(
+        &ctx.accounts.auction_state,
+        &mut ctx.accounts.auction,
+        escrow.holding(),
+        bidder,
+        ctx.accounts.payer.key(),
+        &ctx.accounts.blacklist_entry,
+        deposited,
+        PositionStore::Accounts(bid_page.as_deref_mut()),
+        proxy,
+        proof,
+        ctx.accounts.gate_token_account.as_deref(),
+        ctx.accounts.collection_fee_config.as_deref(),
+        fee_discount_bps,
+        referrer,
+        &events
+    )?;
+    drop(bid_page);
+
+    let auction_key = ctx.accounts.auction.key();
+    let receipt = &mut ctx.accounts.bid_receipt;
+    if receipt.version == 0 {
+        receipt.version = BidReceipt::VERSION;
+        receipt.auction = auction_key;
+        receipt.bidder = bidder;
+        receipt.bump = ctx.bumps.bid_receipt;
+    }
+    let now = Clock::get()?.unix_timestamp;
+    receipt.check_bid_limits(&ctx.accounts.auction, now)?;
+    receipt.record_bid(credited, now)?;
+    receipt.fee_discount_bps = fee_discount_bps;
+    bid_bond::lock(
+        &mut ctx.accounts.auction,
+        receipt,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.vault.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &events
+    )?;
+    if ctx.accounts.auction.payment_mint.is_none() {
+        loyalty::accrue(
+            &ctx.accounts.auction_state,
+            ctx.accounts.loyalty_account.as_mut(),
+            LoyaltyActivity::Bid,
+            credited
+        )?;
+    }
+
+    if previous != Pubkey::default() && !ctx.accounts.auction.is_winner(&previous) {
+        refund_outbid(ctx, listing_id, previous)?;
+    }
+    Ok(())
+}
+
+// Send the outbid leader's inline position straight back out of escrow. Without
+// their wallet and receipt address (or for a position on an overflow page, or
+// on a listing paid in a token) the funds simply stay put for `withdraw`, so a
+// bid never fails because the refund couldn't be made.
+fn refund_outbid(ctx: Context<PlaceBid>, listing_id: &[u8; 32], previous: Pubkey) -> Result<()> {
+    if ctx.accounts.auction.payment_mint.is_some() {
+        return Ok(());
+    }
+    let to = match ctx.accounts.previous_bidder.as_ref() {
+        Some(to) if to.key() == previous && to.owner == &system_program::ID => to,
+        _ => {
+            return Ok(());
+        }
+    };
+    let (receipt_address, _) = Pubkey::find_program_address(
+        &[BID_RECEIPT_SEED, ctx.accounts.auction.key().as_ref(), previous.as_ref()],
+        &crate::ID
+    );
+    let receipt = match ctx.accounts.previous_bid_receipt.as_ref() {
+        Some(receipt) if receipt.key() == receipt_address => receipt,
+        _ => {
+            return Ok(());
+        }
+    };
+
+    let events: EventAuthority = event_authority!(ctx);
+    let auction = &mut ctx.accounts.auction;
+    let refund_amount = match auction.bid_of_mut(&previous) {
+        Some(bid) => std::mem::take(&mut bid.amount),
+        None => {
+            return Ok(());
+        }
+    };
+    if refund_amount == 0 {
+        return Ok(());
+    }
+
+    vault::pay_out(
+        &ctx.accounts.vault.to_account_info(),
+        listing_id,
+        auction.vault_bump,
+        &to.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        refund_amount
+    )?;
+    assert_funds_conserved(&ctx.accounts.vault.to_account_info(), auction)?;
+    BidReceipt::record_refund(receipt, refund_amount)?;
+
+    emitter::emit(&events, BidRefunded {
+        listing_id: *listing_id,
+        bidder: previous,
+        amount: refund_amount,
+    })
+}
+
+// Record `deposited`, already moved into the auction's escrow, as a bid by
+// `bidder`, returning the amount credited after fees. Shared by every path that
+// can place a bid (direct, proxy, CPI, bridged, swapped, compressed); only
+// direct and proxy bids can pay into a token listing's token vault or convert a
+// USD-priced listing's minimum at the oracle price.
+// Paths without a way to pass an allowlist `proof` or the bidder's gate token
+// account hand in none, so they can't bid on private or token-gated listings;
+// without a collection fee config or membership discount they pay the full global fee.
+// Every path hands in the bidder's blacklist entry, so none can skip the blacklist.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_bid(
+    auction_state: &NftComAuction,
+    auction: &mut Account<AuctionDetails>,
+    vault: &AccountInfo,
+    bidder: Pubkey,
+    payer: Pubkey,
+    blacklist_entry: &AccountInfo,
+    deposited: u64,
+    store: PositionStore,
+    proxy: bool,
+    proof: &[[u8; 32]],
+    gate_holding: Option<&TokenAccount>,
+    collection_fees: Option<&CollectionFeeConfig>,
+    fee_discount_bps: u64,
+    referrer: Option<Pubkey>,
+    events: &EventAuthority
+) -> Result<u64> {
+    ensure_address_not_blacklisted(blacklist_entry, &bidder)?;
+    require!(!auction.is_alien, ErrorCode::ContributionsOnly);
+    require!(auction.ticket_price == 0, ErrorCode::TicketsOnly);
+    require!(!auction.delegated_bidding, ErrorCode::DelegatedBidsOnly);
+    // Paths that only move lamports hand in the SOL vault
+    require!(
+        auction.payment_mint.is_some() == token_payments::is_token_program(vault.owner),
+        ErrorCode::WrongPaymentMint
+    );
+    // Who led at a candle's cutoff is only known for plain bids
+    require!(!proxy || auction.candle_window == 0, ErrorCode::ProxyBidUnavailable);
+    require!(referrer != Some(bidder), ErrorCode::InvalidReferrer);
+    require!(bidder != auction.owner, ErrorCode::BidderIsOwner);
+    require!(payer != auction.owner, ErrorCode::BidderIsOwner);
+    allowlist::check(auction, &bidder, proof)?;
+    token_gate::check(auction, &bidder, gate_holding)?;
+
+    match auction.status {
+        AuctionStatus::Live => {}
+        AuctionStatus::Paused => {
+            return Err(ErrorCode::AuctionPaused.into());
+        }
+        _ => {
+            return Err(ErrorCode::AuctionEnded.into());
+        }
+    }
+
+    // Read the clock once; every syscall counts on the bid hot path
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= auction.end_time, ErrorCode::AuctionEnded);
+    // Other paths would be checked against a conversion that may no longer hold
+    require!(
+        auction.usd_pricing.is_none() || auction.usd_priced_at == now,
+        ErrorCode::OraclePriceStale
+    );
+
+    // Fee math only runs once the cheap rejections have passed
+    let buyer_fee_bps = auction_state.buyer_fee_bps_for(auction, collection_fees, deposited);
+    let fee = math::compute_fee(deposited, buyer_fee_bps)?;
+    let fee = fee.try_sub(math::compute_fee(fee, fee_discount_bps)?)?;
+    let bid_amount = deposited.try_sub(fee)?;
+
+    extend_if_sniped(auction_state, auction, now)?;
+
+    auction.fees = auction.fees.try_add(fee)?;
+    auction.total_amount = auction.total_amount.try_add(bid_amount)?;
+    let lead_before = (auction.highest_bidder, auction.highest_bid);
+    // Compressed bids take the lead once the caller has proven the bidder's total
+    if let Some(total) = auction.record_bid(bidder, bid_amount, now, store, proxy, referrer)? {
+        auction.resolve_bid(bidder, total, proxy)?;
+    }
+    if auction.candle_window > 0 {
+        auction.mark_candle(lead_before, now)?;
+    }
+    auction.bid_history.push(BidRecord { bidder, amount: bid_amount, time: now })?;
+
+    assert_funds_conserved(vault, auction)?;
+
+    emitter::emit(events, BidPlaced {
+        listing_id: auction.listing_id,
+        sender: bidder,
+        value: bid_amount,
+        referrer,
+        relayer: (payer != bidder).then_some(payer),
+    })?;
+    Ok(bid_amount)
+}
+
+// Sniping protection: a bid this close to the end pushes it back, up to the
+// marketplace's cap on extensions
+pub(crate) fn extend_if_sniped(
+    auction_state: &NftComAuction,
+    auction: &mut AuctionDetails,
+    now: i64
+) -> Result<()> {
+    if
+        now >= auction.end_time.saturating_sub(auction.sniping_time_window) &&
+        auction.extensions < auction_state.max_extensions
+    {
+        auction.end_time = auction.end_time.try_add(auction.time_extension)?;
+        auction.extensions = auction.extensions.try_add(1)?;
+    }
+    Ok(())
+}
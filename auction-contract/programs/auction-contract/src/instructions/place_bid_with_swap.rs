@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{ AccountMeta, Instruction };
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::token::{ self, spl_token, CloseAccount, Token, TokenAccount };
+use crate::cpi_guard::assert_cpi_caller_allowed;
+use crate::errors::ErrorCode;
+use crate::instructions::place_bid::apply_bid;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, BLACKLIST_SEED, VAULT_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], bidder: Pubkey)]
+pub struct PlaceBidWithSwap<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: the bidder's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, bidder.as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Receives the swap output; closed back into `payer` afterwards
+    #[account(
+        mut,
+        token::mint = spl_token::native_mint::ID,
+        token::authority = payer
+    )]
+    pub wsol_account: Account<'info, TokenAccount>,
+    /// CHECK: must be on the swap whitelist, checked in the handler
+    #[account(executable)]
+    pub swap_program: UncheckedAccount<'info>,
+    // Only needed once the auction's inline bidder list is full
+    #[account(mut, has_one = auction)]
+    pub bid_page: Option<AccountLoader<'info, BidPage>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the instructions sysvar, used to identify CPI callers
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+// Remaining accounts are passed to the swap program as-is, with `swap_data`
+// as its instruction data; the swap must deliver into `wsol_account`.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, PlaceBidWithSwap<'info>>,
+    _listing_id: [u8; 32],
+    bidder: Pubkey,
+    swap_data: Vec<u8>,
+    min_amount: u64
+) -> Result<()> {
+    require!(min_amount > 0, ErrorCode::MinimumBidError);
+    assert_cpi_caller_allowed(
+        &ctx.accounts.instructions,
+        &ctx.accounts.auction_state.aggregator_programs
+    )?;
+    let swap_program = ctx.accounts.swap_program.key();
+    require!(
+        ctx.accounts.auction_state.swap_programs.contains(&swap_program),
+        ErrorCode::SwapProgramNotAllowed
+    );
+    // Bid bonds are only posted, and bid limits only tracked, through place_bid
+    require!(ctx.accounts.auction.bid_bond == 0, ErrorCode::BidBondRequired);
+    require!(!ctx.accounts.auction.has_bid_limits(), ErrorCode::BidLimitsApply);
+
+    // Swap, then measure what actually arrived
+    let wrapped_before = ctx.accounts.wsol_account.amount;
+    let swap = Instruction {
+        program_id: swap_program,
+        accounts: ctx.remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: swap_data,
+    };
+    let mut account_infos = ctx.remaining_accounts.to_vec();
+    account_infos.push(ctx.accounts.swap_program.to_account_info());
+    invoke(&swap, &account_infos)?;
+
+    ctx.accounts.wsol_account.reload()?;
+    let swapped = ctx.accounts.wsol_account.amount.saturating_sub(wrapped_before);
+    require!(swapped >= min_amount, ErrorCode::SwapOutputTooLow);
+
+    // Unwrap into the payer, then escrow exactly the swapped amount
+    token::close_account(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), CloseAccount {
+            account: ctx.accounts.wsol_account.to_account_info(),
+            destination: ctx.accounts.payer.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        })
+    )?;
+
+    let escrow = ctx.accounts.vault.to_account_info();
+    let balance_before = escrow.lamports();
+    system_program::transfer(
+        CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: escrow.clone(),
+        }),
+        swapped
+    )?;
+    let deposited = escrow.lamports() - balance_before;
+
+    let mut bid_page = ctx.accounts.bid_page.as_ref().map(|page| page.load_mut()).transpose()?;
+    apply_bid(
+        &ctx.accounts.auction_state,
+        &mut ctx.accounts.auction,
+        &escrow,
+        bidder,
+        ctx.accounts.payer.key(),
+        &ctx.accounts.blacklist_entry,
+        deposited,
+        PositionStore::Accounts(bid_page.as_deref_mut()),
+        false,
+        &[],
+        None,
+        None,
+        0,
+        None,
+        &None
+    )?;
+    Ok(())
+}
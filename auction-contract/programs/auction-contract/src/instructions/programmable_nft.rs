@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::{ Mint, Token, TokenAccount };
+use crate::errors::ErrorCode;
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::initialize_auction::open_auction;
+use crate::instructions::seller_profile::take_slot;
+use crate::settlement::{ self, Custody };
+use crate::state::*;
+use crate::vault;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    NFT_ESCROW_SEED,
+    SELLER_PROFILE_SEED,
+    VAULT_SEED,
+};
+
+// Programmable NFTs sit frozen in their holder's token account, so a plain
+// token transfer can't escrow them. They go through Token Metadata's transfer
+// into the same [NFT_ESCROW_SEED, mint] account Legacy NFTs are held in.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], minimum: u64, overrides: ListingOverrides)]
+pub struct ListProgrammableNft<'info> {
+    // Owns the escrow until settlement or cancellation
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        init,
+        payer = owner,
+        space = AuctionDetails::space(overrides.bid_history_capacity()),
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    // Keeps the seller's count of open listings; required while the marketplace caps it
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, owner.key().as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    /// CHECK: the seller's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    #[account(constraint = nft_mint.decimals == 0 && nft_mint.supply == 1 @ ErrorCode::InvalidNftMint)]
+    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = owner,
+        constraint = seller_nft_account.amount == 1 @ ErrorCode::NftNotHeld
+    )]
+    pub seller_nft_account: Account<'info, TokenAccount>,
+    // Left open by a settled listing of the same mint, so reused when it's there
+    #[account(
+        init_if_needed,
+        payer = owner,
+        token::mint = nft_mint,
+        token::authority = auction_state,
+        seeds = [NFT_ESCROW_SEED, nft_mint.key().as_ref()],
+        bump
+    )]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    /// CHECK: derived and checked by the Token Metadata program
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: derived and checked by the Token Metadata program
+    pub master_edition: UncheckedAccount<'info>,
+    /// CHECK: the seller's token record, checked by the Token Metadata program
+    #[account(mut)]
+    pub owner_token_record: UncheckedAccount<'info>,
+    /// CHECK: the escrow's token record, created by the Token Metadata program
+    #[account(mut)]
+    pub escrow_token_record: UncheckedAccount<'info>,
+    // The mint's rule set, if it has one
+    /// CHECK: checked by the Token Metadata program
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked by the Token Metadata program
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: the instructions sysvar, which Token Metadata's transfer reads
+    #[account(address = sysvar_instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+}
+
+// List a programmable NFT, moving it into the program's escrow
+pub fn list_programmable_nft(
+    ctx: Context<ListProgrammableNft>,
+    listing_id: [u8; 32],
+    minimum: u64,
+    overrides: ListingOverrides
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let owner = ctx.accounts.owner.key();
+    let owner_info = ctx.accounts.owner.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let seller_nft_account = ctx.accounts.seller_nft_account.to_account_info();
+    let mint = ctx.accounts.nft_mint.to_account_info();
+    let metadata = ctx.accounts.metadata.to_account_info();
+    let edition = ctx.accounts.master_edition.to_account_info();
+    let owner_token_record = ctx.accounts.owner_token_record.to_account_info();
+    let escrow_token_record = ctx.accounts.escrow_token_record.to_account_info();
+    let authorization_rules = ctx.accounts.authorization_rules
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let authorization_rules_program = ctx.accounts.authorization_rules_program
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let token_metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+    let associated_token_program = ctx.accounts.associated_token_program.to_account_info();
+    let sysvar_instructions = ctx.accounts.sysvar_instructions.to_account_info();
+    let custody = Custody {
+        nft_escrow: Some(&ctx.accounts.nft_escrow),
+        token_account: Some(&seller_nft_account),
+        token_program: Some(&ctx.accounts.token_program),
+        metadata: Some(&metadata),
+        mint: Some(&mint),
+        edition: Some(&edition),
+        escrow_token_record: Some(&escrow_token_record),
+        token_record: Some(&owner_token_record),
+        authorization_rules: authorization_rules.as_ref(),
+        authorization_rules_program: authorization_rules_program.as_ref(),
+        token_metadata_program: Some(&token_metadata_program),
+        associated_token_program: Some(&associated_token_program),
+        sysvar_instructions: Some(&sysvar_instructions),
+        ..Custody::new(&ctx.accounts.auction_state, &owner_info, &system_program)
+    };
+    settlement::adapter(AssetStandard::Programmable).escrow(&custody, mint.key(), &owner_info)?;
+
+    vault::fund(&owner_info, &ctx.accounts.vault.to_account_info(), &system_program)?;
+    let mut auction = open_auction(
+        &ctx.accounts.auction_state,
+        listing_id,
+        minimum,
+        owner,
+        &overrides,
+        None,
+        None,
+        ctx.bumps.auction,
+        ctx.bumps.vault
+    )?;
+    auction.nft_mint = Some(mint.key());
+    auction.asset_standard = AssetStandard::Programmable;
+    take_slot(&ctx.accounts.auction_state, ctx.accounts.seller_profile.as_mut(), &mut auction)?;
+    ctx.accounts.auction.set_inner(auction);
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::events::AuctionArchived;
+use crate::state::*;
+use crate::vault;
+use crate::{ ARCHIVE_SEED, AUCTION_STATE_SEED };
+
+#[derive(Accounts)]
+pub struct InitAuctionArchive<'info> {
+    #[account(init, payer = payer, space = AuctionArchive::SPACE, seeds = [ARCHIVE_SEED], bump)]
+    pub archive: Account<'info, AuctionArchive>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PruneArchives<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Records every auction pruned, so its outcome stays provable
+    #[account(mut, seeds = [ARCHIVE_SEED], bump = archive.bump)]
+    pub archive: Account<'info, AuctionArchive>,
+    /// CHECK: only receives lamports; pinned to the configured destination
+    #[account(mut, address = auction_state.rent_destination)]
+    pub rent_destination: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_auction_archive(ctx: Context<InitAuctionArchive>) -> Result<()> {
+    let archive = &mut ctx.accounts.archive;
+    archive.version = AuctionArchive::VERSION;
+    archive.leaves = 0;
+    archive.branch = [[0; 32]; AuctionArchive::DEPTH];
+    archive.update_root();
+    archive.bump = ctx.bumps.archive;
+    Ok(())
+}
+
+// Close up to `batch` of the auction PDAs passed as remaining accounts that
+// reached a final status before both `before_ts` and the retention cutoff, owe
+// nothing to anyone, have given back their slot under the seller's listing cap
+// and are off the auction registry. Vaults and bid pages of pruned auctions
+// passed alongside them are emptied and closed too; the rent freed either way
+// goes to the rent destination. Each pruned auction is appended to the archive first.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PruneArchives<'info>>,
+    before_ts: i64,
+    batch: u8
+) -> Result<()> {
+    let cutoff = before_ts.min(
+        Clock::get()?.unix_timestamp - ctx.accounts.auction_state.archive_retention
+    );
+    let batch = (batch as usize).min(NftComAuction::MAX_PRUNE_BATCH);
+    let rent_destination = ctx.accounts.rent_destination.to_account_info();
+
+    let (auctions, others): (Vec<&AccountInfo<'info>>, Vec<&AccountInfo<'info>>) = ctx
+        .remaining_accounts
+        .iter()
+        .partition(|account| {
+            account
+                .try_borrow_data()
+                .is_ok_and(|data| data.starts_with(&AuctionDetails::DISCRIMINATOR))
+        });
+    let (vaults, pages): (Vec<&AccountInfo<'info>>, Vec<&AccountInfo<'info>>) = others
+        .into_iter()
+        .partition(|account| account.owner == &system_program::ID);
+
+    let mut pruned: Vec<Pubkey> = vec![];
+    for account in auctions {
+        if pruned.len() == batch {
+            break;
+        }
+        let auction = Account::<AuctionDetails>::try_from(account)?;
+        if
+            auction.status.is_final() &&
+            auction.closed_at < cutoff &&
+            auction.outstanding()? == 0 &&
+            !auction.holds_seller_slot &&
+            auction.registry_page.is_none()
+        {
+            let vault_address = vault::address(&auction.listing_id, auction.vault_bump)?;
+            if let Some(vault_account) = vaults.iter().find(|account| account.key() == vault_address) {
+                vault::pay_out(
+                    vault_account,
+                    &auction.listing_id,
+                    auction.vault_bump,
+                    &rent_destination,
+                    &ctx.accounts.system_program.to_account_info(),
+                    vault_account.lamports()
+                )?;
+            }
+            let leaf = auction.archive_leaf();
+            let index = ctx.accounts.archive.append(leaf)?;
+            emit!(AuctionArchived { listing_id: auction.listing_id, index, leaf });
+            pruned.push(auction.key());
+            auction.close(rent_destination.clone())?;
+        }
+    }
+
+    for account in pages {
+        let page = AccountLoader::<BidPage>::try_from(account)?;
+        let page_auction = page.load()?.auction;
+        if pruned.contains(&page_auction) {
+            page.close(rent_destination.clone())?;
+        }
+    }
+    if !pruned.is_empty() {
+        ctx.accounts.archive.update_root();
+    }
+    Ok(())
+}
@@ -0,0 +1,461 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::{
+    AUCTION_REGISTRY_SEED,
+    AUCTION_SEED,
+    COLLECTION_STATS_SEED,
+    FLOOR_ORACLE_SEED,
+    LOYALTY_SEED,
+    MAX_QUERY_RESULTS,
+    REGISTRY_PAGE_SEED,
+};
+
+// Read-only queries. None of them takes a writable account or a signer, so a
+// client runs them with simulateTransaction (or Anchor's `.view()`) without
+// write locks or fees, and reads the response from the simulation's return
+// data. That is the borsh encoding of the handler's return type, written with
+// sol_set_return_data and limited to 1024 bytes: integers little-endian, vecs
+// a u32 length then their items, options a 0/1 tag then the value. A query
+// about something that isn't there fails with a dedicated error, e.g.
+// BidNotFound, rather than answering with zeroes that pass for real values.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct GetUserBid<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+// The auction PDAs to look through are passed as remaining accounts
+#[derive(Accounts)]
+pub struct GetAllBidsOfUser {}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct GetLatestBids<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct GetHighestBidder<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct GetAuctionEndTime<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct HasAuctionEnded<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+// The auction PDAs to look through are passed as remaining accounts
+#[derive(Accounts)]
+pub struct GetActiveAuctionsOf {}
+
+// The auction PDAs to look through are passed as remaining accounts
+#[derive(Accounts)]
+pub struct GetPastAuctionsOf {}
+
+// The auction PDAs to look through are passed as remaining accounts
+#[derive(Accounts)]
+pub struct GetAuctionsEndingSoon {}
+
+// The auction PDAs to look through are passed as remaining accounts
+#[derive(Accounts)]
+pub struct GetPendingWithdrawals {}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct GetBidAmount<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct GetAuctionDetails<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+// The auction PDAs to look through are passed as remaining accounts
+#[derive(Accounts)]
+pub struct GetPendingWithdrawalAmount {}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct GetHighestBidAndEndTime<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct GetWinner<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct GetLoyaltyBalance<'info> {
+    #[account(seeds = [LOYALTY_SEED, owner.as_ref()], bump = loyalty_account.bump)]
+    pub loyalty_account: Account<'info, LoyaltyAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey)]
+pub struct GetCollectionTwap<'info> {
+    #[account(seeds = [COLLECTION_STATS_SEED, collection.as_ref()], bump = collection_stats.bump)]
+    pub collection_stats: Account<'info, CollectionStats>,
+}
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey)]
+pub struct GetFloorPrice<'info> {
+    #[account(seeds = [FLOOR_ORACLE_SEED, collection.as_ref()], bump = floor_oracle.load()?.bump)]
+    pub floor_oracle: AccountLoader<'info, FloorOracle>,
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct GetAuctions<'info> {
+    #[account(seeds = [AUCTION_REGISTRY_SEED], bump = registry.bump)]
+    pub registry: Account<'info, AuctionRegistry>,
+    #[account(
+        seeds = [REGISTRY_PAGE_SEED, &page.to_le_bytes()],
+        bump = registry_page.load()?.bump
+    )]
+    pub registry_page: AccountLoader<'info, AuctionRegistryPage>,
+}
+
+// Deserialize the auction PDAs passed as remaining accounts, at most
+// MAX_QUERY_RESULTS of them
+fn auctions_in<'info>(
+    accounts: &'info [AccountInfo<'info>]
+) -> Result<Vec<Account<'info, AuctionDetails>>> {
+    require!(accounts.len() <= MAX_QUERY_RESULTS, ErrorCode::InvalidBatchSize);
+    accounts.iter().map(Account::<AuctionDetails>::try_from).collect()
+}
+
+// Fails with BidNotFound unless `user` holds an inline position on the listing
+pub fn get_user_bid(
+    ctx: Context<GetUserBid>,
+    _listing_id: [u8; 32],
+    user: Pubkey
+) -> Result<UserBidResponse> {
+    let bid = ctx.accounts.auction.bid_of(&user).ok_or(ErrorCode::BidNotFound)?;
+    Ok(UserBidResponse { bidder: user, amount: bid.amount, time: bid.time })
+}
+
+// Page size is capped at MAX_QUERY_RESULTS, which keeps a page within both the
+// compute budget and the return data limit
+fn page_bounds(offset: u64, limit: u64) -> (usize, usize) {
+    (offset as usize, (limit as usize).min(MAX_QUERY_RESULTS))
+}
+
+pub fn get_all_bids_of_user<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetAllBidsOfUser>,
+    bidder: Pubkey,
+    offset: u64,
+    limit: u64
+) -> Result<UserBidsPage> {
+    let (offset, limit) = page_bounds(offset, limit);
+    let mut page = UserBidsPage { listing_ids: vec![], amounts: vec![], times: vec![], total: 0 };
+
+    for auction in auctions_in(ctx.remaining_accounts)? {
+        if auction.status.is_closed() {
+            continue;
+        }
+        if let Some(bid) = auction.bid_of(&bidder) {
+            let index = page.total as usize;
+            page.total = page.total.try_add(1)?;
+            if index < offset || index - offset >= limit {
+                continue;
+            }
+            page.listing_ids.push(auction.listing_id);
+            page.amounts.push(bid.amount);
+            page.times.push(bid.time);
+        }
+    }
+
+    Ok(page)
+}
+
+pub fn get_latest_bids(
+    ctx: Context<GetLatestBids>,
+    _listing_id: [u8; 32],
+    offset: u64,
+    limit: u64
+) -> Result<BidHistoryPage> {
+    let history = &ctx.accounts.auction.bid_history;
+    let (offset, limit) = page_bounds(offset, limit);
+    let mut page = BidHistoryPage {
+        bidders: vec![],
+        amounts: vec![],
+        times: vec![],
+        total: history.records.len() as u64,
+    };
+
+    // Only the bids still in the auction's history ring, newest first
+    for bid in history.latest().skip(offset).take(limit) {
+        page.bidders.push(bid.bidder);
+        page.amounts.push(bid.amount);
+        page.times.push(bid.time);
+    }
+
+    Ok(page)
+}
+
+pub fn get_highest_bidder(ctx: Context<GetHighestBidder>, _listing_id: [u8; 32]) -> Result<Pubkey> {
+    Ok(ctx.accounts.auction.highest_bidder)
+}
+
+pub fn get_auction_end_time(ctx: Context<GetAuctionEndTime>, _listing_id: [u8; 32]) -> Result<i64> {
+    Ok(ctx.accounts.auction.end_time)
+}
+
+pub fn has_auction_ended(ctx: Context<HasAuctionEnded>, _listing_id: [u8; 32]) -> Result<bool> {
+    Ok(ctx.accounts.auction.status.is_closed())
+}
+
+// Live auctions ending within the next `within` seconds, soonest first
+pub fn get_auctions_ending_soon<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetAuctionsEndingSoon>,
+    within: i64
+) -> Result<Vec<[u8; 32]>> {
+    require!(within >= 0, ErrorCode::InvalidQueryWindow);
+    let now = Clock::get()?.unix_timestamp;
+    let horizon = now.saturating_add(within);
+
+    let mut ending: Vec<_> = auctions_in(ctx.remaining_accounts)?
+        .into_iter()
+        .filter(|auction| {
+            auction.status == AuctionStatus::Live &&
+                auction.end_time >= now &&
+                auction.end_time <= horizon
+        })
+        .collect();
+    ending.sort_by_key(|auction| auction.end_time);
+    Ok(
+        ending
+            .iter()
+            .map(|auction| auction.listing_id)
+            .collect()
+    )
+}
+
+pub fn get_active_auctions_of<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetActiveAuctionsOf>,
+    owner: Pubkey
+) -> Result<Vec<[u8; 32]>> {
+    Ok(
+        auctions_in(ctx.remaining_accounts)?
+            .iter()
+            .filter(|auction| auction.owner == owner && !auction.status.is_closed())
+            .map(|auction| auction.listing_id)
+            .collect()
+    )
+}
+
+// Function to get past auctions for an owner
+pub fn get_past_auctions_of<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetPastAuctionsOf>,
+    owner: Pubkey
+) -> Result<Vec<[u8; 32]>> {
+    Ok(
+        auctions_in(ctx.remaining_accounts)?
+            .iter()
+            .filter(|auction| auction.owner == owner && auction.status.is_closed())
+            .map(|auction| auction.listing_id)
+            .collect()
+    )
+}
+
+// Function to get pending withdrawals for an address: everything it has escrowed
+// on the given auctions except where it holds a winning position (beyond the
+// price, once that sale has settled)
+pub fn get_pending_withdrawals<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetPendingWithdrawals>,
+    address: Pubkey
+) -> Result<u64> {
+    withdrawable(ctx.remaining_accounts, &address)
+}
+
+// Fails with BidNotFound, as get_user_bid, when the bidder holds no position
+pub fn get_bid_amount(
+    ctx: Context<GetBidAmount>,
+    _listing_id: [u8; 32],
+    bidder: Pubkey
+) -> Result<u64> {
+    let bid = ctx.accounts.auction.bid_of(&bidder).ok_or(ErrorCode::BidNotFound)?;
+    Ok(bid.amount)
+}
+
+// Bids listed in get_auction_details; keeps the response within the return data limit
+pub const MAX_DETAIL_BIDS: usize = 12;
+
+pub fn get_auction_details(
+    ctx: Context<GetAuctionDetails>,
+    _listing_id: [u8; 32]
+) -> Result<AuctionDetailsResponse> {
+    let auction = &ctx.accounts.auction;
+    let mut bids: Vec<&BidEntry> = auction.bids.iter().collect();
+    bids.sort_by(|a, b| b.amount.cmp(&a.amount));
+    bids.truncate(MAX_DETAIL_BIDS);
+
+    let response = AuctionDetailsResponse {
+        listing_id: auction.listing_id,
+        highest_bid: auction.highest_bid,
+        highest_bidder: auction.highest_bidder,
+        minimum_bid: auction.minimum_bid,
+        status: auction.status,
+        owner: auction.owner,
+        end_time: auction.end_time,
+        bidders: bids.iter().map(|bid| bid.bidder).collect(),
+        num_bidders: auction.bids.len() as u64,
+        amounts: bids.iter().map(|bid| bid.amount).collect(),
+        times: bids.iter().map(|bid| bid.time).collect(),
+        paused: auction.status == AuctionStatus::Paused,
+        is_alien: auction.is_alien,
+        total_amount: auction.total_amount,
+        buy_now_price: auction.buy_now_price,
+        payment_mint: auction.payment_mint,
+        min_next_bid: auction.min_next_bid()?,
+    };
+
+    Ok(response)
+}
+
+pub fn get_pending_withdrawal_amount<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetPendingWithdrawalAmount>,
+    owner: Pubkey
+) -> Result<u64> {
+    withdrawable(ctx.remaining_accounts, &owner)
+}
+
+fn withdrawable<'info>(accounts: &'info [AccountInfo<'info>], address: &Pubkey) -> Result<u64> {
+    auctions_in(accounts)?
+        .iter()
+        .filter_map(|auction| {
+            let bid = auction.bid_of(address)?;
+            if !auction.is_winner(address) {
+                Some(bid.amount)
+            } else if auction.status == AuctionStatus::Settled {
+                // A winner's surplus over the price they won at
+                Some(bid.amount.saturating_sub(auction.winning_price()))
+            } else {
+                None
+            }
+        })
+        .try_fold(0u64, |total, amount| total.try_add(amount))
+}
+
+pub fn get_highest_bid_and_end_time(
+    ctx: Context<GetHighestBidAndEndTime>,
+    _listing_id: [u8; 32]
+) -> Result<HighestBidResponse> {
+    let auction = &ctx.accounts.auction;
+
+    // Get the current time
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // Calculate the remaining time
+    let remaining_time = if current_time < auction.end_time {
+        (auction.end_time - current_time) as u64
+    } else {
+        0
+    };
+
+    Ok(HighestBidResponse {
+        highest_bidder: auction.highest_bidder,
+        highest_bid: auction.highest_bid,
+        end_time: auction.end_time,
+        remaining_time,
+    })
+}
+
+pub fn get_winner(ctx: Context<GetWinner>, _listing_id: [u8; 32]) -> Result<Pubkey> {
+    let auction = &ctx.accounts.auction;
+
+    // Check if the auction has ended
+    require!(auction.status.is_closed(), ErrorCode::AuctionNotEnded); // Custom error for auction not ended
+
+    Ok(auction.highest_bidder)
+}
+
+pub fn get_loyalty_balance(ctx: Context<GetLoyaltyBalance>, _owner: Pubkey) -> Result<u64> {
+    Ok(ctx.accounts.loyalty_account.points)
+}
+
+// The collection's average sale price over the last `window` seconds; fails
+// with NoCollectionSales until one of its listings has sold
+pub fn get_collection_twap(
+    ctx: Context<GetCollectionTwap>,
+    collection: Pubkey,
+    window: i64
+) -> Result<CollectionTwapResponse> {
+    require!(window > 0, ErrorCode::InvalidQueryWindow);
+    let stats = &ctx.accounts.collection_stats;
+    let now = Clock::get()?.unix_timestamp;
+    let (twap, span) = stats.twap(window, now).ok_or(ErrorCode::NoCollectionSales)?;
+
+    Ok(CollectionTwapResponse {
+        collection,
+        twap,
+        span,
+        last_price: stats.last_price,
+        sales: stats.sales,
+    })
+}
+
+// The collection's floor as of now, which may be higher than the oracle's
+// stored floor once that sale has left the window; fails with
+// NoCollectionSales when no kept sale is within it
+pub fn get_floor_price(
+    ctx: Context<GetFloorPrice>,
+    collection: Pubkey
+) -> Result<FloorPriceResponse> {
+    let oracle = ctx.accounts.floor_oracle.load()?;
+    let now = Clock::get()?.unix_timestamp;
+    let floor = oracle.floor_at(now).ok_or(ErrorCode::NoCollectionSales)?;
+
+    Ok(FloorPriceResponse {
+        collection,
+        floor: floor.price,
+        floor_sale_time: floor.time,
+        window: oracle.window,
+    })
+}
+
+// The live auctions listed on registry page `page`, in no particular order;
+// `pages` tells how far there is to page through
+pub fn get_auctions(ctx: Context<GetAuctions>, page: u32) -> Result<AuctionRegistryResponse> {
+    let registry_page = ctx.accounts.registry_page.load()?;
+    let listed = registry_page.listed();
+
+    Ok(AuctionRegistryResponse {
+        page,
+        pages: ctx.accounts.registry.pages,
+        auctions: listed
+            .iter()
+            .map(|entry| entry.auction)
+            .collect(),
+        end_times: listed
+            .iter()
+            .map(|entry| entry.end_time)
+            .collect(),
+    })
+}
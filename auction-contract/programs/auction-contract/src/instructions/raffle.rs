@@ -0,0 +1,228 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::allowlist;
+use crate::errors::ErrorCode;
+use crate::events::{ RaffleDrawn, TicketsBought };
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::initialize_auction::{ self, InitializeAuction };
+use crate::invariants::assert_funds_conserved;
+use crate::math::{ self, CheckedMath };
+use crate::randomness;
+use crate::state::*;
+use crate::token_gate;
+use crate::vault;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_FEE_SEED,
+    VAULT_SEED,
+};
+
+// Raffles. Participants buy tickets at a fixed price until the listing's end
+// time; the seller then commits Switchboard randomness with
+// `commit_randomness`, which closes the raffle to tickets, and once the oracle
+// reveals anyone can `draw_raffle`. The winner is drawn with odds in
+// proportion to their tickets and the whole pot becomes their winning bid, so
+// from there the NFT and proceeds settle exactly as an auction's do.
+//
+// Tickets are inline positions only, so a raffle has at most
+// AuctionDetails::MAX_INLINE_BIDDERS participants. They can't be withdrawn
+// unless the randomness never arrives and the raffle is cancelled.
+
+#[derive(Accounts)]
+pub struct InitializeRaffle<'info> {
+    // Same accounts as a regular listing
+    pub listing: InitializeAuction<'info>,
+}
+
+// The buyer's wallet is their ticket position
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct BuyTickets<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: the buyer's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, buyer.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // The buyer's holding of the gate mint; only needed on token-gated listings
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    // The listing's collection fee config, if it has one
+    #[account(
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Option<Account<'info, CollectionFeeConfig>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct DrawRaffle<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: checked against the raffle's commitment by `randomness::consume`
+    pub randomness_account: UncheckedAccount<'info>,
+    // Anyone may draw a raffle once its randomness is revealed, and earns the keeper tip for it
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// List the NFT as a raffle selling tickets at `ticket_price` each. The
+// overrides apply as for an auction, except that a raffle has no reserve and
+// can't be bought outright.
+pub fn initialize_raffle<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitializeRaffle<'info>>,
+    listing_id: [u8; 32],
+    ticket_price: u64,
+    owner: Pubkey,
+    overrides: ListingOverrides
+) -> Result<()> {
+    require!(
+        ticket_price > 0 &&
+            overrides.reserve_price.is_none() &&
+            overrides.buy_now_price.is_none() &&
+            overrides.usd_pricing.is_none(),
+        ErrorCode::InvalidRaffle
+    );
+    // Tickets are sold for SOL only
+    require!(overrides.payment_mint.is_none(), ErrorCode::InvalidPaymentMint);
+
+    initialize_auction::handler(
+        Context::new(
+            ctx.program_id,
+            &mut ctx.accounts.listing,
+            ctx.remaining_accounts,
+            ctx.bumps.listing
+        ),
+        listing_id,
+        ticket_price,
+        owner,
+        overrides,
+        None
+    )?;
+    ctx.accounts.listing.auction.ticket_price = ticket_price;
+    Ok(())
+}
+
+// Buy `count` tickets. The buyer fee is charged on top of the ticket price, and
+// a private or token-gated raffle checks its participants as an auction does
+// its bidders.
+pub fn buy_tickets(
+    ctx: Context<BuyTickets>,
+    listing_id: [u8; 32],
+    count: u64,
+    proof: Vec<[u8; 32]>
+) -> Result<()> {
+    ensure_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    let buyer = ctx.accounts.buyer.key();
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.ticket_price > 0, ErrorCode::NotRaffle);
+    require!(buyer != auction.owner, ErrorCode::BidderIsOwner);
+    allowlist::check(auction, &buyer, &proof)?;
+    token_gate::check(auction, &buyer, ctx.accounts.gate_token_account.as_deref())?;
+    match auction.status {
+        AuctionStatus::Live => {}
+        AuctionStatus::Paused => {
+            return Err(ErrorCode::AuctionPaused.into());
+        }
+        _ => {
+            return Err(ErrorCode::AuctionEnded.into());
+        }
+    }
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= auction.end_time, ErrorCode::AuctionEnded);
+    require!(count > 0, ErrorCode::MinimumBidError);
+
+    let cost = auction.ticket_price.try_mul(count)?;
+    let buyer_fee_bps = ctx.accounts.auction_state.buyer_fee_bps_for(
+        auction,
+        ctx.accounts.collection_fee_config.as_deref(),
+        cost
+    );
+    let fee = math::compute_fee(cost, buyer_fee_bps)?;
+
+    let vault_info = ctx.accounts.vault.to_account_info();
+    system_program::transfer(
+        CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+            from: ctx.accounts.buyer.to_account_info(),
+            to: vault_info.clone(),
+        }),
+        cost.try_add(fee)?
+    )?;
+    auction.fees = auction.fees.try_add(fee)?;
+    auction.total_amount = auction.total_amount.try_add(cost)?;
+    auction.record_bid(buyer, cost, now, PositionStore::Accounts(None), false, None)?;
+    auction.bid_history.push(BidRecord { bidder: buyer, amount: cost, time: now })?;
+
+    assert_funds_conserved(&vault_info, auction)?;
+
+    emit!(TicketsBought { listing_id, buyer, count, cost });
+    Ok(())
+}
+
+// Draw the winner from the revealed randomness and close the raffle. Every
+// ticket's price moves onto the winner's position, which makes the pot their
+// winning bid.
+pub fn draw_raffle(ctx: Context<DrawRaffle>, listing_id: [u8; 32]) -> Result<()> {
+    let clock = Clock::get()?;
+    let keeper_tip = ctx.accounts.auction_state.keeper_tip;
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.ticket_price > 0, ErrorCode::NotRaffle);
+    let commitment = auction.randomness.take().ok_or(ErrorCode::RandomnessNotCommitted)?;
+    let value = randomness::consume(&commitment, &ctx.accounts.randomness_account, &clock)?;
+
+    // The modulo bias over a 64-bit roll is negligible for any ticket count
+    let price = auction.ticket_price;
+    let tickets = auction.total_amount / price;
+    require!(tickets > 0, ErrorCode::NoTicketsSold);
+    let mut roll = u64::from_le_bytes(value[..8].try_into().unwrap()) % tickets;
+    let winner = auction.bids
+        .iter()
+        .find(|bid| {
+            let held = bid.amount / price;
+            if roll < held {
+                return true;
+            }
+            roll -= held;
+            false
+        })
+        .map(|bid| bid.bidder)
+        .ok_or(ErrorCode::NoTicketsSold)?;
+
+    let pot = auction.total_amount;
+    for bid in auction.bids.iter_mut() {
+        bid.amount = if bid.bidder == winner { pot } else { 0 };
+    }
+    auction.highest_bid = pot;
+    auction.highest_max_bid = pot;
+    auction.highest_bidder = winner;
+    auction.transition_to(AuctionStatus::Ended)?;
+
+    // The tip comes out of the buyer fees, as when ending an auction
+    let tip = keeper_tip.min(auction.fees);
+    auction.fees = auction.fees.try_sub(tip)?;
+    vault::pay_out(
+        &ctx.accounts.vault.to_account_info(),
+        &listing_id,
+        auction.vault_bump,
+        &ctx.accounts.caller.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        tip
+    )?;
+    assert_funds_conserved(&ctx.accounts.vault.to_account_info(), auction)?;
+
+    emit!(RaffleDrawn { listing_id, winner, tickets, pot });
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::errors::ErrorCode;
+use crate::events::VaultReconciled;
+use crate::invariants::{ assert_funds_conserved, escrow_held };
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::vault::Escrow;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    FEE_TREASURY_SEED,
+    ROLES_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+};
+
+// Vault reconciliation. Anyone can send lamports or tokens straight into a
+// listing's escrow, and nothing the program does would ever pay them out.
+// `reconcile_vault` compares what the escrow holds with what the listing owes
+// out of it, `AuctionDetails::outstanding`: bids not yet refunded, fees and
+// proceeds not yet paid out, bonds and collateral. Whatever is held beyond
+// that goes to the fee treasury; what's owed is never touched, and a shortfall
+// is reported rather than swept.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ReconcileVault<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        constraint = auction_state.manages_fees(roles.as_deref(), &signer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Option<Account<'info, Roles>>,
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    // The treasury for the listing's currency receives the surplus
+    #[account(
+        mut,
+        seeds = [FEE_TREASURY_SEED, auction.currency().as_ref()],
+        bump = fee_treasury.bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    // Listings paid in a token only: the listing's token vault and the fee
+    // treasury's token account for the payment mint
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: must be the fee treasury's token account for the payment mint
+    #[account(mut)]
+    pub treasury_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Sweep whatever the listing's escrow holds beyond what it owes into the fee treasury
+pub fn reconcile_vault(ctx: Context<ReconcileVault>, listing_id: [u8; 32]) -> Result<()> {
+    let auction = &ctx.accounts.auction;
+    let escrow = Escrow::new(
+        auction,
+        &listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?;
+    let held = escrow_held(escrow.holding(), auction)?;
+    let owed = auction.outstanding()?;
+    let surplus = held.saturating_sub(owed);
+
+    if surplus > 0 {
+        let treasury_token_account = ctx.accounts.treasury_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let treasury = escrow.account_of(
+            &ctx.accounts.fee_treasury.to_account_info(),
+            treasury_token_account.as_ref()
+        )?;
+        escrow.pay(&treasury, surplus)?;
+        let fee_treasury = &mut ctx.accounts.fee_treasury;
+        fee_treasury.collected = fee_treasury.collected.try_add(surplus)?;
+        assert_funds_conserved(escrow.holding(), auction)?;
+    }
+
+    emit!(VaultReconciled {
+        listing_id,
+        held,
+        owed,
+        swept: surplus,
+        shortfall: owed.saturating_sub(held),
+    });
+    Ok(())
+}
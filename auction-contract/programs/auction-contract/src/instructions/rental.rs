@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{
+    freeze_delegated_account,
+    thaw_delegated_account,
+    FreezeDelegatedAccount,
+    Metadata,
+    ThawDelegatedAccount,
+};
+use anchor_spl::token::{ self, Approve, Mint, Token, TokenAccount, Transfer };
+use crate::errors::ErrorCode;
+use crate::events::{ RentalExpired, RentalStarted };
+use crate::instructions::nft_escrow::release_escrowed_nft;
+use crate::settlement;
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, NFT_ESCROW_SEED };
+
+// Rental listings. The winner of an auction listed with `rental_days` gets the
+// NFT for that many days rather than for good: `claim_rental` moves it into
+// their wallet, delegates it to the state PDA and freezes it there through the
+// NFT's master edition, so the renter can use it but can't sell, move or burn
+// it. Once the term is over anyone can `expire_rental`, which thaws the token
+// and sends it back to the seller; moving the whole delegated amount ends the
+// delegation with it.
+//
+// Only Legacy NFTs in the program's escrow can be rented, since the freeze goes
+// through Token Metadata's delegate freeze. The seller is paid as for any sale.
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ClaimRental<'info> {
+    // Escrow authority, and the rented token's delegate
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        constraint = auction.is_winner(&winner.key()) @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    // The renter, who approves the delegation and pays for their token account
+    #[account(mut)]
+    pub winner: Signer<'info>,
+    // Gets back the rent of the emptied NFT escrow
+    /// CHECK: only receives lamports; pinned to the auction's seller
+    #[account(mut, address = auction.owner @ ErrorCode::Unauthorized)]
+    pub seller: UncheckedAccount<'info>,
+    #[account(
+        constraint = auction.nft_mint == Some(nft_mint.key()) @ ErrorCode::NftEscrowMismatch
+    )]
+    pub nft_mint: Account<'info, Mint>,
+    #[account(mut, seeds = [NFT_ESCROW_SEED, nft_mint.key().as_ref()], bump)]
+    pub nft_escrow: Account<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = winner,
+        associated_token::mint = nft_mint,
+        associated_token::authority = winner
+    )]
+    pub winner_nft_account: Account<'info, TokenAccount>,
+    /// CHECK: derived and checked by the Token Metadata program
+    pub metadata: UncheckedAccount<'info>,
+    // The NFT's freeze authority
+    /// CHECK: derived and checked by the Token Metadata program
+    pub master_edition: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ExpireRental<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(
+        constraint = auction.nft_mint == Some(nft_mint.key()) @ ErrorCode::NftEscrowMismatch
+    )]
+    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = auction.highest_bidder
+    )]
+    pub renter_nft_account: Account<'info, TokenAccount>,
+    /// CHECK: only owns the returned NFT's token account; pinned to the auction's seller
+    #[account(address = auction.owner @ ErrorCode::Unauthorized)]
+    pub seller: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = seller
+    )]
+    pub seller_nft_account: Account<'info, TokenAccount>,
+    /// CHECK: derived and checked by the Token Metadata program
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: derived and checked by the Token Metadata program
+    pub master_edition: UncheckedAccount<'info>,
+    // Anyone may end a rental once its term is over, and pays for the seller's
+    // token account if it was closed in the meantime
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// The winner of a rental listing takes the NFT for the listing's term
+pub fn claim_rental(ctx: Context<ClaimRental>, listing_id: [u8; 32]) -> Result<()> {
+    let auction_state = &ctx.accounts.auction_state;
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.rental_days > 0, ErrorCode::NotRental);
+    require!(auction.is_sold(), ErrorCode::AuctionNotEnded);
+    require!(!auction.nft_claimed, ErrorCode::NftAlreadyClaimed);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !auction.in_dispute_hold(auction_state.dispute_window, now),
+        ErrorCode::DisputeWindowOpen
+    );
+    require!(
+        settlement::adapter(auction.asset_standard).rents() && auction.bundle_mints.is_empty(),
+        ErrorCode::NftEscrowMismatch
+    );
+    auction.nft_claimed = true;
+
+    let winner_nft_account = ctx.accounts.winner_nft_account.to_account_info();
+    release_escrowed_nft(
+        auction_state,
+        &ctx.accounts.token_program,
+        &ctx.accounts.nft_escrow,
+        &winner_nft_account,
+        &ctx.accounts.seller.to_account_info()
+    )?;
+    token::approve(
+        CpiContext::new(ctx.accounts.token_program.to_account_info(), Approve {
+            to: winner_nft_account.clone(),
+            delegate: auction_state.to_account_info(),
+            authority: ctx.accounts.winner.to_account_info(),
+        }),
+        1
+    )?;
+    let signer_seeds: &[&[&[u8]]] = &[&[AUCTION_STATE_SEED, &[auction_state.bump]]];
+    freeze_delegated_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            FreezeDelegatedAccount {
+                metadata: ctx.accounts.metadata.to_account_info(),
+                delegate: auction_state.to_account_info(),
+                token_account: winner_nft_account,
+                edition: ctx.accounts.master_edition.to_account_info(),
+                mint: ctx.accounts.nft_mint.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            signer_seeds
+        )
+    )?;
+
+    let term = (auction.rental_days as i64) * SECONDS_PER_DAY;
+    auction.rental_expires_at = now + term;
+
+    emit!(RentalStarted {
+        listing_id,
+        renter: ctx.accounts.winner.key(),
+        mint: ctx.accounts.nft_mint.key(),
+        expires_at: auction.rental_expires_at,
+    });
+    Ok(())
+}
+
+// Thaw the rented NFT and return it to the seller once the term is over
+pub fn expire_rental(ctx: Context<ExpireRental>, listing_id: [u8; 32]) -> Result<()> {
+    let auction_state = &ctx.accounts.auction_state;
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.rental_days > 0, ErrorCode::NotRental);
+    require!(auction.rental_expires_at > 0, ErrorCode::RentalNotActive);
+    require!(
+        Clock::get()?.unix_timestamp >= auction.rental_expires_at,
+        ErrorCode::RentalNotExpired
+    );
+    auction.rental_expires_at = 0;
+
+    let signer_seeds: &[&[&[u8]]] = &[&[AUCTION_STATE_SEED, &[auction_state.bump]]];
+    let renter_nft_account = ctx.accounts.renter_nft_account.to_account_info();
+    thaw_delegated_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            ThawDelegatedAccount {
+                metadata: ctx.accounts.metadata.to_account_info(),
+                delegate: auction_state.to_account_info(),
+                token_account: renter_nft_account.clone(),
+                edition: ctx.accounts.master_edition.to_account_info(),
+                mint: ctx.accounts.nft_mint.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            signer_seeds
+        )
+    )?;
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: renter_nft_account,
+                to: ctx.accounts.seller_nft_account.to_account_info(),
+                authority: auction_state.to_account_info(),
+            },
+            signer_seeds
+        ),
+        1
+    )?;
+
+    emit!(RentalExpired {
+        listing_id,
+        renter: auction.highest_bidder,
+        seller: auction.owner,
+    });
+    Ok(())
+}
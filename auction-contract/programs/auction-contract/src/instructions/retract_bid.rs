@@ -0,0 +1,200 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::errors::ErrorCode;
+use crate::events::{ BidRetracted, RetractionPenaltySet };
+use crate::invariants::assert_funds_conserved;
+use crate::math::{ self, CheckedMath };
+use crate::state::*;
+use crate::vault::Escrow;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BID_RECEIPT_SEED,
+    FEE_TREASURY_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+    WSOL_UNWRAP_SEED,
+};
+
+// Bid retraction. A bidder who isn't winning can take their bid off a live
+// auction altogether with `retract_bid`: their position is removed, along with
+// their place among the runners-up a defaulted sale would go to, and their
+// escrow comes back less the marketplace's retraction penalty. The penalty goes
+// to the fee treasury, or to the seller when the admin chose so. Bids are
+// binding while the auction is live, so with a penalty set `withdraw` refuses
+// such early exits and this is the only way out before the end; once the
+// auction closes, losing bidders withdraw in full. The refund place_bid makes
+// to an outbid leader isn't an exit of their choosing and stays free.
+//
+// Not for listings whose outcome depends on every bid placed: crowd sales,
+// raffles, candle and second-price auctions. Delegated bids hold nothing to
+// retract, and compressed positions are withdrawn as usual.
+
+#[derive(Accounts)]
+pub struct SetRetractionPenalty<'info> {
+    // Only direct while no config change delay is set, as for set_fees
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = auction_state.governance.is_none() @ ErrorCode::GovernanceControlled,
+        constraint = auction_state.config_change_delay == 0 @ ErrorCode::ConfigChangeTimelocked
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct RetractBid<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    // Required when the bidder's record lives on an overflow page
+    #[account(mut, has_one = auction)]
+    pub bid_page: Option<AccountLoader<'info, BidPage>>,
+    /// CHECK: the bidder's receipt for this listing, updated if they have one
+    #[account(
+        mut,
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub bid_receipt: UncheckedAccount<'info>,
+    // Receives the penalty when it goes to the seller
+    /// CHECK: only receives lamports; pinned to the auction's seller
+    #[account(mut, address = auction.owner @ ErrorCode::Unauthorized)]
+    pub seller: UncheckedAccount<'info>,
+    // Receives the penalty otherwise, in the treasury for the listing's currency
+    #[account(
+        mut,
+        seeds = [FEE_TREASURY_SEED, auction.currency().as_ref()],
+        bump = fee_treasury.bump
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    // Listings paid in a token only: the listing's token vault, and the
+    // bidder's and the penalty recipient's token accounts for the payment mint
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: must be the bidder's token account for the payment mint
+    #[account(mut)]
+    pub bidder_token_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: must be the seller's or the fee treasury's token account, as the penalty goes
+    #[account(mut)]
+    pub penalty_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    // Wrapped SOL listings only: the account payouts to wallets are unwrapped
+    // through, and the native mint it's opened for
+    /// CHECK: created and closed again within the instruction by the escrow
+    #[account(mut, seeds = [WSOL_UNWRAP_SEED, listing_id.as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Charge bidders who retract `penalty_bps` of their escrowed bid, paid to the
+// seller rather than the fee treasury when `to_seller` is set
+pub fn set_retraction_penalty(
+    ctx: Context<SetRetractionPenalty>,
+    penalty_bps: u64,
+    to_seller: bool
+) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.ensure_unfrozen()?;
+    require!(penalty_bps <= NftComAuction::MAX_FEE_BPS, ErrorCode::InvalidRetractionPenalty);
+    auction_state.retraction_penalty_bps = penalty_bps;
+    auction_state.retraction_penalty_to_seller = to_seller;
+
+    emit!(RetractionPenaltySet { penalty_bps, to_seller });
+    Ok(())
+}
+
+// Take the bidder's bid off the auction before it ends, refunding their escrow
+// less the retraction penalty
+pub fn retract_bid(ctx: Context<RetractBid>, listing_id: [u8; 32]) -> Result<()> {
+    let auction_state = &ctx.accounts.auction_state;
+    let bidder = ctx.accounts.bidder.key();
+    let auction = &mut ctx.accounts.auction;
+    match auction.status {
+        AuctionStatus::Live => {}
+        AuctionStatus::Paused => {
+            return Err(ErrorCode::AuctionPaused.into());
+        }
+        _ => {
+            return Err(ErrorCode::AuctionEnded.into());
+        }
+    }
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < auction.end_time, ErrorCode::AuctionEnded);
+    require!(!auction.is_winner(&bidder), ErrorCode::HighestBidderCannotWithdraw);
+    require!(auction.allows_retraction(), ErrorCode::RetractionNotAllowed);
+
+    // Remove the position itself, inline or on its overflow page
+    let amount = match auction.find_bidder(&bidder) {
+        Ok(index) => auction.bids.remove(index).amount,
+        Err(_) => {
+            let page = ctx.accounts.bid_page.as_ref().ok_or(ErrorCode::NoFundsToWithdraw)?;
+            let mut page = page.load_mut()?;
+            let slot = page.remove(&bidder).ok_or(ErrorCode::NoFundsToWithdraw)?;
+            auction.paged_total = auction.paged_total.try_sub(slot.amount)?;
+            slot.amount
+        }
+    };
+    require!(amount > 0, ErrorCode::NoFundsToWithdraw);
+    auction.runners_up.retain(|entry| entry.bidder != bidder);
+
+    let penalty = math::compute_fee(amount, auction_state.retraction_penalty_bps)?;
+    let refund = amount.try_sub(penalty)?;
+    let to_seller = auction_state.retraction_penalty_to_seller;
+    let escrow = Escrow::new(
+        auction,
+        &listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?.unwrapping(
+        ctx.accounts.unwrap_account.as_ref(),
+        ctx.accounts.native_mint.as_ref(),
+        ctx.accounts.bidder.to_account_info()
+    );
+    let bidder_token_account = ctx.accounts.bidder_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let to = escrow.account_of(
+        &ctx.accounts.bidder.to_account_info(),
+        bidder_token_account.as_ref()
+    )?;
+    escrow.pay(&to, refund)?;
+    if penalty > 0 {
+        let penalty_token_account = ctx.accounts.penalty_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let recipient = if to_seller {
+            ctx.accounts.seller.to_account_info()
+        } else {
+            ctx.accounts.fee_treasury.to_account_info()
+        };
+        let recipient = escrow.account_of(&recipient, penalty_token_account.as_ref())?;
+        escrow.pay(&recipient, penalty)?;
+        if !to_seller {
+            let fee_treasury = &mut ctx.accounts.fee_treasury;
+            fee_treasury.collected = fee_treasury.collected.try_add(penalty)?;
+        }
+    }
+    escrow.settle()?;
+    assert_funds_conserved(escrow.holding(), auction)?;
+    BidReceipt::record_refund(&ctx.accounts.bid_receipt, amount)?;
+
+    emit!(BidRetracted { listing_id, bidder, refunded: refund, penalty, to_seller });
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::RoleChanged;
+use crate::state::*;
+use crate::{ AUCTION_STATE_SEED, ROLES_SEED };
+
+// Role-based access. The admin grants the pauser, fee-manager and
+// blacklist-manager roles to separate keys, each of which can then do that part
+// of the admin's work and nothing more. Instructions a role covers take the
+// roles account as an optional account; leaving it out leaves them to the admin.
+
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Opened with the first grant
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Roles::SPACE,
+        seeds = [ROLES_SEED],
+        bump
+    )]
+    pub roles: Account<'info, Roles>,
+    pub authority: Signer<'info>,
+    // Pays the rent, so a multisig vault holding the authority needn't hold SOL
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Account<'info, Roles>,
+    pub authority: Signer<'info>,
+}
+
+// Hand `role` to `holder`, taking it from whoever held it before
+pub fn grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+    let roles = &mut ctx.accounts.roles;
+    roles.version = Roles::VERSION;
+    roles.bump = ctx.bumps.roles;
+    roles.set_holder(role, Some(holder));
+
+    emit!(RoleChanged { role, holder: Some(holder) });
+    Ok(())
+}
+
+pub fn revoke_role(ctx: Context<RevokeRole>, role: Role) -> Result<()> {
+    ctx.accounts.roles.set_holder(role, None);
+
+    emit!(RoleChanged { role, holder: None });
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::AuctionPaused;
+use crate::state::*;
+use crate::AUCTION_SEED;
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SellerPauseAuction<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SellerPauseAuction>, _listing_id: [u8; 32], paused: bool) -> Result<()> {
+    set_seller_paused(&mut ctx.accounts.auction, paused)
+}
+
+// A seller may pause their listing only while nobody has bid on it, and may
+// resume only a pause of their own; everything else goes through the admin.
+// Nobody can bid while it's paused, so there's no time to give back on resume.
+pub(crate) fn set_seller_paused(auction: &mut AuctionDetails, paused: bool) -> Result<()> {
+    if paused {
+        require!(!auction.has_bids(), ErrorCode::SellerPauseAfterBids);
+        auction.transition_to(AuctionStatus::Paused)?;
+        auction.seller_paused = true;
+    } else {
+        require!(auction.seller_paused, ErrorCode::Unauthorized);
+        auction.transition_to(AuctionStatus::Live)?;
+        auction.seller_paused = false;
+    }
+    emit!(AuctionPaused { listing_id: auction.listing_id, paused, by: auction.owner });
+    Ok(())
+}
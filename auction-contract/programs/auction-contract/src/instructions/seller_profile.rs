@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::{ AuctionExtended, AuctionPaused };
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::SELLER_PROFILE_SEED;
+
+#[derive(Accounts)]
+pub struct InitSellerProfile<'info> {
+    #[account(
+        init,
+        payer = seller,
+        space = SellerProfile::SPACE,
+        seeds = [SELLER_PROFILE_SEED, seller.key().as_ref()],
+        bump
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Every open listing the seller's profile tracks is passed as remaining
+// accounts, so vacation mode can't leave some of them live
+#[derive(Accounts)]
+pub struct SetVacation<'info> {
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, seller.key().as_ref()],
+        bump = seller_profile.bump,
+        has_one = seller @ ErrorCode::Unauthorized
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+    pub seller: Signer<'info>,
+}
+
+// Anyone may release a seller's slots; the seller's closed listings are passed
+// as remaining accounts
+#[derive(Accounts)]
+pub struct ReleaseSellerSlots<'info> {
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, seller_profile.seller.as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Account<'info, SellerProfile>,
+}
+
+pub fn init_seller_profile(ctx: Context<InitSellerProfile>) -> Result<()> {
+    let profile = &mut ctx.accounts.seller_profile;
+    profile.seller = ctx.accounts.seller.key();
+    profile.on_vacation = false;
+    profile.vacation_started = 0;
+    profile.active_auctions = 0;
+    profile.version = SellerProfile::VERSION;
+    profile.bump = ctx.bumps.seller_profile;
+    Ok(())
+}
+
+// Going on vacation pauses every live listing of the seller, which also blocks
+// bids on them. Coming back resumes only the listings vacation mode paused and
+// pushes their end time back by the time spent away, so bidders lose nothing.
+pub fn set_vacation<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SetVacation<'info>>,
+    on_vacation: bool
+) -> Result<()> {
+    let profile = &mut ctx.accounts.seller_profile;
+    if profile.on_vacation == on_vacation {
+        return Ok(());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let away = now - profile.vacation_started;
+    let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+    let mut tracked: u32 = 0;
+    for account in ctx.remaining_accounts {
+        let mut auction = Account::<AuctionDetails>::try_from(account)?;
+        require_keys_eq!(auction.owner, profile.seller, ErrorCode::Unauthorized);
+        require!(!seen.contains(account.key), ErrorCode::IncompleteVacationSet);
+        seen.push(account.key());
+        if auction.holds_seller_slot {
+            tracked = tracked.try_add(1)?;
+        }
+
+        if on_vacation && auction.status == AuctionStatus::Live {
+            auction.transition_to(AuctionStatus::Paused)?;
+            auction.vacation_paused = true;
+            emit!(AuctionPaused {
+                listing_id: auction.listing_id,
+                paused: true,
+                by: profile.seller,
+            });
+        } else if !on_vacation && auction.vacation_paused {
+            auction.vacation_paused = false;
+            // Leave listings an admin has since voided alone
+            if auction.status == AuctionStatus::Paused {
+                auction.transition_to(AuctionStatus::Live)?;
+                auction.end_time = auction.end_time.try_add(away)?;
+                emit!(AuctionPaused {
+                    listing_id: auction.listing_id,
+                    paused: false,
+                    by: profile.seller,
+                });
+                emit!(AuctionExtended {
+                    listing_id: auction.listing_id,
+                    end_time: auction.end_time,
+                });
+            }
+        }
+        auction.exit(&crate::ID)?;
+    }
+    require!(tracked == profile.active_auctions, ErrorCode::IncompleteVacationSet);
+
+    profile.on_vacation = on_vacation;
+    profile.vacation_started = if on_vacation { now } else { 0 };
+    Ok(())
+}
+
+// Count a new listing on its seller's profile, checking it against the cap on
+// open listings if the marketplace has set one. While a cap is set nobody can
+// list without a profile, and nobody lists while their profile is on vacation.
+// Every counted listing has to be passed to `set_vacation`.
+pub(crate) fn take_slot(
+    auction_state: &NftComAuction,
+    profile: Option<&mut Account<SellerProfile>>,
+    auction: &mut AuctionDetails
+) -> Result<()> {
+    let cap = auction_state.max_active_auctions_per_seller;
+    let Some(profile) = profile else {
+        require!(cap == 0, ErrorCode::SellerProfileRequired);
+        return Ok(());
+    };
+    require!(!profile.on_vacation, ErrorCode::SellerOnVacation);
+    require!(cap == 0 || profile.active_auctions < cap, ErrorCode::TooManyActiveAuctions);
+    profile.active_auctions = profile.active_auctions.try_add(1)?;
+    auction.holds_seller_slot = true;
+    Ok(())
+}
+
+// Give a listing's slot back to its seller
+pub(crate) fn release_slot(profile: &mut SellerProfile, auction: &mut AuctionDetails) -> Result<()> {
+    if auction.holds_seller_slot {
+        auction.holds_seller_slot = false;
+        profile.active_auctions = profile.active_auctions.try_sub(1)?;
+    }
+    Ok(())
+}
+
+// A listing stops counting against the cap once it's closed, whether sold or
+// not; this frees the slots of every closed listing passed in.
+pub fn release_seller_slots<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReleaseSellerSlots<'info>>
+) -> Result<()> {
+    let profile = &mut ctx.accounts.seller_profile;
+    for account in ctx.remaining_accounts {
+        let mut auction = Account::<AuctionDetails>::try_from(account)?;
+        require_keys_eq!(auction.owner, profile.seller, ErrorCode::Unauthorized);
+        if auction.status.is_closed() && auction.holds_seller_slot {
+            release_slot(profile, &mut auction)?;
+            auction.exit(&crate::ID)?;
+        }
+    }
+    Ok(())
+}
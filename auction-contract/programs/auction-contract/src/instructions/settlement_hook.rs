@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use crate::errors::ErrorCode;
+use crate::hooks::{ build_hook_instruction, SettlementHookPayload };
+use crate::state::*;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED };
+
+#[derive(Accounts)]
+pub struct ManageHookPrograms<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_STATE_SEED],
+        bump = auction_state.bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub auction_state: Account<'info, NftComAuction>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct RunSettlementHook<'info> {
+    // Signs the hook call as the marketplace
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    /// CHECK: must match the hook recorded on the auction at settlement
+    #[account(executable)]
+    pub hook_program: UncheckedAccount<'info>,
+}
+
+pub fn add_hook_program(ctx: Context<ManageHookPrograms>, program: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    if !auction_state.hook_programs.contains(&program) {
+        require!(
+            auction_state.hook_programs.len() < NftComAuction::MAX_HOOK_PROGRAMS,
+            ErrorCode::HookWhitelistFull
+        );
+        auction_state.hook_programs.push(program);
+    }
+    Ok(())
+}
+
+pub fn remove_hook_program(ctx: Context<ManageHookPrograms>, program: Pubkey) -> Result<()> {
+    let auction_state = &mut ctx.accounts.auction_state;
+    auction_state.hook_programs.retain(|key| *key != program);
+    Ok(())
+}
+
+pub fn run_settlement_hook<'info>(
+    ctx: Context<'_, '_, '_, 'info, RunSettlementHook<'info>>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let auction_state = &ctx.accounts.auction_state;
+    let state_key = auction_state.key();
+    let bump = auction_state.bump;
+    let auction = &mut ctx.accounts.auction;
+
+    require!(auction.status == AuctionStatus::Settled, ErrorCode::AuctionNotEnded);
+    let hook_program = auction.settlement_hook.take().ok_or(ErrorCode::NoPendingHook)?;
+    require_keys_eq!(ctx.accounts.hook_program.key(), hook_program, ErrorCode::HookNotWhitelisted);
+
+    let payload = SettlementHookPayload {
+        listing_id,
+        winner: auction.highest_bidder,
+        price: auction.highest_bid,
+    };
+    let instruction = build_hook_instruction(
+        hook_program,
+        state_key,
+        &payload,
+        ctx.remaining_accounts
+    )?;
+
+    let mut account_infos = vec![auction_state.to_account_info()];
+    account_infos.extend(ctx.remaining_accounts.iter().cloned());
+    account_infos.push(ctx.accounts.hook_program.to_account_info());
+
+    // Persist the cleared hook before handing control to foreign code
+    auction.exit(&crate::ID)?;
+    invoke_signed(&instruction, &account_infos, &[&[AUCTION_STATE_SEED, &[bump]]])?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::instructions::claim::{ self, ClaimNft, ClaimProceeds };
+use crate::instructions::end_auction::{ self, EndAuction };
+use crate::settlement::Custody;
+use crate::state::*;
+
+// Settlement in three steps, for clients that send each as its own transaction
+// to stay within the compute budget: `finalize_auction` closes the sale as
+// end_auction does and works out its fees and royalties, `settle_nft` delivers
+// the NFT as claim_nft does, and `settle_funds` pays out what finalize_auction
+// worked out as claim_proceeds does. They take the same accounts as those. A step whose work is already done
+// succeeds without doing anything, so a client unsure whether one landed can
+// simply send it again; the settlement receipts keep anything from running twice.
+
+pub fn finalize_auction(mut ctx: Context<EndAuction>, listing_id: [u8; 32]) -> Result<()> {
+    if !ctx.accounts.auction.status.is_closed() {
+        end_auction::handler(&mut ctx, listing_id)?;
+    }
+    // Only a sale still to pay out needs a quote
+    let auction = &ctx.accounts.auction;
+    if auction.status != AuctionStatus::Ended || auction.settlement_quote.is_some() {
+        return Ok(());
+    }
+
+    let payer = ctx.accounts.caller.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let metadata = ctx.accounts.metadata.as_ref().map(|account| account.to_account_info());
+    let asset = ctx.accounts.core_asset.as_ref().map(|account| account.to_account_info());
+    let collection = ctx.accounts.core_collection
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let royalty_accounts = Custody {
+        metadata: metadata.as_ref(),
+        asset: asset.as_ref(),
+        collection: collection.as_ref(),
+        ..Custody::new(&ctx.accounts.auction_state, &payer, &system_program)
+    };
+    let quote = claim::quote_settlement(
+        &ctx.accounts.auction_state,
+        auction,
+        ctx.accounts.collection_fee_config.as_deref(),
+        &royalty_accounts
+    )?;
+    require!(
+        quote.royalties.len() <= SettlementQuote::MAX_CREATORS,
+        ErrorCode::TooManyCreators
+    );
+    ctx.accounts.auction.settlement_quote = Some(quote);
+    Ok(())
+}
+
+pub fn settle_nft<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimNft<'info>>,
+    listing_id: [u8; 32],
+    deliver_to: Option<Pubkey>
+) -> Result<()> {
+    if ctx.accounts.delivery_receipt.version != 0 {
+        return Ok(());
+    }
+    claim::claim_nft(ctx, listing_id, deliver_to)
+}
+
+pub fn settle_funds<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimProceeds<'info>>,
+    listing_id: [u8; 32],
+    hook: Pubkey
+) -> Result<()> {
+    if ctx.accounts.funds_receipt.version != 0 {
+        return Ok(());
+    }
+    claim::claim_proceeds(ctx, listing_id, hook)
+}
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use crate::cpi_guard::assert_cpi_caller_allowed;
+use crate::emitter::{ self, event_authority, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::SignedBidPlaced;
+use crate::instructions::bidder_profile;
+use crate::instructions::place_bid::apply_bid;
+use crate::signed_orders;
+use crate::state::*;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BIDDER_PROFILE_SEED,
+    BID_RECEIPT_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_FEE_SEED,
+    VAULT_SEED,
+};
+
+// Bids signed off-chain. A bidder keeps SOL on their bidder profile and signs
+// `BidOrder`s without sending a transaction; a matcher (any account) submits
+// the order it wants to settle, e.g. the best one it holds for a listing,
+// after an Ed25519 program instruction verifying the bidder's signature. The
+// bid is then placed from the profile balance exactly as `place_bid` would
+// place it, with the matcher paying the transaction and any rent, and named
+// as the bid's relayer. A relayer can so bid for users who sign only the
+// order and never hold SOL for fees.
+//
+// Signed orders settle on SOL listings only, into inline positions, and not
+// on listings asking for a bid bond.
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], order: BidOrder)]
+pub struct PlaceSignedBid<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Grows by a step of bid history when a growable history is full; the matcher covers the rent
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        realloc = AuctionDetails::space(auction.bid_history.capacity_for_next_bid()),
+        realloc::payer = matcher,
+        realloc::zero = false
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    // Funds the bid and guards against replaying the order
+    #[account(
+        mut,
+        seeds = [BIDDER_PROFILE_SEED, order.bidder.as_ref()],
+        bump = bidder_profile.bump
+    )]
+    pub bidder_profile: Account<'info, BidderProfile>,
+    /// CHECK: the bidder's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, order.bidder.as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = matcher,
+        space = BidReceipt::SPACE,
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), order.bidder.as_ref()],
+        bump
+    )]
+    pub bid_receipt: Account<'info, BidReceipt>,
+    // The listing's collection fee config, if it has one
+    #[account(
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Option<Account<'info, CollectionFeeConfig>>,
+    #[account(mut)]
+    pub matcher: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the instructions sysvar, read for the order's Ed25519 verification
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+// Settle `order` as a bid on `listing_id`. `proof` places the bidder on a
+// private listing's allowlist, as for `place_bid`.
+pub fn place_signed_bid(
+    ctx: Context<PlaceSignedBid>,
+    listing_id: [u8; 32],
+    order: BidOrder,
+    proof: Vec<[u8; 32]>
+) -> Result<()> {
+    let events: EventAuthority = event_authority!(ctx);
+    require!(order.listing_id == listing_id, ErrorCode::InvalidListingId);
+    require!(order.amount > 0, ErrorCode::MinimumBidError);
+    assert_cpi_caller_allowed(
+        &ctx.accounts.instructions,
+        &ctx.accounts.auction_state.aggregator_programs
+    )?;
+    require!(ctx.accounts.auction.payment_mint.is_none(), ErrorCode::InvalidPaymentMint);
+    require!(ctx.accounts.auction.bid_bond == 0, ErrorCode::BidBondRequired);
+    let now = Clock::get()?.unix_timestamp;
+    require!(now <= order.expires_at, ErrorCode::OrderExpired);
+
+    signed_orders::verify(&ctx.accounts.instructions, &order.bidder, &order.message()?)?;
+    ctx.accounts.bidder_profile.consume_nonce(order.nonce)?;
+
+    // Move the order's amount from the profile balance into escrow
+    let profile = ctx.accounts.bidder_profile.to_account_info();
+    require!(
+        order.amount <= bidder_profile::balance(&profile)?,
+        ErrorCode::InsufficientProfileBalance
+    );
+    profile.sub_lamports(order.amount)?;
+    ctx.accounts.vault.add_lamports(order.amount)?;
+
+    // The account was already reallocated to fit this
+    ctx.accounts.auction.bid_history.grow_for_next_bid();
+    let credited = apply_bid(
+        &ctx.accounts.auction_state,
+        &mut ctx.accounts.auction,
+        &ctx.accounts.vault.to_account_info(),
+        order.bidder,
+        ctx.accounts.matcher.key(),
+        &ctx.accounts.blacklist_entry,
+        order.amount,
+        PositionStore::Accounts(None),
+        false,
+        &proof,
+        None,
+        ctx.accounts.collection_fee_config.as_deref(),
+        0,
+        None,
+        &events
+    )?;
+
+    let auction_key = ctx.accounts.auction.key();
+    let receipt = &mut ctx.accounts.bid_receipt;
+    if receipt.version == 0 {
+        receipt.version = BidReceipt::VERSION;
+        receipt.auction = auction_key;
+        receipt.bidder = order.bidder;
+        receipt.bump = ctx.bumps.bid_receipt;
+    }
+    receipt.check_bid_limits(&ctx.accounts.auction, now)?;
+    receipt.record_bid(credited, now)?;
+
+    emitter::emit(&events, SignedBidPlaced {
+        listing_id,
+        bidder: order.bidder,
+        matcher: ctx.accounts.matcher.key(),
+        nonce: order.nonce,
+        amount: credited,
+    })?;
+    Ok(())
+}
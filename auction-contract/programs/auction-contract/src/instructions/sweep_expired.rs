@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::AuctionSwept;
+use crate::instructions::nft_escrow::SellerNftReturn;
+use crate::instructions::seller_profile::release_slot;
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, AUCTION_STATE_SEED, SELLER_PROFILE_SEED, VAULT_SEED };
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SweepExpired<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    // Closed to the seller once the caller's bounty is taken out
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        close = owner
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    /// CHECK: the seller, who gets the NFT and the rest of the rent back
+    #[account(mut, address = auction.owner @ ErrorCode::Unauthorized)]
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    // Gets back the slot of a listing counted against the seller's cap
+    #[account(
+        mut,
+        seeds = [SELLER_PROFILE_SEED, auction.owner.as_ref()],
+        bump = seller_profile.bump
+    )]
+    pub seller_profile: Option<Account<'info, SellerProfile>>,
+    pub nft: SellerNftReturn<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Clean up a listing whose auction ran out without a single bid: the NFT goes
+// back to the seller, the auction and its vault are closed, and the caller
+// earns SWEEP_BOUNTY from the freed rent. Anyone may crank this.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SweepExpired<'info>>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &mut ctx.accounts.auction;
+    require!(now >= auction.end_time, ErrorCode::AuctionNotEnded);
+    require!(!auction.has_bids(), ErrorCode::AuctionHasBids);
+    // The account is about to go, so its slot has to be given back now
+    if auction.holds_seller_slot {
+        let profile = ctx.accounts.seller_profile
+            .as_mut()
+            .ok_or(ErrorCode::SellerProfileRequired)?;
+        release_slot(profile, auction)?;
+    }
+
+    let owner = ctx.accounts.owner.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    ctx.accounts.nft.release_to_seller(
+        &ctx.accounts.auction_state,
+        auction,
+        &owner,
+        &ctx.accounts.caller.to_account_info(),
+        &system_program,
+        ctx.remaining_accounts
+    )?;
+
+    let vault_info = ctx.accounts.vault.to_account_info();
+    vault::pay_out(
+        &vault_info,
+        &listing_id,
+        auction.vault_bump,
+        &owner,
+        &system_program,
+        vault_info.lamports()
+    )?;
+
+    let bounty = NftComAuction::SWEEP_BOUNTY;
+    let auction_info = auction.to_account_info();
+    auction_info.sub_lamports(bounty)?;
+    ctx.accounts.caller.to_account_info().add_lamports(bounty)?;
+
+    emit!(AuctionSwept { listing_id, caller: ctx.accounts.caller.key(), bounty });
+    Ok(())
+}
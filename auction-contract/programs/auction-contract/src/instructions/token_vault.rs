@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::errors::ErrorCode;
+use crate::events::TokenVaultOpened;
+use crate::state::*;
+use crate::token_payments::assert_supported_mint;
+use crate::{ AUCTION_SEED, FEE_TREASURY_SEED, TOKEN_VAULT_SEED, VAULT_SEED };
+
+// A listing paid in an SPL token names its mint at listing, then the seller
+// opens its token vault here before the first bid. Only mints the marketplace
+// has a fee treasury for are accepted, since settlement pays the fees into the
+// treasury's token account, which is created here if this is its first listing.
+// The mint may belong to either token program, but not carry an extension the
+// escrow can't work with (see `token_payments`).
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct OpenTokenVault<'info> {
+    #[account(
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    // Owns the token vault
+    #[account(seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        constraint = auction.payment_mint == Some(payment_mint.key()) @ ErrorCode::WrongPaymentMint
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [FEE_TREASURY_SEED, payment_mint.key().as_ref()],
+        bump = fee_treasury.bump,
+        constraint = fee_treasury.mint == payment_mint.key() @ ErrorCode::InvalidPaymentMint
+    )]
+    pub fee_treasury: Account<'info, FeeTreasury>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = payment_mint,
+        associated_token::authority = fee_treasury
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = owner,
+        token::mint = payment_mint,
+        token::authority = vault,
+        seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()],
+        bump
+    )]
+    pub token_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<OpenTokenVault>, listing_id: [u8; 32]) -> Result<()> {
+    assert_supported_mint(&ctx.accounts.payment_mint.to_account_info())?;
+
+    emit!(TokenVaultOpened {
+        listing_id,
+        mint: ctx.accounts.payment_mint.key(),
+        token_vault: ctx.accounts.token_vault.key(),
+    });
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::{ BidLimitsSet, ListingUpdated };
+use crate::state::*;
+use crate::AUCTION_SEED;
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct UpdateListing<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+// Fields left as None keep their current value
+pub fn handler(
+    ctx: Context<UpdateListing>,
+    listing_id: [u8; 32],
+    metadata_uri: Option<String>,
+    reserve_price: Option<u64>,
+    end_time: Option<i64>
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &mut ctx.accounts.auction;
+
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+
+    if let Some(metadata_uri) = metadata_uri {
+        require!(
+            metadata_uri.len() <= AuctionDetails::MAX_URI_LENGTH,
+            ErrorCode::MetadataUriTooLong
+        );
+        auction.metadata_uri = metadata_uri;
+    }
+    if let Some(reserve_price) = reserve_price {
+        require!(auction.usd_pricing.is_none(), ErrorCode::UsdPricedListing);
+        auction.reserve_price = reserve_price;
+    }
+    if let Some(end_time) = end_time {
+        require!(end_time > now, ErrorCode::EndTimeError);
+        auction.end_time = end_time;
+    }
+
+    emit!(ListingUpdated {
+        listing_id,
+        metadata_uri: auction.metadata_uri.clone(),
+        reserve_price: auction.reserve_price,
+        end_time: auction.end_time,
+    });
+    Ok(())
+}
+
+// Cap each bidder at `max_bids_per_bidder` bids, at least `bid_cooldown`
+// seconds apart, so no one can keep re-triggering the anti-sniping extension;
+// 0 lifts either limit. Bids without a bid receipt to count them on, such as
+// batched or bridged ones, are refused while a limit is set.
+pub fn set_bid_limits(
+    ctx: Context<UpdateListing>,
+    listing_id: [u8; 32],
+    max_bids_per_bidder: u16,
+    bid_cooldown: i64
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+    require!(bid_cooldown >= 0, ErrorCode::InvalidBidLimits);
+    auction.max_bids_per_bidder = max_bids_per_bidder;
+    auction.bid_cooldown = bid_cooldown;
+
+    emit!(BidLimitsSet { listing_id, max_bids_per_bidder, bid_cooldown });
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::MinimumBidUpdated;
+use crate::state::*;
+use crate::AUCTION_SEED;
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct UpdateMinimumBid<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<UpdateMinimumBid>, listing_id: [u8; 32], new_minimum: u64) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+
+    require!(new_minimum > 0, ErrorCode::MinimumBidError);
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    // Repricing is only fair while nobody has committed funds yet
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+    require!(auction.usd_pricing.is_none(), ErrorCode::UsdPricedListing);
+
+    let old_minimum = std::mem::replace(&mut auction.minimum_bid, new_minimum);
+
+    emit!(MinimumBidUpdated { listing_id, old_minimum, new_minimum });
+    Ok(())
+}
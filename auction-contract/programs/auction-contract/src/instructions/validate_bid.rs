@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::allowlist;
+use crate::errors::ErrorCode;
+use crate::instructions::blacklist::ensure_not_blacklisted;
+use crate::instructions::membership;
+use crate::instructions::place_bid::extend_if_sniped;
+use crate::math::{ self, CheckedMath };
+use crate::oracle::{ self, OracleLimits };
+use crate::state::*;
+use crate::token_gate;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BID_RECEIPT_SEED,
+    BLACKLIST_SEED,
+    COLLECTION_FEE_SEED,
+    FEATURE_FLAGS_SEED,
+    MEMBERSHIP_DISCOUNT_SEED,
+};
+
+// Bid dry run. `validate_bid` puts a bid of `amount` by `bidder` through the
+// checks place_bid would, on a copy of the auction, and answers with the fee
+// it would pay and where it would leave the auction. A bid that would fail
+// comes back with the error place_bid would fail with rather than failing the
+// query, so a wallet can show either before the bidder signs. Like the other
+// queries it takes no signer or writable account and is run with
+// simulateTransaction; its accounts are the read-only ones of place_bid.
+//
+// What depends on the funds themselves, the payer's balance and a listing's
+// bid bond, is left to the real bid.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ValidateBid<'info> {
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    /// CHECK: the wallet the bid would be placed for; needn't sign
+    pub bidder: UncheckedAccount<'info>,
+    /// CHECK: the bidder's blacklist flag; must not exist
+    #[account(seeds = [BLACKLIST_SEED, bidder.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    // Needed once the auction's inline bidder list is full, as for place_bid
+    #[account(has_one = auction)]
+    pub bid_page: Option<AccountLoader<'info, BidPage>>,
+    // The bidder's receipt for this listing, if they've bid on it before
+    #[account(
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid_receipt.bump
+    )]
+    pub bid_receipt: Option<Account<'info, BidReceipt>>,
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [COLLECTION_FEE_SEED, collection_fee_config.collection.as_ref()],
+        bump = collection_fee_config.bump
+    )]
+    pub collection_fee_config: Option<Account<'info, CollectionFeeConfig>>,
+    #[account(
+        seeds = [MEMBERSHIP_DISCOUNT_SEED, membership_discount.collection.as_ref()],
+        bump = membership_discount.bump
+    )]
+    pub membership_discount: Option<Account<'info, MembershipDiscount>>,
+    pub membership_token_account: Option<Account<'info, TokenAccount>>,
+    /// CHECK: the membership NFT's Token Metadata account, checked against its mint when read
+    pub membership_metadata: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the listing's feed by `oracle::load_price`
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+    pub feature_flags: Option<Account<'info, FeatureFlags>>,
+}
+
+// What place_bid would make of a plain bid of `amount`; `proof` as for place_bid
+pub fn validate_bid(
+    ctx: Context<ValidateBid>,
+    _listing_id: [u8; 32],
+    amount: u64,
+    proof: Vec<[u8; 32]>
+) -> Result<BidValidation> {
+    let mut auction = AuctionDetails::clone(&ctx.accounts.auction);
+    let mut outcome = BidValidation {
+        valid: false,
+        error_code: None,
+        min_next_bid: auction.min_next_bid()?,
+        fee: 0,
+        credited: 0,
+        position: 0,
+        leading: false,
+        highest_bid: auction.highest_bid,
+        end_time: auction.end_time,
+    };
+    match dry_run(ctx.accounts, &mut auction, amount, &proof, &mut outcome) {
+        Ok(()) => {
+            outcome.valid = true;
+        }
+        Err(Error::AnchorError(error)) => {
+            outcome.error_code = Some(error.error_code_number);
+        }
+        Err(error) => {
+            return Err(error);
+        }
+    }
+    Ok(outcome)
+}
+
+// The checks of place and apply_bid, in their order, filling in `outcome` as
+// its fields become known
+fn dry_run(
+    accounts: &ValidateBid,
+    auction: &mut AuctionDetails,
+    amount: u64,
+    proof: &[[u8; 32]],
+    outcome: &mut BidValidation
+) -> Result<()> {
+    let bidder = accounts.bidder.key();
+    require!(amount > 0, ErrorCode::MinimumBidError);
+    ensure_not_blacklisted(&accounts.blacklist_entry)?;
+    if auction.payment_mint.is_some() {
+        let flags = accounts.feature_flags.as_ref().ok_or(ErrorCode::FeatureDisabled)?;
+        flags.ensure_enabled(FeatureFlags::TOKEN_BIDS)?;
+    }
+
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    if let Some(pricing) = auction.usd_pricing {
+        let feed = accounts.price_feed.as_ref().ok_or(ErrorCode::OracleFeedMismatch)?;
+        let price = oracle::load_price(feed, &pricing.feed, OracleLimits::default(), &clock)?;
+        auction.reprice(&price, now)?;
+        outcome.min_next_bid = auction.min_next_bid()?;
+    }
+
+    let fee_discount_bps = membership::discount_bps(
+        accounts.membership_discount.as_deref(),
+        &bidder,
+        accounts.membership_token_account.as_deref(),
+        accounts.membership_metadata.as_ref().map(|metadata| metadata.as_ref())
+    )?;
+    let buyer_fee_bps = accounts.auction_state.buyer_fee_bps_for(
+        auction,
+        accounts.collection_fee_config.as_deref(),
+        amount
+    );
+    let fee = math::compute_fee(amount, buyer_fee_bps)?;
+    outcome.fee = fee.try_sub(math::compute_fee(fee, fee_discount_bps)?)?;
+    outcome.credited = amount.try_sub(outcome.fee)?;
+
+    require!(!auction.is_alien, ErrorCode::ContributionsOnly);
+    require!(auction.ticket_price == 0, ErrorCode::TicketsOnly);
+    require!(!auction.delegated_bidding, ErrorCode::DelegatedBidsOnly);
+    require!(bidder != auction.owner, ErrorCode::BidderIsOwner);
+    allowlist::check(auction, &bidder, proof)?;
+    token_gate::check(auction, &bidder, accounts.gate_token_account.as_deref())?;
+    match auction.status {
+        AuctionStatus::Live => {}
+        AuctionStatus::Paused => {
+            return Err(ErrorCode::AuctionPaused.into());
+        }
+        _ => {
+            return Err(ErrorCode::AuctionEnded.into());
+        }
+    }
+    require!(now <= auction.end_time, ErrorCode::AuctionEnded);
+    if let Some(receipt) = accounts.bid_receipt.as_ref() {
+        receipt.check_bid_limits(auction, now)?;
+    }
+
+    // The bidder's position so far, wherever record_bid would add to it
+    require!(auction.position_tree.is_none(), ErrorCode::CompressedPositionsRequired);
+    let held = match auction.bid_of(&bidder) {
+        Some(bid) => bid.amount,
+        None if auction.bids.len() < AuctionDetails::MAX_INLINE_BIDDERS => 0,
+        None => {
+            let page = accounts.bid_page.as_ref().ok_or(ErrorCode::BidPageRequired)?.load()?;
+            match page.find_bidder(&bidder) {
+                Ok(index) => page.slots[index].amount,
+                Err(_) => {
+                    require!(page.index + 1 == auction.bid_pages, ErrorCode::BidPageRequired);
+                    require!(!page.is_full(), ErrorCode::BidPageFull);
+                    0
+                }
+            }
+        }
+    };
+    outcome.position = held.try_add(outcome.credited)?;
+
+    extend_if_sniped(&accounts.auction_state, auction, now)?;
+    outcome.end_time = auction.end_time;
+    auction.resolve_bid(bidder, outcome.position, false)?;
+    outcome.leading = auction.is_winner(&bidder);
+    outcome.highest_bid = auction.highest_bid;
+    Ok(())
+}
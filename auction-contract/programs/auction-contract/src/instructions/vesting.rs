@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::{ ProceedsVestingSet, VestedProceedsClaimed };
+use crate::invariants::assert_funds_conserved;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::vault;
+use crate::{ AUCTION_SEED, VAULT_SEED };
+
+// Vested proceeds. A seller, typically a launchpad running a primary sale,
+// can have the proceeds stream to them over time instead of all at once:
+// `claim_proceeds` then pays fees, royalties and the rest as usual but keeps
+// the seller's share in escrow, and `claim_vested` pays out what has vested
+// linearly since, as often as the seller likes.
+//
+// Sales in SOL paying the seller alone; set before the first bid, so bidders
+// know the terms.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct SetProceedsVesting<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct ClaimVested<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Stream the proceeds over `duration` seconds from settlement; 0 pays them at once
+pub fn set_proceeds_vesting(
+    ctx: Context<SetProceedsVesting>,
+    listing_id: [u8; 32],
+    duration: i64
+) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.status.is_closed(), ErrorCode::AuctionAlreadyEnded);
+    require!(!auction.has_bids(), ErrorCode::BiddingStarted);
+    require!(
+        auction.payment_mint.is_none() &&
+            auction.payout_splits.is_empty() &&
+            (0..=ProceedsVesting::MAX_DURATION).contains(&duration),
+        ErrorCode::InvalidVesting
+    );
+    auction.proceeds_vesting = if duration == 0 {
+        None
+    } else {
+        Some(ProceedsVesting { duration, start: 0, total: 0, claimed: 0 })
+    };
+
+    emit!(ProceedsVestingSet { listing_id, duration });
+    Ok(())
+}
+
+// Pay the seller what has vested and they haven't claimed yet
+pub fn claim_vested(ctx: Context<ClaimVested>, listing_id: [u8; 32]) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(auction.status == AuctionStatus::Settled, ErrorCode::AuctionNotEnded);
+    let mut vesting = auction.proceeds_vesting.ok_or(ErrorCode::InvalidVesting)?;
+    let now = Clock::get()?.unix_timestamp;
+    let amount = vesting.vested(now).try_sub(vesting.claimed)?;
+    require!(amount > 0, ErrorCode::NothingToWithdraw);
+
+    vesting.claimed = vesting.claimed.try_add(amount)?;
+    auction.proceeds_vesting = Some(vesting);
+    vault::pay_out(
+        &ctx.accounts.vault.to_account_info(),
+        &listing_id,
+        auction.vault_bump,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        amount
+    )?;
+    assert_funds_conserved(&ctx.accounts.vault.to_account_info(), auction)?;
+
+    emit!(VestedProceedsClaimed { listing_id, amount, remaining: vesting.unclaimed() });
+    Ok(())
+}
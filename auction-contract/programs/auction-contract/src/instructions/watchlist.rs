@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::WatchReminder;
+use crate::state::*;
+use crate::{ AUCTION_SEED, WATCHLIST_SEED };
+
+#[derive(Accounts)]
+pub struct InitWatchlist<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = Watchlist::SPACE,
+        seeds = [WATCHLIST_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub watchlist: Account<'info, Watchlist>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct Watch<'info> {
+    // Only existing auctions can be watched
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(
+        mut,
+        seeds = [WATCHLIST_SEED, owner.key().as_ref()],
+        bump = watchlist.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub watchlist: Account<'info, Watchlist>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Unwatch<'info> {
+    #[account(
+        mut,
+        seeds = [WATCHLIST_SEED, owner.key().as_ref()],
+        bump = watchlist.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub watchlist: Account<'info, Watchlist>,
+    pub owner: Signer<'info>,
+}
+
+// Watchlists to check are passed as writable remaining accounts
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct Heartbeat<'info> {
+    #[account(seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+}
+
+pub fn init_watchlist(ctx: Context<InitWatchlist>) -> Result<()> {
+    let watchlist = &mut ctx.accounts.watchlist;
+    watchlist.owner = ctx.accounts.owner.key();
+    watchlist.entries = vec![];
+    watchlist.version = Watchlist::VERSION;
+    watchlist.bump = ctx.bumps.watchlist;
+    Ok(())
+}
+
+pub fn watch(ctx: Context<Watch>, listing_id: [u8; 32]) -> Result<()> {
+    let watchlist = &mut ctx.accounts.watchlist;
+    if !watchlist.entries.iter().any(|entry| entry.listing_id == listing_id) {
+        require!(watchlist.entries.len() < Watchlist::CAPACITY, ErrorCode::WatchlistFull);
+        watchlist.entries.push(WatchEntry { listing_id, reminded: false });
+    }
+    Ok(())
+}
+
+pub fn unwatch(ctx: Context<Unwatch>, listing_id: [u8; 32]) -> Result<()> {
+    ctx.accounts.watchlist.entries.retain(|entry| entry.listing_id != listing_id);
+    Ok(())
+}
+
+// Remind each watchlist owner watching the auction once, when it is live and
+// within REMINDER_WINDOW of its end
+pub fn heartbeat<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Heartbeat<'info>>,
+    listing_id: [u8; 32]
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let auction = &ctx.accounts.auction;
+    let ending_soon = (0..=Watchlist::REMINDER_WINDOW).contains(&(auction.end_time - now));
+    if auction.status != AuctionStatus::Live || !ending_soon {
+        return Ok(());
+    }
+
+    for account in ctx.remaining_accounts {
+        let mut watchlist = Account::<Watchlist>::try_from(account)?;
+        let owner = watchlist.owner;
+        let entry = watchlist.entries
+            .iter_mut()
+            .find(|entry| entry.listing_id == listing_id && !entry.reminded);
+        if let Some(entry) = entry {
+            entry.reminded = true;
+            emit!(WatchReminder {
+                owner,
+                listing_id,
+                end_time: auction.end_time,
+            });
+        }
+        watchlist.exit(&crate::ID)?;
+    }
+    Ok(())
+}
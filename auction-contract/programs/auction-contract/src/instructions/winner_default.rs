@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::events::WinnerDefaulted;
+use crate::instructions::bid_bond;
+use crate::state::*;
+use crate::{ AUCTION_SEED, BID_RECEIPT_SEED, VAULT_SEED };
+
+// Winner defaults. A winner who hasn't taken delivery within
+// AuctionDetails::BOND_CLAIM_WINDOW of the close, and before the seller claimed
+// the proceeds, can be declared in default by the seller. Their bid bond, if
+// any, goes to the seller, and the sale goes to the best runner-up still
+// backing their offer in escrow, at that offer, with a fresh claim window. The
+// auction keeps a short ladder of runners-up for this as bids come in. With no
+// runner-up left, the auction reopens for bids as if newly listed.
+//
+// The defaulter is no longer the winner, so they withdraw their bid as any
+// outbid bidder would. Single sales only: not editions, raffles or crowd sales,
+// nor installment sales, which default on a missed installment instead.
+
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32])]
+pub struct DefaultWinner<'info> {
+    #[account(
+        mut,
+        seeds = [AUCTION_SEED, listing_id.as_ref()],
+        bump = auction.bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    // The defaulting winner's receipt; required while the listing takes bid bonds
+    #[account(
+        mut,
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), auction.highest_bidder.as_ref()],
+        bump = winner_bid_receipt.bump
+    )]
+    pub winner_bid_receipt: Option<Account<'info, BidReceipt>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn default_winner(ctx: Context<DefaultWinner>, listing_id: [u8; 32]) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    let defaulter = auction.highest_bidder;
+    require!(
+        auction.is_disputable() &&
+            auction.status == AuctionStatus::Ended &&
+            auction.installment_plan.is_none() &&
+            !auction.has_taken_delivery(&defaulter),
+        ErrorCode::DefaultNotAllowed
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let deadline = auction.closed_at.saturating_add(AuctionDetails::BOND_CLAIM_WINDOW);
+    require!(now >= deadline, ErrorCode::ClaimWindowOpen);
+
+    let mut bond_forfeited = 0;
+    if auction.bid_bond > 0 {
+        let receipt = match ctx.accounts.winner_bid_receipt.as_mut() {
+            Some(receipt) => receipt,
+            None => {
+                return err!(ErrorCode::DefaultNotAllowed);
+            }
+        };
+        bond_forfeited = receipt.bond;
+        if bond_forfeited > 0 {
+            bid_bond::release(
+                auction,
+                receipt,
+                &ctx.accounts.vault.to_account_info(),
+                &ctx.accounts.owner.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &listing_id,
+                true
+            )?;
+        }
+    }
+
+    let new_winner = match auction.next_in_line() {
+        Some((bidder, price)) => {
+            // Runners-up passed over no longer back their offers
+            let index = auction.runners_up.iter().position(|entry| entry.bidder == bidder);
+            auction.runners_up.drain(..=index.unwrap_or_default());
+            auction.highest_bidder = bidder;
+            auction.highest_bid = price;
+            auction.highest_max_bid = price;
+            auction.second_bid = auction.runners_up.first().map_or(0, |entry| entry.amount);
+            auction.closed_at = now;
+            Some(bidder)
+        }
+        None => {
+            auction.reopen(now)?;
+            None
+        }
+    };
+
+    emit!(WinnerDefaulted {
+        listing_id,
+        defaulter,
+        new_winner,
+        price: auction.highest_bid,
+        bond_forfeited,
+    });
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{ Mint, TokenAccount, TokenInterface };
+use crate::emitter::{ self, event_authority, EventAuthority };
+use crate::errors::ErrorCode;
+use crate::events::FundsWithdrawn;
+use crate::invariants::assert_funds_conserved;
+use crate::math::CheckedMath;
+use crate::state::*;
+use crate::vault::Escrow;
+use crate::{
+    AUCTION_SEED,
+    AUCTION_STATE_SEED,
+    BID_RECEIPT_SEED,
+    TOKEN_VAULT_SEED,
+    VAULT_SEED,
+    WSOL_UNWRAP_SEED,
+};
+
+#[cfg_attr(feature = "event-cpi", event_cpi)]
+#[derive(Accounts)]
+#[instruction(listing_id: [u8; 32], recipient: Option<Pubkey>)]
+pub struct Withdraw<'info> {
+    // For the retraction penalty, which keeps early exits to retract_bid
+    #[account(seeds = [AUCTION_STATE_SEED], bump = auction_state.bump)]
+    pub auction_state: Account<'info, NftComAuction>,
+    #[account(mut, seeds = [AUCTION_SEED, listing_id.as_ref()], bump = auction.bump)]
+    pub auction: Account<'info, AuctionDetails>,
+    #[account(mut, seeds = [VAULT_SEED, listing_id.as_ref()], bump = auction.vault_bump)]
+    pub vault: SystemAccount<'info>,
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    /// CHECK: pinned to the requested recipient (or the bidder) and must be a system-owned
+    /// wallet; receives the refund itself unless it's paid into a token account
+    #[account(
+        mut,
+        constraint = to.key() == recipient.unwrap_or(bidder.key()) @ ErrorCode::InvalidRecipient,
+        owner = system_program::ID @ ErrorCode::InvalidRecipient
+    )]
+    pub to: UncheckedAccount<'info>,
+    // Required when the bidder's record lives on an overflow page
+    #[account(mut, has_one = auction)]
+    pub bid_page: Option<AccountLoader<'info, BidPage>>,
+    /// CHECK: the bidder's receipt for this listing, updated if they have one
+    #[account(
+        mut,
+        seeds = [BID_RECEIPT_SEED, auction.key().as_ref(), bidder.key().as_ref()],
+        bump
+    )]
+    pub bid_receipt: UncheckedAccount<'info>,
+    // Listings paid in a token only: the listing's token vault, and the
+    // recipient's token account the refund is paid into
+    #[account(mut, seeds = [TOKEN_VAULT_SEED, listing_id.as_ref()], bump)]
+    pub token_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: must be the recipient's token account for the payment mint
+    #[account(mut)]
+    pub to_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+    // Wrapped SOL listings only: the account payouts to wallets are unwrapped
+    // through, and the native mint it's opened for
+    /// CHECK: created and closed again within the instruction by the escrow
+    #[account(mut, seeds = [WSOL_UNWRAP_SEED, listing_id.as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
+
+// Take back `amount` of what the bidder may withdraw, or all of it when
+// `amount` is None. Whatever is left stays on the bid.
+pub fn handler(
+    ctx: Context<Withdraw>,
+    listing_id: [u8; 32],
+    _recipient: Option<Pubkey>,
+    amount: Option<u64>
+) -> Result<()> {
+    let events: EventAuthority = event_authority!(ctx);
+    let auction = &mut ctx.accounts.auction;
+
+    // Ensure auction is not an "alien" auction
+    require!(!auction.is_alien, ErrorCode::AlienAuctionError);
+    // Raffle tickets are only refunded when the draw was cancelled
+    require!(
+        auction.ticket_price == 0 || auction.status == AuctionStatus::Cancelled,
+        ErrorCode::RaffleTicketsLocked
+    );
+
+    // A winner's funds stay in escrow, except that once the sale has settled a
+    // winner gets back whatever they escrowed above the price they won at. A
+    // sale voided by a dispute refunds the winner in full.
+    let bidder = ctx.accounts.bidder.key();
+    let keep = if auction.is_winner(&bidder) && auction.status != AuctionStatus::Voided {
+        require!(auction.status == AuctionStatus::Settled, ErrorCode::HighestBidderCannotWithdraw);
+        auction.winning_price()
+    } else {
+        0
+    };
+    // Bids are binding until the end: with a retraction penalty set, leaving a
+    // live auction early goes through retract_bid, which charges it
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        auction.status != AuctionStatus::Live ||
+            now >= auction.end_time ||
+            ctx.accounts.auction_state.retraction_penalty_bps == 0 ||
+            !auction.allows_retraction(),
+        ErrorCode::RetractionRequired
+    );
+
+    // Anything above `keep` can come out, all at once or a part at a time
+    let withdrawable = |held: u64| -> Result<u64> {
+        let available = held.saturating_sub(keep);
+        let requested = amount.unwrap_or(available);
+        require!(requested <= available, ErrorCode::WithdrawalTooLarge);
+        Ok(requested)
+    };
+
+    // Look the bid up by key, falling back to the overflow page. A proxy
+    // ceiling can't stay above what's still escrowed behind it.
+    let (refund_amount, remaining) = match auction.bid_of_mut(&bidder) {
+        Some(bid) => {
+            let amount = withdrawable(bid.amount)?;
+            bid.amount = bid.amount.try_sub(amount)?;
+            bid.max_bid = bid.max_bid.min(bid.amount);
+            (amount, bid.amount)
+        }
+        None => {
+            let page = ctx.accounts.bid_page.as_ref().ok_or(ErrorCode::NoFundsToWithdraw)?;
+            let mut page = page.load_mut()?;
+            let entry = page.find_mut(&bidder).ok_or(ErrorCode::NoFundsToWithdraw)?;
+            let amount = withdrawable(entry.amount)?;
+            entry.amount = entry.amount.try_sub(amount)?;
+            entry.max_bid = entry.max_bid.min(entry.amount);
+            auction.paged_total = auction.paged_total.try_sub(amount)?;
+            (amount, entry.amount)
+        }
+    };
+
+    // Ensure the refund amount is greater than 0
+    require!(refund_amount > 0, ErrorCode::NoFundsToWithdraw);
+
+    // Refund out of escrow; the `to` account has already been checked against the recipient
+    let escrow = Escrow::new(
+        auction,
+        &listing_id,
+        ctx.accounts.vault.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_vault.as_ref(),
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref()
+    )?.unwrapping(
+        ctx.accounts.unwrap_account.as_ref(),
+        ctx.accounts.native_mint.as_ref(),
+        ctx.accounts.bidder.to_account_info()
+    );
+    let to_token_account = ctx.accounts.to_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let to = escrow.account_of(&ctx.accounts.to.to_account_info(), to_token_account.as_ref())?;
+    escrow.pay(&to, refund_amount)?;
+    escrow.settle()?;
+    assert_funds_conserved(escrow.holding(), auction)?;
+    BidReceipt::record_refund(&ctx.accounts.bid_receipt, refund_amount)?;
+
+    emitter::emit(&events, FundsWithdrawn {
+        listing_id,
+        bidder,
+        recipient: ctx.accounts.to.key(),
+        amount: refund_amount,
+        remaining,
+    })?;
+    Ok(())
+}
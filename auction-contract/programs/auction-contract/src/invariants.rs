@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::AuctionDetails;
+
+// Accounting check run after every instruction that moves bid funds. It only
+// compiles in with the `invariants` feature so production builds don't pay for
+// the walk over the auction's recorded positions.
+//
+// Surplus lamports are tolerated (anyone can transfer into the escrow); a
+// shortfall means the books have drifted from the funds actually held. A
+// listing paid in a token is checked against its token vault's balance.
+#[cfg(feature = "invariants")]
+pub fn assert_funds_conserved(escrow: &AccountInfo, auction: &AuctionDetails) -> Result<()> {
+    use crate::errors::ErrorCode;
+
+    let held = escrow_held(escrow, auction)?;
+    let owed = auction.outstanding()?;
+    if held < owed {
+        msg!("Escrow holds {} but {} are owed", held, owed);
+        return err!(ErrorCode::FundsNotConserved);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "invariants"))]
+#[inline(always)]
+pub fn assert_funds_conserved(_escrow: &AccountInfo, _auction: &AuctionDetails) -> Result<()> {
+    Ok(())
+}
+
+// What `escrow` holds for the auction, above the rent a SOL vault has to keep
+pub fn escrow_held(escrow: &AccountInfo, auction: &AuctionDetails) -> Result<u64> {
+    if auction.payment_mint.is_some() {
+        return crate::token_payments::token_balance(escrow);
+    }
+    let rent_floor = Rent::get()?.minimum_balance(escrow.data_len());
+    Ok(escrow.lamports().saturating_sub(rent_floor))
+}
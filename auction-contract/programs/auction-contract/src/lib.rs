@@ -1,21 +1,248 @@
 use anchor_lang::prelude::*;
-use std::collections::HashMap;
+pub mod allowlist;
+pub mod cnft;
+pub mod compressed_positions;
+pub mod cpi_guard;
+pub mod emitter;
+pub mod errors;
+pub mod escrow_adapter;
+pub mod events;
+pub mod hooks;
+pub mod instructions;
+pub mod invariants;
+pub mod math;
+pub mod migrations;
+pub mod minting;
+pub mod oracle;
+pub mod payment_processor;
+pub mod randomness;
+pub mod realms;
+pub mod royalties;
+pub mod settlement;
+pub mod signed_orders;
+pub mod state;
+pub mod token_gate;
+pub mod token_payments;
 pub mod utils; // Declare the module
-use crate::utils::generate_metadata;
+pub mod vault;
+pub mod wormhole;
+
+pub use instructions::*;
+pub use compressed_positions::CompressedPosition;
+pub use state::*;
 
 declare_id!("D22VCwbJ1F6FhaPgaeVSvDPNH28SCjzZrWZginAwByut");
 
+// Seed of the global state PDA
+#[constant]
+pub const AUCTION_STATE_SEED: &[u8] = b"auction_state";
+
+// Seed prefix of per-auction accounts: [AUCTION_SEED, listing_id]
+#[constant]
+pub const AUCTION_SEED: &[u8] = b"auction";
+
+// Seed prefix of per-auction vaults holding the bid funds: [VAULT_SEED, listing_id]
+#[constant]
+pub const VAULT_SEED: &[u8] = b"vault";
+
+// Seed prefix of escrow token accounts holding listed NFTs: [NFT_ESCROW_SEED, mint]
+#[constant]
+pub const NFT_ESCROW_SEED: &[u8] = b"nft_escrow";
+
+// Seed prefix of NFTs minted at settlement: [NFT_MINT_SEED, listing_id]
+#[constant]
+pub const NFT_MINT_SEED: &[u8] = b"nft_mint";
+
+// Seed prefix of overflow bid pages: [BID_PAGE_SEED, listing_id, page index]
+#[constant]
+pub const BID_PAGE_SEED: &[u8] = b"bid_page";
+
+// Seed prefix of per-bidder profiles: [BIDDER_PROFILE_SEED, bidder]
+#[constant]
+pub const BIDDER_PROFILE_SEED: &[u8] = b"bidder_profile";
+
+// Seed prefix of per-seller profiles: [SELLER_PROFILE_SEED, seller]
+#[constant]
+pub const SELLER_PROFILE_SEED: &[u8] = b"seller_profile";
+
+// Seed prefix of per-user watchlists: [WATCHLIST_SEED, owner]
+#[constant]
+pub const WATCHLIST_SEED: &[u8] = b"watchlist";
+
+// Seed of the system-owned vault holding liquidity for bridged bids
+#[constant]
+pub const BRIDGE_VAULT_SEED: &[u8] = b"bridge_vault";
+
+// Seed prefix of replay markers for consumed VAAs: [CONSUMED_VAA_SEED, posted vaa]
+#[constant]
+pub const CONSUMED_VAA_SEED: &[u8] = b"consumed_vaa";
+
+// Seed prefix of collection registry entries: [COLLECTION_SEED, collection]
+#[constant]
+pub const COLLECTION_SEED: &[u8] = b"collection";
+
+// Seed prefix of protocol fee treasuries: [FEE_TREASURY_SEED, payment mint]
+#[constant]
+pub const FEE_TREASURY_SEED: &[u8] = b"fee_treasury";
+
+// Seed prefix of blacklist flags: [BLACKLIST_SEED, address]
+#[constant]
+pub const BLACKLIST_SEED: &[u8] = b"blacklist";
+
+// Seed prefix of per-collection fee configs: [COLLECTION_FEE_SEED, collection]
+#[constant]
+pub const COLLECTION_FEE_SEED: &[u8] = b"collection_fee";
+
+// Seed prefix of queued governance parameter changes: [PARAM_CHANGE_SEED, id]
+#[constant]
+pub const PARAM_CHANGE_SEED: &[u8] = b"param_change";
+
+// Seed prefix of per-bidder bid receipts: [BID_RECEIPT_SEED, auction, bidder]
+#[constant]
+pub const BID_RECEIPT_SEED: &[u8] = b"bid_receipt";
+
+// Seed prefix of per-NFT sale histories: [PROVENANCE_SEED, mint]
+#[constant]
+pub const PROVENANCE_SEED: &[u8] = b"provenance";
+
+// Seed prefix of fixed-price listings: [FIXED_PRICE_SEED, listing_id]
+#[constant]
+pub const FIXED_PRICE_SEED: &[u8] = b"fixed_price";
+
+// Seed prefix of standing offers: [OFFER_SEED, mint, buyer]
+#[constant]
+pub const OFFER_SEED: &[u8] = b"offer";
+
+// Seed prefix of crowd auction share mints: [CROWD_SHARE_SEED, listing_id]
+#[constant]
+pub const CROWD_SHARE_SEED: &[u8] = b"crowd_share";
+
+// Seed prefix of the token vaults of listings paid in an SPL token: [TOKEN_VAULT_SEED, listing_id]
+#[constant]
+pub const TOKEN_VAULT_SEED: &[u8] = b"token_vault";
+
+// Seed prefix of wrapped SOL listings' payout unwrap accounts: [WSOL_UNWRAP_SEED, listing_id]
+#[constant]
+pub const WSOL_UNWRAP_SEED: &[u8] = b"wsol_unwrap";
+
+// Seed prefix of per-user loyalty point accounts: [LOYALTY_SEED, owner]
+#[constant]
+pub const LOYALTY_SEED: &[u8] = b"loyalty";
+
+// Seed of the singleton account holding the admin's delegated roles
+#[constant]
+pub const ROLES_SEED: &[u8] = b"roles";
+
+// Seed of the singleton account of feature flags for staged rollouts
+#[constant]
+pub const FEATURE_FLAGS_SEED: &[u8] = b"feature_flags";
+
+// Seed prefix of sellers' listing templates: [AUCTION_TEMPLATE_SEED, owner, template_id]
+#[constant]
+pub const AUCTION_TEMPLATE_SEED: &[u8] = b"auction_template";
+
+// Seed of the singleton Merkle archive of pruned auctions
+#[constant]
+pub const ARCHIVE_SEED: &[u8] = b"archive";
+
+// Seed prefix of per-collection sale price stats: [COLLECTION_STATS_SEED, collection]
+#[constant]
+pub const COLLECTION_STATS_SEED: &[u8] = b"collection_stats";
+
+// Seed prefix of per-collection floor price oracles: [FLOOR_ORACLE_SEED, collection]
+#[constant]
+pub const FLOOR_ORACLE_SEED: &[u8] = b"floor_oracle";
+
+// Seed prefix of membership fee discounts: [MEMBERSHIP_DISCOUNT_SEED, collection]
+#[constant]
+pub const MEMBERSHIP_DISCOUNT_SEED: &[u8] = b"membership_discount";
+
+// Seed of the auction registry's head, which counts its pages
+#[constant]
+pub const AUCTION_REGISTRY_SEED: &[u8] = b"auction_registry";
+
+// Seed prefix of auction registry pages: [REGISTRY_PAGE_SEED, index]
+#[constant]
+pub const REGISTRY_PAGE_SEED: &[u8] = b"registry_page";
+
+// Seed prefix of settlement step receipts: [SETTLEMENT_RECEIPT_SEED, auction, step, party]
+#[constant]
+pub const SETTLEMENT_RECEIPT_SEED: &[u8] = b"settlement_receipt";
+
+// Address of the privileged action log: [AUDIT_LOG_SEED]
+#[constant]
+pub const AUDIT_LOG_SEED: &[u8] = b"audit_log";
+
+// Seed prefix of co-owners' listing approvals: [CO_SELLER_APPROVAL_SEED, listing_id, co_seller]
+#[constant]
+pub const CO_SELLER_APPROVAL_SEED: &[u8] = b"co_seller_approval";
+
+// Upper bound on entries returned by list queries, keeps them within a fixed compute budget
+pub const MAX_QUERY_RESULTS: usize = 50;
+
 #[program]
 pub mod nft_com_auction {
     use super::*;
 
+    // Create the global state; the signer becomes the admin
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        fee_recipient: Pubkey,
+        nft_contract: Pubkey,
+        buyer_fee_bps: u64,
+        seller_fee_bps: u64,
+        defaults: AuctionDefaults
+    ) -> Result<()> {
+        initialize::handler(ctx, fee_recipient, nft_contract, buyer_fee_bps, seller_fee_bps, defaults)
+    }
+
     pub fn change_fee_recipient(
         ctx: Context<ChangeFeeRecipient>,
         new_fee_recipient: Pubkey
     ) -> Result<()> {
-        let auction_state = &mut ctx.accounts.auction_state;
-        auction_state.fee_recipient = new_fee_recipient;
-        Ok(())
+        admin::change_fee_recipient(ctx, new_fee_recipient)
+    }
+
+    // Propose a new admin authority, such as a multisig vault; None withdraws the proposal
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        new_authority: Option<Pubkey>
+    ) -> Result<()> {
+        admin::propose_authority(ctx, new_authority)
+    }
+
+    // The proposed authority takes over
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        admin::accept_authority(ctx)
+    }
+
+    // Allow an aggregator program to call place_bid via CPI
+    pub fn add_aggregator_program(
+        ctx: Context<ManageAggregatorPrograms>,
+        program: Pubkey
+    ) -> Result<()> {
+        admin::add_aggregator_program(ctx, program)
+    }
+
+    pub fn remove_aggregator_program(
+        ctx: Context<ManageAggregatorPrograms>,
+        program: Pubkey
+    ) -> Result<()> {
+        admin::remove_aggregator_program(ctx, program)
+    }
+
+    // Allow a swap program to be used by place_bid_with_swap
+    pub fn add_swap_program(ctx: Context<ManageSwapPrograms>, program: Pubkey) -> Result<()> {
+        admin::add_swap_program(ctx, program)
+    }
+
+    pub fn remove_swap_program(ctx: Context<ManageSwapPrograms>, program: Pubkey) -> Result<()> {
+        admin::remove_swap_program(ctx, program)
+    }
+
+    // Permanently lock the fees, fee recipient and NFT contract; pausing still works
+    pub fn freeze_config(ctx: Context<FreezeConfig>) -> Result<()> {
+        admin::freeze_config(ctx)
     }
 
     // Change the NFT contract address
@@ -23,724 +250,1306 @@ pub mod nft_com_auction {
         ctx: Context<ChangeNFTContract>,
         new_nft_contract: Pubkey
     ) -> Result<()> {
-        let auction_state = &mut ctx.accounts.auction_state;
-        auction_state.nft_contract = new_nft_contract;
-        Ok(())
+        admin::change_nft_contract(ctx, new_nft_contract)
+    }
+
+    // Set the defaults new listings fall back to
+    pub fn set_auction_defaults(
+        ctx: Context<SetAuctionDefaults>,
+        defaults: AuctionDefaults
+    ) -> Result<()> {
+        admin::set_auction_defaults(ctx, defaults)
+    }
+
+    // Set the anti-sniping window and extension for new listings, and the cap on extensions
+    pub fn set_antisniping_config(
+        ctx: Context<SetAntisnipingConfig>,
+        sniping_time_window: i64,
+        time_extension: i64,
+        max_extensions: u32
+    ) -> Result<()> {
+        admin::set_antisniping_config(ctx, sniping_time_window, time_extension, max_extensions)
+    }
+
+    // Set (or clear) the keeper allowed to run seller-side maintenance
+    pub fn set_automation_authority(
+        ctx: Context<SetAutomationAuthority>,
+        automation_authority: Option<Pubkey>
+    ) -> Result<()> {
+        admin::set_automation_authority(ctx, automation_authority)
+    }
+
+    // Set (or clear) the fee manager allowed to negotiate per-auction fees
+    pub fn set_fee_manager(ctx: Context<SetFeeManager>, fee_manager: Option<Pubkey>) -> Result<()> {
+        admin::set_fee_manager(ctx, fee_manager)
+    }
+
+    pub fn init_fee_treasury(ctx: Context<InitFeeTreasury>, mint: Pubkey) -> Result<()> {
+        fee_treasury::init_fee_treasury(ctx, mint)
+    }
+
+    // Start recording privileged actions in the audit log; admin only
+    pub fn init_audit_log(ctx: Context<InitAuditLog>) -> Result<()> {
+        audit_log::init_audit_log(ctx)
+    }
+
+    // Sweep what a listing's escrow holds beyond what it owes into the fee treasury
+    pub fn reconcile_vault(ctx: Context<ReconcileVault>, listing_id: [u8; 32]) -> Result<()> {
+        reconcile::reconcile_vault(ctx, listing_id)
+    }
+
+    // Move accumulated protocol fees out to the fee recipient (admin, fee manager or governance)
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        fee_treasury::withdraw_fees(ctx, amount)
+    }
+
+    // Same for the fees collected on listings paid in an SPL token
+    pub fn withdraw_token_fees(ctx: Context<WithdrawTokenFees>, amount: u64) -> Result<()> {
+        fee_treasury::withdraw_token_fees(ctx, amount)
+    }
+
+    // Set the lamports end_auction pays its caller out of the buyer fees
+    pub fn set_referral_share(
+        ctx: Context<SetReferralShare>,
+        referral_share_bps: u64
+    ) -> Result<()> {
+        admin::set_referral_share(ctx, referral_share_bps)
+    }
+
+    pub fn set_keeper_tip(ctx: Context<SetKeeperTip>, keeper_tip: u64) -> Result<()> {
+        admin::set_keeper_tip(ctx, keeper_tip)
+    }
+
+    // Cap the listings each seller may have open at once; 0 for no cap
+    pub fn set_max_active_auctions_per_seller(
+        ctx: Context<SetMaxActiveAuctions>,
+        max_active_auctions: u32
+    ) -> Result<()> {
+        admin::set_max_active_auctions_per_seller(ctx, max_active_auctions)
+    }
+
+    // Longest a seller may extend an auction to, counted from its listing; 0 for no limit
+    pub fn set_max_auction_duration(
+        ctx: Context<SetMaxAuctionDuration>,
+        max_auction_duration: i64
+    ) -> Result<()> {
+        admin::set_max_auction_duration(ctx, max_auction_duration)
+    }
+
+    // Set how many loyalty points bids and purchases earn, and when
+    pub fn set_loyalty_schedule(
+        ctx: Context<SetLoyaltySchedule>,
+        schedule: LoyaltySchedule
+    ) -> Result<()> {
+        admin::set_loyalty_schedule(ctx, schedule)
+    }
+
+    // Lengthen the wait queued config changes go through; shortening it is queued itself
+    pub fn set_config_change_delay(
+        ctx: Context<SetConfigChangeDelay>,
+        config_change_delay: i64
+    ) -> Result<()> {
+        admin::set_config_change_delay(ctx, config_change_delay)
+    }
+
+    // Hold sales in escrow for `dispute_window` seconds after they close; 0 for no hold
+    pub fn set_dispute_config(
+        ctx: Context<SetDisputeConfig>,
+        dispute_window: i64,
+        arbiter: Option<Pubkey>
+    ) -> Result<()> {
+        admin::set_dispute_config(ctx, dispute_window, arbiter)
+    }
+
+    // Override one listing's buyer and seller fees (basis points) before it gets its first bid
+    pub fn set_auction_fee_override(
+        ctx: Context<SetAuctionFeeOverride>,
+        listing_id: [u8; 32],
+        buyer_fee_bps: u64,
+        seller_fee_bps: u64
+    ) -> Result<()> {
+        admin::set_auction_fee_override(ctx, listing_id, buyer_fee_bps, seller_fee_bps)
+    }
+
+    // Configure how long finished auctions are kept and where pruned rent goes
+    pub fn set_archive_policy(
+        ctx: Context<SetArchivePolicy>,
+        retention: i64,
+        rent_destination: Pubkey
+    ) -> Result<()> {
+        admin::set_archive_policy(ctx, retention, rent_destination)
     }
 
     // Set buyer and seller fees
-    pub fn set_fees(ctx: Context<SetFees>, buyer_fee: u64, seller_fee: u64) -> Result<()> {
-        let auction_state = &mut ctx.accounts.auction_state;
-        auction_state.buyer_fee = buyer_fee;
-        auction_state.seller_fee = seller_fee;
-        Ok(())
+    pub fn set_fees(ctx: Context<SetFees>, buyer_fee_bps: u64, seller_fee_bps: u64) -> Result<()> {
+        admin::set_fees(ctx, buyer_fee_bps, seller_fee_bps)
+    }
+
+    // Charge buyers by tier of bid size instead of the flat buyer fee; empty goes back to it
+    pub fn set_buyer_fee_tiers(
+        ctx: Context<SetBuyerFeeTiers>,
+        tiers: Vec<BuyerFeeTier>
+    ) -> Result<()> {
+        admin::set_buyer_fee_tiers(ctx, tiers)
+    }
+
+    // Set the penalty bidders pay to retract a bid, and whether the seller gets it
+    pub fn set_retraction_penalty(
+        ctx: Context<SetRetractionPenalty>,
+        penalty_bps: u64,
+        to_seller: bool
+    ) -> Result<()> {
+        retract_bid::set_retraction_penalty(ctx, penalty_bps, to_seller)
+    }
+
+    // Make creator royalties mandatory or optional at settlement
+    pub fn set_royalty_enforcement(
+        ctx: Context<SetRoyaltyEnforcement>,
+        enforced: bool
+    ) -> Result<()> {
+        admin::set_royalty_enforcement(ctx, enforced)
+    }
+
+    // Restrict listings to approved collections, or open them to any NFT
+    pub fn set_curated_listings(ctx: Context<SetCuratedListings>, curated: bool) -> Result<()> {
+        admin::set_curated_listings(ctx, curated)
+    }
+
+    // Choose the Bubblegum tree participation receipts are minted into
+    pub fn set_receipt_tree(
+        ctx: Context<SetReceiptTree>,
+        merkle_tree: Option<Pubkey>
+    ) -> Result<()> {
+        admin::set_receipt_tree(ctx, merkle_tree)
+    }
+
+    // Hand control of fees, feature flags and the fee recipient to a governance account
+    pub fn set_governance(ctx: Context<SetGovernance>, governance: Pubkey) -> Result<()> {
+        governance::set_governance(ctx, governance)
+    }
+
+    // Queue a change to fees, feature flags or the fee recipient behind the config change delay
+    pub fn queue_config_change(ctx: Context<QueueConfigChange>, change: ParamChange) -> Result<()> {
+        governance::queue_config_change(ctx, change)
+    }
+
+    // Apply a queued change once its waiting period is over; anyone may crank this
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        governance::execute_config_change(ctx)
+    }
+
+    // Set buyer and seller fees from an executing governance proposal
+    pub fn governance_set_fees(
+        ctx: Context<GovernanceSetFees>,
+        buyer_fee_bps: u64,
+        seller_fee_bps: u64
+    ) -> Result<()> {
+        governance::governance_set_fees(ctx, buyer_fee_bps, seller_fee_bps)
+    }
+
+    // Approve a collection for listing, with optional fee overrides and permissions
+    pub fn register_collection(
+        ctx: Context<RegisterCollection>,
+        collection: Pubkey,
+        fee_override: Option<FeeOverride>,
+        permissions: u64
+    ) -> Result<()> {
+        collection_registry::register_collection(ctx, collection, fee_override, permissions)
+    }
+
+    pub fn update_collection(
+        ctx: Context<UpdateCollection>,
+        enabled: bool,
+        fee_override: Option<FeeOverride>,
+        permissions: u64
+    ) -> Result<()> {
+        collection_registry::update_collection(ctx, enabled, fee_override, permissions)
+    }
+
+    // Open the sale price stats of a collection registered before stats were kept
+    pub fn init_collection_stats(ctx: Context<InitCollectionStats>) -> Result<()> {
+        collection_stats::init_collection_stats(ctx)
+    }
+
+    // Open the floor price oracle of a collection registered before floors were kept
+    pub fn init_floor_oracle(ctx: Context<InitFloorOracle>) -> Result<()> {
+        collection_stats::init_floor_oracle(ctx)
+    }
+
+    // Discounted fees for one collection, e.g. a 0% promotion
+    pub fn create_collection_fee_config(
+        ctx: Context<CreateCollectionFeeConfig>,
+        collection: Pubkey,
+        buyer_fee_bps: u64,
+        seller_fee_bps: u64
+    ) -> Result<()> {
+        collection_fees::create_collection_fee_config(ctx, collection, buyer_fee_bps, seller_fee_bps)
+    }
+
+    pub fn update_collection_fee_config(
+        ctx: Context<UpdateCollectionFeeConfig>,
+        buyer_fee_bps: u64,
+        seller_fee_bps: u64
+    ) -> Result<()> {
+        collection_fees::update_collection_fee_config(ctx, buyer_fee_bps, seller_fee_bps)
+    }
+
+    // Discounted fees for holders of a membership collection's NFTs
+    pub fn create_membership_discount(
+        ctx: Context<CreateMembershipDiscount>,
+        collection: Pubkey,
+        discount_bps: u64
+    ) -> Result<()> {
+        membership::create_membership_discount(ctx, collection, discount_bps)
+    }
+
+    pub fn update_membership_discount(
+        ctx: Context<UpdateMembershipDiscount>,
+        discount_bps: u64
+    ) -> Result<()> {
+        membership::update_membership_discount(ctx, discount_bps)
+    }
+
+    pub fn add_to_blacklist(ctx: Context<AddToBlacklist>, address: Pubkey) -> Result<()> {
+        blacklist::add_to_blacklist(ctx, address)
+    }
+
+    pub fn remove_from_blacklist(ctx: Context<RemoveFromBlacklist>) -> Result<()> {
+        blacklist::remove_from_blacklist(ctx)
     }
 
     // Emergency pause auction
     pub fn emergency_pause_auction(
         ctx: Context<EmergencyPauseAuction>,
-        listing_id: String,
+        listing_id: [u8; 32],
         status: bool
     ) -> Result<()> {
-        let auction_state = &mut ctx.accounts.auction_state;
-        let auction = auction_state.auctions
-            .get_mut(&listing_id)
-            .ok_or(ErrorCode::InvalidListingId)?;
-        auction.paused = status;
-        Ok(())
+        admin::emergency_pause_auction(ctx, listing_id, status)
+    }
+
+    // Pause or resume the seller's own listing while it has no bids
+    pub fn seller_pause_auction(
+        ctx: Context<SellerPauseAuction>,
+        listing_id: [u8; 32],
+        paused: bool
+    ) -> Result<()> {
+        seller_pause::handler(ctx, listing_id, paused)
+    }
+
+    // Pause a batch of one seller's live listings (admin or that seller)
+    pub fn pause_all_of<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PauseAllOf<'info>>,
+        owner: Pubkey
+    ) -> Result<()> {
+        admin::pause_all_of(ctx, owner)
+    }
+
+    // Pause a batch of a collection's live listings (admin only)
+    pub fn pause_collection<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PauseCollection<'info>>,
+        collection: Pubkey
+    ) -> Result<()> {
+        admin::pause_collection(ctx, collection)
     }
 
     // Initialize auction
     pub fn initialize_auction(
         ctx: Context<InitializeAuction>,
-        listing_id: String,
+        listing_id: [u8; 32],
+        minimum: u64,
+        owner: Pubkey,
+        overrides: ListingOverrides,
+        fee_override_bps: Option<FeeOverride>
+    ) -> Result<()> {
+        initialize_auction::handler(ctx, listing_id, minimum, owner, overrides, fee_override_bps)
+    }
+
+    // Deprecated: previous initialize_auction layout, kept for one release cycle
+    pub fn initialize_auction_v1(
+        ctx: Context<InitializeAuction>,
+        listing_id: [u8; 32],
         minimum: u64,
         end_time: i64,
         owner: Pubkey,
         bidder: Option<Pubkey>
     ) -> Result<()> {
-        let auction_state = &mut ctx.accounts.auction_state;
-
-        let bidder = bidder.unwrap_or(ctx.accounts.owner.key());
-
-        if auction_state.auctions.contains_key(&listing_id) {
-            return Err(ErrorCode::InvalidListingId.into());
-        }
-        require!(minimum > 0, ErrorCode::MinimumBidError);
-        require!(end_time > Clock::get().unwrap().unix_timestamp, ErrorCode::EndTimeError);
-
-        let auction = AuctionDetails {
-            listing_id: listing_id.clone(),
-            highest_bid: 0,
-            highest_bidder: Pubkey::default(),
-            bids: std::collections::HashMap::new(), // Initialize bids
-            minimum_bid: minimum,
-            end_time,
-            fees: 0,
-            ended: false,
-            paused: false,
-            is_alien: false,
-            total_amount: 0,
-            owner,
-            bidders: vec![], // Initialize empty list of bidders
-            active_auctions: std::collections::HashMap::new(), // Initialize empty active auctions
-            past_auctions: std::collections::HashMap::new(), // Initialize empty past auctions
-            pending_withdrawals: std::collections::HashMap::new(),
-        };
+        legacy::initialize_auction_v1(ctx, listing_id, minimum, end_time, owner, bidder)
+    }
 
-        auction_state.auctions.insert(listing_id.clone(), auction);
-        auction_state.active_auctions.entry(owner).or_default().push(listing_id.clone());
-        place_bid(ctx, listing_id, bidder)?;
-        emit!(AuctionInitialized { listing_id, minimum, end_time });
-        Ok(())
+    // Store listing terms the seller can list on again by template id
+    pub fn create_auction_template(
+        ctx: Context<CreateAuctionTemplate>,
+        template_id: u16,
+        terms: TemplateTerms
+    ) -> Result<()> {
+        auction_template::create_auction_template(ctx, template_id, terms)
     }
 
-    // Place a bid
-    pub fn place_bid(ctx: Context<PlaceBid>, listing_id: String, bidder: Pubkey) -> Result<()> {
-        let auction_state = &mut ctx.accounts.auction_state;
-        let auction = auction_state.auctions
-            .get_mut(&listing_id)
-            .ok_or(ErrorCode::InvalidListingId)?;
+    pub fn update_auction_template(
+        ctx: Context<UpdateAuctionTemplate>,
+        terms: TemplateTerms
+    ) -> Result<()> {
+        auction_template::update_auction_template(ctx, terms)
+    }
 
-        require!(bidder != auction.owner, ErrorCode::BidderIsOwner);
-        require!(ctx.accounts.owner.key() != auction.owner, ErrorCode::BidderIsOwner);
+    pub fn close_auction_template(ctx: Context<CloseAuctionTemplate>) -> Result<()> {
+        auction_template::close_auction_template(ctx)
+    }
 
-        let fee = (ctx.accounts.bid_amount * auction_state.buyer_fee) / 1000;
-        let bid_amount = ctx.accounts.bid_amount - fee;
+    // List an NFT on one of the seller's templates
+    pub fn initialize_from_template(
+        ctx: Context<InitializeFromTemplate>,
+        listing_id: [u8; 32],
+        name: String
+    ) -> Result<()> {
+        auction_template::initialize_from_template(ctx, listing_id, name)
+    }
 
-        require!(!auction.ended, ErrorCode::AuctionEnded);
-        require!(!auction.paused, ErrorCode::AuctionPaused);
-        require!(Clock::get().unwrap().unix_timestamp <= auction.end_time, ErrorCode::AuctionEnded);
+    // List a Metaplex Core asset, moving it into the program's custody
+    pub fn list_core_asset(
+        ctx: Context<ListCoreAsset>,
+        listing_id: [u8; 32],
+        minimum: u64,
+        overrides: ListingOverrides
+    ) -> Result<()> {
+        core_asset::list_core_asset(ctx, listing_id, minimum, overrides)
+    }
 
-        // Check for sniping protection
-        if
-            Clock::get().unwrap().unix_timestamp >=
-            auction.end_time - auction_state.sniping_time_window
-        {
-            auction.end_time += auction_state.time_extension;
-        }
+    // List a programmable NFT, moving it into the program's escrow through Token Metadata
+    pub fn list_programmable_nft(
+        ctx: Context<ListProgrammableNft>,
+        listing_id: [u8; 32],
+        minimum: u64,
+        overrides: ListingOverrides
+    ) -> Result<()> {
+        programmable_nft::list_programmable_nft(ctx, listing_id, minimum, overrides)
+    }
 
-        auction.total_amount += bid_amount;
+    // List a Bubblegum cNFT, making the program its leaf owner; the leaf's
+    // proof follows as remaining accounts
+    pub fn list_compressed_nft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ListCompressedNft<'info>>,
+        listing_id: [u8; 32],
+        minimum: u64,
+        overrides: ListingOverrides,
+        nft: CompressedNftArgs
+    ) -> Result<()> {
+        compressed_nft::list_compressed_nft(ctx, listing_id, minimum, overrides, nft)
+    }
 
-        // Update highest bid logic
-        // (Similar to the original logic...)
+    // List an NFT that stays delegated to an approved external escrow program
+    pub fn list_external<'info>(
+        ctx: Context<'_, '_, '_, 'info, ListExternal<'info>>,
+        listing_id: [u8; 32],
+        minimum: u64,
+        overrides: ListingOverrides
+    ) -> Result<()> {
+        external_listing::list_external(ctx, listing_id, minimum, overrides)
+    }
 
-        emit!(BidPlaced { listing_id, sender: bidder, value: bid_amount });
-        Ok(())
+    // Have the external escrow deliver a settled listing's NFT; anyone may crank this
+    pub fn release_external<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleaseExternal<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        external_listing::release_external(ctx, listing_id)
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>, listing_id: String, to: Option<Pubkey>) -> Result<()> {
-        let auction_state = &mut ctx.accounts.auction_state;
-        let auction = auction_state.auctions
-            .get_mut(&listing_id)
-            .ok_or(ErrorCode::InvalidListingId)?;
+    pub fn add_escrow_adapter(ctx: Context<ManageEscrowAdapters>, program: Pubkey) -> Result<()> {
+        external_listing::add_escrow_adapter(ctx, program)
+    }
 
-        // Ensure auction is not an "alien" auction
-        require!(!auction.is_alien, ErrorCode::AlienAuctionError);
+    pub fn remove_escrow_adapter(
+        ctx: Context<ManageEscrowAdapters>,
+        program: Pubkey
+    ) -> Result<()> {
+        external_listing::remove_escrow_adapter(ctx, program)
+    }
 
-        // Ensure the caller is not the highest bidder
-        require!(
-            ctx.accounts.bidder.key() != auction.highest_bidder,
-            ErrorCode::HighestBidderCannotWithdraw
-        );
+    // Reprice a listing; only the seller, and only before the first bid
+    pub fn update_minimum_bid(
+        ctx: Context<UpdateMinimumBid>,
+        listing_id: [u8; 32],
+        new_minimum: u64
+    ) -> Result<()> {
+        update_minimum_bid::handler(ctx, listing_id, new_minimum)
+    }
 
-        // Get the refund amount
-        let refund_amount = auction.bidders
-            .iter()
-            .find(|b| b.key == ctx.accounts.bidder.key())
-            .ok_or(ErrorCode::NoFundsToWithdraw)?.amount;
+    // Require a refundable bond from each bidder; only the seller, and only before the first bid
+    pub fn set_bid_bond(ctx: Context<SetBidBond>, listing_id: [u8; 32], bond: u64) -> Result<()> {
+        bid_bond::set_bid_bond(ctx, listing_id, bond)
+    }
 
-        // Ensure the refund amount is greater than 0
-        require!(refund_amount > 0, ErrorCode::NoFundsToWithdraw);
+    // Take a bid bond back once the bidder lost, or won and claimed the NFT
+    pub fn refund_bid_bond(ctx: Context<RefundBidBond>, listing_id: [u8; 32]) -> Result<()> {
+        bid_bond::refund_bid_bond(ctx, listing_id)
+    }
 
-        // Process refund (handle case for `to` address)
-        let recipient = to.unwrap_or(ctx.accounts.bidder.key());
+    // The seller keeps the bond of a winner who never claimed the NFT
+    pub fn forfeit_bid_bond(ctx: Context<ForfeitBidBond>, listing_id: [u8; 32]) -> Result<()> {
+        bid_bond::forfeit_bid_bond(ctx, listing_id)
+    }
 
-        **ctx.accounts.bidder.try_borrow_mut_lamports()? -= refund_amount;
-        **ctx.accounts.to.try_borrow_mut_lamports()? += refund_amount;
+    // Consent, as a co-owner, to a listing under `listing_id` paying you `share_bps`
+    pub fn approve_co_listing(
+        ctx: Context<ApproveCoListing>,
+        listing_id: [u8; 32],
+        share_bps: u16
+    ) -> Result<()> {
+        co_seller::approve_co_listing(ctx, listing_id, share_bps)
+    }
 
-        // Update the bidder's amount to 0 after withdrawal
-        auction.bidders
-            .iter_mut()
-            .find(|b| b.key == ctx.accounts.bidder.key())
-            .unwrap().amount = 0;
+    pub fn revoke_co_listing(ctx: Context<RevokeCoListing>, listing_id: [u8; 32]) -> Result<()> {
+        co_seller::revoke_co_listing(ctx, listing_id)
+    }
 
-        Ok(())
+    // Switch a token listing to bids made by delegate approval; only before the first bid
+    pub fn set_delegated_bidding(
+        ctx: Context<SetDelegatedBidding>,
+        listing_id: [u8; 32],
+        enabled: bool
+    ) -> Result<()> {
+        delegated_bid::set_delegated_bidding(ctx, listing_id, enabled)
     }
 
-    pub fn get_user_bid(
-        ctx: Context<GetUserBid>,
-        listing_id: String,
-        user: Pubkey
-    ) -> Result<(Pubkey, u64, i64)> {
-        let auction_state = &ctx.accounts.auction_state;
-        let auction = auction_state.auctions.get(&listing_id).ok_or(ErrorCode::InvalidListingId)?;
+    // Bid from a token account that approved the listing's vault as delegate
+    pub fn place_delegated_bid(
+        ctx: Context<PlaceDelegatedBid>,
+        listing_id: [u8; 32],
+        amount: u64
+    ) -> Result<()> {
+        delegated_bid::place_delegated_bid(ctx, listing_id, amount)
+    }
 
-        if let Some(bid) = auction.bidders.iter().find(|b| b.key == user) {
-            return Ok((user, bid.amount, bid.time));
-        }
+    // Collect the winning delegated bid, or fall back to the next one
+    pub fn collect_delegated_bid(
+        ctx: Context<CollectDelegatedBid>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        delegated_bid::collect_delegated_bid(ctx, listing_id)
+    }
 
-        Ok((Pubkey::default(), 0, 0))
+    // Let the winner pay in `count` installments; only the seller, and only before the first bid
+    pub fn set_installment_plan(
+        ctx: Context<SetInstallmentPlan>,
+        listing_id: [u8; 32],
+        count: u8,
+        interval: i64,
+        penalty_bps: u16
+    ) -> Result<()> {
+        installments::set_installment_plan(ctx, listing_id, count, interval, penalty_bps)
     }
 
-    pub fn get_all_bids_of_user(
-        ctx: Context<GetAllBidsOfUser>,
-        bidder: Pubkey
-    ) -> Result<(Vec<String>, Vec<u64>, Vec<i64>)> {
-        let auction_state = &ctx.accounts.auction_state;
-        let active_bids_for_user = auction_state.active_bids.get(&bidder).unwrap_or(&vec![]);
+    // Stream the seller's proceeds over `duration` seconds; only before the first bid
+    pub fn set_proceeds_vesting(
+        ctx: Context<SetProceedsVesting>,
+        listing_id: [u8; 32],
+        duration: i64
+    ) -> Result<()> {
+        vesting::set_proceeds_vesting(ctx, listing_id, duration)
+    }
 
-        let mut amounts = vec![];
-        let mut times = vec![];
+    // Seller claims the proceeds vested so far
+    pub fn claim_vested(ctx: Context<ClaimVested>, listing_id: [u8; 32]) -> Result<()> {
+        vesting::claim_vested(ctx, listing_id)
+    }
 
-        for listing_id in active_bids_for_user.iter() {
-            if let Some(auction) = auction_state.auctions.get(listing_id) {
-                let bid = auction.bidders
-                    .iter()
-                    .find(|b| b.key == bidder)
-                    .unwrap();
-                amounts.push(bid.amount);
-                times.push(bid.time);
-            }
-        }
+    // Winner pays their next installment into escrow
+    pub fn pay_installment(ctx: Context<PayInstallment>, listing_id: [u8; 32]) -> Result<()> {
+        installments::pay_installment(ctx, listing_id)
+    }
 
-        Ok((active_bids_for_user.clone(), amounts, times))
+    // Seller keeps the penalty and voids the sale once an installment is overdue
+    pub fn default_installments(
+        ctx: Context<DefaultInstallments>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        installments::default_installments(ctx, listing_id)
     }
 
-    pub fn get_latest_bids(
-        ctx: Context<GetLatestBids>,
-        listing_id: String,
-        n: u64
-    ) -> Result<(Vec<Pubkey>, Vec<u64>, Vec<i64>)> {
-        let auction_state = &ctx.accounts.auction_state;
-        let auction = auction_state.auctions.get(&listing_id).ok_or(ErrorCode::InvalidListingId)?;
-
-        let length = auction.bidders.len();
-        let n = if (n as usize) > length { length } else { n as usize };
-
-        let mut latest_bidders = vec![];
-        let mut latest_bid_amounts = vec![];
-        let mut latest_bid_times = vec![];
-
-        for i in 0..n {
-            let bidder = &auction.bidders[length - 1 - i];
-            latest_bidders.push(bidder.key);
-            latest_bid_amounts.push(bidder.amount);
-            latest_bid_times.push(bidder.time);
-        }
-
-        Ok((latest_bidders, latest_bid_amounts, latest_bid_times))
-    }
-
-    pub fn end_auction(ctx: Context<EndAuction>, listing_id: String, hook: Pubkey) -> Result<()> {
-        let auction_state = &mut ctx.accounts.auction_state;
-        let auction = auction_state.auctions
-            .get_mut(&listing_id)
-            .ok_or(ErrorCode::InvalidListingId)?;
-
-        // Ensure auction has ended
-        let clock = Clock::get().unwrap();
-        require!(clock.unix_timestamp >= auction.end_time, ErrorCode::AuctionNotEnded);
-        require!(!auction.ended, ErrorCode::AuctionAlreadyEnded);
-        require!(auction.highest_bid > 0, ErrorCode::NothingToWithdraw);
-
-        auction.ended = true;
-
-        // Calculate fees and owner earnings
-        let seller_fee = auction_state.seller_fee;
-        let mut fee = (auction.highest_bid * seller_fee) / 1000;
-        let mut owner_earnings = auction.highest_bid - fee;
-
-        fee += auction.fees;
-
-        if auction.is_alien {
-            let total_fees = (auction.total_amount * seller_fee) / 1000;
-            fee += total_fees;
-            owner_earnings += auction.total_amount - total_fees;
-        }
-
-        // Emit AuctionEnded event (replace with Solana event)
-        msg!("Auction ended for listing: {}", listing_id);
-
-        // Remove the listing from active auctions and add to past auctions
-        if
-            let Some(index) = auction_state.active_auctions[&auction.owner]
-                .iter()
-                .position(|x| *x == listing_id)
-        {
-            auction_state.active_auctions.get_mut(&auction.owner).unwrap().remove(index);
-            auction_state.past_auctions.get_mut(&auction.owner).unwrap().push(listing_id.clone());
-        }
-
-        // Generate Metadata for minting
-        let metadata = generate_metadata(
-            listing_id.clone(),
-            auction.highest_bid,
-            auction.bids.get(&auction.highest_bidder).unwrap().time,
-            auction.owner,
-            ctx.accounts.system_program.key()
-        );
-
-        // Try minting
-        if
-            let Err(_) = mint_nft(
-                auction.highest_bidder,
-                listing_id.clone(),
-                metadata,
-                auction.owner,
-                auction.highest_bid,
-                hook
-            )
-        {
-            // Minting failed, revert with custom error
-            return Err(ErrorCode::MintingFailed.into());
-        }
-
-        // Transfer funds to the owner and fee recipient
-        invoke(
-            &system_instruction::transfer(
-                &ctx.accounts.owner.key(),
-                &auction.owner,
-                owner_earnings
-            ),
-            &[
-                ctx.accounts.owner.to_account_info(),
-                ctx.accounts.fee_recipient.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ]
-        )?;
-
-        invoke(
-            &system_instruction::transfer(
-                &ctx.accounts.owner.key(),
-                &ctx.accounts.fee_recipient.key(),
-                fee
-            ),
-            &[
-                ctx.accounts.owner.to_account_info(),
-                ctx.accounts.fee_recipient.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ]
-        )?;
-
-        Ok(())
+    // Re-award a sale whose winner never claimed to the runner-up, or reopen it
+    pub fn default_winner(ctx: Context<DefaultWinner>, listing_id: [u8; 32]) -> Result<()> {
+        winner_default::default_winner(ctx, listing_id)
     }
 
-    pub fn get_highest_bidder(
-        ctx: Context<GetHighestBidder>,
-        listing_id: String
-    ) -> Result<Pubkey> {
-        let auction_state = &ctx.accounts.auction_state;
+    // Edit a listing's metadata URI, reserve and end time; only before the first bid
+    pub fn update_listing(
+        ctx: Context<UpdateListing>,
+        listing_id: [u8; 32],
+        metadata_uri: Option<String>,
+        reserve_price: Option<u64>,
+        end_time: Option<i64>
+    ) -> Result<()> {
+        update_listing::handler(ctx, listing_id, metadata_uri, reserve_price, end_time)
+    }
 
-        // Attempt to retrieve the auction details by listing_id
-        match auction_state.auctions.get(&listing_id) {
-            Some(auction) => Ok(auction.highest_bidder), // Return the highest_bidder if found
-            None => Err(ErrorCode::InvalidListingId.into()), // Return an error if auction not found
-        }
+    // Limit how often each bidder may bid on a listing; only before the first bid
+    pub fn set_bid_limits(
+        ctx: Context<UpdateListing>,
+        listing_id: [u8; 32],
+        max_bids_per_bidder: u16,
+        bid_cooldown: i64
+    ) -> Result<()> {
+        update_listing::set_bid_limits(ctx, listing_id, max_bids_per_bidder, bid_cooldown)
     }
 
-    pub fn get_auction_end_time(
-        ctx: Context<GetAuctionEndTime>,
-        listing_id: String
-    ) -> Result<i64> {
-        let auction_state = &ctx.accounts.auction_state;
+    // List a limited edition whose best `editions` bidders all win, at the lowest winning bid
+    pub fn initialize_edition_auction(
+        ctx: Context<InitializeEditionAuction>,
+        listing_id: [u8; 32],
+        minimum: u64,
+        editions: u8,
+        overrides: ListingOverrides
+    ) -> Result<()> {
+        edition_auction::initialize_edition_auction(ctx, listing_id, minimum, editions, overrides)
+    }
 
-        // Attempt to retrieve the auction details by listing_id
-        match auction_state.auctions.get(&listing_id) {
-            Some(auction) => Ok(auction.end_time), // Return the end_time if found
-            None => Err(ErrorCode::InvalidListingId.into()), // Return an error if auction not found
-        }
+    // Create up to MAX_BATCH_AUCTIONS listings for the signer in one transaction
+    pub fn initialize_auctions_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeAuctionsBatch<'info>>,
+        auctions: Vec<AuctionParams>
+    ) -> Result<()> {
+        initialize_auction::initialize_auctions_batch(ctx, auctions)
     }
 
-    pub fn has_auction_ended(ctx: Context<HasAuctionEnded>, listing_id: String) -> Result<bool> {
-        let auction_state = &ctx.accounts.auction_state;
+    // Relist an unsold listing under a new ID, keeping its parameters unless overridden
+    pub fn clone_listing(
+        ctx: Context<CloneListing>,
+        source_listing: [u8; 32],
+        listing_id: [u8; 32],
+        overrides: ListingOverrides
+    ) -> Result<()> {
+        clone_listing::handler(ctx, source_listing, listing_id, overrides)
+    }
 
-        // Attempt to retrieve the auction details by listing_id
-        match auction_state.auctions.get(&listing_id) {
-            Some(auction) => Ok(auction.ended), // Return true/false based on ended status
-            None => Err(ErrorCode::InvalidListingId.into()), // Return an error if auction not found
-        }
+    // Push back the end of a listing nobody has bid on (seller or automation authority)
+    pub fn extend_if_no_bids(
+        ctx: Context<ExtendIfNoBids>,
+        listing_id: [u8; 32],
+        extra_seconds: i64
+    ) -> Result<()> {
+        extend_if_no_bids::handler(ctx, listing_id, extra_seconds)
     }
 
-    pub fn get_active_auctions_of(
-        ctx: Context<GetActiveAuctionsOf>,
-        owner: Pubkey
-    ) -> Result<Vec<String>> {
-        let auction_data = &ctx.accounts.auction_data;
+    // Seller pushes back the end of a running listing, bids or not
+    pub fn extend_auction(
+        ctx: Context<ExtendAuction>,
+        listing_id: [u8; 32],
+        new_end_time: i64
+    ) -> Result<()> {
+        extend_auction::handler(ctx, listing_id, new_end_time)
+    }
 
-        // Attempt to retrieve the active auctions for the given owner
-        match auction_data.active_auctions.get(&owner) {
-            Some(auctions) => Ok(auctions.clone()), // Return the active auctions if found
-            None => Ok(vec![]), // Return an empty vector if no active auctions are found
-        }
+    // Place a bid of `amount` lamports, escrowed by the instruction itself
+    pub fn place_bid(
+        ctx: Context<PlaceBid>,
+        listing_id: [u8; 32],
+        bidder: Pubkey,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+        referrer: Option<Pubkey>
+    ) -> Result<()> {
+        place_bid::handler(ctx, listing_id, bidder, amount, proof, referrer)
     }
 
-    // Function to get past auctions for an owner
-    pub fn get_past_auctions_of(
-        ctx: Context<GetPastAuctionsOf>,
-        owner: Pubkey
-    ) -> Result<Vec<String>> {
-        let auction_data = &ctx.accounts.auction_data;
+    // Bid on up to MAX_BATCH_BIDS listings in one transaction
+    pub fn place_bids<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceBids<'info>>,
+        bids: Vec<BidRequest>
+    ) -> Result<()> {
+        place_bid::place_bids(ctx, bids)
+    }
 
-        // Attempt to retrieve the past auctions for the given owner
-        match auction_data.past_auctions.get(&owner) {
-            Some(auctions) => Ok(auctions.clone()), // Return the past auctions if found
-            None => Ok(vec![]), // Return an empty vector if no past auctions are found
-        }
+    // Deposit toward a proxy bid the program raises automatically, up to the bidder's total
+    pub fn place_proxy_bid(
+        ctx: Context<PlaceBid>,
+        listing_id: [u8; 32],
+        bidder: Pubkey,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+        referrer: Option<Pubkey>
+    ) -> Result<()> {
+        place_bid::place_proxy_bid(ctx, listing_id, bidder, amount, proof, referrer)
     }
 
-    // Function to get pending withdrawals for an owner
-    pub fn get_pending_withdrawals(
-        ctx: Context<GetPendingWithdrawals>,
-        address: Pubkey
-    ) -> Result<u64> {
-        let auction_data = &ctx.accounts.auction_data;
+    // Buy a listing outright at its buy-now price, ending the auction
+    pub fn buy_now<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyNow<'info>>,
+        listing_id: [u8; 32],
+        proof: Vec<[u8; 32]>
+    ) -> Result<()> {
+        buy_now::handler(ctx, listing_id, proof)
+    }
 
-        // Attempt to retrieve the pending withdrawals for the given address
-        match auction_data.pending_withdrawals.get(&address) {
-            Some(&amount) => Ok(amount), // Return the pending withdrawal amount if found
-            None => Ok(0), // Return 0 if no pending withdrawals are found
-        }
+    // Seller ends the auction early at its current highest bid
+    pub fn accept_highest_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptHighestBid<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        accept_highest_bid::handler(ctx, listing_id)
     }
 
-    pub fn get_bid_amount(
-        ctx: Context<GetBidAmount>,
-        listing_id: String,
+    // Place a bid carried by a Wormhole VAA from another chain, funded from the bridge vault
+    pub fn bridge_bid(ctx: Context<BridgeBid>, listing_id: [u8; 32]) -> Result<()> {
+        bridge_bid::handler(ctx, listing_id)
+    }
+
+    // Record a Wormhole attestation of the other chain's side of a crowd sale
+    pub fn attest_alien_settlement(ctx: Context<AttestAlienSettlement>, listing_id: [u8; 32]) -> Result<()> {
+        alien_attestation::attest_alien_settlement(ctx, listing_id)
+    }
+
+    // Allow a foreign-chain emitter to place bids through Wormhole
+    pub fn add_bridge_emitter(
+        ctx: Context<ManageBridgeEmitters>,
+        chain: u16,
+        address: [u8; 32]
+    ) -> Result<()> {
+        bridge_bid::add_bridge_emitter(ctx, chain, address)
+    }
+
+    pub fn remove_bridge_emitter(
+        ctx: Context<ManageBridgeEmitters>,
+        chain: u16,
+        address: [u8; 32]
+    ) -> Result<()> {
+        bridge_bid::remove_bridge_emitter(ctx, chain, address)
+    }
+
+    // Swap the bidder's tokens to SOL through a whitelisted swap program and bid the proceeds
+    pub fn place_bid_with_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, PlaceBidWithSwap<'info>>,
+        listing_id: [u8; 32],
+        bidder: Pubkey,
+        swap_data: Vec<u8>,
+        min_amount: u64
+    ) -> Result<()> {
+        place_bid_with_swap::handler(ctx, listing_id, bidder, swap_data, min_amount)
+    }
+
+    // Keep a listing's bidder positions in a compressed merkle tree
+    pub fn enable_compressed_positions(
+        ctx: Context<EnableCompressedPositions>,
+        listing_id: [u8; 32],
+        max_depth: u32,
+        max_buffer_size: u32
+    ) -> Result<()> {
+        compressed_bid::enable_compressed_positions(ctx, listing_id, max_depth, max_buffer_size)
+    }
+
+    pub fn place_compressed_bid<'info>(
+        ctx: Context<'_, '_, '_, 'info, PlaceCompressedBid<'info>>,
+        listing_id: [u8; 32],
+        bidder: Pubkey,
+        amount: u64,
+        prior: Option<CompressedPosition>,
+        root: [u8; 32]
+    ) -> Result<()> {
+        compressed_bid::place_compressed_bid(ctx, listing_id, bidder, amount, prior, root)
+    }
+
+    pub fn withdraw_compressed<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawCompressed<'info>>,
+        listing_id: [u8; 32],
+        position: CompressedPosition,
+        root: [u8; 32]
+    ) -> Result<()> {
+        compressed_bid::withdraw_compressed(ctx, listing_id, position, root)
+    }
+
+    // Create the profile that tracks a bidder's replay-protection nonces
+    pub fn init_bidder_profile(ctx: Context<InitBidderProfile>) -> Result<()> {
+        bidder_profile::init_bidder_profile(ctx)
+    }
+
+    // Top up the bidder profile balance that signed bid orders draw on
+    pub fn fund_bidder_profile(ctx: Context<ManageBidderBalance>, amount: u64) -> Result<()> {
+        bidder_profile::fund_bidder_profile(ctx, amount)
+    }
+
+    // Take lamports back out of the bidder profile balance
+    pub fn withdraw_bidder_balance(ctx: Context<ManageBidderBalance>, amount: u64) -> Result<()> {
+        bidder_profile::withdraw_bidder_balance(ctx, amount)
+    }
+
+    // Matcher settles a bid order signed off-chain, checked by the preceding Ed25519 instruction
+    pub fn place_signed_bid(
+        ctx: Context<PlaceSignedBid>,
+        listing_id: [u8; 32],
+        order: BidOrder,
+        proof: Vec<[u8; 32]>
+    ) -> Result<()> {
+        signed_bid::place_signed_bid(ctx, listing_id, order, proof)
+    }
+
+    // Create the feature flags account with the families in `flags` switched on
+    pub fn init_feature_flags(ctx: Context<InitFeatureFlags>, flags: u64) -> Result<()> {
+        feature_flags::init_feature_flags(ctx, flags)
+    }
+
+    pub fn set_feature_flags(ctx: Context<SetFeatureFlags>, flags: u64) -> Result<()> {
+        feature_flags::set_feature_flags(ctx, flags)
+    }
+
+    // Delegate a role (pauser, fee manager, blacklist manager) to `holder`
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+        roles::grant_role(ctx, role, holder)
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: Role) -> Result<()> {
+        roles::revoke_role(ctx, role)
+    }
+
+    // Open the account a user's loyalty points accrue to
+    pub fn init_loyalty_account(ctx: Context<InitLoyaltyAccount>) -> Result<()> {
+        loyalty::init_loyalty_account(ctx)
+    }
+
+    // Create the profile holding a seller's account-wide settings
+    pub fn init_seller_profile(ctx: Context<InitSellerProfile>) -> Result<()> {
+        seller_profile::init_seller_profile(ctx)
+    }
+
+    // Pause every live listing of the seller, or resume them with the time spent away added back
+    pub fn set_vacation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SetVacation<'info>>,
+        on_vacation: bool
+    ) -> Result<()> {
+        seller_profile::set_vacation(ctx, on_vacation)
+    }
+
+    // Give back the cap slots of the seller's closed listings, passed as remaining accounts
+    pub fn release_seller_slots<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseSellerSlots<'info>>
+    ) -> Result<()> {
+        seller_profile::release_seller_slots(ctx)
+    }
+
+    // Winner or arbiter holds a sale in escrow past its dispute window
+    pub fn open_dispute(ctx: Context<OpenDispute>, listing_id: [u8; 32]) -> Result<()> {
+        dispute::open_dispute(ctx, listing_id)
+    }
+
+    // Arbiter voids a held sale in the buyer's favour or releases it to the seller
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        listing_id: [u8; 32],
+        refund_buyer: bool
+    ) -> Result<()> {
+        dispute::resolve_dispute(ctx, listing_id, refund_buyer)
+    }
+
+    pub fn init_watchlist(ctx: Context<InitWatchlist>) -> Result<()> {
+        watchlist::init_watchlist(ctx)
+    }
+
+    pub fn watch(ctx: Context<Watch>, listing_id: [u8; 32]) -> Result<()> {
+        watchlist::watch(ctx, listing_id)
+    }
+
+    pub fn unwatch(ctx: Context<Unwatch>, listing_id: [u8; 32]) -> Result<()> {
+        watchlist::unwatch(ctx, listing_id)
+    }
+
+    // Emit reminders for a watched auction entering its final window; anyone may crank this
+    pub fn heartbeat<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Heartbeat<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        watchlist::heartbeat(ctx, listing_id)
+    }
+
+    // Open the next overflow page once an auction's inline bidder list is full
+    pub fn open_bid_page(ctx: Context<OpenBidPage>, listing_id: [u8; 32], index: u32) -> Result<()> {
+        open_bid_page::handler(ctx, listing_id, index)
+    }
+
+    // Take a losing bid off a live auction, refunded less the retraction penalty
+    pub fn retract_bid(ctx: Context<RetractBid>, listing_id: [u8; 32]) -> Result<()> {
+        retract_bid::retract_bid(ctx, listing_id)
+    }
+
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        listing_id: [u8; 32],
+        recipient: Option<Pubkey>,
+        amount: Option<u64>
+    ) -> Result<()> {
+        withdraw::handler(ctx, listing_id, recipient, amount)
+    }
+
+    // Commit the Switchboard randomness account that decides an auction's outcome
+    pub fn commit_randomness(ctx: Context<CommitRandomness>, listing_id: [u8; 32]) -> Result<()> {
+        commit_randomness::commit_randomness(ctx, listing_id)
+    }
+
+    // Cancel an auction whose randomness never arrived; anyone may crank this
+    pub fn cancel_stale_randomness(
+        ctx: Context<CancelStaleRandomness>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        commit_randomness::cancel_stale_randomness(ctx, listing_id)
+    }
+
+    // List an NFT as a raffle selling tickets at `ticket_price`
+    pub fn initialize_raffle<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeRaffle<'info>>,
+        listing_id: [u8; 32],
+        ticket_price: u64,
+        owner: Pubkey,
+        overrides: ListingOverrides
+    ) -> Result<()> {
+        raffle::initialize_raffle(ctx, listing_id, ticket_price, owner, overrides)
+    }
+
+    pub fn buy_tickets(
+        ctx: Context<BuyTickets>,
+        listing_id: [u8; 32],
+        count: u64,
+        proof: Vec<[u8; 32]>
+    ) -> Result<()> {
+        raffle::buy_tickets(ctx, listing_id, count, proof)
+    }
+
+    // Draw a raffle's winner from its revealed randomness; anyone may crank this
+    pub fn draw_raffle(ctx: Context<DrawRaffle>, listing_id: [u8; 32]) -> Result<()> {
+        raffle::draw_raffle(ctx, listing_id)
+    }
+
+    // Close a candle auction at a cutoff drawn from its revealed randomness; anyone may crank this
+    pub fn close_candle_auction(
+        ctx: Context<CloseCandleAuction>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        candle_auction::handler(ctx, listing_id)
+    }
+
+    // Open the token vault of a listing paid in an SPL token, before its first bid
+    pub fn open_token_vault(ctx: Context<OpenTokenVault>, listing_id: [u8; 32]) -> Result<()> {
+        token_vault::handler(ctx, listing_id)
+    }
+
+    // The winner of a rental listing takes the NFT, frozen in their wallet, for its term
+    pub fn claim_rental(ctx: Context<ClaimRental>, listing_id: [u8; 32]) -> Result<()> {
+        rental::claim_rental(ctx, listing_id)
+    }
+
+    // Return a rented NFT to its seller once the term is over; anyone may crank this
+    pub fn expire_rental(ctx: Context<ExpireRental>, listing_id: [u8; 32]) -> Result<()> {
+        rental::expire_rental(ctx, listing_id)
+    }
+
+    pub fn add_payment_processor(
+        ctx: Context<ManagePaymentProcessors>,
+        program: Pubkey
+    ) -> Result<()> {
+        off_chain_settlement::add_payment_processor(ctx, program)
+    }
+
+    pub fn remove_payment_processor(
+        ctx: Context<ManagePaymentProcessors>,
+        program: Pubkey
+    ) -> Result<()> {
+        off_chain_settlement::remove_payment_processor(ctx, program)
+    }
+
+    // Let a whitelisted payment processor settle the seller's listing off-chain
+    pub fn set_payment_processor(
+        ctx: Context<SetPaymentProcessor>,
+        listing_id: [u8; 32],
+        processor: Option<Pubkey>
+    ) -> Result<()> {
+        off_chain_settlement::set_payment_processor(ctx, listing_id, processor)
+    }
+
+    // Settle a listing paid off-chain, as attested by its payment processor; anyone may crank this
+    pub fn settle_off_chain(ctx: Context<SettleOffChain>, listing_id: [u8; 32]) -> Result<()> {
+        off_chain_settlement::settle_off_chain(ctx, listing_id)
+    }
+
+    pub fn end_auction(mut ctx: Context<EndAuction>, listing_id: [u8; 32]) -> Result<()> {
+        end_auction::handler(&mut ctx, listing_id)
+    }
+
+    // Winner claims the NFT of an ended auction, optionally delivering it to someone else
+    pub fn claim_nft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimNft<'info>>,
+        listing_id: [u8; 32],
+        deliver_to: Option<Pubkey>
+    ) -> Result<()> {
+        claim::claim_nft(ctx, listing_id, deliver_to)
+    }
+
+    // Seller claims the proceeds of an ended auction, settling it
+    pub fn claim_proceeds<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimProceeds<'info>>,
+        listing_id: [u8; 32],
+        hook: Pubkey
+    ) -> Result<()> {
+        claim::claim_proceeds(ctx, listing_id, hook)
+    }
+
+    // end_auction as the first of three settlement steps, which also works out the
+    // sale's payouts; a no-op once the sale is closed and quoted
+    pub fn finalize_auction(ctx: Context<EndAuction>, listing_id: [u8; 32]) -> Result<()> {
+        settlement_steps::finalize_auction(ctx, listing_id)
+    }
+
+    // claim_nft as a settlement step; a no-op once the NFT is delivered
+    pub fn settle_nft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimNft<'info>>,
+        listing_id: [u8; 32],
+        deliver_to: Option<Pubkey>
+    ) -> Result<()> {
+        settlement_steps::settle_nft(ctx, listing_id, deliver_to)
+    }
+
+    // claim_proceeds as a settlement step; a no-op once the proceeds are paid
+    pub fn settle_funds<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimProceeds<'info>>,
+        listing_id: [u8; 32],
+        hook: Pubkey
+    ) -> Result<()> {
+        settlement_steps::settle_funds(ctx, listing_id, hook)
+    }
+
+    // Mint a settled auction's bidder a compressed NFT receipt of their participation
+    pub fn mint_participation_receipt(
+        ctx: Context<MintParticipationReceipt>,
+        listing_id: [u8; 32],
         bidder: Pubkey
-    ) -> Result<u64> {
-        let auction = &ctx.accounts.auction;
+    ) -> Result<()> {
+        participation_receipt::mint_participation_receipt(ctx, listing_id, bidder)
+    }
 
-        // Check if the bidder exists in the bids mapping
-        if let Some(bid) = auction.bids.get(&bidder) {
-            Ok(bid.amount) // Return the bid amount if found
-        } else {
-            Ok(0) // Return 0 if no bid exists for the bidder
-        }
+    // Delist an auction that has no bids yet, returning the NFT to the seller
+    pub fn cancel_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelAuction<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        cancel_auction::handler(ctx, listing_id)
     }
 
-    pub fn get_auction_details(
-        ctx: Context<GetAuctionDetails>,
-        listing_id: String
-    ) -> Result<AuctionDetailsResponse> {
-        let auction = &ctx.accounts.auction;
-
-        // Create and return an AuctionDetailsResponse struct
-        let response = AuctionDetailsResponse {
-            listing_id: auction.listing_id.clone(),
-            highest_bid: auction.highest_bid,
-            highest_bidder: auction.highest_bidder,
-            minimum_bid: auction.minimum_bid,
-            ended: auction.ended,
-            owner: auction.owner,
-            end_time: auction.end_time,
-            bidders: auction.bidders.clone(),
-            num_bidders: auction.bidders.len() as u64,
-        };
-
-        Ok(response) // Return the response wrapped in Ok
-    }
-
-    pub fn get_pending_withdrawal_amount(
-        ctx: Context<GetPendingWithdrawalAmount>,
-        owner: Pubkey
-    ) -> Result<u64> {
-        let auction_details = &ctx.accounts.auction_details;
+    // List an NFT for instant sale at `price`
+    pub fn create_listing(
+        ctx: Context<CreateListing>,
+        listing_id: [u8; 32],
+        price: u64
+    ) -> Result<()> {
+        fixed_price::create_listing(ctx, listing_id, price)
+    }
 
-        // Attempt to retrieve the pending withdrawal amount for the given owner
-        let amount = auction_details.pending_withdrawals.get(&owner).copied().unwrap_or(0);
-        Ok(amount) // Return the amount wrapped in Ok
+    // Buy a fixed-price listing; creators owed royalties follow as remaining accounts
+    pub fn buy_listing<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyListing<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        fixed_price::buy_listing(ctx, listing_id)
     }
 
-    pub fn get_highest_bid_and_end_time(
-        ctx: Context<GetHighestBidAndEndTime>,
-        listing_id: String
-    ) -> Result<(Pubkey, u64, i64, u64)> {
-        let auction = &ctx.accounts.auction_details;
+    pub fn cancel_listing(ctx: Context<CancelListing>, listing_id: [u8; 32]) -> Result<()> {
+        fixed_price::cancel_listing(ctx, listing_id)
+    }
 
-        // Get the current time
-        let current_time = Clock::get()?.unix_timestamp;
+    // Escrow a standing offer on an NFT that isn't listed
+    pub fn make_offer(ctx: Context<MakeOffer>, amount: u64, expires_at: i64) -> Result<()> {
+        offers::make_offer(ctx, amount, expires_at)
+    }
 
-        // Calculate the remaining time
-        let remaining_time = if current_time < auction.end_time {
-            auction.end_time - current_time
-        } else {
-            0
-        };
+    // Sell the NFT at an offer; creators owed royalties follow as remaining accounts
+    pub fn accept_offer<'info>(ctx: Context<'_, '_, '_, 'info, AcceptOffer<'info>>) -> Result<()> {
+        offers::accept_offer(ctx)
+    }
 
-        Ok((auction.highest_bidder, auction.highest_bid, auction.end_time, remaining_time))
+    // Withdraw an offer, or clean up an expired one on the buyer's behalf
+    pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+        offers::cancel_offer(ctx)
     }
 
-    pub fn get_winner(ctx: Context<GetWinner>, listing_id: String) -> Result<Pubkey> {
-        let auction = &ctx.accounts.auction_details;
+    // List an NFT as a crowd auction, pooling contributions toward `target`
+    pub fn initialize_crowd_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeCrowdAuction<'info>>,
+        listing_id: [u8; 32],
+        minimum: u64,
+        owner: Pubkey,
+        overrides: ListingOverrides,
+        target: u64,
+        collateral: Option<SellerCollateral>
+    ) -> Result<()> {
+        crowd_auction::initialize_crowd_auction(
+            ctx,
+            listing_id,
+            minimum,
+            owner,
+            overrides,
+            target,
+            collateral
+        )
+    }
 
-        // Check if the auction has ended
-        require!(auction.ended, ErrorCode::AuctionNotEnded); // Custom error for auction not ended
+    pub fn contribute(ctx: Context<Contribute>, listing_id: [u8; 32], amount: u64) -> Result<()> {
+        crowd_auction::contribute(ctx, listing_id, amount)
+    }
 
-        Ok(auction.highest_bidder)
+    // Take back a contribution to a crowd auction that missed its target
+    pub fn refund_contribution(ctx: Context<RefundContribution>, listing_id: [u8; 32]) -> Result<()> {
+        crowd_auction::refund_contribution(ctx, listing_id)
     }
-}
 
-#[account]
-pub struct AuctionDetails {
-    pub listing_id: String,
-    pub highest_bid: u64,
-    pub highest_bidder: Pubkey,
-    pub bids: std::collections::HashMap<Pubkey, Bid>,
-    pub minimum_bid: u64,
-    pub end_time: i64,
-    pub fees: u64,
-    pub ended: bool,
-    pub paused: bool,
-    pub is_alien: bool,
-    pub total_amount: u64,
-    pub owner: Pubkey,
-    pub bidders: Vec<Pubkey>, // Store bidders' public keys
-    pub active_auctions: HashMap<Pubkey, Vec<String>>,
-    pub past_auctions: HashMap<Pubkey, Vec<String>>,
-    pub pending_withdrawals: HashMap<Pubkey, u64>,
-}
+    // Mint a contributor's shares of a crowd auction that met its target
+    pub fn claim_crowd_shares(ctx: Context<ClaimCrowdShares>, listing_id: [u8; 32]) -> Result<()> {
+        crowd_auction::claim_crowd_shares(ctx, listing_id)
+    }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct AuctionDetailsResponse {
-    pub listing_id: String,
-    pub highest_bid: u64,
-    pub highest_bidder: Pubkey,
-    pub minimum_bid: u64,
-    pub ended: bool,
-    pub owner: Pubkey,
-    pub end_time: i64,
-    pub bidders: Vec<Pubkey>, // or whatever type is appropriate for your bidders
-    pub num_bidders: u64,
-}
+    // Confirm off-chain delivery of a crowd sale, releasing its proceeds and shares
+    pub fn confirm_delivery(ctx: Context<ConfirmDelivery>, listing_id: [u8; 32]) -> Result<()> {
+        crowd_auction::confirm_delivery(ctx, listing_id)
+    }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct Bid {
-    pub amount: u64,
-    pub time: i64,
-}
+    // Take back the collateral staked on a crowd auction that went unsold
+    pub fn reclaim_seller_collateral(
+        ctx: Context<ReclaimSellerCollateral>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        crowd_auction::reclaim_seller_collateral(ctx, listing_id)
+    }
 
-#[account]
-pub struct NftComAuction {
-    pub auctions: HashMap<String, AuctionDetails>,
-    pub active_auctions: HashMap<Pubkey, Vec<String>>,
-    pub past_auctions: HashMap<Pubkey, Vec<String>>,
-    pub pending_withdrawals: HashMap<Pubkey, u64>,
-    pub fee_recipient: Pubkey,
-    pub active_bids: HashMap<Pubkey, Vec<String>>,
-    pub buyer_fee: u64,
-    pub seller_fee: u64,
-    pub nft_contract: Pubkey,
-}
+    // Close an expired listing nobody bid on, for a small bounty; anyone may crank this
+    pub fn sweep_expired<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepExpired<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        sweep_expired::handler(ctx, listing_id)
+    }
 
-#[event]
-pub struct AuctionEnded {
-    pub listing_id: String,
-    pub winner: Pubkey,
-    pub amount: u64,
-}
+    // Escrow another NFT with a listing that has no bids yet, to be sold as a bundle
+    pub fn add_to_bundle(ctx: Context<AddToBundle>, listing_id: [u8; 32]) -> Result<()> {
+        bundle::add_to_bundle(ctx, listing_id)
+    }
 
-#[event]
-pub struct AuctionInitialized {
-    pub listing_id: String,
-    pub minimum: u64,
-    pub end_time: i64,
-}
+    // Give an unsold listing's escrowed NFT back to the seller
+    pub fn return_nft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReturnNft<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        nft_escrow::return_nft(ctx, listing_id)
+    }
 
-#[event]
-pub struct BidPlaced {
-    pub listing_id: String,
-    pub sender: Pubkey,
-    pub value: u64,
-}
+    // Give an unsold listing's Core asset back to the seller
+    pub fn return_core_asset(ctx: Context<ReturnCoreAsset>, listing_id: [u8; 32]) -> Result<()> {
+        core_asset::return_core_asset(ctx, listing_id)
+    }
 
-#[derive(Accounts)]
-pub struct ChangeFeeRecipient<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, NftComAuction>,
-    pub owner: Signer<'info>,
-}
+    // Give an unsold listing's NFT back to the seller, whatever its asset standard
+    pub fn return_listed_nft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReturnListedNft<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        nft_escrow::return_listed_nft(ctx, listing_id)
+    }
 
-#[derive(Accounts)]
-pub struct ChangeNFTContract<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, NftComAuction>,
-    pub owner: Signer<'info>,
-}
+    // Approve a program to receive settlement notifications
+    pub fn add_hook_program(ctx: Context<ManageHookPrograms>, program: Pubkey) -> Result<()> {
+        settlement_hook::add_hook_program(ctx, program)
+    }
 
-#[derive(Accounts)]
-pub struct SetFees<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, NftComAuction>,
-    pub owner: Signer<'info>,
-}
+    pub fn remove_hook_program(ctx: Context<ManageHookPrograms>, program: Pubkey) -> Result<()> {
+        settlement_hook::remove_hook_program(ctx, program)
+    }
 
-#[derive(Accounts)]
-pub struct EmergencyPauseAuction<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, NftComAuction>,
-    pub owner: Signer<'info>,
-}
+    // Notify a settled auction's hook program; anyone may crank this
+    pub fn run_settlement_hook<'info>(
+        ctx: Context<'_, '_, '_, 'info, RunSettlementHook<'info>>,
+        listing_id: [u8; 32]
+    ) -> Result<()> {
+        settlement_hook::run_settlement_hook(ctx, listing_id)
+    }
 
-#[derive(Accounts)]
-pub struct InitializeAuction<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, NftComAuction>,
-    pub owner: Signer<'info>,
-}
+    // Create the archive pruned auctions are recorded in; anyone may pay for it
+    pub fn init_auction_archive(ctx: Context<InitAuctionArchive>) -> Result<()> {
+        prune_archives::init_auction_archive(ctx)
+    }
 
-#[derive(Accounts)]
-pub struct PlaceBid<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, NftComAuction>,
-    pub owner: Signer<'info>,
-    pub bid_amount: Account<'info, BidAmount>,
-}
+    // Create the registry of live auctions; anyone may pay for it
+    pub fn init_auction_registry(ctx: Context<InitAuctionRegistry>) -> Result<()> {
+        auction_registry::init_auction_registry(ctx)
+    }
 
-#[account]
-pub struct BidAmount {
-    pub amount: u64,
-}
+    // Open the next auction registry page once the last one is full
+    pub fn open_registry_page(ctx: Context<OpenRegistryPage>, index: u32) -> Result<()> {
+        auction_registry::open_registry_page(ctx, index)
+    }
 
-#[derive(Accounts)]
-pub struct Withdraw<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, NftComAuction>,
-    #[account(mut)]
-    pub bidder: Signer<'info>,
-    #[account(mut)]
-    pub to: AccountInfo<'info>,
-}
+    // List an open auction on a registry page, or refresh its end time there; anyone may crank this
+    pub fn register_auction(ctx: Context<RegisterAuction>, listing_id: [u8; 32]) -> Result<()> {
+        auction_registry::register_auction(ctx, listing_id)
+    }
 
-#[derive(Accounts)]
-pub struct GetUserBid<'info> {
-    pub auction_state: Account<'info, NftComAuction>,
-}
+    // Take a closed auction off the registry; anyone may crank this
+    pub fn deregister_auction(ctx: Context<RegisterAuction>, listing_id: [u8; 32]) -> Result<()> {
+        auction_registry::deregister_auction(ctx, listing_id)
+    }
 
-#[derive(Accounts)]
-pub struct GetAllBidsOfUser<'info> {
-    pub auction_state: Account<'info, NftComAuction>,
-}
+    // Close finished, fully paid-out auction accounts past retention; anyone may crank this
+    pub fn prune_archives<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PruneArchives<'info>>,
+        before_ts: i64,
+        batch: u8
+    ) -> Result<()> {
+        prune_archives::handler(ctx, before_ts, batch)
+    }
 
-#[derive(Accounts)]
-pub struct GetLatestBids<'info> {
-    pub auction_state: Account<'info, NftComAuction>,
-}
+    // Rewrite an auction left in an older account layout by a program upgrade
+    pub fn migrate_auction(ctx: Context<MigrateAuction>, listing_id: [u8; 32]) -> Result<()> {
+        migrate::migrate_auction(ctx, listing_id)
+    }
 
-#[account]
-pub struct AuctionState {
-    pub auction_details: AuctionDetails,
-    pub is_active: bool,
-    pub end_time: i64,
-    pub owner: Pubkey,
-}
+    // Same for the global state; admin only
+    pub fn migrate_auction_state(ctx: Context<MigrateAuctionState>) -> Result<()> {
+        migrate::migrate_auction_state(ctx)
+    }
 
-#[derive(Accounts)]
-pub struct EndAuction<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, AuctionState>,
-    pub owner: Signer<'info>,
-    #[account(mut)]
-    pub fee_recipient: AccountInfo<'info>,
-    pub system_program: Program<'info, System>,
-}
+    pub fn get_user_bid(
+        ctx: Context<GetUserBid>,
+        listing_id: [u8; 32],
+        user: Pubkey
+    ) -> Result<UserBidResponse> {
+        queries::get_user_bid(ctx, listing_id, user)
+    }
 
-#[derive(Accounts)]
-pub struct GetHighestBidder<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, AuctionState>,
-}
+    pub fn get_all_bids_of_user<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetAllBidsOfUser>,
+        bidder: Pubkey,
+        offset: u64,
+        limit: u64
+    ) -> Result<UserBidsPage> {
+        queries::get_all_bids_of_user(ctx, bidder, offset, limit)
+    }
 
-#[derive(Accounts)]
-pub struct GetAuctionEndTime<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, AuctionState>,
-}
+    pub fn get_latest_bids(
+        ctx: Context<GetLatestBids>,
+        listing_id: [u8; 32],
+        offset: u64,
+        limit: u64
+    ) -> Result<BidHistoryPage> {
+        queries::get_latest_bids(ctx, listing_id, offset, limit)
+    }
 
-#[derive(Accounts)]
-pub struct HasAuctionEnded<'info> {
-    #[account(mut)]
-    pub auction_state: Account<'info, AuctionState>,
-}
+    pub fn get_highest_bidder(
+        ctx: Context<GetHighestBidder>,
+        listing_id: [u8; 32]
+    ) -> Result<Pubkey> {
+        queries::get_highest_bidder(ctx, listing_id)
+    }
 
-#[account]
-pub struct AuctionData {
-    pub auction_id: String,
-    pub highest_bid: u64,
-    pub highest_bidder: Pubkey,
-    pub is_active: bool,
-    pub start_time: i64,
-    pub end_time: i64,
-    pub bids: Vec<Bid>, // or a HashMap of bidders
-    pub owner: Pubkey,
-}
+    pub fn get_auction_end_time(
+        ctx: Context<GetAuctionEndTime>,
+        listing_id: [u8; 32]
+    ) -> Result<i64> {
+        queries::get_auction_end_time(ctx, listing_id)
+    }
 
-#[derive(Accounts)]
-pub struct GetActiveAuctionsOf<'info> {
-    #[account(mut)]
-    pub auction_data: Account<'info, AuctionData>, // The account holding auction data
-}
+    pub fn has_auction_ended(ctx: Context<HasAuctionEnded>, listing_id: [u8; 32]) -> Result<bool> {
+        queries::has_auction_ended(ctx, listing_id)
+    }
 
-#[derive(Accounts)]
-pub struct GetPastAuctionsOf<'info> {
-    #[account(mut)]
-    pub auction_data: Account<'info, AuctionData>, // The account holding auction data
-}
+    pub fn get_active_auctions_of<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetActiveAuctionsOf>,
+        owner: Pubkey
+    ) -> Result<Vec<[u8; 32]>> {
+        queries::get_active_auctions_of(ctx, owner)
+    }
 
-#[derive(Accounts)]
-pub struct GetPendingWithdrawals<'info> {
-    #[account(mut)]
-    pub auction_data: Account<'info, AuctionData>, // The account holding auction data
-}
+    // Function to get past auctions for an owner
+    pub fn get_past_auctions_of<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetPastAuctionsOf>,
+        owner: Pubkey
+    ) -> Result<Vec<[u8; 32]>> {
+        queries::get_past_auctions_of(ctx, owner)
+    }
 
-#[account]
-pub struct Auction {
-    pub auction_id: String,
-    pub highest_bid: u64,
-    pub highest_bidder: Pubkey,
-    pub start_time: i64,
-    pub end_time: i64,
-    pub owner: Pubkey,
-    pub bids: Vec<Bid>, // A list of bids or a HashMap of bids by Pubkey
-}
+    pub fn get_auctions_ending_soon<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetAuctionsEndingSoon>,
+        within: i64
+    ) -> Result<Vec<[u8; 32]>> {
+        queries::get_auctions_ending_soon(ctx, within)
+    }
 
-#[derive(Accounts)]
-pub struct GetBidAmount<'info> {
-    #[account(mut)]
-    pub auction: Account<'info, Auction>, // The auction account holding bid data
-}
+    // Function to get pending withdrawals for an owner
+    pub fn get_pending_withdrawals<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetPendingWithdrawals>,
+        address: Pubkey
+    ) -> Result<u64> {
+        queries::get_pending_withdrawals(ctx, address)
+    }
 
-#[derive(Accounts)]
-pub struct GetAuctionDetails<'info> {
-    #[account(mut)]
-    pub auction: Account<'info, AuctionDetails>, // The auction account holding details
-}
+    pub fn get_bid_amount(
+        ctx: Context<GetBidAmount>,
+        listing_id: [u8; 32],
+        bidder: Pubkey
+    ) -> Result<u64> {
+        queries::get_bid_amount(ctx, listing_id, bidder)
+    }
 
-#[derive(Accounts)]
-pub struct GetPendingWithdrawalAmount<'info> {
-    #[account(mut)]
-    pub auction_details: Account<'info, AuctionDetails>,
-}
+    pub fn get_auction_details(
+        ctx: Context<GetAuctionDetails>,
+        listing_id: [u8; 32]
+    ) -> Result<AuctionDetailsResponse> {
+        queries::get_auction_details(ctx, listing_id)
+    }
 
-#[derive(Accounts)]
-pub struct GetHighestBidAndEndTime<'info> {
-    #[account(mut)]
-    pub auction_details: Account<'info, AuctionDetails>,
-}
+    pub fn get_pending_withdrawal_amount<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetPendingWithdrawalAmount>,
+        owner: Pubkey
+    ) -> Result<u64> {
+        queries::get_pending_withdrawal_amount(ctx, owner)
+    }
 
-#[derive(Accounts)]
-pub struct GetWinner<'info> {
-    #[account(mut)]
-    pub auction_details: Account<'info, AuctionDetails>,
-}
+    pub fn get_highest_bid_and_end_time(
+        ctx: Context<GetHighestBidAndEndTime>,
+        listing_id: [u8; 32]
+    ) -> Result<HighestBidResponse> {
+        queries::get_highest_bid_and_end_time(ctx, listing_id)
+    }
+
+    pub fn get_winner(ctx: Context<GetWinner>, listing_id: [u8; 32]) -> Result<Pubkey> {
+        queries::get_winner(ctx, listing_id)
+    }
+
+    pub fn get_loyalty_balance(ctx: Context<GetLoyaltyBalance>, owner: Pubkey) -> Result<u64> {
+        queries::get_loyalty_balance(ctx, owner)
+    }
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Invalid listing ID.")]
-    InvalidListingId,
-    #[msg("Auction has not ended yet.")]
-    AuctionNotEnded,
-    #[msg("The bid must be greater than zero.")]
-    MinimumBidError,
-    #[msg("End time must be in the future.")]
-    EndTimeError,
+    // Time-weighted average sale price of a registered collection over the last `window` seconds
+    pub fn get_collection_twap(
+        ctx: Context<GetCollectionTwap>,
+        collection: Pubkey,
+        window: i64
+    ) -> Result<CollectionTwapResponse> {
+        queries::get_collection_twap(ctx, collection, window)
+    }
+
+    // Lowest sale price of a registered collection within its floor oracle's window
+    pub fn get_floor_price(
+        ctx: Context<GetFloorPrice>,
+        collection: Pubkey
+    ) -> Result<FloorPriceResponse> {
+        queries::get_floor_price(ctx, collection)
+    }
+
+    // One page of the auction registry's live auctions and their end times
+    pub fn get_auctions(ctx: Context<GetAuctions>, page: u32) -> Result<AuctionRegistryResponse> {
+        queries::get_auctions(ctx, page)
+    }
+
+    // Dry run of a bid of `amount` by the `bidder` account: the fee it would pay
+    // and the outcome, or the error place_bid would reject it with
+    pub fn validate_bid(
+        ctx: Context<ValidateBid>,
+        listing_id: [u8; 32],
+        amount: u64,
+        proof: Vec<[u8; 32]>
+    ) -> Result<BidValidation> {
+        validate_bid::validate_bid(ctx, listing_id, amount, proof)
+    }
 }
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+
+// Basis points in a whole: fees, shares and increments are all fractions of this
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// `bps` of `amount`. Rounds down, so the payer is never charged a fraction of
+// a lamport more than the rate, and any dust stays with the payer.
+pub fn compute_fee(amount: u64, bps: u64) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(ErrorCode::ArithmeticOverflow)?
+        / (BPS_DENOMINATOR as u128);
+    u64::try_from(fee).map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+}
+
+// Checked arithmetic that fails the instruction with ArithmeticOverflow
+// instead of wrapping, e.g. `auction.total_amount = auction.total_amount.try_add(bid)?`
+pub trait CheckedMath: Sized {
+    fn try_add(self, rhs: Self) -> Result<Self>;
+    fn try_sub(self, rhs: Self) -> Result<Self>;
+    fn try_mul(self, rhs: Self) -> Result<Self>;
+}
+
+macro_rules! impl_checked_math {
+    ($($t:ty),*) => {
+        $(
+            impl CheckedMath for $t {
+                fn try_add(self, rhs: Self) -> Result<Self> {
+                    self.checked_add(rhs).ok_or_else(|| error!(ErrorCode::ArithmeticOverflow))
+                }
+
+                fn try_sub(self, rhs: Self) -> Result<Self> {
+                    self.checked_sub(rhs).ok_or_else(|| error!(ErrorCode::ArithmeticOverflow))
+                }
+
+                fn try_mul(self, rhs: Self) -> Result<Self> {
+                    self.checked_mul(rhs).ok_or_else(|| error!(ErrorCode::ArithmeticOverflow))
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_math!(u8, u16, u32, u64, u128, i64);
+
+// `part` / `whole` of `amount`, rounding down like `compute_fee`
+pub fn pro_rata(amount: u64, part: u64, whole: u64) -> Result<u64> {
+    if whole == 0 {
+        return Ok(0);
+    }
+    let share = (amount as u128)
+        .checked_mul(part as u128)
+        .ok_or(ErrorCode::ArithmeticOverflow)?
+        / (whole as u128);
+    u64::try_from(share).map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::{ AuctionDetails, NftComAuction };
+
+// Every account starts with its layout `version` right after the discriminator;
+// zero-copy bid pages keep theirs in the page header instead. A program upgrade
+// that changes an account's layout bumps that account's VERSION, keeps the old
+// layout around as a plain struct, and adds an arm to its upgrade function
+// mapping the old fields onto the new ones. The migrate_* instructions then
+// rewrite old accounts in place, so no listing is stranded by an upgrade.
+// Accounts written before layouts were versioned carry no version and can't be
+// migrated; those listings have to be cancelled and relisted.
+
+pub const VERSION_OFFSET: usize = 8;
+
+pub fn version_of(account: &AccountInfo) -> Result<u8> {
+    let data = account.try_borrow_data()?;
+    let version = data.get(VERSION_OFFSET).copied();
+    version.ok_or_else(|| error!(ErrorCode::UnsupportedAccountVersion))
+}
+
+// The current layout of an auction written at `version`; None when it already is
+pub fn upgrade_auction(version: u8, _data: &[u8]) -> Result<Option<AuctionDetails>> {
+    match version {
+        AuctionDetails::VERSION => Ok(None),
+        _ => Err(ErrorCode::UnsupportedAccountVersion.into()),
+    }
+}
+
+// The current layout of the global state written at `version`; None when it already is
+pub fn upgrade_auction_state(version: u8, _data: &[u8]) -> Result<Option<NftComAuction>> {
+    match version {
+        NftComAuction::VERSION => Ok(None),
+        _ => Err(ErrorCode::UnsupportedAccountVersion.into()),
+    }
+}
+
+// Store `upgraded` in `account`, resized to `space`, with `payer` topping up
+// whatever rent the new size needs
+pub fn rewrite<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    upgraded: &T
+) -> Result<()> {
+    let missing = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+    if missing > 0 {
+        system_program::transfer(
+            CpiContext::new(system_program.clone(), system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            }),
+            missing
+        )?;
+    }
+    account.realloc(space, false)?;
+    let mut data = account.try_borrow_mut_data()?;
+    upgraded.try_serialize(&mut &mut data[..])
+}
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::associated_token::{ self, get_associated_token_address };
+use anchor_spl::metadata::{
+    create_master_edition_v3,
+    create_metadata_accounts_v3,
+    mpl_token_metadata::types::DataV2,
+    CreateMasterEditionV3,
+    CreateMetadataAccountsV3,
+};
+use anchor_spl::token::{ self, spl_token, InitializeMint2, MintTo };
+use crate::errors::ErrorCode;
+use crate::state::NftComAuction;
+use crate::{ AUCTION_STATE_SEED, NFT_MINT_SEED };
+
+// Listings without an escrowed or externally held NFT mint one for the winner
+// at settlement. The mint lives at [NFT_MINT_SEED, listing_id]; the state PDA
+// is its mint authority and the metadata's update authority, and a master
+// edition with zero supply locks it at one token. Each winner of an edition
+// auction gets their own such NFT, at [NFT_MINT_SEED, listing_id, edition].
+
+pub struct MintNftAccounts<'a, 'info> {
+    pub auction_state: &'a Account<'info, NftComAuction>,
+    pub payer: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub recipient: AccountInfo<'info>,
+    pub recipient_token_account: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub master_edition: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub token_metadata_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+}
+
+pub fn mint_address(listing_id: &[u8; 32], edition: Option<u8>) -> (Pubkey, u8) {
+    match edition {
+        Some(edition) =>
+            Pubkey::find_program_address(
+                &[NFT_MINT_SEED, listing_id.as_ref(), &[edition]],
+                &crate::ID
+            ),
+        None => Pubkey::find_program_address(&[NFT_MINT_SEED, listing_id.as_ref()], &crate::ID),
+    }
+}
+
+// Create the mint, give one token to the recipient's associated token account
+// and attach Token Metadata and a master edition
+pub fn mint_nft(
+    accounts: MintNftAccounts,
+    listing_id: &[u8; 32],
+    name: &str,
+    edition: Option<u8>,
+    uri: String
+) -> Result<()> {
+    let (mint_address, mint_bump) = mint_address(listing_id, edition);
+    let edition_seed = edition.map(|edition| [edition]);
+    let mut mint_seeds: Vec<&[u8]> = vec![NFT_MINT_SEED, listing_id.as_ref()];
+    if let Some(edition_seed) = edition_seed.as_ref() {
+        mint_seeds.push(edition_seed);
+    }
+    let bump_seed = [mint_bump];
+    mint_seeds.push(&bump_seed);
+    let name = match edition {
+        Some(edition) => format!("{} #{}", name, (edition as u16) + 1),
+        None => name.to_string(),
+    };
+    require_keys_eq!(accounts.mint.key(), mint_address, ErrorCode::MintingFailed);
+    require_keys_eq!(
+        accounts.recipient_token_account.key(),
+        get_associated_token_address(&accounts.recipient.key(), &mint_address),
+        ErrorCode::MintingFailed
+    );
+
+    let state_info = accounts.auction_state.to_account_info();
+    let state_seeds: &[&[&[u8]]] = &[&[AUCTION_STATE_SEED, &[accounts.auction_state.bump]]];
+
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            accounts.system_program.clone(),
+            system_program::CreateAccount {
+                from: accounts.payer.clone(),
+                to: accounts.mint.clone(),
+            },
+            &[mint_seeds.as_slice()]
+        ),
+        Rent::get()?.minimum_balance(spl_token::state::Mint::LEN),
+        spl_token::state::Mint::LEN as u64,
+        &token::ID
+    )?;
+    token::initialize_mint2(
+        CpiContext::new(accounts.token_program.clone(), InitializeMint2 {
+            mint: accounts.mint.clone(),
+        }),
+        0,
+        &state_info.key(),
+        Some(&state_info.key())
+    )?;
+
+    associated_token::create_idempotent(
+        CpiContext::new(accounts.associated_token_program.clone(), associated_token::Create {
+            payer: accounts.payer.clone(),
+            associated_token: accounts.recipient_token_account.clone(),
+            authority: accounts.recipient.clone(),
+            mint: accounts.mint.clone(),
+            system_program: accounts.system_program.clone(),
+            token_program: accounts.token_program.clone(),
+        })
+    )?;
+    token::mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            MintTo {
+                mint: accounts.mint.clone(),
+                to: accounts.recipient_token_account.clone(),
+                authority: state_info.clone(),
+            },
+            state_seeds
+        ),
+        1
+    )?;
+
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            accounts.token_metadata_program.clone(),
+            CreateMetadataAccountsV3 {
+                metadata: accounts.metadata.clone(),
+                mint: accounts.mint.clone(),
+                mint_authority: state_info.clone(),
+                payer: accounts.payer.clone(),
+                update_authority: state_info.clone(),
+                system_program: accounts.system_program.clone(),
+                rent: accounts.rent.clone(),
+            },
+            state_seeds
+        ),
+        DataV2 {
+            name,
+            symbol: String::new(),
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        true,
+        true,
+        None
+    )?;
+    create_master_edition_v3(
+        CpiContext::new_with_signer(
+            accounts.token_metadata_program.clone(),
+            CreateMasterEditionV3 {
+                edition: accounts.master_edition.clone(),
+                mint: accounts.mint.clone(),
+                update_authority: state_info.clone(),
+                mint_authority: state_info,
+                payer: accounts.payer,
+                metadata: accounts.metadata,
+                token_program: accounts.token_program,
+                system_program: accounts.system_program,
+                rent: accounts.rent,
+            },
+            state_seeds
+        ),
+        Some(0)
+    )
+}
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use switchboard_on_demand::{ PullFeedAccountData, ON_DEMAND_MAINNET_PID };
+use crate::errors::ErrorCode;
+use crate::math::{ self, CheckedMath };
+
+// Vendor-neutral price feeds for USD-denominated features.
+//
+// A feed is identified by an `OracleFeed`: for Pyth the 32-byte price feed id
+// carried inside PriceUpdateV2 accounts, for Switchboard the pull feed account
+// itself. `load_price` picks the layout from the account owner, checks the feed
+// identity, staleness and confidence, and returns a price normalised to
+// `OraclePrice` so callers never touch vendor types.
+
+// Switchboard pull feed results are fixed point with 18 decimals
+const SWITCHBOARD_DECIMALS: i32 = 18;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OracleKind {
+    Pyth,
+    Switchboard,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct OracleFeed {
+    pub kind: OracleKind,
+    pub id: [u8; 32],
+}
+
+#[derive(Clone, Copy)]
+pub struct OracleLimits {
+    pub max_age: i64, // Seconds
+    pub max_confidence_bps: u64, // Confidence interval as a share of the price
+}
+
+impl Default for OracleLimits {
+    fn default() -> Self {
+        OracleLimits { max_age: 60, max_confidence_bps: 200 }
+    }
+}
+
+// `price * 10^expo` units of quote currency, +/- `confidence`
+#[derive(Clone, Copy)]
+pub struct OraclePrice {
+    pub price: u128,
+    pub confidence: u128,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    // Lamports worth `usd_micros` millionths of a dollar, for a SOL/USD feed
+    pub fn lamports_for_usd(&self, usd_micros: u64) -> Result<u64> {
+        // lamports = usd_micros * 10^3 / (price * 10^expo)
+        let scale = 3 - self.expo;
+        let numerator = if scale >= 0 {
+            (usd_micros as u128).checked_mul(10u128.pow(scale as u32))
+        } else {
+            Some((usd_micros as u128) / 10u128.pow(-scale as u32))
+        };
+        numerator
+            .and_then(|numerator| numerator.checked_div(self.price))
+            .and_then(|lamports| u64::try_from(lamports).ok())
+            .ok_or_else(|| error!(ErrorCode::OraclePriceInvalid))
+    }
+}
+
+pub fn load_price(
+    account: &AccountInfo,
+    feed: &OracleFeed,
+    limits: OracleLimits,
+    clock: &Clock
+) -> Result<OraclePrice> {
+    let price = match feed.kind {
+        OracleKind::Pyth => load_pyth(account, feed)?,
+        OracleKind::Switchboard => load_switchboard(account, feed)?,
+    };
+
+    require!(price.price > 0, ErrorCode::OraclePriceInvalid);
+    require!(
+        clock.unix_timestamp.saturating_sub(price.publish_time) <= limits.max_age,
+        ErrorCode::OraclePriceStale
+    );
+    require!(
+        price.confidence.try_mul(math::BPS_DENOMINATOR as u128)? <=
+            price.price.try_mul(limits.max_confidence_bps as u128)?,
+        ErrorCode::OracleConfidenceTooWide
+    );
+    Ok(price)
+}
+
+fn load_pyth(account: &AccountInfo, feed: &OracleFeed) -> Result<OraclePrice> {
+    require_keys_eq!(*account.owner, pyth_solana_receiver_sdk::ID, ErrorCode::UnsupportedOracle);
+
+    let data = account.try_borrow_data()?;
+    let update = PriceUpdateV2::try_deserialize(&mut &data[..])?;
+    let message = update.price_message;
+    require!(message.feed_id == feed.id, ErrorCode::OracleFeedMismatch);
+    require!(message.price > 0, ErrorCode::OraclePriceInvalid);
+
+    Ok(OraclePrice {
+        price: message.price as u128,
+        confidence: message.conf as u128,
+        expo: message.exponent,
+        publish_time: message.publish_time,
+    })
+}
+
+fn load_switchboard(account: &AccountInfo, feed: &OracleFeed) -> Result<OraclePrice> {
+    require_keys_eq!(*account.owner, ON_DEMAND_MAINNET_PID, ErrorCode::UnsupportedOracle);
+    require!(account.key().to_bytes() == feed.id, ErrorCode::OracleFeedMismatch);
+
+    let data = account.try_borrow_data()?;
+    let pull_feed = PullFeedAccountData::parse(data).map_err(|_|
+        error!(ErrorCode::UnsupportedOracle)
+    )?;
+    require!(pull_feed.result.value > 0, ErrorCode::OraclePriceInvalid);
+
+    Ok(OraclePrice {
+        price: pull_feed.result.value as u128,
+        confidence: pull_feed.result.std_dev.unsigned_abs(),
+        expo: -SWITCHBOARD_DECIMALS,
+        publish_time: pull_feed.last_update_timestamp,
+    })
+}
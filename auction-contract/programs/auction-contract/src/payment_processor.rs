@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use crate::errors::ErrorCode;
+
+// Payment processor interface for listings paid off-chain (fiat on-ramps).
+//
+// A whitelisted processor program confirms a payment by writing an attestation
+// account it owns: the 8-byte Anchor discriminator of `PaymentAttestation`
+// followed by the borsh-encoded struct below. Since only the owning program can
+// write it, the account's owner is the processor's signature; settlement then
+// needs no funds to move on-chain.
+pub const ATTESTATION_ACCOUNT_NAME: &str = "account:PaymentAttestation";
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PaymentAttestation {
+    pub listing_id: [u8; 32],
+    pub buyer: Pubkey,
+    pub amount: u64, // In `currency` minor units, informational only
+    pub currency: [u8; 3], // ISO 4217 code
+    pub reference: [u8; 32], // Processor's payment reference
+}
+
+pub fn attestation_discriminator() -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(ATTESTATION_ACCOUNT_NAME.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+// Read the attestation in `account`, which must be owned by `processor`
+pub fn load_attestation(account: &AccountInfo, processor: &Pubkey) -> Result<PaymentAttestation> {
+    require_keys_eq!(*account.owner, *processor, ErrorCode::PaymentProcessorNotAllowed);
+
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() > 8 && data[..8] == attestation_discriminator(),
+        ErrorCode::InvalidAttestation
+    );
+    PaymentAttestation::deserialize(&mut &data[8..]).map_err(|_|
+        error!(ErrorCode::InvalidAttestation)
+    )
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use switchboard_on_demand::RandomnessAccountData;
+use crate::errors::ErrorCode;
+
+// Switchboard on-demand randomness, shared by every feature that needs an
+// unpredictable outcome (candle auction end times, raffles).
+//
+// 1. The client creates a randomness account and requests a value from the
+//    oracle in the same transaction as our commit; `commit` only accepts an
+//    account seeded in the previous slot and not yet revealed, so the value
+//    cannot be known to anyone at commit time.
+// 2. Once the oracle reveals, `consume` returns the value for the committed
+//    account only.
+// 3. If nothing is revealed within RANDOMNESS_TIMEOUT, `is_stale` lets the
+//    caller fall back to cancelling instead of leaving funds stuck.
+pub const RANDOMNESS_TIMEOUT: i64 = 60 * 60;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct RandomnessCommitment {
+    pub randomness_account: Pubkey,
+    pub seed_slot: u64,
+    pub committed_at: i64,
+}
+
+impl RandomnessCommitment {
+    pub const SIZE: usize = 32 + 8 + 8;
+
+    pub fn is_stale(&self, now: i64) -> bool {
+        now > self.committed_at + RANDOMNESS_TIMEOUT
+    }
+}
+
+pub fn commit(randomness_account: &AccountInfo, clock: &Clock) -> Result<RandomnessCommitment> {
+    let data = randomness_account.try_borrow_data()?;
+    let randomness = RandomnessAccountData::parse(data).map_err(|_|
+        error!(ErrorCode::InvalidRandomnessAccount)
+    )?;
+
+    require!(randomness.seed_slot == clock.slot - 1, ErrorCode::RandomnessExpired);
+    require!(randomness.get_value(clock).is_err(), ErrorCode::RandomnessAlreadyRevealed);
+
+    Ok(RandomnessCommitment {
+        randomness_account: randomness_account.key(),
+        seed_slot: randomness.seed_slot,
+        committed_at: clock.unix_timestamp,
+    })
+}
+
+pub fn consume(
+    commitment: &RandomnessCommitment,
+    randomness_account: &AccountInfo,
+    clock: &Clock
+) -> Result<[u8; 32]> {
+    require_keys_eq!(
+        randomness_account.key(),
+        commitment.randomness_account,
+        ErrorCode::InvalidRandomnessAccount
+    );
+
+    let data = randomness_account.try_borrow_data()?;
+    let randomness = RandomnessAccountData::parse(data).map_err(|_|
+        error!(ErrorCode::InvalidRandomnessAccount)
+    )?;
+
+    // A re-requested account would carry a new seed slot and a different value
+    require!(randomness.seed_slot == commitment.seed_slot, ErrorCode::RandomnessExpired);
+    randomness.get_value(clock).map_err(|_| error!(ErrorCode::RandomnessNotRevealed))
+}
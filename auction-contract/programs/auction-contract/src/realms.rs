@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::NftComAuction;
+
+// Realms (SPL Governance) as the config authority.
+//
+// `set_governance` records a realm's governance account. spl-governance runs a
+// passed proposal's instructions itself, signing each one with invoke_signed
+// for the governance PDA, or for the governance's native treasury where the
+// instruction moves SOL; neither address has a private key, so only an
+// executed proposal can produce those signatures. Built with the
+// `spl-governance` feature (the default), `governance_set_fees` and the fee
+// treasury withdrawals accept either one as their signer, so a proposal takes
+// effect as soon as it executes, after the realm's own voting and hold-up time.
+// Without the feature governance only changes parameters through the config
+// change queue.
+pub const SPL_GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!(
+    "GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw"
+);
+
+// SOL treasury spl-governance keeps for `governance`
+pub fn native_treasury_address(governance: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"native-treasury", governance.as_ref()],
+        &SPL_GOVERNANCE_PROGRAM_ID
+    ).0
+}
+
+// Whether `signer` is one of the addresses an executing proposal of the
+// marketplace's governance signs for
+#[cfg(feature = "spl-governance")]
+pub fn is_proposal_signer(auction_state: &NftComAuction, signer: &Pubkey) -> bool {
+    match auction_state.governance {
+        Some(governance) =>
+            *signer == governance || *signer == native_treasury_address(&governance),
+        None => false,
+    }
+}
+
+#[cfg(not(feature = "spl-governance"))]
+#[inline(always)]
+pub fn is_proposal_signer(_auction_state: &NftComAuction, _signer: &Pubkey) -> bool {
+    false
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::{ self, accounts::Metadata };
+use mpl_core::accounts::{ BaseAssetV1, BaseCollectionV1 };
+use mpl_core::types::{ PluginType, Royalties };
+use crate::errors::ErrorCode;
+use crate::instructions::core_asset::core_collection_of;
+use crate::math;
+use crate::state::CompressedLeaf;
+
+// Creator royalties come out of the seller's proceeds at settlement, split by
+// the shares in the NFT's Token Metadata account, or its Royalties plugin for
+// Core assets. Whether they're mandatory is the FLAG_ENFORCE_ROYALTIES feature
+// flag; otherwise they're paid only when the settling transaction passes the
+// metadata. A Core asset is always passed at settlement, and a cNFT's terms
+// are kept on the auction, so their royalties are always paid.
+
+// What each creator of `mint` is owed on a sale at `price`, in metadata order
+pub fn creator_shares(metadata: &AccountInfo, mint: &Pubkey, price: u64) -> Result<Vec<(Pubkey, u64)>> {
+    require_keys_eq!(*metadata.owner, mpl_token_metadata::ID, ErrorCode::InvalidRoyaltyMetadata);
+    require_keys_eq!(metadata.key(), Metadata::find_pda(mint).0, ErrorCode::InvalidRoyaltyMetadata);
+    let metadata = Metadata::safe_deserialize(&metadata.try_borrow_data()?).map_err(
+        |_| ErrorCode::InvalidRoyaltyMetadata
+    )?;
+
+    let royalty = math::compute_fee(price, metadata.seller_fee_basis_points.into())? as u128;
+    Ok(
+        metadata.creators
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|creator| creator.share > 0)
+            .map(|creator| (creator.address, ((royalty * (creator.share as u128)) / 100) as u64))
+            .collect()
+    )
+}
+
+// Same for a Core asset, from its Royalties plugin or else its collection's
+pub fn core_creator_shares(
+    asset: &AccountInfo,
+    collection: Option<&AccountInfo>,
+    price: u64
+) -> Result<Vec<(Pubkey, u64)>> {
+    let plugin = match
+        mpl_core::fetch_plugin::<BaseAssetV1, Royalties>(asset, PluginType::Royalties)
+    {
+        Ok((_, royalties, _)) => Some(royalties),
+        Err(_) =>
+            match collection {
+                Some(collection) => {
+                    require!(
+                        core_collection_of(asset)? == Some(collection.key()),
+                        ErrorCode::InvalidRoyaltyMetadata
+                    );
+                    mpl_core::fetch_plugin::<BaseCollectionV1, Royalties>(
+                        collection,
+                        PluginType::Royalties
+                    )
+                        .ok()
+                        .map(|(_, royalties, _)| royalties)
+                }
+                None => None,
+            }
+    };
+    let royalties = match plugin {
+        Some(royalties) => royalties,
+        None => {
+            return Ok(vec![]);
+        }
+    };
+
+    let royalty = math::compute_fee(price, royalties.basis_points.into())? as u128;
+    Ok(
+        royalties.creators
+            .into_iter()
+            .filter(|creator| creator.percentage > 0)
+            .map(|creator| (creator.address, ((royalty * (creator.percentage as u128)) / 100) as u64))
+            .collect()
+    )
+}
+
+// Same for a Bubblegum cNFT, from the terms proven against its leaf at listing
+pub fn compressed_creator_shares(leaf: &CompressedLeaf, price: u64) -> Result<Vec<(Pubkey, u64)>> {
+    let royalty = math::compute_fee(price, leaf.seller_fee_basis_points.into())? as u128;
+    Ok(
+        leaf.creators
+            .iter()
+            .filter(|creator| creator.share > 0)
+            .map(|creator| (creator.address, ((royalty * (creator.share as u128)) / 100) as u64))
+            .collect()
+    )
+}
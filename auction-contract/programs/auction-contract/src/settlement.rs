@@ -0,0 +1,530 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::instructions::TransferV1CpiBuilder as MetadataTransferCpiBuilder;
+use anchor_spl::token::{ self, Token, TokenAccount, Transfer };
+use mpl_core::accounts::BaseAssetV1;
+use mpl_core::instructions::TransferV1CpiBuilder;
+use crate::cnft::{ self, TransferAccounts };
+use crate::errors::ErrorCode;
+use crate::instructions::bundle::release_bundle;
+use crate::instructions::core_asset::{ assert_escrowable, release_core_asset };
+use crate::instructions::nft_escrow::release_escrowed_nft;
+use crate::royalties;
+use crate::state::{ AssetStandard, AuctionDetails, CompressedLeaf, NftComAuction };
+use crate::AUCTION_STATE_SEED;
+
+// How a listed NFT goes into the program's custody, and comes out again to the
+// winner or back to the seller, depends on its asset standard, which the
+// auction records at listing. So do where its creator royalties are read from
+// and whether it can be bundled or rented out. Each standard has a
+// SettlementAdapter and `adapter` picks an auction's, so the listing, claim,
+// return and settlement instructions only gather the accounts into a Custody
+// and leave the rest to it. Supporting another standard takes an
+// AssetStandard variant and an adapter here rather than a branch in each of them.
+
+// The accounts an adapter may need, passed through from whichever instruction
+// moves the NFT. Each adapter requires the ones its standard uses.
+pub(crate) struct Custody<'a, 'info> {
+    pub auction_state: &'a Account<'info, NftComAuction>,
+    // Covers any account created or resized on the way
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    // Legacy and programmable: the program's escrow, the other party's token
+    // account for the NFT, and the instruction's remaining accounts, which hold a
+    // bundle's further (escrow, token account) pairs or a cNFT's proof nodes
+    pub nft_escrow: Option<&'a Account<'info, TokenAccount>>,
+    pub token_account: Option<&'a AccountInfo<'info>>,
+    pub token_program: Option<&'a Program<'info, Token>>,
+    pub remaining: &'info [AccountInfo<'info>],
+    // The NFT's Token Metadata, read for royalties
+    pub metadata: Option<&'a AccountInfo<'info>>,
+    // Programmable: the mint and its master edition, the token records of the
+    // escrow and of the other party's token account, the mint's rule set if it
+    // has one, and what Token Metadata's transfer calls on the way
+    pub mint: Option<&'a AccountInfo<'info>>,
+    pub edition: Option<&'a AccountInfo<'info>>,
+    pub escrow_token_record: Option<&'a AccountInfo<'info>>,
+    pub token_record: Option<&'a AccountInfo<'info>>,
+    pub authorization_rules: Option<&'a AccountInfo<'info>>,
+    pub authorization_rules_program: Option<&'a AccountInfo<'info>>,
+    pub token_metadata_program: Option<&'a AccountInfo<'info>>,
+    pub associated_token_program: Option<&'a AccountInfo<'info>>,
+    pub sysvar_instructions: Option<&'a AccountInfo<'info>>,
+    // Core: the asset, its collection if any, the Core program, and on release
+    // (Core or Bubblegum) the wallet taking ownership
+    pub asset: Option<&'a AccountInfo<'info>>,
+    pub collection: Option<&'a AccountInfo<'info>>,
+    pub mpl_core_program: Option<&'a AccountInfo<'info>>,
+    pub recipient: Option<&'a AccountInfo<'info>>,
+    // Bubblegum: the tree, its config and the programs a transfer goes
+    // through; on escrow also the leaf being listed and its delegate, if the
+    // seller has one
+    pub merkle_tree: Option<&'a AccountInfo<'info>>,
+    pub tree_config: Option<&'a AccountInfo<'info>>,
+    pub log_wrapper: Option<&'a AccountInfo<'info>>,
+    pub compression_program: Option<&'a AccountInfo<'info>>,
+    pub bubblegum_program: Option<&'a AccountInfo<'info>>,
+    pub leaf: Option<&'a CompressedLeaf>,
+    pub leaf_delegate: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> Custody<'a, 'info> {
+    // A Custody with none of the per-standard accounts, for filling in the
+    // ones the caller has
+    pub fn new(
+        auction_state: &'a Account<'info, NftComAuction>,
+        payer: &'a AccountInfo<'info>,
+        system_program: &'a AccountInfo<'info>
+    ) -> Self {
+        Custody {
+            auction_state,
+            payer,
+            system_program,
+            nft_escrow: None,
+            token_account: None,
+            token_program: None,
+            remaining: &[],
+            metadata: None,
+            mint: None,
+            edition: None,
+            escrow_token_record: None,
+            token_record: None,
+            authorization_rules: None,
+            authorization_rules_program: None,
+            token_metadata_program: None,
+            associated_token_program: None,
+            sysvar_instructions: None,
+            asset: None,
+            collection: None,
+            mpl_core_program: None,
+            recipient: None,
+            merkle_tree: None,
+            tree_config: None,
+            log_wrapper: None,
+            compression_program: None,
+            bubblegum_program: None,
+            leaf: None,
+            leaf_delegate: None,
+        }
+    }
+}
+
+pub(crate) trait SettlementAdapter<'info> {
+    // Take `nft` from `seller`, who signs, into the program's custody
+    fn escrow(
+        &self,
+        custody: &Custody<'_, 'info>,
+        nft: Pubkey,
+        seller: &AccountInfo<'info>
+    ) -> Result<()>;
+
+    // Hand what `auction` holds in custody to `recipient`; the rent of any
+    // escrow account emptied on the way goes to `rent_to`
+    fn release(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        recipient: Pubkey,
+        rent_to: &AccountInfo<'info>
+    ) -> Result<()>;
+
+    // What each creator is owed on a sale at `price`, out of the seller's share
+    fn creator_shares(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        price: u64
+    ) -> Result<Vec<(Pubkey, u64)>>;
+
+    // Whether further NFTs can be escrowed with the listed one
+    fn bundles(&self) -> bool {
+        false
+    }
+
+    // Whether the NFT can be lent out: delivered frozen, with the program as
+    // its delegate to take it back when the term is up
+    fn rents(&self) -> bool {
+        false
+    }
+}
+
+// The adapter for NFTs of `standard`
+pub(crate) fn adapter<'info>(standard: AssetStandard) -> &'info dyn SettlementAdapter<'info> {
+    match standard {
+        AssetStandard::Legacy => &LegacyAdapter,
+        AssetStandard::Core => &CoreAdapter,
+        AssetStandard::Programmable => &ProgrammableAdapter,
+        AssetStandard::Compressed => &CompressedAdapter,
+    }
+}
+
+// Royalties from the Token Metadata of the escrowed mint, or of the one an
+// external escrow holds; a minted NFT has none
+fn metadata_creator_shares(
+    custody: &Custody,
+    auction: &AuctionDetails,
+    price: u64
+) -> Result<Vec<(Pubkey, u64)>> {
+    let royalty_mint = auction.nft_mint.or(
+        auction.external_escrow.as_ref().map(|escrow| escrow.mint)
+    );
+    match (royalty_mint, custody.metadata) {
+        (Some(mint), Some(metadata)) => royalties::creator_shares(metadata, &mint, price),
+        (Some(_), None) if custody.auction_state.enforces_royalties() => {
+            Err(ErrorCode::MissingRoyaltyAccounts.into())
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+// SPL token mints with Token Metadata, escrowed in a token account at
+// [NFT_ESCROW_SEED, mint] the state PDA owns, along with any bundled NFTs
+struct LegacyAdapter;
+
+impl<'info> SettlementAdapter<'info> for LegacyAdapter {
+    fn escrow(
+        &self,
+        custody: &Custody<'_, 'info>,
+        nft: Pubkey,
+        seller: &AccountInfo<'info>
+    ) -> Result<()> {
+        let nft_escrow = custody.nft_escrow.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let token_account = custody.token_account.ok_or(ErrorCode::NftNotHeld)?;
+        let token_program = custody.token_program.ok_or(ErrorCode::NftEscrowMismatch)?;
+        require_keys_eq!(nft_escrow.mint, nft, ErrorCode::NftEscrowMismatch);
+        token::transfer(
+            CpiContext::new(token_program.to_account_info(), Transfer {
+                from: token_account.clone(),
+                to: nft_escrow.to_account_info(),
+                authority: seller.clone(),
+            }),
+            1
+        )
+    }
+
+    fn release(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        recipient: Pubkey,
+        rent_to: &AccountInfo<'info>
+    ) -> Result<()> {
+        let nft_mint = auction.nft_mint.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let nft_escrow = custody.nft_escrow.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let token_account = custody.token_account.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let token_program = custody.token_program.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let destination = Account::<TokenAccount>::try_from(token_account)?;
+        require_keys_eq!(nft_escrow.mint, nft_mint, ErrorCode::NftEscrowMismatch);
+        require_keys_eq!(destination.mint, nft_mint, ErrorCode::NftEscrowMismatch);
+        require_keys_eq!(destination.owner, recipient, ErrorCode::NftEscrowMismatch);
+
+        release_escrowed_nft(
+            custody.auction_state,
+            token_program,
+            nft_escrow,
+            token_account,
+            rent_to
+        )?;
+        release_bundle(
+            custody.auction_state,
+            token_program,
+            &auction.bundle_mints,
+            custody.remaining,
+            recipient,
+            rent_to
+        )
+    }
+
+    fn creator_shares(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        price: u64
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        metadata_creator_shares(custody, auction, price)
+    }
+
+    fn bundles(&self) -> bool {
+        true
+    }
+
+    fn rents(&self) -> bool {
+        true
+    }
+}
+
+// Metaplex Core assets, escrowed by making the state PDA their owner
+struct CoreAdapter;
+
+impl<'info> SettlementAdapter<'info> for CoreAdapter {
+    fn escrow(
+        &self,
+        custody: &Custody<'_, 'info>,
+        nft: Pubkey,
+        seller: &AccountInfo<'info>
+    ) -> Result<()> {
+        let asset = custody.asset.ok_or(ErrorCode::InvalidNftMint)?;
+        let mpl_core_program = custody.mpl_core_program.ok_or(ErrorCode::InvalidNftMint)?;
+        require_keys_eq!(asset.key(), nft, ErrorCode::InvalidNftMint);
+        {
+            let data = asset.try_borrow_data()?;
+            let base = BaseAssetV1::from_bytes(&data).map_err(|_| ErrorCode::InvalidNftMint)?;
+            require_keys_eq!(base.owner, seller.key(), ErrorCode::NftNotHeld);
+        }
+        assert_escrowable(asset)?;
+
+        TransferV1CpiBuilder::new(mpl_core_program)
+            .asset(asset)
+            .collection(custody.collection)
+            .payer(custody.payer)
+            .authority(Some(seller))
+            .new_owner(&custody.auction_state.to_account_info())
+            .system_program(Some(custody.system_program))
+            .invoke()?;
+        Ok(())
+    }
+
+    fn release(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        recipient: Pubkey,
+        _rent_to: &AccountInfo<'info>
+    ) -> Result<()> {
+        let asset = custody.asset.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let mpl_core_program = custody.mpl_core_program.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let wallet = custody.recipient.ok_or(ErrorCode::NftEscrowMismatch)?;
+        require!(auction.nft_mint == Some(asset.key()), ErrorCode::NftEscrowMismatch);
+        require_keys_eq!(wallet.key(), recipient, ErrorCode::InvalidRecipient);
+
+        release_core_asset(
+            custody.auction_state,
+            mpl_core_program,
+            asset,
+            custody.collection,
+            wallet,
+            custody.payer,
+            custody.system_program
+        )
+    }
+
+    fn creator_shares(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        price: u64
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        let asset = custody.asset.ok_or(ErrorCode::NftEscrowMismatch)?;
+        require!(auction.nft_mint == Some(asset.key()), ErrorCode::NftEscrowMismatch);
+        royalties::core_creator_shares(asset, custody.collection, price)
+    }
+}
+
+// Programmable NFTs, escrowed in the same token account as Legacy NFTs but
+// moved in and out through Token Metadata's transfer, which thaws and refreezes
+// them and enforces the mint's rule set
+struct ProgrammableAdapter;
+
+// One side of a programmable transfer: a token account, its owner and its
+// token record
+struct Holding<'a, 'info> {
+    token: &'a AccountInfo<'info>,
+    owner: &'a AccountInfo<'info>,
+    record: &'a AccountInfo<'info>,
+}
+
+impl ProgrammableAdapter {
+    fn transfer<'info>(
+        custody: &Custody<'_, 'info>,
+        from: Holding<'_, 'info>,
+        to: Holding<'_, 'info>,
+        signer_seeds: &[&[&[u8]]]
+    ) -> Result<()> {
+        let token_metadata_program = custody.token_metadata_program.ok_or(
+            ErrorCode::NftEscrowMismatch
+        )?;
+        let mint = custody.mint.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let metadata = custody.metadata.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let edition = custody.edition.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let sysvar_instructions = custody.sysvar_instructions.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let associated_token_program = custody.associated_token_program.ok_or(
+            ErrorCode::NftEscrowMismatch
+        )?;
+        let token_program = custody.token_program.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let token_program = token_program.to_account_info();
+
+        MetadataTransferCpiBuilder::new(token_metadata_program)
+            .token(from.token)
+            .token_owner(from.owner)
+            .destination_token(to.token)
+            .destination_owner(to.owner)
+            .mint(mint)
+            .metadata(metadata)
+            .edition(Some(edition))
+            .token_record(Some(from.record))
+            .destination_token_record(Some(to.record))
+            .authority(from.owner)
+            .payer(custody.payer)
+            .system_program(custody.system_program)
+            .sysvar_instructions(sysvar_instructions)
+            .spl_token_program(&token_program)
+            .spl_ata_program(associated_token_program)
+            .authorization_rules_program(custody.authorization_rules_program)
+            .authorization_rules(custody.authorization_rules)
+            .amount(1)
+            .invoke_signed(signer_seeds)?;
+        Ok(())
+    }
+}
+
+impl<'info> SettlementAdapter<'info> for ProgrammableAdapter {
+    fn escrow(
+        &self,
+        custody: &Custody<'_, 'info>,
+        nft: Pubkey,
+        seller: &AccountInfo<'info>
+    ) -> Result<()> {
+        let nft_escrow = custody.nft_escrow.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let token_account = custody.token_account.ok_or(ErrorCode::NftNotHeld)?;
+        let token_record = custody.token_record.ok_or(ErrorCode::NftNotHeld)?;
+        let escrow_token_record = custody.escrow_token_record.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let mint = custody.mint.ok_or(ErrorCode::InvalidNftMint)?;
+        require_keys_eq!(mint.key(), nft, ErrorCode::InvalidNftMint);
+        require_keys_eq!(nft_escrow.mint, nft, ErrorCode::NftEscrowMismatch);
+
+        let escrow_info = nft_escrow.to_account_info();
+        let auction_state = custody.auction_state.to_account_info();
+        ProgrammableAdapter::transfer(
+            custody,
+            Holding { token: token_account, owner: seller, record: token_record },
+            Holding { token: &escrow_info, owner: &auction_state, record: escrow_token_record },
+            &[]
+        )
+    }
+
+    // The emptied escrow stays open; the mint's next listing reuses it
+    fn release(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        recipient: Pubkey,
+        _rent_to: &AccountInfo<'info>
+    ) -> Result<()> {
+        let nft_mint = auction.nft_mint.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let nft_escrow = custody.nft_escrow.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let token_account = custody.token_account.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let token_record = custody.token_record.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let escrow_token_record = custody.escrow_token_record.ok_or(ErrorCode::NftEscrowMismatch)?;
+        let wallet = custody.recipient.ok_or(ErrorCode::NftEscrowMismatch)?;
+        require_keys_eq!(nft_escrow.mint, nft_mint, ErrorCode::NftEscrowMismatch);
+        require_keys_eq!(wallet.key(), recipient, ErrorCode::InvalidRecipient);
+
+        let escrow_info = nft_escrow.to_account_info();
+        let auction_state = custody.auction_state.to_account_info();
+        ProgrammableAdapter::transfer(
+            custody,
+            Holding { token: &escrow_info, owner: &auction_state, record: escrow_token_record },
+            Holding { token: token_account, owner: wallet, record: token_record },
+            &[&[AUCTION_STATE_SEED, &[custody.auction_state.bump]]]
+        )
+    }
+
+    fn creator_shares(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        price: u64
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        metadata_creator_shares(custody, auction, price)
+    }
+}
+
+// Bubblegum cNFTs, escrowed by making the state PDA their leaf owner. The
+// leaf's hashes are kept on the auction, so release only needs the tree and a
+// fresh proof.
+struct CompressedAdapter;
+
+impl CompressedAdapter {
+    fn transfer<'info>(
+        custody: &Custody<'_, 'info>,
+        leaf: &CompressedLeaf,
+        owner: &AccountInfo<'info>,
+        delegate: &AccountInfo<'info>,
+        new_owner: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]]
+    ) -> Result<()> {
+        let accounts = TransferAccounts {
+            tree_config: custody.tree_config.ok_or(ErrorCode::InvalidMerkleTree)?,
+            leaf_owner: owner,
+            leaf_delegate: delegate,
+            new_leaf_owner: new_owner,
+            merkle_tree: custody.merkle_tree.ok_or(ErrorCode::InvalidMerkleTree)?,
+            log_wrapper: custody.log_wrapper.ok_or(ErrorCode::InvalidMerkleTree)?,
+            compression_program: custody.compression_program.ok_or(ErrorCode::InvalidMerkleTree)?,
+            bubblegum_program: custody.bubblegum_program.ok_or(ErrorCode::InvalidMerkleTree)?,
+            system_program: custody.system_program,
+        };
+        cnft::transfer(accounts, leaf, custody.remaining, signer_seeds)
+    }
+}
+
+impl<'info> SettlementAdapter<'info> for CompressedAdapter {
+    fn escrow(
+        &self,
+        custody: &Custody<'_, 'info>,
+        nft: Pubkey,
+        seller: &AccountInfo<'info>
+    ) -> Result<()> {
+        let leaf = custody.leaf.ok_or(ErrorCode::InvalidMerkleTree)?;
+        require_keys_eq!(
+            cnft::asset_id(&leaf.merkle_tree, leaf.nonce),
+            nft,
+            ErrorCode::InvalidNftMint
+        );
+        let auction_state = custody.auction_state.to_account_info();
+        CompressedAdapter::transfer(
+            custody,
+            leaf,
+            seller,
+            custody.leaf_delegate.unwrap_or(seller),
+            &auction_state,
+            &[]
+        )
+    }
+
+    fn release(
+        &self,
+        custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        recipient: Pubkey,
+        _rent_to: &AccountInfo<'info>
+    ) -> Result<()> {
+        let leaf = auction.compressed_leaf.as_ref().ok_or(ErrorCode::NftEscrowMismatch)?;
+        let wallet = custody.recipient.ok_or(ErrorCode::NftEscrowMismatch)?;
+        require!(
+            auction.nft_mint == Some(cnft::asset_id(&leaf.merkle_tree, leaf.nonce)),
+            ErrorCode::NftEscrowMismatch
+        );
+        require_keys_eq!(wallet.key(), recipient, ErrorCode::InvalidRecipient);
+
+        // A transfer makes the new owner the delegate too, so the state PDA is both
+        let auction_state = custody.auction_state.to_account_info();
+        CompressedAdapter::transfer(
+            custody,
+            leaf,
+            &auction_state,
+            &auction_state,
+            wallet,
+            &[&[AUCTION_STATE_SEED, &[custody.auction_state.bump]]]
+        )
+    }
+
+    fn creator_shares(
+        &self,
+        _custody: &Custody<'_, 'info>,
+        auction: &AuctionDetails,
+        price: u64
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        let leaf = auction.compressed_leaf.as_ref().ok_or(ErrorCode::NftEscrowMismatch)?;
+        royalties::compressed_creator_shares(leaf, price)
+    }
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked,
+    load_instruction_at_checked,
+};
+use crate::errors::ErrorCode;
+
+// Signed bid orders aren't verified by the program itself: the matcher puts an
+// Ed25519 program instruction right before the one settling the order, and the
+// runtime fails the transaction unless that signature holds. All that's left
+// here is to make sure the instruction verified `signer` over `message`.
+//
+// Ed25519 instruction data is a signature count and a padding byte, then per
+// signature seven u16 offsets: signature, its instruction index, public key,
+// its instruction index, message, message size and its instruction index. An
+// index of u16::MAX points into the Ed25519 instruction itself, which is the
+// only layout accepted, so nothing else in the transaction can stand in for
+// the key or message.
+
+const OFFSETS_START: usize = 2;
+const OFFSETS_SIZE: usize = 14;
+const THIS_INSTRUCTION: u16 = u16::MAX;
+
+pub fn verify(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, ErrorCode::OrderSignatureMissing);
+    let verify_ix = load_instruction_at_checked((current - 1) as usize, instructions)?;
+    require_keys_eq!(verify_ix.program_id, ed25519_program::ID, ErrorCode::OrderSignatureMissing);
+
+    let data = &verify_ix.data;
+    require!(
+        data.len() >= OFFSETS_START + OFFSETS_SIZE && data[0] == 1,
+        ErrorCode::InvalidOrderSignature
+    );
+    let read = |field: usize| -> u16 {
+        let at = OFFSETS_START + 2 * field;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+    require!(
+        read(1) == THIS_INSTRUCTION && read(3) == THIS_INSTRUCTION && read(6) == THIS_INSTRUCTION,
+        ErrorCode::InvalidOrderSignature
+    );
+
+    let key_start = read(2) as usize;
+    let message_start = read(4) as usize;
+    let key = data.get(key_start..key_start + 32).ok_or(ErrorCode::InvalidOrderSignature)?;
+    let signed = data
+        .get(message_start..message_start + read(5) as usize)
+        .ok_or(ErrorCode::InvalidOrderSignature)?;
+    require!(key == signer.as_ref() && signed == message, ErrorCode::InvalidOrderSignature);
+    Ok(())
+}
@@ -0,0 +1,2638 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak::hashv;
+use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
+use anchor_spl::token::spl_token::native_mint;
+use crate::allowlist;
+use crate::errors::ErrorCode;
+use crate::events::{ FeeRecipientChanged, FeesUpdated };
+use crate::math::{ self, CheckedMath };
+use crate::oracle::{ OracleFeed, OraclePrice };
+use crate::randomness::RandomnessCommitment;
+use crate::realms;
+
+// One auction, stored in its own PDA at [AUCTION_SEED, listing_id]. Its bid
+// funds are held separately, in the vault at [VAULT_SEED, listing_id]. The id
+// is the hash of the seller's key and the listing's name, so every seed and
+// event carries a fixed-size key and each seller lists under ids nobody else
+// can take.
+#[account]
+pub struct AuctionDetails {
+    pub version: u8, // Layout version, first so migrations can read it from any layout
+    pub listing_id: [u8; 32],
+    pub name: String, // Human-readable name, shown to bidders and given to a minted NFT
+    pub highest_bid: u64,
+    pub highest_bidder: Pubkey,
+    pub highest_max_bid: u64, // Ceiling the highest bidder's proxy defends; the highest bid for a plain bid
+    pub bids: Vec<BidEntry>, // Inline positions, sorted by bidder
+    pub minimum_bid: u64,
+    pub end_time: i64,
+    pub fees: u64,
+    pub status: AuctionStatus,
+    pub is_alien: bool, // Crowd auction: contributions pool toward `reserve_price`
+    pub total_amount: u64,
+    pub owner: Pubkey,
+    pub bid_pages: u32, // Overflow pages opened once `bids` is full
+    pub paged_total: u64, // Sum of the amounts recorded on overflow pages
+    pub settlement_hook: Option<Pubkey>, // Hook program still to be notified of settlement
+    pub external_escrow: Option<ExternalEscrow>, // Set when the NFT stays in another program's escrow
+    pub randomness: Option<RandomnessCommitment>, // Pending Switchboard randomness deciding the outcome
+    pub position_tree: Option<Pubkey>, // Merkle tree holding compressed bidder positions
+    pub compressed_positions: u32, // Leaves appended to `position_tree` so far
+    pub compressed_total: u64, // Sum of the amounts held in compressed positions
+    pub collection: Option<Pubkey>, // Registered collection the listing was made under
+    pub fee_override: Option<FeeOverride>, // Negotiated or collection fees replacing the global ones
+    pub collection_permissions: u64, // CollectionConfig::permissions at listing time
+    pub payment_processor: Option<Pubkey>, // Processor the seller lets settle this listing off-chain
+    pub off_chain_reference: Option<[u8; 32]>, // Processor's payment reference once paid off-chain
+    pub metadata_uri: String,
+    pub reserve_price: u64, // Lowest winning bid the seller accepts; 0 for none
+    pub min_increment_bps: u64, // Smallest raise over the highest bid, in basis points
+    pub min_increment: u64, // Smallest raise over the highest bid, in lamports
+    pub created_at: i64, // When the listing was made
+    pub closed_at: i64, // When the auction last moved to a closed status
+    pub deliver_to: Option<Pubkey>, // Gift recipient named by the winner; the winner stays the buyer of record
+    pub vacation_paused: bool, // Paused by the seller's vacation mode rather than by an admin
+    pub holds_seller_slot: bool, // Counted in the seller profile's active_auctions until released
+    pub bump: u8,
+    pub vault_bump: u8,
+    pub nft_mint: Option<Pubkey>, // NFT held in the program's escrow until settlement or cancellation; the asset itself for Core
+    pub asset_standard: AssetStandard,
+    pub buy_now_price: Option<u64>, // Price at which a buyer may end the auction immediately
+    pub nft_claimed: bool, // The winner has pulled the NFT with `claim_nft`
+    pub sniping_time_window: i64, // Bids this close to the end extend the auction...
+    pub time_extension: i64, // ...by this many seconds
+    pub extensions: u32, // Anti-sniping extensions applied so far
+    pub bundle_mints: Vec<Pubkey>, // Further escrowed NFTs sold together with `nft_mint`
+    pub editions: u8, // Winners of an edition auction; 0 for a single-winner auction
+    pub edition_book: Vec<BidEntry>, // Best positions of an edition auction, highest first
+    pub clearing_price: u64, // Price every edition winner pays, fixed when the auction ends
+    pub editions_claimed: u16, // Bit per `edition_book` slot whose edition has been minted
+    pub allowlist_root: Option<[u8; 32]>, // Merkle root of the wallets allowed to bid on a private listing
+    pub gate_mint: Option<Pubkey>, // Mint a bidder must hold to bid on a token-gated listing
+    pub seller_paused: bool, // Paused by the seller through `seller_pause_auction`
+    pub payout_splits: Vec<PayoutSplit>, // Recipients sharing the seller's proceeds; empty pays the seller alone
+    pub ticket_price: u64, // Price of one ticket of a raffle; 0 for an auction
+    pub second_bid: u64, // Best offer from anyone but the leader: the runner-up's bid or proxy ceiling
+    pub second_price: bool, // Vickrey listing: the winner pays `second_bid` rather than their own bid
+    pub candle_window: i64, // Candle auction: final stretch the effective end is drawn from; 0 for none
+    pub candle_marks: Vec<CandleMark>, // Every change of lead during the candle window, oldest first
+    pub candle_cutoff: i64, // Effective end drawn at close; 0 until then
+    pub rental_days: u16, // Rental listing: days the winner holds the NFT for; 0 for a sale
+    pub rental_expires_at: i64, // When the running rental can be ended; 0 when none is running
+    pub payment_mint: Option<Pubkey>, // SPL token bids and payouts are made in; None for SOL
+    pub usd_pricing: Option<UsdPricing>, // Minimum bid and reserve set in USD rather than lamports
+    pub usd_priced_at: i64, // When they were last converted to `minimum_bid` and `reserve_price`
+    pub bid_bond: u64, // Lamports each bidder locks alongside their bids; 0 for none
+    pub bonds_held: u64, // Bonds in the vault, not yet refunded or forfeited
+    pub seller_collateral: u64, // Crowd auction delivered off-chain: lamports the seller stakes on delivery
+    pub delivery_window: i64, // How long after close the seller has to confirm delivery
+    pub delivery_confirmed: bool,
+    pub collateral_slashed: bool, // Delivery went unconfirmed, so the collateral goes to the contributors
+    pub alien_attestation: Option<AttestationKind>, // What a Wormhole VAA confirmed of the other chain's side
+    pub bid_snapshot: Option<[u8; 32]>, // Merkle root of the bid history, taken when the auction ended
+    pub runners_up: Vec<BidEntry>, // Best offers behind the leader, highest first, to re-award a default to
+    pub installment_plan: Option<InstallmentPlan>, // Winner pays in installments, the winning bid the first
+    pub delegated_bidding: bool, // Token listing bid on by delegate approval rather than escrow
+    pub delegated_bids: Vec<DelegatedBid>, // Approved bids still to collect from, highest first
+    pub max_bids_per_bidder: u16, // Bids each bidder may place; 0 for no limit
+    pub bid_cooldown: i64, // Seconds a bidder waits between bids; 0 for none
+    pub co_owned: bool, // Payout split recipients are co-owners who consented to the listing
+    pub proceeds_vesting: Option<ProceedsVesting>, // Seller's proceeds stream out after settlement
+    pub seller_fee_discount_bps: u64, // Seller's membership discount off the seller fee
+    pub beneficiary: Option<Beneficiary>, // Cause receiving a share of the seller's proceeds
+    pub registry_page: Option<u32>, // Auction registry page listing the auction while it's live
+    pub compressed_leaf: Option<CompressedLeaf>, // The escrowed cNFT's leaf, on Bubblegum listings
+    pub settlement_quote: Option<SettlementQuote>, // Payouts fixed by `finalize_auction`, until paid
+    pub bid_history: BidHistory, // The most recent bids, plus totals over all of them
+}
+
+impl AuctionDetails {
+    pub const VERSION: u8 = 1;
+    // Bidders kept inline on the auction; everyone after that lands in a BidPage
+    pub const MAX_INLINE_BIDDERS: usize = 32;
+    // Same limit as Metaplex token metadata
+    pub const MAX_URI_LENGTH: usize = 200;
+    // Same limit as Metaplex token metadata names
+    pub const MAX_NAME_LEN: usize = 32;
+    // NFTs a bundle can add on top of the listing's own
+    pub const MAX_BUNDLE_NFTS: usize = 8;
+    // Winners an edition auction can have; one bit each in `editions_claimed`
+    pub const MAX_EDITIONS: usize = 16;
+    // Recipients a seller can split the proceeds between
+    pub const MAX_PAYOUT_SPLITS: usize = 5;
+    // Changes of lead a candle auction's window can record
+    pub const MAX_CANDLE_MARKS: usize = 16;
+    // Longest term a rental listing can offer
+    pub const MAX_RENTAL_DAYS: u16 = 365;
+    // How long a winner has to take delivery before the seller may keep their bid bond
+    pub const BOND_CLAIM_WINDOW: i64 = 7 * 24 * 60 * 60;
+    // Offers kept behind the leader of a single-winner auction
+    pub const MAX_RUNNERS_UP: usize = 3;
+    // Delegated bids kept to fall back on, the leader's included
+    pub const MAX_DELEGATED_BIDS: usize = 8;
+    pub const SPACE: usize =
+        8 + // discriminator
+        1 + // version
+        32 + (4 + AuctionDetails::MAX_NAME_LEN) + // listing_id, name
+        8 + 32 + 8 + // highest_bid, highest_bidder, highest_max_bid
+        (4 + AuctionDetails::MAX_INLINE_BIDDERS * BidEntry::SIZE) + // bids
+        8 + 8 + 8 + 1 + 1 + 8 + 32 + // minimum_bid .. owner
+        4 + 8 + (1 + 32) + // bid_pages, paged_total, settlement_hook
+        (1 + ExternalEscrow::SIZE) +
+        (1 + RandomnessCommitment::SIZE) +
+        (1 + 32) + 4 + 8 + // position_tree, compressed_positions, compressed_total
+        (1 + 32) + (1 + FeeOverride::SIZE) + 8 + // collection, fee_override, collection_permissions
+        (1 + 32) + (1 + 32) + // payment_processor, off_chain_reference
+        (4 + AuctionDetails::MAX_URI_LENGTH) + // metadata_uri
+        8 + 8 + 8 + 8 + 8 + (1 + 32) + 1 + 1 + // reserve_price .. holds_seller_slot
+        1 + 1 + // bump, vault_bump
+        (1 + 32) + 1 + // nft_mint, asset_standard
+        (1 + 8) + 1 + // buy_now_price, nft_claimed
+        8 + 8 + 4 + // sniping_time_window, time_extension, extensions
+        (4 + AuctionDetails::MAX_BUNDLE_NFTS * 32) + // bundle_mints
+        1 + (4 + AuctionDetails::MAX_EDITIONS * BidEntry::SIZE) + 8 + 2 + // editions .. editions_claimed
+        (1 + 32) + // allowlist_root
+        (1 + 32) + // gate_mint
+        1 + // seller_paused
+        (4 + AuctionDetails::MAX_PAYOUT_SPLITS * PayoutSplit::SIZE) + // payout_splits
+        8 + // ticket_price
+        8 + 1 + // second_bid, second_price
+        8 + (4 + AuctionDetails::MAX_CANDLE_MARKS * CandleMark::SIZE) + 8 + // candle_window .. candle_cutoff
+        2 + 8 + // rental_days, rental_expires_at
+        (1 + 32) + // payment_mint
+        (1 + UsdPricing::SIZE) + 8 + // usd_pricing, usd_priced_at
+        8 + 8 + // bid_bond, bonds_held
+        8 + 8 + 1 + 1 + // seller_collateral .. collateral_slashed
+        (1 + 1) + // alien_attestation
+        (1 + 32) + // bid_snapshot
+        (4 + AuctionDetails::MAX_RUNNERS_UP * BidEntry::SIZE) + // runners_up
+        (1 + InstallmentPlan::SIZE) + // installment_plan
+        1 + (4 + AuctionDetails::MAX_DELEGATED_BIDS * DelegatedBid::SIZE) + // delegated_bidding, delegated_bids
+        2 + 8 + // max_bids_per_bidder, bid_cooldown
+        1 + // co_owned
+        (1 + ProceedsVesting::SIZE) + // proceeds_vesting
+        8 + // seller_fee_discount_bps
+        (1 + Beneficiary::SIZE) + // beneficiary
+        (1 + 4) + // registry_page
+        (1 + CompressedLeaf::SIZE) + // compressed_leaf
+        (1 + SettlementQuote::SIZE) + // settlement_quote
+        BidHistory::HEADER_SIZE; // bid_history, without its records
+
+    // The id `owner` lists `name` under
+    pub fn listing_id_for(owner: &Pubkey, name: &str) -> [u8; 32] {
+        hashv(&[owner.as_ref(), name.as_bytes()]).0
+    }
+
+    // What the archive keeps of the auction once it's pruned: the keccak hash
+    // of listing_id | owner | highest_bidder | highest_bid | closed_at | status
+    pub fn archive_leaf(&self) -> [u8; 32] {
+        hashv(
+            &[
+                self.listing_id.as_ref(),
+                self.owner.as_ref(),
+                self.highest_bidder.as_ref(),
+                &self.highest_bid.to_le_bytes(),
+                &self.closed_at.to_le_bytes(),
+                &[self.status as u8],
+            ]
+        ).0
+    }
+
+    // Room for an auction keeping the last `bid_history_capacity` bids
+    pub fn space(bid_history_capacity: u16) -> usize {
+        AuctionDetails::SPACE + (bid_history_capacity as usize) * BidRecord::SIZE
+    }
+
+    // Closed without a sale, so the item is free to go back to the seller or be relisted
+    pub fn is_unsold(&self) -> bool {
+        match self.status {
+            AuctionStatus::Cancelled | AuctionStatus::Voided => true,
+            AuctionStatus::Ended => !self.has_bids(),
+            _ => false,
+        }
+    }
+
+    // A crowd sale delivered off-chain whose seller let the delivery window run
+    // out without confirming
+    pub fn delivery_overdue(&self, now: i64) -> bool {
+        self.seller_collateral > 0 &&
+            !self.delivery_confirmed &&
+            self.is_sold() &&
+            now >= self.closed_at.saturating_add(self.delivery_window)
+    }
+
+    // A sale stays in escrow for `window` seconds after it closes, and for as
+    // long as a dispute over it is open
+    pub fn in_dispute_hold(&self, window: i64, now: i64) -> bool {
+        let held = self.status == AuctionStatus::Ended && now < self.closed_at.saturating_add(window);
+        self.status == AuctionStatus::Disputed || held
+    }
+
+    // Whether a losing bid can be taken off the auction before it ends. Not for
+    // listings whose outcome depends on every bid placed, nor delegated bids,
+    // which hold nothing to retract.
+    pub fn allows_retraction(&self) -> bool {
+        !self.is_alien &&
+            self.ticket_price == 0 &&
+            self.candle_window == 0 &&
+            !self.second_price &&
+            !self.delegated_bidding
+    }
+
+    // A single sale whose proceeds are still escrowed, which a dispute can unwind
+    pub fn is_disputable(&self) -> bool {
+        matches!(self.status, AuctionStatus::Ended | AuctionStatus::Disputed) &&
+            self.has_bids() &&
+            self.editions == 0 &&
+            self.ticket_price == 0 &&
+            !self.is_alien
+    }
+
+    // Closed with a winner, whose NFT is theirs to claim
+    pub fn is_sold(&self) -> bool {
+        match self.status {
+            AuctionStatus::Settled | AuctionStatus::Disputed => true,
+            AuctionStatus::Ended => self.has_bids(),
+            _ => false,
+        }
+    }
+
+    // Mint of the listing's currency, which keys its fee treasury; the native mint for SOL
+    pub fn currency(&self) -> Pubkey {
+        self.payment_mint.unwrap_or(native_mint::ID)
+    }
+
+    // Convert a USD-priced listing's minimum bid and reserve to lamports at
+    // `price`, a SOL/USD price. Every direct bid does this before it's checked.
+    pub fn reprice(&mut self, price: &OraclePrice, now: i64) -> Result<()> {
+        if let Some(pricing) = self.usd_pricing {
+            self.minimum_bid = price.lamports_for_usd(pricing.minimum_bid_usd)?.max(1);
+            self.reserve_price = price.lamports_for_usd(pricing.reserve_usd)?;
+            self.usd_priced_at = now;
+        }
+        Ok(())
+    }
+
+    // Whether any funds have been escrowed for this auction yet
+    pub fn has_bids(&self) -> bool {
+        self.highest_bid > 0 || !self.bids.is_empty() || self.compressed_positions > 0
+    }
+
+    // Whether `winner` has claimed what they won
+    pub fn has_taken_delivery(&self, winner: &Pubkey) -> bool {
+        match self.edition_of(winner) {
+            Some(index) => self.editions_claimed & (1 << index) != 0,
+            None => self.nft_claimed,
+        }
+    }
+
+    // Binary search over the inline positions: Ok is the bidder's slot, Err the
+    // slot a new position for them belongs in
+    pub(crate) fn find_bidder(&self, bidder: &Pubkey) -> std::result::Result<usize, usize> {
+        self.bids.binary_search_by(|entry| entry.bidder.cmp(bidder))
+    }
+
+    pub fn bid_of(&self, bidder: &Pubkey) -> Option<&BidEntry> {
+        self.find_bidder(bidder)
+            .ok()
+            .map(|index| &self.bids[index])
+    }
+
+    pub fn bid_of_mut(&mut self, bidder: &Pubkey) -> Option<&mut BidEntry> {
+        let index = self.find_bidder(bidder).ok()?;
+        self.bids.get_mut(index)
+    }
+
+    // The bidder's running total, looked up the way `record_bid` finds it: inline
+    // first, then on `page`
+    pub fn position_of(&self, bidder: &Pubkey, page: Option<&BidPage>) -> u64 {
+        match self.bid_of(bidder) {
+            Some(bid) => bid.amount,
+            None => page.and_then(|page| page.find(bidder)).map_or(0, |slot| slot.amount),
+        }
+    }
+
+    // Add `amount` to the bidder's running total and return the new total.
+    // Account-backed auctions spill new bidders into `page` once the inline list
+    // is at capacity; compressed auctions only keep the aggregate here and the
+    // caller, which proves the leaf, learns the total (so this returns None).
+    // A proxy bid makes the whole total the bidder's ceiling; a plain bid drops it.
+    pub fn record_bid(
+        &mut self,
+        bidder: Pubkey,
+        amount: u64,
+        time: i64,
+        store: PositionStore,
+        proxy: bool,
+        referrer: Option<Pubkey>
+    ) -> Result<Option<u64>> {
+        let page = match store {
+            PositionStore::Accounts(page) => {
+                require!(self.position_tree.is_none(), ErrorCode::CompressedPositionsRequired);
+                page
+            }
+            PositionStore::Compressed => {
+                require!(self.position_tree.is_some(), ErrorCode::CompressedPositionsDisabled);
+                require!(!proxy, ErrorCode::ProxyBidUnavailable);
+                self.compressed_total = self.compressed_total.try_add(amount)?;
+                return Ok(None);
+            }
+        };
+
+        match self.find_bidder(&bidder) {
+            Ok(index) => {
+                let bid = &mut self.bids[index];
+                bid.amount = bid.amount.try_add(amount)?;
+                bid.time = time;
+                bid.max_bid = if proxy { bid.amount } else { 0 };
+                bid.referrer = bid.referrer.or(referrer);
+                return Ok(Some(bid.amount));
+            }
+            Err(index) if self.bids.len() < AuctionDetails::MAX_INLINE_BIDDERS => {
+                let max_bid = if proxy { amount } else { 0 };
+                self.bids.insert(index, BidEntry { bidder, amount, time, max_bid, referrer });
+                return Ok(Some(amount));
+            }
+            Err(_) => {}
+        }
+
+        let page = page.ok_or(ErrorCode::BidPageRequired)?;
+        if let Some(slot) = page.find_mut(&bidder) {
+            slot.amount = slot.amount.try_add(amount)?;
+            slot.time = time;
+            slot.max_bid = if proxy { slot.amount } else { 0 };
+            if slot.referrer().is_none() {
+                slot.referrer = referrer.unwrap_or_default();
+            }
+            self.paged_total = self.paged_total.try_add(amount)?;
+            return Ok(Some(slot.amount));
+        }
+
+        // New bidders always go to the newest page
+        require!(page.index.try_add(1)? == self.bid_pages, ErrorCode::BidPageRequired);
+        require!(!page.is_full(), ErrorCode::BidPageFull);
+        let max_bid = if proxy { amount } else { 0 };
+        page.insert(BidSlot {
+            bidder,
+            amount,
+            time,
+            max_bid,
+            referrer: referrer.unwrap_or_default(),
+        })?;
+        self.paged_total = self.paged_total.try_add(amount)?;
+        Ok(Some(amount))
+    }
+
+    // Smallest position that takes the lead: the minimum bid at first, then the
+    // highest bid plus its increment. In an edition auction, the smallest that
+    // gets into the book: anything from the minimum while it has room, then the
+    // lowest winning position plus its increment.
+    pub fn min_next_bid(&self) -> Result<u64> {
+        if self.editions > 0 {
+            return match self.edition_book.last() {
+                Some(lowest) if self.edition_book.len() == (self.editions as usize) =>
+                    lowest.amount.try_add(self.increment_over(lowest.amount)?),
+                _ => Ok(self.minimum_bid),
+            };
+        }
+        if self.highest_bid == 0 {
+            return Ok(self.minimum_bid);
+        }
+        self.highest_bid.try_add(self.increment_over(self.highest_bid)?)
+    }
+
+    // Whether `address` currently holds a winning position
+    pub fn is_winner(&self, address: &Pubkey) -> bool {
+        if self.editions > 0 {
+            self.edition_of(address).is_some()
+        } else {
+            self.highest_bid > 0 && self.highest_bidder == *address
+        }
+    }
+
+    // Slot of `address` in an edition auction's book
+    pub fn edition_of(&self, address: &Pubkey) -> Option<usize> {
+        self.edition_book.iter().position(|entry| entry.bidder == *address)
+    }
+
+    // What a single winner pays
+    pub fn winning_price(&self) -> u64 {
+        if self.editions > 0 { self.clearing_price } else { self.single_winner_price() }
+    }
+
+    // A single winner pays their own bid, or on a second-price listing the best
+    // competing offer, but never less than the minimum bid or the reserve; on
+    // an installment listing, that many times over
+    fn single_winner_price(&self) -> u64 {
+        let installment = self.installment_amount();
+        match self.installment_plan {
+            Some(plan) => installment.saturating_mul(plan.count as u64),
+            None => installment,
+        }
+    }
+
+    // The winning bid, which on an installment listing is each installment
+    pub fn installment_amount(&self) -> u64 {
+        if !self.second_price {
+            return self.highest_bid;
+        }
+        self.second_bid.max(self.minimum_bid).max(self.reserve_price).min(self.highest_bid)
+    }
+
+    // Put `bid` among the delegated bids by amount, replacing the bidder's
+    // earlier one; only the best MAX_DELEGATED_BIDS are kept
+    pub fn rank_delegated_bid(&mut self, bid: DelegatedBid) {
+        self.delegated_bids.retain(|entry| entry.bidder != bid.bidder);
+        let index = self.delegated_bids.partition_point(|entry| entry.credited >= bid.credited);
+        self.delegated_bids.insert(index, bid);
+        self.delegated_bids.truncate(AuctionDetails::MAX_DELEGATED_BIDS);
+    }
+
+    // Whether bids are rate limited per bidder, which takes a bid receipt to track
+    pub fn has_bid_limits(&self) -> bool {
+        self.max_bids_per_bidder > 0 || self.bid_cooldown > 0
+    }
+
+    // Whether the winner has paid everything they owe; always so without a plan
+    pub fn installments_complete(&self) -> bool {
+        self.installment_plan.map_or(true, |plan| plan.paid >= plan.count)
+    }
+
+    // What the sale raised in total, before fees and royalties
+    pub fn sale_proceeds(&self) -> Result<u64> {
+        if self.editions > 0 {
+            self.clearing_price.try_mul(self.edition_book.len() as u64)
+        } else {
+            Ok(self.single_winner_price())
+        }
+    }
+
+    // Put `bidder`'s position, now totalling `total`, into the edition book. A
+    // position already in the book only moves up; a new one has to beat the
+    // lowest winning position, which then drops out. Equal positions keep the
+    // order they got in.
+    fn enter_book(&mut self, bidder: Pubkey, total: u64) -> Result<()> {
+        let time = Clock::get()?.unix_timestamp;
+        match self.edition_of(&bidder) {
+            Some(index) => {
+                self.edition_book.remove(index);
+            }
+            None => {
+                require!(total >= self.min_next_bid()?, ErrorCode::BidTooLow);
+            }
+        }
+        let index = self.edition_book.partition_point(|entry| entry.amount >= total);
+        self.edition_book.insert(index, BidEntry {
+            bidder,
+            amount: total,
+            time,
+            max_bid: 0,
+            referrer: None,
+        });
+        self.edition_book.truncate(self.editions as usize);
+
+        self.highest_bid = self.edition_book[0].amount;
+        self.highest_bidder = self.edition_book[0].bidder;
+        self.highest_max_bid = self.highest_bid;
+        Ok(())
+    }
+
+    // Fix the price of an ended edition auction: positions below the reserve
+    // drop out, and every remaining winner pays the lowest winning position
+    pub fn close_edition_book(&mut self) -> Result<()> {
+        let reserve_price = self.reserve_price;
+        self.edition_book.retain(|entry| entry.amount >= reserve_price);
+        let lowest = self.edition_book.last().ok_or(ErrorCode::ReserveNotMet)?;
+        self.clearing_price = lowest.amount;
+        Ok(())
+    }
+
+    // The raise needed over `amount`: the larger of the absolute and percentage
+    // increments, and always at least one lamport
+    fn increment_over(&self, amount: u64) -> Result<u64> {
+        let percentage = math::compute_fee(amount, self.min_increment_bps)?;
+        Ok(percentage.max(self.min_increment).max(1))
+    }
+
+    // Update the lead now that `bidder`'s position totals `total`. A plain bid
+    // bids exactly `total`. A proxy bid offers up to `total`, and the visible
+    // price only rises as far as needed to beat the competing ceiling.
+    pub fn resolve_bid(&mut self, bidder: Pubkey, total: u64, proxy: bool) -> Result<()> {
+        if self.editions > 0 {
+            // Every winner pays the same clearing price, so there's nothing for a proxy to do
+            require!(!proxy, ErrorCode::ProxyBidUnavailable);
+            return self.enter_book(bidder, total);
+        }
+        let leader_before = (self.highest_bidder, self.highest_max_bid);
+        if bidder == self.highest_bidder && self.highest_bid > 0 {
+            // The leader raising their own ceiling doesn't move the price
+            if !proxy {
+                require!(total >= self.min_next_bid()?, ErrorCode::BidTooLow);
+                self.highest_bid = total;
+            }
+            self.highest_max_bid = self.highest_max_bid.max(total);
+        } else {
+            require!(total >= self.min_next_bid()?, ErrorCode::BidTooLow);
+            let leader_max = self.highest_max_bid;
+            if self.highest_bid == 0 || total > leader_max {
+                // The displaced leader's ceiling was their best offer
+                if self.highest_bid > 0 {
+                    self.second_bid = leader_max;
+                }
+                self.highest_bid = match (proxy, self.highest_bid) {
+                    (false, _) => total,
+                    (true, 0) => self.minimum_bid,
+                    (true, _) => total.min(leader_max.saturating_add(self.increment_over(leader_max)?)),
+                };
+                self.highest_bidder = bidder;
+                self.highest_max_bid = total;
+            } else {
+                // Outbid straight away by the leader's proxy, up to its ceiling
+                self.highest_bid = leader_max.min(total.saturating_add(self.increment_over(total)?));
+                self.second_bid = self.second_bid.max(total);
+            }
+        }
+
+        // A ceiling that covers the reserve lifts the price to it
+        if self.highest_max_bid >= self.reserve_price {
+            self.highest_bid = self.highest_bid.max(self.reserve_price);
+        }
+
+        let time = Clock::get()?.unix_timestamp;
+        if leader_before.0 != self.highest_bidder && leader_before.1 > 0 {
+            self.rank_runner_up(leader_before.0, leader_before.1, time);
+        }
+        if bidder == self.highest_bidder {
+            self.runners_up.retain(|entry| entry.bidder != bidder);
+        } else {
+            self.rank_runner_up(bidder, total, time);
+        }
+        Ok(())
+    }
+
+    // Put `bidder`'s offer of `amount` into the runners-up, where it stays
+    // only while it's among the best MAX_RUNNERS_UP
+    fn rank_runner_up(&mut self, bidder: Pubkey, amount: u64, time: i64) {
+        self.runners_up.retain(|entry| entry.bidder != bidder);
+        let index = self.runners_up.partition_point(|entry| entry.amount >= amount);
+        self.runners_up.insert(index, BidEntry {
+            bidder,
+            amount,
+            time,
+            max_bid: 0,
+            referrer: None,
+        });
+        self.runners_up.truncate(AuctionDetails::MAX_RUNNERS_UP);
+    }
+
+    // The best runner-up still backing their offer with an inline position
+    // that clears the minimum and the reserve, and what they'd win at: their
+    // offer, or less if they have since withdrawn part of it
+    pub fn next_in_line(&self) -> Option<(Pubkey, u64)> {
+        let floor = self.minimum_bid.max(self.reserve_price);
+        self.runners_up.iter().find_map(|entry| {
+            let price = self.bid_of(&entry.bidder)?.amount.min(entry.amount);
+            (price > 0 && price >= floor).then_some((entry.bidder, price))
+        })
+    }
+
+    // Note a change of lead during a candle auction's window, so its close can
+    // tell who led at the drawn cutoff. The first one also records who led going
+    // into the window.
+    pub fn mark_candle(&mut self, lead_before: (Pubkey, u64), now: i64) -> Result<()> {
+        let window_start = self.end_time.saturating_sub(self.candle_window);
+        let lead = (self.highest_bidder, self.highest_bid);
+        if now < window_start || lead == lead_before {
+            return Ok(());
+        }
+        if self.candle_marks.is_empty() && lead_before.1 > 0 {
+            self.candle_marks.push(CandleMark {
+                bidder: lead_before.0,
+                bid: lead_before.1,
+                time: window_start,
+            });
+        }
+        require!(
+            self.candle_marks.len() < AuctionDetails::MAX_CANDLE_MARKS,
+            ErrorCode::CandleMarksFull
+        );
+        self.candle_marks.push(CandleMark { bidder: lead.0, bid: lead.1, time: now });
+        Ok(())
+    }
+
+    // Amount the program still owes on this auction's recorded bids
+    pub fn outstanding(&self) -> Result<u64> {
+        let mut recorded = self.paged_total.try_add(self.compressed_total)?;
+        for bid in &self.bids {
+            recorded = recorded.try_add(bid.amount)?;
+        }
+        let held = self.bonds_held.try_add(self.seller_collateral)?;
+        if self.status == AuctionStatus::Settled {
+            // The winning bids and the buyer fees have been paid out at settlement,
+            // but for proceeds still vesting
+            let vesting = self.proceeds_vesting.map_or(0, |vesting| vesting.unclaimed());
+            recorded.saturating_sub(self.sale_proceeds()?).try_add(held)?.try_add(vesting)
+        } else {
+            recorded.try_add(self.fees)?.try_add(held)
+        }
+    }
+
+    // Put an ended auction whose winner defaulted, with no one left to re-award
+    // it to, back up for bids from scratch for as long as it first ran. The
+    // only way from Ended back to Live, so it bypasses `transition_to`.
+    pub fn reopen(&mut self, now: i64) -> Result<()> {
+        require!(self.status == AuctionStatus::Ended, ErrorCode::InvalidStatusTransition);
+        let duration = self.end_time.saturating_sub(self.created_at).max(0);
+        self.status = AuctionStatus::Live;
+        self.end_time = now.try_add(duration)?;
+        self.highest_bid = 0;
+        self.highest_bidder = Pubkey::default();
+        self.highest_max_bid = 0;
+        self.second_bid = 0;
+        self.runners_up.clear();
+        self.extensions = 0;
+        self.candle_marks.clear();
+        self.candle_cutoff = 0;
+        self.bid_snapshot = None;
+        Ok(())
+    }
+
+    // Move the auction to `next`, rejecting transitions the lifecycle doesn't allow
+    pub fn transition_to(&mut self, next: AuctionStatus) -> Result<()> {
+        let previous = self.status;
+        require!(previous.can_transition_to(next), ErrorCode::InvalidStatusTransition);
+        self.status = next;
+        // Opening and rejecting a dispute leave the sale's closing time, and so its window, alone
+        let dispute_step = matches!(
+            (previous, next),
+            (_, AuctionStatus::Disputed) | (AuctionStatus::Disputed, AuctionStatus::Ended)
+        );
+        if next.is_closed() && !dispute_step {
+            self.closed_at = Clock::get()?.unix_timestamp;
+        }
+        Ok(())
+    }
+}
+
+// Where a bid's running total is kept
+pub enum PositionStore<'a> {
+    // Inline on the auction, or on the given overflow page
+    Accounts(Option<&'a mut BidPage>),
+    // A leaf in the auction's position tree
+    Compressed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AuctionStatus {
+    Scheduled,
+    Live,
+    Paused,
+    Ended,
+    Settled,
+    Cancelled,
+    Voided,
+    AwaitingRandomness, // Closed to bids until the committed randomness decides the outcome
+    Disputed, // Ended sale held in escrow until the arbiter resolves the dispute over it
+}
+
+impl AuctionStatus {
+    pub fn can_transition_to(self, next: AuctionStatus) -> bool {
+        use AuctionStatus::*;
+        matches!(
+            (self, next),
+            (Scheduled, Live) |
+                (Scheduled, Cancelled) |
+                (Live, Paused) |
+                (Live, Ended) |
+                (Live, Cancelled) |
+                (Live, Voided) |
+                (Live, AwaitingRandomness) |
+                (Paused, Live) |
+                (Paused, Voided) |
+                (Ended, Settled) |
+                (Ended, Voided) |
+                (Ended, Disputed) |
+                (Disputed, Ended) |
+                (Disputed, Voided) |
+                (AwaitingRandomness, Ended) |
+                (AwaitingRandomness, Cancelled)
+        )
+    }
+
+    // Settled, cancelled and voided auctions can't change status any more
+    pub fn is_final(self) -> bool {
+        matches!(self, AuctionStatus::Settled | AuctionStatus::Cancelled | AuctionStatus::Voided)
+    }
+
+    // Only scheduled, live and paused auctions can still take bids
+    pub fn is_closed(self) -> bool {
+        matches!(
+            self,
+            AuctionStatus::Ended |
+                AuctionStatus::Settled |
+                AuctionStatus::Cancelled |
+                AuctionStatus::Voided |
+                AuctionStatus::AwaitingRandomness |
+                AuctionStatus::Disputed
+        )
+    }
+}
+
+// A listing at a glance. The bids are the largest positions, leader first;
+// `num_bidders` counts every inline position.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AuctionDetailsResponse {
+    pub listing_id: [u8; 32],
+    pub highest_bid: u64,
+    pub highest_bidder: Pubkey,
+    pub minimum_bid: u64,
+    pub status: AuctionStatus,
+    pub owner: Pubkey,
+    pub end_time: i64,
+    pub bidders: Vec<Pubkey>,
+    pub num_bidders: u64,
+    pub amounts: Vec<u64>, // Each bidder's running total
+    pub times: Vec<i64>, // When each bidder last bid
+    pub paused: bool,
+    pub is_alien: bool,
+    pub total_amount: u64,
+    pub buy_now_price: Option<u64>,
+    pub payment_mint: Option<Pubkey>,
+    pub min_next_bid: u64, // Smallest bid that takes the lead now
+}
+
+// A bidder's inline position on a listing.
+// Encoded as bidder (32 bytes) | amount (u64) | time (i64).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UserBidResponse {
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub time: i64,
+}
+
+// The leading bid and the time left to beat it. Encoded as highest_bidder
+// (32 bytes) | highest_bid (u64) | end_time (i64) | remaining_time (u64).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct HighestBidResponse {
+    pub highest_bidder: Pubkey,
+    pub highest_bid: u64,
+    pub end_time: i64,
+    pub remaining_time: u64, // Seconds until end_time; 0 once it has passed
+}
+
+// One page of an auction's bid history, newest first; `total` counts every page
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BidHistoryPage {
+    pub bidders: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub times: Vec<i64>,
+    pub total: u64,
+}
+
+// One page of a bidder's open positions; `total` counts every page
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UserBidsPage {
+    pub listing_ids: Vec<[u8; 32]>,
+    pub amounts: Vec<u64>,
+    pub times: Vec<i64>,
+    pub total: u64,
+}
+
+// A collection's floor as of now, in lamports: its cheapest sale within the
+// oracle's window. Encoded as collection (32 bytes) | floor (u64) |
+// floor_sale_time (i64) | window (i64).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FloorPriceResponse {
+    pub collection: Pubkey,
+    pub floor: u64,
+    pub floor_sale_time: i64,
+    pub window: i64,
+}
+
+// A collection's time-weighted average sale price, in lamports. `span` is the
+// part of the asked window the average actually covers, shorter when the
+// collection's kept sales don't reach back that far. Encoded as collection
+// (32 bytes) | twap (u64) | span (i64) | last_price (u64) | sales (u64).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CollectionTwapResponse {
+    pub collection: Pubkey,
+    pub twap: u64,
+    pub span: i64,
+    pub last_price: u64,
+    pub sales: u64,
+}
+
+// One page of the auction registry: live auction PDAs and their end times at
+// the same index, plus how many pages there are to page through
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AuctionRegistryResponse {
+    pub page: u32,
+    pub pages: u32,
+    pub auctions: Vec<Pubkey>,
+    pub end_times: Vec<i64>,
+}
+
+// What validate_bid makes of a bid. When `valid` is false, `error_code` is the
+// error place_bid would fail with, and only the fields filled in before the
+// failing check are set. Amounts are in the listing's currency; `fee` and
+// `credited` split the amount bid, and the rest describe the auction as the
+// bid would leave it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BidValidation {
+    pub valid: bool,
+    pub error_code: Option<u32>,
+    pub min_next_bid: u64, // Smallest position that takes the lead, before the bid
+    pub fee: u64, // Buyer fee, after any membership discount
+    pub credited: u64, // What the bid adds to the bidder's position
+    pub position: u64, // The bidder's whole position after the bid
+    pub leading: bool,
+    pub highest_bid: u64,
+    pub end_time: i64, // After any anti-sniping extension the bid triggers
+}
+
+// Overflow bidders of a single auction. Zero-copy with fixed-size slots, so
+// bidding only touches the bytes of the slot it updates instead of
+// (de)serializing the whole page. The auction account itself stays borsh: its
+// strings, vecs and enums have no fixed layout, and only its first
+// MAX_INLINE_BIDDERS bidders live on it, however many bid.
+#[account(zero_copy)]
+pub struct BidPage {
+    pub auction: Pubkey,
+    pub index: u32,
+    pub len: u32, // Slots in use, the first `len` of `slots`
+    pub bump: u8,
+    pub version: u8,
+    pub _padding: [u8; 6],
+    pub slots: [BidSlot; BidPage::CAPACITY],
+}
+
+impl BidPage {
+    pub const VERSION: u8 = 1;
+    pub const CAPACITY: usize = 64;
+    pub const SPACE: usize = 8 + std::mem::size_of::<BidPage>();
+
+    pub fn is_full(&self) -> bool {
+        (self.len as usize) >= BidPage::CAPACITY
+    }
+
+    pub fn entries(&self) -> &[BidSlot] {
+        &self.slots[..self.len as usize]
+    }
+
+    // Slots in use are kept sorted by bidder, so lookups are a binary search: Ok
+    // is the bidder's slot, Err the slot a new bidder belongs in
+    pub(crate) fn find_bidder(&self, bidder: &Pubkey) -> std::result::Result<usize, usize> {
+        self.entries().binary_search_by(|slot| slot.bidder.cmp(bidder))
+    }
+
+    pub fn find(&self, bidder: &Pubkey) -> Option<&BidSlot> {
+        let index = self.find_bidder(bidder).ok()?;
+        self.slots.get(index)
+    }
+
+    pub fn find_mut(&mut self, bidder: &Pubkey) -> Option<&mut BidSlot> {
+        let index = self.find_bidder(bidder).ok()?;
+        self.slots.get_mut(index)
+    }
+
+    // Shift the later slots up one to keep them sorted; callers check `is_full` first
+    pub fn insert(&mut self, slot: BidSlot) -> Result<()> {
+        if let Err(index) = self.find_bidder(&slot.bidder) {
+            let len = self.len as usize;
+            self.slots.copy_within(index..len, index + 1);
+            self.slots[index] = slot;
+            self.len = self.len.try_add(1)?;
+        }
+        Ok(())
+    }
+
+    // Take the bidder's slot out, shifting the later slots down to keep them sorted
+    pub fn remove(&mut self, bidder: &Pubkey) -> Option<BidSlot> {
+        let index = self.find_bidder(bidder).ok()?;
+        let slot = self.slots[index];
+        let len = self.len as usize;
+        self.slots.copy_within(index + 1..len, index);
+        self.len = self.len.checked_sub(1)?;
+        Some(slot)
+    }
+}
+
+// Ring buffer of an auction's latest bids. Its capacity is fixed when the
+// auction is listed and paid for in the account's rent, so the account never
+// outgrows its allocation however many bids come in; older bids only survive
+// in the totals and in the BidPlaced events. A growable history instead has
+// `place_bid` realloc the auction a step at a time, at the bidder's expense,
+// until it reaches MAX_GROWN_CAPACITY and starts rotating like any other.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct BidHistory {
+    pub capacity: u16,
+    pub growable: bool,
+    pub head: u16, // Slot the next bid overwrites once `records` is full
+    pub records: Vec<BidRecord>,
+    pub bid_count: u64, // Every bid ever placed, including those rotated out
+    pub bid_volume: u64, // Sum of their amounts
+}
+
+impl BidHistory {
+    pub const DEFAULT_CAPACITY: u16 = 16;
+    pub const MAX_CAPACITY: u16 = 64;
+    // Bound on how far a growable history can push the auction's rent
+    pub const MAX_GROWN_CAPACITY: u16 = 1_024;
+    pub const GROWTH_STEP: u16 = 16;
+    pub const HEADER_SIZE: usize = 2 + 1 + 2 + 4 + 8 + 8;
+
+    pub fn new(capacity: u16, growable: bool) -> Result<BidHistory> {
+        require!(
+            (1..=BidHistory::MAX_CAPACITY).contains(&capacity),
+            ErrorCode::InvalidBidHistoryCapacity
+        );
+        Ok(BidHistory { capacity, growable, ..Default::default() })
+    }
+
+    // Capacity to make room for one more bid at: a step more than now when a
+    // growable history is full, up to MAX_GROWN_CAPACITY
+    pub fn capacity_for_next_bid(&self) -> u16 {
+        if self.growable && self.records.len() >= (self.capacity as usize) {
+            self.capacity.saturating_add(BidHistory::GROWTH_STEP).min(BidHistory::MAX_GROWN_CAPACITY)
+        } else {
+            self.capacity
+        }
+    }
+
+    // Take up the room `capacity_for_next_bid` asked for. Records a path without
+    // realloc rotated in are put back in order first, so growth appends after
+    // the newest.
+    pub fn grow_for_next_bid(&mut self) {
+        let capacity = self.capacity_for_next_bid();
+        if capacity > self.capacity {
+            self.records.rotate_left(self.head as usize);
+            self.head = 0;
+            self.capacity = capacity;
+        }
+    }
+
+    pub fn push(&mut self, record: BidRecord) -> Result<()> {
+        self.bid_count = self.bid_count.try_add(1)?;
+        self.bid_volume = self.bid_volume.try_add(record.amount)?;
+        if self.records.len() < (self.capacity as usize) {
+            self.records.push(record);
+        } else if self.capacity > 0 {
+            self.records[self.head as usize] = record;
+            self.head = (self.head + 1) % self.capacity;
+        }
+        Ok(())
+    }
+
+    // Newest first
+    pub fn latest(&self) -> impl Iterator<Item = &BidRecord> {
+        let (newer, older) = self.records.split_at(self.head as usize);
+        older.iter().chain(newer).rev()
+    }
+
+    // Merkle root over the bids still in the history, so a bidder can prove
+    // taking part with `allowlist`-style proofs. Leaves are the keccak hash of
+    // bidder | amount | time, oldest first.
+    pub fn snapshot_root(&self) -> [u8; 32] {
+        let leaves: Vec<[u8; 32]> = self
+            .latest()
+            .rev()
+            .map(|record| {
+                hashv(
+                    &[
+                        record.bidder.as_ref(),
+                        &record.amount.to_le_bytes(),
+                        &record.time.to_le_bytes(),
+                    ]
+                ).0
+            })
+            .collect();
+        allowlist::root_of(leaves)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BidRecord {
+    pub bidder: Pubkey,
+    pub amount: u64, // What this bid added, after fees
+    pub time: i64,
+}
+
+impl BidRecord {
+    pub const SIZE: usize = 32 + 8 + 8;
+}
+
+// The lead of a candle auction as it stood from `time` on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CandleMark {
+    pub bidder: Pubkey,
+    pub bid: u64,
+    pub time: i64,
+}
+
+impl CandleMark {
+    pub const SIZE: usize = 32 + 8 + 8;
+}
+
+// A listing's minimum bid and reserve in millionths of a dollar, and the SOL/USD
+// feed they're converted with
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct UsdPricing {
+    pub feed: OracleFeed,
+    pub minimum_bid_usd: u64,
+    pub reserve_usd: u64, // 0 for no reserve
+}
+
+impl UsdPricing {
+    pub const SIZE: usize = 1 + 32 + 8 + 8;
+}
+
+// A bid made by approving the listing's vault as delegate over the bidder's
+// token account, collected from it only if it wins
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct DelegatedBid {
+    pub bidder: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64, // What is pulled from the account, buyer fee included
+    pub credited: u64, // The bid, net of the buyer fee
+}
+
+impl DelegatedBid {
+    pub const SIZE: usize = 32 + 32 + 8 + 8;
+}
+
+// Installments a seller lets the winner pay in. The winning bid, escrowed like
+// any other, is the first of `count` equal installments; the next is due
+// `interval` seconds after the close, and so on. The NFT stays escrowed until
+// the last is in.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct InstallmentPlan {
+    pub count: u8,
+    pub interval: i64,
+    pub penalty_bps: u16, // Share of what the winner paid the seller keeps if they miss one
+    pub paid: u8, // Installments in so far, the winning bid included
+}
+
+impl InstallmentPlan {
+    pub const SIZE: usize = 1 + 8 + 2 + 1;
+    pub const MAX_INSTALLMENTS: u8 = 12;
+
+    // When the next installment is due, given the sale closed at `closed_at`
+    pub fn next_due(&self, closed_at: i64) -> i64 {
+        closed_at.saturating_add(self.interval.saturating_mul(self.paid as i64))
+    }
+}
+
+// Seller proceeds paid out linearly over `duration` seconds from settlement
+// instead of at once. claim_proceeds fixes `total`, what the seller would have
+// been paid, and `start`; claim_vested pays out what has vested since.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ProceedsVesting {
+    pub duration: i64,
+    pub start: i64, // Settlement time; 0 until then
+    pub total: u64,
+    pub claimed: u64,
+}
+
+impl ProceedsVesting {
+    pub const SIZE: usize = 8 + 8 + 8 + 8;
+    pub const MAX_DURATION: i64 = 365 * 24 * 60 * 60;
+
+    // Of `total`, what has vested by `now`
+    pub fn vested(&self, now: i64) -> u64 {
+        if self.start == 0 {
+            return 0;
+        }
+        let elapsed = now.saturating_sub(self.start).clamp(0, self.duration);
+        (((self.total as u128) * (elapsed as u128)) / (self.duration as u128)) as u64
+    }
+
+    // Still held in escrow for the seller
+    pub fn unclaimed(&self) -> u64 {
+        self.total.saturating_sub(self.claimed)
+    }
+}
+
+// What the seller of a crowd auction delivered off-chain or cross-chain stakes
+// on delivering: `amount` lamports, to be confirmed within `delivery_window`
+// seconds of the sale closing
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SellerCollateral {
+    pub amount: u64,
+    pub delivery_window: i64,
+}
+
+// What the emitter on the other chain attests to for a crowd sale settled there
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AttestationKind {
+    Payment, // The sale's payment was received on that chain
+    NftLock, // The NFT is locked there on behalf of the contributors
+}
+
+// A bid page's fixed-layout counterpart of BidEntry
+#[zero_copy]
+pub struct BidSlot {
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub time: i64,
+    pub max_bid: u64,
+    pub referrer: Pubkey, // Pubkey::default() when the bidder came unreferred
+}
+
+impl BidSlot {
+    pub fn referrer(&self) -> Option<Pubkey> {
+        Some(self.referrer).filter(|referrer| *referrer != Pubkey::default())
+    }
+}
+
+// A bidder's running total, inline on the auction or on an overflow page
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BidEntry {
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub time: i64,
+    pub max_bid: u64, // Proxy ceiling, the whole running total; 0 for a plain bid
+    pub referrer: Option<Pubkey>, // Affiliate who brought the bidder, from their first referred bid
+}
+
+impl BidEntry {
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + (1 + 32);
+}
+
+// Marketplace-wide configuration; auctions themselves live in their own PDAs
+#[account]
+pub struct NftComAuction {
+    pub version: u8,
+    pub fee_recipient: Pubkey,
+    pub buyer_fee_bps: u64, // Basis points, at most MAX_FEE_BPS
+    pub seller_fee_bps: u64,
+    pub nft_contract: Pubkey,
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub hook_programs: Vec<Pubkey>, // Settlement hook programs approved by the admin
+    pub aggregator_programs: Vec<Pubkey>, // Programs allowed to bid via CPI
+    pub bridge_emitters: Vec<BridgeEmitter>, // Wormhole emitters allowed to place bids
+    pub escrow_adapters: Vec<Pubkey>, // External escrow programs listings may stay in
+    pub governance: Option<Pubkey>, // Realm governance account; once set, the only way to change parameters
+    pub feature_flags: u64,
+    pub param_changes_queued: u64, // Id of the next queued parameter change
+    pub swap_programs: Vec<Pubkey>, // Swap programs place_bid_with_swap may route through
+    pub payment_processors: Vec<Pubkey>, // Programs allowed to attest off-chain payments
+    // Marketplace defaults applied by initialize_auction when a listing doesn't override them
+    pub default_duration: i64,
+    pub min_increment_bps: u64,
+    pub sniping_time_window: i64, // Bids this close to the end extend the auction...
+    pub time_extension: i64, // ...by this many seconds
+    pub reserve_policy: ReservePolicy,
+    pub automation_authority: Option<Pubkey>, // Keeper allowed to run seller-side maintenance like extend_if_no_bids
+    pub archive_retention: i64, // How long finished auctions are kept before prune_archives may drop them
+    pub rent_destination: Pubkey, // Receives the rent freed by pruning
+    pub fee_manager: Option<Pubkey>, // May negotiate per-auction fees alongside the authority
+    pub keeper_tip: u64, // Paid out of the buyer fees to whoever cranks end_auction
+    pub max_extensions: u32, // Most anti-sniping extensions a single auction can get
+    pub referral_share_bps: u64, // Share of an auction's buyer fees paid to the winning bid's referrer
+    pub max_active_auctions_per_seller: u32, // Listings a seller may have open at once; 0 for no cap
+    pub dispute_window: i64, // Seconds a sale stays in escrow after it closes; 0 for none
+    pub arbiter: Option<Pubkey>, // Resolves disputes alongside the authority
+    pub max_auction_duration: i64, // Longest an auction can be extended to from its listing; 0 for none
+    pub pending_authority: Option<Pubkey>, // Proposed new authority, until it accepts
+    pub config_change_delay: i64, // Wait before a queued config change applies; 0 lets the admin change fees directly
+    pub loyalty_schedule: LoyaltySchedule, // Loyalty points paid out on bids and purchases
+    pub receipt_tree: Option<Pubkey>, // Bubblegum tree participation receipts are minted into; None turns them off
+    pub buyer_fee_tiers: Vec<BuyerFeeTier>, // Buyer fee by amount; empty for the flat buyer_fee_bps
+    pub config_frozen: bool, // Fees, fee recipient and NFT contract can no longer change
+    pub retraction_penalty_bps: u64, // Share of a retracted bid kept as a penalty, at most MAX_FEE_BPS
+    pub retraction_penalty_to_seller: bool, // Penalties go to the seller instead of the fee treasury
+}
+
+impl NftComAuction {
+    pub const VERSION: u8 = 1;
+    // feature_flags bits
+    pub const FLAG_ENFORCE_ROYALTIES: u64 = 1 << 0; // Settlement must pay the NFT's creators
+    pub const FLAG_CURATED_LISTINGS: u64 = 1 << 1; // Listed NFTs must be of an approved collection
+    pub const MAX_HOOK_PROGRAMS: usize = 8;
+    pub const MAX_AGGREGATOR_PROGRAMS: usize = 8;
+    pub const MAX_BRIDGE_EMITTERS: usize = 8;
+    pub const MAX_ESCROW_ADAPTERS: usize = 8;
+    pub const MAX_SWAP_PROGRAMS: usize = 8;
+    pub const MAX_PAYMENT_PROCESSORS: usize = 8;
+    pub const MAX_BUYER_FEE_TIERS: usize = 8;
+    // Longest single push extend_if_no_bids may apply
+    pub const MAX_NO_BID_EXTENSION: i64 = 30 * 24 * 60 * 60;
+    // Upper bound on auctions closed per prune_archives call
+    pub const MAX_PRUNE_BATCH: usize = 25;
+    // Least time a parameter change queued by governance waits before it can be executed
+    pub const PARAM_CHANGE_DELAY: i64 = 2 * 24 * 60 * 60;
+    // Paid out of the freed rent to whoever sweeps an expired listing nobody bid on
+    pub const SWEEP_BOUNTY: u64 = 10_000;
+    // Extension cap applied until the admin sets an anti-sniping config
+    pub const DEFAULT_MAX_EXTENSIONS: u32 = 20;
+    // Retention applied until the admin sets an archive policy
+    pub const DEFAULT_ARCHIVE_RETENTION: i64 = 30 * 24 * 60 * 60;
+    // Cap on each of the buyer and seller fees, in basis points (10%)
+    pub const MAX_FEE_BPS: u64 = 1_000;
+    pub const SPACE: usize =
+        8 + // discriminator
+        1 + // version
+        32 + 8 + 8 + 32 + 32 + 1 + // fee_recipient .. bump
+        (4 + NftComAuction::MAX_HOOK_PROGRAMS * 32) +
+        (4 + NftComAuction::MAX_AGGREGATOR_PROGRAMS * 32) +
+        (4 + NftComAuction::MAX_BRIDGE_EMITTERS * BridgeEmitter::SIZE) +
+        (4 + NftComAuction::MAX_ESCROW_ADAPTERS * 32) +
+        (1 + 32) + 8 + 8 + // governance, feature_flags, param_changes_queued
+        (4 + NftComAuction::MAX_SWAP_PROGRAMS * 32) +
+        (4 + NftComAuction::MAX_PAYMENT_PROCESSORS * 32) +
+        8 + 8 + 8 + 8 + 1 + // default_duration .. reserve_policy
+        (1 + 32) + 8 + 32 + // automation_authority, archive_retention, rent_destination
+        (1 + 32) + 8 + 4 + // fee_manager, keeper_tip, max_extensions
+        8 + 4 + // referral_share_bps, max_active_auctions_per_seller
+        8 + (1 + 32) + // dispute_window, arbiter
+        8 + // max_auction_duration
+        (1 + 32) + // pending_authority
+        8 + // config_change_delay
+        LoyaltySchedule::SIZE + // loyalty_schedule
+        (1 + 32) + // receipt_tree
+        (4 + NftComAuction::MAX_BUYER_FEE_TIERS * BuyerFeeTier::SIZE) + // buyer_fee_tiers
+        1 + // config_frozen
+        8 + 1; // retraction_penalty_bps, retraction_penalty_to_seller
+
+    // Queues config changes: governance once it has taken over, the admin before that
+    pub fn config_controller(&self) -> Pubkey {
+        self.governance.unwrap_or(self.authority)
+    }
+
+    // How long a config change queued now waits before it can be executed
+    pub fn config_change_wait(&self) -> i64 {
+        if self.governance.is_some() {
+            self.config_change_delay.max(NftComAuction::PARAM_CHANGE_DELAY)
+        } else {
+            self.config_change_delay
+        }
+    }
+
+    // The admin, or either fee manager: the one set on the config or the holder
+    // of the fee-manager role
+    pub fn manages_fees(&self, roles: Option<&Roles>, signer: &Pubkey) -> bool {
+        self.authority == *signer ||
+            self.fee_manager == Some(*signer) ||
+            Roles::grants(roles, Role::FeeManager, signer)
+    }
+
+    // A fee manager, or a governance proposal as it executes
+    pub fn may_withdraw_fees(&self, roles: Option<&Roles>, signer: &Pubkey) -> bool {
+        self.manages_fees(roles, signer) || realms::is_proposal_signer(self, signer)
+    }
+
+    // The admin or the holder of the pauser role
+    pub fn may_pause(&self, roles: Option<&Roles>, signer: &Pubkey) -> bool {
+        self.authority == *signer || Roles::grants(roles, Role::Pauser, signer)
+    }
+
+    // May resolve disputes over sales
+    pub fn is_arbiter(&self, address: &Pubkey) -> bool {
+        self.authority == *address || self.arbiter == Some(*address)
+    }
+
+    // Every way of setting fees goes through this, so no fee can exceed the
+    // amount it's taken from
+    pub fn validate_fees(buyer_fee_bps: u64, seller_fee_bps: u64) -> Result<()> {
+        require!(buyer_fee_bps <= NftComAuction::MAX_FEE_BPS, ErrorCode::BuyerFeeTooHigh);
+        require!(seller_fee_bps <= NftComAuction::MAX_FEE_BPS, ErrorCode::SellerFeeTooHigh);
+        Ok(())
+    }
+
+    // Fees that apply to `auction`: its own override if it has one, otherwise the
+    // global fees, lowered by its collection's fee config when that's passed
+    pub fn fees_for(
+        &self,
+        auction: &AuctionDetails,
+        collection_fees: Option<&CollectionFeeConfig>
+    ) -> FeeOverride {
+        if let Some(fees) = auction.fee_override {
+            return fees;
+        }
+        let global = FeeOverride {
+            buyer_fee_bps: self.buyer_fee_bps,
+            seller_fee_bps: self.seller_fee_bps,
+        };
+        match collection_fees {
+            Some(config) if auction.collection == Some(config.collection) =>
+                FeeOverride {
+                    buyer_fee_bps: global.buyer_fee_bps.min(config.buyer_fee_bps),
+                    seller_fee_bps: global.seller_fee_bps.min(config.seller_fee_bps),
+                },
+            _ => global,
+        }
+    }
+
+    // Buyer fee rate on a bid or purchase of `amount`, buyer fee included: that
+    // of the first tier reaching it, or the flat rate without tiers
+    pub fn buyer_fee_bps_at(&self, amount: u64) -> u64 {
+        self.buyer_fee_tiers
+            .iter()
+            .find(|tier| amount <= tier.up_to)
+            .map_or(self.buyer_fee_bps, |tier| tier.bps)
+    }
+
+    // As fees_for, for the buyer fee on `amount` when the marketplace charges by tier
+    pub fn buyer_fee_bps_for(
+        &self,
+        auction: &AuctionDetails,
+        collection_fees: Option<&CollectionFeeConfig>,
+        amount: u64
+    ) -> u64 {
+        if let Some(fees) = auction.fee_override {
+            return fees.buyer_fee_bps;
+        }
+        let tiered = self.buyer_fee_bps_at(amount);
+        match collection_fees {
+            Some(config) if auction.collection == Some(config.collection) =>
+                tiered.min(config.buyer_fee_bps),
+            _ => tiered,
+        }
+    }
+
+    // Tiers go up in amount and the last one covers every amount, so every
+    // bid falls in exactly one
+    pub fn validate_buyer_fee_tiers(tiers: &[BuyerFeeTier]) -> Result<()> {
+        require!(tiers.len() <= NftComAuction::MAX_BUYER_FEE_TIERS, ErrorCode::InvalidFeeTiers);
+        require!(
+            tiers.windows(2).all(|pair| pair[0].up_to < pair[1].up_to) &&
+                tiers.last().map_or(true, |tier| tier.up_to == u64::MAX),
+            ErrorCode::InvalidFeeTiers
+        );
+        for tier in tiers {
+            require!(tier.bps <= NftComAuction::MAX_FEE_BPS, ErrorCode::BuyerFeeTooHigh);
+        }
+        Ok(())
+    }
+
+    // Once frozen, the parameters users rely on stay as they are for good
+    pub fn ensure_unfrozen(&self) -> Result<()> {
+        require!(!self.config_frozen, ErrorCode::ConfigFrozen);
+        Ok(())
+    }
+
+    // Callers validate the new fees first
+    pub fn set_fee_recipient(&mut self, fee_recipient: Pubkey) -> Result<()> {
+        self.ensure_unfrozen()?;
+        emit!(FeeRecipientChanged { old_fee_recipient: self.fee_recipient, fee_recipient });
+        self.fee_recipient = fee_recipient;
+        Ok(())
+    }
+
+    pub fn update_fees(&mut self, buyer_fee_bps: u64, seller_fee_bps: u64) -> Result<()> {
+        self.ensure_unfrozen()?;
+        emit!(FeesUpdated {
+            old_buyer_fee_bps: self.buyer_fee_bps,
+            old_seller_fee_bps: self.seller_fee_bps,
+            buyer_fee_bps,
+            seller_fee_bps,
+        });
+        self.buyer_fee_bps = buyer_fee_bps;
+        self.seller_fee_bps = seller_fee_bps;
+        Ok(())
+    }
+
+    pub fn enforces_royalties(&self) -> bool {
+        self.feature_flags & NftComAuction::FLAG_ENFORCE_ROYALTIES != 0
+    }
+
+    pub fn curates_listings(&self) -> bool {
+        self.feature_flags & NftComAuction::FLAG_CURATED_LISTINGS != 0
+    }
+}
+
+// Buyer fee charged on bids and purchases of up to `up_to` lamports or tokens,
+// buyer fee included, that a lower tier doesn't cover
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct BuyerFeeTier {
+    pub up_to: u64,
+    pub bps: u64,
+}
+
+impl BuyerFeeTier {
+    pub const SIZE: usize = 8 + 8;
+}
+
+// Default auction parameters, set together by the admin
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct AuctionDefaults {
+    pub default_duration: i64,
+    pub min_increment_bps: u64,
+    pub sniping_time_window: i64,
+    pub time_extension: i64,
+    pub reserve_policy: ReservePolicy,
+}
+
+impl AuctionDefaults {
+    pub fn is_valid(&self) -> bool {
+        self.default_duration > 0 &&
+            self.sniping_time_window >= 0 &&
+            self.time_extension >= 0 &&
+            self.min_increment_bps <= math::BPS_DENOMINATOR
+    }
+}
+
+// How the listed NFT is represented on chain, which decides how it's escrowed and delivered
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AssetStandard {
+    Legacy, // SPL token mint with Token Metadata, escrowed in a token account
+    Core, // Metaplex Core asset, escrowed by making the state PDA its owner
+    Programmable, // Token Metadata programmable NFT, escrowed through Token Metadata's transfer
+    Compressed, // Bubblegum cNFT, escrowed by making the state PDA its leaf owner
+}
+
+// A creator as a Bubblegum leaf's creator hash commits to it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CompressedCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+impl CompressedCreator {
+    pub const SIZE: usize = 32 + 1 + 1;
+}
+
+// A Bubblegum cNFT's leaf: where it sits and the hashes it commits to, which
+// every transfer of it has to repeat. The royalty terms behind those hashes
+// are proven against them at listing and kept for settlement.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct CompressedLeaf {
+    pub merkle_tree: Pubkey,
+    pub nonce: u64,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CompressedCreator>,
+}
+
+impl CompressedLeaf {
+    // Same limit as Bubblegum
+    pub const MAX_CREATORS: usize = 5;
+    pub const SIZE: usize =
+        32 + 8 + 32 + 32 + 2 + (4 + CompressedLeaf::MAX_CREATORS * CompressedCreator::SIZE);
+}
+
+// An amount owed to one wallet out of a sale
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Payout {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+impl Payout {
+    pub const SIZE: usize = 32 + 8;
+}
+
+// How a closed sale's proceeds divide: the fee treasury's cut, the winning
+// bid's referrer's share of the buyer fees, each creator's royalty and what's
+// left for the seller. `finalize_auction` works it out once the sale closes so
+// `settle_funds` only has to pay it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct SettlementQuote {
+    pub fee: u64,
+    pub owner_earnings: u64,
+    pub referral: Option<Payout>,
+    pub royalties: Vec<Payout>,
+}
+
+impl SettlementQuote {
+    // Same limit as Token Metadata's creators
+    pub const MAX_CREATORS: usize = 5;
+    pub const SIZE: usize =
+        8 + 8 + (1 + Payout::SIZE) + (4 + SettlementQuote::MAX_CREATORS * Payout::SIZE);
+}
+
+// What a listing's reserve is when the seller doesn't set one
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReservePolicy {
+    NoReserve,
+    MatchMinimum,
+    Required, // Sellers must always set a reserve explicitly
+}
+
+// Per-listing values taking precedence over the marketplace defaults
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ListingOverrides {
+    pub end_time: Option<i64>,
+    pub reserve_price: Option<u64>,
+    pub min_increment_bps: Option<u64>,
+    pub min_increment: Option<u64>,
+    pub buy_now_price: Option<u64>,
+    pub sniping_time_window: Option<i64>,
+    pub time_extension: Option<i64>,
+    pub allowlist_root: Option<[u8; 32]>,
+    pub gate_mint: Option<Pubkey>,
+    pub payout_splits: Option<Vec<PayoutSplit>>,
+    pub bid_history_capacity: Option<u16>,
+    pub grow_bid_history: Option<bool>,
+    pub second_price: Option<bool>,
+    pub candle_window: Option<i64>,
+    pub rental_days: Option<u16>,
+    pub payment_mint: Option<Pubkey>,
+    pub usd_pricing: Option<UsdPricing>,
+    pub name: Option<String>,
+    pub co_owned: Option<bool>, // Splits go to co-owners, who must consent; initialize_auction only
+    pub beneficiary: Option<Beneficiary>,
+}
+
+impl ListingOverrides {
+    pub fn bid_history_capacity(&self) -> u16 {
+        self.bid_history_capacity.unwrap_or(BidHistory::DEFAULT_CAPACITY)
+    }
+}
+
+// A co-owner's standing consent to be listed under `listing_id` for
+// `share_bps` of the proceeds, at [CO_SELLER_APPROVAL_SEED, listing_id, co_seller]
+#[account]
+pub struct CoSellerApproval {
+    pub version: u8,
+    pub listing_id: [u8; 32],
+    pub co_seller: Pubkey,
+    pub share_bps: u16,
+    pub bump: u8,
+}
+
+impl CoSellerApproval {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 32 + 2 + 1;
+}
+
+// One recipient's share of a listing's proceeds, e.g. artist 70% and gallery 30%
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct PayoutSplit {
+    pub recipient: Pubkey,
+    pub share_bps: u16,
+}
+
+impl PayoutSplit {
+    pub const SIZE: usize = 32 + 2;
+
+    // Up to MAX_PAYOUT_SPLITS distinct recipients, each with a share, together 100%
+    pub fn validate(splits: &[PayoutSplit]) -> Result<()> {
+        require!(splits.len() <= AuctionDetails::MAX_PAYOUT_SPLITS, ErrorCode::InvalidPayoutSplits);
+        if splits.is_empty() {
+            return Ok(());
+        }
+        let total: u64 = splits.iter().map(|split| split.share_bps as u64).sum();
+        require!(total == math::BPS_DENOMINATOR, ErrorCode::InvalidPayoutSplits);
+        for (index, split) in splits.iter().enumerate() {
+            require!(split.share_bps > 0, ErrorCode::InvalidPayoutSplits);
+            require!(
+                !splits[..index].iter().any(|other| other.recipient == split.recipient),
+                ErrorCode::InvalidPayoutSplits
+            );
+        }
+        Ok(())
+    }
+
+    // Divide `amount` by share; rounding dust goes to the first recipient
+    pub fn amounts(splits: &[PayoutSplit], amount: u64) -> Result<Vec<(Pubkey, u64)>> {
+        let mut amounts = splits
+            .iter()
+            .map(|split| Ok((split.recipient, math::compute_fee(amount, split.share_bps.into())?)))
+            .collect::<Result<Vec<(Pubkey, u64)>>>()?;
+        let paid = amounts
+            .iter()
+            .try_fold(0u64, |total, (_, share)| total.try_add(*share))?;
+        if let Some(first) = amounts.first_mut() {
+            first.1 = first.1.try_add(amount.try_sub(paid)?)?;
+        }
+        Ok(amounts)
+    }
+}
+
+// A charity or other cause the seller gives `share_bps` of their proceeds to,
+// e.g. 20%, taken after fees and royalties and before any payout splits
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Beneficiary {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+}
+
+impl Beneficiary {
+    pub const SIZE: usize = 32 + 2;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.share_bps > 0 && (self.share_bps as u64) <= math::BPS_DENOMINATOR,
+            ErrorCode::InvalidBeneficiary
+        );
+        Ok(())
+    }
+}
+
+// Fees (basis points, like the global ones) that apply instead of the global fees
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FeeOverride {
+    pub buyer_fee_bps: u64,
+    pub seller_fee_bps: u64,
+}
+
+impl FeeOverride {
+    pub const SIZE: usize = 8 + 8;
+}
+
+// Live fees for one collection's listings, at [COLLECTION_FEE_SEED, collection].
+// They can only discount the global fees, so a bidder or seller leaving the
+// account out of a transaction never pays less.
+#[account]
+pub struct CollectionFeeConfig {
+    pub version: u8,
+    pub collection: Pubkey,
+    pub buyer_fee_bps: u64,
+    pub seller_fee_bps: u64,
+    pub bump: u8,
+}
+
+impl CollectionFeeConfig {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 8 + 8 + 1;
+}
+
+// Fee discount for members at [MEMBERSHIP_DISCOUNT_SEED, collection]: holders
+// of an NFT verified in `collection` pay `discount_bps` less of their fee
+#[account]
+pub struct MembershipDiscount {
+    pub version: u8,
+    pub collection: Pubkey,
+    pub discount_bps: u64, // Of the fee, at most BPS_DENOMINATOR
+    pub bump: u8,
+}
+
+impl MembershipDiscount {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 8 + 1;
+}
+
+// Admin-maintained registry entry for an approved collection, consulted by
+// initialize_auction. `permissions` is a bit set of per-collection feature
+// grants that gated features check against the listing's copy.
+#[account]
+pub struct CollectionConfig {
+    pub version: u8,
+    pub collection: Pubkey,
+    pub enabled: bool,
+    pub fee_override: Option<FeeOverride>,
+    pub permissions: u64,
+    pub bump: u8,
+}
+
+impl CollectionConfig {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 1 + (1 + 16) + 8 + 1;
+}
+
+// Time-weighted sale price of a registered collection, at
+// [COLLECTION_STATS_SEED, collection]. Each SOL sale ended with end_auction
+// sets the collection's price until the next one; `cumulative` sums that price
+// over time, and a ring of past observations of it lets the TWAP be taken over
+// any window the ring still reaches back to. A single sale only moves the
+// average by its price times the time it stood, which is what makes it hard to
+// push around with one wash sale.
+#[account]
+pub struct CollectionStats {
+    pub version: u8,
+    pub collection: Pubkey,
+    pub sales: u64,
+    pub last_price: u64, // Lamports, of the latest sale
+    pub last_sale_time: i64,
+    pub cumulative: u128, // Price × seconds, up to last_sale_time
+    pub observations: [PriceObservation; CollectionStats::MAX_OBSERVATIONS],
+    pub next_observation: u8, // Slot the next sale's observation goes in
+    pub bump: u8,
+}
+
+// `cumulative` as it stood at `time`, just before that time's sale
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct PriceObservation {
+    pub time: i64,
+    pub cumulative: u128,
+}
+
+impl PriceObservation {
+    pub const SIZE: usize = 8 + 16;
+}
+
+impl CollectionStats {
+    pub const VERSION: u8 = 1;
+    pub const MAX_OBSERVATIONS: usize = 16;
+    pub const SPACE: usize =
+        8 + // discriminator
+        1 + // version
+        32 + 8 + 8 + 8 + 16 + // collection .. cumulative
+        CollectionStats::MAX_OBSERVATIONS * PriceObservation::SIZE + // observations
+        1 + 1; // next_observation, bump
+
+    // The cumulative price at `now`, the last price standing since its sale
+    fn cumulative_at(&self, now: i64) -> u128 {
+        let elapsed = now.saturating_sub(self.last_sale_time).max(0) as u128;
+        self.cumulative.saturating_add((self.last_price as u128).saturating_mul(elapsed))
+    }
+
+    pub fn record_sale(&mut self, price: u64, now: i64) {
+        if self.sales > 0 {
+            self.cumulative = self.cumulative_at(now);
+        }
+        let slot = self.next_observation as usize;
+        self.observations[slot] = PriceObservation { time: now, cumulative: self.cumulative };
+        self.next_observation = ((slot + 1) % CollectionStats::MAX_OBSERVATIONS) as u8;
+        self.sales = self.sales.saturating_add(1);
+        self.last_price = price;
+        self.last_sale_time = now;
+    }
+
+    // Average price over the `window` seconds up to `now`, or over as much of
+    // it as the kept observations cover, with that span; None before any sale
+    pub fn twap(&self, window: i64, now: i64) -> Option<(u64, i64)> {
+        if self.sales == 0 {
+            return None;
+        }
+        let kept = (self.sales as usize).min(CollectionStats::MAX_OBSERVATIONS);
+        let start = now.saturating_sub(window);
+        let observations = &self.observations[..kept];
+        // Where the ring reaches back to the window's start, the cumulative
+        // price there; otherwise the average starts at the oldest observation
+        let before = observations
+            .iter()
+            .filter(|observation| observation.time <= start)
+            .max_by_key(|observation| observation.time);
+        let (from, from_cumulative) = match before {
+            Some(before) => (start, self.interpolate(observations, before, start)),
+            None => {
+                let oldest = observations.iter().min_by_key(|observation| observation.time)?;
+                (oldest.time, oldest.cumulative)
+            }
+        };
+        let span = now.saturating_sub(from);
+        if span <= 0 {
+            return Some((self.last_price, 0));
+        }
+        let sum = self.cumulative_at(now).saturating_sub(from_cumulative);
+        Some(((sum / (span as u128)) as u64, span))
+    }
+
+    // The cumulative price at `time`, from the observation `before` it and the
+    // next of `observations` after it if any, between which the price held steady
+    fn interpolate(
+        &self,
+        observations: &[PriceObservation],
+        before: &PriceObservation,
+        time: i64
+    ) -> u128 {
+        let after = observations
+            .iter()
+            .filter(|observation| observation.time > time)
+            .min_by_key(|observation| observation.time);
+        match after {
+            Some(after) => {
+                let elapsed = time.saturating_sub(before.time) as u128;
+                let gap = after.time.saturating_sub(before.time) as u128;
+                let rise = after.cumulative.saturating_sub(before.cumulative);
+                before.cumulative.saturating_add(rise.saturating_mul(elapsed) / gap)
+            }
+            None => self.cumulative_at(time),
+        }
+    }
+}
+
+// Floor price of a registered collection, at [FLOOR_ORACLE_SEED, collection]:
+// the lowest of its SOL sales ended with end_auction within the last `window`
+// seconds, among the CAPACITY most recent. Zero-copy with a fixed layout so
+// other programs can read it straight from the account data without this
+// crate. After the 8-byte discriminator: collection at 8, floor (u64) at 40,
+// floor_sale_time (i64) at 48, window (i64) at 56, updated_at (i64) at 64,
+// all little-endian. `floor` is as of `updated_at` (0 when no sale fell in the
+// window then); a reader wanting it as of now checks that `floor_sale_time`
+// is still within `window`, or calls get_floor_price.
+#[account(zero_copy)]
+pub struct FloorOracle {
+    pub collection: Pubkey,
+    pub floor: u64,
+    pub floor_sale_time: i64,
+    pub window: i64,
+    pub updated_at: i64,
+    pub len: u32, // Slots in use, the first `len` of `sales`
+    pub next: u32, // Slot the next sale goes in
+    pub version: u8,
+    pub bump: u8,
+    pub _padding: [u8; 6],
+    pub sales: [FloorSale; FloorOracle::CAPACITY],
+}
+
+#[zero_copy]
+pub struct FloorSale {
+    pub price: u64,
+    pub time: i64,
+}
+
+impl FloorOracle {
+    pub const VERSION: u8 = 1;
+    pub const CAPACITY: usize = 32;
+    pub const SPACE: usize = 8 + std::mem::size_of::<FloorOracle>();
+    pub const WINDOW: i64 = 7 * 24 * 60 * 60;
+
+    pub fn record_sale(&mut self, price: u64, now: i64) {
+        let slot = self.next as usize;
+        self.sales[slot] = FloorSale { price, time: now };
+        self.next = ((slot + 1) % FloorOracle::CAPACITY) as u32;
+        self.len = (self.len + 1).min(FloorOracle::CAPACITY as u32);
+
+        let floor = self.floor_at(now);
+        self.floor = floor.map_or(0, |sale| sale.price);
+        self.floor_sale_time = floor.map_or(0, |sale| sale.time);
+        self.updated_at = now;
+    }
+
+    // The cheapest kept sale within the window up to `now`
+    pub fn floor_at(&self, now: i64) -> Option<FloorSale> {
+        let start = now.saturating_sub(self.window);
+        self.sales[..self.len as usize]
+            .iter()
+            .filter(|sale| sale.time >= start)
+            .min_by_key(|sale| sale.price)
+            .copied()
+    }
+}
+
+// Accumulates the protocol fees collected in one payment mint until the admin
+// or fee manager withdraws them. Lamport fees use the native mint.
+#[account]
+pub struct FeeTreasury {
+    pub version: u8,
+    pub mint: Pubkey,
+    pub collected: u64, // Running total of fees paid in
+    pub withdrawn: u64, // Running total withdrawn by withdraw_fees
+    pub bump: u8,
+}
+
+impl FeeTreasury {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 8 + 8 + 1;
+}
+
+// Merkle root over every auction prune_archives has closed, so an auction's
+// outcome stays provable after its account is gone. Leaves are
+// `AuctionDetails::archive_leaf`, appended in pruning order; each is also
+// emitted in an AuctionArchived event with its index, from which indexers
+// rebuild the tree. Nodes hash (left, right) in order, empty subtrees are
+// zero-filled, and `branch` holds the left siblings of the next leaf's path.
+#[account]
+pub struct AuctionArchive {
+    pub version: u8,
+    pub leaves: u64,
+    pub root: [u8; 32],
+    pub branch: [[u8; 32]; AuctionArchive::DEPTH],
+    pub bump: u8,
+}
+
+impl AuctionArchive {
+    pub const VERSION: u8 = 1;
+    pub const DEPTH: usize = 32;
+    pub const SPACE: usize = 8 + 1 + 8 + 32 + 32 * AuctionArchive::DEPTH + 1;
+
+    // Add `leaf` as the next leaf, returning its index; `update_root` brings the root up to date
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<u64> {
+        let index = self.leaves;
+        self.leaves = index.try_add(1)?;
+        let mut node = leaf;
+        let mut size = self.leaves;
+        for level in 0..AuctionArchive::DEPTH {
+            if size & 1 == 1 {
+                self.branch[level] = node;
+                break;
+            }
+            node = hashv(&[&self.branch[level], &node]).0;
+            size >>= 1;
+        }
+        Ok(index)
+    }
+
+    pub fn update_root(&mut self) {
+        let mut node = [0u8; 32];
+        let mut zero = [0u8; 32];
+        let mut size = self.leaves;
+        for level in 0..AuctionArchive::DEPTH {
+            node = if size & 1 == 1 {
+                hashv(&[&self.branch[level], &node]).0
+            } else {
+                hashv(&[&node, &zero]).0
+            };
+            zero = hashv(&[&zero, &zero]).0;
+            size >>= 1;
+        }
+        self.root = node;
+    }
+}
+
+// The last CAPACITY privileged actions, at [AUDIT_LOG_SEED]: fee changes,
+// admin pauses, blacklist updates, authority handovers and treasury
+// withdrawals. Instructions taking such actions append to it once it exists,
+// overwriting the oldest entry when it's full. `digest` chains every entry
+// ever recorded, hashed onto the one before, so an indexer that kept the
+// entries can check none was left out or altered.
+#[account]
+pub struct AuditLog {
+    pub version: u8,
+    pub recorded: u64, // Entries ever appended; the next goes to `recorded % CAPACITY`
+    pub digest: [u8; 32],
+    pub entries: Vec<AuditEntry>,
+    pub bump: u8,
+}
+
+impl AuditLog {
+    pub const VERSION: u8 = 1;
+    pub const CAPACITY: usize = 32;
+    pub const SPACE: usize = 8 + 1 + 8 + 32 + (4 + AuditLog::CAPACITY * AuditEntry::SIZE) + 1;
+
+    pub fn append(&mut self, entry: AuditEntry) -> Result<()> {
+        self.digest = hashv(&[&self.digest, &entry.try_to_vec()?]).0;
+        let index = (self.recorded % (AuditLog::CAPACITY as u64)) as usize;
+        if index < self.entries.len() {
+            self.entries[index] = entry;
+        } else {
+            self.entries.push(entry);
+        }
+        self.recorded = self.recorded.try_add(1)?;
+        Ok(())
+    }
+
+    // Append an entry for `action` to the log at `info`, whose address the
+    // caller has checked, if it has been initialized
+    pub fn record(
+        info: &AccountInfo,
+        action: AuditAction,
+        actor: Pubkey,
+        target: Pubkey,
+        old_value: u64,
+        new_value: u64
+    ) -> Result<()> {
+        if info.owner != &crate::ID {
+            return Ok(());
+        }
+        let slot = Clock::get()?.slot;
+        let mut data = info.try_borrow_mut_data()?;
+        let mut log = AuditLog::try_deserialize(&mut &data[..])?;
+        log.append(AuditEntry { action, actor, target, old_value, new_value, slot })?;
+        log.try_serialize(&mut &mut data[..])
+    }
+}
+
+// One privileged action. What `target` and the values hold depends on the action.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct AuditEntry {
+    pub action: AuditAction,
+    pub actor: Pubkey, // Who signed for it
+    pub target: Pubkey,
+    pub old_value: u64,
+    pub new_value: u64,
+    pub slot: u64,
+}
+
+impl AuditEntry {
+    pub const SIZE: usize = 1 + 32 + 32 + 8 + 8 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    BuyerFeeChanged, // Old and new fee in basis points
+    SellerFeeChanged, // Same for the seller fee
+    AuctionPaused, // Target is the auction; values are 1 while paused, 0 otherwise
+    BlacklistUpdated, // Target is the address; values are 1 while blacklisted, 0 otherwise
+    AuthorityProposed, // Target is the proposed authority, default when withdrawn
+    AuthorityTransferred, // Actor is the new authority, target the old one
+    TreasuryWithdrawal, // Target is the treasury's mint; values are its balance before and after
+}
+
+// Head of the auction registry: counts the pages opened so far, which are
+// numbered from 0 and opened in order
+#[account]
+pub struct AuctionRegistry {
+    pub version: u8,
+    pub pages: u32,
+    pub bump: u8,
+}
+
+impl AuctionRegistry {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 4 + 1;
+}
+
+// One page of the auction registry, listing live auction PDAs with their end
+// times so clients can find them without scanning every program account.
+// Listings are added when initialized and taken out when they close; entries
+// aren't kept in any order, and a removal moves the last entry into the gap.
+// CAPACITY keeps a whole page within one query's return data.
+#[account(zero_copy)]
+pub struct AuctionRegistryPage {
+    pub index: u32,
+    pub len: u32, // Entries in use, the first `len` of `entries`
+    pub bump: u8,
+    pub version: u8,
+    pub _padding: [u8; 6],
+    pub entries: [RegistryEntry; AuctionRegistryPage::CAPACITY],
+}
+
+impl AuctionRegistryPage {
+    pub const VERSION: u8 = 1;
+    pub const CAPACITY: usize = 24;
+    pub const SPACE: usize = 8 + std::mem::size_of::<AuctionRegistryPage>();
+
+    pub fn is_full(&self) -> bool {
+        (self.len as usize) >= AuctionRegistryPage::CAPACITY
+    }
+
+    pub fn listed(&self) -> &[RegistryEntry] {
+        &self.entries[..self.len as usize]
+    }
+
+    pub fn position(&self, auction: &Pubkey) -> Option<usize> {
+        self.listed()
+            .iter()
+            .position(|entry| entry.auction == *auction)
+    }
+
+    // Callers check `is_full` first
+    pub fn push(&mut self, entry: RegistryEntry) -> Result<()> {
+        self.entries[self.len as usize] = entry;
+        self.len = self.len.try_add(1)?;
+        Ok(())
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> Result<()> {
+        self.len = self.len.try_sub(1)?;
+        let last = self.len as usize;
+        self.entries[index] = self.entries[last];
+        self.entries[last] = RegistryEntry::default();
+        Ok(())
+    }
+}
+
+// A live auction on a registry page. The end time is the one it had when
+// listed or last re-registered; anti-sniping extensions move the auction's own.
+#[zero_copy]
+#[derive(Default)]
+pub struct RegistryEntry {
+    pub auction: Pubkey,
+    pub end_time: i64,
+}
+
+// Marks `address` as barred from listing and bidding, e.g. for a compliance
+// takedown. The flag is the account's existence; removing it closes the account.
+#[account]
+pub struct BlacklistEntry {
+    pub version: u8,
+    pub address: Pubkey,
+    pub bump: u8,
+}
+
+impl BlacklistEntry {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 1;
+}
+
+// Protocol parameters changed through the config change queue; the only way
+// once governance has taken over, or while the admin has set a delay
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ParamChange {
+    Fees {
+        buyer_fee_bps: u64,
+        seller_fee_bps: u64,
+    },
+    FeatureFlags(u64),
+    FeeRecipient(Pubkey),
+    ConfigChangeDelay(i64),
+}
+
+#[account]
+pub struct ParamChangeProposal {
+    pub version: u8,
+    pub id: u64,
+    pub change: ParamChange,
+    pub executable_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl ParamChangeProposal {
+    pub const VERSION: u8 = 1;
+    // Largest variant is FeeRecipient / Fees: 1 tag + 32 bytes
+    pub const SPACE: usize = 8 + 1 + 8 + (1 + 32) + 8 + 1 + 1;
+}
+
+// An NFT listed while it stays delegated to an external escrow program, which
+// releases it to the winner when asked through the adapter handshake
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ExternalEscrow {
+    pub program: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub released: bool,
+}
+
+impl ExternalEscrow {
+    pub const SIZE: usize = 32 + 32 + 32 + 1;
+}
+
+// A Wormhole emitter: the foreign chain id and the emitting contract's address
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct BridgeEmitter {
+    pub chain: u16,
+    pub address: [u8; 32],
+}
+
+impl BridgeEmitter {
+    pub const SIZE: usize = 2 + 32;
+}
+
+// Replay marker: exists once the VAA it is derived from has been consumed
+#[account]
+pub struct ConsumedVaa {
+    pub version: u8,
+    pub bump: u8,
+}
+
+impl ConsumedVaa {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 1;
+}
+
+// How many loyalty points a SOL of activity earns while the schedule runs. All
+// zero, the default, pays out nothing.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LoyaltySchedule {
+    pub bid_points_per_sol: u64,
+    pub purchase_points_per_sol: u64,
+    pub starts_at: i64,
+    pub ends_at: i64, // 0 to keep emitting until the admin changes the schedule
+}
+
+impl LoyaltySchedule {
+    pub const SIZE: usize = 8 + 8 + 8 + 8;
+
+    // Points `volume` lamports earn at `points_per_sol`, or none outside the schedule
+    pub fn points_for(&self, points_per_sol: u64, volume: u64, now: i64) -> Result<u64> {
+        if now < self.starts_at || (self.ends_at != 0 && now >= self.ends_at) {
+            return Ok(0);
+        }
+        math::pro_rata(volume, points_per_sol, LAMPORTS_PER_SOL)
+    }
+}
+
+// A user's loyalty points, at [LOYALTY_SEED, owner]. Bids and purchases only
+// accrue to it when the user's account is passed along, so it has to be opened
+// first with `init_loyalty_account`.
+#[account]
+pub struct LoyaltyAccount {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub points: u64,
+    pub bid_volume: u64, // Lamports bid, over every bid that earned points or not
+    pub purchase_volume: u64, // Lamports paid for NFTs bought
+    pub bump: u8,
+}
+
+impl LoyaltyAccount {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 8 + 8 + 8 + 1;
+}
+
+// Keys the admin delegates day-to-day work to, at [ROLES_SEED], so the admin
+// key itself can stay in cold storage. Each role sits alongside the admin,
+// which keeps every power it delegates.
+#[account]
+pub struct Roles {
+    pub version: u8,
+    pub pauser: Option<Pubkey>, // Pauses and unpauses listings as the admin does
+    pub fee_manager: Option<Pubkey>, // Same powers as NftComAuction::fee_manager
+    pub blacklist_manager: Option<Pubkey>, // Adds and removes blacklist entries
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Pauser,
+    FeeManager,
+    BlacklistManager,
+}
+
+impl Roles {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 3 * (1 + 32) + 1;
+
+    pub fn holder(&self, role: Role) -> Option<Pubkey> {
+        match role {
+            Role::Pauser => self.pauser,
+            Role::FeeManager => self.fee_manager,
+            Role::BlacklistManager => self.blacklist_manager,
+        }
+    }
+
+    pub fn set_holder(&mut self, role: Role, holder: Option<Pubkey>) {
+        match role {
+            Role::Pauser => {
+                self.pauser = holder;
+            }
+            Role::FeeManager => {
+                self.fee_manager = holder;
+            }
+            Role::BlacklistManager => {
+                self.blacklist_manager = holder;
+            }
+        }
+    }
+
+    // Whether `signer` holds `role`; without the roles account nobody does
+    pub fn grants(roles: Option<&Roles>, role: Role, signer: &Pubkey) -> bool {
+        roles.is_some_and(|roles| roles.holder(role) == Some(*signer))
+    }
+}
+
+// Instruction families switched on per deployment, at [FEATURE_FLAGS_SEED]. A
+// family can ship in the program and stay off on a cluster until the admin
+// turns its bit on, without another deploy.
+#[account]
+pub struct FeatureFlags {
+    pub version: u8,
+    pub flags: u64,
+    pub bump: u8,
+}
+
+impl FeatureFlags {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 8 + 1;
+    // flags bits
+    pub const TOKEN_BIDS: u64 = 1 << 0; // Bids on listings paid in an SPL token
+    pub const BUY_NOW: u64 = 1 << 1;
+    pub const OFFERS: u64 = 1 << 2; // Making standing offers on unlisted NFTs
+    pub const ALL: u64 = FeatureFlags::TOKEN_BIDS | FeatureFlags::BUY_NOW | FeatureFlags::OFFERS;
+
+    pub fn ensure_enabled(&self, flag: u64) -> Result<()> {
+        require!(self.flags & flag != 0, ErrorCode::FeatureDisabled);
+        Ok(())
+    }
+}
+
+// Per-seller settings that apply to all of the seller's listings at once
+#[account]
+pub struct SellerProfile {
+    pub version: u8,
+    pub seller: Pubkey,
+    pub on_vacation: bool,
+    pub vacation_started: i64,
+    pub bump: u8,
+    pub active_auctions: u32, // Listings counted against the per-seller cap and not yet released
+}
+
+impl SellerProfile {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 1 + 8 + 1 + 4;
+}
+
+// Listing terms a seller reuses across listings; `initialize_from_template`
+// lists an NFT on them given only the listing's name
+#[account]
+pub struct AuctionTemplate {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub template_id: u16,
+    pub terms: TemplateTerms,
+    pub bump: u8,
+}
+
+impl AuctionTemplate {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 2 + TemplateTerms::SIZE + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TemplateTerms {
+    pub duration: i64, // Seconds from listing to end; 0 for the marketplace default
+    pub minimum: u64,
+    pub min_increment_bps: Option<u64>,
+    pub min_increment: Option<u64>,
+    pub payment_mint: Option<Pubkey>,
+    pub allowlist_root: Option<[u8; 32]>,
+}
+
+impl TemplateTerms {
+    pub const SIZE: usize = 8 + 8 + (1 + 8) + (1 + 8) + (1 + 32) + (1 + 32);
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.minimum > 0, ErrorCode::MinimumBidError);
+        require!(self.duration >= 0, ErrorCode::EndTimeError);
+        Ok(())
+    }
+
+    // The overrides of a listing named `name` created at `now` on these terms
+    pub fn overrides(&self, name: String, now: i64) -> Result<ListingOverrides> {
+        let end_time = match self.duration {
+            0 => None,
+            duration => Some(now.try_add(duration)?),
+        };
+        Ok(ListingOverrides {
+            end_time,
+            min_increment_bps: self.min_increment_bps,
+            min_increment: self.min_increment,
+            allowlist_root: self.allowlist_root,
+            payment_mint: self.payment_mint,
+            name: Some(name),
+            ..ListingOverrides::default()
+        })
+    }
+}
+
+// Auctions a user follows; the heartbeat crank reminds them once each as the
+// auction enters its final window
+#[account]
+pub struct Watchlist {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub entries: Vec<WatchEntry>,
+    pub bump: u8,
+}
+
+impl Watchlist {
+    pub const VERSION: u8 = 1;
+    pub const CAPACITY: usize = 16;
+    pub const REMINDER_WINDOW: i64 = 60 * 60;
+    pub const SPACE: usize = 8 + 1 + 32 + (4 + Watchlist::CAPACITY * WatchEntry::MAX_SIZE) + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WatchEntry {
+    pub listing_id: [u8; 32],
+    pub reminded: bool,
+}
+
+impl WatchEntry {
+    pub const MAX_SIZE: usize = 32 + 1;
+}
+
+// A bidder's position on one listing at [BID_RECEIPT_SEED, auction, bidder], opened
+// by their first place_bid or place_bids so a wallet has a single account to watch.
+// Bids that come in through other paths (swaps, bridges, compressed positions) and
+// the refunds buy_now pays out in bulk don't touch it; the auction stays the record.
+#[account]
+pub struct BidReceipt {
+    pub version: u8,
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub escrowed: u64, // What the bidder has in escrow, after fees
+    pub last_bid_time: i64,
+    pub refunded: bool, // Paid back out of escrow since the last bid
+    pub bump: u8,
+    pub bond: u64, // Bid bond locked by the bidder's first bid, until refunded or forfeited
+    pub best_bid: u64, // Most the bidder has had in escrow at once
+    pub participation_minted: bool, // A participation receipt cNFT went to the bidder
+    pub bid_count: u32, // Bids placed, against the listing's per-bidder limit
+    pub fee_discount_bps: u64, // Membership discount off the buyer fee of the latest bid
+}
+
+impl BidReceipt {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 32 + 8 + 8 + 1 + 1 + 8 + 8 + 1 + 4 + 8;
+
+    // Hold the bidder to the listing's bid limits before their next bid
+    pub fn check_bid_limits(&self, auction: &AuctionDetails, now: i64) -> Result<()> {
+        require!(
+            auction.max_bids_per_bidder == 0 ||
+                self.bid_count < (auction.max_bids_per_bidder as u32),
+            ErrorCode::BidLimitReached
+        );
+        require!(
+            self.bid_count == 0 || now >= self.last_bid_time.saturating_add(auction.bid_cooldown),
+            ErrorCode::BidCooldownActive
+        );
+        Ok(())
+    }
+
+    pub fn record_bid(&mut self, credited: u64, now: i64) -> Result<()> {
+        self.escrowed = self.escrowed.try_add(credited)?;
+        self.bid_count = self.bid_count.saturating_add(1);
+        self.best_bid = self.best_bid.max(self.escrowed);
+        self.last_bid_time = now;
+        self.refunded = false;
+        Ok(())
+    }
+
+    // Note `amount` going back to the bidder on the receipt at `info`, whose
+    // address the caller has checked. Bidders who never used place_bid have none.
+    pub fn record_refund(info: &AccountInfo, amount: u64) -> Result<()> {
+        if info.owner != &crate::ID {
+            return Ok(());
+        }
+        let mut data = info.try_borrow_mut_data()?;
+        let mut receipt = BidReceipt::try_deserialize(&mut &data[..])?;
+        receipt.escrowed = receipt.escrowed.saturating_sub(amount);
+        receipt.refunded = true;
+        receipt.try_serialize(&mut &mut data[..])
+    }
+}
+
+// Proof that one settlement step of an auction has run, at
+// [SETTLEMENT_RECEIPT_SEED, auction, step, party]. The settling instruction
+// opens it, checks it's still blank before doing anything and fills it in at
+// the end, so a retried or duplicated transaction fails with
+// SettlementStepDone before anything is paid. Each step runs at most once per
+// party: once per edition winner for delivery, once per auction otherwise.
+#[account]
+pub struct SettlementReceipt {
+    pub version: u8,
+    pub auction: Pubkey,
+    pub step: SettlementStep,
+    pub party: Pubkey, // Who the step paid or delivered to
+    pub amount: u64, // Lamports or tokens paid; the winning price for a delivery
+    pub slot: u64,
+    pub completed_at: i64,
+    pub bump: u8,
+}
+
+impl SettlementReceipt {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 1 + 32 + 8 + 8 + 8 + 1;
+
+    // A receipt opened for this call has no version yet
+    pub fn ensure_pending(&self) -> Result<()> {
+        require!(self.version == 0, ErrorCode::SettlementStepDone);
+        Ok(())
+    }
+
+    pub fn record(
+        &mut self,
+        auction: Pubkey,
+        step: SettlementStep,
+        party: Pubkey,
+        amount: u64,
+        bump: u8
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        self.version = SettlementReceipt::VERSION;
+        self.auction = auction;
+        self.step = step;
+        self.party = party;
+        self.amount = amount;
+        self.slot = clock.slot;
+        self.completed_at = clock.unix_timestamp;
+        self.bump = bump;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementStep {
+    FundsPaid, // The seller's proceeds left escrow
+    NftDelivered, // The winner took delivery
+    FeesCollected, // The protocol fees went to the treasury
+}
+
+impl SettlementStep {
+    pub fn seed(self) -> [u8; 1] {
+        [self as u8]
+    }
+}
+
+// Sale history of one NFT at [PROVENANCE_SEED, mint], appended each time an
+// auction of it settles through claim_proceeds or it's bought off a fixed-price
+// listing. Editions, NFTs minted on claim and sales settled off-chain in fiat
+// aren't recorded.
+#[account]
+pub struct Provenance {
+    pub version: u8,
+    pub mint: Pubkey,
+    pub sales: Vec<Sale>, // The last CAPACITY sales, oldest first
+    pub total_sales: u64,
+    pub bump: u8,
+}
+
+impl Provenance {
+    pub const VERSION: u8 = 1;
+    pub const CAPACITY: usize = 16;
+    pub const SPACE: usize = 8 + 1 + 32 + (4 + Provenance::CAPACITY * Sale::SIZE) + 8 + 1;
+
+    pub fn record_sale(&mut self, mint: Pubkey, bump: u8, sale: Sale) -> Result<()> {
+        if self.version == 0 {
+            self.version = Provenance::VERSION;
+            self.mint = mint;
+            self.bump = bump;
+        }
+        if self.sales.len() >= Provenance::CAPACITY {
+            self.sales.remove(0);
+        }
+        self.sales.push(sale);
+        self.total_sales = self.total_sales.try_add(1)?;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Sale {
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub time: i64,
+}
+
+impl Sale {
+    pub const SIZE: usize = 32 + 32 + 8 + 8;
+}
+
+// An NFT offered at a fixed price, at [FIXED_PRICE_SEED, listing_id]. The NFT
+// sits in its escrow at [NFT_ESCROW_SEED, mint] until it's bought or cancelled.
+#[account]
+pub struct FixedPriceListing {
+    pub version: u8,
+    pub listing_id: [u8; 32],
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub bump: u8,
+}
+
+impl FixedPriceListing {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 32 + 32 + 8 + 1;
+}
+
+// A standing offer by `buyer` on `mint` at [OFFER_SEED, mint, buyer]. The offered
+// lamports are held on this account on top of its rent.
+#[account]
+pub struct Offer {
+    pub version: u8,
+    pub buyer: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Offer {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 32 + 8 + 8 + 1;
+}
+
+// Per-bidder record backing replay protection for bids submitted on a
+// bidder's behalf (relayed or delegated). Nonces are accepted once each
+// within a sliding window starting at `nonce_floor`. Lamports held above the
+// profile's rent are the bidder's balance for signed bid orders.
+#[account]
+pub struct BidderProfile {
+    pub version: u8,
+    pub bidder: Pubkey,
+    pub nonce_floor: u64,
+    pub used_nonces: u64, // Bit i marks nonce_floor + i as used
+    pub bump: u8,
+}
+
+impl BidderProfile {
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = 8 + 1 + 32 + 8 + 8 + 1;
+    pub const NONCE_WINDOW: u64 = 64;
+
+    // Mark `nonce` as used, rejecting reuse and nonces that fell out of the window
+    pub fn consume_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(nonce >= self.nonce_floor, ErrorCode::NonceTooOld);
+
+        // A floor within a window of u64::MAX, which a signer can pick a nonce
+        // to reach, has its window end at u64::MAX instead of overflowing
+        let window_end = self.nonce_floor.saturating_add(BidderProfile::NONCE_WINDOW);
+        if nonce >= window_end {
+            // Slide the window so `nonce` becomes its newest slot
+            let shift = nonce.try_sub(window_end - 1)?;
+            self.used_nonces = if shift >= BidderProfile::NONCE_WINDOW {
+                0
+            } else {
+                self.used_nonces >> shift
+            };
+            self.nonce_floor = self.nonce_floor.try_add(shift)?;
+        }
+
+        let bit = 1u64 << (nonce - self.nonce_floor);
+        require!(self.used_nonces & bit == 0, ErrorCode::NonceAlreadyUsed);
+        self.used_nonces |= bit;
+        Ok(())
+    }
+}
+
+// A bid the bidder signs off-chain for a matcher to submit. `amount` is drawn
+// from the bidder's profile balance, buyer fee included, and `nonce` is
+// consumed from the profile's window so the order settles at most once.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BidOrder {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+    pub expires_at: i64,
+}
+
+impl BidOrder {
+    // The bytes the bidder signs: the program id, so an order can't be replayed
+    // against another deployment, followed by the serialized order
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = crate::ID.to_bytes().to_vec();
+        self.serialize(&mut message)?;
+        Ok(message)
+    }
+}
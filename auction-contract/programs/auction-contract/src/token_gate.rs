@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::errors::ErrorCode;
+use crate::state::AuctionDetails;
+
+// Token-gated listings only take bids from holders of `gate_mint`, such as a
+// membership token or an NFT of the seller's collection. The bidder shows one
+// of their own token accounts for that mint holding a nonzero balance.
+
+// Let `bidder` through unless the listing is gated and `holding` doesn't prove they hold the mint
+pub fn check(auction: &AuctionDetails, bidder: &Pubkey, holding: Option<&TokenAccount>) -> Result<()> {
+    if let Some(gate_mint) = auction.gate_mint {
+        let holding = holding.ok_or(ErrorCode::NotTokenHolder)?;
+        require!(
+            holding.mint == gate_mint && holding.owner == *bidder && holding.amount > 0,
+            ErrorCode::NotTokenHolder
+        );
+    }
+    Ok(())
+}
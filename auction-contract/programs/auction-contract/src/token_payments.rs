@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_interface::{ self, TransferChecked };
+use spl_token_2022::extension::{ BaseStateWithExtensions, ExtensionType, StateWithExtensions };
+use spl_token_2022::state::{ Account as TokenAccount, Mint };
+use crate::errors::ErrorCode;
+use crate::math::CheckedMath;
+
+// Escrow deposits and refunds in SPL payment mints, Token-2022 included.
+//
+// Mints with a transfer fee deliver less than was sent, so deposits credit
+// what the escrow's balance actually grew by rather than the amount requested.
+//
+// Mints with a transfer hook aren't accepted yet: every escrow transfer would
+// need the hook's extra accounts forwarded, on every path that pays out.
+
+// Extensions that break escrow: the tokens can't move at all, someone other
+// than the escrow could move them out, the balances can't be read, or moving
+// them takes accounts the escrow paths don't carry
+const UNSUPPORTED_EXTENSIONS: [ExtensionType; 5] = [
+    ExtensionType::NonTransferable,
+    ExtensionType::PermanentDelegate,
+    ExtensionType::ConfidentialTransferMint,
+    ExtensionType::ConfidentialTransferFeeConfig,
+    ExtensionType::TransferHook,
+];
+
+// Reject mints carrying any extension in UNSUPPORTED_EXTENSIONS
+pub fn assert_supported_mint(mint: &AccountInfo) -> Result<()> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>
+        ::unpack(&data)
+        .map_err(|_| error!(ErrorCode::InvalidPaymentMint))?;
+    let extensions = state
+        .get_extension_types()
+        .map_err(|_| error!(ErrorCode::InvalidPaymentMint))?;
+    require!(
+        !extensions.iter().any(|extension| UNSUPPORTED_EXTENSIONS.contains(extension)),
+        ErrorCode::UnsupportedMintExtension
+    );
+    Ok(())
+}
+
+// An SPL token account's base state, Token-2022 extensions included
+pub fn token_account(account: &AccountInfo) -> Result<TokenAccount> {
+    let data = account.try_borrow_data()?;
+    let state = StateWithExtensions::<TokenAccount>
+        ::unpack(&data)
+        .map_err(|_| error!(ErrorCode::InvalidPaymentMint))?;
+    Ok(state.base)
+}
+
+// Whether `program` is one of the two SPL token programs
+pub fn is_token_program(program: &Pubkey) -> bool {
+    *program == anchor_spl::token::ID || *program == anchor_spl::token_2022::ID
+}
+
+// Balance of an SPL token account, Token-2022 extensions included
+pub fn token_balance(account: &AccountInfo) -> Result<u64> {
+    Ok(token_account(account)?.amount)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_payment<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]]
+) -> Result<()> {
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            TransferChecked {
+                from: from.clone(),
+                mint: mint.clone(),
+                to: to.clone(),
+                authority: authority.clone(),
+            },
+            signer_seeds
+        ),
+        amount,
+        decimals
+    )
+}
+
+// Move `amount` into escrow and return how much actually arrived, which is
+// less than `amount` when the mint withholds a transfer fee. `signer_seeds`
+// sign for `authority` when it's a PDA, as for a delegated bid being collected.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_payment<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]]
+) -> Result<u64> {
+    assert_supported_mint(mint)?;
+
+    let balance_before = token_balance(escrow)?;
+    transfer_payment(
+        token_program,
+        from,
+        mint,
+        escrow,
+        authority,
+        amount,
+        decimals,
+        signer_seeds
+    )?;
+    token_balance(escrow)?.try_sub(balance_before)
+}
@@ -1,12 +1,5 @@
 use anchor_lang::prelude::*;
-
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Invalid seller address.")]
-    InvalidSellerAddress,
-    #[msg("Invalid payment contract address.")]
-    InvalidPaymentContractAddress,
-}
+use crate::errors::ErrorCode;
 
 pub fn uint_to_string(value: u64) -> String {
     // Convert the unsigned integer to a string using Rust's built-in method
@@ -14,7 +7,7 @@ pub fn uint_to_string(value: u64) -> String {
 }
 
 pub fn generate_metadata(
-    listing_id: &str,
+    name: &str,
     amount: u64,
     time: i64,
     seller_address: Pubkey,
@@ -33,8 +26,8 @@ pub fn generate_metadata(
 
     // Construct the metadata string using format!
     let metadata = format!(
-        "listing_id:{}, amount:{}, time:{}, seller:{}, minter:{}",
-        listing_id,
+        "listing:{}, amount:{}, time:{}, seller:{}, minter:{}",
+        name,
         amount_str,
         time_str,
         seller_address,
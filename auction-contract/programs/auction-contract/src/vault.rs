@@ -0,0 +1,398 @@
+use std::cell::RefCell;
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{
+    self,
+    CloseAccount,
+    InitializeAccount3,
+    Mint,
+    SyncNative,
+    TokenAccount,
+    TokenInterface,
+};
+use crate::errors::ErrorCode;
+use crate::math::CheckedMath;
+use crate::state::AuctionDetails;
+use crate::token_payments::{ self, deposit_payment, transfer_payment };
+use crate::{ VAULT_SEED, WSOL_UNWRAP_SEED };
+
+// Each auction's bid funds sit in a system-owned PDA at [VAULT_SEED, listing_id].
+// Bidders pay into it with plain transfers; nothing leaves it except through a
+// System Program transfer the program signs with the vault seeds.
+//
+// A listing paid in an SPL token keeps its funds in a token account at
+// [TOKEN_VAULT_SEED, listing_id] instead. The vault PDA owns it, so the same
+// seeds sign its transfers out. Either token program may own the mint; every
+// transfer is a checked one, so Token-2022 mints go through the same path.
+//
+// A listing paid in wrapped SOL holds wSOL in its token vault, but bidders and
+// payees can deal in plain SOL: lamports paid in are wrapped with `sync_native`,
+// and payouts to wallets are unwrapped through a short-lived token account at
+// [WSOL_UNWRAP_SEED, listing_id] that's closed again in the same instruction.
+
+// Put the vault's rent-exempt minimum in up front, so small bids and partial
+// refunds never leave it below rent exemption. The seller pays it at listing.
+pub fn fund<'info>(
+    payer: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>
+) -> Result<()> {
+    let floor = Rent::get()?.minimum_balance(0);
+    let missing = floor.saturating_sub(vault.lamports());
+    if missing == 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new(system_program.clone(), system_program::Transfer {
+            from: payer.clone(),
+            to: vault.clone(),
+        }),
+        missing
+    )
+}
+
+pub fn pay_out<'info>(
+    vault: &AccountInfo<'info>,
+    listing_id: &[u8; 32],
+    bump: u8,
+    to: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    amount: u64
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Transfer {
+                from: vault.clone(),
+                to: to.clone(),
+            },
+            &[&[VAULT_SEED, listing_id.as_ref(), &[bump]]]
+        ),
+        amount
+    )
+}
+
+// Address of the vault for a listing whose bump is already known
+pub fn address(listing_id: &[u8; 32], bump: u8) -> Result<Pubkey> {
+    Pubkey::create_program_address(&[VAULT_SEED, listing_id.as_ref(), &[bump]], &crate::ID).map_err(
+        |_| error!(ErrorCode::InvalidListingId)
+    )
+}
+
+// A listing's escrow in whichever currency it's paid in. Wallets are paid in
+// lamports on a SOL listing; on a token listing every payee is a token account
+// of the payment mint, owned by whoever is being paid. A wrapped SOL listing
+// pays either, queuing wallet payouts until `settle` unwraps them all at once.
+pub struct Escrow<'a, 'info> {
+    listing_id: &'a [u8; 32],
+    bump: u8,
+    vault: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    funds: Funds<'info>,
+}
+
+enum Funds<'info> {
+    Lamports,
+    Tokens {
+        mint: Pubkey,
+        mint_account: AccountInfo<'info>,
+        decimals: u8,
+        token_vault: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        unwrap: Option<Unwrap<'info>>,
+    },
+}
+
+// What a wrapped SOL listing needs to pay wallets: the unwrap account's address,
+// the native mint it's opened for, and a signer to front its rent
+struct Unwrap<'info> {
+    account: AccountInfo<'info>,
+    native_mint: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    pending: RefCell<Vec<(AccountInfo<'info>, u64)>>,
+}
+
+impl<'a, 'info> Escrow<'a, 'info> {
+    // The token vault, token program and payment mint are only needed, and then
+    // required, when the listing is paid in a token
+    pub fn new(
+        auction: &AuctionDetails,
+        listing_id: &'a [u8; 32],
+        vault: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        token_vault: Option<&InterfaceAccount<'info, TokenAccount>>,
+        token_program: Option<&Interface<'info, TokenInterface>>,
+        payment_mint: Option<&InterfaceAccount<'info, Mint>>
+    ) -> Result<Self> {
+        let funds = match auction.payment_mint {
+            None => Funds::Lamports,
+            Some(mint) => {
+                let token_vault = token_vault.ok_or(ErrorCode::WrongPaymentMint)?;
+                let token_program = token_program.ok_or(ErrorCode::WrongPaymentMint)?;
+                let payment_mint = payment_mint.ok_or(ErrorCode::WrongPaymentMint)?;
+                require_keys_eq!(token_vault.mint, mint, ErrorCode::WrongPaymentMint);
+                require_keys_eq!(payment_mint.key(), mint, ErrorCode::WrongPaymentMint);
+                Funds::Tokens {
+                    mint,
+                    mint_account: payment_mint.to_account_info(),
+                    decimals: payment_mint.decimals,
+                    token_vault: token_vault.to_account_info(),
+                    token_program: token_program.to_account_info(),
+                    unwrap: None,
+                }
+            }
+        };
+        Ok(Escrow { listing_id, bump: auction.vault_bump, vault, system_program, funds })
+    }
+
+    // Let a wrapped SOL listing pay wallets, with `payer` fronting the unwrap
+    // account's rent. Without the unwrap accounts it can only pay token accounts.
+    pub fn unwrapping(
+        mut self,
+        account: Option<&UncheckedAccount<'info>>,
+        native_mint: Option<&InterfaceAccount<'info, Mint>>,
+        payer: AccountInfo<'info>
+    ) -> Self {
+        if let Funds::Tokens { mint, unwrap, .. } = &mut self.funds {
+            if let (true, Some(account), Some(native_mint)) = (
+                *mint == native_mint::ID,
+                account,
+                native_mint,
+            ) {
+                *unwrap = Some(Unwrap {
+                    account: account.to_account_info(),
+                    native_mint: native_mint.to_account_info(),
+                    payer,
+                    pending: RefCell::new(vec![]),
+                });
+            }
+        }
+        self
+    }
+
+    // The account actually holding the funds
+    pub fn holding(&self) -> &AccountInfo<'info> {
+        match &self.funds {
+            Funds::Lamports => &self.vault,
+            Funds::Tokens { token_vault, .. } => token_vault,
+        }
+    }
+
+    // Move `amount` in from `from`: a wallet signing for itself, or a token
+    // account `authority` signs for. A wallet's lamports are wrapped on their way
+    // into a wrapped SOL listing. Returns what actually landed in escrow, which
+    // for a mint withholding a transfer fee is less than `amount`.
+    pub fn deposit(
+        &self,
+        from: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        amount: u64
+    ) -> Result<u64> {
+        let before = self.balance()?;
+        match &self.funds {
+            Funds::Tokens { mint, token_vault, token_program, .. } if
+                *mint == native_mint::ID &&
+                from.owner == &system_program::ID
+            => {
+                self.transfer_lamports(from, token_vault, amount)?;
+                token_interface::sync_native(
+                    CpiContext::new(token_program.clone(), SyncNative {
+                        account: token_vault.clone(),
+                    })
+                )?;
+            }
+            Funds::Lamports => {
+                self.transfer_lamports(from, &self.vault, amount)?;
+            }
+            Funds::Tokens { mint_account, decimals, token_vault, token_program, .. } => {
+                return deposit_payment(
+                    token_program,
+                    from,
+                    mint_account,
+                    token_vault,
+                    authority,
+                    amount,
+                    *decimals,
+                    &[]
+                );
+            }
+        }
+        self.balance()?.try_sub(before)
+    }
+
+    pub fn pay(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        match &self.funds {
+            Funds::Lamports => {
+                pay_out(&self.vault, self.listing_id, self.bump, to, &self.system_program, amount)
+            }
+            Funds::Tokens { mint, token_program, .. } if
+                *mint == native_mint::ID &&
+                to.owner != token_program.key
+            => {
+                let unwrap = self.unwrap()?;
+                unwrap.pending.borrow_mut().push((to.clone(), amount));
+                Ok(())
+            }
+            Funds::Tokens { mint_account, decimals, token_vault, token_program, .. } => {
+                transfer_payment(
+                    token_program,
+                    token_vault,
+                    mint_account,
+                    to,
+                    &self.vault,
+                    amount,
+                    *decimals,
+                    &[&[VAULT_SEED, self.listing_id.as_ref(), &[self.bump]]]
+                )
+            }
+        }
+    }
+
+    // Pay out everything queued for wallets on a wrapped SOL listing: move the
+    // total into a fresh unwrap account, close it to the payer, who gets their
+    // rent back along with the lamports, and pass those on to each wallet. A
+    // no-op for every other listing, and for one with nothing queued.
+    pub fn settle(&self) -> Result<()> {
+        let (mint_account, decimals, token_vault, token_program, unwrap) = match &self.funds {
+            Funds::Tokens {
+                mint_account,
+                decimals,
+                token_vault,
+                token_program,
+                unwrap: Some(unwrap),
+                ..
+            } => (mint_account, *decimals, token_vault, token_program, unwrap),
+            _ => {
+                return Ok(());
+            }
+        };
+        let pending = unwrap.pending.take();
+        let total = pending.iter().try_fold(0u64, |total, (_, amount)| total.try_add(*amount))?;
+        if total == 0 {
+            return Ok(());
+        }
+
+        let (address, bump) = Pubkey::find_program_address(
+            &[WSOL_UNWRAP_SEED, self.listing_id.as_ref()],
+            &crate::ID
+        );
+        require_keys_eq!(unwrap.account.key(), address, ErrorCode::WrongPaymentMint);
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                self.system_program.clone(),
+                system_program::CreateAccount {
+                    from: unwrap.payer.clone(),
+                    to: unwrap.account.clone(),
+                },
+                &[&[WSOL_UNWRAP_SEED, self.listing_id.as_ref(), &[bump]]]
+            ),
+            Rent::get()?.minimum_balance(anchor_spl::token::TokenAccount::LEN),
+            anchor_spl::token::TokenAccount::LEN as u64,
+            token_program.key
+        )?;
+        token_interface::initialize_account3(
+            CpiContext::new(token_program.clone(), InitializeAccount3 {
+                account: unwrap.account.clone(),
+                mint: unwrap.native_mint.clone(),
+                authority: self.vault.clone(),
+            })
+        )?;
+        let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, self.listing_id.as_ref(), &[self.bump]]];
+        transfer_payment(
+            token_program,
+            token_vault,
+            mint_account,
+            &unwrap.account,
+            &self.vault,
+            total,
+            decimals,
+            vault_seeds
+        )?;
+        token_interface::close_account(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                CloseAccount {
+                    account: unwrap.account.clone(),
+                    destination: unwrap.payer.clone(),
+                    authority: self.vault.clone(),
+                },
+                vault_seeds
+            )
+        )?;
+        for (to, amount) in pending.iter().filter(|(to, _)| to.key() != unwrap.payer.key()) {
+            self.transfer_lamports(&unwrap.payer, to, *amount)?;
+        }
+        Ok(())
+    }
+
+    // Whether paying `to` pays `recipient`: the recipient's own wallet on a SOL
+    // listing, their token account for the payment mint on a token listing, and
+    // either on a wrapped SOL listing
+    pub fn pays(&self, to: &AccountInfo, recipient: &Pubkey) -> bool {
+        match &self.funds {
+            Funds::Lamports => to.key() == *recipient,
+            Funds::Tokens { mint, .. } if *mint == native_mint::ID && to.key() == *recipient => {
+                true
+            }
+            Funds::Tokens { mint, token_program, .. } =>
+                to.owner == token_program.key &&
+                    token_payments::token_account(to).is_ok_and(|account| {
+                        account.mint == *mint && account.owner == *recipient
+                    }),
+        }
+    }
+
+    // The account holding `wallet`'s funds in the listing's currency: the wallet
+    // itself on a SOL listing, otherwise the token account named alongside it,
+    // which must be theirs. A wrapped SOL listing falls back to the wallet.
+    pub fn account_of(
+        &self,
+        wallet: &AccountInfo<'info>,
+        token_account: Option<&AccountInfo<'info>>
+    ) -> Result<AccountInfo<'info>> {
+        match (&self.funds, token_account) {
+            (Funds::Lamports, _) => Ok(wallet.clone()),
+            (Funds::Tokens { .. }, Some(token_account)) => {
+                require!(self.pays(token_account, &wallet.key()), ErrorCode::WrongPaymentMint);
+                Ok(token_account.clone())
+            }
+            (Funds::Tokens { mint, .. }, None) if *mint == native_mint::ID => Ok(wallet.clone()),
+            (Funds::Tokens { .. }, None) => Err(ErrorCode::WrongPaymentMint.into()),
+        }
+    }
+
+    fn unwrap(&self) -> Result<&Unwrap<'info>> {
+        match &self.funds {
+            Funds::Tokens { unwrap: Some(unwrap), .. } => Ok(unwrap),
+            _ => Err(ErrorCode::WrongPaymentMint.into()),
+        }
+    }
+
+    fn transfer_lamports(
+        &self,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        amount: u64
+    ) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(self.system_program.clone(), system_program::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            amount
+        )
+    }
+
+    fn balance(&self) -> Result<u64> {
+        match &self.funds {
+            Funds::Lamports => Ok(self.vault.lamports()),
+            Funds::Tokens { token_vault, .. } => token_payments::token_balance(token_vault),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use crate::errors::ErrorCode;
+use crate::state::{ AttestationKind, AuctionDetails };
+
+// Wormhole integration for bids placed from other chains, and for crowd
+// ("alien") auctions whose NFT or payment lives on another chain.
+//
+// The core bridge verifies guardian signatures and posts each VAA to a
+// `PostedVAA` account it owns; this program only trusts such accounts and
+// reads the emitter, sequence and payload back out of them. Whether the
+// emitter is allowed to place bids is up to the admin whitelist.
+//
+// Bid payload layout (big-endian, as EVM emitters encode it):
+//   u8 payload id (1) | [u8; 32] bidder | u64 amount | [u8; 32] listing id
+pub const WORMHOLE_CORE_BRIDGE: Pubkey = pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+pub const BRIDGED_BID_PAYLOAD_ID: u8 = 1;
+
+// Settlement attestation payload layout:
+//   u8 payload id (2) | [u8; 32] listing id | u8 kind (0 payment, 1 NFT lock)
+//
+// Built with the `wormhole-attestations` feature, an alien auction only
+// settles (`claim_crowd_shares`, `claim_proceeds`) once such an attestation
+// from a whitelisted emitter has been recorded on it. Without the feature
+// attestations can still be recorded but settlement doesn't wait for them.
+pub const ALIEN_ATTESTATION_PAYLOAD_ID: u8 = 2;
+
+const POSTED_VAA_MAGIC: &[u8] = b"vaa";
+// magic, version, consistency level, vaa time, signature set, submission time, nonce
+const POSTED_VAA_HEADER_LEN: usize = 3 + 1 + 1 + 4 + 32 + 4 + 4;
+
+#[derive(AnchorDeserialize)]
+pub struct PostedVaa {
+    pub sequence: u64,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub payload: Vec<u8>,
+}
+
+impl PostedVaa {
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, WORMHOLE_CORE_BRIDGE, ErrorCode::InvalidVaa);
+
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() > POSTED_VAA_HEADER_LEN && data.starts_with(POSTED_VAA_MAGIC),
+            ErrorCode::InvalidVaa
+        );
+        PostedVaa::deserialize(&mut &data[POSTED_VAA_HEADER_LEN..]).map_err(|_|
+            error!(ErrorCode::InvalidVaa)
+        )
+    }
+}
+
+pub struct BridgedBid {
+    pub listing_id: [u8; 32],
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+impl BridgedBid {
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        require!(payload.len() == 73, ErrorCode::InvalidBridgePayload);
+        require!(payload[0] == BRIDGED_BID_PAYLOAD_ID, ErrorCode::InvalidBridgePayload);
+
+        let bidder = Pubkey::try_from(&payload[1..33]).unwrap();
+        let amount = u64::from_be_bytes(payload[33..41].try_into().unwrap());
+        let listing_id = payload[41..73].try_into().unwrap();
+
+        Ok(BridgedBid { listing_id, bidder, amount })
+    }
+}
+
+pub struct AlienAttestation {
+    pub listing_id: [u8; 32],
+    pub kind: AttestationKind,
+}
+
+impl AlienAttestation {
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        require!(payload.len() == 34, ErrorCode::InvalidAttestationPayload);
+        require!(payload[0] == ALIEN_ATTESTATION_PAYLOAD_ID, ErrorCode::InvalidAttestationPayload);
+
+        let listing_id = payload[1..33].try_into().unwrap();
+        let kind = match payload[33] {
+            0 => AttestationKind::Payment,
+            1 => AttestationKind::NftLock,
+            _ => {
+                return err!(ErrorCode::InvalidAttestationPayload);
+            }
+        };
+        Ok(AlienAttestation { listing_id, kind })
+    }
+}
+
+// Settlement gate for alien auctions
+#[cfg(feature = "wormhole-attestations")]
+pub fn ensure_attested(auction: &AuctionDetails) -> Result<()> {
+    require!(
+        !auction.is_alien || auction.alien_attestation.is_some(),
+        ErrorCode::AttestationRequired
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "wormhole-attestations"))]
+#[inline(always)]
+pub fn ensure_attested(_auction: &AuctionDetails) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::{ self, Token, TokenAccount, Transfer };
+
+declare_id!("6jWDCw2XaPf99M64CLbWV9FqLfXHnWbKjm1GG53rzmMg");
+
+// Seed of the PDA sellers delegate their NFT to
+#[constant]
+pub const ESCROW_AUTHORITY_SEED: &[u8] = b"escrow_authority";
+
+// Stand-in external escrow for the auction program's tests, speaking the
+// adapter handshake in its `escrow_adapter` module. The NFT stays in the
+// seller's token account, delegated to this program's escrow authority, which
+// moves it to the recipient when the auction program asks. Each release logs
+// the payload's price so tests can check what the auction program reported.
+#[program]
+pub mod mock_escrow_adapter {
+    use super::*;
+
+    // Vouch for a listing only while the NFT is delegated to the escrow authority
+    pub fn confirm_external_listing(
+        ctx: Context<ConfirmExternalListing>,
+        _listing_id: [u8; 32],
+        seller: Pubkey,
+        mint: Pubkey
+    ) -> Result<()> {
+        let token_account = &ctx.accounts.token_account;
+        require_keys_eq!(token_account.owner, seller, AdapterError::NotHeld);
+        require_keys_eq!(token_account.mint, mint, AdapterError::NotHeld);
+        require!(
+            token_account.delegate == COption::Some(escrow_authority()) &&
+                token_account.delegated_amount == 1,
+            AdapterError::NotHeld
+        );
+        Ok(())
+    }
+
+    // Hand the NFT to `recipient`, whose token account comes first after the
+    // handshake's own accounts
+    pub fn release_to_winner(
+        ctx: Context<ReleaseToWinner>,
+        _listing_id: [u8; 32],
+        _winner: Pubkey,
+        price: u64,
+        recipient: Pubkey
+    ) -> Result<()> {
+        require_keys_eq!(ctx.accounts.destination.owner, recipient, AdapterError::WrongRecipient);
+        msg!("Released to {} at price {}", recipient, price);
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.token_account.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.escrow_authority.to_account_info(),
+                },
+                &[&[ESCROW_AUTHORITY_SEED, &[ctx.bumps.escrow_authority]]]
+            ),
+            1
+        )
+    }
+}
+
+fn escrow_authority() -> Pubkey {
+    Pubkey::find_program_address(&[ESCROW_AUTHORITY_SEED], &crate::ID).0
+}
+
+#[derive(Accounts)]
+pub struct ConfirmExternalListing<'info> {
+    // The auction program's state PDA
+    pub auction_state: Signer<'info>,
+    pub seller: Signer<'info>,
+    pub token_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseToWinner<'info> {
+    // The auction program's state PDA
+    pub auction_state: Signer<'info>,
+    #[account(mut)]
+    pub token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+    /// CHECK: only signs the transfer as the token account's delegate
+    #[account(seeds = [ESCROW_AUTHORITY_SEED], bump)]
+    pub escrow_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[error_code]
+pub enum AdapterError {
+    #[msg("The NFT isn't delegated to the escrow for this seller")]
+    NotHeld,
+    #[msg("The destination isn't the recipient's")]
+    WrongRecipient,
+}
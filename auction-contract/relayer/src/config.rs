@@ -0,0 +1,57 @@
+use std::env;
+use std::time::Duration;
+
+use solana_sdk::pubkey::Pubkey;
+
+// Relayer settings, read from the environment so the binary runs unchanged
+// under systemd, docker or a plain shell
+pub struct Config {
+    pub ws_url: String,
+    pub program_id: Pubkey,
+    pub webhooks: Vec<String>,
+    pub webhook_secret: String,
+    pub ending_soon_window: i64,
+    pub tick_interval: Duration,
+    pub max_retries: u32,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, String> {
+        let webhooks: Vec<String> = env
+            ::var("RELAYER_WEBHOOKS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+        if webhooks.is_empty() {
+            return Err("RELAYER_WEBHOOKS must list at least one endpoint".to_string());
+        }
+
+        let webhook_secret = env
+            ::var("RELAYER_WEBHOOK_SECRET")
+            .map_err(|_| "RELAYER_WEBHOOK_SECRET is required to sign payloads".to_string())?;
+
+        let program_id = match env::var("RELAYER_PROGRAM_ID") {
+            Ok(id) => id.parse().map_err(|_| format!("invalid RELAYER_PROGRAM_ID: {}", id))?,
+            Err(_) => nft_com_auction::ID,
+        };
+
+        Ok(Config {
+            ws_url: env::var("RELAYER_WS_URL").unwrap_or_else(|_| "ws://127.0.0.1:8900".to_string()),
+            program_id,
+            webhooks,
+            webhook_secret,
+            ending_soon_window: parse_or("RELAYER_ENDING_SOON_SECS", 600)?,
+            tick_interval: Duration::from_secs(parse_or("RELAYER_TICK_SECS", 15)?),
+            max_retries: parse_or("RELAYER_MAX_RETRIES", 5)?,
+        })
+    }
+}
+
+fn parse_or<T: std::str::FromStr>(key: &str, default: T) -> Result<T, String> {
+    match env::var(key) {
+        Ok(value) => value.parse().map_err(|_| format!("invalid {}: {}", key, value)),
+        Err(_) => Ok(default),
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::{ AnchorDeserialize, Discriminator };
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use nft_com_auction::events::{ AuctionEnded, AuctionExtended, AuctionInitialized, BidPlaced };
+
+// Anchor `emit!` writes each event as base64 after this prefix in the logs
+const PROGRAM_DATA: &str = "Program data: ";
+
+pub enum ProgramEvent {
+    AuctionInitialized(AuctionInitialized),
+    BidPlaced(BidPlaced),
+    AuctionExtended(AuctionExtended),
+    AuctionEnded(AuctionEnded),
+}
+
+// Pull every auction event out of one transaction's logs; lines from other
+// programs and unknown events are skipped
+pub fn decode_logs(logs: &[String]) -> Vec<ProgramEvent> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA))
+        .filter_map(|encoded| STANDARD.decode(encoded).ok())
+        .filter_map(|bytes| decode_event(&bytes))
+        .collect()
+}
+
+fn decode_event(bytes: &[u8]) -> Option<ProgramEvent> {
+    if bytes.len() < 8 {
+        return None;
+    }
+    let (discriminator, mut data) = bytes.split_at(8);
+
+    if discriminator == AuctionInitialized::DISCRIMINATOR {
+        AuctionInitialized::deserialize(&mut data).ok().map(ProgramEvent::AuctionInitialized)
+    } else if discriminator == BidPlaced::DISCRIMINATOR {
+        BidPlaced::deserialize(&mut data).ok().map(ProgramEvent::BidPlaced)
+    } else if discriminator == AuctionExtended::DISCRIMINATOR {
+        AuctionExtended::deserialize(&mut data).ok().map(ProgramEvent::AuctionExtended)
+    } else if discriminator == AuctionEnded::DISCRIMINATOR {
+        AuctionEnded::deserialize(&mut data).ok().map(ProgramEvent::AuctionEnded)
+    } else {
+        None
+    }
+}
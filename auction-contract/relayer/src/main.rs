@@ -0,0 +1,207 @@
+// Tails auction program logs and forwards events to webhook endpoints.
+//
+// Every notification is decoded into the program's Anchor events, mapped to
+// bid_placed / outbid / ending_soon / settled payloads, HMAC-signed and POSTed
+// with retries. See config.rs for the environment variables it reads.
+mod config;
+mod decode;
+mod webhook;
+
+use std::collections::HashMap;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use futures_util::StreamExt;
+use serde_json::json;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::{ RpcTransactionLogsConfig, RpcTransactionLogsFilter };
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::mpsc;
+
+use config::Config;
+use decode::{ decode_logs, ProgramEvent };
+use webhook::{ WebhookPayload, WebhookSender };
+
+// What the relayer remembers about a live listing between notifications.
+// A BidPlaced carries only the amount deposited, so each bidder's position is
+// the sum of the deposits seen from them.
+#[derive(Default)]
+struct ListingWatch {
+    end_time: i64,
+    positions: HashMap<Pubkey, u64>,
+    leader: Option<(Pubkey, u64)>,
+    ending_soon_sent: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // Delivery runs on its own task so slow endpoints never stall the log stream
+    let (tx, mut rx) = mpsc::unbounded_channel::<WebhookPayload>();
+    let sender = WebhookSender::new(config.webhooks.clone(), &config.webhook_secret, config.max_retries);
+    tokio::spawn(async move {
+        while let Some(payload) = rx.recv().await {
+            sender.deliver(&payload).await;
+        }
+    });
+
+    loop {
+        if let Err(err) = relay(&config, &tx).await {
+            eprintln!("log subscription dropped: {}, reconnecting", err);
+        }
+        tokio::time::sleep(config.tick_interval).await;
+    }
+}
+
+async fn relay(config: &Config, tx: &mpsc::UnboundedSender<WebhookPayload>) -> Result<(), String> {
+    let client = PubsubClient::new(&config.ws_url).await.map_err(|err| err.to_string())?;
+    let (mut logs, _unsubscribe) = client
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![config.program_id.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+            }
+        ).await
+        .map_err(|err| err.to_string())?;
+
+    let mut listings: HashMap<[u8; 32], ListingWatch> = HashMap::new();
+    let mut ticker = tokio::time::interval(config.tick_interval);
+
+    loop {
+        tokio::select! {
+            notification = logs.next() => {
+                let notification = notification.ok_or("stream closed")?;
+                // Failed transactions still log, but none of their events happened
+                if notification.value.err.is_some() {
+                    continue;
+                }
+                let signature = notification.value.signature;
+                for event in decode_logs(&notification.value.logs) {
+                    for payload in translate(event, &signature, &mut listings) {
+                        let _ = tx.send(payload);
+                    }
+                }
+            }
+            _ = ticker.tick() => {
+                for payload in ending_soon(config.ending_soon_window, &mut listings) {
+                    let _ = tx.send(payload);
+                }
+            }
+        }
+    }
+}
+
+fn translate(
+    event: ProgramEvent,
+    signature: &str,
+    listings: &mut HashMap<[u8; 32], ListingWatch>
+) -> Vec<WebhookPayload> {
+    let signature = Some(signature.to_string());
+    match event {
+        ProgramEvent::AuctionInitialized(event) => {
+            listings.insert(event.listing_id, ListingWatch {
+                end_time: event.end_time,
+                ..ListingWatch::default()
+            });
+            vec![]
+        }
+        ProgramEvent::BidPlaced(event) => {
+            // Listings created before the relayer started are picked up on first bid,
+            // without an end time until the program reports one
+            let watch = listings.entry(event.listing_id).or_insert_with(|| ListingWatch {
+                end_time: i64::MAX,
+                ..ListingWatch::default()
+            });
+            let position = watch.positions.entry(event.sender).or_default();
+            *position = position.saturating_add(event.value);
+            let total = *position;
+
+            let mut payloads = vec![WebhookPayload {
+                event: "bid_placed",
+                listing_id: hex::encode(event.listing_id),
+                signature: signature.clone(),
+                timestamp: now(),
+                data: json!({
+                    "bidder": event.sender.to_string(),
+                    "amount": event.value,
+                    "position": total,
+                    "relayer": event.relayer.map(|relayer| relayer.to_string()),
+                }),
+            }];
+            match watch.leader {
+                Some((leader, amount)) if total > amount => {
+                    if leader != event.sender {
+                        payloads.push(WebhookPayload {
+                            event: "outbid",
+                            listing_id: hex::encode(event.listing_id),
+                            signature,
+                            timestamp: now(),
+                            data: json!({
+                                "previous_bidder": leader.to_string(),
+                                "previous_amount": amount,
+                                "bidder": event.sender.to_string(),
+                                "amount": total,
+                            }),
+                        });
+                    }
+                    watch.leader = Some((event.sender, total));
+                }
+                Some(_) => {}
+                None => {
+                    watch.leader = Some((event.sender, total));
+                }
+            }
+            payloads
+        }
+        ProgramEvent::AuctionExtended(event) => {
+            // A later end time may move the listing back out of the ending-soon window
+            let watch = listings.entry(event.listing_id).or_default();
+            watch.end_time = event.end_time;
+            watch.ending_soon_sent = false;
+            vec![]
+        }
+        ProgramEvent::AuctionEnded(event) => {
+            listings.remove(&event.listing_id);
+            vec![WebhookPayload {
+                event: "settled",
+                listing_id: hex::encode(event.listing_id),
+                signature,
+                timestamp: now(),
+                data: json!({ "winner": event.winner.to_string(), "amount": event.amount }),
+            }]
+        }
+    }
+}
+
+// One ending_soon per listing, sent the first tick it is inside the window
+fn ending_soon(window: i64, listings: &mut HashMap<[u8; 32], ListingWatch>) -> Vec<WebhookPayload> {
+    let now = now();
+    listings
+        .iter_mut()
+        .filter(|(_, watch)| !watch.ending_soon_sent && watch.end_time.saturating_sub(window) <= now)
+        .map(|(listing_id, watch)| {
+            watch.ending_soon_sent = true;
+            WebhookPayload {
+                event: "ending_soon",
+                listing_id: hex::encode(listing_id),
+                signature: None,
+                timestamp: now,
+                data: json!({ "end_time": watch.end_time }),
+            }
+        })
+        .collect()
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
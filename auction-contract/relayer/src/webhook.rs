@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use hmac::{ Hmac, Mac };
+use serde::Serialize;
+use serde_json::Value;
+use sha2::Sha256;
+
+// Body POSTed to every endpoint. `event` is one of bid_placed, outbid,
+// ending_soon or settled; `data` carries the event-specific fields
+#[derive(Serialize)]
+pub struct WebhookPayload {
+    pub event: &'static str,
+    pub listing_id: String, // Hex of the 32-byte listing id
+    pub signature: Option<String>,
+    pub timestamp: i64,
+    pub data: Value,
+}
+
+pub struct WebhookSender {
+    client: reqwest::Client,
+    endpoints: Vec<String>,
+    secret: Vec<u8>,
+    max_retries: u32,
+}
+
+impl WebhookSender {
+    pub fn new(endpoints: Vec<String>, secret: &str, max_retries: u32) -> Self {
+        WebhookSender {
+            client: reqwest::Client::new(),
+            endpoints,
+            secret: secret.as_bytes().to_vec(),
+            max_retries,
+        }
+    }
+
+    // Deliver to every endpoint, retrying each with exponential backoff;
+    // a failing endpoint never blocks delivery to the others
+    pub async fn deliver(&self, payload: &WebhookPayload) {
+        let body = match serde_json::to_vec(payload) {
+            Ok(body) => body,
+            Err(err) => {
+                eprintln!("failed to encode {} payload: {}", payload.event, err);
+                return;
+            }
+        };
+        let signature = self.sign(&body);
+
+        for endpoint in &self.endpoints {
+            if
+                let Err(err) = self.post_with_retries(
+                    endpoint,
+                    &body,
+                    &signature,
+                    payload.event
+                ).await
+            {
+                eprintln!("giving up on {} for {}: {}", endpoint, payload.event, err);
+            }
+        }
+    }
+
+    async fn post_with_retries(
+        &self,
+        endpoint: &str,
+        body: &[u8],
+        signature: &str,
+        event: &str
+    ) -> Result<(), String> {
+        let mut attempt = 0;
+        loop {
+            let result = self.client
+                .post(endpoint)
+                .header("Content-Type", "application/json")
+                .header("X-Auction-Event", event)
+                .header("X-Auction-Signature", format!("sha256={}", signature))
+                .body(body.to_vec())
+                .send().await;
+
+            let error = match result {
+                Ok(response) if response.status().is_success() => {
+                    return Ok(());
+                }
+                Ok(response) => format!("HTTP {}", response.status()),
+                Err(err) => err.to_string(),
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error);
+            }
+            let backoff = Duration::from_millis(500 << attempt.min(6));
+            eprintln!("{} to {} failed ({}), retrying in {:?}", event, endpoint, error, backoff);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    // HMAC-SHA256 of the raw body, hex encoded, so receivers can verify the sender
+    fn sign(&self, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>
+            ::new_from_slice(&self.secret)
+            .expect("HMAC accepts keys of any length");
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
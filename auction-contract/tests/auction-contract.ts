@@ -1,16 +1,5996 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { Ed25519Program, Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { keccak_256 } from "@noble/hashes/sha3";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  NATIVE_MINT,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  approve,
+  createAccount,
+  createInitializeMintInstruction,
+  createInitializeTransferHookInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  createWrappedNativeAccount,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+  mintTo,
+} from "@solana/spl-token";
+import { expect } from "chai";
 import { AuctionContract } from "../target/types/auction_contract";
+import { MockEscrowAdapter } from "../target/types/mock_escrow_adapter";
 
 describe("auction-contract", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.AuctionContract as Program<AuctionContract>;
+  const authority = provider.wallet.publicKey;
+  const feeRecipient = Keypair.generate().publicKey;
+  const nftContract = Keypair.generate().publicKey;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  const [auctionState] = PublicKey.findProgramAddressSync(
+    [Buffer.from("auction_state")],
+    program.programId
+  );
+  // A listing's id is the hash of its seller and name
+  const listingKey = (seller: PublicKey, name: string) =>
+    Array.from(keccak_256(Buffer.concat([seller.toBuffer(), Buffer.from(name)])));
+  const auctionPda = (listingId: number[]) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("auction"), Buffer.from(listingId)],
+      program.programId
+    )[0];
+  const vaultPda = (listingId: number[]) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), Buffer.from(listingId)],
+      program.programId
+    )[0];
+
+  const [feeTreasury] = PublicKey.findProgramAddressSync(
+    [Buffer.from("fee_treasury"), NATIVE_MINT.toBuffer()],
+    program.programId
+  );
+  const [featureFlags] = PublicKey.findProgramAddressSync(
+    [Buffer.from("feature_flags")],
+    program.programId
+  );
+  // Token bids, buy-now and offers
+  const allFeatures = 0b111;
+  const provenancePda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("provenance"), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  // Steps in SettlementStep order: funds paid, NFT delivered, fees collected
+  const settlementReceiptPda = (auction: PublicKey, step: number, party: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("settlement_receipt"),
+        auction.toBuffer(),
+        Buffer.from([step]),
+        party.toBuffer(),
+      ],
+      program.programId
+    )[0];
+
+  const bidReceiptPda = (auction: PublicKey, bidder: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("bid_receipt"), auction.toBuffer(), bidder.toBuffer()],
+      program.programId
+    )[0];
+
+  const sellerProfilePda = (seller: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("seller_profile"), seller.toBuffer()],
+      program.programId
+    )[0];
+
+  const blacklistPda = (address: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("blacklist"), address.toBuffer()],
+      program.programId
+    )[0];
+
+  // Bar `address` from listing and bidding, as the admin
+  const blacklist = (address: PublicKey) =>
+    program.methods
+      .addToBlacklist(address)
+      .accounts({
+        auctionState,
+        blacklistEntry: blacklistPda(address),
+        authority,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  const defaults = {
+    defaultDuration: new anchor.BN(24 * 60 * 60),
+    minIncrementBps: new anchor.BN(500),
+    snipingTimeWindow: new anchor.BN(5 * 60),
+    timeExtension: new anchor.BN(5 * 60),
+    reservePolicy: { noReserve: {} },
+  };
+
+  const funded = async (wallet = Keypair.generate()) => {
+    const signature = await provider.connection.requestAirdrop(
+      wallet.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature);
+    return wallet;
+  };
+
+  const nftEscrowPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("nft_escrow"), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  // Mint a fresh NFT to `seller` and list it under `name`
+  const listNft = async (
+    name: string,
+    minimum: number,
+    seller: Keypair,
+    overrides: {
+      minIncrement?: anchor.BN;
+      buyNowPrice?: anchor.BN;
+      snipingTimeWindow?: anchor.BN;
+      timeExtension?: anchor.BN;
+      allowlistRoot?: number[];
+      gateMint?: PublicKey;
+      payoutSplits?: { recipient: PublicKey; shareBps: number }[];
+      bidHistoryCapacity?: number;
+      growBidHistory?: boolean;
+      secondPrice?: boolean;
+      candleWindow?: anchor.BN;
+      rentalDays?: number;
+      paymentMint?: PublicKey;
+      usdPricing?: { feed: { kind: object; id: number[] }; minimumBidUsd: anchor.BN; reserveUsd: anchor.BN };
+      feeOverrideBps?: { buyerFeeBps: anchor.BN; sellerFeeBps: anchor.BN };
+      feeApprover?: PublicKey;
+      sellerProfile?: PublicKey;
+      collectionConfig?: PublicKey;
+      // Sponsors the listing's rent instead of the seller
+      payer?: Keypair;
+      // Lists under this id rather than the one the seller's key and name hash to
+      listingId?: number[];
+      coOwned?: boolean;
+      // Co-owners co-signing a co-owned listing
+      coSellers?: Keypair[];
+      beneficiary?: { wallet: PublicKey; shareBps: number };
+    } = {}
+  ) => {
+    const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+    const sellerNftAccount = await createAccount(
+      provider.connection,
+      seller,
+      nftMint,
+      seller.publicKey
+    );
+    await mintTo(provider.connection, seller, nftMint, sellerNftAccount, seller, 1);
+
+    const listingId = overrides.listingId ?? listingKey(seller.publicKey, name);
+    await program.methods
+      .initializeAuction(
+        listingId,
+        new anchor.BN(minimum),
+        seller.publicKey,
+        {
+          endTime: null,
+          reservePrice: null,
+          minIncrementBps: null,
+          minIncrement: overrides.minIncrement ?? null,
+          buyNowPrice: overrides.buyNowPrice ?? null,
+          snipingTimeWindow: overrides.snipingTimeWindow ?? null,
+          timeExtension: overrides.timeExtension ?? null,
+          allowlistRoot: overrides.allowlistRoot ?? null,
+          gateMint: overrides.gateMint ?? null,
+          payoutSplits: overrides.payoutSplits ?? null,
+          bidHistoryCapacity: overrides.bidHistoryCapacity ?? null,
+          growBidHistory: overrides.growBidHistory ?? null,
+          secondPrice: overrides.secondPrice ?? null,
+          candleWindow: overrides.candleWindow ?? null,
+          rentalDays: overrides.rentalDays ?? null,
+          paymentMint: overrides.paymentMint ?? null,
+          usdPricing: overrides.usdPricing ?? null,
+          name,
+          coOwned: overrides.coOwned ?? null,
+          beneficiary: overrides.beneficiary ?? null,
+        },
+        overrides.feeOverrideBps ?? null
+      )
+      .accounts({
+        auctionState,
+        auction: auctionPda(listingId),
+        vault: vaultPda(listingId),
+        owner: seller.publicKey,
+        payer: (overrides.payer ?? seller).publicKey,
+        sellerProfile: overrides.sellerProfile ?? null,
+        blacklistEntry: blacklistPda(seller.publicKey),
+        feeApprover: overrides.feeApprover ?? null,
+        collectionConfig: overrides.collectionConfig ?? null,
+        nftMetadata: null,
+        nftMint,
+        sellerNftAccount,
+        nftEscrow: nftEscrowPda(nftMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        (overrides.coSellers ?? []).map((coSeller) => ({
+          pubkey: coSeller.publicKey,
+          isSigner: true,
+          isWritable: false,
+        }))
+      )
+      .signers([seller, ...(overrides.payer ? [overrides.payer] : []), ...(overrides.coSellers ?? [])])
+      .rpc();
+    return { nftMint, sellerNftAccount, listingId };
+  };
+
+  it("initializes the global state", async () => {
+    await program.methods
+      .initialize(feeRecipient, nftContract, new anchor.BN(250), new anchor.BN(250), defaults)
+      .accounts({ auctionState, authority, systemProgram: SystemProgram.programId })
+      .rpc();
+
+    const state = await program.account.nftComAuction.fetch(auctionState);
+    expect(state.authority.toBase58()).to.equal(authority.toBase58());
+    expect(state.feeRecipient.toBase58()).to.equal(feeRecipient.toBase58());
+    expect(state.nftContract.toBase58()).to.equal(nftContract.toBase58());
+    expect(state.buyerFeeBps.toNumber()).to.equal(250);
+    expect(state.sellerFeeBps.toNumber()).to.equal(250);
+    expect(state.snipingTimeWindow.toNumber()).to.equal(300);
+    expect(state.timeExtension.toNumber()).to.equal(300);
+  });
+
+  it("creates the SOL fee treasury", async () => {
+    await program.methods
+      .initFeeTreasury(NATIVE_MINT)
+      .accounts({
+        auctionState,
+        feeTreasury,
+        authority,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const treasury = await program.account.feeTreasury.fetch(feeTreasury);
+    expect(treasury.mint.toBase58()).to.equal(NATIVE_MINT.toBase58());
+    expect(treasury.collected.toNumber()).to.equal(0);
+  });
+
+  it("switches on every feature for the suite", async () => {
+    await program.methods
+      .initFeatureFlags(new anchor.BN(allFeatures))
+      .accounts({
+        auctionState,
+        featureFlags,
+        authority,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const flags = await program.account.featureFlags.fetch(featureFlags);
+    expect(flags.flags.toNumber()).to.equal(allFeatures);
+  });
+
+  it("cannot be initialized twice", async () => {
+    let failed = false;
+    try {
+      await program.methods
+        .initialize(feeRecipient, nftContract, new anchor.BN(0), new anchor.BN(0), defaults)
+        .accounts({ auctionState, authority, systemProgram: SystemProgram.programId })
+        .rpc();
+    } catch (_) {
+      failed = true;
+    }
+    expect(failed).to.be.true;
+  });
+
+  describe("admin-only instructions", () => {
+    const expectUnauthorized = async (call: () => Promise<string>) => {
+      let error: any;
+      try {
+        await call();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+    };
+
+    let stranger: Keypair;
+    before(async () => {
+      stranger = await funded();
+    });
+
+    it("rejects change_fee_recipient from an arbitrary signer", async () => {
+      await expectUnauthorized(() =>
+        program.methods
+          .changeFeeRecipient(stranger.publicKey)
+          .accounts({ auctionState, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc()
+      );
+    });
+
+    it("rejects change_nft_contract from an arbitrary signer", async () => {
+      await expectUnauthorized(() =>
+        program.methods
+          .changeNftContract(stranger.publicKey)
+          .accounts({ auctionState, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc()
+      );
+    });
+
+    it("rejects set_fees from an arbitrary signer", async () => {
+      await expectUnauthorized(() =>
+        program.methods
+          .setFees(new anchor.BN(0), new anchor.BN(0))
+          .accounts({ auctionState, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc()
+      );
+    });
+
+    it("rejects set_royalty_enforcement from an arbitrary signer", async () => {
+      await expectUnauthorized(() =>
+        program.methods
+          .setRoyaltyEnforcement(true)
+          .accounts({ auctionState, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc()
+      );
+    });
+
+    it("lets the admin enforce creator royalties", async () => {
+      await program.methods.setRoyaltyEnforcement(true).accounts({ auctionState, authority }).rpc();
+      let state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.featureFlags.toNumber() & 1).to.equal(1);
+
+      await program.methods.setRoyaltyEnforcement(false).accounts({ auctionState, authority }).rpc();
+      state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.featureFlags.toNumber() & 1).to.equal(0);
+    });
+
+    it("rejects set_curated_listings from an arbitrary signer", async () => {
+      await expectUnauthorized(() =>
+        program.methods
+          .setCuratedListings(true)
+          .accounts({ auctionState, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc()
+      );
+    });
+
+    it("only lists approved collections while curating", async () => {
+      await program.methods.setCuratedListings(true).accounts({ auctionState, authority }).rpc();
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.featureFlags.toNumber() & 2).to.equal(2);
+
+      const seller = await funded();
+      let error: any;
+      try {
+        await listNft("uncurated", anchor.web3.LAMPORTS_PER_SOL, seller);
+      } catch (e) {
+        error = e;
+      } finally {
+        await program.methods.setCuratedListings(false).accounts({ auctionState, authority }).rpc();
+      }
+      expect(error?.error?.errorCode?.code).to.equal("CollectionNotApproved");
+      await listNft("uncurated", anchor.web3.LAMPORTS_PER_SOL, seller);
+    });
+
+    it("rejects set_keeper_tip from an arbitrary signer", async () => {
+      await expectUnauthorized(() =>
+        program.methods
+          .setKeeperTip(new anchor.BN(5_000))
+          .accounts({ auctionState, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc()
+      );
+    });
+
+    it("lets the admin set the keeper tip", async () => {
+      await program.methods
+        .setKeeperTip(new anchor.BN(5_000))
+        .accounts({ auctionState, authority })
+        .rpc();
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.keeperTip.toNumber()).to.equal(5_000);
+    });
+
+    it("rejects set_antisniping_config from an arbitrary signer", async () => {
+      await expectUnauthorized(() =>
+        program.methods
+          .setAntisnipingConfig(new anchor.BN(0), new anchor.BN(0), 0)
+          .accounts({ auctionState, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc()
+      );
+    });
+
+    it("lets the admin set the anti-sniping config", async () => {
+      await program.methods
+        .setAntisnipingConfig(defaults.snipingTimeWindow, defaults.timeExtension, 3)
+        .accounts({ auctionState, authority })
+        .rpc();
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.snipingTimeWindow.toNumber()).to.equal(5 * 60);
+      expect(state.timeExtension.toNumber()).to.equal(5 * 60);
+      expect(state.maxExtensions).to.equal(3);
+    });
+
+    it("rejects fees above the 10% cap", async () => {
+      const setFees = async (buyerFeeBps: number, sellerFeeBps: number) => {
+        try {
+          await program.methods
+            .setFees(new anchor.BN(buyerFeeBps), new anchor.BN(sellerFeeBps))
+            .accounts({ auctionState, authority })
+            .rpc();
+        } catch (e) {
+          return e?.error?.errorCode?.code;
+        }
+      };
+      expect(await setFees(1_001, 0)).to.equal("BuyerFeeTooHigh");
+      expect(await setFees(0, 1_001)).to.equal("SellerFeeTooHigh");
+    });
+
+    it("charges buyers by tier of bid size", async () => {
+      const SOL = anchor.web3.LAMPORTS_PER_SOL;
+      const setTiers = async (tiers: { upTo: anchor.BN; bps: anchor.BN }[]) => {
+        try {
+          await program.methods.setBuyerFeeTiers(tiers).accounts({ auctionState, authority }).rpc();
+        } catch (e) {
+          return e?.error?.errorCode?.code;
+        }
+      };
+      const tiers = [
+        { upTo: new anchor.BN(SOL), bps: new anchor.BN(500) },
+        { upTo: new anchor.BN(100 * SOL), bps: new anchor.BN(250) },
+        { upTo: new anchor.BN("18446744073709551615"), bps: new anchor.BN(100) },
+      ];
+      // Amounts past the last tier would have no fee
+      expect(await setTiers(tiers.slice(0, 2))).to.equal("InvalidFeeTiers");
+      expect(await setTiers([tiers[1], tiers[0], tiers[2]])).to.equal("InvalidFeeTiers");
+
+      expect(await setTiers(tiers)).to.equal(undefined);
+      let state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.buyerFeeTiers.map((tier) => tier.bps.toNumber())).to.deep.equal([500, 250, 100]);
+
+      expect(await setTiers([])).to.equal(undefined);
+      state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.buyerFeeTiers).to.be.empty;
+    });
+
+    // Freezing can't be undone, so the suite leaves the config unfrozen
+    it("only lets the admin freeze the config", async () => {
+      const stranger = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .freezeConfig()
+          .accounts({ auctionState, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+      expect((await program.account.nftComAuction.fetch(auctionState)).configFrozen).to.be.false;
+    });
+
+    it("lets the admin change the fees", async () => {
+      await program.methods
+        .setFees(new anchor.BN(300), new anchor.BN(200))
+        .accounts({ auctionState, authority })
+        .rpc();
+
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.buyerFeeBps.toNumber()).to.equal(300);
+      expect(state.sellerFeeBps.toNumber()).to.equal(200);
+    });
+  });
+
+  describe("audit log", () => {
+    const [auditLog] = PublicKey.findProgramAddressSync(
+      [Buffer.from("audit_log")],
+      program.programId
+    );
+
+    before(async () => {
+      await program.methods
+        .initAuditLog()
+        .accounts({ auctionState, auditLog, authority, payer: authority })
+        .rpc();
+    });
+
+    it("records fee changes with their old and new values", async () => {
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      const { recorded } = await program.account.auditLog.fetch(auditLog);
+      await program.methods
+        .setFees(new anchor.BN(250), new anchor.BN(150))
+        .accounts({ auctionState, authority, auditLog })
+        .rpc();
+
+      const log = await program.account.auditLog.fetch(auditLog);
+      expect(log.recorded.toNumber()).to.equal(recorded.toNumber() + 2);
+      const [buyer, seller] = log.entries.slice(-2);
+      expect(buyer.action).to.deep.equal({ buyerFeeChanged: {} });
+      expect(buyer.actor.toBase58()).to.equal(authority.toBase58());
+      expect(buyer.oldValue.toNumber()).to.equal(state.buyerFeeBps.toNumber());
+      expect(buyer.newValue.toNumber()).to.equal(250);
+      expect(seller.action).to.deep.equal({ sellerFeeChanged: {} });
+      expect(seller.newValue.toNumber()).to.equal(150);
+      expect(buyer.slot.toNumber()).to.be.greaterThan(0);
+      expect(log.digest).to.not.deep.equal(new Array(32).fill(0));
+
+      await program.methods
+        .setFees(state.buyerFeeBps, state.sellerFeeBps)
+        .accounts({ auctionState, authority, auditLog })
+        .rpc();
+    });
+
+    it("records blacklist updates", async () => {
+      const flagged = Keypair.generate().publicKey;
+      await program.methods
+        .addToBlacklist(flagged)
+        .accounts({
+          auctionState,
+          blacklistEntry: blacklistPda(flagged),
+          authority,
+          payer: authority,
+          auditLog,
+        })
+        .rpc();
+      await program.methods
+        .removeFromBlacklist()
+        .accounts({ auctionState, blacklistEntry: blacklistPda(flagged), authority, auditLog })
+        .rpc();
+
+      const [added, removed] = (await program.account.auditLog.fetch(auditLog)).entries.slice(-2);
+      for (const entry of [added, removed]) {
+        expect(entry.action).to.deep.equal({ blacklistUpdated: {} });
+        expect(entry.target.toBase58()).to.equal(flagged.toBase58());
+      }
+      expect([added.newValue.toNumber(), removed.newValue.toNumber()]).to.deep.equal([1, 0]);
+    });
+  });
+
+  // Stands in for a Squads-style vault: the vault is an address holding no SOL
+  // that only signs once enough members approve, while someone else pays the
+  // fees and rent, as when the multisig program executes a transaction
+  describe("multisig authority", () => {
+    const vault = Keypair.generate();
+    const members = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    const threshold = 2;
+
+    const execute = async (approvals: Keypair[], call: (signer: PublicKey) => any) => {
+      const approved = new Set(
+        approvals
+          .filter((approver) => members.some((member) => member.publicKey.equals(approver.publicKey)))
+          .map((approver) => approver.publicKey.toBase58())
+      );
+      if (approved.size < threshold) {
+        throw new Error("multisig threshold not met");
+      }
+      return call(vault.publicKey).signers([vault]).rpc();
+    };
+
+    before(async () => {
+      await program.methods
+        .proposeAuthority(vault.publicKey)
+        .accounts({ auctionState, authority })
+        .rpc();
+    });
+
+    after(async () => {
+      await execute(members, (signer) =>
+        program.methods.proposeAuthority(authority).accounts({ auctionState, authority: signer })
+      );
+      await program.methods
+        .acceptAuthority()
+        .accounts({ auctionState, newAuthority: authority })
+        .rpc();
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.authority.toBase58()).to.equal(authority.toBase58());
+    });
+
+    it("only lets the proposed authority accept", async () => {
+      const stranger = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .acceptAuthority()
+          .accounts({ auctionState, newAuthority: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+
+      await execute(members.slice(0, threshold), (signer) =>
+        program.methods.acceptAuthority().accounts({ auctionState, newAuthority: signer })
+      );
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.authority.toBase58()).to.equal(vault.publicKey.toBase58());
+      expect(state.pendingAuthority).to.be.null;
+    });
+
+    it("drives config changes through the vault", async () => {
+      const { keeperTip } = await program.account.nftComAuction.fetch(auctionState);
+      await execute(members.slice(1), (signer) =>
+        program.methods
+          .setKeeperTip(new anchor.BN(7_000))
+          .accounts({ auctionState, authority: signer })
+      );
+      expect((await program.account.nftComAuction.fetch(auctionState)).keeperTip.toNumber()).to.equal(
+        7_000
+      );
+
+      const flagged = Keypair.generate().publicKey;
+      await execute(members, (signer) =>
+        program.methods.addToBlacklist(flagged).accounts({
+          auctionState,
+          blacklistEntry: blacklistPda(flagged),
+          authority: signer,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+      );
+      await execute(members, (signer) =>
+        program.methods
+          .removeFromBlacklist()
+          .accounts({ auctionState, blacklistEntry: blacklistPda(flagged), authority: signer })
+      );
+      await execute(members, (signer) =>
+        program.methods.setKeeperTip(keeperTip).accounts({ auctionState, authority: signer })
+      );
+    });
+
+    it("rejects the old authority once the vault has taken over", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .setKeeperTip(new anchor.BN(1))
+          .accounts({ auctionState, authority })
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+    });
+  });
+
+  describe("timelocked config changes", () => {
+    const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+    const proposalPda = (id: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("param_change"), id.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const queue = async (change: any) => {
+      const { paramChangesQueued } = await program.account.nftComAuction.fetch(auctionState);
+      const proposal = proposalPda(paramChangesQueued);
+      await program.methods
+        .queueConfigChange(change)
+        .accounts({
+          auctionState,
+          proposal,
+          authority,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      return proposal;
+    };
+
+    const execute = (proposal: PublicKey) =>
+      program.methods.executeConfigChange().accounts({ auctionState, proposal }).rpc();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    before(async () => {
+      await program.methods
+        .setConfigChangeDelay(new anchor.BN(2))
+        .accounts({ auctionState, authority })
+        .rpc();
+    });
+
+    it("stops direct fee changes once a delay is set", async () => {
+      expect(
+        await errorOf(
+          program.methods
+            .setFees(new anchor.BN(100), new anchor.BN(100))
+            .accounts({ auctionState, authority })
+            .rpc()
+        )
+      ).to.equal("ConfigChangeTimelocked");
+      expect(
+        await errorOf(
+          program.methods
+            .setConfigChangeDelay(new anchor.BN(0))
+            .accounts({ auctionState, authority })
+            .rpc()
+        )
+      ).to.equal("InvalidConfigChangeDelay");
+    });
+
+    it("applies a queued fee change only after the delay", async () => {
+      const before = await program.account.nftComAuction.fetch(auctionState);
+      const proposal = await queue({
+        fees: { buyerFeeBps: new anchor.BN(100), sellerFeeBps: new anchor.BN(150) },
+      });
+      expect(await errorOf(execute(proposal))).to.equal("ParamChangeNotReady");
+
+      await sleep(3_000);
+      await execute(proposal);
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.buyerFeeBps.toNumber()).to.equal(100);
+      expect(state.sellerFeeBps.toNumber()).to.equal(150);
+      expect(await errorOf(execute(proposal))).to.equal("ParamChangeExecuted");
+
+      const restore = await queue({
+        fees: { buyerFeeBps: before.buyerFeeBps, sellerFeeBps: before.sellerFeeBps },
+      });
+      await sleep(3_000);
+      await execute(restore);
+    });
+
+    it("queues a shorter delay like any other change", async () => {
+      const proposal = await queue({ configChangeDelay: [new anchor.BN(0)] });
+      await sleep(3_000);
+      await execute(proposal);
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.configChangeDelay.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("collection fee configs", () => {
+    const collection = Keypair.generate().publicKey;
+    const [collectionFeeConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("collection_fee"), collection.toBuffer()],
+      program.programId
+    );
+
+    it("rejects a fee config from an arbitrary signer", async () => {
+      const stranger = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .createCollectionFeeConfig(collection, new anchor.BN(0), new anchor.BN(0))
+          .accounts({
+            auctionState,
+            collectionFeeConfig,
+            signer: stranger.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([stranger])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+    });
+
+    it("lets the admin run a 0% promotion for a collection", async () => {
+      await program.methods
+        .createCollectionFeeConfig(collection, new anchor.BN(0), new anchor.BN(0))
+        .accounts({
+          auctionState,
+          collectionFeeConfig,
+          signer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const config = await program.account.collectionFeeConfig.fetch(collectionFeeConfig);
+      expect(config.collection.toBase58()).to.equal(collection.toBase58());
+      expect(config.buyerFeeBps.toNumber()).to.equal(0);
+      expect(config.sellerFeeBps.toNumber()).to.equal(0);
+    });
+
+    it("rejects an update above the fee cap", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .updateCollectionFeeConfig(new anchor.BN(0), new anchor.BN(1_001))
+          .accounts({ auctionState, collectionFeeConfig, signer: authority })
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("SellerFeeTooHigh");
+    });
+  });
+
+  describe("membership discounts", () => {
+    const collection = Keypair.generate().publicKey;
+    const [membershipDiscount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("membership_discount"), collection.toBuffer()],
+      program.programId
+    );
+
+    it("lets the admin give members half off their fees", async () => {
+      await program.methods
+        .createMembershipDiscount(collection, new anchor.BN(5_000))
+        .accounts({
+          auctionState,
+          membershipDiscount,
+          signer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const discount = await program.account.membershipDiscount.fetch(membershipDiscount);
+      expect(discount.collection.toBase58()).to.equal(collection.toBase58());
+      expect(discount.discountBps.toNumber()).to.equal(5_000);
+    });
+
+    it("rejects a discount above the whole fee", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .updateMembershipDiscount(new anchor.BN(10_001))
+          .accounts({ auctionState, membershipDiscount, signer: authority })
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidMembershipDiscount");
+    });
+  });
+
+  describe("collection registry", () => {
+    const collection = Keypair.generate().publicKey;
+    const [collectionConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("collection"), collection.toBuffer()],
+      program.programId
+    );
+    const [collectionStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("collection_stats"), collection.toBuffer()],
+      program.programId
+    );
+    const [floorOracle] = PublicKey.findProgramAddressSync(
+      [Buffer.from("floor_oracle"), collection.toBuffer()],
+      program.programId
+    );
+
+    before(async () => {
+      await program.methods
+        .registerCollection(collection, null, new anchor.BN(0))
+        .accounts({
+          auctionState,
+          collectionConfig,
+          collectionStats,
+          floorOracle,
+          authority,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("won't list an NFT under a collection it isn't verified in", async () => {
+      const seller = await funded();
+      let error: any;
+      try {
+        await listNft("unverified member", anchor.web3.LAMPORTS_PER_SOL, seller, { collectionConfig });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("CollectionNotVerified");
+    });
+
+    it("opens the collection's price stats, with no sales to average yet", async () => {
+      const stats = await program.account.collectionStats.fetch(collectionStats);
+      expect(stats.collection.toBase58()).to.equal(collection.toBase58());
+      expect(stats.sales.toNumber()).to.equal(0);
+
+      let error: any;
+      try {
+        await program.methods
+          .getCollectionTwap(collection, new anchor.BN(3_600))
+          .accounts({ collectionStats })
+          .view();
+      } catch (e) {
+        error = e;
+      }
+      const logs = error?.simulationResponse?.logs ?? error?.logs ?? [];
+      expect(logs.join("\n")).to.contain("NoCollectionSales");
+    });
+
+    it("opens an empty floor oracle with the default window", async () => {
+      const oracle = await program.account.floorOracle.fetch(floorOracle);
+      expect(oracle.collection.toBase58()).to.equal(collection.toBase58());
+      expect(oracle.floor.toNumber()).to.equal(0);
+      expect(oracle.window.toNumber()).to.equal(7 * 24 * 60 * 60);
+    });
+  });
+
+  describe("negotiated listing fees", () => {
+    const feeOverrideBps = { buyerFeeBps: new anchor.BN(50), sellerFeeBps: new anchor.BN(100) };
+
+    it("rejects a fee override nobody from the marketplace co-signed", async () => {
+      let error: any;
+      try {
+        await listNft("fee-deal-unsigned", 1_000, await funded(), { feeOverrideBps });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("FeeOverrideNotApproved");
+    });
+
+    it("applies a fee override the admin co-signed", async () => {
+      const { listingId } = await listNft("fee-deal", 1_000, await funded(), {
+        feeOverrideBps,
+        feeApprover: authority,
+      });
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.feeOverride.buyerFeeBps.toNumber()).to.equal(50);
+      expect(details.feeOverride.sellerFeeBps.toNumber()).to.equal(100);
+    });
+  });
+
+  describe("listing names", () => {
+    const seller = Keypair.generate();
+
+    before(async () => {
+      await funded(seller);
+    });
+
+    it("keys the listing by the hash of its seller and name", async () => {
+      const { listingId } = await listNft("Sunset #1", 1_000, seller);
+      expect(listingId).to.deep.equal(listingKey(seller.publicKey, "Sunset #1"));
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.listingId).to.deep.equal(listingId);
+      expect(details.name).to.equal("Sunset #1");
+    });
+
+    it("lets another seller use the same name", async () => {
+      const other = await funded();
+      const { listingId } = await listNft("Sunset #1", 1_000, other);
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.owner.toBase58()).to.equal(other.publicKey.toBase58());
+    });
+
+    it("rejects an id outside the seller's namespace", async () => {
+      const squatter = await funded();
+      let error: any;
+      try {
+        await listNft("Sunset #2", 1_000, squatter, {
+          listingId: listingKey(seller.publicKey, "Sunset #2"),
+        });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidListingId");
+    });
+
+    it("lets a marketplace sponsor the listing's rent", async () => {
+      const sponsor = await funded();
+      const before = await provider.connection.getBalance(sponsor.publicKey);
+      const { listingId } = await listNft("Sponsored", 1_000, seller, { payer: sponsor });
+      const rent = await provider.connection.getBalance(auctionPda(listingId));
+      expect(await provider.connection.getBalance(sponsor.publicKey)).to.be.at.most(before - rent);
+    });
+
+    it("rejects a name longer than 32 bytes", async () => {
+      let error: any;
+      try {
+        await listNft("x".repeat(33), 1_000, seller);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("ListingNameTooLong");
+    });
+
+    it("rejects a name with control characters", async () => {
+      let error: any;
+      try {
+        await listNft("line\nbreak", 1_000, seller);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidListingName");
+    });
+  });
+
+  describe("listing templates", () => {
+    const seller = Keypair.generate();
+    const templateId = 7;
+    const [template] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("auction_template"),
+        seller.publicKey.toBuffer(),
+        new anchor.BN(templateId).toArrayLike(Buffer, "le", 2),
+      ],
+      program.programId
+    );
+    const terms = {
+      duration: new anchor.BN(3_600),
+      minimum: new anchor.BN(5_000),
+      minIncrementBps: new anchor.BN(500),
+      minIncrement: null,
+      paymentMint: null,
+      allowlistRoot: null,
+    };
+
+    before(async () => {
+      await funded(seller);
+      await program.methods
+        .createAuctionTemplate(templateId, terms)
+        .accounts({ template, owner: seller.publicKey, systemProgram: SystemProgram.programId })
+        .signers([seller])
+        .rpc();
+    });
+
+    it("lists an NFT on the template's terms", async () => {
+      const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      const sellerNftAccount = await createAccount(
+        provider.connection,
+        seller,
+        nftMint,
+        seller.publicKey
+      );
+      await mintTo(provider.connection, seller, nftMint, sellerNftAccount, seller, 1);
+
+      const listingId = listingKey(seller.publicKey, "templated");
+      await program.methods
+        .initializeFromTemplate(listingId, "templated")
+        .accounts({
+          auctionState,
+          template,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          owner: seller.publicKey,
+          payer: seller.publicKey,
+          sellerProfile: null,
+          blacklistEntry: blacklistPda(seller.publicKey),
+          collectionConfig: null,
+          nftMetadata: null,
+          nftMint,
+          sellerNftAccount,
+          nftEscrow: nftEscrowPda(nftMint),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.minimumBid.toNumber()).to.equal(5_000);
+      expect(details.minIncrementBps.toNumber()).to.equal(500);
+      expect(details.endTime.toNumber() - details.createdAt.toNumber()).to.equal(3_600);
+    });
+
+    it("rejects terms without a minimum", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .updateAuctionTemplate({ ...terms, minimum: new anchor.BN(0) })
+          .accounts({ template, owner: seller.publicKey })
+          .signers([seller])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("MinimumBidError");
+    });
+  });
+
+  describe("seller listing cap", () => {
+    const seller = Keypair.generate();
+    const sellerProfile = sellerProfilePda(seller.publicKey);
+
+    const setCap = (cap: number) =>
+      program.methods
+        .setMaxActiveAuctionsPerSeller(cap)
+        .accounts({ auctionState, authority })
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      await program.methods
+        .initSellerProfile()
+        .accounts({ sellerProfile, seller: seller.publicKey, systemProgram: SystemProgram.programId })
+        .signers([seller])
+        .rpc();
+      await setCap(1);
+    });
+
+    after(async () => {
+      await setCap(0);
+    });
+
+    it("rejects a listing without a seller profile while a cap is set", async () => {
+      let error: any;
+      try {
+        await listNft("capped-no-profile", 1_000, await funded());
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("SellerProfileRequired");
+    });
+
+    let first: { nftMint: PublicKey; sellerNftAccount: PublicKey; listingId: number[] };
+
+    it("counts listings against the cap and rejects one over it", async () => {
+      first = await listNft("capped-first", 1_000, seller, { sellerProfile });
+      const { listingId } = first;
+      expect((await program.account.sellerProfile.fetch(sellerProfile)).activeAuctions).to.equal(1);
+      expect((await program.account.auctionDetails.fetch(auctionPda(listingId))).holdsSellerSlot).to
+        .be.true;
+
+      let error: any;
+      try {
+        await listNft("capped-second", 1_000, seller, { sellerProfile });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("TooManyActiveAuctions");
+    });
+
+    const release = () =>
+      program.methods
+        .releaseSellerSlots()
+        .accounts({ sellerProfile })
+        .remainingAccounts([
+          { pubkey: auctionPda(first.listingId), isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+    it("keeps the slot of a listing that is still open", async () => {
+      await release();
+      expect((await program.account.sellerProfile.fetch(sellerProfile)).activeAuctions).to.equal(1);
+    });
+
+    it("frees the slot once the listing is closed", async () => {
+      await program.methods
+        .cancelAuction(first.listingId)
+        .accounts({
+          auctionState,
+          auction: auctionPda(first.listingId),
+          owner: seller.publicKey,
+          nft: {
+            nftEscrow: nftEscrowPda(first.nftMint),
+            ownerNftAccount: first.sellerNftAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            coreAsset: null,
+            coreCollection: null,
+            mplCoreProgram: null,
+          },
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+      await release();
+      expect((await program.account.sellerProfile.fetch(sellerProfile)).activeAuctions).to.equal(0);
+
+      await listNft("capped-second", 1_000, seller, { sellerProfile });
+      expect((await program.account.sellerProfile.fetch(sellerProfile)).activeAuctions).to.equal(1);
+    });
+  });
+
+  describe("account versions", () => {
+    let listingId: number[];
+
+    it("stamps new accounts with the current layout version", async () => {
+      ({ listingId } = await listNft("versioned", 1_000, await funded()));
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.version).to.equal(1);
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.version).to.equal(1);
+    });
+
+    it("leaves an auction already on the current layout untouched", async () => {
+      const auction = auctionPda(listingId);
+      const before = await provider.connection.getAccountInfo(auction);
+      await program.methods
+        .migrateAuction(listingId)
+        .accounts({ auction, payer: authority, systemProgram: SystemProgram.programId })
+        .rpc();
+      const after = await provider.connection.getAccountInfo(auction);
+      expect(after.data.equals(before.data)).to.be.true;
+    });
+  });
+
+  describe("bid history", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "history-test");
+    const auction = auctionPda(listingId);
+
+    const bid = async (bidder: Keypair, amount: number) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault: vaultPda(listingId),
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    it("rejects a history with no room for bids", async () => {
+      let error: any;
+      try {
+        await listNft("history-empty", 1_000, await funded(), { bidHistoryCapacity: 0 });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidBidHistoryCapacity");
+    });
+
+    it("keeps only the latest bids but counts them all", async () => {
+      await listNft("history-test", 1_000, await funded(seller), { bidHistoryCapacity: 2 });
+      const bidders = [await funded(), await funded(), await funded()];
+      for (const [i, bidder] of bidders.entries()) {
+        await bid(bidder, (i + 1) * 1_000_000);
+      }
+
+      const { bidHistory } = await program.account.auctionDetails.fetch(auction);
+      expect(bidHistory.capacity).to.equal(2);
+      expect(bidHistory.records).to.have.length(2);
+      expect(bidHistory.bidCount.toNumber()).to.equal(3);
+      expect(bidHistory.records.map((r) => r.bidder.toBase58())).to.have.members(
+        bidders.slice(1).map((b) => b.publicKey.toBase58())
+      );
+    });
+
+    it("pages through the latest bids", async () => {
+      const page = await program.methods
+        .getLatestBids(listingId, new anchor.BN(1), new anchor.BN(5))
+        .accounts({ auction })
+        .view();
+      expect(page.total.toNumber()).to.equal(2);
+      expect(page.bidders).to.have.length(1);
+    });
+
+    it("grows a growable history instead of dropping bids", async () => {
+      const { listingId: growing } = await listNft("history-grow", 1_000, await funded(), {
+        bidHistoryCapacity: 1,
+        growBidHistory: true,
+      });
+      const before = await provider.connection.getAccountInfo(auctionPda(growing));
+
+      for (const [i, bidder] of [await funded(), await funded()].entries()) {
+        await program.methods
+          .placeBid(growing, bidder.publicKey, new anchor.BN((i + 1) * 1_000_000), [], null)
+          .accounts({
+            auctionState,
+            auction: auctionPda(growing),
+            vault: vaultPda(growing),
+            payer: bidder.publicKey,
+            blacklistEntry: blacklistPda(bidder.publicKey),
+            bidPage: null,
+            previousBidder: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            priceFeed: null,
+            tokenVault: null,
+            payerTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([bidder])
+          .rpc();
+      }
+
+      const { bidHistory } = await program.account.auctionDetails.fetch(auctionPda(growing));
+      expect(bidHistory.records).to.have.length(2);
+      expect(bidHistory.capacity).to.equal(17);
+      const after = await provider.connection.getAccountInfo(auctionPda(growing));
+      expect(after.data.length).to.be.greaterThan(before.data.length);
+    });
+  });
+
+  describe("payout splits", () => {
+    it("rejects splits whose shares do not add up to the whole", async () => {
+      const payoutSplits = [
+        { recipient: Keypair.generate().publicKey, shareBps: 7_000 },
+        { recipient: Keypair.generate().publicKey, shareBps: 2_000 },
+      ];
+      let error: any;
+      try {
+        await listNft("split-bad", 1_000, await funded(), { payoutSplits });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidPayoutSplits");
+    });
+
+    it("stores the seller's payout splits on the listing", async () => {
+      const payoutSplits = [
+        { recipient: Keypair.generate().publicKey, shareBps: 7_000 },
+        { recipient: Keypair.generate().publicKey, shareBps: 3_000 },
+      ];
+      const { listingId } = await listNft("split", 1_000, await funded(), { payoutSplits });
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.payoutSplits.map((s) => s.recipient.toBase58())).to.deep.equal(
+        payoutSplits.map((s) => s.recipient.toBase58())
+      );
+      expect(details.payoutSplits.map((s) => s.shareBps)).to.deep.equal([7_000, 3_000]);
+    });
+
+    it("won't list a co-owned NFT without every co-owner's consent", async () => {
+      const seller = await funded();
+      const payoutSplits = [
+        { recipient: seller.publicKey, shareBps: 5_000 },
+        { recipient: Keypair.generate().publicKey, shareBps: 5_000 },
+      ];
+      let error: any;
+      try {
+        await listNft("co-owned-bad", 1_000, seller, { payoutSplits, coOwned: true });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("CoSellerNotApproved");
+    });
+
+    it("lists a co-owned NFT its co-owners co-signed", async () => {
+      const seller = await funded();
+      const partner = await funded();
+      const payoutSplits = [
+        { recipient: seller.publicKey, shareBps: 6_000 },
+        { recipient: partner.publicKey, shareBps: 4_000 },
+      ];
+      const { listingId } = await listNft("co-owned", 1_000, seller, {
+        payoutSplits,
+        coOwned: true,
+        coSellers: [partner],
+      });
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.coOwned).to.be.true;
+      expect(details.payoutSplits.map((s) => s.shareBps)).to.deep.equal([6_000, 4_000]);
+    });
+
+    it("rejects a beneficiary share above the whole", async () => {
+      const beneficiary = { wallet: Keypair.generate().publicKey, shareBps: 10_001 };
+      let error: any;
+      try {
+        await listNft("charity-bad", 1_000, await funded(), { beneficiary });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidBeneficiary");
+    });
+
+    it("stores the seller's beneficiary on the listing", async () => {
+      const beneficiary = { wallet: Keypair.generate().publicKey, shareBps: 2_000 };
+      const { listingId } = await listNft("charity", 1_000, await funded(), { beneficiary });
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.beneficiary.wallet.toBase58()).to.equal(beneficiary.wallet.toBase58());
+      expect(details.beneficiary.shareBps).to.equal(2_000);
+    });
+  });
+
+  describe("bid escrow", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "vault-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let bidder: Keypair;
+
+    before(async () => {
+      await funded(seller);
+      bidder = await funded();
+      await listNft("vault-test", 1_000, seller);
+    });
+
+    it("funds the vault with its rent-exempt minimum at listing", async () => {
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(0);
+      expect(await provider.connection.getBalance(vault)).to.equal(rent);
+    });
+
+    it("moves the bid into the vault", async () => {
+      const before = await provider.connection.getBalance(vault);
+      await program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(5_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+      expect(await provider.connection.getBalance(vault)).to.equal(before + 5_000_000);
+    });
+
+    it("makes the bidder the highest bidder", async () => {
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.highestBidder.toBase58()).to.equal(bidder.publicKey.toBase58());
+      expect(details.highestBid.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("reports each bid and the next minimum in the auction details", async () => {
+      const details = await program.methods
+        .getAuctionDetails(listingId)
+        .accounts({ auction })
+        .view();
+      expect(details.numBidders.toNumber()).to.equal(1);
+      expect(details.bidders[0].toBase58()).to.equal(bidder.publicKey.toBase58());
+      expect(details.amounts[0].toNumber()).to.equal(details.highestBid.toNumber());
+      expect(details.times[0].toNumber()).to.be.greaterThan(0);
+      expect(details.paused).to.be.false;
+      expect(details.isAlien).to.be.false;
+      expect(details.buyNowPrice).to.be.null;
+      expect(details.paymentMint).to.be.null;
+      expect(details.minNextBid.toNumber()).to.be.greaterThan(details.highestBid.toNumber());
+    });
+
+    it("reports a bidder's bid, and BidNotFound for someone without one", async () => {
+      const amount = await program.methods
+        .getBidAmount(listingId, bidder.publicKey)
+        .accounts({ auction })
+        .view();
+      expect(amount.toNumber()).to.be.greaterThan(0);
+
+      let error: any;
+      try {
+        await program.methods
+          .getUserBid(listingId, Keypair.generate().publicKey)
+          .accounts({ auction })
+          .view();
+      } catch (e) {
+        error = e;
+      }
+      const logs = error?.simulationResponse?.logs ?? error?.logs ?? [];
+      expect(logs.join("\n")).to.contain("BidNotFound");
+    });
+
+    it("rejects a bid that doesn't beat the highest bid", async () => {
+      const rival = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .placeBid(listingId, rival.publicKey, new anchor.BN(1_000_000), [], null)
+          .accounts({
+            auctionState,
+            auction,
+            vault,
+            payer: rival.publicKey,
+            blacklistEntry: blacklistPda(rival.publicKey),
+            bidPage: null,
+            previousBidder: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            priceFeed: null,
+            tokenVault: null,
+            payerTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([rival])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("BidTooLow");
+    });
+
+    it("sweeps only what was sent to the vault directly", async () => {
+      const stray = 250_000;
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          SystemProgram.transfer({ fromPubkey: authority, toPubkey: vault, lamports: stray })
+        )
+      );
+      const vaultBefore = await provider.connection.getBalance(vault);
+      const treasuryBefore = await provider.connection.getBalance(feeTreasury);
+
+      await program.methods
+        .reconcileVault(listingId)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          feeTreasury,
+          tokenVault: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          signer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      expect(await provider.connection.getBalance(vault)).to.equal(vaultBefore - stray);
+      expect(await provider.connection.getBalance(feeTreasury)).to.equal(treasuryBefore + stray);
+    });
+  });
+
+  describe("ending soon", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "ending-soon-test");
+
+    const endingSoon = (within: number) =>
+      program.methods
+        .getAuctionsEndingSoon(new anchor.BN(within))
+        .remainingAccounts([
+          { pubkey: auctionPda(listingId), isSigner: false, isWritable: false },
+        ])
+        .view();
+
+    before(async () => {
+      await listNft("ending-soon-test", 1_000, await funded(seller));
+    });
+
+    it("lists only the auctions ending within the window", async () => {
+      expect(await endingSoon(60)).to.deep.equal([]);
+      expect(await endingSoon(2 * 24 * 60 * 60)).to.deep.equal([listingId]);
+    });
+  });
+
+  describe("auction registry", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "registry-test");
+    const [registry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("auction_registry")],
+      program.programId
+    );
+    const registryPage = (index: number) => {
+      const seed = Buffer.alloc(4);
+      seed.writeUInt32LE(index);
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("registry_page"), seed],
+        program.programId
+      )[0];
+    };
+    const openPage = (index: number) =>
+      program.methods
+        .openRegistryPage(index)
+        .accounts({
+          registry,
+          registryPage: registryPage(index),
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    before(async () => {
+      await program.methods
+        .initAuctionRegistry()
+        .accounts({ registry, payer: authority, systemProgram: SystemProgram.programId })
+        .rpc();
+      await openPage(0);
+      await listNft("registry-test", 1_000, await funded(seller));
+    });
+
+    it("opens registry pages only in order", async () => {
+      expect(await errorOf(openPage(2))).to.equal("InvalidRegistryPage");
+    });
+
+    it("lists a registered auction with its end time", async () => {
+      await program.methods
+        .registerAuction(listingId)
+        .accounts({ auction: auctionPda(listingId), registryPage: registryPage(0) })
+        .rpc();
+
+      const page = await program.methods
+        .getAuctions(0)
+        .accounts({ registry, registryPage: registryPage(0) })
+        .view();
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(page.pages).to.equal(1);
+      expect(page.auctions.map((key) => key.toBase58())).to.deep.equal([
+        auctionPda(listingId).toBase58(),
+      ]);
+      expect(page.endTimes[0].toNumber()).to.equal(auction.endTime.toNumber());
+      expect(auction.registryPage).to.equal(0);
+    });
+
+    it("keeps a live auction on the registry", async () => {
+      const deregister = program.methods
+        .deregisterAuction(listingId)
+        .accounts({ auction: auctionPda(listingId), registryPage: registryPage(0) })
+        .rpc();
+      expect(await errorOf(deregister)).to.equal("AuctionNotEnded");
+    });
+  });
+
+  describe("minimum increment", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "increment-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let first: Keypair;
+    let second: Keypair;
+
+    const bid = (bidder: Keypair, amount: number, previousBidder: PublicKey | null = null) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder,
+          previousBidReceipt: previousBidder && bidReceiptPda(auction, previousBidder),
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      first = await funded();
+      second = await funded();
+      await listNft("increment-test", 1_000, seller, { minIncrement: new anchor.BN(10_000_000) });
+      await bid(first, 10_000_000);
+    });
+
+    it("rejects a raise smaller than the absolute increment", async () => {
+      let error: any;
+      try {
+        await bid(second, 15_000_000);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("BidTooLow");
+    });
+
+    it("dry-runs a bid with its fee and outcome", async () => {
+      const validate = (amount: number) =>
+        program.methods
+          .validateBid(listingId, new anchor.BN(amount), [])
+          .accounts({
+            auctionState,
+            auction,
+            bidder: second.publicKey,
+            blacklistEntry: blacklistPda(second.publicKey),
+            bidPage: null,
+            bidReceipt: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            membershipDiscount: null,
+            membershipTokenAccount: null,
+            membershipMetadata: null,
+            priceFeed: null,
+            featureFlags: null,
+          })
+          .view();
+      const { buyerFeeBps } = await program.account.nftComAuction.fetch(auctionState);
+      const bidTooLow = program.idl.errors.find((error) => error.name === "BidTooLow").code;
+
+      const low = await validate(15_000_000);
+      expect(low.valid).to.equal(false);
+      expect(low.errorCode).to.equal(bidTooLow);
+
+      const raise = await validate(25_000_000);
+      const fee = Math.floor((25_000_000 * buyerFeeBps.toNumber()) / 10_000);
+      expect(raise.valid).to.equal(true);
+      expect(raise.errorCode).to.equal(null);
+      expect(raise.fee.toNumber()).to.equal(fee);
+      expect(raise.credited.toNumber()).to.equal(25_000_000 - fee);
+      expect(raise.leading).to.equal(true);
+      expect(raise.highestBid.toNumber()).to.equal(25_000_000 - fee);
+
+      // Nothing was placed
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.highestBidder.toBase58()).to.equal(first.publicKey.toBase58());
+    });
+
+    it("accepts a raise of at least the increment and refunds the outbid bidder", async () => {
+      const firstBefore = await provider.connection.getBalance(first.publicKey);
+      await bid(second, 25_000_000, first.publicKey);
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.highestBidder.toBase58()).to.equal(second.publicKey.toBase58());
+      const firstBid = details.bids.find((entry) => entry.bidder.equals(first.publicKey));
+      expect(firstBid.amount.toNumber()).to.equal(0);
+      expect(await provider.connection.getBalance(first.publicKey)).to.be.greaterThan(firstBefore);
+    });
+
+    it("keeps each bidder's position on their bid receipt", async () => {
+      const leader = await program.account.bidReceipt.fetch(bidReceiptPda(auction, second.publicKey));
+      expect(leader.bidder.toBase58()).to.equal(second.publicKey.toBase58());
+      expect(leader.escrowed.toNumber()).to.be.greaterThan(0);
+      expect(leader.refunded).to.equal(false);
+
+      const outbid = await program.account.bidReceipt.fetch(bidReceiptPda(auction, first.publicKey));
+      expect(outbid.escrowed.toNumber()).to.equal(0);
+      expect(outbid.refunded).to.equal(true);
+    });
+  });
+
+  describe("proxy bidding", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "proxy-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let proxyBidder: Keypair;
+    let rival: Keypair;
+
+    const accounts = (payer: Keypair) => ({
+      auctionState,
+      auction,
+      vault,
+      payer: payer.publicKey,
+      bidPage: null,
+      previousBidder: null,
+      gateTokenAccount: null,
+      collectionFeeConfig: null,
+      priceFeed: null,
+      systemProgram: SystemProgram.programId,
+      instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+    });
+
+    before(async () => {
+      await funded(seller);
+      proxyBidder = await funded();
+      rival = await funded();
+      await listNft("proxy-test", 1_000, seller);
+    });
+
+    it("opens a proxy bid at the minimum", async () => {
+      await program.methods
+        .placeProxyBid(listingId, proxyBidder.publicKey, new anchor.BN(10_000_000), [], null)
+        .accounts(accounts(proxyBidder))
+        .signers([proxyBidder])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.highestBidder.toBase58()).to.equal(proxyBidder.publicKey.toBase58());
+      expect(details.highestBid.toNumber()).to.equal(1_000);
+    });
+
+    it("answers a lower rival bid just enough to stay ahead", async () => {
+      await program.methods
+        .placeBid(listingId, rival.publicKey, new anchor.BN(5_000_000), [], null)
+        .accounts(accounts(rival))
+        .signers([rival])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      const rivalBid = details.bids.find((bid) => bid.bidder.equals(rival.publicKey));
+      const proxyBid = details.bids.find((bid) => bid.bidder.equals(proxyBidder.publicKey));
+      expect(details.highestBidder.toBase58()).to.equal(proxyBidder.publicKey.toBase58());
+      expect(details.highestBid.toNumber()).to.be.greaterThan(rivalBid.amount.toNumber());
+      expect(details.highestBid.toNumber()).to.be.lessThan(proxyBid.maxBid.toNumber());
+    });
+  });
+
+  describe("buy now", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "buy-now-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let bidder: Keypair;
+    let buyer: Keypair;
+    let nftMint: PublicKey;
+
+    before(async () => {
+      await funded(seller);
+      bidder = await funded();
+      buyer = await funded();
+      ({ nftMint } = await listNft("buy-now-test", 1_000, seller, {
+        buyNowPrice: new anchor.BN(50_000_000),
+      }));
+      await program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(1_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+    });
+
+    it("rejects a buy-now purchase that skips refunding a bidder", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .buyNow(listingId, [])
+          .accounts({
+            auctionState,
+            auction,
+            vault,
+            buyer: buyer.publicKey,
+            blacklistEntry: blacklistPda(buyer.publicKey),
+            bidPage: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            tokenVault: null,
+            buyerTokenAccount: null,
+            tokenProgram: null,
+            unwrapAccount: null,
+            nativeMint: null,
+            featureFlags,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([buyer])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("RefundAccountsMismatch");
+    });
+
+    it("waits for buy-now to be switched on", async () => {
+      const setFlags = (flags: number) =>
+        program.methods
+          .setFeatureFlags(new anchor.BN(flags))
+          .accounts({ auctionState, featureFlags, authority })
+          .rpc();
+
+      await setFlags(allFeatures & ~0b010);
+      let error: any;
+      try {
+        await program.methods
+          .buyNow(listingId, [])
+          .accounts({
+            auctionState,
+            auction,
+            vault,
+            buyer: buyer.publicKey,
+            blacklistEntry: blacklistPda(buyer.publicKey),
+            bidPage: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            tokenVault: null,
+            buyerTokenAccount: null,
+            tokenProgram: null,
+            unwrapAccount: null,
+            nativeMint: null,
+            featureFlags,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts([{ pubkey: bidder.publicKey, isSigner: false, isWritable: true }])
+          .signers([buyer])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      await setFlags(allFeatures);
+      expect(error?.error?.errorCode?.code).to.equal("FeatureDisabled");
+    });
+
+    it("won't sell to a blacklisted buyer", async () => {
+      const outcast = await funded();
+      await blacklist(outcast.publicKey);
+      let error: any;
+      try {
+        await program.methods
+          .buyNow(listingId, [])
+          .accounts({
+            auctionState,
+            auction,
+            vault,
+            buyer: outcast.publicKey,
+            blacklistEntry: blacklistPda(outcast.publicKey),
+            bidPage: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            tokenVault: null,
+            buyerTokenAccount: null,
+            tokenProgram: null,
+            unwrapAccount: null,
+            nativeMint: null,
+            featureFlags,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts([{ pubkey: bidder.publicKey, isSigner: false, isWritable: true }])
+          .signers([outcast])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Blacklisted");
+    });
+
+    it("ends the auction at the buy-now price and refunds the other bidders", async () => {
+      const bidderBefore = await provider.connection.getBalance(bidder.publicKey);
+      await program.methods
+        .buyNow(listingId, [])
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          buyer: buyer.publicKey,
+          blacklistEntry: blacklistPda(buyer.publicKey),
+          bidPage: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          tokenVault: null,
+          buyerTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          featureFlags,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([{ pubkey: bidder.publicKey, isSigner: false, isWritable: true }])
+        .signers([buyer])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ ended: {} });
+      expect(details.highestBidder.toBase58()).to.equal(buyer.publicKey.toBase58());
+      expect(details.highestBid.toNumber()).to.equal(50_000_000);
+      expect(await provider.connection.getBalance(bidder.publicKey)).to.be.greaterThan(
+        bidderBefore
+      );
+    });
+
+    const claimProceeds = async () =>
+      program.methods
+        .claimProceeds(listingId, PublicKey.default)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          owner: seller.publicKey,
+          feeTreasury,
+          collectionFeeConfig: null,
+          referrer: null,
+          metadata: null,
+          coreAsset: null,
+          coreCollection: null,
+          provenance: provenancePda((await program.account.auctionDetails.fetch(auction)).nftMint),
+          tokenVault: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          fundsReceipt: settlementReceiptPda(auction, 0, seller.publicKey),
+          feesReceipt: settlementReceiptPda(auction, 2, feeTreasury),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+
+    it("lets the seller claim the proceeds once", async () => {
+      const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+      await claimProceeds();
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ settled: {} });
+      expect(await provider.connection.getBalance(seller.publicKey)).to.be.greaterThan(
+        sellerBefore
+      );
+
+      const provenance = await program.account.provenance.fetch(provenancePda(details.nftMint));
+      expect(provenance.totalSales.toNumber()).to.equal(1);
+      expect(provenance.sales[0].seller.toBase58()).to.equal(seller.publicKey.toBase58());
+      expect(provenance.sales[0].buyer.toBase58()).to.equal(buyer.publicKey.toBase58());
+      expect(provenance.sales[0].price.toNumber()).to.equal(50_000_000);
+
+      const receipt = await program.account.settlementReceipt.fetch(
+        settlementReceiptPda(auction, 0, seller.publicKey)
+      );
+      expect(receipt.step).to.deep.equal({ fundsPaid: {} });
+      expect(receipt.amount.toNumber()).to.be.greaterThan(0);
+
+      let error: any;
+      try {
+        await claimProceeds();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("AuctionNotEnded");
+    });
+
+    it("mints no participation receipts until a receipt tree is set", async () => {
+      const receipt = await program.account.bidReceipt.fetch(
+        bidReceiptPda(auction, bidder.publicKey)
+      );
+      expect(receipt.bestBid.toNumber()).to.be.greaterThan(0);
+      expect(receipt.participationMinted).to.equal(false);
+
+      const tree = Keypair.generate().publicKey;
+      let error: any;
+      try {
+        await program.methods
+          .mintParticipationReceipt(listingId, bidder.publicKey)
+          .accounts({
+            auctionState,
+            auction,
+            bidReceipt: bidReceiptPda(auction, bidder.publicKey),
+            leafOwner: bidder.publicKey,
+            treeConfig: tree,
+            merkleTree: tree,
+            payer: authority,
+            logWrapper: SystemProgram.programId,
+            compressionProgram: SystemProgram.programId,
+            bubblegumProgram: SystemProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("ReceiptTreeNotSet");
+    });
+
+    it("collects the fees in the treasury until the admin withdraws them", async () => {
+      const treasury = await program.account.feeTreasury.fetch(feeTreasury);
+      expect(treasury.collected.toNumber()).to.be.greaterThan(1_000_000);
+
+      const stranger = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .withdrawFees(new anchor.BN(1_000_000))
+          .accounts({ auctionState, feeTreasury, feeRecipient, signer: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+
+      await program.methods
+        .withdrawFees(new anchor.BN(1_000_000))
+        .accounts({ auctionState, feeTreasury, feeRecipient, signer: authority })
+        .rpc();
+      expect(await provider.connection.getBalance(feeRecipient)).to.equal(1_000_000);
+    });
+
+    it("lets the buyer claim the escrowed NFT", async () => {
+      const buyerNftAccount = await createAccount(
+        provider.connection,
+        buyer,
+        nftMint,
+        buyer.publicKey
+      );
+      await program.methods
+        .claimNft(listingId, null)
+        .accounts({
+          auctionState,
+          auction,
+          winner: buyer.publicKey,
+          seller: seller.publicKey,
+          tokenAccount: null,
+          adapterProgram: null,
+          nftEscrow: nftEscrowPda(nftMint),
+          recipient: null,
+          recipientNftAccount: buyerNftAccount,
+          nftMint: null,
+          metadata: null,
+          masterEdition: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: null,
+          tokenMetadataProgram: null,
+          rent: null,
+          coreAsset: null,
+          coreCollection: null,
+          mplCoreProgram: null,
+          deliveryReceipt: settlementReceiptPda(auction, 1, buyer.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const account = await getAccount(provider.connection, buyerNftAccount);
+      expect(Number(account.amount)).to.equal(1);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.nftClaimed).to.equal(true);
+    });
+
+    it("treats each settlement step as done once it has run", async () => {
+      const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+      const buyerNftAccount = getAssociatedTokenAddressSync(nftMint, buyer.publicKey);
+
+      // The sale is closed, paid out and delivered above, so each step is a no-op
+      await program.methods
+        .finalizeAuction(listingId)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          caller: authority,
+          tokenVault: null,
+          callerTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          collectionStats: null,
+          floorOracle: null,
+          registryPage: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .settleFunds(listingId, PublicKey.default)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          owner: seller.publicKey,
+          feeTreasury,
+          collectionFeeConfig: null,
+          referrer: null,
+          metadata: null,
+          coreAsset: null,
+          coreCollection: null,
+          provenance: provenancePda(nftMint),
+          tokenVault: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          fundsReceipt: settlementReceiptPda(auction, 0, seller.publicKey),
+          feesReceipt: settlementReceiptPda(auction, 2, feeTreasury),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+      // The NFT's escrow was closed on delivery
+      await program.methods
+        .settleNft(listingId, null)
+        .accounts({
+          auctionState,
+          auction,
+          winner: buyer.publicKey,
+          seller: seller.publicKey,
+          tokenAccount: null,
+          adapterProgram: null,
+          nftEscrow: null,
+          recipient: null,
+          recipientNftAccount: buyerNftAccount,
+          nftMint: null,
+          metadata: null,
+          masterEdition: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: null,
+          tokenMetadataProgram: null,
+          rent: null,
+          coreAsset: null,
+          coreCollection: null,
+          mplCoreProgram: null,
+          deliveryReceipt: settlementReceiptPda(auction, 1, buyer.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ settled: {} });
+      expect(Number((await getAccount(provider.connection, buyerNftAccount)).amount)).to.equal(1);
+      // Only the transaction fee left the seller's wallet
+      expect(await provider.connection.getBalance(seller.publicKey)).to.be.lessThan(sellerBefore);
+    });
+  });
+
+  describe("sale disputes", () => {
+    const seller = Keypair.generate();
+    const arbiter = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "dispute-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let buyer: Keypair;
+    let nftMint: PublicKey;
+    let sellerNftAccount: PublicKey;
+
+    const setDisputeConfig = (window: number, arbiterKey: PublicKey | null) =>
+      program.methods
+        .setDisputeConfig(new anchor.BN(window), arbiterKey)
+        .accounts({ auctionState, authority })
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      buyer = await funded();
+      await setDisputeConfig(60 * 60, arbiter.publicKey);
+      ({ nftMint, sellerNftAccount } = await listNft("dispute-test", 1_000, seller, {
+        buyNowPrice: new anchor.BN(5_000_000),
+      }));
+      await program.methods
+        .buyNow(listingId, [])
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          buyer: buyer.publicKey,
+          blacklistEntry: blacklistPda(buyer.publicKey),
+          bidPage: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          tokenVault: null,
+          buyerTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          featureFlags,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+    });
+
+    after(async () => {
+      await setDisputeConfig(0, null);
+    });
+
+    it("holds the proceeds in escrow during the dispute window", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .claimProceeds(listingId, PublicKey.default)
+          .accounts({
+            auctionState,
+            auction,
+            vault,
+            owner: seller.publicKey,
+            feeTreasury,
+            collectionFeeConfig: null,
+            referrer: null,
+            metadata: null,
+            coreAsset: null,
+            coreCollection: null,
+            provenance: provenancePda(nftMint),
+            tokenVault: null,
+            ownerTokenAccount: null,
+            treasuryTokenAccount: null,
+            tokenProgram: null,
+            unwrapAccount: null,
+            nativeMint: null,
+            fundsReceipt: settlementReceiptPda(auction, 0, seller.publicKey),
+            feesReceipt: settlementReceiptPda(auction, 2, feeTreasury),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([seller])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("DisputeWindowOpen");
+    });
+
+    it("lets only the winner or the arbiter open a dispute", async () => {
+      const stranger = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .openDispute(listingId)
+          .accounts({ auctionState, auction, openedBy: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+
+      await program.methods
+        .openDispute(listingId)
+        .accounts({ auctionState, auction, openedBy: buyer.publicKey })
+        .signers([buyer])
+        .rpc();
+      expect((await program.account.auctionDetails.fetch(auction)).status).to.deep.equal({
+        disputed: {},
+      });
+    });
+
+    it("voids the sale when the arbiter refunds the buyer", async () => {
+      await program.methods
+        .resolveDispute(listingId, true)
+        .accounts({ auctionState, auction, arbiter: arbiter.publicKey })
+        .signers([arbiter])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ voided: {} });
+
+      await program.methods
+        .withdraw(listingId, null, null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          bidder: buyer.publicKey,
+          to: buyer.publicKey,
+          bidPage: null,
+          tokenVault: null,
+          toTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+      const refunded = await program.account.auctionDetails.fetch(auction);
+      const position = refunded.bids.find((entry) => entry.bidder.equals(buyer.publicKey));
+      expect(position.amount.toNumber()).to.equal(0);
+
+      await program.methods
+        .returnNft(listingId)
+        .accounts({
+          auctionState,
+          auction,
+          owner: seller.publicKey,
+          nftEscrow: nftEscrowPda(nftMint),
+          ownerNftAccount: sellerNftAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([seller])
+        .rpc();
+      expect(Number((await getAccount(provider.connection, sellerNftAccount)).amount)).to.equal(1);
+    });
+  });
+
+  describe("external escrow listings", () => {
+    const adapter = anchor.workspace.MockEscrowAdapter as Program<MockEscrowAdapter>;
+    const [escrowAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow_authority")],
+      adapter.programId
+    );
+    const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    // Mint an NFT to `seller`, delegate it to the mock escrow and list it under `name`
+    const listExternal = async (
+      name: string,
+      seller: Keypair,
+      overrides: { endTime?: anchor.BN; secondPrice?: boolean; buyNowPrice?: anchor.BN } = {}
+    ) => {
+      const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      const tokenAccount = await createAccount(provider.connection, seller, nftMint, seller.publicKey);
+      await mintTo(provider.connection, seller, nftMint, tokenAccount, seller, 1);
+      await approve(provider.connection, seller, tokenAccount, escrowAuthority, seller, 1);
+
+      const listingId = listingKey(seller.publicKey, name);
+      await program.methods
+        .listExternal(listingId, new anchor.BN(1_000_000), {
+          endTime: overrides.endTime ?? null,
+          reservePrice: null,
+          minIncrementBps: null,
+          minIncrement: null,
+          buyNowPrice: overrides.buyNowPrice ?? null,
+          snipingTimeWindow: new anchor.BN(0),
+          timeExtension: null,
+          allowlistRoot: null,
+          gateMint: null,
+          payoutSplits: null,
+          bidHistoryCapacity: null,
+          growBidHistory: null,
+          secondPrice: overrides.secondPrice ?? null,
+          candleWindow: null,
+          rentalDays: null,
+          paymentMint: null,
+          usdPricing: null,
+          name,
+          coOwned: null,
+          beneficiary: null,
+        })
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          seller: seller.publicKey,
+          blacklistEntry: blacklistPda(seller.publicKey),
+          sellerProfile: null,
+          tokenAccount,
+          adapterProgram: adapter.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+      return { nftMint, tokenAccount, listingId };
+    };
+
+    const buyNow = (listingId: number[], buyer: Keypair) =>
+      program.methods
+        .buyNow(listingId, [])
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          buyer: buyer.publicKey,
+          blacklistEntry: blacklistPda(buyer.publicKey),
+          bidPage: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          tokenVault: null,
+          buyerTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          featureFlags,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+    // The mock escrow moves the NFT into `destination`, the recipient's token account
+    const release = (listingId: number[], tokenAccount: PublicKey, destination: PublicKey) =>
+      program.methods
+        .releaseExternal(listingId)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          tokenAccount,
+          adapterProgram: adapter.programId,
+        })
+        .remainingAccounts([
+          { pubkey: destination, isSigner: false, isWritable: true },
+          { pubkey: escrowAuthority, isSigner: false, isWritable: false },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ])
+        .rpc({ commitment: "confirmed" });
+
+    before(async () => {
+      await program.methods
+        .addEscrowAdapter(adapter.programId)
+        .accounts({ auctionState, authority })
+        .rpc();
+    });
+
+    it("lists several NFTs per seller, each under its own name", async () => {
+      const seller = await funded();
+      const first = await listExternal("external-first", seller);
+      const second = await listExternal("external-second", seller);
+      expect(first.listingId).to.not.deep.equal(second.listingId);
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(second.listingId));
+      expect(details.name).to.equal("external-second");
+      expect(details.externalEscrow.tokenAccount.toBase58()).to.equal(
+        second.tokenAccount.toBase58()
+      );
+    });
+
+    it("releases a sold NFT to the winner at the price they pay", async () => {
+      const seller = await funded();
+      const first = await funded();
+      const second = await funded();
+      const endTime = new anchor.BN(Math.floor(Date.now() / 1000) + 15);
+      const { nftMint, tokenAccount, listingId } = await listExternal("external-sale", seller, {
+        endTime,
+        secondPrice: true,
+      });
+      const bid = (bidder: Keypair, amount: number, previousBidder: PublicKey | null) =>
+        program.methods
+          .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+          .accounts({
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            payer: bidder.publicKey,
+            blacklistEntry: blacklistPda(bidder.publicKey),
+            bidPage: null,
+            previousBidder,
+            previousBidReceipt:
+              previousBidder && bidReceiptPda(auctionPda(listingId), previousBidder),
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            priceFeed: null,
+            tokenVault: null,
+            payerTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([bidder])
+          .rpc();
+      await bid(first, 10_000_000, null);
+      await bid(second, 20_000_000, first.publicKey);
+
+      await sleep(endTime.toNumber() * 1000 - Date.now() + 2_000);
+      await program.methods
+        .endAuction(listingId)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          caller: authority,
+          tokenVault: null,
+          callerTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          collectionStats: null,
+          floorOracle: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      // Finalizing a sale end_auction already closed only works out its payouts
+      await program.methods
+        .finalizeAuction(listingId)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          caller: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      const { settlementQuote, status } = await program.account.auctionDetails.fetch(
+        auctionPda(listingId)
+      );
+      expect(status).to.deep.equal({ ended: {} });
+      expect(settlementQuote.royalties).to.have.length(0);
+      expect(settlementQuote.ownerEarnings.toNumber()).to.be.greaterThan(0);
+
+      const destination = await createAccount(
+        provider.connection,
+        second,
+        nftMint,
+        second.publicKey,
+        Keypair.generate()
+      );
+      const signature = await release(listingId, tokenAccount, destination);
+      expect(Number((await getAccount(provider.connection, destination)).amount)).to.equal(1);
+
+      // A second-price winner pays the runner-up's bid, not their own
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.externalEscrow.released).to.be.true;
+      expect(details.secondBid.toNumber()).to.be.lessThan(details.highestBid.toNumber());
+      const transaction = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      expect(transaction.meta.logMessages.join("\n")).to.contain(
+        `Released to ${second.publicKey.toBase58()} at price ${details.secondBid.toNumber()}`
+      );
+    });
+
+    it("won't release an unsold NFT", async () => {
+      const seller = await funded();
+      const { nftMint, tokenAccount, listingId } = await listExternal("external-unsold", seller);
+      const destination = await createAccount(
+        provider.connection,
+        seller,
+        nftMint,
+        authority,
+        Keypair.generate()
+      );
+      expect(await errorOf(release(listingId, tokenAccount, destination))).to.equal(
+        "AuctionNotEnded"
+      );
+    });
+
+    it("holds the NFT for the dispute window", async () => {
+      const seller = await funded();
+      const buyer = await funded();
+      const { nftMint, tokenAccount, listingId } = await listExternal("external-dispute", seller, {
+        buyNowPrice: new anchor.BN(5_000_000),
+      });
+      const destination = await createAccount(
+        provider.connection,
+        buyer,
+        nftMint,
+        buyer.publicKey,
+        Keypair.generate()
+      );
+      await program.methods
+        .setDisputeConfig(new anchor.BN(60 * 60), Keypair.generate().publicKey)
+        .accounts({ auctionState, authority })
+        .rpc();
+      try {
+        await buyNow(listingId, buyer);
+        expect(await errorOf(release(listingId, tokenAccount, destination))).to.equal(
+          "DisputeWindowOpen"
+        );
+      } finally {
+        await program.methods
+          .setDisputeConfig(new anchor.BN(0), null)
+          .accounts({ auctionState, authority })
+          .rpc();
+      }
+    });
+
+    it("holds the NFT until every installment is paid", async () => {
+      const seller = await funded();
+      const buyer = await funded();
+      const { nftMint, tokenAccount, listingId } = await listExternal(
+        "external-installments",
+        seller,
+        { buyNowPrice: new anchor.BN(5_000_000) }
+      );
+      await program.methods
+        .setInstallmentPlan(listingId, 2, new anchor.BN(60 * 60), 0)
+        .accounts({ auction: auctionPda(listingId), owner: seller.publicKey })
+        .signers([seller])
+        .rpc();
+      await buyNow(listingId, buyer);
+
+      const destination = await createAccount(
+        provider.connection,
+        buyer,
+        nftMint,
+        buyer.publicKey,
+        Keypair.generate()
+      );
+      expect(await errorOf(release(listingId, tokenAccount, destination))).to.equal(
+        "InstallmentsOutstanding"
+      );
+    });
+
+    it("won't list for a blacklisted seller", async () => {
+      const seller = await funded();
+      await blacklist(seller.publicKey);
+      expect(await errorOf(listExternal("external-blacklisted", seller))).to.equal("Blacklisted");
+    });
+  });
+
+  describe("early acceptance", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "accept-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let outbid: Keypair;
+    let leader: Keypair;
+
+    const bid = (bidder: Keypair, amount: number) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const accept = (owner: Keypair, refunded: PublicKey[]) =>
+      program.methods
+        .acceptHighestBid(listingId)
+        .accounts({
+          auction,
+          vault,
+          owner: owner.publicKey,
+          tokenVault: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          refunded.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+        )
+        .signers([owner])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      outbid = await funded();
+      leader = await funded();
+      await listNft("accept-test", 1_000, seller);
+      await bid(outbid, 1_000_000);
+      await bid(leader, 2_000_000);
+    });
+
+    it("only lets the seller accept", async () => {
+      const stranger = await funded();
+      let error: any;
+      try {
+        await accept(stranger, [outbid.publicKey]);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+    });
+
+    it("ends the auction at the highest bid and refunds the others", async () => {
+      const outbidBefore = await provider.connection.getBalance(outbid.publicKey);
+      await accept(seller, [outbid.publicKey]);
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ ended: {} });
+      expect(details.highestBidder.toBase58()).to.equal(leader.publicKey.toBase58());
+      expect(details.highestBid.toNumber()).to.equal(2_000_000);
+      expect(await provider.connection.getBalance(outbid.publicKey)).to.equal(
+        outbidBefore + 1_000_000
+      );
+    });
+  });
+
+  describe("bid bonds", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "bond-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    const BOND = 5_000_000;
+    let loser: Keypair;
+    let winner: Keypair;
+
+    const bid = (bidder: Keypair, amount: number) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const refundBond = (bidder: Keypair) =>
+      program.methods
+        .refundBidBond(listingId)
+        .accounts({
+          auction,
+          vault,
+          bidReceipt: bidReceiptPda(auction, bidder.publicKey),
+          bidder: bidder.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    before(async () => {
+      await funded(seller);
+      loser = await funded();
+      winner = await funded();
+      await listNft("bond-test", 1_000, seller);
+      await program.methods
+        .setBidBond(listingId, new anchor.BN(BOND))
+        .accounts({ auction, owner: seller.publicKey })
+        .signers([seller])
+        .rpc();
+    });
+
+    it("locks the bond once, with the bidder's first bid", async () => {
+      const vaultBefore = await provider.connection.getBalance(vault);
+      await bid(loser, 10_000_000);
+      await bid(loser, 1_000_000);
+      expect(await provider.connection.getBalance(vault)).to.equal(
+        vaultBefore + 11_000_000 + BOND
+      );
+      const receipt = await program.account.bidReceipt.fetch(bidReceiptPda(auction, loser.publicKey));
+      expect(receipt.bond.toNumber()).to.equal(BOND);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.bondsHeld.toNumber()).to.equal(BOND);
+    });
+
+    it("can't be changed once bidding has started", async () => {
+      expect(
+        await errorOf(
+          program.methods
+            .setBidBond(listingId, new anchor.BN(0))
+            .accounts({ auction, owner: seller.publicKey })
+            .signers([seller])
+            .rpc()
+        )
+      ).to.equal("BiddingStarted");
+    });
+
+    it("holds every bond while the auction runs", async () => {
+      await bid(winner, 20_000_000);
+      expect(await errorOf(refundBond(loser))).to.equal("BidBondLocked");
+    });
+
+    it("refunds the losing bidder's bond once the auction closes", async () => {
+      await program.methods
+        .acceptHighestBid(listingId)
+        .accounts({
+          auction,
+          vault,
+          owner: seller.publicKey,
+          tokenVault: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([{ pubkey: loser.publicKey, isSigner: false, isWritable: true }])
+        .signers([seller])
+        .rpc();
+
+      const before = await provider.connection.getBalance(loser.publicKey);
+      await refundBond(loser);
+      // The bidder pays the transaction fee out of the refund
+      expect(await provider.connection.getBalance(loser.publicKey)).to.be.greaterThan(
+        before + BOND - 10_000
+      );
+      expect(await errorOf(refundBond(loser))).to.equal("NothingToWithdraw");
+    });
+
+    it("keeps the winner's bond until they claim, and the seller's claim on it until the window passes", async () => {
+      expect(await errorOf(refundBond(winner))).to.equal("BidBondLocked");
+      expect(
+        await errorOf(
+          program.methods
+            .forfeitBidBond(listingId)
+            .accounts({
+              auction,
+              vault,
+              bidReceipt: bidReceiptPda(auction, winner.publicKey),
+              owner: seller.publicKey,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([seller])
+            .rpc()
+        )
+      ).to.equal("BidBondLocked");
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.bondsHeld.toNumber()).to.equal(BOND);
+    });
+  });
+
+  describe("minimum bid updates", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "reprice-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+
+    const updateMinimumBid = (owner: Keypair, newMinimum: number) =>
+      program.methods
+        .updateMinimumBid(listingId, new anchor.BN(newMinimum))
+        .accounts({ auction, owner: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    before(async () => {
+      await funded(seller);
+      await listNft("reprice-test", 1_000, seller);
+    });
+
+    it("lets the seller reprice a listing nobody has bid on", async () => {
+      await updateMinimumBid(seller, 5_000);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.minimumBid.toNumber()).to.equal(5_000);
+    });
+
+    it("rejects anyone but the seller", async () => {
+      const stranger = await funded();
+      expect(await errorOf(updateMinimumBid(stranger, 2_000))).to.equal("Unauthorized");
+    });
+
+    it("locks the minimum once bidding has started", async () => {
+      const bidder = await funded();
+      await program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(1_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+      expect(await errorOf(updateMinimumBid(seller, 2_000))).to.equal("BiddingStarted");
+    });
+  });
+
+  describe("bid retraction", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "retraction-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let loser: Keypair;
+    let winner: Keypair;
+
+    const setPenalty = (penaltyBps: number, toSeller: boolean) =>
+      program.methods
+        .setRetractionPenalty(new anchor.BN(penaltyBps), toSeller)
+        .accounts({ auctionState, authority })
+        .rpc();
+
+    const bid = (bidder: Keypair, amount: number) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const retract = (bidder: Keypair) =>
+      program.methods
+        .retractBid(listingId)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          bidder: bidder.publicKey,
+          bidPage: null,
+          bidReceipt: bidReceiptPda(auction, bidder.publicKey),
+          seller: seller.publicKey,
+          feeTreasury,
+          tokenVault: null,
+          bidderTokenAccount: null,
+          penaltyTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    before(async () => {
+      await listNft("retraction-test", 1_000, await funded(seller));
+      loser = await funded();
+      winner = await funded();
+      await bid(loser, 1_000_000);
+      await bid(winner, 2_000_000);
+      await setPenalty(500, false);
+    });
+
+    after(async () => {
+      await setPenalty(0, false);
+    });
+
+    it("caps the penalty at the fee cap", async () => {
+      expect(await errorOf(setPenalty(5_000, false))).to.equal("InvalidRetractionPenalty");
+    });
+
+    it("keeps the leading bid in", async () => {
+      expect(await errorOf(retract(winner))).to.equal("HighestBidderCannotWithdraw");
+    });
+
+    it("won't let a losing bidder withdraw to skip the penalty", async () => {
+      const code = await errorOf(
+        program.methods
+          .withdraw(listingId, null, null)
+          .accounts({
+            auctionState,
+            auction,
+            vault,
+            bidder: loser.publicKey,
+            to: loser.publicKey,
+            bidPage: null,
+            tokenVault: null,
+            toTokenAccount: null,
+            tokenProgram: null,
+            unwrapAccount: null,
+            nativeMint: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([loser])
+          .rpc()
+      );
+      expect(code).to.equal("RetractionRequired");
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      const position = details.bids.find((entry) => entry.bidder.equals(loser.publicKey));
+      expect(position.amount.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("refunds a losing bid less the penalty, which goes to the fee treasury", async () => {
+      const before = await program.account.auctionDetails.fetch(auction);
+      const position = before.bids.find((entry) => entry.bidder.equals(loser.publicKey));
+      const amount = position.amount.toNumber();
+      const penalty = Math.floor((amount * 500) / 10_000);
+      const loserBefore = await provider.connection.getBalance(loser.publicKey);
+      const treasuryBefore = await program.account.feeTreasury.fetch(feeTreasury);
+
+      await retract(loser);
+
+      expect(await provider.connection.getBalance(loser.publicKey)).to.equal(
+        loserBefore + amount - penalty
+      );
+      const treasury = await program.account.feeTreasury.fetch(feeTreasury);
+      expect(treasury.collected.toNumber()).to.equal(treasuryBefore.collected.toNumber() + penalty);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.bids.some((entry) => entry.bidder.equals(loser.publicKey))).to.equal(false);
+      expect(details.highestBidder.toBase58()).to.equal(winner.publicKey.toBase58());
+
+      expect(await errorOf(retract(loser))).to.equal("NoFundsToWithdraw");
+    });
+  });
+
+  describe("bid limits", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "bid-limit-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+
+    const setBidLimits = (maxBids: number, cooldown: number) =>
+      program.methods
+        .setBidLimits(listingId, maxBids, new anchor.BN(cooldown))
+        .accounts({ auction, owner: seller.publicKey })
+        .signers([seller])
+        .rpc();
+
+    const bid = (bidder: Keypair, amount: number) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    before(async () => {
+      await funded(seller);
+      await listNft("bid-limit-test", 1_000, seller);
+    });
+
+    it("rejects a negative cooldown", async () => {
+      expect(await errorOf(setBidLimits(1, -1))).to.equal("InvalidBidLimits");
+    });
+
+    it("stops a bidder once they've used their bids", async () => {
+      await setBidLimits(1, 0);
+      const bidder = await funded();
+      await bid(bidder, 1_000_000);
+      expect(await errorOf(bid(bidder, 2_000_000))).to.equal("BidLimitReached");
+    });
+
+    it("locks the limits once bidding has started", async () => {
+      expect(await errorOf(setBidLimits(0, 0))).to.equal("BiddingStarted");
+    });
+  });
+
+  describe("vested proceeds", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "vesting-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+
+    const setVesting = (duration: number) =>
+      program.methods
+        .setProceedsVesting(listingId, new anchor.BN(duration))
+        .accounts({ auction, owner: seller.publicKey })
+        .signers([seller])
+        .rpc();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    before(async () => {
+      await funded(seller);
+      await listNft("vesting-test", 1_000, seller);
+    });
+
+    it("rejects a vesting period over a year", async () => {
+      expect(await errorOf(setVesting(366 * 24 * 60 * 60))).to.equal("InvalidVesting");
+    });
+
+    it("vests the proceeds over the chosen period", async () => {
+      await setVesting(30 * 24 * 60 * 60);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.proceedsVesting.duration.toNumber()).to.equal(30 * 24 * 60 * 60);
+      expect(details.proceedsVesting.total.toNumber()).to.equal(0);
+    });
+
+    it("has nothing to claim before settlement", async () => {
+      const call = program.methods
+        .claimVested(listingId)
+        .accounts({
+          auction,
+          vault,
+          owner: seller.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+      expect(await errorOf(call)).to.equal("AuctionNotEnded");
+    });
+  });
+
+  describe("seller extensions", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "extend-test");
+    const auction = auctionPda(listingId);
+    const maxDuration = 30 * 24 * 60 * 60;
+
+    const setMaxAuctionDuration = (duration: number) =>
+      program.methods
+        .setMaxAuctionDuration(new anchor.BN(duration))
+        .accounts({ auctionState, authority })
+        .rpc();
+
+    const extend = (owner: Keypair, newEndTime: number) =>
+      program.methods
+        .extendAuction(listingId, new anchor.BN(newEndTime))
+        .accounts({ auctionState, auction, owner: owner.publicKey })
+        .signers([owner])
+        .rpc();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    before(async () => {
+      await funded(seller);
+      await setMaxAuctionDuration(maxDuration);
+      await listNft("extend-test", 1_000, seller);
+    });
+
+    after(async () => {
+      await setMaxAuctionDuration(0);
+    });
+
+    it("lets the seller push the end time back", async () => {
+      const { endTime } = await program.account.auctionDetails.fetch(auction);
+      await extend(seller, endTime.toNumber() + 60 * 60);
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.endTime.toNumber()).to.equal(endTime.toNumber() + 60 * 60);
+    });
+
+    it("never shortens the auction", async () => {
+      const { endTime } = await program.account.auctionDetails.fetch(auction);
+      expect(await errorOf(extend(seller, endTime.toNumber() - 1))).to.equal("InvalidExtension");
+    });
+
+    it("rejects anyone but the seller", async () => {
+      const stranger = await funded();
+      const { endTime } = await program.account.auctionDetails.fetch(auction);
+      expect(await errorOf(extend(stranger, endTime.toNumber() + 60))).to.equal("Unauthorized");
+    });
+
+    it("caps the total duration", async () => {
+      const { createdAt } = await program.account.auctionDetails.fetch(auction);
+      expect(await errorOf(extend(seller, createdAt.toNumber() + maxDuration + 1))).to.equal(
+        "AuctionTooLong"
+      );
+    });
+  });
+
+  describe("edition auctions", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "edition-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let bidders: Keypair[];
+
+    const bid = (bidder: Keypair, amount: number) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const withdraw = (bidder: Keypair, amount: number | null) =>
+      program.methods
+        .withdraw(listingId, null, amount === null ? null : new anchor.BN(amount))
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          bidder: bidder.publicKey,
+          to: bidder.publicKey,
+          bidPage: null,
+          tokenVault: null,
+          toTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder])
+        .rpc();
+
+    const escrowedBy = async (bidder: Keypair) => {
+      const details = await program.account.auctionDetails.fetch(auction);
+      return details.bids.find((entry) => entry.bidder.equals(bidder.publicKey)).amount.toNumber();
+    };
+
+    before(async () => {
+      await funded(seller);
+      bidders = [await funded(), await funded(), await funded()];
+      await program.methods
+        .initializeEditionAuction(listingId, new anchor.BN(1_000), 2, {
+          endTime: null,
+          reservePrice: null,
+          minIncrementBps: null,
+          minIncrement: null,
+          buyNowPrice: null,
+          snipingTimeWindow: null,
+          timeExtension: null,
+          allowlistRoot: null,
+          gateMint: null,
+          payoutSplits: null,
+          bidHistoryCapacity: null,
+          growBidHistory: null,
+          secondPrice: null,
+          candleWindow: null,
+          rentalDays: null,
+          paymentMint: null,
+          usdPricing: null,
+          name: "edition-test",
+        })
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          owner: seller.publicKey,
+          sellerProfile: null,
+          collectionConfig: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+    });
+
+    it("keeps the best positions in the edition book", async () => {
+      await bid(bidders[0], 1_000_000);
+      await bid(bidders[1], 2_000_000);
+      await bid(bidders[2], 3_000_000);
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.editions).to.equal(2);
+      expect(details.editionBook.map((entry) => entry.bidder.toBase58())).to.deep.equal([
+        bidders[2].publicKey.toBase58(),
+        bidders[1].publicKey.toBase58(),
+      ]);
+    });
+
+    it("lets a bidder pushed out of the book withdraw part of their escrow", async () => {
+      const before = await escrowedBy(bidders[0]);
+      const balance = await provider.connection.getBalance(bidders[0].publicKey);
+      await withdraw(bidders[0], 400_000);
+
+      expect(await escrowedBy(bidders[0])).to.equal(before - 400_000);
+      expect(await provider.connection.getBalance(bidders[0].publicKey)).to.be.above(balance);
+    });
+
+    it("rejects a withdrawal above what's escrowed", async () => {
+      let error: any;
+      try {
+        await withdraw(bidders[0], (await escrowedBy(bidders[0])) + 1);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("WithdrawalTooLarge");
+    });
+
+    it("lets a bidder pushed out of the book withdraw the rest", async () => {
+      await withdraw(bidders[0], null);
+      expect(await escrowedBy(bidders[0])).to.equal(0);
+    });
+  });
+
+  describe("private auctions", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "allowlist-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let member: Keypair;
+    let otherMember: Keypair;
+
+    // Sorted-pair keccak tree, as the program checks it
+    const leaf = (wallet: PublicKey) => Buffer.from(keccak_256(wallet.toBuffer()));
+    const parent = (a: Buffer, b: Buffer) =>
+      Buffer.from(keccak_256(Buffer.concat(Buffer.compare(a, b) <= 0 ? [a, b] : [b, a])));
+
+    const bid = (bidder: Keypair, proof: number[][]) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(1_000_000), proof, null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      member = await funded();
+      otherMember = await funded();
+      const root = parent(leaf(member.publicKey), leaf(otherMember.publicKey));
+      await listNft("allowlist-test", 1_000, seller, { allowlistRoot: [...root] });
+    });
+
+    it("accepts a bid with a valid allowlist proof", async () => {
+      await bid(member, [[...leaf(otherMember.publicKey)]]);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.highestBidder.toBase58()).to.equal(member.publicKey.toBase58());
+    });
+
+    it("rejects a bidder who isn't on the allowlist", async () => {
+      const outsider = await funded();
+      let error: any;
+      try {
+        await bid(outsider, [[...leaf(otherMember.publicKey)]]);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("NotAllowlisted");
+    });
+  });
+
+  describe("token-gated auctions", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "token-gate-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let gateMint: PublicKey;
+
+    const bid = (bidder: Keypair, gateTokenAccount: PublicKey | null) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(1_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      gateMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      await listNft("token-gate-test", 1_000, seller, { gateMint });
+    });
+
+    it("accepts a bid from a holder of the gate mint", async () => {
+      const bidder = await funded();
+      const holding = await createAccount(provider.connection, bidder, gateMint, bidder.publicKey);
+      await mintTo(provider.connection, seller, gateMint, holding, seller, 1);
+
+      await bid(bidder, holding);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.highestBidder.toBase58()).to.equal(bidder.publicKey.toBase58());
+    });
+
+    it("rejects a bidder with an empty gate token account", async () => {
+      const bidder = await funded();
+      const holding = await createAccount(provider.connection, bidder, gateMint, bidder.publicKey);
+      let error: any;
+      try {
+        await bid(bidder, holding);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("NotTokenHolder");
+    });
+
+    it("rejects a bidder who passes no gate token account", async () => {
+      const bidder = await funded();
+      let error: any;
+      try {
+        await bid(bidder, null);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("NotTokenHolder");
+    });
+  });
+
+  describe("blacklist", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "blacklist-test");
+    const auction = auctionPda(listingId);
+    const vault = vaultPda(listingId);
+    let flagged: Keypair;
+
+    const bid = (bidder: Keypair) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(1_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    before(async () => {
+      await listNft("blacklist-test", 1_000, await funded(seller));
+      flagged = await funded();
+    });
+
+    it("only lets the admin blacklist an address", async () => {
+      const outsider = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .addToBlacklist(flagged.publicKey)
+          .accounts({
+            auctionState,
+            blacklistEntry: blacklistPda(flagged.publicKey),
+            authority: outsider.publicKey,
+            payer: outsider.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([outsider])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+    });
+
+    it("rejects bids and listings from a blacklisted address", async () => {
+      await program.methods
+        .addToBlacklist(flagged.publicKey)
+        .accounts({
+          auctionState,
+          blacklistEntry: blacklistPda(flagged.publicKey),
+          authority,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      let error: any;
+      try {
+        await bid(flagged);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Blacklisted");
+
+      error = undefined;
+      try {
+        await listNft("blacklist-listing", 1_000, flagged);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Blacklisted");
+    });
+
+    it("rejects a blacklisted bidder's compressed bids", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .placeCompressedBid(listingId, flagged.publicKey, new anchor.BN(1_000_000), null, Array(32).fill(0))
+          .accounts({
+            tree: {
+              auctionState,
+              merkleTree: Keypair.generate().publicKey,
+              compressionProgram: new PublicKey("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"),
+              noopProgram: new PublicKey("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV"),
+            },
+            auction,
+            vault,
+            payer: flagged.publicKey,
+            blacklistEntry: blacklistPda(flagged.publicKey),
+            systemProgram: SystemProgram.programId,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([flagged])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Blacklisted");
+    });
+
+    it("rejects a blacklisted bidder's swapped bids", async () => {
+      // The token program stands in for the swap: its SyncNative credits the
+      // wrapped SOL account with the lamports sent to it just before
+      await program.methods
+        .addSwapProgram(TOKEN_PROGRAM_ID)
+        .accounts({ auctionState, authority })
+        .rpc();
+      const wsolAccount = await createWrappedNativeAccount(
+        provider.connection,
+        flagged,
+        flagged.publicKey,
+        0,
+        Keypair.generate()
+      );
+
+      let error: any;
+      try {
+        await program.methods
+          .placeBidWithSwap(listingId, flagged.publicKey, Buffer.from([17]), new anchor.BN(1_000_000))
+          .accounts({
+            auctionState,
+            auction,
+            vault,
+            payer: flagged.publicKey,
+            blacklistEntry: blacklistPda(flagged.publicKey),
+            wsolAccount,
+            swapProgram: TOKEN_PROGRAM_ID,
+            bidPage: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .remainingAccounts([{ pubkey: wsolAccount, isSigner: false, isWritable: true }])
+          .preInstructions([
+            SystemProgram.transfer({
+              fromPubkey: flagged.publicKey,
+              toPubkey: wsolAccount,
+              lamports: 1_000_000,
+            }),
+          ])
+          .signers([flagged])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Blacklisted");
+
+      await program.methods
+        .removeSwapProgram(TOKEN_PROGRAM_ID)
+        .accounts({ auctionState, authority })
+        .rpc();
+    });
+
+    it("accepts bids again once the address is removed", async () => {
+      await program.methods
+        .removeFromBlacklist()
+        .accounts({ auctionState, blacklistEntry: blacklistPda(flagged.publicKey), authority })
+        .rpc();
+
+      await bid(flagged);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.highestBidder.toBase58()).to.equal(flagged.publicKey.toBase58());
+    });
+  });
+
+  describe("referrals", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "referral-test");
+    const auction = auctionPda(listingId);
+    const affiliate = Keypair.generate().publicKey;
+
+    before(async () => {
+      await listNft("referral-test", 1_000, await funded(seller));
+    });
+
+    it("lets the admin set the referral share up to 100% of the buyer fee", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .setReferralShare(new anchor.BN(10_001))
+          .accounts({ auctionState, authority })
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidReferralShare");
+
+      await program.methods
+        .setReferralShare(new anchor.BN(2_000))
+        .accounts({ auctionState, authority })
+        .rpc();
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.referralShareBps.toNumber()).to.equal(2_000);
+    });
+
+    it("records the referrer on the bid", async () => {
+      const bidder = await funded();
+      await program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(1_000_000), [], affiliate)
+        .accounts({
+          auctionState,
+          auction,
+          vault: vaultPda(listingId),
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      const position = details.bids.find((entry) => entry.bidder.equals(bidder.publicKey));
+      expect(position.referrer.toBase58()).to.equal(affiliate.toBase58());
+    });
+  });
+
+  describe("batched listings", () => {
+    const errorOf = async (call: Promise<unknown>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    // Mint an NFT to `seller` for each of `names` and list them all in one batch
+    const listBatch = async (seller: Keypair, names: string[]) => {
+      const listings = names.map((name) => listingKey(seller.publicKey, name));
+      const nfts = [];
+      for (const _ of listings) {
+        const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+        const sellerNftAccount = await createAccount(
+          provider.connection,
+          seller,
+          nftMint,
+          seller.publicKey
+        );
+        await mintTo(provider.connection, seller, nftMint, sellerNftAccount, seller, 1);
+        nfts.push({ nftMint, sellerNftAccount });
+      }
+
+      await program.methods
+        .initializeAuctionsBatch(
+          names.map((name, i) => ({
+            listingId: listings[i],
+            minimum: new anchor.BN(1_000),
+            overrides: {
+              endTime: null,
+              reservePrice: null,
+              minIncrementBps: null,
+              minIncrement: null,
+              buyNowPrice: null,
+              snipingTimeWindow: null,
+              timeExtension: null,
+              allowlistRoot: null,
+              gateMint: null,
+              payoutSplits: null,
+              bidHistoryCapacity: null,
+              growBidHistory: null,
+              secondPrice: null,
+              candleWindow: null,
+              rentalDays: null,
+              paymentMint: null,
+              usdPricing: null,
+              name,
+              coOwned: null,
+              beneficiary: null,
+            },
+          }))
+        )
+        .accounts({
+          auctionState,
+          owner: seller.publicKey,
+          blacklistEntry: blacklistPda(seller.publicKey),
+          sellerProfile: null,
+          collectionConfig: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          listings.flatMap((listingId, i) => [
+            { pubkey: auctionPda(listingId), isSigner: false, isWritable: true },
+            { pubkey: vaultPda(listingId), isSigner: false, isWritable: true },
+            { pubkey: nfts[i].nftMint, isSigner: false, isWritable: false },
+            { pubkey: nfts[i].sellerNftAccount, isSigner: false, isWritable: true },
+            { pubkey: nftEscrowPda(nfts[i].nftMint), isSigner: false, isWritable: true },
+          ])
+        )
+        .signers([seller])
+        .rpc();
+      return { listings, nfts };
+    };
+
+    it("lists and escrows every NFT in the batch", async () => {
+      const seller = await funded();
+      const { listings, nfts } = await listBatch(seller, ["batch-list-a", "batch-list-b"]);
+
+      for (const [i, listingId] of listings.entries()) {
+        const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+        expect(details.nftMint.toBase58()).to.equal(nfts[i].nftMint.toBase58());
+        const escrow = await getAccount(provider.connection, nftEscrowPda(nfts[i].nftMint));
+        expect(Number(escrow.amount)).to.equal(1);
+      }
+    });
+
+    it("won't list a batch for a blacklisted seller", async () => {
+      const seller = await funded();
+      await blacklist(seller.publicKey);
+      expect(await errorOf(listBatch(seller, ["batch-list-blacklisted"]))).to.equal("Blacklisted");
+    });
+  });
+
+  describe("batched bids", () => {
+    const errorOf = async (call: Promise<unknown>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    // Bid 0.001 SOL on the first of `listings`, 0.002 on the second and so on, in one batch
+    const bidBatch = (bidder: Keypair, listings: number[][]) =>
+      program.methods
+        .placeBids(
+          listings.map((listingId, i) => ({
+            listingId,
+            amount: new anchor.BN(1_000_000 * (i + 1)),
+            proof: [],
+          }))
+        )
+        .accounts({
+          auctionState,
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(
+          listings.flatMap((listingId) => [
+            { pubkey: auctionPda(listingId), isSigner: false, isWritable: true },
+            { pubkey: vaultPda(listingId), isSigner: false, isWritable: true },
+            {
+              pubkey: bidReceiptPda(auctionPda(listingId), bidder.publicKey),
+              isSigner: false,
+              isWritable: true,
+            },
+          ])
+        )
+        .signers([bidder])
+        .rpc();
+
+    it("places one bid on each listing in the batch", async () => {
+      const seller = await funded();
+      const bidder = await funded();
+      const listings = [];
+      for (const name of ["batch-bid-a", "batch-bid-b"]) {
+        listings.push((await listNft(name, 1_000, seller)).listingId);
+      }
+
+      await bidBatch(bidder, listings);
+
+      for (const listingId of listings) {
+        const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+        expect(details.highestBidder.toBase58()).to.equal(bidder.publicKey.toBase58());
+        const receipt = await program.account.bidReceipt.fetch(
+          bidReceiptPda(auctionPda(listingId), bidder.publicKey)
+        );
+        expect(receipt.bidCount).to.equal(1);
+        expect(receipt.escrowed.toString()).to.equal(details.highestBid.toString());
+      }
+    });
+
+    it("rejects a batch from a blacklisted bidder", async () => {
+      const seller = await funded();
+      const bidder = await funded();
+      const { listingId } = await listNft("batch-bid-blacklisted", 1_000, seller);
+      await blacklist(bidder.publicKey);
+      expect(await errorOf(bidBatch(bidder, [listingId]))).to.equal("Blacklisted");
+    });
+  });
+
+  describe("signed bid orders", () => {
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+    const bidderProfilePda = (bidder: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("bidder_profile"), bidder.toBuffer()],
+        program.programId
+      )[0];
+    // Program id, then the Borsh-encoded order
+    const orderMessage = (order) => {
+      const numbers = Buffer.alloc(24);
+      numbers.writeBigUInt64LE(BigInt(order.amount.toString()), 0);
+      numbers.writeBigUInt64LE(BigInt(order.nonce.toString()), 8);
+      numbers.writeBigInt64LE(BigInt(order.expiresAt.toString()), 16);
+      return Buffer.concat([
+        program.programId.toBuffer(),
+        Buffer.from(order.listingId),
+        order.bidder.toBuffer(),
+        numbers,
+      ]);
+    };
+    const settle = (order, signer: Keypair, matcher: Keypair) =>
+      program.methods
+        .placeSignedBid(order.listingId, order, [])
+        .accounts({
+          auctionState,
+          auction: auctionPda(order.listingId),
+          vault: vaultPda(order.listingId),
+          bidderProfile: bidderProfilePda(order.bidder),
+          blacklistEntry: blacklistPda(order.bidder),
+          collectionFeeConfig: null,
+          matcher: matcher.publicKey,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions([
+          Ed25519Program.createInstructionWithPrivateKey({
+            privateKey: signer.secretKey,
+            message: orderMessage(order),
+          }),
+        ])
+        .signers([matcher])
+        .rpc();
+
+    const seller = Keypair.generate();
+    const bidder = Keypair.generate();
+    let listingId: number[];
+    let order;
+
+    before(async () => {
+      await funded(seller);
+      await funded(bidder);
+      ({ listingId } = await listNft("signed-orders", 1_000, seller));
+      await program.methods
+        .initBidderProfile()
+        .accounts({
+          bidderProfile: bidderProfilePda(bidder.publicKey),
+          bidder: bidder.publicKey,
+          payer: bidder.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder])
+        .rpc();
+      await program.methods
+        .fundBidderProfile(new anchor.BN(5_000_000))
+        .accounts({
+          bidderProfile: bidderProfilePda(bidder.publicKey),
+          bidder: bidder.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder])
+        .rpc();
+      order = {
+        listingId,
+        bidder: bidder.publicKey,
+        amount: new anchor.BN(2_000_000),
+        nonce: new anchor.BN(0),
+        expiresAt: new anchor.BN(Math.floor(Date.now() / 1000) + 600),
+      };
+    });
+
+    it("rejects an order signed by someone other than its bidder", async () => {
+      const matcher = await funded();
+      expect(await errorOf(settle(order, Keypair.generate(), matcher))).to.equal(
+        "InvalidOrderSignature"
+      );
+    });
+
+    it("settles a signed order from the bidder's profile balance", async () => {
+      const matcher = await funded();
+      const profileBefore = await provider.connection.getBalance(bidderProfilePda(bidder.publicKey));
+      await settle(order, bidder, matcher);
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.highestBidder.toBase58()).to.equal(bidder.publicKey.toBase58());
+      const profileAfter = await provider.connection.getBalance(bidderProfilePda(bidder.publicKey));
+      expect(profileBefore - profileAfter).to.equal(2_000_000);
+    });
+
+    it("settles each order only once", async () => {
+      const matcher = await funded();
+      expect(await errorOf(settle(order, bidder, matcher))).to.equal("NonceAlreadyUsed");
+    });
+
+    it("rejects an expired order", async () => {
+      const matcher = await funded();
+      const expired = { ...order, nonce: new anchor.BN(1), expiresAt: new anchor.BN(1) };
+      expect(await errorOf(settle(expired, bidder, matcher))).to.equal("OrderExpired");
+    });
+  });
+
+  describe("anti-sniping overrides", () => {
+    it("copies the marketplace anti-sniping settings unless the listing overrides them", async () => {
+      const seller = await funded();
+      const { listingId: plainId } = await listNft("antisnipe-default", 1_000, seller);
+      const { listingId: customId } = await listNft("antisnipe-override", 1_000, seller, {
+        snipingTimeWindow: new anchor.BN(60),
+        timeExtension: new anchor.BN(30),
+      });
+
+      const plain = await program.account.auctionDetails.fetch(auctionPda(plainId));
+      expect(plain.snipingTimeWindow.toNumber()).to.equal(5 * 60);
+      expect(plain.timeExtension.toNumber()).to.equal(5 * 60);
+      const custom = await program.account.auctionDetails.fetch(auctionPda(customId));
+      expect(custom.snipingTimeWindow.toNumber()).to.equal(60);
+      expect(custom.timeExtension.toNumber()).to.equal(30);
+      expect(custom.extensions).to.equal(0);
+    });
+  });
+
+  describe("nft escrow", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "nft-escrow-test");
+    let nftMint: PublicKey;
+    let sellerNftAccount: PublicKey;
+
+    before(async () => {
+      await funded(seller);
+      ({ nftMint, sellerNftAccount } = await listNft("nft-escrow-test", 1_000, seller));
+    });
+
+    it("moves the NFT into the escrow at listing", async () => {
+      const escrow = await getAccount(provider.connection, nftEscrowPda(nftMint));
+      expect(Number(escrow.amount)).to.equal(1);
+      expect(escrow.owner.toBase58()).to.equal(auctionState.toBase58());
+      const sellerAccount = await getAccount(provider.connection, sellerNftAccount);
+      expect(Number(sellerAccount.amount)).to.equal(0);
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.nftMint.toBase58()).to.equal(nftMint.toBase58());
+    });
+
+    it("refuses to return the NFT of a live listing", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .returnNft(listingId)
+          .accounts({
+            auctionState,
+            auction: auctionPda(listingId),
+            owner: seller.publicKey,
+            nftEscrow: nftEscrowPda(nftMint),
+            ownerNftAccount: sellerNftAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([seller])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("ListingNotRelistable");
+    });
+
+    it("lets the seller cancel a listing without bids and get the NFT back", async () => {
+      await program.methods
+        .cancelAuction(listingId)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          owner: seller.publicKey,
+          nft: {
+            nftEscrow: nftEscrowPda(nftMint),
+            ownerNftAccount: sellerNftAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            coreAsset: null,
+            coreCollection: null,
+            mplCoreProgram: null,
+          },
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.status).to.deep.equal({ cancelled: {} });
+      const sellerAccount = await getAccount(provider.connection, sellerNftAccount);
+      expect(Number(sellerAccount.amount)).to.equal(1);
+      expect(await provider.connection.getAccountInfo(nftEscrowPda(nftMint))).to.be.null;
+    });
+  });
+
+  describe("bundles", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "bundle-test");
+    let nftMint: PublicKey;
+    let sellerNftAccount: PublicKey;
+    let extraMint: PublicKey;
+    let extraAccount: PublicKey;
+
+    before(async () => {
+      await funded(seller);
+      ({ nftMint, sellerNftAccount } = await listNft("bundle-test", 1_000, seller));
+      extraMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      extraAccount = await createAccount(provider.connection, seller, extraMint, seller.publicKey);
+      await mintTo(provider.connection, seller, extraMint, extraAccount, seller, 1);
+    });
+
+    it("escrows a further NFT with the listing", async () => {
+      await program.methods
+        .addToBundle(listingId)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          owner: seller.publicKey,
+          nftMint: extraMint,
+          sellerNftAccount: extraAccount,
+          nftEscrow: nftEscrowPda(extraMint),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.bundleMints.map((mint) => mint.toBase58())).to.deep.equal([
+        extraMint.toBase58(),
+      ]);
+      const escrow = await getAccount(provider.connection, nftEscrowPda(extraMint));
+      expect(Number(escrow.amount)).to.equal(1);
+    });
+
+    it("returns every bundled NFT when the listing is cancelled", async () => {
+      await program.methods
+        .cancelAuction(listingId)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          owner: seller.publicKey,
+          nft: {
+            nftEscrow: nftEscrowPda(nftMint),
+            ownerNftAccount: sellerNftAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            coreAsset: null,
+            coreCollection: null,
+            mplCoreProgram: null,
+          },
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: nftEscrowPda(extraMint), isSigner: false, isWritable: true },
+          { pubkey: extraAccount, isSigner: false, isWritable: true },
+        ])
+        .signers([seller])
+        .rpc();
+
+      for (const account of [sellerNftAccount, extraAccount]) {
+        expect(Number((await getAccount(provider.connection, account)).amount)).to.equal(1);
+      }
+      const details = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(details.bundleMints).to.be.empty;
+    });
+  });
+
+  describe("emergency pause", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "pause-test");
+    const auction = auctionPda(listingId);
+
+    before(async () => {
+      await funded(seller);
+      await listNft("pause-test", 1_000_000, seller);
+    });
+
+    it("rejects a wallet that is neither the admin nor the seller", async () => {
+      const stranger = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .emergencyPauseAuction(listingId, true)
+          .accounts({ auctionState, auction, owner: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Unauthorized");
+    });
+
+    it("rejects an auction account at the wrong address", async () => {
+      const { listingId: other } = await listNft("pause-test-other", 1_000_000, seller);
+
+      let error: any;
+      try {
+        await program.methods
+          .emergencyPauseAuction(listingId, true)
+          .accounts({ auctionState, auction: auctionPda(other), owner: seller.publicKey })
+          .signers([seller])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("ConstraintSeeds");
+    });
+
+    it("lets the seller pause their own listing", async () => {
+      await program.methods
+        .emergencyPauseAuction(listingId, true)
+        .accounts({ auctionState, auction, owner: seller.publicKey })
+        .signers([seller])
+        .rpc();
+
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ paused: {} });
+    });
+  });
+
+  describe("seller pause", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "seller-pause-test");
+    const auction = auctionPda(listingId);
+
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .sellerPauseAuction(listingId, paused)
+        .accounts({ auction, owner: seller.publicKey })
+        .signers([seller])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      await listNft("seller-pause-test", 1_000, seller);
+    });
+
+    it("lets the seller pause and resume a listing without bids", async () => {
+      await setPaused(true);
+      let details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ paused: {} });
+
+      await setPaused(false);
+      details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ live: {} });
+    });
+
+    it("rejects a seller pause once the listing has a bid", async () => {
+      const bidder = await funded();
+      await program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(1_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction,
+          vault: vaultPda(listingId),
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+      let error: any;
+      try {
+        await setPaused(true);
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("SellerPauseAfterBids");
+    });
+  });
+
+  describe("offers", () => {
+    let seller: Keypair;
+    let buyer: Keypair;
+    let mint: PublicKey;
+    let sellerNftAccount: PublicKey;
+    let offer: PublicKey;
+
+    const offerPda = (mint: PublicKey, buyer: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("offer"), mint.toBuffer(), buyer.toBuffer()],
+        program.programId
+      )[0];
+
+    before(async () => {
+      seller = await funded();
+      buyer = await funded();
+      mint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      sellerNftAccount = await createAccount(provider.connection, seller, mint, seller.publicKey);
+      await mintTo(provider.connection, seller, mint, sellerNftAccount, seller, 1);
+      offer = offerPda(mint, buyer.publicKey);
+
+      const expiresAt = Math.floor(Date.now() / 1000) + 60 * 60;
+      await program.methods
+        .makeOffer(new anchor.BN(100_000_000), new anchor.BN(expiresAt))
+        .accounts({
+          auctionState,
+          offer,
+          buyer: buyer.publicKey,
+          blacklistEntry: blacklistPda(buyer.publicKey),
+          mint,
+          featureFlags,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([buyer])
+        .rpc();
+    });
+
+    it("lets only the buyer cancel an offer before it expires", async () => {
+      const stranger = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .cancelOffer()
+          .accounts({ offer, buyer: buyer.publicKey, signer: stranger.publicKey })
+          .signers([stranger])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("OfferNotExpired");
+    });
+
+    it("sells the NFT to the buyer when the holder accepts", async () => {
+      const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+      await program.methods
+        .acceptOffer()
+        .accounts({
+          auctionState,
+          offer,
+          buyer: buyer.publicKey,
+          seller: seller.publicKey,
+          blacklistEntry: blacklistPda(seller.publicKey),
+          mint,
+          sellerNftAccount,
+          buyerNftAccount: getAssociatedTokenAddressSync(mint, buyer.publicKey),
+          feeTreasury,
+          metadata: null,
+        })
+        .signers([seller])
+        .rpc();
+
+      const delivered = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(mint, buyer.publicKey)
+      );
+      expect(Number(delivered.amount)).to.equal(1);
+      expect(await provider.connection.getBalance(seller.publicKey)).to.be.greaterThan(sellerBefore);
+      expect(await provider.connection.getAccountInfo(offer)).to.equal(null);
+    });
+  });
+
+  describe("fixed-price listings", () => {
+    const fixedPricePda = (listingId: number[]) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("fixed_price"), Buffer.from(listingId)],
+        program.programId
+      )[0];
+
+    // Mint a fresh NFT to `seller` and list it at `price`
+    const list = async (listingId: number[], seller: Keypair, price: number) => {
+      const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      const sellerNftAccount = await createAccount(
+        provider.connection,
+        seller,
+        nftMint,
+        seller.publicKey
+      );
+      await mintTo(provider.connection, seller, nftMint, sellerNftAccount, seller, 1);
+      await program.methods
+        .createListing(listingId, new anchor.BN(price))
+        .accounts({
+          auctionState,
+          listing: fixedPricePda(listingId),
+          seller: seller.publicKey,
+          blacklistEntry: blacklistPda(seller.publicKey),
+          nftMint,
+          sellerNftAccount,
+          nftEscrow: nftEscrowPda(nftMint),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+      return { nftMint, sellerNftAccount };
+    };
+
+    it("sells the NFT at the listed price", async () => {
+      const seller = await funded();
+      const listingId = listingKey(seller.publicKey, "fixed-price-sale");
+      const buyer = await funded();
+      const { nftMint } = await list(listingId, seller, 100_000_000);
+      const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+
+      await program.methods
+        .buyListing(listingId)
+        .accounts({
+          auctionState,
+          listing: fixedPricePda(listingId),
+          seller: seller.publicKey,
+          buyer: buyer.publicKey,
+          blacklistEntry: blacklistPda(buyer.publicKey),
+          nftMint,
+          nftEscrow: nftEscrowPda(nftMint),
+          buyerNftAccount: getAssociatedTokenAddressSync(nftMint, buyer.publicKey),
+          feeTreasury,
+          provenance: provenancePda(nftMint),
+          metadata: null,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const delivered = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(nftMint, buyer.publicKey)
+      );
+      expect(Number(delivered.amount)).to.equal(1);
+      expect(await provider.connection.getBalance(seller.publicKey)).to.be.greaterThan(sellerBefore);
+      const provenance = await program.account.provenance.fetch(provenancePda(nftMint));
+      expect(provenance.sales[0].price.toNumber()).to.equal(100_000_000);
+      expect(await provider.connection.getAccountInfo(fixedPricePda(listingId))).to.equal(null);
+    });
+
+    it("gives the NFT back when the seller cancels", async () => {
+      const seller = await funded();
+      const listingId = listingKey(seller.publicKey, "fixed-price-cancel");
+      const { nftMint, sellerNftAccount } = await list(listingId, seller, 100_000_000);
+
+      await program.methods
+        .cancelListing(listingId)
+        .accounts({
+          auctionState,
+          listing: fixedPricePda(listingId),
+          seller: seller.publicKey,
+          nftEscrow: nftEscrowPda(nftMint),
+          sellerNftAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([seller])
+        .rpc();
+
+      const returned = await getAccount(provider.connection, sellerNftAccount);
+      expect(Number(returned.amount)).to.equal(1);
+    });
+  });
+
+  describe("crowd auctions", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "crowd-auction");
+    const target = 500_000_000;
+
+    const shareMintPda = (listingId: number[]) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("crowd_share"), Buffer.from(listingId)],
+        program.programId
+      )[0];
+
+    const contribute = (contributor: Keypair, amount: number) =>
+      program.methods
+        .contribute(listingId, new anchor.BN(amount))
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          contributor: contributor.publicKey,
+          blacklistEntry: blacklistPda(contributor.publicKey),
+          collectionFeeConfig: null,
+        })
+        .signers([contributor])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      const sellerNftAccount = await createAccount(
+        provider.connection,
+        seller,
+        nftMint,
+        seller.publicKey
+      );
+      await mintTo(provider.connection, seller, nftMint, sellerNftAccount, seller, 1);
+
+      await program.methods
+        .initializeCrowdAuction(
+          listingId,
+          new anchor.BN(10_000_000),
+          seller.publicKey,
+          {
+            endTime: null,
+            reservePrice: null,
+            minIncrementBps: null,
+            minIncrement: null,
+            buyNowPrice: null,
+            snipingTimeWindow: null,
+            timeExtension: null,
+            allowlistRoot: null,
+            gateMint: null,
+            payoutSplits: null,
+            bidHistoryCapacity: null,
+            growBidHistory: null,
+            secondPrice: null,
+            candleWindow: null,
+            rentalDays: null,
+            paymentMint: null,
+            usdPricing: null,
+            name: "crowd-auction",
+          },
+          new anchor.BN(target),
+          null
+        )
+        .accounts({
+          listing: {
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            owner: seller.publicKey,
+            payer: seller.publicKey,
+            sellerProfile: null,
+            blacklistEntry: blacklistPda(seller.publicKey),
+            feeApprover: null,
+            collectionConfig: null,
+            nftMint,
+            sellerNftAccount,
+            nftEscrow: nftEscrowPda(nftMint),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+          shareMint: shareMintPda(listingId),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+    });
+
+    it("pools every contribution toward the target", async () => {
+      const first = await funded();
+      const second = await funded();
+      await contribute(first, 100_000_000);
+      await contribute(second, 200_000_000);
+      await contribute(first, 100_000_000);
+
+      // The 3% buyer fee comes out of each contribution
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.isAlien).to.be.true;
+      expect(auction.reservePrice.toNumber()).to.equal(target);
+      expect(auction.totalAmount.toNumber()).to.equal(388_000_000);
+      expect(auction.highestBid.toNumber()).to.equal(388_000_000);
+      expect(auction.highestBidder.toBase58()).to.equal(auctionPda(listingId).toBase58());
+    });
+
+    it("rejects regular bids", async () => {
+      const bidder = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .placeBid(listingId, bidder.publicKey, new anchor.BN(600_000_000), [], null)
+          .accounts({
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            payer: bidder.publicKey,
+            blacklistEntry: blacklistPda(bidder.publicKey),
+            bidPage: null,
+            previousBidder: null,
+            previousBidReceipt: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            priceFeed: null,
+            tokenVault: null,
+            payerTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([bidder])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("ContributionsOnly");
+    });
+
+    it("keeps contributions locked until the auction has run out", async () => {
+      const contributor = await funded();
+      await contribute(contributor, 50_000_000);
+      let error: any;
+      try {
+        await program.methods
+          .refundContribution(listingId)
+          .accounts({
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            contributor: contributor.publicKey,
+          })
+          .signers([contributor])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("AuctionNotEnded");
+    });
+  });
+
+  describe("seller collateral", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "crowd-collateral");
+    const collateral = 50_000_000;
+    const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+    const contributors: Keypair[] = [];
+
+    const errorOf = async (call: Promise<unknown>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    const refund = (contributor: Keypair) =>
+      program.methods
+        .refundContribution(listingId)
+        .accounts({
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          contributor: contributor.publicKey,
+        })
+        .signers([contributor])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      const sellerNftAccount = await createAccount(
+        provider.connection,
+        seller,
+        nftMint,
+        seller.publicKey
+      );
+      await mintTo(provider.connection, seller, nftMint, sellerNftAccount, seller, 1);
+
+      const now = Math.floor(Date.now() / 1000);
+      await program.methods
+        .initializeCrowdAuction(
+          listingId,
+          new anchor.BN(10_000_000),
+          seller.publicKey,
+          {
+            endTime: new anchor.BN(now + 5),
+            reservePrice: null,
+            minIncrementBps: null,
+            minIncrement: null,
+            buyNowPrice: null,
+            snipingTimeWindow: null,
+            timeExtension: null,
+            allowlistRoot: null,
+            gateMint: null,
+            payoutSplits: null,
+            bidHistoryCapacity: null,
+            growBidHistory: null,
+            secondPrice: null,
+            candleWindow: null,
+            rentalDays: null,
+            paymentMint: null,
+            usdPricing: null,
+            name: "crowd-collateral",
+          },
+          new anchor.BN(100_000_000),
+          { amount: new anchor.BN(collateral), deliveryWindow: new anchor.BN(3) }
+        )
+        .accounts({
+          listing: {
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            owner: seller.publicKey,
+            payer: seller.publicKey,
+            sellerProfile: null,
+            blacklistEntry: blacklistPda(seller.publicKey),
+            feeApprover: null,
+            collectionConfig: null,
+            nftMint,
+            sellerNftAccount,
+            nftEscrow: nftEscrowPda(nftMint),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+          shareMint: PublicKey.findProgramAddressSync(
+            [Buffer.from("crowd_share"), Buffer.from(listingId)],
+            program.programId
+          )[0],
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+    });
+
+    it("stakes the seller's collateral in the vault", async () => {
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.sellerCollateral.toNumber()).to.equal(collateral);
+      expect(auction.deliveryWindow.toNumber()).to.equal(3);
+      const vault = await provider.connection.getBalance(vaultPda(listingId));
+      expect(vault).to.be.at.least(collateral);
+
+      // Nothing to reclaim while the listing is still running
+      const code = await errorOf(
+        program.methods
+          .reclaimSellerCollateral(listingId)
+          .accounts({
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            owner: seller.publicKey,
+          })
+          .signers([seller])
+          .rpc()
+      );
+      expect(code).to.equal("ListingNotRelistable");
+    });
+
+    it("slashes the collateral to the contributors when delivery goes unconfirmed", async () => {
+      for (let i = 0; i < 2; i++) {
+        const contributor = await funded();
+        await program.methods
+          .contribute(listingId, new anchor.BN(60_000_000))
+          .accounts({
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            contributor: contributor.publicKey,
+            blacklistEntry: blacklistPda(contributor.publicKey),
+            collectionFeeConfig: null,
+          })
+          .signers([contributor])
+          .rpc();
+        contributors.push(contributor);
+      }
+      await sleep(6_000);
+      await program.methods
+        .endAuction(listingId)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          caller: authority,
+          tokenVault: null,
+          callerTokenAccount: null,
+          tokenProgram: null,
+          unwrapAccount: null,
+          nativeMint: null,
+          collectionStats: null,
+          floorOracle: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // The sale met its target, but nothing is refunded before the deadline
+      expect(await errorOf(refund(contributors[0]))).to.equal("CrowdTargetMet");
+
+      await sleep(4_000);
+      const code = await errorOf(
+        program.methods
+          .confirmDelivery(listingId)
+          .accounts({ auction: auctionPda(listingId), owner: seller.publicKey })
+          .signers([seller])
+          .rpc()
+      );
+      expect(code).to.equal("DeliveryOverdue");
+
+      // Equal contributions split the collateral evenly
+      await refund(contributors[0]);
+      let auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.status).to.have.property("voided");
+      expect(auction.collateralSlashed).to.be.true;
+      expect(auction.sellerCollateral.toNumber()).to.equal(collateral / 2);
+
+      await refund(contributors[1]);
+      auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.sellerCollateral.toNumber()).to.equal(0);
+      expect(auction.totalAmount.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("raffles", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "raffle");
+    const ticketPrice = 10_000_000;
+    let participant: Keypair;
+
+    before(async () => {
+      await funded(seller);
+      participant = await funded();
+      const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      const sellerNftAccount = await createAccount(
+        provider.connection,
+        seller,
+        nftMint,
+        seller.publicKey
+      );
+      await mintTo(provider.connection, seller, nftMint, sellerNftAccount, seller, 1);
+
+      await program.methods
+        .initializeRaffle(listingId, new anchor.BN(ticketPrice), seller.publicKey, {
+          endTime: null,
+          reservePrice: null,
+          minIncrementBps: null,
+          minIncrement: null,
+          buyNowPrice: null,
+          snipingTimeWindow: null,
+          timeExtension: null,
+          allowlistRoot: null,
+          gateMint: null,
+          payoutSplits: null,
+          bidHistoryCapacity: null,
+          growBidHistory: null,
+          secondPrice: null,
+          candleWindow: null,
+          rentalDays: null,
+          paymentMint: null,
+          usdPricing: null,
+          name: "raffle",
+        })
+        .accounts({
+          listing: {
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            owner: seller.publicKey,
+            payer: seller.publicKey,
+            sellerProfile: null,
+            blacklistEntry: blacklistPda(seller.publicKey),
+            feeApprover: null,
+            collectionConfig: null,
+            nftMint,
+            sellerNftAccount,
+            nftEscrow: nftEscrowPda(nftMint),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+        })
+        .signers([seller])
+        .rpc();
+    });
+
+    it("sells tickets at the fixed price", async () => {
+      await program.methods
+        .buyTickets(listingId, new anchor.BN(3), [])
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          buyer: participant.publicKey,
+          blacklistEntry: blacklistPda(participant.publicKey),
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+        })
+        .signers([participant])
+        .rpc();
+
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.ticketPrice.toNumber()).to.equal(ticketPrice);
+      expect(auction.totalAmount.toNumber()).to.equal(3 * ticketPrice);
+      expect(auction.highestBid.toNumber()).to.equal(0);
+    });
+
+    it("keeps tickets locked until the draw", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .withdraw(listingId, null, null)
+          .accounts({
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            bidder: participant.publicKey,
+            to: participant.publicKey,
+            bidPage: null,
+            tokenVault: null,
+            toTokenAccount: null,
+            tokenProgram: null,
+            unwrapAccount: null,
+            nativeMint: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([participant])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("RaffleTicketsLocked");
+    });
+  });
+
+  describe("second-price auctions", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "second-price");
+
+    const bid = (bidder: Keypair, amount: number, previousBidder: PublicKey | null) =>
+      program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder,
+          previousBidReceipt:
+            previousBidder && bidReceiptPda(auctionPda(listingId), previousBidder),
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      await listNft("second-price", 1_000_000, seller, { secondPrice: true });
+    });
+
+    it("tracks the runner-up bid next to the leading one", async () => {
+      const first = await funded();
+      const second = await funded();
+      await bid(first, 10_000_000, null);
+      await bid(second, 20_000_000, null);
+
+      // The 3% buyer fee comes out of each deposit
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.secondPrice).to.be.true;
+      expect(auction.highestBidder.toBase58()).to.equal(second.publicKey.toBase58());
+      expect(auction.highestBid.toNumber()).to.equal(19_400_000);
+      expect(auction.secondBid.toNumber()).to.equal(9_700_000);
+    });
+  });
+
+  describe("candle auctions", () => {
+    it("rejects a candle window with a buy-now price", async () => {
+      let error: any;
+      try {
+        await listNft("candle-buy-now", 1_000_000, await funded(), {
+          candleWindow: new anchor.BN(10 * 60),
+          buyNowPrice: new anchor.BN(50_000_000),
+        });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidCandleWindow");
+    });
+
+    it("drops anti-sniping extensions and proxy bids", async () => {
+      const bidder = await funded();
+      const { listingId } = await listNft("candle", 1_000_000, await funded(), {
+        candleWindow: new anchor.BN(10 * 60),
+      });
+
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.candleWindow.toNumber()).to.equal(600);
+      expect(auction.snipingTimeWindow.toNumber()).to.equal(0);
+      expect(auction.timeExtension.toNumber()).to.equal(0);
+
+      let error: any;
+      try {
+        await program.methods
+          .placeProxyBid(listingId, bidder.publicKey, new anchor.BN(10_000_000), [], null)
+          .accounts({
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            payer: bidder.publicKey,
+            blacklistEntry: blacklistPda(bidder.publicKey),
+            bidPage: null,
+            previousBidder: null,
+            previousBidReceipt: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            priceFeed: null,
+            tokenVault: null,
+            payerTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([bidder])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("ProxyBidUnavailable");
+    });
+  });
+
+  describe("rental auctions", () => {
+    it("rejects a term over a year", async () => {
+      let error: any;
+      try {
+        await listNft("rental-long", 1_000_000, await funded(), { rentalDays: 400 });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidRentalTerm");
+    });
+
+    it("records the term and starts with no rental running", async () => {
+      const { listingId } = await listNft("rental", 1_000_000, await funded(), { rentalDays: 7 });
+
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.rentalDays).to.equal(7);
+      expect(auction.rentalExpiresAt.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("USD-priced listings", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "usd-priced");
+    const usdPricing = {
+      feed: { kind: { pyth: {} }, id: Array.from(Keypair.generate().publicKey.toBytes()) },
+      minimumBidUsd: new anchor.BN(25_000_000),
+      reserveUsd: new anchor.BN(0),
+    };
+
+    it("rejects USD pricing on a listing paid in a token", async () => {
+      const seller = await funded();
+      const paymentMint = await createMint(provider.connection, seller, seller.publicKey, null, 6);
+      let error: any;
+      try {
+        await listNft("usd-priced-token", 1_000, seller, { paymentMint, usdPricing });
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidUsdPricing");
+    });
+
+    it("rejects a bid that comes without the listing's price feed", async () => {
+      await listNft("usd-priced", 1_000, await funded(seller), { usdPricing });
+      const bidder = await funded();
+      let error: any;
+      try {
+        await program.methods
+          .placeBid(listingId, bidder.publicKey, new anchor.BN(1_000_000), [], null)
+          .accounts({
+            auctionState,
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            payer: bidder.publicKey,
+            blacklistEntry: blacklistPda(bidder.publicKey),
+            bidPage: null,
+            previousBidder: null,
+            previousBidReceipt: null,
+            gateTokenAccount: null,
+            collectionFeeConfig: null,
+            priceFeed: null,
+            tokenVault: null,
+            payerTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([bidder])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("OracleFeedMismatch");
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.usdPricing.minimumBidUsd.toNumber()).to.equal(25_000_000);
+      expect(auction.usdPricedAt.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("listing clones", () => {
+    it("won't relist for a blacklisted seller", async () => {
+      const seller = await funded();
+      const { listingId: source } = await listNft("clone-source", 1_000, seller);
+      await blacklist(seller.publicKey);
+
+      const listingId = listingKey(seller.publicKey, "clone-copy");
+      let error: any;
+      try {
+        await program.methods
+          .cloneListing(source, listingId, {
+            endTime: null,
+            reservePrice: null,
+            minIncrementBps: null,
+            minIncrement: null,
+            buyNowPrice: null,
+            snipingTimeWindow: null,
+            timeExtension: null,
+            allowlistRoot: null,
+            gateMint: null,
+            payoutSplits: null,
+            bidHistoryCapacity: null,
+            growBidHistory: null,
+            secondPrice: null,
+            candleWindow: null,
+            rentalDays: null,
+            paymentMint: null,
+            usdPricing: null,
+            name: "clone-copy",
+            coOwned: null,
+            beneficiary: null,
+          })
+          .accounts({
+            auctionState,
+            source: auctionPda(source),
+            auction: auctionPda(listingId),
+            vault: vaultPda(listingId),
+            owner: seller.publicKey,
+            blacklistEntry: blacklistPda(seller.publicKey),
+            sellerProfile: null,
+            collectionConfig: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([seller])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("Blacklisted");
+    });
+  });
+
+  describe("token-paid listings", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "token-paid");
+    const tokenVaultPda = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_vault"), Buffer.from(listingId)],
+      program.programId
+    )[0];
+
+    it("escrows bids in the listing's token", async () => {
+      await funded(seller);
+      const bidder = await funded();
+      const paymentMint = await createMint(provider.connection, seller, seller.publicKey, null, 6);
+      const [tokenTreasury] = PublicKey.findProgramAddressSync(
+        [Buffer.from("fee_treasury"), paymentMint.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initFeeTreasury(paymentMint)
+        .accounts({
+          auctionState,
+          feeTreasury: tokenTreasury,
+          authority,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await listNft("token-paid", 1_000_000, seller, { paymentMint });
+      await program.methods
+        .openTokenVault(listingId)
+        .accounts({
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          paymentMint,
+          feeTreasury: tokenTreasury,
+          treasuryTokenAccount: getAssociatedTokenAddressSync(paymentMint, tokenTreasury, true),
+          tokenVault: tokenVaultPda,
+          owner: seller.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+
+      const bidderTokens = await createAccount(
+        provider.connection,
+        bidder,
+        paymentMint,
+        bidder.publicKey
+      );
+      await mintTo(provider.connection, seller, paymentMint, bidderTokens, seller, 10_000_000);
+      await program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(10_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          previousBidReceipt: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: tokenVaultPda,
+          payerTokenAccount: bidderTokens,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          paymentMint,
+          featureFlags,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+      // The 3% buyer fee stays in the token vault with the bid
+      const vault = await getAccount(provider.connection, tokenVaultPda);
+      expect(Number(vault.amount)).to.equal(10_000_000);
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.paymentMint.toBase58()).to.equal(paymentMint.toBase58());
+      expect(auction.highestBid.toNumber()).to.equal(9_700_000);
+    });
+
+    it("credits a Token-2022 bid with only what arrives after the mint's transfer fee", async () => {
+      const feeListingId = listingKey(seller.publicKey, "token-fee");
+      const feeTokenVault = PublicKey.findProgramAddressSync(
+        [Buffer.from("token_vault"), Buffer.from(feeListingId)],
+        program.programId
+      )[0];
+      const bidder = await funded();
+      // 1% of every transfer is withheld by the mint
+      const mint = Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          SystemProgram.createAccount({
+            fromPubkey: seller.publicKey,
+            newAccountPubkey: mint.publicKey,
+            space: mintLen,
+            lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+            programId: TOKEN_2022_PROGRAM_ID,
+          }),
+          createInitializeTransferFeeConfigInstruction(
+            mint.publicKey,
+            seller.publicKey,
+            seller.publicKey,
+            100,
+            BigInt(1_000_000_000),
+            TOKEN_2022_PROGRAM_ID
+          ),
+          createInitializeMintInstruction(mint.publicKey, 6, seller.publicKey, null, TOKEN_2022_PROGRAM_ID)
+        ),
+        [seller, mint]
+      );
+      const paymentMint = mint.publicKey;
+      const [tokenTreasury] = PublicKey.findProgramAddressSync(
+        [Buffer.from("fee_treasury"), paymentMint.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initFeeTreasury(paymentMint)
+        .accounts({
+          auctionState,
+          feeTreasury: tokenTreasury,
+          authority,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await listNft("token-fee", 1_000_000, seller, { paymentMint });
+      await program.methods
+        .openTokenVault(feeListingId)
+        .accounts({
+          auction: auctionPda(feeListingId),
+          vault: vaultPda(feeListingId),
+          paymentMint,
+          feeTreasury: tokenTreasury,
+          treasuryTokenAccount: getAssociatedTokenAddressSync(
+            paymentMint,
+            tokenTreasury,
+            true,
+            TOKEN_2022_PROGRAM_ID
+          ),
+          tokenVault: feeTokenVault,
+          owner: seller.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+
+      const bidderTokens = await createAccount(
+        provider.connection,
+        bidder,
+        paymentMint,
+        bidder.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await mintTo(
+        provider.connection,
+        seller,
+        paymentMint,
+        bidderTokens,
+        seller,
+        10_000_000,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await program.methods
+        .placeBid(feeListingId, bidder.publicKey, new anchor.BN(10_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction: auctionPda(feeListingId),
+          vault: vaultPda(feeListingId),
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          previousBidReceipt: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: feeTokenVault,
+          payerTokenAccount: bidderTokens,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          paymentMint,
+          featureFlags,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+      // 100_000 is withheld on the way in; the 3% buyer fee is taken from the rest
+      const vault = await getAccount(
+        provider.connection,
+        feeTokenVault,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(Number(vault.amount)).to.equal(9_900_000);
+      const auction = await program.account.auctionDetails.fetch(auctionPda(feeListingId));
+      expect(auction.highestBid.toNumber()).to.equal(9_603_000);
+      expect(auction.fees.toNumber()).to.equal(297_000);
+    });
+
+    it("won't open a token vault for a mint with a transfer hook", async () => {
+      const hookListingId = listingKey(seller.publicKey, "token-hook");
+      const mint = Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferHook]);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          SystemProgram.createAccount({
+            fromPubkey: seller.publicKey,
+            newAccountPubkey: mint.publicKey,
+            space: mintLen,
+            lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+            programId: TOKEN_2022_PROGRAM_ID,
+          }),
+          createInitializeTransferHookInstruction(
+            mint.publicKey,
+            seller.publicKey,
+            Keypair.generate().publicKey,
+            TOKEN_2022_PROGRAM_ID
+          ),
+          createInitializeMintInstruction(mint.publicKey, 6, seller.publicKey, null, TOKEN_2022_PROGRAM_ID)
+        ),
+        [seller, mint]
+      );
+      const paymentMint = mint.publicKey;
+      const [tokenTreasury] = PublicKey.findProgramAddressSync(
+        [Buffer.from("fee_treasury"), paymentMint.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initFeeTreasury(paymentMint)
+        .accounts({
+          auctionState,
+          feeTreasury: tokenTreasury,
+          authority,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await listNft("token-hook", 1_000_000, seller, { paymentMint });
+
+      let error: any;
+      try {
+        await program.methods
+          .openTokenVault(hookListingId)
+          .accounts({
+            auction: auctionPda(hookListingId),
+            vault: vaultPda(hookListingId),
+            paymentMint,
+            feeTreasury: tokenTreasury,
+            treasuryTokenAccount: getAssociatedTokenAddressSync(
+              paymentMint,
+              tokenTreasury,
+              true,
+              TOKEN_2022_PROGRAM_ID
+            ),
+            tokenVault: PublicKey.findProgramAddressSync(
+              [Buffer.from("token_vault"), Buffer.from(hookListingId)],
+              program.programId
+            )[0],
+            owner: seller.publicKey,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([seller])
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("UnsupportedMintExtension");
+    });
+  });
+
+  describe("wrapped SOL listings", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "wsol-paid");
+    const tokenVaultPda = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_vault"), Buffer.from(listingId)],
+      program.programId
+    )[0];
+    const unwrapAccount = PublicKey.findProgramAddressSync(
+      [Buffer.from("wsol_unwrap"), Buffer.from(listingId)],
+      program.programId
+    )[0];
+    let bidder: Keypair;
+    let rival: Keypair;
+
+    before(async () => {
+      await funded(seller);
+      bidder = await funded();
+      rival = await funded();
+      await listNft("wsol-paid", 1_000_000, seller, { paymentMint: NATIVE_MINT });
+      await program.methods
+        .openTokenVault(listingId)
+        .accounts({
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          paymentMint: NATIVE_MINT,
+          feeTreasury,
+          treasuryTokenAccount: getAssociatedTokenAddressSync(NATIVE_MINT, feeTreasury, true),
+          tokenVault: tokenVaultPda,
+          owner: seller.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+    });
+
+    const bid = (who: Keypair, amount: number) =>
+      program.methods
+        .placeBid(listingId, who.publicKey, new anchor.BN(amount), [], null)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          payer: who.publicKey,
+          blacklistEntry: blacklistPda(who.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          previousBidReceipt: null,
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: tokenVaultPda,
+          payerTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          paymentMint: NATIVE_MINT,
+          featureFlags,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([who])
+        .rpc();
+
+    it("wraps a bid paid in plain SOL into the wSOL vault", async () => {
+      await bid(bidder, 10_000_000);
+
+      const vault = await getAccount(provider.connection, tokenVaultPda);
+      expect(Number(vault.amount)).to.equal(10_000_000);
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      expect(auction.paymentMint.toBase58()).to.equal(NATIVE_MINT.toBase58());
+      expect(auction.highestBid.toNumber()).to.equal(9_700_000);
+    });
+
+    it("unwraps an outbid bidder's refund straight into their wallet", async () => {
+      await bid(rival, 20_000_000);
+      const before = await provider.connection.getBalance(bidder.publicKey);
+      await program.methods
+        .withdraw(listingId, null, null)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          bidder: bidder.publicKey,
+          to: bidder.publicKey,
+          bidPage: null,
+          tokenVault: tokenVaultPda,
+          toTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          paymentMint: NATIVE_MINT,
+          unwrapAccount,
+          nativeMint: NATIVE_MINT,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder])
+        .rpc();
+
+      // Only the transaction fee is taken from the refund; the unwrap account's rent comes back
+      const after = await provider.connection.getBalance(bidder.publicKey);
+      expect(after - before).to.be.greaterThan(9_700_000 - 10_000);
+      expect(await provider.connection.getAccountInfo(unwrapAccount)).to.be.null;
+      const vault = await getAccount(provider.connection, tokenVaultPda);
+      expect(Number(vault.amount)).to.equal(20_000_000);
+    });
+  });
+
+  describe("loyalty points", () => {
+    const seller = Keypair.generate();
+    const bidder = Keypair.generate();
+    const loyaltyPda = (owner: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("loyalty"), owner.toBuffer()],
+        program.programId
+      )[0];
+    const listingId = listingKey(seller.publicKey, "loyalty-test");
+    const fixedPriceId = listingKey(seller.publicKey, "loyalty-fixed");
+
+    const balance = (owner: PublicKey) =>
+      program.methods
+        .getLoyaltyBalance(owner)
+        .accounts({ loyaltyAccount: loyaltyPda(owner) })
+        .view();
+
+    before(async () => {
+      await funded(seller);
+      await funded(bidder);
+      await listNft("loyalty-test", 10_000_000, seller);
+      await program.methods
+        .setLoyaltySchedule({
+          bidPointsPerSol: new anchor.BN(100),
+          purchasePointsPerSol: new anchor.BN(1_000),
+          startsAt: new anchor.BN(0),
+          endsAt: new anchor.BN(0),
+        })
+        .accounts({ auctionState, authority })
+        .rpc();
+      await program.methods
+        .initLoyaltyAccount()
+        .accounts({
+          loyaltyAccount: loyaltyPda(bidder.publicKey),
+          owner: bidder.publicKey,
+          payer: bidder.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bidder])
+        .rpc();
+    });
+
+    after(async () => {
+      await program.methods
+        .setLoyaltySchedule({
+          bidPointsPerSol: new anchor.BN(0),
+          purchasePointsPerSol: new anchor.BN(0),
+          startsAt: new anchor.BN(0),
+          endsAt: new anchor.BN(0),
+        })
+        .accounts({ auctionState, authority })
+        .rpc();
+    });
+
+    it("rejects a schedule that ends before it starts", async () => {
+      let error: any;
+      try {
+        await program.methods
+          .setLoyaltySchedule({
+            bidPointsPerSol: new anchor.BN(100),
+            purchasePointsPerSol: new anchor.BN(100),
+            startsAt: new anchor.BN(2_000_000_000),
+            endsAt: new anchor.BN(1_000_000_000),
+          })
+          .accounts({ auctionState, authority })
+          .rpc();
+      } catch (e) {
+        error = e;
+      }
+      expect(error?.error?.errorCode?.code).to.equal("InvalidLoyaltySchedule");
+    });
+
+    it("earns points on a bid, weighted by the amount bid", async () => {
+      await program.methods
+        .placeBid(listingId, bidder.publicKey, new anchor.BN(500_000_000), [], null)
+        .accounts({
+          auctionState,
+          auction: auctionPda(listingId),
+          vault: vaultPda(listingId),
+          payer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          bidPage: null,
+          previousBidder: null,
+          loyaltyAccount: loyaltyPda(bidder.publicKey),
+          gateTokenAccount: null,
+          collectionFeeConfig: null,
+          priceFeed: null,
+          tokenVault: null,
+          payerTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([bidder])
+        .rpc();
+
+      const auction = await program.account.auctionDetails.fetch(auctionPda(listingId));
+      const bid = auction.highestBid.toNumber();
+      const account = await program.account.loyaltyAccount.fetch(loyaltyPda(bidder.publicKey));
+      expect(account.bidVolume.toNumber()).to.equal(bid);
+      expect(account.points.toNumber()).to.equal(Math.floor((bid * 100) / 1_000_000_000));
+      expect((await balance(bidder.publicKey)).toNumber()).to.equal(account.points.toNumber());
+    });
+
+    it("earns purchase points on a fixed-price buy", async () => {
+      const fixedPricePda = PublicKey.findProgramAddressSync(
+        [Buffer.from("fixed_price"), Buffer.from(fixedPriceId)],
+        program.programId
+      )[0];
+      const nftMint = await createMint(provider.connection, seller, seller.publicKey, null, 0);
+      const sellerNftAccount = await createAccount(
+        provider.connection,
+        seller,
+        nftMint,
+        seller.publicKey
+      );
+      await mintTo(provider.connection, seller, nftMint, sellerNftAccount, seller, 1);
+      await program.methods
+        .createListing(fixedPriceId, new anchor.BN(200_000_000))
+        .accounts({
+          auctionState,
+          listing: fixedPricePda,
+          seller: seller.publicKey,
+          blacklistEntry: blacklistPda(seller.publicKey),
+          nftMint,
+          sellerNftAccount,
+          nftEscrow: nftEscrowPda(nftMint),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([seller])
+        .rpc();
+
+      const before = (await balance(bidder.publicKey)).toNumber();
+      await program.methods
+        .buyListing(fixedPriceId)
+        .accounts({
+          auctionState,
+          listing: fixedPricePda,
+          seller: seller.publicKey,
+          buyer: bidder.publicKey,
+          blacklistEntry: blacklistPda(bidder.publicKey),
+          nftMint,
+          nftEscrow: nftEscrowPda(nftMint),
+          buyerNftAccount: getAssociatedTokenAddressSync(nftMint, bidder.publicKey),
+          feeTreasury,
+          provenance: provenancePda(nftMint),
+          loyaltyAccount: loyaltyPda(bidder.publicKey),
+          metadata: null,
+        })
+        .signers([bidder])
+        .rpc();
+
+      const account = await program.account.loyaltyAccount.fetch(loyaltyPda(bidder.publicKey));
+      expect(account.purchaseVolume.toNumber()).to.equal(200_000_000);
+      expect(account.points.toNumber()).to.equal(before + 200);
+    });
+  });
+
+  describe("roles", () => {
+    const seller = Keypair.generate();
+    const listingId = listingKey(seller.publicKey, "roles-test");
+    const auction = auctionPda(listingId);
+    const roles = PublicKey.findProgramAddressSync([Buffer.from("roles")], program.programId)[0];
+    let pauser: Keypair;
+    let blacklistManager: Keypair;
+
+    const errorOf = async (call: Promise<unknown>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    const grant = (role: object, holder: PublicKey) =>
+      program.methods
+        .grantRole(role as any, holder)
+        .accounts({
+          auctionState,
+          roles,
+          authority,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    const revoke = (role: object) =>
+      program.methods
+        .revokeRole(role as any)
+        .accounts({ auctionState, roles, authority })
+        .rpc();
+
+    const pause = (signer: Keypair, withRoles: boolean) =>
+      program.methods
+        .emergencyPauseAuction(listingId, true)
+        .accounts({ auctionState, auction, owner: signer.publicKey, roles: withRoles ? roles : null })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await funded(seller);
+      await listNft("roles-test", 1_000_000, seller);
+      pauser = await funded();
+      blacklistManager = await funded();
+    });
+
+    it("only lets the admin grant roles", async () => {
+      const outsider = await funded();
+      const code = await errorOf(
+        program.methods
+          .grantRole({ pauser: {} }, outsider.publicKey)
+          .accounts({
+            auctionState,
+            roles,
+            authority: outsider.publicKey,
+            payer: outsider.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([outsider])
+          .rpc()
+      );
+      expect(code).to.equal("Unauthorized");
+    });
+
+    it("lets the pauser pause a listing as the admin would", async () => {
+      await grant({ pauser: {} }, pauser.publicKey);
+      const account = await program.account.roles.fetch(roles);
+      expect(account.pauser.toBase58()).to.equal(pauser.publicKey.toBase58());
+
+      // The role only counts with the roles account passed along
+      expect(await errorOf(pause(pauser, false))).to.equal("Unauthorized");
+
+      await pause(pauser, true);
+      const details = await program.account.auctionDetails.fetch(auction);
+      expect(details.status).to.deep.equal({ paused: {} });
+      expect(details.sellerPaused).to.be.false;
+    });
+
+    it("keeps each role to its own instructions", async () => {
+      await grant({ blacklistManager: {} }, blacklistManager.publicKey);
+      const flagged = Keypair.generate().publicKey;
+
+      // The pauser can't blacklist...
+      const code = await errorOf(
+        program.methods
+          .addToBlacklist(flagged)
+          .accounts({
+            auctionState,
+            roles,
+            blacklistEntry: blacklistPda(flagged),
+            authority: pauser.publicKey,
+            payer: pauser.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([pauser])
+          .rpc()
+      );
+      expect(code).to.equal("Unauthorized");
+
+      // ...but the blacklist manager can
+      await program.methods
+        .addToBlacklist(flagged)
+        .accounts({
+          auctionState,
+          roles,
+          blacklistEntry: blacklistPda(flagged),
+          authority: blacklistManager.publicKey,
+          payer: blacklistManager.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([blacklistManager])
+        .rpc();
+      await program.methods
+        .removeFromBlacklist()
+        .accounts({
+          auctionState,
+          roles,
+          blacklistEntry: blacklistPda(flagged),
+          authority: blacklistManager.publicKey,
+        })
+        .signers([blacklistManager])
+        .rpc();
+    });
+
+    it("takes a revoked role's powers away", async () => {
+      await revoke({ pauser: {} });
+      await revoke({ blacklistManager: {} });
+      const account = await program.account.roles.fetch(roles);
+      expect(account.pauser).to.be.null;
+      expect(account.blacklistManager).to.be.null;
+
+      expect(await errorOf(pause(pauser, true))).to.equal("Unauthorized");
+    });
+  });
+
+  // Runs last: handing the config to governance can't be undone
+  describe("governance proposals", () => {
+    // Stands in for a realm's governance PDA; spl-governance signs for it with
+    // invoke_signed when a proposal's instructions execute
+    const governance = Keypair.generate();
+
+    const errorOf = async (call: Promise<string>) => {
+      try {
+        await call;
+      } catch (e) {
+        return e?.error?.errorCode?.code;
+      }
+      return undefined;
+    };
+
+    // What spl-governance's ExecuteTransaction does with a passed proposal's instruction
+    const executeProposal = async (ix: anchor.web3.TransactionInstruction) => {
+      const tx = new anchor.web3.Transaction().add(ix);
+      return provider.sendAndConfirm(tx, [governance]);
+    };
+
+    it("only accepts proposals from the governance that holds the config", async () => {
+      expect(
+        await errorOf(
+          program.methods
+            .governanceSetFees(new anchor.BN(250), new anchor.BN(150))
+            .accounts({ auctionState, governance: governance.publicKey })
+            .signers([governance])
+            .rpc()
+        )
+      ).to.equal("Unauthorized");
+
+      await program.methods
+        .setGovernance(governance.publicKey)
+        .accounts({ auctionState, signer: authority })
+        .rpc();
+      expect(
+        await errorOf(
+          program.methods
+            .setFees(new anchor.BN(250), new anchor.BN(150))
+            .accounts({ auctionState, authority })
+            .rpc()
+        )
+      ).to.equal("GovernanceControlled");
+    });
+
+    it("sets fees when a proposal executes", async () => {
+      const ix = await program.methods
+        .governanceSetFees(new anchor.BN(250), new anchor.BN(150))
+        .accounts({ auctionState, governance: governance.publicKey })
+        .instruction();
+      await executeProposal(ix);
+
+      const state = await program.account.nftComAuction.fetch(auctionState);
+      expect(state.buyerFeeBps.toNumber()).to.equal(250);
+      expect(state.sellerFeeBps.toNumber()).to.equal(150);
+    });
+
+    it("withdraws treasury fees when a proposal executes", async () => {
+      const before = await provider.connection.getBalance(feeRecipient);
+      const ix = await program.methods
+        .withdrawFees(new anchor.BN(100_000))
+        .accounts({ auctionState, feeTreasury, feeRecipient, signer: governance.publicKey })
+        .instruction();
+      await executeProposal(ix);
+      expect(await provider.connection.getBalance(feeRecipient)).to.equal(before + 100_000);
+    });
   });
 });